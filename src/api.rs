@@ -0,0 +1,300 @@
+//! The core operations of blast, for tools that provision and maintain Catalyst projects.
+//!
+//! Everything here follows semver: options and reports are `#[non_exhaustive]` and built through their
+//! constructors, so fields can be added in minor releases. Questions blast would ask on the terminal go to the
+//! [`Prompter`] installed with [`set_prompter`], and log lines go to the [`LogSink`] installed with
+//! [`set_log_sink`]; without one, blast prints as the CLI does.
+//!
+//! Operations resolve every path from the [`Project`] they are given and run diesel and cargo there, so the
+//! working directory of the calling process is left alone.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::configs::Config;
+use crate::table_selection::TableSelection;
+
+pub use crate::logger::{LogLevel, LogSink};
+pub use crate::project::{CacheBackend, GitSetup, ProjectTemplate};
+pub use crate::prompt::Prompter;
+
+/// Why an operation failed, as the CLI would have reported it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+}
+
+impl Error {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error { message }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Answer blast's questions with `prompter` instead of the terminal, from now on.
+pub fn set_prompter(prompter: impl Prompter + 'static) {
+    crate::prompt::set_prompter(Arc::new(prompter));
+}
+
+/// Send every log line, at every level, to `sink` instead of printing it. Progress bars are not drawn.
+pub fn set_log_sink(sink: impl LogSink + 'static) {
+    crate::logger::set_sink(Arc::new(sink));
+}
+
+/// A Catalyst project loaded with [`open_project`], passed to the operations that work on it.
+#[derive(Debug, Clone)]
+pub struct Project {
+    config: Config,
+}
+
+impl Project {
+    /// The directory holding Catalyst.toml
+    pub fn dir(&self) -> &Path {
+        &self.config.project_dir
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.project_name
+    }
+
+    /// The environment from Catalyst.toml, e.g. "dev" or "prod"
+    pub fn environment(&self) -> &str {
+        &self.config.environment
+    }
+}
+
+/// Load the project whose Catalyst.toml is in `dir` or the closest parent directory.
+pub fn open_project(dir: &Path) -> Result<Project> {
+    Ok(Project { config: crate::configs::get_project_info_in(dir)? })
+}
+
+/// What [`create_project`] creates.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ProjectOptions {
+    pub name: String,
+    /// Directory the project is created in; the working directory by default
+    pub parent_dir: PathBuf,
+    /// Asked through the prompter when not set
+    pub template: Option<ProjectTemplate>,
+    /// Asked through the prompter when not set
+    pub cache: Option<CacheBackend>,
+    pub i18n: bool,
+    /// Clone the template's dev branch
+    pub dev_branch: bool,
+    /// Create a git repository with a `.gitignore` and a first commit; on by default
+    pub git: bool,
+}
+
+impl ProjectOptions {
+    pub fn new(name: &str) -> Self {
+        ProjectOptions { name: name.to_string(), parent_dir: PathBuf::new(), template: None, cache: None, i18n: false, dev_branch: false, git: true }
+    }
+
+    pub fn parent_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.parent_dir = dir.into();
+        self
+    }
+
+    pub fn template(mut self, template: ProjectTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    pub fn cache(mut self, cache: CacheBackend) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn i18n(mut self, i18n: bool) -> Self {
+        self.i18n = i18n;
+        self
+    }
+
+    pub fn dev_branch(mut self, dev_branch: bool) -> Self {
+        self.dev_branch = dev_branch;
+        self
+    }
+
+    pub fn git(mut self, git: bool) -> Self {
+        self.git = git;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ProjectReport {
+    pub path: PathBuf,
+    pub template: ProjectTemplate,
+    pub cache: CacheBackend,
+    pub i18n: bool,
+    /// What was done about version control
+    pub git: GitSetup,
+}
+
+/// `blast new`: clone the Catalyst template and configure it. Never opens an editor on `.env`.
+pub fn create_project(options: ProjectOptions) -> Result<ProjectReport> {
+    let template = options.template.or_else(crate::project::select_template).ok_or_else(|| "Project creation cancelled: no template chosen".to_string())?;
+    let cache = options.cache.or_else(crate::project::select_cache_backend).ok_or_else(|| "Project creation cancelled: no cache backend chosen".to_string())?;
+    let path = crate::project::create_project(&options.parent_dir, &options.name, options.dev_branch, template, cache, true, options.i18n)?;
+    let git = crate::project::setup_git(&path, !options.git);
+    Ok(ProjectReport { path, template, cache, i18n: options.i18n, git })
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MigrationReport {
+    /// Migrations this run applied
+    pub applied: usize,
+    /// Migrations still pending afterwards
+    pub pending: usize,
+    /// Whether schema.rs, structs and models were brought up to date afterwards ([codegen] auto_regen)
+    pub code_regenerated: bool,
+}
+
+/// `blast migrate` on the default connection, including the snapshot before and the regeneration after.
+pub fn run_migrations(project: &Project) -> Result<MigrationReport> {
+    let config = &project.config;
+    crate::dependencies::DependencyManager::new().ensure_installed(&["diesel"])?;
+
+    let before = crate::database::pending_migrations(config, "default").ok();
+    if before != Some(0) {
+        crate::db_snapshot::snapshot_before_migrate(config)?;
+    }
+    if !crate::database::migrate_connection(config, "default") {
+        return Err("Running migrations on the default connection failed; the log has the details".to_string().into());
+    }
+    let pending = crate::database::pending_migrations(config, "default").unwrap_or(0);
+    let code_regenerated = crate::database::regenerate_if_stale(config);
+    Ok(MigrationReport { applied: before.unwrap_or(pending).saturating_sub(pending), pending, code_regenerated })
+}
+
+/// Which tables [`generate_models`] regenerates.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct GenOptions {
+    /// Every table, not just those whose schema changed since the last run
+    pub all: bool,
+    /// Only these tables, whether or not they changed
+    pub only: Vec<String>,
+    pub except: Vec<String>,
+}
+
+impl GenOptions {
+    pub fn new() -> Self {
+        GenOptions::default()
+    }
+
+    pub fn all(mut self, all: bool) -> Self {
+        self.all = all;
+        self
+    }
+
+    pub fn only<S: Into<String>>(mut self, tables: impl IntoIterator<Item = S>) -> Self {
+        self.only = tables.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn except<S: Into<String>>(mut self, tables: impl IntoIterator<Item = S>) -> Self {
+        self.except = tables.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CodegenReport {
+    /// Tables whose files were regenerated
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    /// Tables dropped from the schema whose files were removed
+    pub removed: Vec<String>,
+}
+
+/// `blast gen models`.
+pub fn generate_models(project: &Project, options: GenOptions) -> Result<CodegenReport> {
+    let config = &project.config;
+    let selection = TableSelection { only: options.only, except: options.except };
+    let (ok, changes) = crate::models::generate_changes(config, options.all, &selection);
+    crate::file_writer::finish();
+    if !ok {
+        return Err("Model generation failed; the log has the details".to_string().into());
+    }
+    Ok(match changes {
+        Some(changes) => CodegenReport { changed: changes.changed, unchanged: changes.unchanged, removed: changes.removed },
+        None => CodegenReport { changed: Vec::new(), unchanged: 0, removed: Vec::new() },
+    })
+}
+
+/// Where [`install_spark`] gets a spark from.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum SparkSource {
+    /// A git URL, as `blast spark add` takes it
+    Git(String),
+    /// A spark directory on disk, copied into the project
+    Local(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SparkReport {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// Crates the spark added to Cargo.toml
+    pub dependencies: Vec<String>,
+    /// Variables the spark needs in .env
+    pub required_env: Vec<String>,
+}
+
+/// `blast spark add`: clone, validate and install a spark, add its dependencies and run its migrations.
+pub fn install_spark(project: &Project, source: SparkSource) -> Result<SparkReport> {
+    let source = match source {
+        SparkSource::Git(url) => crate::sparks::SparkOrigin::Git(url),
+        SparkSource::Local(path) => crate::sparks::SparkOrigin::Path(path.canonicalize().map_err(|e| format!("Failed to find {}: {}", path.display(), e))?),
+    };
+    let manifest = crate::sparks::add_spark(&source, &project.config)?;
+    Ok(SparkReport {
+        name: manifest.name,
+        version: manifest.version,
+        description: manifest.description,
+        dependencies: manifest.dependencies.into_iter().map(|dependency| dependency.crate_name).collect(),
+        required_env: manifest.required_env,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_build_up_from_defaults() {
+        let options = ProjectOptions::new("shop").template(ProjectTemplate::Api).i18n(true);
+        assert_eq!(options.template, Some(ProjectTemplate::Api));
+        assert_eq!(options.cache, None);
+        assert!(options.i18n && !options.dev_branch);
+
+        let gen = GenOptions::new().only(["users", "posts"]);
+        assert_eq!(gen.only, vec!["users".to_string(), "posts".to_string()]);
+        assert!(!gen.all && gen.except.is_empty());
+        assert_eq!(Error::from("boom".to_string()).to_string(), "boom");
+    }
+}
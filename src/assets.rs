@@ -3,36 +3,141 @@ use css_minify::optimizations::{Level, Minifier};
 // Remove unused imports
 // Removed unused imports
 use sass_rs::{compile_file, Options, OutputStyle};
-use std::path::Path;
-use std::io::Write;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::io::{self, Read, Write};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use regex::Regex;
 // No sync primitives needed anymore
 // No longer using tokio for file operations
 use walkdir::WalkDir;
 
-// Helper function to get public_dir with fallback
-fn get_public_dir(config: &Config) -> &str {
-    // Get from config or use default
-    config.assets.get("public_dir")
-        .and_then(|v| v.as_str())
-        .unwrap_or("public")
+// Error for an [assets.<name>] section that isn't configured (api/minimal templates have none)
+fn missing_section(name: &str) -> String {
+    format!("Missing [assets.{}] section in Catalyst.toml", name)
 }
 
-fn download_file(url: &str, dest_path: &Path) -> Result<(), String> {
-    let response = reqwest::blocking::get(url).map_err(|e| e.to_string())?;
-    let bytes = response.bytes().map_err(|e| e.to_string())?;
-    let mut file = std::fs::File::create(dest_path).map_err(|e| e.to_string())?;
-    file.write_all(&bytes).map_err(|e| e.to_string())?;
+// `--skip-hooks`, set once from main
+static SKIP_HOOKS: AtomicBool = AtomicBool::new(false);
+
+// Exit code of the [assets.hooks] command that failed, so blast can exit with it
+static FAILED_HOOK_EXIT_CODE: OnceLock<i32> = OnceLock::new();
+
+pub fn set_skip_hooks(skip: bool) {
+    SKIP_HOOKS.store(skip, Ordering::Relaxed);
+}
+
+pub fn failed_hook_exit_code() -> Option<i32> {
+    FAILED_HOOK_EXIT_CODE.get().copied()
+}
+
+// Run a step between its [assets.hooks] pre_<step> and post_<step> commands. Each step function
+// wraps only its own hooks, so running one step never fires another step's hooks
+fn with_hooks(config: &Config, step: &str, run: impl FnOnce() -> Result<(), String>) -> Result<(), String> {
+    let _span = crate::profile::span(step);
+    sync_public_dir(config)?;
+    let hooks = config.assets_config().hooks;
+    run_hooks(config, &format!("pre_{}", step), hooks.commands(&format!("pre_{}", step)))?;
+    run()?;
+    run_hooks(config, &format!("post_{}", step), hooks.commands(&format!("post_{}", step)))
+}
+
+// Hook commands run through the shell in the project directory, with BLAST_ENV and BLAST_PUBLIC_DIR set.
+// Their output is logged at debug level; the first failure stops the step
+fn run_hooks(config: &Config, hook: &str, commands: &[String]) -> Result<(), String> {
+    if commands.is_empty() {
+        return Ok(());
+    }
+    if SKIP_HOOKS.load(Ordering::Relaxed) {
+        return crate::logger::info(&format!("Skipping {} hooks (--skip-hooks)", hook));
+    }
+
+    let public_dir = config.project_dir.join(config.public_dir());
+    for command in commands {
+        crate::logger::info(&format!("Running {} hook: {}", hook, command))?;
+
+        let mut shell = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+        shell.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(command);
+        shell.current_dir(&config.project_dir).env("BLAST_ENV", &config.environment).env("BLAST_PUBLIC_DIR", &public_dir);
+
+        let output = crate::logger::command_output_at(hook, &mut shell, crate::logger::Verbosity::Debug).map_err(|e| format!("Failed to run {} hook `{}`: {}", hook, command, e))?;
+        if !output.status.success() {
+            // Killed by a signal has no exit code
+            let code = output.status.code().unwrap_or(1);
+            let _ = FAILED_HOOK_EXIT_CODE.set(code);
+            return Err(format!("{} hook `{}` failed with exit code {}: {}", hook, command, code, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+    }
+
     Ok(())
 }
 
+// The template mounts its static files from Rocket's `public_dir` setting, "public" when unset. Rocket.toml's
+// [default] value follows Catalyst.toml so a renamed directory is what the app serves
+fn public_dir_knob(content: &str, public_dir: &str) -> Result<String, String> {
+    let mut doc: toml_edit::DocumentMut = content.parse().map_err(|e| format!("Failed to parse Rocket.toml: {}", e))?;
+    let current = doc.get("default").and_then(|default| default.get("public_dir")).and_then(|value| value.as_str());
+    if current.unwrap_or("public") == public_dir {
+        return Ok(content.to_string());
+    }
+    let default = doc.entry("default").or_insert_with(toml_edit::table).as_table_mut().ok_or("[default] in Rocket.toml is not a table")?;
+    default.insert("public_dir", toml_edit::value(public_dir));
+    Ok(doc.to_string())
+}
+
+fn sync_public_dir(config: &Config) -> Result<(), String> {
+    let rocket_toml = config.project_dir.join("Rocket.toml");
+    let content = std::fs::read_to_string(&rocket_toml).unwrap_or_default();
+    let updated = public_dir_knob(&content, &config.public_dir())?;
+    if updated != content {
+        std::fs::write(&rocket_toml, updated).map_err(|e| format!("Failed to write Rocket.toml: {}", e))?;
+        crate::logger::info(&format!("Set public_dir = \"{}\" in Rocket.toml", config.public_dir()))?;
+    }
+    Ok(())
+}
+
+// Whether this project uses the asset pipeline at all (no [assets] section means API-only or minimal)
+pub fn has_asset_pipeline(config: &Config) -> bool {
+    config.assets.get("assets").is_some()
+}
+
+fn download_file(url: &str, dest_path: &Path) -> Result<(), String> {
+    let mut response = reqwest::blocking::get(url).and_then(|response| response.error_for_status()).map_err(|e| e.to_string())?;
+    save_download(&mut response, dest_path)
+}
+
+// Stream into <dest>.part and rename it into place once the whole body arrived, so a failed or interrupted
+// download never leaves a file that looks present
+fn save_download(body: &mut impl Read, dest_path: &Path) -> Result<(), String> {
+    let mut part_name = dest_path.as_os_str().to_owned();
+    part_name.push(".part");
+    let part = crate::platform::TempPath::new(part_name);
+
+    let mut file = std::fs::File::create(part.path()).map_err(|e| e.to_string())?;
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        crate::platform::check_interrupted()?;
+        let read = body.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+    }
+    file.flush().map_err(|e| e.to_string())?;
+    std::fs::rename(part.path(), dest_path).map_err(|e| format!("Failed to move {} into place: {}", dest_path.display(), e))
+}
+
 fn download_fontawesome(config: &Config) -> Result<(), String> {
     let project_dir = &config.project_dir;
-    let public_dir = get_public_dir(config);
+    let public_dir = config.public_dir();
     
     // Get FontAwesome config section
-    let fa_section = &config.assets["assets"]["fontawesome"];
-    let fa_base_url = fa_section["base_url"].as_str()
-        .ok_or_else(|| "Missing fontawesome base_url in config")?;
+    let fa_section = config.assets_config().fontawesome.ok_or_else(|| missing_section("fontawesome"))?;
+    let fa_base_url = fa_section.base_url.as_deref()
+        .ok_or("Missing fontawesome base_url in config")?;
         
     // Standard directory structure
     let fa_public_dir = project_dir.join(public_dir).join("fonts").join("fontawesome");
@@ -45,21 +150,15 @@ fn download_fontawesome(config: &Config) -> Result<(), String> {
     // All FontAwesome asset paths are in Catalyst.toml - no defaults needed
 
     // Extract asset paths directly from config (no defaults needed as they're in Catalyst.toml)
-    let get_string_array = |key: &str| -> Result<Vec<String>, String> {
-        fa_section.get(key)
-            .and_then(|v| v.as_array())
-            .map(|arr| arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-            )
-            .ok_or_else(|| format!("Missing '{}' in fontawesome config", key))
+    let get_string_array = |key: &str, files: &Option<Vec<String>>| -> Result<Vec<String>, String> {
+        files.clone().ok_or_else(|| format!("Missing '{}' in fontawesome config", key))
     };
 
     // Get asset lists from config
-    let css_files = get_string_array("css")?;
-    let js_files = get_string_array("js")?;
-    let sprite_files = get_string_array("sprites")?;
-    let webfont_files = get_string_array("webfonts")?;
+    let css_files = get_string_array("css", &fa_section.css)?;
+    let js_files = get_string_array("js", &fa_section.js)?;
+    let sprite_files = get_string_array("sprites", &fa_section.sprites)?;
+    let webfont_files = get_string_array("webfonts", &fa_section.webfonts)?;
 
     // Download all assets sequentially 
     let all_assets: Vec<String> = css_files.into_iter()
@@ -72,6 +171,7 @@ fn download_fontawesome(config: &Config) -> Result<(), String> {
 
     // Simple sequential download
     for asset_path in all_assets {
+        crate::platform::check_interrupted()?;
         let url = format!("{}/{}", fa_base_url, asset_path);
         let dest_path = fa_public_dir.join(&asset_path);
         
@@ -91,18 +191,18 @@ fn download_fontawesome(config: &Config) -> Result<(), String> {
 
 fn download_materialicons(config: &Config) -> Result<(), String> {
     let project_dir = &config.project_dir;
-    let public_dir = get_public_dir(config);
+    let public_dir = config.public_dir();
     
     // Get the materialicons section
-    let mi_section = &config.assets["assets"]["materialicons"];
+    let mi_section = config.assets_config().materialicons.ok_or_else(|| missing_section("materialicons"))?;
     
     // Get base URL and file names
-    let mi_base_url = mi_section["base_url"].as_str()
-        .ok_or_else(|| "Missing materialicons base_url in config")?;
-    let woff2_file = mi_section["woff2"].as_str()
-        .ok_or_else(|| "Missing materialicons woff2 in config")?;
-    let ttf_file = mi_section["ttf"].as_str()
-        .ok_or_else(|| "Missing materialicons ttf in config")?;
+    let mi_base_url = mi_section.base_url.as_deref()
+        .ok_or("Missing materialicons base_url in config")?;
+    let woff2_file = mi_section.woff2.as_deref()
+        .ok_or("Missing materialicons woff2 in config")?;
+    let ttf_file = mi_section.ttf.as_deref()
+        .ok_or("Missing materialicons ttf in config")?;
 
     // Create standard directory
     let mi_public_dir = project_dir.join(public_dir).join("fonts").join("material-icons");
@@ -126,18 +226,18 @@ fn download_materialicons(config: &Config) -> Result<(), String> {
 
 fn download_htmx_js(config: &Config) -> Result<(), String> {
     let project_dir = &config.project_dir;
-    let public_dir = get_public_dir(config);
+    let public_dir = config.public_dir();
     
     // Get the HTMX config section
-    let htmx_section = &config.assets["assets"]["htmx"];
+    let htmx_section = config.assets_config().htmx.ok_or_else(|| missing_section("htmx"))?;
     
     // Create standard directory
     let htmx_dir = project_dir.join(public_dir).join("js").join("htmx");
     std::fs::create_dir_all(&htmx_dir).map_err(|e| e.to_string())?;
 
     // Get the JS URL
-    let js_url = htmx_section["js_url"].as_str()
-        .ok_or_else(|| "Missing htmx js_url in config")?;
+    let js_url = htmx_section.js_url.as_deref()
+        .ok_or("Missing htmx js_url in config")?;
 
     crate::logger::info("Downloading HTMX JS...")?;
 
@@ -148,217 +248,219 @@ fn download_htmx_js(config: &Config) -> Result<(), String> {
     Ok(())
 }
 
+// Written into src/assets/materialize after a successful setup. Holds the version (or branch) the sources
+// came from, so running again with the same one is a no-op
+const MATERIALIZE_STAMP: &str = ".blast-version";
+
+enum MaterializeSource {
+    Archive(String),
+    Branch(String),
+}
+
+// Source archive GitHub publishes for a release tag
+fn materialize_archive_url(repo_url: &str, version: &str) -> String {
+    let repo = repo_url.trim_end_matches('/').trim_end_matches(".git");
+    let tag = if version.starts_with('v') { version.to_string() } else { format!("v{}", version) };
+    format!("{}/archive/refs/tags/{}.tar.gz", repo, tag)
+}
+
+// Hashes everything read through it, so a streamed download can be checked without keeping it around
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Not ErrorKind::Interrupted, which readers retry
+        if crate::platform::interrupted() {
+            return Err(io::Error::other("interrupted"));
+        }
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// Stream a .tar.gz and unpack only sass/ and dist/js/materialize.min.js into `staging`, checking the
+// archive's SHA-256 against `checksum` when one is configured
+fn extract_materialize_archive(url: &str, checksum: Option<&str>, staging: &Path) -> Result<(), String> {
+    let response = reqwest::blocking::get(url).and_then(|response| response.error_for_status()).map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(HashingReader { inner: response, hasher: Sha256::new() }));
+    let invalid = |e: io::Error| format!("Invalid Materialize archive {}: {}", url, e);
+
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        // Everything sits in a top-level directory such as materialize-2.0.3/
+        let relative: PathBuf = entry.path().map_err(invalid)?.components().skip(1).collect();
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            continue;
+        }
+
+        let target = if relative.starts_with("sass") {
+            staging.join(&relative)
+        } else if relative == Path::new("dist/js/materialize.min.js") {
+            staging.join("materialize.min.js")
+        } else {
+            continue;
+        };
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        entry.unpack(&target).map_err(|e| format!("Failed to extract {}: {}", relative.display(), e))?;
+    }
+
+    // tar stops at its end-of-archive marker; the checksum covers the whole download
+    let mut decoder = archive.into_inner();
+    io::copy(&mut decoder, &mut io::sink()).map_err(invalid)?;
+    let mut reader = decoder.into_inner();
+    io::copy(&mut reader, &mut io::sink()).map_err(invalid)?;
+
+    if let Some(expected) = checksum {
+        let expected = expected.trim().trim_start_matches("sha256:").to_lowercase();
+        let actual = format!("{:x}", reader.hasher.finalize());
+        if actual != expected {
+            return Err(format!("Checksum mismatch for {}: expected {}, got {}", url, expected, actual));
+        }
+    }
+
+    if !staging.join("sass").is_dir() {
+        return Err(format!("No sass/ directory in {}", url));
+    }
+    Ok(())
+}
+
+// Shallow clone of a branch, for `repo_ref`. Leaves the same layout in `staging` as an archive
+fn clone_materialize_branch(repo_url: &str, branch: &str, staging: &Path) -> Result<(), String> {
+    let clone_dir = staging.join("repository");
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--depth=1", "--branch", branch, repo_url]).arg(&clone_dir);
+    if crate::logger::level() >= crate::logger::Verbosity::Trace {
+        clone.arg("--progress");
+    }
+    let output = crate::logger::command_output("git", &mut clone).map_err(|e| format!("Failed to run git clone: {}. Is git installed and on PATH?", e))?;
+    if !output.status.success() {
+        return Err(format!("Git clone failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    std::fs::rename(clone_dir.join("sass"), staging.join("sass")).map_err(|_| format!("Could not find a sass directory on branch '{}' of {}", branch, repo_url))?;
+    let js = clone_dir.join("dist/js/materialize.min.js");
+    if js.exists() {
+        std::fs::rename(&js, staging.join("materialize.min.js")).map_err(|e| e.to_string())?;
+    }
+    std::fs::remove_dir_all(&clone_dir).map_err(|e| format!("Failed to remove {}: {}", clone_dir.display(), e))
+}
+
 fn download_materialize_scss(config: &Config) -> Result<(), String> {
     let project_dir = &config.project_dir;
-    let src_sass_dir = project_dir.join("src/assets/sass");
     let materialize_dir = project_dir.join("src/assets/materialize");
+    let js_dest_dir = project_dir.join(config.public_dir()).join("js").join("materialize");
+    let materialize_js_dest = js_dest_dir.join("materialize.min.js");
 
-    // Create the directories if they don't exist
-    std::fs::create_dir_all(&src_sass_dir).map_err(|e| e.to_string())?;
-    
-    // Create a progress bar spinner for the operation
     let mut progress = crate::logger::create_progress(None);
     progress.set_message("Setting up Materialize assets...");
-    
-    // Direct access to the materialize section we know exists
-    let mat_section = &config.assets["assets"]["materialize"];
-    
-    // Get repository URL and version directly from the config structure
-    let repo_url = mat_section["repo_url"].as_str()
-        .ok_or_else(|| "Missing materialize repo_url in config")?;
-    
-    let version = mat_section["version"].as_str()
-        .ok_or_else(|| "Missing materialize version in config")?;
-    
-    // Check if we should force a fresh clone (for debugging or version changes)
+
+    let mat_section = config.assets_config().materialize.ok_or_else(|| missing_section("materialize"))?;
+    let repo_url = mat_section.repo_url.as_deref().ok_or("Missing materialize repo_url in config")?;
+
+    // A branch in repo_ref is the only case that still clones; everything else is a pinned release archive
+    let (stamp, source) = match (&mat_section.repo_ref, &mat_section.version, &mat_section.archive_url) {
+        (Some(branch), _, _) => (format!("branch {}", branch), MaterializeSource::Branch(branch.clone())),
+        (None, Some(version), url) => (version.clone(), MaterializeSource::Archive(url.clone().unwrap_or_else(|| materialize_archive_url(repo_url, version)))),
+        (None, None, Some(url)) => (url.clone(), MaterializeSource::Archive(url.clone())),
+        (None, None, None) => return Err("Missing materialize version in config".to_string()),
+    };
+
     let force_fresh = std::env::var("BLAST_FORCE_FRESH_MATERIALIZE").unwrap_or_else(|_| String::from("0")) == "1";
-    
-    // Check if materialize repo already exists
-    let repo_exists = materialize_dir.exists();
-    
-    if repo_exists && force_fresh {
-        // Clean up existing repo if forced
-        progress.set_message("Forcing fresh clone of Materialize repository...");
-        match std::fs::remove_dir_all(&materialize_dir) {
-            Ok(_) => {
-                progress.set_message("Removed existing Materialize directory");
-            },
-            Err(e) => {
-                let warning_msg = format!("Failed to remove existing Materialize directory: {}", e);
-                progress.set_message(&warning_msg);
-                // Continue anyway, the clone might still succeed
-            }
-        }
+    let stamp_path = materialize_dir.join(MATERIALIZE_STAMP);
+    let installed = std::fs::read_to_string(&stamp_path).map(|installed| installed.trim() == stamp).unwrap_or(false);
+    if installed && !force_fresh && materialize_dir.join("sass").is_dir() && materialize_js_dest.exists() {
+        progress.success(&format!("Materialize {} is already set up", stamp));
+        return Ok(());
     }
-    
-    // Clone or check the repository
-    if !repo_exists || force_fresh {
-        // Clone Materialize repository if it doesn't exist or we're forcing a fresh clone
-        progress.set_message(&format!("Cloning Materialize v{} repository...", version));
-        
-        // Make sure parent directory exists
-        if let Some(parent) = materialize_dir.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+
+    // Unpack next to the current sources and only swap them in once everything arrived
+    let staging_guard = crate::platform::TempPath::new(project_dir.join("src/assets/.materialize-download"));
+    let staging = staging_guard.path();
+    if staging.exists() {
+        std::fs::remove_dir_all(staging).map_err(|e| format!("Failed to remove {}: {}", staging.display(), e))?;
+    }
+    std::fs::create_dir_all(staging).map_err(|e| e.to_string())?;
+
+    let fetched = match &source {
+        MaterializeSource::Archive(url) => {
+            progress.set_message(&format!("Downloading Materialize {}...", stamp));
+            extract_materialize_archive(url, mat_section.checksum.as_deref(), staging)
         }
-        
-        // Use standard process to run git clone
-        // The Materialize repo hasn't been updated in years, so just clone the default branch
-        let clone_output = std::process::Command::new("git")
-            .args(&["clone", "--depth=1", &repo_url])
-            .arg(&materialize_dir)
-            .output();
-            
-        match clone_output {
-            Ok(output) => {
-                if !output.status.success() {
-                    let error = String::from_utf8_lossy(&output.stderr);
-                    let error_msg = format!("Failed to clone Materialize repository: {}", error);
-                    progress.set_message(&error_msg);
-                    
-                    return Err(format!("Git clone failed: {}", error));
-                }
-                
-                // Successfully cloned, now extract the compiled JS file if it exists and keep only the sass folder
-                progress.set_message("Extracting JS file and keeping sass folder...");
-                
-                // First make sure the sass directory exists
-                let sass_dir = materialize_dir.join("sass");
-                if !sass_dir.exists() {
-                    return Err("Could not find sass directory in the cloned repository".to_string());
-                }
-                
-                // Check if the dist directory with compiled JS exists
-                // Use the exact path: dist/js/materialize.min.js
-                let js_file_path = materialize_dir.join("dist").join("js").join("materialize.min.js");
-                let public_dir = get_public_dir(config);
-                let js_dest_dir = project_dir.join(&public_dir).join("js").join("materialize");
-                let materialize_js_dest = js_dest_dir.join("materialize.min.js");
-                
-                // Create js directory if it doesn't exist
-                std::fs::create_dir_all(&js_dest_dir).map_err(|e| e.to_string())?;
-                
-                // Copy JS file from dist directory if it exists
-                let js_from_dist = if js_file_path.exists() {
-                    progress.set_message("Found Materialize JS in dist folder, copying...");
-                    match std::fs::copy(&js_file_path, &materialize_js_dest) {
-                        Ok(_) => {
-                            progress.set_message("Materialize JS copied from dist folder successfully");
-                            true
-                        },
-                        Err(e) => {
-                            progress.set_message(&format!("Failed to copy Materialize JS from dist: {}", e));
-                            false
-                        }
-                    }
-                } else {
-                    progress.set_message("Materialize JS not found in dist folder");
-                    false
-                };
-                
-                // Get all entries in the materialize directory
-                for entry in std::fs::read_dir(&materialize_dir).map_err(|e| e.to_string())? {
-                    if let Ok(entry) = entry {
-                        let path = entry.path();
-                        
-                        // Skip the sass directory
-                        if path.file_name().map_or(false, |name| name == "sass") {
-                            continue;
-                        }
-                        
-                        // Remove the entry - handle both files and directories
-                        if path.is_dir() {
-                            std::fs::remove_dir_all(&path).map_err(|e| 
-                                format!("Failed to remove directory {}: {}", path.display(), e))?;
-                        } else {
-                            std::fs::remove_file(&path).map_err(|e| 
-                                format!("Failed to remove file {}: {}", path.display(), e))?;
-                        }
-                    }
-                }
-                
-                progress.set_message("Materialize repository cloned and cleaned - kept only sass directory");
-                
-                // Return whether we successfully copied the JS file from dist
-                if js_from_dist {
-                    return Ok(());
-                }
-            },
-            Err(e) => {
-                let error_msg = format!("Failed to execute git clone: {}", e);
-                progress.set_message(&error_msg);
-                
-                // Check if git is installed
-                let git_check = std::process::Command::new("git")
-                    .arg("--version")
-                    .output();
-                    
-                if git_check.is_err() {
-                    progress.set_message("Git may not be installed or is not in PATH. Please install git and try again.");
-                }
-                
-                return Err(e.to_string());
-            }
+        MaterializeSource::Branch(branch) => {
+            progress.set_message(&format!("Cloning Materialize branch {}...", branch));
+            clone_materialize_branch(repo_url, branch, staging)
         }
-    } else {
-        progress.set_message("Using existing Materialize repository");
+    };
+    if let Err(e) = fetched {
+        progress.error("Materialize setup failed");
+        return Err(e);
     }
 
-    // If we already copied the JS file from the distribution folder, skip this part
-    // Otherwise, fall back to using CDN for JS
-    let public_dir = get_public_dir(config);
-    let js_dest_dir = project_dir.join(&public_dir).join("js").join("materialize");
-    let materialize_js_dest = js_dest_dir.join("materialize.min.js");
-    
-    // Check if the JS file already exists (from the local copy operation)
-    if !materialize_js_dest.exists() {
-        // Create js directory if it doesn't exist
-        std::fs::create_dir_all(&js_dest_dir).map_err(|e| e.to_string())?;
-        
-        // Fallback to CDN for the JS file
-        let js_url = config.assets["assets"]["materialize"]["js_url"].as_str()
-            .ok_or_else(|| "Missing materialize js_url in config")?;
-            
-        progress.set_message("JS file not found locally, downloading Materialize JS from CDN...");
-        match download_file(&js_url, &materialize_js_dest) {
-            Ok(_) => {
-                progress.set_message("Materialize JS file downloaded from CDN successfully");
-            },
-            Err(e) => {
-                progress.set_message(&format!("Failed to download Materialize JS: {}", e));
-                return Err(format!("Failed to download Materialize JS: {}", e));
-            }
-        }
+    if materialize_dir.exists() {
+        std::fs::remove_dir_all(&materialize_dir).map_err(|e| format!("Failed to remove {}: {}", materialize_dir.display(), e))?;
+    }
+    std::fs::rename(staging, &materialize_dir).map_err(|e| format!("Failed to move Materialize sources into place: {}", e))?;
+
+    // The compiled JS comes from the archive when it ships one, otherwise from js_url
+    std::fs::create_dir_all(&js_dest_dir).map_err(|e| e.to_string())?;
+    let bundled_js = materialize_dir.join("materialize.min.js");
+    if bundled_js.exists() {
+        std::fs::copy(&bundled_js, &materialize_js_dest).map_err(|e| format!("Failed to copy Materialize JS: {}", e))?;
+        std::fs::remove_file(&bundled_js).map_err(|e| e.to_string())?;
     } else {
-        progress.set_message("Using Materialize JS file copied from local repository");
+        let js_url = mat_section.js_url.as_deref().ok_or("Missing materialize js_url in config")?;
+        progress.set_message("Downloading Materialize JS from CDN...");
+        download_file(js_url, &materialize_js_dest).map_err(|e| format!("Failed to download Materialize JS: {}", e))?;
     }
 
-    // Don't create SCSS files - they're already in the template
-    // Just make sure the directory exists for future operations
-    std::fs::create_dir_all(&src_sass_dir).map_err(|e| e.to_string())?;
-
-    progress.set_message("Materialize setup completed successfully");
+    std::fs::write(&stamp_path, format!("{}\n", stamp)).map_err(|e| format!("Failed to write {}: {}", stamp_path.display(), e))?;
+    progress.success(&format!("Materialize {} set up", stamp));
     Ok(())
 }
 
+// Display name, Catalyst.toml [assets] key, whether it is configured, and the download
+type Downloader = (&'static str, &'static str, bool, fn(&Config) -> Result<(), String>);
+
 pub fn download_assets(config: &Config) -> Result<(), String> {
     // Use fresh config to ensure we have the latest settings
     let fresh_config = crate::configs::get_fresh_config(&config.project_dir).map_err(|e| e.to_string())?;
     
-    // Verify required config sections exist
-    let assets = &fresh_config.assets;
-    if !assets.as_table().map_or(false, |t| t.contains_key("assets")) {
-        return Err("Missing [assets] section in Catalyst.toml".into());
+    // Projects without an asset pipeline (api/minimal templates) have nothing to download
+    if !has_asset_pipeline(&fresh_config) {
+        crate::logger::info("No [assets] section in Catalyst.toml, skipping CDN downloads")?;
+        return Ok(());
     }
     
+    sync_public_dir(&fresh_config)?;
     crate::logger::info("Downloading CDN assets...")?;
     
-    // Simple linear download of all assets
-    let asset_downloads = [
-        ("FontAwesome", download_fontawesome(&fresh_config)),
-        ("Material Icons", download_materialicons(&fresh_config)),
-        ("Materialize", download_materialize_scss(&fresh_config)),
-        ("HTMX", download_htmx_js(&fresh_config)),
+    // Simple linear download of the assets configured in Catalyst.toml
+    let assets = fresh_config.assets_config();
+    let downloaders: [Downloader; 4] = [
+        ("FontAwesome", "fontawesome", assets.fontawesome.is_some(), download_fontawesome),
+        ("Material Icons", "materialicons", assets.materialicons.is_some(), download_materialicons),
+        ("Materialize", "materialize", assets.materialize.is_some(), download_materialize_scss),
+        ("HTMX", "htmx", assets.htmx.is_some(), download_htmx_js),
     ];
+    let mut asset_downloads = Vec::new();
+    for (name, section, configured, download) in downloaders {
+        if !configured {
+            crate::logger::info(&format!("No [assets.{}] section in Catalyst.toml, skipping {}", section, name))?;
+            continue;
+        }
+        // Ctrl-C during `blast init` stops before the next download
+        if crate::platform::interrupted() {
+            return Err("Interrupted".to_string());
+        }
+        let _span = crate::profile::span(&format!("cdn {}", section));
+        asset_downloads.push((name, download(&fresh_config)));
+    }
     
     let mut success_count = 0;
     let total_count = asset_downloads.len();
@@ -387,10 +489,120 @@ pub fn download_assets(config: &Config) -> Result<(), String> {
 }
 
 pub fn transpile_all_scss(config: &Config) -> Result<(), String> {
+    with_hooks(config, "scss", || transpile_scss_files(config))
+}
+
+// Outputs the last `blast scss` run wrote into public/css, so outputs of renamed or removed entrypoints can go
+const SCSS_OUTPUTS_PATH: &str = ".blast/scss-outputs";
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct ScssOutputs {
+    outputs: Vec<String>,
+}
+
+// Files in public_dir/css the last `blast scss` run wrote, for `blast clean`
+pub fn recorded_scss_outputs(config: &Config) -> Vec<String> {
+    std::fs::read_to_string(config.project_dir.join(SCSS_OUTPUTS_PATH)).ok().and_then(|content| toml::from_str::<ScssOutputs>(&content).ok()).map(|recorded| recorded.outputs).unwrap_or_default()
+}
+
+// Entry files to compile: [assets.scss] entrypoints, or every non-partial file anywhere under src/assets/sass
+fn scss_entrypoints(config: &Config, sass_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let configured = config.assets_config().scss.entrypoints;
+    if !configured.is_empty() {
+        let missing: Vec<&str> = configured.iter().filter(|entry| !sass_dir.join(entry).is_file()).map(String::as_str).collect();
+        if !missing.is_empty() {
+            return Err(format!("[assets.scss] entrypoints not found in src/assets/sass: {}", missing.join(", ")));
+        }
+        return Ok(configured.iter().map(|entry| sass_dir.join(entry)).collect());
+    }
+
+    let mut entrypoints = vec![];
+    for entry in WalkDir::new(sass_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let partial = entry.file_name().to_string_lossy().starts_with('_');
+        if entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "scss") && !partial {
+            entrypoints.push(entry.into_path());
+        }
+    }
+    Ok(entrypoints)
+}
+
+// Output path for a source file, relative to its output directory and with / separators:
+// pages/home.scss -> pages/home.min.css
+fn mirrored_output(relative: &Path, extension: &str) -> String {
+    let stem = relative.file_stem().unwrap_or_default().to_string_lossy();
+    let output = relative.with_file_name(format!("{}.min.{}", stem, extension));
+    output.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+// One line per (source, output path, size), grouped under the directory the output was written to
+fn print_outputs(outputs: &[(String, String, u64)]) -> Result<(), String> {
+    let mut grouped: std::collections::BTreeMap<&str, Vec<String>> = std::collections::BTreeMap::new();
+    for (source, output, size) in outputs {
+        let (dir, name) = output.rsplit_once('/').unwrap_or((".", output.as_str()));
+        grouped.entry(dir).or_default().push(format!("    {} -> {} ({})", source, name, crate::build::format_size(*size)));
+    }
+    for (dir, lines) in grouped {
+        crate::logger::plain(&format!("  {}/", dir))?;
+        for line in lines {
+            crate::logger::plain(&line)?;
+        }
+    }
+    Ok(())
+}
+
+// Remove the public/css outputs of entrypoints that were renamed or removed since the last run. Only files an
+// earlier run recorded are removed, so hook outputs and css/app/ (owned by publish_css) are never touched.
+// Before anything is recorded, the first run just lists the .min.css files no entrypoint produces
+fn remove_stale_css(config: &Config, css_dir: &Path, outputs: &[String]) -> Result<(), String> {
+    let state_path = config.project_dir.join(SCSS_OUTPUTS_PATH);
+    let previous: Option<ScssOutputs> = std::fs::read_to_string(&state_path).ok().and_then(|content| toml::from_str(&content).ok());
+
+    match previous {
+        Some(previous) => {
+            for name in previous.outputs.iter().filter(|name| !outputs.contains(name)) {
+                let path = css_dir.join(name);
+                if path.is_file() {
+                    std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                    crate::logger::plain(&format!("  removed {}/css/{} (no matching SCSS entrypoint)", config.public_dir(), name))?;
+                    // Directories mirrored from sass/ go once their last output does
+                    for dir in path.ancestors().skip(1).take_while(|dir| *dir != css_dir) {
+                        if std::fs::remove_dir(dir).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        None => {
+            let mut unmatched: Vec<String> = std::fs::read_dir(css_dir)
+                .map_err(|e| e.to_string())?
+                .flatten()
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with(".min.css") && !outputs.contains(name))
+                .collect();
+            unmatched.sort();
+            if !unmatched.is_empty() {
+                crate::logger::warning(&format!(
+                    "These files in {}/css don't match an SCSS entrypoint: {}. blast didn't record writing them, so it leaves them alone; delete any that are stale",
+                    config.public_dir(),
+                    unmatched.join(", ")
+                ))?;
+            }
+        }
+    }
+
+    let state = toml::to_string(&ScssOutputs { outputs: outputs.to_vec() }).map_err(|e| e.to_string())?;
+    crate::file_writer::write_state(state_path, &state)
+}
+
+fn transpile_scss_files(config: &Config) -> Result<(), String> {
     let project_dir = &config.project_dir;
-    let is_production = config.environment == "prod" || config.environment == "production";
+    // Minification follows [environments.<name>] minify, defaulting to on for prod
+    let minify = config.minify_assets();
     let sass_dir = project_dir.join("src/assets/sass");
-    let public_dir = get_public_dir(config);
+    let public_dir = config.public_dir();
     let css_dir = project_dir.join(&public_dir).join("css");
 
     // Create directories
@@ -403,18 +615,8 @@ pub fn transpile_all_scss(config: &Config) -> Result<(), String> {
         return Ok(());
     }
 
-    // Find all SCSS files
-    let entries = std::fs::read_dir(&sass_dir).map_err(|e| e.to_string())?;
-    let mut scss_files = vec![];
-    let mut file_count = 0;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        if entry.path().extension().is_some_and(|ext| ext == "scss") {
-            scss_files.push(entry.path());
-            file_count += 1;
-        }
-    }
+    let scss_files = scss_entrypoints(config, &sass_dir)?;
+    let file_count = scss_files.len();
 
     if scss_files.is_empty() {
         crate::logger::info("No SCSS files found!").map_err(|e| e.to_string())?;
@@ -425,18 +627,20 @@ pub fn transpile_all_scss(config: &Config) -> Result<(), String> {
 
     // Counter for error tracking
     let mut error_count = 0;
-    let mut success_count = 0;
+    let mut outputs = Vec::new();
+    let mut summary = Vec::new();
 
-    // Process each file
+    // Process each file, mirroring its directory under sass/ into css/
     for scss_file in &scss_files {
-        let file_stem = scss_file.file_stem().unwrap().to_str().unwrap();
-        let output_file = css_dir.join(format!("{}.min.css", file_stem));
+        let entrypoint = scss_file.strip_prefix(&sass_dir).unwrap_or(scss_file);
+        let output_name = mirrored_output(entrypoint, "css");
+        let output_file = css_dir.join(&output_name);
         
         crate::logger::debug(&format!("Transpiling {} to {}", scss_file.display(), output_file.display()))?;
         
         // Setup SCSS compilation options (create new options for each file)
         let mut sass_options = Options::default();
-        if is_production {
+        if minify {
             sass_options.output_style = OutputStyle::Compressed;
         } else {
             sass_options.output_style = OutputStyle::Expanded;
@@ -446,12 +650,16 @@ pub fn transpile_all_scss(config: &Config) -> Result<(), String> {
         match compile_file(scss_file.to_str().unwrap(), sass_options) {
             Ok(css_content) => {
                 // Write the CSS file (always as .min.css)
+                if let Some(parent) = output_file.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
                 std::fs::write(&output_file, &css_content).map_err(|e| e.to_string())?;
-                success_count += 1;
+                summary.push((entrypoint.display().to_string(), format!("{}/css/{}", public_dir, output_name), css_content.len() as u64));
+                outputs.push(output_name);
             }
             Err(e) => {
                 error_count += 1;
-                crate::logger::warning(&format!("Error compiling {}: {}", file_stem, e)).map_err(|e| e.to_string())?;
+                crate::logger::warning(&format!("Error compiling {}: {}", entrypoint.display(), e)).map_err(|e| e.to_string())?;
             }
         }
     }
@@ -462,11 +670,17 @@ pub fn transpile_all_scss(config: &Config) -> Result<(), String> {
             crate::logger::error(&format!("SCSS processing failed - all {} files had errors", error_count)).map_err(|e| e.to_string())?;
         } else {
             crate::logger::warning(&format!("SCSS processing completed: {} succeeded, {} failed", 
-                success_count, error_count)).map_err(|e| e.to_string())?;
+                outputs.len(), error_count)).map_err(|e| e.to_string())?;
         }
     } else {
         crate::logger::success(&format!("All {} SCSS files processed successfully", file_count)).map_err(|e| e.to_string())?;
     }
+    print_outputs(&summary)?;
+
+    // Only a complete run knows every output that should still exist
+    if error_count == 0 {
+        remove_stale_css(config, &css_dir, &outputs)?;
+    }
     
     Ok(())
 }
@@ -480,10 +694,73 @@ pub fn minify_css_files(config: &Config) -> Result<(), String> {
 }
 
 pub fn process_js(config: &Config) -> Result<(), String> {
+    with_hooks(config, "js", || process_js_files(config))
+}
+
+// A glob relative to src/assets/js as an anchored regex: `**` spans directories, `*` and `?` stay within one
+fn path_glob(pattern: &str) -> Result<Regex, String> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.trim_start_matches("./").trim_start_matches('/').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| format!("Invalid [assets.js] pattern \"{}\": {}", pattern, e))
+}
+
+// Where a file under src/assets/js goes
+#[derive(Debug)]
+enum JsTarget {
+    Skip,
+    // js/app/<path>.min.js, or js/app/<path> unchanged when verbatim
+    App { verbatim: bool },
+    // <public_dir>/<path>, unchanged
+    Root,
+}
+
+// The [assets.js] patterns, compiled once per run
+struct JsRules {
+    exclude: Vec<(String, Regex)>,
+    copy_verbatim: Vec<(String, Regex)>,
+    root_files: Vec<(String, Regex)>,
+}
+
+impl JsRules {
+    fn new(js: &crate::configs::JsConfig) -> Result<Self, String> {
+        let compile = |patterns: &[String]| patterns.iter().map(|pattern| path_glob(pattern).map(|regex| (pattern.clone(), regex))).collect::<Result<Vec<_>, String>>();
+        Ok(JsRules { exclude: compile(&js.exclude)?, copy_verbatim: compile(&js.copy_verbatim)?, root_files: compile(&js.root_files)? })
+    }
+
+    // `relative` uses / separators
+    fn target(&self, relative: &str) -> Result<JsTarget, String> {
+        let first_match = |patterns: &[(String, Regex)]| patterns.iter().find(|(_, regex)| regex.is_match(relative)).map(|(pattern, _)| pattern.clone());
+        match (first_match(&self.exclude), first_match(&self.root_files)) {
+            (Some(excluded), Some(root)) => Err(format!("src/assets/js/{} matches both [assets.js] exclude \"{}\" and root_files \"{}\"; keep it in only one of them", relative, excluded, root)),
+            (Some(_), None) => Ok(JsTarget::Skip),
+            (None, Some(_)) => Ok(JsTarget::Root),
+            (None, None) => Ok(JsTarget::App { verbatim: first_match(&self.copy_verbatim).is_some() }),
+        }
+    }
+}
+
+fn process_js_files(config: &Config) -> Result<(), String> {
     let project_dir = &config.project_dir;
-    let public_dir = get_public_dir(config);
+    let public_dir = config.public_dir();
     let public_path = project_dir.join(&public_dir);
-    let is_production = config.environment == "prod" || config.environment == "production";
+    let minify = config.minify_assets();
+    let rules = JsRules::new(&config.assets_config().js)?;
 
     // Source and destination directories
     let src_js_dir = project_dir.join("src").join("assets").join("js");
@@ -500,12 +777,18 @@ pub fn process_js(config: &Config) -> Result<(), String> {
         return Ok(());
     }
 
-    // Find all JS files
+    // Find all JS files and where each one goes. Conflicting patterns fail the run before anything is written
     let mut js_files = Vec::new();
-    for entry in WalkDir::new(&src_js_dir).into_iter().filter_map(Result::ok) {
+    let mut excluded = 0;
+    for entry in WalkDir::new(&src_js_dir).sort_by_file_name().into_iter().filter_map(Result::ok) {
         let path = entry.path();
         if path.is_file() && path.extension().map_or(false, |ext| ext == "js") {
-            js_files.push(path.to_path_buf());
+            let rel_path = path.strip_prefix(&src_js_dir).unwrap().to_path_buf();
+            let relative = rel_path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            match rules.target(&relative)? {
+                JsTarget::Skip => excluded += 1,
+                target => js_files.push((rel_path, relative, target)),
+            }
         }
     }
 
@@ -516,13 +799,17 @@ pub fn process_js(config: &Config) -> Result<(), String> {
 
     crate::logger::info(&format!("Processing {} JS files...", js_files.len())).map_err(|e| e.to_string())?;
 
-    // Process each file
-    for js_file in &js_files {
-        // Get relative path and create destination path
-        let rel_path = js_file.strip_prefix(&src_js_dir).unwrap();
-        let min_dest_path = dest_js_dir.join("app").join(
-            rel_path.with_file_name(format!("{}.min.js", rel_path.file_stem().unwrap().to_str().unwrap()))
-        );
+    // Process each file, mirroring its directory under js/ into js/app/ (or public_dir for root_files)
+    let mut summary = Vec::new();
+    for (rel_path, relative, target) in &js_files {
+        let (min_dest_path, output) = match target {
+            JsTarget::Root => (public_path.join(rel_path), format!("{}/{}", public_dir, relative)),
+            JsTarget::App { verbatim: true } => (dest_js_dir.join("app").join(rel_path), format!("{}/js/app/{}", public_dir, relative)),
+            _ => {
+                let output_name = mirrored_output(rel_path, "js");
+                (dest_js_dir.join("app").join(&output_name), format!("{}/js/app/{}", public_dir, output_name))
+            }
+        };
 
         // Create parent directory if needed
         if let Some(parent) = min_dest_path.parent() {
@@ -530,10 +817,10 @@ pub fn process_js(config: &Config) -> Result<(), String> {
         }
 
         // Read the file content
-        let content = std::fs::read_to_string(js_file).map_err(|e| e.to_string())?;
+        let content = std::fs::read_to_string(src_js_dir.join(rel_path)).map_err(|e| e.to_string())?;
 
-        // Process based on environment
-        if is_production {
+        // Process based on environment; verbatim and root files are always copied as they are
+        if minify && matches!(target, JsTarget::App { verbatim: false }) {
             crate::logger::debug(&format!("Copying {} to {}", rel_path.display(), min_dest_path.display())).map_err(|e| e.to_string())?;
             // TODO: Implement actual JS minification in the future
             std::fs::write(&min_dest_path, &content).map_err(|e| e.to_string())?;
@@ -541,23 +828,29 @@ pub fn process_js(config: &Config) -> Result<(), String> {
             crate::logger::debug(&format!("Copying {} to {}", rel_path.display(), min_dest_path.display())).map_err(|e| e.to_string())?;
             std::fs::write(&min_dest_path, &content).map_err(|e| e.to_string())?;
         }
+        summary.push((rel_path.display().to_string(), output, content.len() as u64));
     }
 
     // Success message
-    let mode = if is_production { "production" } else { "development" };
-    crate::logger::success(&format!("Processed {} JS files in {} mode", js_files.len(), mode)).map_err(|e| e.to_string())?;
+    let skipped = if excluded > 0 { format!(", {} excluded by [assets.js]", excluded) } else { String::new() };
+    crate::logger::success(&format!("Processed {} JS files for {}{}", js_files.len(), config.environment, skipped)).map_err(|e| e.to_string())?;
+    print_outputs(&summary)?;
 
     Ok(())
 }
 
 // Publish CSS files from src/assets/css to public/css with environment-based minification
 pub fn publish_css(config: &Config) -> Result<(), String> {
-    let is_production = config.environment == "prod" || config.environment == "production";
+    with_hooks(config, "css", || publish_css_files(config))
+}
+
+fn publish_css_files(config: &Config) -> Result<(), String> {
+    let minify = config.minify_assets();
     let project_dir = &config.project_dir;
 
     // Source and destination directories
     let src_css_dir = project_dir.join("src").join("assets").join("css");
-    let public_dir = get_public_dir(config);
+    let public_dir = config.public_dir();
     let dest_css_dir = project_dir.join(&public_dir).join("css");
 
     // Create directories
@@ -571,11 +864,11 @@ pub fn publish_css(config: &Config) -> Result<(), String> {
         return Ok(());
     }
 
-    crate::logger::info(&format!("Publishing CSS files ({} mode)...", 
-        if is_production { "production" } else { "development" })).map_err(|e| e.to_string())?;
+    crate::logger::info(&format!("Publishing CSS files ({})...", config.environment)).map_err(|e| e.to_string())?;
 
     // Get all CSS files in the source directory (excluding already minified ones)
     let css_files = WalkDir::new(&src_css_dir)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| {
@@ -590,13 +883,13 @@ pub fn publish_css(config: &Config) -> Result<(), String> {
         return Ok(());
     }
 
-    // Process each CSS file
+    // Process each CSS file, mirroring its directory under css/ into css/app/
+    let mut summary = Vec::new();
     for entry in &css_files {
         let src_path = entry.path();
         let rel_path = src_path.strip_prefix(&src_css_dir).unwrap();
-        let min_dest_path = dest_css_dir.join("app").join(
-            rel_path.with_file_name(format!("{}.min.css", rel_path.file_stem().unwrap().to_str().unwrap()))
-        );
+        let output_name = mirrored_output(rel_path, "css");
+        let min_dest_path = dest_css_dir.join("app").join(&output_name);
 
         // Create parent directory if needed
         if let Some(parent) = min_dest_path.parent() {
@@ -608,7 +901,7 @@ pub fn publish_css(config: &Config) -> Result<(), String> {
         crate::logger::debug(&format!("Processing {}", rel_path.display())).map_err(|e| e.to_string())?;
 
         // Process based on environment
-        if is_production {
+        if minify {
             // Minify the content
             let minified = Minifier::default()
                 .minify(&content, Level::Three)
@@ -616,16 +909,72 @@ pub fn publish_css(config: &Config) -> Result<(), String> {
 
             // Write the minified content
             std::fs::write(&min_dest_path, &minified).map_err(|e| e.to_string())?;
+            summary.push((rel_path.display().to_string(), format!("{}/css/app/{}", public_dir, output_name), minified.len() as u64));
         } else {
             // In development mode, write expanded content but still use .min.css extension
             std::fs::write(&min_dest_path, &content).map_err(|e| e.to_string())?;
+            summary.push((rel_path.display().to_string(), format!("{}/css/app/{}", public_dir, output_name), content.len() as u64));
         }
     }
 
     // Success message
-    let mode_msg = if is_production { "minified for production" } else { "expanded for development" };
+    let mode_msg = if minify { format!("minified for {}", config.environment) } else { format!("expanded for {}", config.environment) };
     crate::logger::success(&format!("Published {} CSS files as .min.css ({})", css_files.len(), mode_msg)).map_err(|e| e.to_string())?;
+    print_outputs(&summary)?;
 
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::JsConfig;
+
+    #[test]
+    fn js_rules_place_files_and_reject_conflicts() {
+        let patterns = |list: &[&str]| list.iter().map(|pattern| pattern.to_string()).collect();
+        let config = JsConfig { exclude: patterns(&["drafts/**", "**/*.test.js"]), copy_verbatim: patterns(&["vendor/**"]), root_files: patterns(&["sw.js"]) };
+        let rules = JsRules::new(&config).unwrap();
+
+        assert!(matches!(rules.target("drafts/a/b.js"), Ok(JsTarget::Skip)));
+        assert!(matches!(rules.target("pages/home.test.js"), Ok(JsTarget::Skip)));
+        assert!(matches!(rules.target("vendor/htmx.min.js"), Ok(JsTarget::App { verbatim: true })));
+        assert!(matches!(rules.target("sw.js"), Ok(JsTarget::Root)));
+        // `*` doesn't cross directories, so only the top-level sw.js is a root file
+        assert!(matches!(rules.target("pages/sw.js"), Ok(JsTarget::App { verbatim: false })));
+
+        let conflicting = JsConfig { exclude: patterns(&["*.js"]), root_files: patterns(&["sw.js"]), ..Default::default() };
+        let error = JsRules::new(&conflicting).unwrap().target("sw.js").unwrap_err();
+        assert!(error.contains("\"*.js\"") && error.contains("\"sw.js\""), "{}", error);
+    }
+
+    // Yields a first chunk, then fails like a dropped connection
+    struct BrokenBody(bool);
+
+    impl Read for BrokenBody {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if std::mem::replace(&mut self.0, true) {
+                return Err(io::Error::new(io::ErrorKind::ConnectionReset, "connection reset"));
+            }
+            buf[..4].copy_from_slice(b"wOF2");
+            Ok(4)
+        }
+    }
+
+    #[test]
+    fn downloads_appear_only_once_complete() {
+        let dir = std::env::temp_dir().join(format!("blast-download-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let font = dir.join("icons.woff2");
+
+        assert!(save_download(&mut BrokenBody(false), &font).is_err());
+        assert!(!font.exists());
+        assert!(!dir.join("icons.woff2.part").exists());
+
+        save_download(&mut io::Cursor::new(b"wOF2 complete".to_vec()), &font).unwrap();
+        assert_eq!(std::fs::read(&font).unwrap(), b"wOF2 complete");
+        assert!(!dir.join("icons.woff2.part").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
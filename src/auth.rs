@@ -0,0 +1,411 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::configs::Config;
+use crate::envfile;
+use crate::logger;
+use crate::models::TableInfo;
+use crate::sparks::Dependency;
+
+// Crates the generated controller uses
+const DEPENDENCIES: &[(&str, &str)] = &[("argon2", "0.5"), ("jsonwebtoken", "9"), ("rand", "0.8"), ("sha2", "0.10")];
+
+// `blast gen auth`: register/login/refresh controller on top of the users table, a refresh_tokens table,
+// JWT env placeholders and the crates the controller needs
+pub fn generate(config: &Config, force: bool) -> Result<(), String> {
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
+    let schema = crate::file_writer::read(config.project_dir.join(schema_path)).map_err(|e| format!("Failed to read {}: {}. Run `blast schema` first", schema_path, e))?;
+    let tables = crate::models::parse_schema_tables(&schema);
+    let users = tables.iter().find(|table| table.name == "users");
+    let refresh_tokens = tables.iter().find(|table| table.name == "refresh_tokens");
+
+    // Refuse before touching anything when the existing tables can't back the controller
+    let id_type = match users {
+        Some(users) => check_users(users)?,
+        None => "i32",
+    };
+    if let Some(refresh_tokens) = refresh_tokens {
+        check_refresh_tokens(refresh_tokens)?;
+    }
+
+    let routes_dir = config.project_dir.join("src").join("routes");
+    let (path, module): (PathBuf, &str) = if routes_dir.is_dir() {
+        (routes_dir.join("auth.rs"), "crate::routes::auth")
+    } else {
+        (config.project_dir.join("src").join("auth.rs"), "crate::auth")
+    };
+    if path.exists() && !force {
+        return Err(format!("{} already exists. Re-run with --force to regenerate it", path.display()));
+    }
+
+    let needs_migration = users.is_none() || refresh_tokens.is_none();
+    if needs_migration {
+        let (up_sql, down_sql) = migration_sql(users.is_none(), refresh_tokens.is_none(), id_type);
+        let (up_file, _) = crate::database::create_migration_files(config, "create_auth_tables", &up_sql, &down_sql, None)?;
+        logger::success(&format!("Created migration {}", up_file))?;
+    }
+
+    fs::write(&path, controller_source(id_type)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    logger::success(&format!("Generated {}", path.strip_prefix(&config.project_dir).unwrap_or(&path).display()))?;
+
+    // New projects get a JWT_SECRET from `blast new`; it becomes the first key of the list
+    let existing_secret = envfile::EnvFile::load(&envfile::env_path(config))?.get("JWT_SECRET").filter(|secret| !secret.is_empty());
+    let entries: Vec<(String, String)> = [
+        ("JWT_SECRETS", existing_secret.as_deref().unwrap_or(envfile::PLACEHOLDER)),
+        ("JWT_ISSUER", ""),
+        ("JWT_AUDIENCE", ""),
+        ("JWT_ACCESS_TTL_SECS", "900"),
+        ("JWT_REFRESH_TTL_DAYS", "30"),
+    ]
+    .iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    let added = envfile::append_missing(config, "Auth (blast gen auth). JWT_SECRETS is a comma-separated list, newest key first", &entries)?;
+    if !added.is_empty() {
+        logger::success(&format!("Added {} to .env", added.join(", ")))?;
+    }
+
+    let dependencies: Vec<Dependency> = DEPENDENCIES.iter().map(|(name, version)| Dependency { crate_name: name.to_string(), version: Some(version.to_string()), features: Vec::new() }).collect();
+    crate::sparks::update_cargo_toml(config, &dependencies, "blast gen auth")?;
+
+    logger::plain("Register it in your Rocket setup:")?;
+    logger::plain("  - declare the module (`pub mod auth;`)")?;
+    logger::plain(&format!("  - mount it: `.mount(\"/\", routes![{0}::register, {0}::login, {0}::refresh, {0}::me])`", module))?;
+    logger::plain(&format!("  - guard your own routes with `{}::AuthUser`", module))?;
+    if needs_migration {
+        logger::plain("  - run `blast migrate` to create the tables and regenerate schema, structs and models")?;
+    }
+    if existing_secret.is_none() {
+        logger::plain("  - set JWT_SECRETS in .env")?;
+    }
+    Ok(())
+}
+
+// The generated auth controller's module, when there is one
+pub fn module(config: &Config) -> Option<&'static str> {
+    [("src/routes/auth.rs", "crate::routes::auth"), ("src/auth.rs", "crate::auth")].into_iter().find_map(|(path, module)| {
+        let source = fs::read_to_string(config.project_dir.join(path)).ok()?;
+        source.starts_with("// Generated by `blast gen auth`").then_some(module)
+    })
+}
+
+// The controller reads id, email and password_hash through the generated Users struct
+fn check_users(users: &TableInfo) -> Result<&'static str, String> {
+    let mut problems = Vec::new();
+
+    let id_type = match users.columns.iter().find(|column| column.name == "id") {
+        Some(column) if column.column_type == "Int4" && !column.nullable => Some("i32"),
+        Some(column) if column.column_type == "Int8" && !column.nullable => Some("i64"),
+        Some(column) => {
+            problems.push(format!("id is {}, expected a NOT NULL Int4 or Int8", describe(column.column_type.as_str(), column.nullable)));
+            None
+        }
+        None => {
+            problems.push("id is missing (integer primary key)".to_string());
+            None
+        }
+    };
+    if users.primary_key != ["id"] {
+        problems.push(format!("the primary key is ({}), expected (id)", users.primary_key.join(", ")));
+    }
+
+    for name in ["email", "password_hash"] {
+        match users.columns.iter().find(|column| column.name == name) {
+            Some(column) if is_text(&column.column_type) && !column.nullable => {}
+            Some(column) => problems.push(format!("{} is {}, expected NOT NULL Text or Varchar", name, describe(column.column_type.as_str(), column.nullable))),
+            None => problems.push(format!("{} is missing (NOT NULL Text)", name)),
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(format!(
+            "The users table can't back `blast gen auth`:\n  - {}\nAdd a migration that fixes these columns, run `blast migrate`, then try again",
+            problems.join("\n  - ")
+        ));
+    }
+
+    // Registration only sets email and password_hash
+    let others: Vec<&str> = users.columns.iter().filter(|column| !column.nullable && !["id", "email", "password_hash"].contains(&column.name.as_str())).map(|column| column.name.as_str()).collect();
+    if !others.is_empty() {
+        logger::warning(&format!("Registration only sets email and password_hash. These NOT NULL users columns need database defaults: {}", others.join(", ")))?;
+    }
+
+    Ok(id_type.unwrap_or("i32"))
+}
+
+// Column name, type check, nullable, and how to describe the expected type
+type ColumnCheck = (&'static str, fn(&str) -> bool, bool, &'static str);
+
+fn check_refresh_tokens(refresh_tokens: &TableInfo) -> Result<(), String> {
+    let expected: [ColumnCheck; 4] = [
+        ("user_id", |kind| kind == "Int4" || kind == "Int8", false, "NOT NULL integer"),
+        ("token_hash", is_text, false, "NOT NULL Text"),
+        ("expires_at", |kind| kind == "Timestamp", false, "NOT NULL Timestamp"),
+        ("revoked_at", |kind| kind == "Timestamp", true, "Nullable<Timestamp>"),
+    ];
+
+    let problems: Vec<String> = expected
+        .iter()
+        .filter_map(|(name, type_ok, nullable, wanted)| match refresh_tokens.columns.iter().find(|column| column.name == *name) {
+            Some(column) if type_ok(&column.column_type) && column.nullable == *nullable => None,
+            Some(column) => Some(format!("{} is {}, expected {}", name, describe(column.column_type.as_str(), column.nullable), wanted)),
+            None => Some(format!("{} is missing ({})", name, wanted)),
+        })
+        .collect();
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("The existing refresh_tokens table can't store refresh tokens:\n  - {}\nRename or fix it, then try again", problems.join("\n  - ")))
+    }
+}
+
+pub fn is_text(kind: &str) -> bool {
+    kind == "Text" || kind == "Varchar"
+}
+
+pub fn describe(kind: &str, nullable: bool) -> String {
+    if nullable {
+        format!("Nullable<{}>", kind)
+    } else {
+        kind.to_string()
+    }
+}
+
+fn migration_sql(create_users: bool, create_refresh_tokens: bool, id_type: &str) -> (String, String) {
+    let mut up = String::new();
+    let mut down = String::new();
+
+    if create_users {
+        up.push_str("CREATE TABLE users (\n    id SERIAL PRIMARY KEY,\n    email VARCHAR(255) NOT NULL UNIQUE,\n    password_hash TEXT NOT NULL,\n    created_at TIMESTAMP NOT NULL DEFAULT NOW()\n);\n\n");
+    }
+    if create_refresh_tokens {
+        let user_id_type = if id_type == "i64" { "BIGINT" } else { "INTEGER" };
+        up.push_str(&format!(
+            "-- Only a SHA-256 hash of each refresh token is stored; refreshing revokes the token it used\nCREATE TABLE refresh_tokens (\n    id SERIAL PRIMARY KEY,\n    user_id {} NOT NULL REFERENCES users(id) ON DELETE CASCADE,\n    token_hash TEXT NOT NULL UNIQUE,\n    expires_at TIMESTAMP NOT NULL,\n    revoked_at TIMESTAMP,\n    created_at TIMESTAMP NOT NULL DEFAULT NOW()\n);\n\nCREATE INDEX refresh_tokens_user_id_idx ON refresh_tokens (user_id);\n",
+            user_id_type
+        ));
+        down.push_str("DROP TABLE IF EXISTS refresh_tokens;\n");
+    }
+    if create_users {
+        down.push_str("DROP TABLE IF EXISTS users;\n");
+    }
+
+    (up, down)
+}
+
+fn controller_source(id_type: &str) -> String {
+    format!(
+        r#"// Generated by `blast gen auth`. Register, login and refresh against the users table.
+// Access tokens are HS256 JWTs signed with the first key in JWT_SECRETS, and every key in the list is accepted.
+// To rotate, put the new key first and drop the old one once its tokens have expired (JWT_ACCESS_TTL_SECS).
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{{PasswordHash, PasswordHasher, PasswordVerifier, SaltString}};
+use argon2::Argon2;
+use diesel::prelude::*;
+use jsonwebtoken::{{Algorithm, DecodingKey, EncodingKey, Header, Validation}};
+use rand::RngCore;
+use rocket::http::Status;
+use rocket::request::{{FromRequest, Outcome, Request}};
+use rocket::serde::json::{{json, Json, Value}};
+use serde::{{Deserialize, Serialize}};
+use sha2::{{Digest, Sha256}};
+
+use crate::database::db::establish_connection;
+use crate::database::schema::{{refresh_tokens, users}};
+use crate::structs::Users;
+
+#[derive(Deserialize)]
+pub struct Credentials {{
+    pub email: String,
+    pub password: String,
+}}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {{
+    pub refresh_token: String,
+}}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {{
+    pub sub: {id_type},
+    pub iat: i64,
+    pub exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+}}
+
+struct JwtSettings {{
+    secrets: Vec<String>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    access_ttl_secs: i64,
+    refresh_ttl_days: i64,
+}}
+
+fn settings() -> JwtSettings {{
+    let env = |key: &str| std::env::var(key).ok().map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+    let keys = |value: String| -> Vec<String> {{
+        value.split(',').map(|secret| secret.trim().to_string()).filter(|secret| !secret.is_empty() && !secret.contains("REPLACE_THIS_WITH_YOUR_VALUE")).collect()
+    }};
+    // The single JWT_SECRET of older projects still works until JWT_SECRETS is set
+    let secrets = env("JWT_SECRETS").map(keys).filter(|secrets| !secrets.is_empty()).or_else(|| env("JWT_SECRET").map(keys)).unwrap_or_default();
+    JwtSettings {{
+        secrets,
+        issuer: env("JWT_ISSUER"),
+        audience: env("JWT_AUDIENCE"),
+        access_ttl_secs: env("JWT_ACCESS_TTL_SECS").and_then(|value| value.parse().ok()).unwrap_or(900),
+        refresh_ttl_days: env("JWT_REFRESH_TTL_DAYS").and_then(|value| value.parse().ok()).unwrap_or(30),
+    }}
+}}
+
+fn hash_token(token: &str) -> String {{
+    Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{{:02x}}", byte)).collect()
+}}
+
+// A fresh access token plus a refresh token stored (hashed) in refresh_tokens
+fn issue_tokens(conn: &mut PgConnection, user_id: {id_type}) -> Result<Value, Status> {{
+    let settings = settings();
+    let secret = settings.secrets.first().ok_or(Status::InternalServerError)?;
+    let now = chrono::Utc::now();
+    let claims = Claims {{
+        sub: user_id,
+        iat: now.timestamp(),
+        exp: now.timestamp() + settings.access_ttl_secs,
+        iss: settings.issuer.clone(),
+        aud: settings.audience.clone(),
+    }};
+    let access_token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret.as_bytes())).map_err(|_| Status::InternalServerError)?;
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let refresh_token: String = bytes.iter().map(|byte| format!("{{:02x}}", byte)).collect();
+    diesel::insert_into(refresh_tokens::table)
+        .values((
+            refresh_tokens::user_id.eq(user_id),
+            refresh_tokens::token_hash.eq(hash_token(&refresh_token)),
+            refresh_tokens::expires_at.eq((now + chrono::Duration::days(settings.refresh_ttl_days)).naive_utc()),
+        ))
+        .execute(conn)
+        .map_err(|_| Status::InternalServerError)?;
+
+    Ok(json!({{
+        "access_token": access_token,
+        "token_type": "Bearer",
+        "expires_in": settings.access_ttl_secs,
+        "refresh_token": refresh_token,
+    }}))
+}}
+
+// Claims of a valid access token, checked against every key in JWT_SECRETS
+pub fn decode_access_token(token: &str) -> Option<Claims> {{
+    let settings = settings();
+    let mut validation = Validation::new(Algorithm::HS256);
+    if let Some(issuer) = &settings.issuer {{
+        validation.set_issuer(&[issuer]);
+    }}
+    match &settings.audience {{
+        Some(audience) => validation.set_audience(&[audience]),
+        None => validation.validate_aud = false,
+    }}
+
+    settings
+        .secrets
+        .iter()
+        .find_map(|secret| jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation).ok())
+        .map(|data| data.claims)
+}}
+
+#[post("/auth/register", format = "json", data = "<credentials>")]
+pub async fn register(credentials: Json<Credentials>) -> Result<(Status, Value), Status> {{
+    let email = credentials.email.trim().to_lowercase();
+    if !email.contains('@') || credentials.password.len() < 8 {{
+        return Err(Status::UnprocessableEntity);
+    }}
+
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default().hash_password(credentials.password.as_bytes(), &salt).map_err(|_| Status::InternalServerError)?.to_string();
+
+    let mut conn = establish_connection();
+    let user_id: {id_type} = diesel::insert_into(users::table)
+        .values((users::email.eq(&email), users::password_hash.eq(&password_hash)))
+        .returning(users::id)
+        .get_result(&mut conn)
+        .map_err(|e| match e {{
+            diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _) => Status::Conflict,
+            _ => Status::InternalServerError,
+        }})?;
+
+    Ok((Status::Created, issue_tokens(&mut conn, user_id)?))
+}}
+
+#[post("/auth/login", format = "json", data = "<credentials>")]
+pub async fn login(credentials: Json<Credentials>) -> Result<Value, Status> {{
+    let email = credentials.email.trim().to_lowercase();
+    let mut conn = establish_connection();
+    let user = users::table
+        .filter(users::email.eq(&email))
+        .first::<Users>(&mut conn)
+        .optional()
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::Unauthorized)?;
+
+    let parsed = PasswordHash::new(&user.password_hash).map_err(|_| Status::Unauthorized)?;
+    Argon2::default().verify_password(credentials.password.as_bytes(), &parsed).map_err(|_| Status::Unauthorized)?;
+
+    issue_tokens(&mut conn, user.id)
+}}
+
+// Trade a refresh token for a new pair. The old refresh token is revoked, so each one works once
+#[post("/auth/refresh", format = "json", data = "<request>")]
+pub async fn refresh(request: Json<RefreshRequest>) -> Result<Value, Status> {{
+    let now = chrono::Utc::now().naive_utc();
+    let mut conn = establish_connection();
+    let user_id: {id_type} = diesel::update(
+        refresh_tokens::table
+            .filter(refresh_tokens::token_hash.eq(hash_token(&request.refresh_token)))
+            .filter(refresh_tokens::revoked_at.is_null())
+            .filter(refresh_tokens::expires_at.gt(now)),
+    )
+    .set(refresh_tokens::revoked_at.eq(Some(now)))
+    .returning(refresh_tokens::user_id)
+    .get_result(&mut conn)
+    .optional()
+    .map_err(|_| Status::InternalServerError)?
+    .ok_or(Status::Unauthorized)?;
+
+    issue_tokens(&mut conn, user_id)
+}}
+
+// Request guard for routes that need a signed-in user: `Authorization: Bearer <access token>`
+pub struct AuthUser {{
+    pub id: {id_type},
+}}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthUser {{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {{
+        let token = request.headers().get_one("Authorization").and_then(|header| header.strip_prefix("Bearer "));
+        match token.and_then(decode_access_token) {{
+            Some(claims) => Outcome::Success(AuthUser {{ id: claims.sub }}),
+            None => Outcome::Error((Status::Unauthorized, ())),
+        }}
+    }}
+}}
+
+// Example guarded route
+#[get("/auth/me")]
+pub async fn me(user: AuthUser) -> Result<Value, Status> {{
+    let mut conn = establish_connection();
+    let user = users::table.find(user.id).first::<Users>(&mut conn).map_err(|_| Status::NotFound)?;
+    Ok(json!({{ "id": user.id, "email": user.email }}))
+}}
+"#,
+        id_type = id_type
+    )
+}
@@ -0,0 +1,276 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Instant, UNIX_EPOCH};
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::configs::Config;
+use crate::logger;
+
+type BlastResult = Result<(), String>;
+
+// File extensions treated as static assets when checking template references
+pub const ASSET_EXTENSIONS: &[&str] = &["css", "js", "map", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "woff", "woff2", "ttf", "eot"];
+
+// `blast build`: release binary + production assets, assembled into a deployable dist/ directory
+pub fn build(config: &Config, tarball: bool, use_cache: bool) -> BlastResult {
+    let started = Instant::now();
+
+    // Assets are always built the way production serves them
+    let mut config = config.clone();
+    if !config.is_production() {
+        logger::info(&format!("Building assets for prod instead of the active '{}' environment", config.environment))?;
+        config.environment = "prod".to_string();
+    }
+    let config = &config;
+    let build_settings = config.build();
+
+    // 1. Compile first so warnings-as-errors fail before any other work
+    cargo_build_release(config, build_settings.deny_warnings)?;
+
+    // 2. Asset pipeline, skipped when nothing under src/assets or Catalyst.toml changed since the last build
+    let public_dir = config.project_dir.join(config.public_dir());
+    if !crate::assets::has_asset_pipeline(config) {
+        logger::info("No [assets] section in Catalyst.toml, skipping the asset pipeline")?;
+    } else {
+        let fingerprint = asset_fingerprint(config);
+        let cache_path = config.project_dir.join("storage").join("blast").join("build_cache");
+        let cached = fs::read_to_string(&cache_path).map(|previous| previous.trim() == fingerprint).unwrap_or(false);
+
+        if use_cache && cached && public_dir.exists() {
+            logger::success("Assets unchanged since the last build, reusing them")?;
+        } else {
+            run_asset_pipeline(config)?;
+            if let Some(parent) = cache_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(&cache_path, &fingerprint).map_err(|e| e.to_string())?;
+        }
+    }
+
+    // 3. Templates must parse and point at assets and routes that exist, or they only fail at runtime
+    crate::templates::validate(config)?;
+
+    // 4. Assemble dist/
+    let dist_dir = config.project_dir.join(&build_settings.dist_dir);
+    let binary_size = assemble_dist(config, &dist_dir, &public_dir)?;
+
+    // 5. Every asset a template points at must ship in the bundle
+    verify_template_assets(config, &dist_dir)?;
+
+    if tarball {
+        let tarball_path = create_tarball(config, &dist_dir)?;
+        logger::success(&format!("Created {}", tarball_path.display()))?;
+    }
+
+    logger::success(&format!("Build complete: {} ({}) in {:.1}s", dist_dir.display(), format_size(binary_size), started.elapsed().as_secs_f64()))
+}
+
+fn cargo_build_release(config: &Config, deny_warnings: bool) -> BlastResult {
+    logger::info("Running cargo build --release...")?;
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["build", "--release", "--bin", &config.project_name]).current_dir(&config.project_dir);
+
+    if deny_warnings {
+        cmd.env("RUSTFLAGS", rustflags(env::var("RUSTFLAGS").ok(), "-D warnings"));
+    } else if !config.show_compiler_warnings {
+        cmd.env("RUSTFLAGS", rustflags(env::var("RUSTFLAGS").ok(), "-Awarnings")).arg("--quiet");
+    }
+
+    let status = logger::command_status("cargo", &mut cmd).map_err(|e| format!("Failed to run cargo: {}", e))?;
+    if !status.success() {
+        let hint = if deny_warnings { " ([build] deny_warnings is on, so warnings fail the build)" } else { "" };
+        return Err(format!("cargo build --release failed{}", hint));
+    }
+
+    Ok(())
+}
+
+// Adds to the RUSTFLAGS the user already exported (target-cpu, linker flags) instead of replacing them
+fn rustflags(existing: Option<String>, extra: &str) -> String {
+    match existing.as_deref().map(str::trim) {
+        Some(flags) if !flags.is_empty() => format!("{} {}", flags, extra),
+        _ => extra.to_string(),
+    }
+}
+
+fn run_asset_pipeline(config: &Config) -> BlastResult {
+    crate::assets::download_assets(config)?;
+    crate::assets::transpile_all_scss(config)?;
+    crate::assets::publish_css(config)?;
+    crate::assets::process_js(config)?;
+    crate::optimize::optimize_assets(config)
+}
+
+// Paths, sizes and modification times of the asset sources; any edit changes the fingerprint
+fn asset_fingerprint(config: &Config) -> String {
+    let mut hasher = DefaultHasher::new();
+    config.environment.hash(&mut hasher);
+
+    let catalyst = config.project_dir.join("Catalyst.toml");
+    let sources = WalkDir::new(config.project_dir.join("src").join("assets"))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path());
+
+    for path in std::iter::once(catalyst).chain(sources) {
+        if let Ok(metadata) = fs::metadata(&path) {
+            path.hash(&mut hasher);
+            metadata.len().hash(&mut hasher);
+            if let Ok(modified) = metadata.modified().map(|time| time.duration_since(UNIX_EPOCH).unwrap_or_default()) {
+                modified.as_nanos().hash(&mut hasher);
+            }
+        }
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+// Copy the binary, public/, templates/ and Rocket.toml into dist/ and write the run script. Returns the binary size
+fn assemble_dist(config: &Config, dist_dir: &Path, public_dir: &Path) -> Result<u64, String> {
+    if dist_dir.exists() {
+        fs::remove_dir_all(dist_dir).map_err(|e| format!("Failed to clear {}: {}", dist_dir.display(), e))?;
+    }
+    fs::create_dir_all(dist_dir).map_err(|e| e.to_string())?;
+
+    let binary_name = format!("{}{}", config.project_name, std::env::consts::EXE_SUFFIX);
+    let binary = config.project_dir.join("target").join("release").join(&binary_name);
+    if !binary.exists() {
+        return Err(format!("Release binary not found at {}", binary.display()));
+    }
+    fs::copy(&binary, dist_dir.join(&binary_name)).map_err(|e| format!("Failed to copy binary: {}", e))?;
+
+    if public_dir.exists() {
+        let public_name = public_dir.file_name().map(|name| name.to_os_string()).unwrap_or_else(|| "public".into());
+        crate::sparks::copy_dir_all(public_dir, dist_dir.join(public_name)).map_err(|e| format!("Failed to copy {}: {}", public_dir.display(), e))?;
+    }
+
+    let templates_dir = config.project_dir.join("templates");
+    if templates_dir.exists() {
+        crate::sparks::copy_dir_all(&templates_dir, dist_dir.join("templates")).map_err(|e| format!("Failed to copy templates: {}", e))?;
+    }
+
+    let rocket_toml = config.project_dir.join("Rocket.toml");
+    if rocket_toml.exists() {
+        fs::copy(&rocket_toml, dist_dir.join("Rocket.toml")).map_err(|e| format!("Failed to copy Rocket.toml: {}", e))?;
+    }
+
+    write_run_script(config, dist_dir)?;
+
+    fs::metadata(&binary).map(|metadata| metadata.len()).map_err(|e| e.to_string())
+}
+
+// run.sh (run.cmd on Windows) starts the binary from the bundle directory; address/port default to [server] but can be overridden
+fn write_run_script(config: &Config, dist_dir: &Path) -> BlastResult {
+    let server = config.server(None);
+    // The public directory sits at the top of the bundle, whatever its path in the project
+    let public_name = Path::new(&config.public_dir()).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "public".to_string());
+    if cfg!(windows) {
+        let script = format!(
+            "@echo off\r\nrem Generated by blast build\r\ncd /d \"%~dp0\"\r\nif not defined ROCKET_PROFILE set \"ROCKET_PROFILE=release\"\r\nif not defined ROCKET_ADDRESS set \"ROCKET_ADDRESS={}\"\r\nif not defined ROCKET_PORT set \"ROCKET_PORT={}\"\r\nif not defined ROCKET_PUBLIC_DIR set \"ROCKET_PUBLIC_DIR={}\"\r\nif not defined CATALYST_ENV set \"CATALYST_ENV={}\"\r\n\"%~dp0{}.exe\" %*\r\n",
+            server.address, server.port, public_name, config.environment, config.project_name
+        );
+        return fs::write(dist_dir.join("run.cmd"), script).map_err(|e| e.to_string());
+    }
+
+    let script = format!(
+        "#!/bin/sh\n# Generated by blast build\ncd \"$(dirname \"$0\")\" || exit 1\nexport ROCKET_PROFILE=\"${{ROCKET_PROFILE:-release}}\"\nexport ROCKET_ADDRESS=\"${{ROCKET_ADDRESS:-{}}}\"\nexport ROCKET_PORT=\"${{ROCKET_PORT:-{}}}\"\nexport ROCKET_PUBLIC_DIR=\"${{ROCKET_PUBLIC_DIR:-{}}}\"\nexport CATALYST_ENV=\"${{CATALYST_ENV:-{}}}\"\nexec ./{} \"$@\"\n",
+        server.address, server.port, public_name, config.environment, config.project_name
+    );
+
+    let script_path = dist_dir.join("run.sh");
+    fs::write(&script_path, script).map_err(|e| e.to_string())?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+// Check that every local asset referenced from a template (href="/css/app.min.css", src="/js/...") exists in the bundle
+fn verify_template_assets(config: &Config, dist_dir: &Path) -> BlastResult {
+    let templates_dir = config.project_dir.join("templates");
+    if !templates_dir.exists() {
+        return Ok(());
+    }
+
+    let public_name = Path::new(&config.public_dir()).file_name().map(|name| name.to_os_string()).unwrap_or_else(|| "public".into());
+    let bundled_public = dist_dir.join(public_name);
+    let reference = Regex::new(r#"(?:href|src)\s*=\s*["']/([^"'?#{}\s]+)"#).map_err(|e| e.to_string())?;
+
+    let mut missing = BTreeSet::new();
+    for entry in WalkDir::new(&templates_dir).into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file()) {
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+
+        for capture in reference.captures_iter(&content) {
+            let asset = &capture[1];
+            let is_asset = Path::new(asset)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ASSET_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false);
+
+            // Rocket serves public/ at the site root; some projects keep the prefix in their URLs
+            let bundled = bundled_public.join(asset).exists() || bundled_public.join(asset.split_once('/').map(|(_, rest)| rest).unwrap_or(asset)).exists();
+            if is_asset && !bundled {
+                let template = entry.path().strip_prefix(&config.project_dir).unwrap_or(entry.path()).to_path_buf();
+                missing.insert(format!("/{} (referenced in {})", asset, template.display()));
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    for asset in &missing {
+        logger::error(&format!("Missing asset: {}", asset))?;
+    }
+    Err(format!("{} asset(s) referenced by templates are not in the bundle", missing.len()))
+}
+
+// Written with the tar crate rather than a tar binary, which Windows machines may not have
+fn create_tarball(config: &Config, dist_dir: &Path) -> Result<PathBuf, String> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let tarball_path = config.project_dir.join(format!("{}-{}.tar.gz", config.project_name, timestamp));
+
+    let file = fs::File::create(&tarball_path).map_err(|e| format!("Failed to create {}: {}", tarball_path.display(), e))?;
+    let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(file, flate2::Compression::default()));
+    archive.append_dir_all(".", dist_dir).map_err(|e| format!("Failed to archive {}: {}", dist_dir.display(), e))?;
+    archive.into_inner().and_then(|encoder| encoder.finish()).map_err(|e| format!("Failed to write {}: {}", tarball_path.display(), e))?;
+
+    Ok(tarball_path)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rustflags;
+
+    #[test]
+    fn rustflags_keep_what_the_user_exported() {
+        assert_eq!(rustflags(Some("-C target-cpu=native".to_string()), "-D warnings"), "-C target-cpu=native -D warnings");
+        assert_eq!(rustflags(Some("  ".to_string()), "-Awarnings"), "-Awarnings");
+        assert_eq!(rustflags(None, "-Awarnings"), "-Awarnings");
+    }
+}
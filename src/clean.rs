@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use console::style;
+use walkdir::WalkDir;
+
+use crate::codegen_manifest::CodegenManifest;
+use crate::configs::Config;
+use crate::logger;
+
+// Flags of `blast clean`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanOptions {
+    // Generated struct and model files that are unchanged since blast wrote them (--codegen)
+    pub codegen: bool,
+    // Compiled CSS/JS in public_dir and the downloaded fonts (--assets)
+    pub assets: bool,
+    // The Materialize sources and the JS bundles `blast cdn` fetched (--downloads)
+    pub downloads: bool,
+    // storage/logs and blast's own log (--logs)
+    pub logs: bool,
+    // Delete instead of listing (--yes)
+    pub yes: bool,
+}
+
+// Downloaded into src/assets by `blast cdn`; every other path there is a source file and never cleaned
+const DOWNLOADED_SOURCES: &[&str] = &["src/assets/materialize", "src/assets/.materialize-download"];
+
+struct Category {
+    name: &'static str,
+    // Project-relative paths, files or whole directories
+    paths: Vec<String>,
+    // Files left in place, with the reason
+    kept: Vec<(String, String)>,
+}
+
+// Bytes under a path, without following symlinks
+fn size_of(path: &Path) -> u64 {
+    WalkDir::new(path).follow_root_links(false).into_iter().filter_map(Result::ok).filter_map(|entry| entry.path().symlink_metadata().ok()).filter(|metadata| metadata.is_file()).map(|metadata| metadata.len()).sum()
+}
+
+// The path is deleted only if it resolves inside the project and isn't a source file in src/assets
+fn check_target(project_dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let root = project_dir.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", project_dir.display(), e))?;
+    let path = project_dir.join(relative);
+    // A symlink is removed itself, so it is its parent that has to be inside the project
+    let parent = path.parent().and_then(|parent| parent.canonicalize().ok()).ok_or_else(|| format!("{} has no parent directory", relative))?;
+    let resolved = parent.join(path.file_name().unwrap_or_default());
+    if !resolved.starts_with(&root) || resolved == root {
+        return Err(format!("{} is outside the project directory; not touching it", relative));
+    }
+    let assets = root.join("src").join("assets");
+    if resolved.starts_with(&assets) && !DOWNLOADED_SOURCES.iter().any(|source| resolved.starts_with(root.join(source))) {
+        return Err(format!("{} is a source file in src/assets; not touching it", relative));
+    }
+    Ok(path)
+}
+
+fn codegen(config: &Config) -> Category {
+    let mut category = Category { name: "Codegen", paths: Vec::new(), kept: Vec::new() };
+    for (path, unchanged) in CodegenManifest::load(&config.project_dir).files_on_disk() {
+        if unchanged {
+            category.paths.push(path);
+        } else {
+            category.kept.push((path, "edited since blast generated it".to_string()));
+        }
+    }
+    // With the generated files gone, the next `blast gen` has to compare against nothing and write every table
+    for cache in [".blast/schema-snapshot.toml", ".blast/failed-codegen"] {
+        if config.project_dir.join(cache).exists() {
+            category.paths.push(cache.to_string());
+        }
+    }
+    category
+}
+
+fn assets(config: &Config) -> Category {
+    let public_dir = config.public_dir();
+    let mut paths: Vec<String> = ["css/app", "js/app", "fonts/fontawesome", "fonts/material-icons"].iter().map(|dir| format!("{}/{}", public_dir, dir)).collect();
+    // Only the SCSS outputs blast recorded writing; anything else in css/ may be the user's
+    for output in crate::assets::recorded_scss_outputs(config) {
+        for suffix in ["", ".gz", ".br"] {
+            paths.push(format!("{}/css/{}{}", public_dir, output, suffix));
+        }
+    }
+    paths.extend([".blast/scss-outputs".to_string(), ".blast/assets-cache".to_string()]);
+    Category { name: "Assets", paths, kept: Vec::new() }
+}
+
+fn downloads(config: &Config) -> Category {
+    let public_dir = config.public_dir();
+    let mut paths: Vec<String> = DOWNLOADED_SOURCES.iter().map(|source| source.to_string()).collect();
+    paths.extend(["js/htmx", "js/materialize"].iter().map(|dir| format!("{}/{}", public_dir, dir)));
+    Category { name: "Downloads", paths, kept: Vec::new() }
+}
+
+fn logs(config: &Config) -> Category {
+    // The files only: tool runs are recorded only while storage/logs exists
+    let logs_dir = config.project_dir.join("storage/logs");
+    let mut paths: Vec<String> = fs::read_dir(&logs_dir)
+        .map(|entries| entries.flatten().filter(|entry| entry.path().is_file()).map(|entry| format!("storage/logs/{}", entry.file_name().to_string_lossy())).collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths.push("storage/blast/blast.log".to_string());
+    Category { name: "Logs", paths, kept: Vec::new() }
+}
+
+// `blast clean`: list what the chosen categories would delete, and delete it with --yes
+pub fn run(config: &Config, options: &CleanOptions) -> Result<(), String> {
+    let mut categories = Vec::new();
+    if options.codegen {
+        categories.push(codegen(config));
+    }
+    if options.assets {
+        categories.push(assets(config));
+    }
+    if options.downloads {
+        categories.push(downloads(config));
+    }
+    if options.logs {
+        categories.push(logs(config));
+    }
+    if categories.is_empty() {
+        return Err("Choose what to clean: --codegen, --assets, --downloads, --logs or --all".to_string());
+    }
+
+    let mut manifest = CodegenManifest::load(&config.project_dir);
+    let mut total = 0;
+    for category in &categories {
+        let mut bytes = 0;
+        let mut removed = 0;
+        let mut lines = Vec::new();
+        for relative in &category.paths {
+            let path = config.project_dir.join(relative);
+            if path.symlink_metadata().is_err() {
+                continue;
+            }
+            let target = match check_target(&config.project_dir, relative) {
+                Ok(target) => target,
+                Err(e) => {
+                    logger::warning(&e)?;
+                    continue;
+                }
+            };
+            let size = size_of(&target);
+            let is_dir = target.symlink_metadata().map(|metadata| metadata.is_dir()).unwrap_or(false);
+            if options.yes {
+                let result = if is_dir { fs::remove_dir_all(&target) } else { fs::remove_file(&target) };
+                result.map_err(|e| format!("Failed to remove {}: {}", relative, e))?;
+                manifest.forget(relative);
+            }
+            lines.push(format!("  {}{} ({})", relative, if is_dir { "/" } else { "" }, crate::build::format_size(size)));
+            bytes += size;
+            removed += 1;
+        }
+
+        let verb = if options.yes { "removed" } else { "would remove" };
+        logger::plain(&format!("{}: {} {} path(s), {}", style(category.name).bold(), verb, removed, crate::build::format_size(bytes)))?;
+        for line in &lines {
+            logger::plain(line)?;
+        }
+        for (path, reason) in &category.kept {
+            logger::warning(&format!("Keeping {} ({})", path, reason))?;
+        }
+        total += bytes;
+    }
+
+    if !options.yes {
+        return logger::plain(&format!("Dry run, nothing was deleted. Run again with --yes to reclaim {}", crate::build::format_size(total)));
+    }
+    if options.codegen {
+        manifest.save()?;
+        logger::plain("Run `blast gen structs --all` and `blast gen models --all` to generate the code again")?;
+    }
+    logger::success(&format!("Reclaimed {}", crate::build::format_size(total)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_stay_inside_the_project_and_out_of_asset_sources() {
+        let project = std::env::temp_dir().join(format!("blast-clean-{}", std::process::id()));
+        fs::create_dir_all(project.join("src/assets/materialize")).unwrap();
+        fs::create_dir_all(project.join("public/css")).unwrap();
+
+        assert!(check_target(&project, "public/css/app").is_ok());
+        assert!(check_target(&project, "src/assets/materialize").is_ok());
+        assert!(check_target(&project, "src/assets/sass").is_err());
+        assert!(check_target(&project, "../elsewhere").is_err());
+        assert!(check_target(&project, "public/../..").is_err());
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Generated code that doesn't parse is kept here, under its project path, instead of being written into src/
+const FAILED_DIR: &str = ".blast/failed-codegen";
+
+// Header of every generated Rust file. It has no timestamp, so regenerating unchanged code changes nothing
+fn header(schema_file: &str, table: Option<&str>) -> String {
+    let source = match table {
+        Some(table) => format!("table `{}` in {}", table, schema_file),
+        None => schema_file.to_string(),
+    };
+    format!(
+        "// @generated by blast {} from {}.\n// Do not edit: blast regenerates this file, and skips it with a warning once edited until you pass --force-overwrite.\n\n",
+        env!("CARGO_PKG_VERSION"),
+        source
+    )
+}
+
+// Edition for rustfmt, from the project's Cargo.toml
+fn edition(project_dir: &Path) -> String {
+    fs::read_to_string(project_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Value>().ok())
+        .and_then(|manifest| manifest.get("package")?.get("edition")?.as_str().map(String::from))
+        .unwrap_or_else(|| "2021".to_string())
+}
+
+// The rustfmt binary reads the project's rustfmt.toml, so its output is what `cargo fmt` leaves alone.
+// None when rustfmt isn't installed or rejects the code
+fn rustfmt(project_dir: &Path, source: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", &edition(project_dir), "--emit", "stdout", "--quiet"])
+        .current_dir(project_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(source.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    output.status.success().then(|| String::from_utf8(output.stdout).ok()).flatten()
+}
+
+// Generated Rust source as it's written to disk: checked with syn, formatted, and headed with where it came from.
+// Formatting uses rustfmt, or prettyplease when rustfmt isn't installed (which drops `//` comments). Code that
+// doesn't parse fails generation and is dumped to .blast/failed-codegen/ for debugging
+pub fn render(project_dir: &Path, path: &str, source: &str, schema_file: &str, table: Option<&str>) -> Result<String, String> {
+    let file = match syn::parse_file(source) {
+        Ok(file) => file,
+        Err(e) => {
+            let dump = project_dir.join(FAILED_DIR).join(path.trim_start_matches("./"));
+            let saved = dump.parent().map(fs::create_dir_all).unwrap_or(Ok(())).and_then(|_| fs::write(&dump, source));
+            let start = e.span().start();
+            let location = format!("line {}, column {}", start.line, start.column + 1);
+            return Err(match saved {
+                Ok(()) => format!("Generated code for {} doesn't parse ({} at {}), so it wasn't written. The code is in {}", path, e, location, dump.display()),
+                Err(write_error) => format!("Generated code for {} doesn't parse ({} at {}), so it wasn't written. Saving it to {} failed: {}", path, e, location, dump.display(), write_error),
+            });
+        }
+    };
+
+    let formatted = rustfmt(project_dir, source).unwrap_or_else(|| prettyplease::unparse(&file));
+    Ok(format!("{}{}", header(schema_file, table), formatted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_source_is_formatted_under_a_stable_header() {
+        let source = render(Path::new("."), "src/structs/users.rs", "use crate::database::schema::users;\npub struct Users{pub id:i32,\n      pub name :String}", "src/database/schema.rs", Some("users")).unwrap();
+
+        assert!(source.starts_with(&format!("// @generated by blast {} from table `users` in src/database/schema.rs.\n", env!("CARGO_PKG_VERSION"))));
+        assert!(source.ends_with("use crate::database::schema::users;\npub struct Users {\n    pub id: i32,\n    pub name: String,\n}\n"));
+        // No timestamp: the same input renders the same file
+        assert_eq!(source, render(Path::new("."), "src/structs/users.rs", "use crate::database::schema::users;\npub struct Users{pub id:i32,\n      pub name :String}", "src/database/schema.rs", Some("users")).unwrap());
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use console::style;
+
+use crate::configs::{CodegenConfig, Config};
+use crate::logger;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Generator {
+    Structs,
+    Insertable,
+    Models,
+}
+
+impl Generator {
+    fn label(self) -> &'static str {
+        match self {
+            Generator::Structs => "structs",
+            Generator::Insertable => "insertable",
+            Generator::Models => "models",
+        }
+    }
+}
+
+// Case-insensitive glob: `*` matches any run of characters, `?` exactly one. A pattern without either is a plain name
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and how much of the name it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            star = Some((star_p, star_n + 1));
+            p = star_p + 1;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// The ignore lists a generator reads, with the key each is configured under. [codegen] ignore applies to every
+// generator and the per-section lists add to it; an ignored struct has no insertable either
+fn ignore_lists(codegen: &CodegenConfig, generator: Generator) -> Vec<(&'static str, &[String])> {
+    let mut lists = vec![("codegen.ignore", codegen.ignore.as_slice())];
+    match generator {
+        Generator::Structs => lists.push(("codegen.structs.ignore", &codegen.structs.ignore)),
+        Generator::Insertable => {
+            lists.push(("codegen.structs.ignore", &codegen.structs.ignore));
+            lists.push(("codegen.structs.insertable.ignore", &codegen.structs.insertable.ignore));
+        }
+        Generator::Models => lists.push(("codegen.models.ignore", &codegen.models.ignore)),
+    }
+    lists
+}
+
+// The first ignore entry that matches `table` for `generator`, as (key, pattern). A schema-qualified name also
+// matches on its table part
+pub fn ignored_by(codegen: &CodegenConfig, generator: Generator, table: &str) -> Option<(&'static str, String)> {
+    let unqualified = table.rsplit('.').next().unwrap_or(table);
+    ignore_lists(codegen, generator).into_iter().find_map(|(key, patterns)| {
+        patterns.iter().find(|pattern| glob_matches(pattern, table) || glob_matches(pattern, unqualified)).map(|pattern| (key, pattern.clone()))
+    })
+}
+
+// Whether `generator` skips `table`, logging the matching pattern at debug level
+pub fn skips(codegen: &CodegenConfig, generator: Generator, table: &str) -> bool {
+    match ignored_by(codegen, generator, table) {
+        Some((key, pattern)) => {
+            logger::debug(&format!("{}: skipping {} (matches '{}' in {})", generator.label(), table, pattern, key)).unwrap_or_default();
+            true
+        }
+        None => false,
+    }
+}
+
+// `blast gen --explain <table>`: why each generator did or didn't produce code for a table
+pub fn explain(config: &Config, table: &str) -> Result<(), String> {
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
+    let schema = crate::file_writer::read(config.project_dir.join(schema_path)).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+    let tables = crate::models::parse_schema_tables(&schema);
+
+    logger::plain(&style(table).bold().to_string())?;
+    let Some(info) = tables.iter().find(|info| info.name.eq_ignore_ascii_case(table)) else {
+        let reason = if codegen.schema.includes(table) {
+            "run `blast migrate` or `blast schema` if the table exists in the database"
+        } else {
+            "[codegen.schema] only_tables/except_tables leave it out"
+        };
+        logger::plain(&format!("  {} not in {}: {}", style("✗").red(), schema_path, reason))?;
+        return Ok(());
+    };
+    logger::plain(&format!("  {} in {} ({} columns)", style("✓").green(), schema_path, info.columns.len()))?;
+
+    if !codegen.schema.includes(&info.name) {
+        logger::plain(&format!("  {} every generator skips it: [codegen.schema] only_tables/except_tables leave it out", style("✗").red()))?;
+        return Ok(());
+    }
+
+    for (generator, path, command) in [
+        (Generator::Structs, format!("{}/{}.rs", codegen.structs_dir, info.name), "blast gen structs"),
+        (Generator::Insertable, format!("{}/insertable/{}.rs", codegen.structs_dir, info.name), "blast gen structs"),
+        (Generator::Models, format!("{}/{}.rs", codegen.models_dir, info.name), "blast gen models"),
+    ] {
+        let line = match ignored_by(&codegen, generator, &info.name) {
+            Some((key, pattern)) => format!("{} {}: skipped, matches '{}' in {}", style("✗").red(), generator.label(), pattern, key),
+            None if Path::new(&path).exists() => format!("{} {}: generated to {}", style("✓").green(), generator.label(), path),
+            None => format!("{} {}: not ignored, but {} doesn't exist yet; run `{}`", style("!").yellow(), generator.label(), path, command),
+        };
+        logger::plain(&format!("  {}", line))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_case_insensitively() {
+        assert!(glob_matches("users", "Users"));
+        assert!(glob_matches("spatial_*", "spatial_ref_sys"));
+        assert!(glob_matches("pg_*", "pg_stat"));
+        assert!(glob_matches("*_audit", "orders_audit"));
+        assert!(glob_matches("log_??", "log_01"));
+        assert!(glob_matches("*a*b*", "xxaxxbxx"));
+        assert!(!glob_matches("users", "users_archive"));
+        assert!(!glob_matches("pg_*", "topology"));
+        assert!(!glob_matches("log_??", "log_1"));
+    }
+
+    #[test]
+    fn section_lists_add_to_the_codegen_list() {
+        let mut codegen = CodegenConfig { ignore: vec!["spatial_*".to_string()], ..CodegenConfig::default() };
+        codegen.structs.insertable.ignore = vec!["audit_*".to_string()];
+        codegen.models.ignore = vec!["sessions".to_string()];
+
+        for generator in [Generator::Structs, Generator::Insertable, Generator::Models] {
+            assert_eq!(ignored_by(&codegen, generator, "spatial_ref_sys"), Some(("codegen.ignore", "spatial_*".to_string())));
+        }
+        assert_eq!(ignored_by(&codegen, Generator::Insertable, "audit_log"), Some(("codegen.structs.insertable.ignore", "audit_*".to_string())));
+        assert_eq!(ignored_by(&codegen, Generator::Structs, "audit_log"), None);
+        assert_eq!(ignored_by(&codegen, Generator::Models, "public.sessions"), Some(("codegen.models.ignore", "sessions".to_string())));
+        assert_eq!(ignored_by(&codegen, Generator::Structs, "sessions"), None);
+    }
+}
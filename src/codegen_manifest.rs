@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::file_writer;
+use crate::logger;
+
+// Hashes of every file `blast gen structs/models` wrote, relative to the project root
+const MANIFEST_PATH: &str = ".blast/codegen-manifest.toml";
+
+// `--force-overwrite <file|all>`, set once from main
+static FORCE_OVERWRITE: OnceLock<String> = OnceLock::new();
+
+pub fn set_force_overwrite(target: String) {
+    FORCE_OVERWRITE.get_or_init(|| target);
+}
+
+pub fn force_overwrite(path: &str) -> bool {
+    FORCE_OVERWRITE.get().map(|target| target == "all" || normalize(target) == normalize(path)).unwrap_or(false)
+}
+
+fn normalize(path: &str) -> &str {
+    path.trim_start_matches("./")
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    sha256: String,
+    // Table the file was generated for; None for shared files like insertable/validate.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    table: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct CodegenManifest {
+    #[serde(default)]
+    files: BTreeMap<String, ManifestEntry>,
+    // The project the relative paths above belong to
+    #[serde(skip)]
+    project_dir: PathBuf,
+}
+
+impl CodegenManifest {
+    pub fn load(project_dir: &Path) -> Self {
+        let manifest: Option<Self> = fs::read_to_string(project_dir.join(MANIFEST_PATH)).ok().and_then(|content| toml::from_str(&content).ok());
+        Self { project_dir: project_dir.to_path_buf(), ..manifest.unwrap_or_default() }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let content = toml::to_string(self).map_err(|e| format!("Failed to serialize the codegen manifest: {}", e))?;
+        file_writer::write_state(self.project_dir.join(MANIFEST_PATH), &content)
+    }
+
+    // Write a generated file unless it was edited since blast last wrote it. Ok(false) means it was skipped
+    pub fn write(&mut self, path: &str, content: &str, table: Option<&str>) -> Result<bool, String> {
+        let key = normalize(path).to_string();
+
+        if let (Ok(existing), Some(entry)) = (file_writer::read(self.project_dir.join(&key)), self.files.get(&key)) {
+            if content_hash(&existing) != entry.sha256 && existing != content && !force_overwrite(path) {
+                logger::warning(&format!("{} was edited since blast generated it; skipped. Use --force-overwrite {} (or all) to regenerate it", key, key))?;
+                return Ok(false);
+            }
+        }
+
+        file_writer::write(self.project_dir.join(&key), content)?;
+        self.files.insert(
+            key,
+            ManifestEntry {
+                sha256: content_hash(content),
+                table: table.map(str::to_string),
+            },
+        );
+        Ok(true)
+    }
+
+    // Remove files in `dir` generated for tables that no longer exist; callers resync mod.rs from tables_in.
+    // Files edited since generation are kept. Returns the removed tables
+    pub fn remove_stale(&mut self, dir: &str, tables: &[String]) -> Result<Vec<String>, String> {
+        let dir = normalize(dir).trim_end_matches('/');
+        let stale: Vec<(String, String)> = self
+            .files
+            .iter()
+            .filter(|(path, _)| Path::new(path).parent().and_then(|parent| parent.to_str()) == Some(dir))
+            .filter_map(|(path, entry)| entry.table.clone().filter(|table| !tables.contains(table)).map(|table| (path.clone(), table)))
+            .collect();
+
+        let mut removed = Vec::new();
+        for (path, table) in stale {
+            match file_writer::read(self.project_dir.join(&path)) {
+                Ok(existing) if Some(content_hash(&existing)) != self.files.get(&path).map(|entry| entry.sha256.clone()) => {
+                    logger::warning(&format!("Table '{}' is gone but {} was edited since blast generated it; left in place", table, path))?;
+                    continue;
+                }
+                Ok(_) => file_writer::remove(self.project_dir.join(&path))?,
+                Err(_) => {}
+            }
+            self.files.remove(&path);
+            removed.push(table);
+        }
+
+        Ok(removed)
+    }
+
+    // Every generated file still on disk, with whether it is unchanged since blast wrote it
+    pub fn files_on_disk(&self) -> Vec<(String, bool)> {
+        self.files
+            .iter()
+            .filter_map(|(path, entry)| file_writer::read(self.project_dir.join(path)).ok().map(|existing| (path.clone(), content_hash(&existing) == entry.sha256)))
+            .collect()
+    }
+
+    // Drop a file blast deleted, so the manifest doesn't vouch for it any more
+    pub fn forget(&mut self, path: &str) {
+        self.files.remove(normalize(path));
+    }
+
+    // Tables with a generated file in `dir` that is still on disk, in file name order
+    pub fn tables_in(&self, dir: &str) -> Vec<String> {
+        let dir = normalize(dir).trim_end_matches('/');
+        self.files
+            .iter()
+            .filter(|(path, _)| Path::new(path).parent().and_then(|parent| parent.to_str()) == Some(dir) && file_writer::exists(self.project_dir.join(path)))
+            .filter_map(|(_, entry)| entry.table.clone())
+            .collect()
+    }
+}
+
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
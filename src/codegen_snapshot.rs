@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::codegen_manifest::content_hash;
+use crate::configs::{CodegenConfig, Config};
+use crate::table_selection::TableSelection;
+
+// Parsed table definitions as of the last successful gen structs/models, per generator
+const SNAPSHOT_PATH: &str = ".blast/schema-snapshot.toml";
+
+// Bump when the table description below changes shape. Older snapshots are discarded,
+// which costs one full regeneration and nothing else
+const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Default, Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    #[serde(default)]
+    generators: BTreeMap<String, GeneratorSnapshot>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GeneratorSnapshot {
+    // Hash of the [codegen] settings and blast version; any change regenerates every table
+    settings: String,
+    tables: BTreeMap<String, Vec<String>>,
+}
+
+impl GeneratorSnapshot {
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables.keys().cloned().collect()
+    }
+}
+
+pub struct SchemaChanges {
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    pub removed: Vec<String>,
+}
+
+impl SchemaChanges {
+    pub fn summary(&self) -> String {
+        format!("{} changed, {} unchanged, {} removed", self.changed.len(), self.unchanged, self.removed.len())
+    }
+}
+
+// Compare the current schema with the last snapshot for `generator`. With `all` (or no usable snapshot)
+// every table counts as changed; `ignored` drops tables the generator skips, `missing` marks tables whose
+// generated file is gone. With a selection only the selected tables are planned, and tables named with
+// --only count as changed
+pub fn plan(config: &Config, generator: &str, ignored: impl Fn(&str) -> bool, all: bool, selection: &TableSelection, missing: impl Fn(&str) -> bool) -> Result<(GeneratorSnapshot, SchemaChanges), String> {
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
+    let schema = crate::schema_patch::read_schema(config, schema_path).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+
+    let mut tables = describe_tables(config, &schema);
+    tables.retain(|table, _| !ignored(table));
+
+    // auto_regen decides when codegen runs, not what it writes
+    let settings = serde_json::to_string(&CodegenConfig { auto_regen: true, ..codegen.clone() }).map_err(|e| e.to_string())?;
+    let current = GeneratorSnapshot {
+        settings: content_hash(&format!("{}\n{}", env!("CARGO_PKG_VERSION"), settings)),
+        tables,
+    };
+
+    let previous = load(&config.project_dir).generators.remove(generator).filter(|previous| !all && previous.settings == current.settings).unwrap_or_default();
+
+    let changed: Vec<String> = current
+        .tables
+        .iter()
+        .filter(|(name, _)| selection.includes(name))
+        .filter(|(name, columns)| previous.tables.get(*name) != Some(columns) || missing(name) || !selection.only.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect();
+    if !selection.is_active() {
+        let removed: Vec<String> = previous.tables.keys().filter(|name| !current.tables.contains_key(*name)).cloned().collect();
+        let unchanged = current.tables.len() - changed.len();
+        return Ok((current, SchemaChanges { changed, unchanged, removed }));
+    }
+
+    // Tables left out keep their previous entry, dropped ones included, so the next full run still picks
+    // up their changes and removes their files
+    let unchanged = current.tables.keys().filter(|name| selection.includes(name)).count() - changed.len();
+    let mut tables = previous.tables;
+    for (name, columns) in current.tables {
+        if selection.includes(&name) {
+            tables.insert(name, columns);
+        }
+    }
+    Ok((GeneratorSnapshot { settings: current.settings, tables }, SchemaChanges { changed, unchanged, removed: Vec::new() }))
+}
+
+pub fn save(config: &Config, generator: &str, snapshot: &GeneratorSnapshot) -> Result<(), String> {
+    let mut file = load(&config.project_dir);
+    file.version = SNAPSHOT_VERSION;
+    file.generators.insert(generator.to_string(), snapshot.clone());
+
+    let content = toml::to_string(&file).map_err(|e| format!("Failed to serialize the schema snapshot: {}", e))?;
+    crate::file_writer::write_state(config.project_dir.join(SNAPSHOT_PATH), &content)
+}
+
+fn load(project_dir: &Path) -> SnapshotFile {
+    fs::read_to_string(project_dir.join(SNAPSHOT_PATH))
+        .ok()
+        .and_then(|content| toml::from_str::<SnapshotFile>(&content).ok())
+        .filter(|file| file.version == SNAPSHOT_VERSION)
+        .unwrap_or_default()
+}
+
+// One line per column plus the relationships the model generator derives for the table.
+// A table's generated code depends only on these lines
+fn describe_tables(config: &Config, schema: &str) -> BTreeMap<String, Vec<String>> {
+    let parsed = crate::models::parse_schema_tables(schema);
+    let joinable_re = Regex::new(r"joinable!\s*\(\s*([A-Za-z0-9_]+)\s*->\s*([A-Za-z0-9_]+)\s*\(\s*([A-Za-z0-9_]+)\s*\)\s*\)").unwrap();
+
+    let mut tables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for table in &parsed {
+        let mut lines: Vec<String> = table
+            .columns
+            .iter()
+            .map(|column| {
+                if column.nullable {
+                    format!("{} Nullable<{}>", column.name, column.column_type)
+                } else {
+                    format!("{} {}", column.name, column.column_type)
+                }
+            })
+            .collect();
+
+        // Foreign keys inferred from <table>_id columns depend on which tables exist
+        for column in table.columns.iter().filter(|column| column.name != "id" && column.name.ends_with("_id")) {
+            let target = column.name.trim_end_matches("_id");
+            if let Some(target) = [target.to_string(), format!("{}s", target)].into_iter().find(|name| parsed.iter().any(|t| &t.name == name)) {
+                lines.push(format!("{} references {}", column.name, target));
+            }
+        }
+        tables.insert(table.name.clone(), lines);
+    }
+
+    for cap in joinable_re.captures_iter(schema) {
+        if let Some(lines) = tables.get_mut(&cap[1]) {
+            lines.push(format!("{} joins {}", &cap[3], &cap[2]));
+        }
+    }
+
+    // Keys only the migrations declare (self-references, a second key to the same table)
+    for (source, column, target) in crate::database::migration_foreign_keys(config) {
+        let line = format!("{} references {}", column, target);
+        if let (true, Some(lines)) = (tables.contains_key(&target), tables.get_mut(&source)) {
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+    }
+
+    // A model has accessors for the keys pointing at its table, so those change it too
+    let incoming: Vec<(String, String)> = tables
+        .iter()
+        .flat_map(|(source, lines)| {
+            lines.iter().filter_map(move |line| {
+                let (column, target) = line.split_once(" references ").or_else(|| line.split_once(" joins "))?;
+                Some((target.to_string(), format!("{}.{} refers here", source, column)))
+            })
+        })
+        .collect();
+    for (target, line) in incoming {
+        if let Some(lines) = tables.get_mut(&target) {
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+    }
+
+    tables
+}
@@ -1,6 +1,7 @@
 use crate::configs::Config;
 use crate::dependencies::DependencyManager;
 use crate::logger;
+use crate::table_selection::TableSelection;
 
 // Type alias for consistent error handling
 type BlastResult = Result<(), String>;
@@ -9,19 +10,41 @@ type BlastResult = Result<(), String>;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     // Project commands
-    NewProject(String, bool), // String = project name, bool = use dev branch
-    InitProject, // New command to initialize a project
+    NewProject(String, bool, Option<String>, Option<String>, Option<String>, bool, bool, bool), // project name, use dev branch, --template name, --from starter repo, --cache backend, --yes, --i18n, --no-git
+    RepairProject(String, Option<String>), // directory, --template name
+    Verify(Option<String>, bool), // --template name, --keep
+    Doctor,
+    SelfUpdate(bool), // --check
+    Report(usize, Option<String>), // --last <n>, --output <file or ->
+    Clean(crate::clean::CleanOptions),
+    InitProject(bool, bool, Vec<String>), // skip the brownfield baseline offer (--no-baseline), --resume, --only phases
 
     // Database commands
-    NewMigration,
-    Migrate,
-    Rollback,
-    Seed(Option<String>),
+    NewMigration(String), // --connection name
+    EditMigrationSpec(String), // --edit <spec>
+    MigrationFromSpec(String),
+    Migrate(String), // --connection name or "all"
+    MigrationStatus(String, Option<String>, bool), // --connection name or "all", --output <table|json>, --check
+    MigrationVerify(String, bool), // --connection name or "all", --all
+    Rollback(String), // --connection name or "all"
+    Seed(Option<String>, Option<String>), // file, --connection name
+    SeedList,
+    SeedGen(String, usize, Option<u64>), // table, --count, --deterministic <seed>
     GenerateSchema,
+    SchemaDiff(bool), // bool = write the new schema and regenerate changed tables
+    DbCreate(String), // String = connection name
+    DbDrop(String, bool, bool), // connection name, --yes, --i-know-what-im-doing
+    DbReset(String, bool, bool), // connection name, --yes, --i-know-what-im-doing
+    DbBaseline, // Baseline migration from an existing database
+    DbDump(String, bool, Vec<String>), // connection name, --data-only, --tables <a,b>
+    DbSnapshots,
+    DbRestore(String, Option<String>, bool, bool), // connection name, snapshot file, --yes, --i-know-what-im-doing
+    DbConsole(String, Option<String>), // connection name, --command
 
     // Code generation commands
-    GenerateStructs,
-    GenerateModels,
+    GenerateStructs(bool, TableSelection), // --all, --only/--except
+    GenerateModels(bool, TableSelection),  // --all, --only/--except
+    ExplainCodegen(String),
 
     // Asset commands
     // Locale commands removed
@@ -29,60 +52,211 @@ pub enum Command {
     MinifyCss,
     PublishCss,
     ProcessJs,
+    OptimizeAssets,
+    CheckTemplates(bool), // --watch
+    CheckOpenApi,
+    CheckLocales,
     DownloadCdn,
 
     // Server commands
-    RunDevServer,
-    RunProdServer,
+    RunDevServer(Option<u16>),  // --port
+    RunProdServer(Option<u16>), // --port
+    ServerStatus(Option<String>, u64), // --remote <url>, --timeout <secs>
+    Build(bool, bool), // --tar, --no-cache
+    Routes(Option<String>, Option<String>), // --grep <text>, --output <table|json>
+    Test(bool, bool, Option<String>, Vec<String>), // --keep-db, --seed, --db-suffix <suffix>, cargo test args after --
+    GenerateDocker(bool, bool), // --force, --no-compose
+    RunDocker,
+    GenerateAuth(bool),   // --force
+    GenerateGuards(bool), // --force
+    GenerateHealth(bool), // --force
+    GenerateObservability(bool), // --force
+    GenerateLiveReload(bool), // --force
+    GenerateLocale(String, bool), // language tag, --force
+    GenerateOpenApi,
+    GenerateStorageBucket(String, bool), // name, --force
     StopServer, // New command to stop the server
+    Watch,
 
     // Dashboard and interactive CLI commands
-    LaunchDashboard,
+    LaunchDashboard(bool), // --builtin
     RunInteractiveCLI,
 
     // Environment commands
     ToggleEnvironment,
+    EnvGet(String),
+    EnvSet(String, String),
+    EnvUnset(String),
+    EnvCheck,
+    EnvUse(String),
+
+    // Config commands
+    ConfigValidate,
+    ConfigGet(String),
+    ConfigSet(String, String),
+    ConfigMigrate,
+    ConfigSyncDiesel,
+    ConfigRocketSet(String, String, Option<String>), // key, value, --profile <env>
+    ConfigAdd(String, String),
 
     // Log commands
     LogTruncate(Option<String>),
 
     // Spark plugin commands
-    AddSpark(String),
+    AddSpark(String, bool, bool), // repo url or spark directory, --review, --link
+    SparkList,
+    SparkVerify(bool), // bool = apply fixes
+    SparkNew(String, Option<String>), // name, --dir <path>
+    SparkLint(String), // spark directory
 
     // Cronjob commands
     CronjobsList,
     CronjobsAdd(String, i32),
     CronjobsRemove(i32),
     CronjobsToggle(i32),
+    CronjobsImport(Option<String>),
     CronjobsInteractive, // Interactive TUI for cronjob management
     CronjobsLiveTable, // Live auto-refreshing table view
 
     // App commands
-    RefreshApp,
-    Help,
+    RefreshApp(crate::refresh::RefreshOptions),
+    Completions(String), // shell
+    CompleteValues(String), // kind of dynamic value the completion scripts ask for
+    Help(Option<String>), // command words to show the page of
+    InvalidArgument(String), // a known command with a flag value it can't use; the message
     #[allow(dead_code)]
     Exit,
 }
 
+// `migration list` and `migrate --status` cover every connection unless --connection names one
+fn migration_status(args: &[String]) -> Command {
+    Command::MigrationStatus(flag_value(args, "--connection").unwrap_or_else(|| "all".to_string()), flag_value(args, "--output"), args.iter().any(|arg| arg == "--check"))
+}
+
+// Value following a flag, e.g. `--connection analytics`
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1)).cloned()
+}
+
+// `help db reset` and `db reset --yes --help` both ask for the page of `db reset`
+fn help_topic(words: &[String]) -> Option<String> {
+    let words: Vec<&str> = words.iter().map(String::as_str).filter(|word| !matches!(*word, "--help" | "-h")).collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
 // Parse CLI arguments into a Command
 pub fn parse_cli_args(args: &[String]) -> Option<Command> {
+    // `blast <command> --help`; whatever follows `--` belongs to the tool blast runs
+    let own = &args[..args.iter().position(|arg| arg == "--").unwrap_or(args.len())];
+    if own.len() > 2 && own[2..].iter().any(|arg| matches!(arg.as_str(), "--help" | "-h")) {
+        return Some(Command::Help(help_topic(&own[1..])));
+    }
+
     match args.get(1).map(|s| s.as_str()) {
         // Project creation
+        Some("new") if args.get(2).map(|s| s.as_str()) == Some("--repair") => Some(Command::RepairProject(args.get(3)?.clone(), flag_value(args, "--template"))),
         Some("new") if args.len() >= 3 => {
             // Check if the --dev flag is present
             let use_dev_branch = args.iter().any(|arg| arg == "--dev");
-            Some(Command::NewProject(args[2].clone(), use_dev_branch))
+            let yes = args.iter().any(|arg| arg == "--yes" || arg == "-y");
+            Some(Command::NewProject(args[2].clone(), use_dev_branch, flag_value(args, "--template"), flag_value(args, "--from"), flag_value(args, "--cache"), yes, args.iter().any(|arg| arg == "--i18n"), args.iter().any(|arg| arg == "--no-git")))
         },
-        Some("init") => Some(Command::InitProject),
+        Some("init") => {
+            let only = flag_value(args, "--only").map(|phases| phases.split(',').map(|phase| phase.trim().to_string()).filter(|phase| !phase.is_empty()).collect()).unwrap_or_default();
+            Some(Command::InitProject(args.iter().any(|arg| arg == "--no-baseline"), args.iter().any(|arg| arg == "--resume"), only))
+        }
+        Some("verify") => Some(Command::Verify(flag_value(args, "--template"), args.iter().any(|arg| arg == "--keep"))),
+        Some("doctor") => Some(Command::Doctor),
+        Some("report") => {
+            let last = match flag_value(args, "--last") {
+                Some(last) => last.parse().ok()?,
+                None => crate::report::DEFAULT_RUNS,
+            };
+            Some(Command::Report(last, flag_value(args, "--output")))
+        }
+        Some("clean") => {
+            let has = |flag: &str| args.iter().any(|arg| arg == flag);
+            let all = has("--all");
+            Some(Command::Clean(crate::clean::CleanOptions {
+                codegen: all || has("--codegen"),
+                assets: all || has("--assets"),
+                downloads: all || has("--downloads"),
+                logs: all || has("--logs"),
+                yes: has("--yes") || has("-y"),
+            }))
+        }
+        Some("self") if args.get(2).map(|s| s.as_str()) == Some("update") => Some(Command::SelfUpdate(args.iter().any(|arg| arg == "--check"))),
 
         // App commands
-        Some("refresh") => Some(Command::RefreshApp),
-        Some("run") | Some("serve") => Some(Command::RunDevServer),
-        Some("run-prod") | Some("serve-prod") => Some(Command::RunProdServer),
+        Some("refresh") => {
+            let has = |flag: &str| args.iter().any(|arg| arg == flag);
+            let steps = flag_value(args, "--steps").map(|steps| steps.split(',').map(|step| step.trim().to_string()).filter(|step| !step.is_empty()).collect());
+            Some(Command::RefreshApp(crate::refresh::RefreshOptions {
+                all: has("--all"),
+                no_drop: has("--no-drop"),
+                skip_seed: has("--skip-seed"),
+                skip_assets: has("--skip-assets"),
+                steps,
+                connection: flag_value(args, "--connection"),
+                tables: TableSelection::from_flags(flag_value(args, "--only"), flag_value(args, "--except")),
+            }))
+        }
+        Some("run") if args.iter().any(|arg| arg == "--docker") => Some(Command::RunDocker),
+        Some("run") | Some("serve") | Some("run-prod") | Some("serve-prod") => {
+            let port = match flag_value(args, "--port").map(|port| port.parse::<u16>().map_err(|_| port)) {
+                Some(Ok(port)) => Some(port),
+                Some(Err(port)) => return Some(Command::InvalidArgument(format!("invalid --port value '{}': expected a port number between 0 and 65535", port))),
+                None => None,
+            };
+            if matches!(args[1].as_str(), "run-prod" | "serve-prod") {
+                Some(Command::RunProdServer(port))
+            } else {
+                Some(Command::RunDevServer(port))
+            }
+        }
+        Some("status") => {
+            let timeout = match flag_value(args, "--timeout") {
+                Some(secs) => secs.parse::<u64>().ok()?,
+                None => 5,
+            };
+            Some(Command::ServerStatus(flag_value(args, "--remote"), timeout))
+        }
+        Some("build") => Some(Command::Build(args.iter().any(|arg| arg == "--tar"), args.iter().any(|arg| arg == "--no-cache"))),
         Some("stop") => Some(Command::StopServer),
-        Some("dashboard") => Some(Command::LaunchDashboard),
+        Some("watch") => Some(Command::Watch),
+        Some("routes") => Some(Command::Routes(flag_value(args, "--grep"), flag_value(args, "--output"))),
+        Some("test") => {
+            // Everything after `--` goes to cargo test as is
+            let split = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+            let (own, cargo_args) = args.split_at(split);
+            Some(Command::Test(own.iter().any(|arg| arg == "--keep-db"), own.iter().any(|arg| arg == "--seed"), flag_value(own, "--db-suffix"), cargo_args.iter().skip(1).cloned().collect()))
+        }
+        Some("dashboard") => Some(Command::LaunchDashboard(args.iter().any(|arg| arg == "--builtin"))),
         Some("cli") => Some(Command::RunInteractiveCLI),
-        Some("toggle-env") | Some("env") => Some(Command::ToggleEnvironment),
+        Some("toggle-env") => Some(Command::ToggleEnvironment),
+
+        // Environment commands
+        Some("env") => match args.get(2).map(|s| s.as_str()) {
+            Some("get") if args.len() >= 4 => Some(Command::EnvGet(args[3].clone())),
+            Some("set") if args.len() >= 5 => Some(Command::EnvSet(args[3].clone(), args[4..].join(" "))),
+            Some("unset") if args.len() >= 4 => Some(Command::EnvUnset(args[3].clone())),
+            Some("check") => Some(Command::EnvCheck),
+            Some("use") if args.len() >= 4 => Some(Command::EnvUse(args[3].clone())),
+            Some("toggle") | None => Some(Command::ToggleEnvironment),
+            _ => None,
+        },
+
+        // Config commands
+        Some("config") => match args.get(2).map(|s| s.as_str()) {
+            Some("validate") => Some(Command::ConfigValidate),
+            Some("migrate") => Some(Command::ConfigMigrate),
+            Some("sync-diesel") => Some(Command::ConfigSyncDiesel),
+            Some("rocket") if args.get(3).map(|s| s.as_str()) == Some("set") && args.len() >= 6 && !args[5].starts_with("--") => Some(Command::ConfigRocketSet(args[4].clone(), args[5].clone(), flag_value(args, "--profile"))),
+            Some("get") if args.len() >= 4 => Some(Command::ConfigGet(args[3].clone())),
+            Some("set") if args.len() >= 5 => Some(Command::ConfigSet(args[3].clone(), args[4..].join(" "))),
+            Some("add") if args.len() >= 5 => Some(Command::ConfigAdd(args[3].clone(), args[4..].join(" "))),
+            _ => None,
+        },
 
         // Cronjob commands
         Some("cronjobs") => {
@@ -109,6 +283,7 @@ pub fn parse_cli_args(args: &[String]) -> Option<Command> {
                         None
                     }
                 }
+                Some("import") => Some(Command::CronjobsImport(args.get(3).filter(|arg| !arg.starts_with("--")).cloned())),
                 Some("interactive") | Some("tui") => Some(Command::CronjobsInteractive),
                 Some("table") | Some("live") => Some(Command::CronjobsLiveTable),
                 None => Some(Command::CronjobsInteractive), // Default to interactive mode if just "cronjobs" is provided
@@ -117,33 +292,95 @@ pub fn parse_cli_args(args: &[String]) -> Option<Command> {
         }
 
         // DB commands
-        Some("migration") => Some(Command::NewMigration),
-        Some("migrate") => Some(Command::Migrate),
-        Some("rollback") => Some(Command::Rollback),
-        Some("seed") => {
-            if args.len() >= 3 {
-                Some(Command::Seed(Some(args[2].clone())))
-            } else {
-                Some(Command::Seed(None))
-            }
+        Some("migration") if args.get(2).map(|s| s.as_str()) == Some("list") => Some(migration_status(args)),
+        Some("migration") if args.get(2).map(|s| s.as_str()) == Some("verify") => Some(Command::MigrationVerify(flag_value(args, "--connection").unwrap_or_else(|| "default".to_string()), args.iter().any(|arg| arg == "--all"))),
+        Some("migration") if args.get(2).map(|s| s.as_str()) == Some("from-spec") => Some(Command::MigrationFromSpec(args.get(3)?.clone())),
+        Some("migration") => match flag_value(args, "--edit") {
+            Some(spec) => Some(Command::EditMigrationSpec(spec)),
+            None if args.iter().any(|arg| arg == "--edit") => None,
+            None => Some(Command::NewMigration(flag_value(args, "--connection").unwrap_or_else(|| "default".to_string()))),
+        },
+        Some("migrate") if args.iter().any(|arg| arg == "--status") => Some(migration_status(args)),
+        Some("migrate") => Some(Command::Migrate(flag_value(args, "--connection").unwrap_or_else(|| "default".to_string()))),
+        Some("rollback") => Some(Command::Rollback(flag_value(args, "--connection").unwrap_or_else(|| "default".to_string()))),
+        Some("seed") if args.get(2).map(|s| s.as_str()) == Some("gen") => {
+            let table = args.get(3).filter(|arg| !arg.starts_with("--"))?.clone();
+            let count = match flag_value(args, "--count") {
+                Some(count) => count.parse().ok()?,
+                None => 50,
+            };
+            let seed = match flag_value(args, "--deterministic") {
+                Some(seed) => Some(seed.parse().ok()?),
+                None => None,
+            };
+            Some(Command::SeedGen(table, count, seed))
         }
+        Some("seed") if args.iter().any(|arg| arg == "--list") => Some(Command::SeedList),
+        Some("seed") => Some(Command::Seed(args.get(2).filter(|arg| !arg.starts_with("--")).cloned(), flag_value(args, "--connection"))),
+        Some("schema") if args.get(2).map(|s| s.as_str()) == Some("diff") => Some(Command::SchemaDiff(args.iter().any(|arg| arg == "--update"))),
         Some("schema") => Some(Command::GenerateSchema),
+        Some("db") => {
+            let connection = flag_value(args, "--connection").unwrap_or_else(|| "default".to_string());
+            let yes = args.iter().any(|arg| arg == "--yes" || arg == "-y");
+            let force = args.iter().any(|arg| arg == "--i-know-what-im-doing");
+
+            match args.get(2).map(|s| s.as_str()) {
+                Some("create") => Some(Command::DbCreate(connection)),
+                Some("drop") => Some(Command::DbDrop(connection, yes, force)),
+                Some("reset") => Some(Command::DbReset(connection, yes, force)),
+                Some("baseline") => Some(Command::DbBaseline),
+                Some("dump") => {
+                    let tables = flag_value(args, "--tables").map(|tables| tables.split(',').map(|table| table.trim().to_string()).filter(|table| !table.is_empty()).collect()).unwrap_or_default();
+                    Some(Command::DbDump(connection, args.iter().any(|arg| arg == "--data-only"), tables))
+                }
+                Some("snapshots") => Some(Command::DbSnapshots),
+                Some("restore") => Some(Command::DbRestore(connection, args.get(3).filter(|arg| !arg.starts_with('-')).cloned(), yes, force)),
+                Some("console") => Some(Command::DbConsole(connection, flag_value(args, "--command"))),
+                _ => None,
+            }
+        }
 
         // Asset/code generation
-        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("structs") => Some(Command::GenerateStructs),
-        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("models") => Some(Command::GenerateModels),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("--explain") => Some(Command::ExplainCodegen(flag_value(args, "--explain")?)),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("structs") => Some(Command::GenerateStructs(args.iter().any(|arg| arg == "--all"), TableSelection::from_flags(flag_value(args, "--only"), flag_value(args, "--except")))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("models") => Some(Command::GenerateModels(args.iter().any(|arg| arg == "--all"), TableSelection::from_flags(flag_value(args, "--only"), flag_value(args, "--except")))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("auth") => Some(Command::GenerateAuth(args.iter().any(|arg| arg == "--force"))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("guards") => Some(Command::GenerateGuards(args.iter().any(|arg| arg == "--force"))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("openapi") => Some(Command::GenerateOpenApi),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("health") => Some(Command::GenerateHealth(args.iter().any(|arg| arg == "--force"))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("observability") => Some(Command::GenerateObservability(args.iter().any(|arg| arg == "--force"))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("locale") => Some(Command::GenerateLocale(args.get(3).filter(|arg| !arg.starts_with("--"))?.clone(), args.iter().any(|arg| arg == "--force"))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("livereload") => Some(Command::GenerateLiveReload(args.iter().any(|arg| arg == "--force"))),
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("storage-bucket") && args.len() >= 4 => {
+            Some(Command::GenerateStorageBucket(args[3].clone(), args.iter().any(|arg| arg == "--force")))
+        }
+        Some("gen") if args.get(2).map(|s| s.as_str()) == Some("docker") => {
+            Some(Command::GenerateDocker(args.iter().any(|arg| arg == "--force"), args.iter().any(|arg| arg == "--no-compose")))
+        }
         // Locale commands removed
         Some("scss") => Some(Command::TranspileScss),
         Some("css") => Some(Command::MinifyCss),
         Some("publish-css") => Some(Command::PublishCss),
         Some("js") => Some(Command::ProcessJs),
+        Some("assets") if args.get(2).map(|s| s.as_str()) == Some("optimize") => Some(Command::OptimizeAssets),
+        Some("check") if args.get(2).map(|s| s.as_str()) == Some("openapi") => Some(Command::CheckOpenApi),
+        Some("check") if args.get(2).map(|s| s.as_str()) == Some("locales") => Some(Command::CheckLocales),
+        Some("check") if args.get(2).map(|s| s.as_str()) == Some("templates") => Some(Command::CheckTemplates(args.iter().any(|arg| arg == "--watch"))),
         Some("cdn") => Some(Command::DownloadCdn),
 
         // Spark plugin commands
-        Some("spark") if args.get(2).map(|s| s.as_str()) == Some("add") && args.len() >= 4 => Some(Command::AddSpark(args[3].clone())),
+        Some("spark") if args.get(2).map(|s| s.as_str()) == Some("add") && args.len() >= 4 => Some(Command::AddSpark(args[3].clone(), args.iter().any(|arg| arg == "--review"), args.iter().any(|arg| arg == "--link"))),
+        Some("spark") if args.get(2).map(|s| s.as_str()) == Some("list") => Some(Command::SparkList),
+        Some("spark") if args.get(2).map(|s| s.as_str()) == Some("verify") => Some(Command::SparkVerify(args.iter().any(|arg| arg == "--fix"))),
+        Some("spark") if args.get(2).map(|s| s.as_str()) == Some("new") && args.len() >= 4 => Some(Command::SparkNew(args[3].clone(), flag_value(args, "--dir"))),
+        Some("spark") if args.get(2).map(|s| s.as_str()) == Some("lint") => Some(Command::SparkLint(args.get(3).cloned().unwrap_or_else(|| ".".to_string()))),
 
         // Help
-        Some("help") | Some("-h") | Some("--help") => Some(Command::Help),
+        Some("help") | Some("-h") | Some("--help") => Some(Command::Help(help_topic(&args[2..]))),
+
+        // Shell completions
+        Some("completions") if args.len() >= 3 => Some(Command::Completions(args[2].clone())),
+        Some("__complete") if args.len() >= 3 => Some(Command::CompleteValues(args[2].clone())),
 
         // Log management
         Some("logs") | Some("log") if args.get(2).map(|s| s.as_str()) == Some("truncate") => {
@@ -158,95 +395,192 @@ pub fn parse_cli_args(args: &[String]) -> Option<Command> {
     }
 }
 
-// Print help information to stdout
-pub fn show_help() {
-    println!("Blast - Suckless Web Framework CLI");
-    println!();
-    println!("USAGE:");
-    println!("  blast [OPTIONS] [COMMAND]");
-    println!();
-    println!("OPTIONS:");
-    println!("  -v, --verbose       Enable verbose output (show INFO and DEBUG messages)");
-    println!();
-    println!("APP COMMANDS:");
-    println!("  refresh              Refresh the application (rollback, migrate, seed, gen schema & structs)");
-    println!("  run                  Run the development server");
-    println!("  run-prod             Run the production server");
-    println!("  stop                 Stop the running server");
-    println!("  dashboard            Launch the interactive dashboard");
-    println!("  cli                  Launch the interactive CLI");
-    println!("  toggle-env           Toggle between development and production environments");
-    println!();
-    println!("CRONJOB COMMANDS:");
-    println!("  cronjobs             Launch interactive TUI for cronjob management");
-    println!("  cronjobs interactive Launch interactive TUI for cronjob management");
-    println!("  cronjobs table       Display live auto-refreshing table of cronjobs");
-    println!("  cronjobs live        Display live auto-refreshing table of cronjobs");
-    println!("  cronjobs list        List all scheduled jobs and their status");
-    println!("  cronjobs add <name> <interval>  Add a new cronjob with name and interval in seconds");
-    println!("  cronjobs remove <id> Remove a scheduled job by ID");
-    println!("  cronjobs toggle <id> Toggle a job's active status");
-    println!();
-    println!("DATABASE COMMANDS:");
-    println!("  migration            Create a new migration");
-    println!("  migrate              Run all pending migrations");
-    println!("  rollback             Rollback all migrations");
-    println!("  seed [file]          Run database seeds (all or specific file)");
-    println!("  schema               Generate database schema");
-    println!();
-    println!("ASSET MANAGEMENT:");
-    println!("  gen structs          Generate structs from schema");
-    println!("  gen models           Generate model implementations");
-    // Locale commands removed from help
-    println!("  scss                 Transpile SCSS files");
-    println!("  css                  Minify CSS files");
-    println!("  publish-css          Copy CSS files from src/assets/css to public/css with optional minification");
-    println!("  js                   Process JS files");
-    println!("  cdn                  Download assets (git clone for Materialize, CDN for others)");
-    println!();
-    println!("LOG MANAGEMENT:");
-    println!("  log truncate [file]   Truncate log files (all or specific file)");
-    println!();
-    println!("SPARK PLUGINS:");
-    println!("  spark add <repo_url>  Add a spark plugin from a git repository");
-    println!("                       Dependencies listed in manifest.toml are automatically added to Cargo.toml");
-    println!("                       Required environment variables are added to .env with SPARKNAME_ prefix");
-    println!("                       Automatically opens an editor to replace placeholder values with actual configuration");
-    println!("                       Updates Catalyst.toml with [sparks] section");
-    println!("                       Sparks can also be defined in Catalyst.toml and will be installed during 'blast init'");
-    println!("                       Format: [sparks]");
-    println!("                               plznohac = \"https://github.com/catalyst-framework/plznohac\"");
-    println!();
-    println!("OTHER COMMANDS:");
-    println!("  new <project_name>   Create a new project");
-    println!("    --dev              Use the dev branch of the template repository");
-    println!("  init                 Initialize project completely (migrations, seeds, assets, etc.)");
-    println!("  help                 Show this help message");
-    println!();
-    println!("NOTES:");
-    println!("  - Running 'blast' without arguments launches the interactive dashboard");
+// Stop our previous server so its port is released, then make sure nothing else holds the port
+fn prepare_server(config: &Config, port: Option<u16>) -> Result<crate::configs::ServerConfig, String> {
+    crate::dashboard::stop_server(&config.project_dir)?;
+
+    let server = config.server(port);
+    crate::dashboard::ensure_port_free(&server)?;
+    Ok(server)
+}
+
+// Fallback when start_server fails. On Unix `script` keeps cargo's colors in the server log;
+// Windows has no `script`, so the output is redirected to the log directly
+fn spawn_server_fallback(config: &Config, program: &str, args: &[&str], server: &crate::configs::ServerConfig) -> BlastResult {
+    let public_dir = config.public_dir();
+    #[cfg(not(windows))]
+    {
+        let cmd = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+        std::process::Command::new("script")
+            .args(["-q", "-c", &cmd, "storage/logs/server.log"])
+            .current_dir(&config.project_dir)
+            .env("ROCKET_ADDRESS", &server.address)
+            .env("ROCKET_PORT", server.port.to_string())
+            .env("ROCKET_PUBLIC_DIR", &public_dir)
+            .envs(crate::rocket_config::server_env(config))
+            .spawn()
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(windows)]
+    {
+        let mut cmd = std::process::Command::new(program);
+        cmd.args(args).current_dir(&config.project_dir).env("ROCKET_ADDRESS", &server.address).env("ROCKET_PORT", server.port.to_string()).env("ROCKET_PUBLIC_DIR", &public_dir).envs(crate::rocket_config::server_env(config));
+        crate::platform::spawn_logged(&mut cmd, &config.project_dir.join("storage/logs/server.log"))?;
+    }
+
+    Ok(())
+}
+
+// Refuse destructive database commands in prod unless explicitly overridden
+fn ensure_not_production(config: &Config, force: bool) -> BlastResult {
+    if config.is_production() && !force {
+        return Err(format!(
+            "Refusing to run a destructive database command while the environment is {} (a production environment). Pass --i-know-what-im-doing to override.",
+            config.environment
+        ));
+    }
+    Ok(())
+}
+
+// Long-running commands report their progress through .blast/current-operation.json
+fn operation_name(cmd: &Command) -> Option<&'static str> {
+    Some(match cmd {
+        Command::InitProject(..) => "init",
+        Command::RefreshApp(_) => "refresh",
+        Command::Migrate(_) => "migrate",
+        Command::Rollback(_) => "rollback",
+        Command::Seed(..) => "seed",
+        Command::GenerateSchema => "schema",
+        Command::SchemaDiff(_) => "schema-diff",
+        Command::MigrationVerify(..) => "migration verify",
+        Command::DbReset(..) => "db reset",
+        Command::DbRestore(..) => "db restore",
+        Command::GenerateStructs(..) => "gen structs",
+        Command::GenerateModels(..) => "gen models",
+        Command::TranspileScss => "scss",
+        Command::MinifyCss | Command::PublishCss => "css",
+        Command::ProcessJs => "js",
+        Command::OptimizeAssets => "optimize",
+        Command::DownloadCdn => "cdn",
+        Command::Build(..) => "build",
+        Command::Test(..) => "test",
+        _ => return None,
+    })
 }
 
 // Execute a command with config and dependency manager
 pub fn execute(cmd: Command, config: &mut Config, dep_manager: &mut DependencyManager) -> BlastResult {
+    // Ctrl-C stops long commands at their next step, with their children stopped and temp files removed;
+    // blast then exits with 130 (platform::exit_if_interrupted)
+    let _interrupts = (operation_name(&cmd).is_some() || matches!(cmd, Command::AddSpark(..))).then(crate::platform::handle_interrupts);
+    let Some(name) = operation_name(&cmd) else {
+        return dispatch(cmd, config, dep_manager);
+    };
+
+    crate::operation::begin(&config.project_dir, name);
+    logger::begin_summary(name);
+    crate::profile::begin();
+    let result = dispatch(cmd, config, dep_manager);
+    let profile = crate::profile::finish();
+    if let Some(profile) = &profile {
+        crate::operation::set_timings(profile);
+    }
+    crate::operation::finish(&result);
+    logger::finish_summary(result.as_ref().err());
+    if let Some(profile) = &profile {
+        crate::profile::print(name, profile);
+    }
+    result
+}
+
+// Commands that read DATABASE_URL or hand the env files to the app
+fn needs_env(cmd: &Command) -> bool {
+    matches!(
+        cmd,
+        Command::InitProject(..)
+            | Command::Migrate(_)
+            | Command::MigrationStatus(..)
+            | Command::MigrationVerify(..)
+            | Command::Rollback(_)
+            | Command::Seed(..)
+            | Command::GenerateSchema
+            | Command::SchemaDiff(_)
+            | Command::DbCreate(_)
+            | Command::DbDrop(..)
+            | Command::DbReset(..)
+            | Command::DbBaseline
+            | Command::DbDump(..)
+            | Command::DbRestore(..)
+            | Command::DbConsole(..)
+            | Command::GenerateStructs(..)
+            | Command::GenerateModels(..)
+            | Command::RunDevServer(_)
+            | Command::RunProdServer(_)
+            | Command::Test(..)
+            | Command::RunDocker
+            | Command::RefreshApp(_)
+            | Command::CronjobsList
+            | Command::CronjobsAdd(..)
+            | Command::CronjobsRemove(_)
+            | Command::CronjobsToggle(_)
+            | Command::CronjobsImport(_)
+            | Command::CronjobsInteractive
+            | Command::CronjobsLiveTable
+    )
+}
+
+fn dispatch(cmd: Command, config: &mut Config, dep_manager: &mut DependencyManager) -> BlastResult {
     // Only try to reload config for commands that require an existing project
-    if cmd != Command::Help && !matches!(cmd, Command::NewProject(..)) {
+    if !matches!(cmd, Command::Help(_) | Command::NewProject(..) | Command::RepairProject(..) | Command::Doctor | Command::SelfUpdate(_) | Command::Verify(..) | Command::ServerStatus(Some(_), _) | Command::Completions(_) | Command::SparkNew(..) | Command::SparkLint(_)) {
         // Reload config if it's been modified
         if let Err(e) = config.reload_if_modified() {
             logger::warning(&format!("Failed to reload config: {}", e))?;
         }
+
+        // Keep reminding until schema.rs catches up with the migrations. These commands regenerate it
+        // themselves, and completion values must stay machine-readable
+        let skip_reminder = matches!(cmd, Command::Migrate(_) | Command::GenerateSchema | Command::RefreshApp(..) | Command::DbReset(..) | Command::InitProject(..) | Command::CompleteValues(_));
+        if !skip_reminder {
+            crate::state::warn_if_schema_stale(&config.project_dir)?;
+        }
+        if !matches!(cmd, Command::ConfigMigrate | Command::CompleteValues(_)) {
+            crate::config_migrate::warn_if_behind(config)?;
+        }
+        if !matches!(cmd, Command::ConfigSyncDiesel | Command::InitProject(..) | Command::CompleteValues(_)) {
+            crate::diesel_toml::warn_if_out_of_sync(config)?;
+        }
+        if needs_env(&cmd) {
+            crate::envfile::ensure_env(config)?;
+        }
     }
 
     match cmd {
         // Cronjob commands
         Command::CronjobsList => crate::cronjobs::list_cronjobs(config),
 
+        Command::ConfigValidate => config.validate(),
+
+        Command::ConfigGet(key) => config.get_key(&key),
+
+        Command::ConfigSet(key, value) => config.set_key(&key, &value),
+        Command::ConfigMigrate => crate::config_migrate::migrate(config),
+        Command::ConfigSyncDiesel => crate::diesel_toml::run(config),
+        Command::ConfigRocketSet(key, value, profile) => crate::rocket_config::set(config, &key, &value, profile.as_deref()),
+
+        Command::ConfigAdd(key, value) => config.add_to_key(&key, &value),
+
         Command::CronjobsAdd(name, interval) => crate::cronjobs::add_cronjob(config, &name, interval),
 
         Command::CronjobsRemove(id) => crate::cronjobs::remove_cronjob(config, id),
 
         Command::CronjobsToggle(id) => crate::cronjobs::toggle_cronjob(config, id),
 
+        Command::CronjobsImport(file) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            crate::cronjobs::import_jobs(config, file)
+        }
+
         Command::CronjobsInteractive => {
             logger::info("Launching interactive cronjob manager...")?;
             crate::cronjobs_tui::run_cronjobs_tui(config)
@@ -257,9 +591,11 @@ pub fn execute(cmd: Command, config: &mut Config, dep_manager: &mut DependencyMa
             crate::cronjobs_tui::display_cronjobs_table(config)
         }
 
+        Command::Watch => crate::livereload::watch(config),
+
         Command::StopServer => {
             logger::info("Stopping running server...")?;
-            if let Err(e) = crate::dashboard::stop_server() {
+            if let Err(e) = crate::dashboard::stop_server(&config.project_dir) {
                 logger::error(&format!("Failed to stop server: {}", e))?;
                 return Err(e);
             }
@@ -267,225 +603,130 @@ pub fn execute(cmd: Command, config: &mut Config, dep_manager: &mut DependencyMa
             Ok(())
         }
 
-        Command::AddSpark(repo_url) => {
-            logger::info(&format!("Adding spark plugin from: {}", repo_url))?;
-            crate::sparks::add_spark(&repo_url, config)
-        }
-
-        Command::NewProject(name, use_dev_branch) => {
-            // Create the project using styled output - the function handles all output
-            crate::project::create_new_project(&name, use_dev_branch);
+        Command::AddSpark(source, true, link) => crate::sparks::review_spark(&crate::sparks::SparkOrigin::parse(&source, link)?, config),
 
-            // No need for repetitive success message since create_new_project already prints it
-            // Next steps are also already displayed in create_new_project
-            Ok(())
+        Command::AddSpark(source, false, link) => {
+            let source = crate::sparks::SparkOrigin::parse(&source, link)?;
+            logger::info(&format!("Adding spark plugin from: {}", source))?;
+            crate::sparks::add_spark(&source, config).map(|_| ())
         }
 
-        Command::InitProject => {
-            use console::style;
-
-            // Check for verbose mode to adjust displayed information
-            let is_verbose = logger::is_verbose();
+        Command::SparkList => crate::sparks::list_sparks(config),
 
-            // Always show an initial message to indicate we're starting
-            println!("{} Initializing project...", style("🚀").cyan());
+        Command::SparkVerify(fix) => crate::sparks::verify_sparks(config, fix),
 
-            // Create a progress tracker for the overall process with known steps
-            let total_steps = 7; // Dependencies, DB, Schema, Code Gen, Assets, SCSS/CSS/JS, Sparks
-            let mut main_progress = logger::create_progress(Some(total_steps));
+        Command::SparkNew(name, dir) => crate::spark_scaffold::new(&name, dir.as_deref()),
 
-            // 1. Ensure dependencies are installed - less verbose messaging
-            if is_verbose {
-                main_progress.set_message("Project initialization (1/7): Setting up dependencies");
-            }
-            dep_manager.ensure_installed(&["diesel"], true)?;
-            main_progress.inc(1);
-
-            // 2. Database operations - standardize primary step messages
-            if is_verbose {
-                main_progress.set_message("Project initialization (2/7): Setting up database");
-            }
-
-            // Run migrations - make sure they're executed fully
-            main_progress.set_message("Running database migrations...");
-            let migrations_ok = crate::database::migrate();
-            if !migrations_ok {
-                main_progress.warning("Some migration issues occurred - check database configuration")?;
-            }
-
-            // Run seeds with complete setup
-            main_progress.set_message("Seeding database...");
-            let seed_ok = crate::database::seed(None); // Use None to run complete seed process
-            if !seed_ok {
-                main_progress.warning("Some seeding issues occurred - this may be normal for new projects")?;
-            }
-
-            main_progress.inc(1);
-
-            // 3. Generate schema - use the explicit force function to avoid any environment issues
-            if is_verbose {
-                main_progress.set_message("Project initialization (3/7): Generating database schema");
-            } else {
-                main_progress.set_message("Generating database schema...");
-            }
-
-            // Use force_regenerate_main_schema to ensure we're using the main DATABASE_URL 
-            // even at this early stage
-            let schema_ok = crate::database::force_regenerate_main_schema();
-            if !schema_ok {
-                main_progress.warning("Some schema generation issues occurred")?;
-            }
-            main_progress.inc(1);
-
-            // 4. Code generation - ensure complete generation of all models and structs
-            if is_verbose {
-                main_progress.set_message("Project initialization (4/7): Generating code files");
-            }
-
-            // Retry struct generation if needed to ensure complete success
-            main_progress.set_message("Generating structs...");
-            let mut structs_ok = crate::structs::generate(config);
-            if !structs_ok {
-                // Retry struct generation once more after schema is confirmed generated
-                structs_ok = crate::structs::generate(config);
-                if !structs_ok {
-                    main_progress.warning("Struct generation issues persisted - may be normal for empty schemas")?;
-                }
-            }
-
-            // Retry model generation if needed to ensure complete success
-            main_progress.set_message("Generating models...");
-            let mut models_ok = crate::models::generate(config);
-            if !models_ok {
-                // Retry model generation once more with confirmed structs
-                models_ok = crate::models::generate(config);
-                if !models_ok {
-                    main_progress.warning("Model generation issues persisted - may be normal for empty schemas")?;
-                }
-            }
-            main_progress.inc(1);
-
-            // 5. Download assets
-            if is_verbose {
-                main_progress.set_message("Project initialization (5/7): Downloading assets");
-            } else {
-                main_progress.set_message("Downloading assets...");
-            }
+        Command::SparkLint(path) => crate::spark_scaffold::lint(&path),
 
-            let assets_result = crate::assets::download_assets(config);
-            if let Err(e) = &assets_result {
-                main_progress.warning(&format!("Some asset downloads failed: {}", e))?;
-            }
-            main_progress.inc(1);
-
-            // 6. Process assets (SCSS, CSS, JS)
-            if is_verbose {
-                main_progress.set_message("Project initialization (6/7): Processing asset files");
-            } else {
-                main_progress.set_message("Processing asset files...");
+        Command::NewProject(name, _, template, Some(repo_url), cache, yes, i18n, no_git) => {
+            if template.is_some() || cache.is_some() || i18n {
+                return Err("--template, --cache and --i18n can't be combined with --from; a starter repository is its own template".to_string());
             }
+            crate::project::create_project_from_repo(&name, &repo_url, yes, no_git)
+        }
 
-            // Process SCSS files - these are part of final step, don't increment yet
-            let scss_result = crate::assets::transpile_all_scss(config);
-            if let Err(e) = &scss_result {
-                main_progress.warning(&format!("SCSS processing error: {}", e))?;
-            }
+        Command::NewProject(name, use_dev_branch, template, None, cache, yes, i18n, no_git) => {
+            // Validate --template up front; without it the user picks one interactively
+            let template = match template {
+                Some(template_name) => Some(crate::project::ProjectTemplate::from_name(&template_name).ok_or_else(|| format!("Unknown template '{}'. Available templates: full, api, minimal", template_name))?),
+                None => None,
+            };
 
-            // Process CSS files
-            let css_result = crate::assets::publish_css(config);
-            if let Err(e) = &css_result {
-                main_progress.warning(&format!("CSS publishing error: {}", e))?;
-            }
+            let cache = match cache {
+                Some(backend) => Some(crate::project::CacheBackend::from_name(&backend).ok_or_else(|| format!("Unknown cache backend '{}'. Available backends: memory, redis", backend))?),
+                None => None,
+            };
 
-            // Process JS files
-            let js_result = crate::assets::process_js(config);
-            if let Err(e) = &js_result {
-                main_progress.warning(&format!("JS processing error: {}", e))?;
-            }
+            // Prints its progress and the next steps itself
+            crate::project::create_new_project(&name, use_dev_branch, template, cache, yes, i18n, no_git)
+        }
 
-            main_progress.inc(1);
+        Command::RepairProject(dir, template) => crate::integrity::repair_directory(&dir, template),
 
-            // 7. Check for and install sparks from Catalyst.toml
-            if is_verbose {
-                main_progress.set_message("Project initialization (7/7): Installing spark plugins");
-            } else {
-                main_progress.set_message("Installing spark plugins...");
-            }
+        Command::Verify(template, keep) => crate::verify::run(template, keep),
 
-            if let Err(e) = crate::sparks::install_sparks_from_config(config) {
-                main_progress.warning(&format!("Some issues with spark installation: {}", e))?;
-            }
-            main_progress.inc(1);
-
-            // CRITICAL: Force regenerate schema from main DATABASE_URL to override any spark changes
-            main_progress.set_message("Ensuring schema is generated for main database...");
-            
-            // Always force regenerate the schema as the last step to ensure it's correct
-            let schema_fixed = crate::database::force_regenerate_main_schema();
-            if !schema_fixed {
-                main_progress.warning("Failed to force-regenerate schema from main database. The schema may be incorrect.")?;
-            } else {
-                main_progress.success("Schema has been correctly regenerated from main DATABASE_URL");
-            }
-            
-            // Re-run struct and model generation to ensure they match the fixed schema
-            main_progress.set_message("Regenerating structs and models from fixed schema...");
-            let structs_regenerated = crate::structs::generate(config);
-            let models_regenerated = crate::models::generate(config);
-            
-            if !structs_regenerated || !models_regenerated {
-                main_progress.warning("Failed to regenerate some structs or models. You may need to run 'blast gen structs' and 'blast gen models' manually.")?;
-            } else {
-                main_progress.success("Structs and models have been regenerated successfully");
-            }
+        Command::Doctor => crate::doctor::run(config),
 
-            // Finish with success message - clear the progress bar first
-            main_progress.success("Project initialization complete!");
+        Command::SelfUpdate(check_only) => crate::self_update::run(check_only),
 
-            // Show next steps for the user with consistent styling
-            println!("{} Your project is ready to run! {}", style("🎉").green(), style("🚀").green());
+        Command::Report(last, output) => crate::report::run(config, last, output),
 
-            println!("\nNext steps:");
-            println!("  {} Run 'blast run' to start the development server", style("1.").cyan());
-            println!("  {} Run 'blast dashboard' to launch the interactive dashboard", style("2.").cyan());
+        Command::Clean(options) => crate::clean::run(config, &options),
 
-            Ok(())
-        }
+        Command::InitProject(no_baseline, resume, only) => crate::init::run(config, dep_manager, no_baseline, resume, &only),
 
         Command::RunInteractiveCLI => {
             // Now sync, no need for Box::pin
             return crate::interactive::run_interactive_cli(config.clone(), dep_manager);
         }
 
-        Command::NewMigration => {
-            dep_manager.ensure_installed(&["diesel"], true)?;
-            crate::database::new_migration();
-            Ok(())
+        Command::NewMigration(connection) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            crate::database::new_migration(config, &connection)
+        }
+
+        Command::EditMigrationSpec(spec) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            crate::database::edit_migration_spec(config, &spec)
+        }
+
+        Command::MigrationFromSpec(spec) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            crate::migration_spec::from_spec(config, &spec)
         }
 
-        Command::Migrate => {
-            dep_manager.ensure_installed(&["diesel"], true)?;
-            if !crate::database::migrate() {
+        Command::MigrationStatus(connection, output, check) => crate::migration_status::run(config, &connection, output, check),
+        Command::MigrationVerify(connection, all) => crate::migration_verify::run(config, &connection, all),
+
+        Command::Migrate(connection) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            let connections = crate::database::resolve_connections(config, &connection)?;
+            let mut ok = true;
+            for connection in &connections {
+                crate::platform::check_interrupted()?;
+                // Only worth a snapshot when something is about to run
+                if connection == "default" && crate::database::pending_migrations(config, connection).map(|pending| pending > 0).unwrap_or(true) {
+                    crate::db_snapshot::snapshot_before_migrate(config)?;
+                }
+                ok &= crate::database::migrate_connection(config, connection);
+            }
+            crate::platform::check_interrupted()?;
+            if !ok {
                 logger::warning("Some migration issues occurred")?;
             }
+            // Codegen reads the default connection's schema.rs only
+            if connections.iter().any(|connection| connection == "default") {
+                crate::database::regenerate_if_stale(config);
+            }
             Ok(())
         }
 
-        Command::Rollback => {
-            dep_manager.ensure_installed(&["diesel"], true)?;
-            if !crate::database::rollback_all() {
+        Command::Rollback(connection) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            let mut ok = true;
+            for connection in crate::database::resolve_connections(config, &connection)? {
+                crate::platform::check_interrupted()?;
+                ok &= crate::database::rollback_connection(config, &connection);
+            }
+            if !ok {
                 logger::warning("Some rollback issues occurred")?;
             }
             Ok(())
         }
 
-        Command::Seed(file_name) => {
-            dep_manager.ensure_installed(&["diesel"], true)?;
+        Command::Seed(file_name, connection) => {
+            dep_manager.ensure_installed(&["diesel"])?;
 
-            let success = if let Some(file) = file_name {
-                crate::database::seed_specific_file(&file)
-            } else {
-                crate::database::seed(Some(0))
+            let success = match (file_name, connection) {
+                (Some(file), connection) => {
+                    let target = crate::database::seed_connection(&config.project_dir.join("src/database/seeds").join(&file));
+                    if let Some(connection) = connection.filter(|connection| *connection != target) {
+                        return Err(format!("{} targets the {} connection, not {}", file, target, connection));
+                    }
+                    crate::database::seed_specific_file(config, &file)
+                }
+                (None, connection) => crate::database::seed(config, Some(0), connection.as_deref()),
             };
 
             if !success {
@@ -494,63 +735,123 @@ pub fn execute(cmd: Command, config: &mut Config, dep_manager: &mut DependencyMa
             Ok(())
         }
 
+        Command::SeedList => crate::database::list_seeds(config),
+
+        Command::SeedGen(table, count, seed) => crate::seed_gen::generate_seed(config, &table, count, seed),
+
         Command::GenerateSchema => {
-            dep_manager.ensure_installed(&["diesel"], true)?;
-            if !crate::database::generate_schema() {
+            dep_manager.ensure_installed(&["diesel"])?;
+            if !crate::database::generate_schema(config) {
                 logger::warning("Some schema generation issues occurred")?;
             }
             Ok(())
         }
 
-        Command::GenerateStructs => {
-            if !crate::structs::generate(config) {
-                logger::warning("Some struct generation issues occurred")?;
+        Command::SchemaDiff(update) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            crate::database::schema_diff(config, update)
+        }
+
+        Command::DbBaseline => {
+            if !crate::database::baseline(config) {
+                return Err("Failed to create baseline migration".to_string());
             }
             Ok(())
         }
 
-        Command::GenerateModels => {
-            if !crate::models::generate(config) {
-                logger::warning("Some model generation issues occurred")?;
+        Command::DbDump(connection, data_only, tables) => crate::db_snapshot::dump(config, &connection, data_only, &tables).map(|_| ()),
+
+        Command::Test(keep_db, seed, db_suffix, cargo_args) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+            crate::test_runner::run_tests(config, keep_db, seed, db_suffix.as_deref(), &cargo_args)
+        }
+
+        Command::DbSnapshots => crate::db_snapshot::list_snapshots(config),
+
+        Command::DbConsole(connection, statement) => crate::db_console::run(config, &connection, statement.as_deref()),
+
+        Command::DbRestore(connection, file, yes, force) => {
+            ensure_not_production(config, force)?;
+            crate::db_snapshot::restore(config, &connection, file.as_deref(), yes)
+        }
+
+        Command::DbCreate(connection) => {
+            if !crate::database::create_database(config, &connection) {
+                return Err(format!("Failed to create database for {} connection", connection));
             }
             Ok(())
         }
 
-        // Locale commands removed
-        Command::RefreshApp => {
-            // App refresh involves multiple steps
-            let mut progress = logger::create_progress(None);
+        Command::DbDrop(connection, yes, force) => {
+            ensure_not_production(config, force)?;
+            crate::db_safety::confirm_connection(config, "drop", &connection, yes)?;
+            if !crate::database::drop_database(config, &connection, yes) {
+                return Err(format!("Database for {} connection was not dropped", connection));
+            }
+            Ok(())
+        }
+
+        Command::DbReset(connection, yes, force) => {
+            ensure_not_production(config, force)?;
+            crate::db_safety::confirm_connection(config, "reset", &connection, yes)?;
+            dep_manager.ensure_installed(&["diesel"])?;
 
-            // Database operations
-            dep_manager.ensure_installed(&["diesel"], true)?;
+            if !crate::database::drop_database(config, &connection, yes) {
+                return Err(format!("Database for {} connection was not dropped", connection));
+            }
+            if !crate::database::create_database(config, &connection) {
+                return Err(format!("Failed to create database for {} connection", connection));
+            }
 
-            progress.set_message("Rolling back migrations...");
-            let rollback_ok = crate::database::rollback_all();
+            let mut progress = logger::create_progress(None);
 
             progress.set_message("Running migrations...");
-            let migrations_ok = crate::database::migrate();
+            let migrations_ok = crate::database::migrate_connection(config, &connection);
 
+            // Only the seeds that target this connection; the main schema belongs to the default one
             progress.set_message("Seeding database...");
-            let seed_ok = crate::database::seed(Some(0));
-
+            let seed_ok = crate::database::seed(config, Some(0), Some(&connection));
             progress.set_message("Generating schema...");
-            let schema_ok = crate::database::generate_schema();
+            let schema_ok = if connection == "default" { crate::database::generate_schema(config) } else { crate::database::generate_schema_for_connection(config, &connection) };
 
             progress.set_message("Generating structs...");
-            let structs_ok = crate::structs::generate(config);
+            let structs_ok = crate::structs::generate(config, false, &Default::default());
 
             progress.set_message("Generating models...");
-            let models_ok = crate::models::generate(config);
+            let models_ok = crate::models::generate(config, false, &Default::default());
 
-            if rollback_ok && migrations_ok && seed_ok && schema_ok && structs_ok && models_ok {
-                progress.success("App refresh complete!");
+            let steps = [("migrations", migrations_ok), ("seeds", seed_ok), ("schema", schema_ok), ("structs", structs_ok), ("models", models_ok)];
+            let failed: Vec<&str> = steps.iter().filter(|(_, ok)| !ok).map(|(step, _)| *step).collect();
+            if failed.is_empty() {
+                progress.success("Database reset complete!");
+                Ok(())
             } else {
-                progress.error("App refresh completed with some issues");
+                // Non-zero exit so CI notices a reset that left the database or generated code broken
+                progress.error("Database reset completed with some issues");
+                Err(format!("Database reset of the {} connection failed at: {}", connection, failed.join(", ")))
+            }
+        }
+
+        Command::GenerateStructs(all, tables) => {
+            dep_manager.ensure_installed(&["diesel_ext"])?;
+            if !crate::structs::generate(config, all, &tables) {
+                logger::warning("Some struct generation issues occurred")?;
             }
+            Ok(())
+        }
 
+        Command::GenerateModels(all, tables) => {
+            if !crate::models::generate(config, all, &tables) {
+                logger::warning("Some model generation issues occurred")?;
+            }
             Ok(())
         }
 
+        Command::ExplainCodegen(table) => crate::codegen_ignore::explain(config, &table),
+
+        // Locale commands removed
+        Command::RefreshApp(options) => crate::refresh::run(config, dep_manager, &options),
+
         Command::TranspileScss => {
             // Use the built-in Rust sass-rs crate, no external dependency needed
             crate::assets::transpile_all_scss(config)
@@ -562,6 +863,13 @@ pub fn execute(cmd: Command, config: &mut Config, dep_manager: &mut DependencyMa
 
         Command::ProcessJs => crate::assets::process_js(config),
 
+        Command::OptimizeAssets => crate::optimize::optimize_assets(config),
+
+        Command::CheckTemplates(watch) => crate::templates::check_templates(config, watch),
+        Command::CheckLocales => crate::locales::check(config),
+
+        Command::CheckOpenApi => crate::openapi::check(config),
+
         Command::DownloadCdn => {
             // The download_assets_async function now handles environment mode setting internally
             // to ensure consistent behavior between CLI and dashboard modes
@@ -578,62 +886,124 @@ pub fn execute(cmd: Command, config: &mut Config, dep_manager: &mut DependencyMa
             }
         }
 
-        Command::RunDevServer => {
-            if let Ok(pid) = crate::dashboard::start_server(config, true) {
-                logger::success(&format!("Development server started with PID: {}", pid))?;
+        Command::RunDevServer(port) => {
+            let server = prepare_server(config, port)?;
+            if let Ok(pid) = crate::dashboard::start_server(config, true, &server) {
+                logger::success(&format!("Development server ({}) started with PID: {} at {}", config.environment, pid, server.url()))?;
             } else {
-                let cmd = format!("cargo run --bin {}", &config.project_name);
-                std::process::Command::new("script").args(["-q", "-c", &cmd, "storage/logs/server.log"]).spawn().map_err(|e| e.to_string())?;
-                logger::success("Development server started with cargo run")?;
+                spawn_server_fallback(config, "cargo", &["run", "--bin", &config.project_name], &server)?;
+                logger::success(&format!("Development server ({}) started with cargo run at {}", config.environment, server.url()))?;
             }
-            Ok(())
+            logger::plain(&crate::livereload::status_line(config))
         }
 
-        Command::RunProdServer => {
-            if let Ok(pid) = crate::dashboard::start_server(config, false) {
-                logger::success(&format!("Production server started with PID: {}", pid))?;
+        Command::RunProdServer(port) => {
+            let server = prepare_server(config, port)?;
+            if let Ok(pid) = crate::dashboard::start_server(config, false, &server) {
+                logger::success(&format!("Production server ({}) started with PID: {} at {}", config.environment, pid, server.url()))?;
             } else {
                 // Check if binary exists
-                let binary_path = format!("target/release/{}", &config.project_name);
-                if std::path::Path::new(&binary_path).exists() {
-                    std::process::Command::new("script")
-                        .args(["-q", "-c", &binary_path, "storage/logs/server.log"])
-                        .spawn()
-                        .map_err(|e| e.to_string())?;
-                    logger::success(&format!("Production server started using compiled binary: {}", binary_path))?;
+                let binary_path = format!("target/release/{}{}", &config.project_name, std::env::consts::EXE_SUFFIX);
+                let binary = config.project_dir.join(&binary_path);
+                if binary.exists() {
+                    spawn_server_fallback(config, &binary.to_string_lossy(), &[], &server)?;
+                    logger::success(&format!("Production server ({}) started using compiled binary: {} at {}", config.environment, binary_path, server.url()))?;
                 } else {
-                    let cmd = format!("cargo run --release --bin {}", &config.project_name);
-                    std::process::Command::new("script").args(["-q", "-c", &cmd, "storage/logs/server.log"]).spawn().map_err(|e| e.to_string())?;
-                    logger::success("Production server started with cargo run --release")?;
+                    spawn_server_fallback(config, "cargo", &["run", "--release", "--bin", &config.project_name], &server)?;
+                    logger::success(&format!("Production server ({}) started with cargo run --release at {}", config.environment, server.url()))?;
                     logger::info("Tip: Build with 'cargo build --release' for faster startup next time")?;
                 }
             }
             Ok(())
         }
 
-        Command::LaunchDashboard => {
-            dep_manager.ensure_installed(&["zellij", "diesel"], false)?;
-            crate::dashboard::launch_dashboard(config)?;
+        Command::ServerStatus(None, _) => crate::dashboard::server_status(config),
+
+        Command::ServerStatus(Some(url), timeout) => {
+            // Exit codes distinguish unhealthy, HTTP errors, timeouts and unreachable hosts for monitoring
+            let code = crate::health::check_remote(config, &url, timeout);
+            if code != crate::health::EXIT_HEALTHY {
+                std::process::exit(code);
+            }
             Ok(())
         }
 
-        Command::ToggleEnvironment => {
-            // Toggle environment
-            config.toggle_environment()?;
+        Command::GenerateAuth(force) => crate::auth::generate(config, force),
+        Command::GenerateGuards(force) => crate::guards::generate(config, force),
+        Command::GenerateHealth(force) => crate::health::generate_controller(config, force),
+
+        Command::GenerateObservability(force) => crate::observability::generate(config, force),
+        Command::GenerateLiveReload(force) => crate::livereload::generate(config, force),
+        Command::GenerateLocale(lang, force) => crate::locales::generate(config, &lang, force),
+        Command::GenerateOpenApi => crate::openapi::generate(config),
+        Command::GenerateStorageBucket(name, force) => crate::storage::generate_bucket(config, &name, force),
+
+        Command::Build(tarball, no_cache) => crate::build::build(config, tarball, !no_cache),
+
+        Command::Routes(grep, output) => crate::routes::list_routes(config, grep, output),
+
+        Command::GenerateDocker(force, no_compose) => crate::docker::generate(config, force, !no_compose),
+
+        Command::RunDocker => crate::docker::compose_up(config),
+
+        Command::LaunchDashboard(builtin) => {
+            dep_manager.ensure_installed(&["diesel"])?;
+
+            // Zellij has no Windows build; elsewhere fall back to the built-in dashboard when it's missing
+            if builtin {
+                crate::dashboard_tui::run_dashboard_tui(config, dep_manager, None)
+            } else if cfg!(windows) || !dep_manager.is_installed("zellij") {
+                crate::dashboard_tui::run_dashboard_tui(config, dep_manager, Some("zellij not found, using the built-in dashboard. Install it with 'cargo install zellij' for the full layout"))
+            } else {
+                crate::dashboard::launch_dashboard(config)
+            }
+        }
+
+        Command::ToggleEnvironment | Command::EnvUse(_) => {
+            if let Command::EnvUse(name) = &cmd {
+                config.use_environment(name)?;
+                logger::success(&format!("Environment set to {}", config.environment))?;
+            } else {
+                config.toggle_environment()?;
+            }
+
+            // Show which env file now provides DATABASE_URL for the new environment
+            let effective_env = crate::envfile::load_effective(&config.project_dir, &config.environment);
+            match effective_env.source("DATABASE_URL") {
+                Some(source) => println!("DATABASE_URL for '{}' now comes from {}", config.environment, crate::envfile::display_name(source)),
+                None => logger::warning(&format!("DATABASE_URL is not set for environment '{}'", config.environment))?,
+            }
             logger::info("Run `blast scss`, `blast css`, or `blast js` to rebuild assets with new settings")?;
             Ok(())
         }
 
+        Command::EnvGet(key) => crate::envfile::get(config, &key),
+
+        Command::EnvSet(key, value) => crate::envfile::set(config, &key, &value),
+
+        Command::EnvUnset(key) => crate::envfile::unset(config, &key),
+
+        Command::EnvCheck => crate::envfile::check(config),
+
         Command::LogTruncate(file_name) => {
             logger::info("Managing log files...")?;
             crate::logger::ensure_log_files_exist(config)?;
             crate::logger::truncate_specific_log(config, file_name)
         }
 
-        Command::Help => {
-            show_help();
+        Command::Completions(shell) => crate::completions::generate(&shell),
+
+        Command::CompleteValues(kind) => {
+            crate::completions::print_values(config, &kind);
+            Ok(())
+        }
+
+        Command::Help(None) => {
+            crate::help::print_overview();
             Ok(())
         }
+        Command::Help(Some(topic)) => crate::help::print_topic(&topic),
+        Command::InvalidArgument(message) => Err(message),
 
         Command::Exit => Ok(()),
     }
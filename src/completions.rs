@@ -0,0 +1,289 @@
+use std::fs;
+
+use crate::configs::Config;
+
+// A command word as the completion scripts see it, derived from the help table so the two can't drift apart
+struct CommandSpec {
+    name: &'static str,
+    about: &'static str,
+    subcommands: Vec<&'static str>,
+    flags: Vec<&'static str>,
+}
+
+// Fixed choices for positional arguments: (command, values)
+const FIXED_ARGS: &[(&str, &[&str])] = &[("completions", &["bash", "zsh", "fish"])];
+
+fn commands() -> Vec<CommandSpec> {
+    crate::help::command_words()
+        .into_iter()
+        .map(|name| {
+            let mut subcommands = crate::help::subcommands(name);
+            subcommands.extend(FIXED_ARGS.iter().filter(|(command, _)| *command == name).flat_map(|(_, values)| values.iter().copied()));
+            CommandSpec { name, about: crate::help::summary(name), subcommands, flags: crate::help::flags(name) }
+        })
+        .collect()
+}
+
+// Global options one by one: "-v, --verbose" is two of them
+fn global_flags() -> Vec<(&'static str, &'static str)> {
+    crate::help::GLOBAL_FLAGS.iter().flat_map(|flag| flag.name.split(", ").filter_map(|name| name.split_whitespace().next()).map(|name| (name, flag.about))).collect()
+}
+
+// Descriptions go inside single quotes in the zsh and fish scripts
+fn quoted(about: &str, escaped_quote: &str) -> String {
+    about.replace('\'', escaped_quote)
+}
+
+// Flags that take a value: fixed choices, a `blast __complete` kind, or nothing to offer
+const VALUE_FLAGS: &[(&str, &str, Option<&str>)] = &[
+    ("--template", "full api minimal", None),
+    ("--cache", "memory redis", None),
+    ("--log-format", "text json", None),
+    ("--force-overwrite", "all", None),
+    ("-p", "", None),
+    ("--env", "", Some("environments")),
+    ("--connection", "", Some("connections")),
+    ("--from", "", None),
+    ("--port", "", None),
+    ("--remote", "", None),
+    ("--timeout", "", None),
+    ("--grep", "", None),
+    ("--output", "table json", None),
+    ("--count", "", None),
+    ("--deterministic", "", None),
+    ("--tables", "", None),
+    ("--db-suffix", "", None),
+    ("--only", crate::init::ONLY_VALUES, None),
+    ("--edit", "", Some("migration_specs")),
+    ("--steps", "rollback migrate seed schema structs models assets", None),
+    ("--dir", "", None),
+    ("--explain", "", Some("tables")),
+    ("--except", "", Some("tables")),
+];
+
+// Positional arguments completed from the project: (command, subcommand, `blast __complete` kind)
+const DYNAMIC_ARGS: &[(&str, Option<&str>, &str)] = &[
+    ("migration", Some("from-spec"), "migration_specs"),
+    ("seed", None, "seeds"),
+    ("seed", Some("gen"), "tables"),
+    ("db", Some("restore"), "snapshots"),
+    ("cronjobs", Some("remove"), "cronjobs"),
+    ("cronjobs", Some("toggle"), "cronjobs"),
+    ("env", Some("use"), "environments"),
+];
+
+// `blast completions <shell>`
+pub fn generate(shell: &str) -> Result<(), String> {
+    let script = match shell {
+        "bash" => bash(),
+        "zsh" => zsh(),
+        "fish" => fish(),
+        _ => return Err(format!("Unsupported shell '{}'. Use bash, zsh or fish", shell)),
+    };
+    print!("{}", script);
+    Ok(())
+}
+
+// `blast __complete <kind>`: values for dynamic arguments, one per line as `value<TAB>description`.
+// Completion scripts call this on every tab, so failures just print nothing
+pub fn print_values(config: &Config, kind: &str) {
+    let values: Vec<(String, String)> = match kind {
+        "seeds" => {
+            // `seed gen` shares the position with the seed files
+            let mut values = vec![("gen".to_string(), "Generate a seed file from the schema".to_string())];
+            values.extend(crate::database::seed_files(&config.project_dir.join("src/database/seeds")).into_iter().map(|file| (file, String::new())));
+            values
+        }
+        "tables" => crate::file_writer::read(config.project_dir.join(config.codegen().schema_path()))
+            .map(|schema| crate::models::parse_schema_tables(&schema).into_iter().map(|table| (table.name, String::new())).collect())
+            .unwrap_or_default(),
+        "migration_specs" => fs::read_dir(config.project_dir.join(crate::migration_spec::SPECS_DIR))
+            .map(|entries| {
+                let mut names: Vec<String> = entries.flatten().filter_map(|entry| entry.file_name().to_string_lossy().strip_suffix(".toml").map(str::to_string)).collect();
+                names.sort();
+                names.into_iter().map(|name| (name, String::new())).collect()
+            })
+            .unwrap_or_default(),
+        "snapshots" => crate::db_snapshot::snapshots(config).into_iter().filter_map(|snapshot| Some((snapshot.path.file_name()?.to_string_lossy().into_owned(), crate::build::format_size(snapshot.size)))).collect(),
+        "cronjobs" => crate::cronjobs::job_names(config).unwrap_or_default().into_iter().map(|(id, name)| (id.to_string(), name)).collect(),
+        "environments" => config.environment_names().into_iter().map(|name| (name, String::new())).collect(),
+        "connections" => crate::database::get_connection_names(config).into_iter().map(|name| (name, String::new())).collect(),
+        _ => Vec::new(),
+    };
+
+    for (value, description) in values {
+        if description.is_empty() {
+            println!("{}", value);
+        } else {
+            println!("{}\t{}", value, description);
+        }
+    }
+}
+
+fn command_names(commands: &[CommandSpec]) -> String {
+    commands.iter().map(|command| command.name).collect::<Vec<_>>().join(" ")
+}
+
+fn global_flag_names() -> String {
+    global_flags().iter().map(|(flag, _)| *flag).collect::<Vec<_>>().join(" ")
+}
+
+fn value_flag_names(separator: &str) -> String {
+    VALUE_FLAGS.iter().map(|(flag, _, _)| *flag).collect::<Vec<_>>().join(separator)
+}
+
+fn bash() -> String {
+    let commands = commands();
+    let mut script = String::from("# bash completion for blast\n\n_blast_values() {\n    blast __complete \"$1\" 2>/dev/null | cut -f1\n}\n\n_blast() {\n");
+    script.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n    local cmd=\"\" sub=\"\" words=\"\" n=0 i\n\n");
+
+    script.push_str("    case \"$prev\" in\n");
+    for (flag, fixed, dynamic) in VALUE_FLAGS {
+        match (fixed.is_empty(), dynamic) {
+            (_, Some(kind)) => script.push_str(&format!("        {}) COMPREPLY=($(compgen -W \"$(_blast_values {})\" -- \"$cur\")); return ;;\n", flag, kind)),
+            (false, None) => script.push_str(&format!("        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n", flag, fixed)),
+            (true, None) => script.push_str(&format!("        {}) return ;;\n", flag)),
+        }
+    }
+    script.push_str("    esac\n\n");
+
+    // The first two words that aren't options or option values are the command and subcommand
+    script.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n        case \"${COMP_WORDS[i]}\" in\n");
+    script.push_str(&format!("            {}) ((i++)) ;;\n", value_flag_names("|")));
+    script.push_str("            -*) ;;\n            *)\n                ((n++))\n                if ((n == 1)); then cmd=\"${COMP_WORDS[i]}\"; elif ((n == 2)); then sub=\"${COMP_WORDS[i]}\"; fi\n                ;;\n        esac\n    done\n\n");
+
+    script.push_str("    case \"$cmd:$sub:$n\" in\n");
+    for (command, subcommand, kind) in DYNAMIC_ARGS {
+        let n = if subcommand.is_some() { 2 } else { 1 };
+        script.push_str(&format!("        {}:{}:{}) COMPREPLY=($(compgen -W \"$(_blast_values {})\" -- \"$cur\")); return ;;\n", command, subcommand.unwrap_or(""), n, kind));
+    }
+    script.push_str("    esac\n\n");
+
+    script.push_str(&format!("    if ((n == 0)); then\n        COMPREPLY=($(compgen -W \"{} {}\" -- \"$cur\"))\n        return\n    fi\n\n", command_names(&commands), global_flag_names()));
+
+    script.push_str("    case \"$cmd\" in\n");
+    for command in commands.iter().filter(|command| !command.subcommands.is_empty() || !command.flags.is_empty()) {
+        let mut actions = Vec::new();
+        if !command.subcommands.is_empty() {
+            actions.push(format!("((n == 1)) && words=\"{}\"", command.subcommands.join(" ")));
+        }
+        if !command.flags.is_empty() {
+            actions.push(format!("words=\"$words {}\"", command.flags.join(" ")));
+        }
+        script.push_str(&format!("        {}) {} ;;\n", command.name, actions.join("; ")));
+    }
+    script.push_str("    esac\n\n");
+
+    script.push_str(&format!("    COMPREPLY=($(compgen -W \"$words {}\" -- \"$cur\"))\n}}\n\ncomplete -F _blast blast\n", global_flag_names()));
+    script
+}
+
+fn zsh() -> String {
+    let commands = commands();
+    let mut script = String::from("#compdef blast\n\n_blast_values() {\n    local -a values\n    values=(${(f)\"$(blast __complete $1 2>/dev/null)\"})\n    values=(${values//$'\\t'/:})\n    _describe -t values \"$1\" values\n}\n\n_blast() {\n");
+    script.push_str("    local prev=${words[CURRENT-1]} cmd=\"\" sub=\"\" n=0 i\n\n");
+
+    script.push_str("    case $prev in\n");
+    for (flag, fixed, dynamic) in VALUE_FLAGS {
+        match (fixed.is_empty(), dynamic) {
+            (_, Some(kind)) => script.push_str(&format!("        {}) _blast_values {}; return ;;\n", flag, kind)),
+            (false, None) => script.push_str(&format!("        {}) compadd -- {}; return ;;\n", flag, fixed)),
+            (true, None) => script.push_str(&format!("        {}) _message 'value'; return ;;\n", flag)),
+        }
+    }
+    script.push_str("    esac\n\n");
+
+    script.push_str("    for ((i = 2; i < CURRENT; i++)); do\n        case ${words[i]} in\n");
+    script.push_str(&format!("            {}) ((i++)) ;;\n", value_flag_names("|")));
+    script.push_str("            -*) ;;\n            *)\n                ((n++))\n                if ((n == 1)); then cmd=${words[i]}; elif ((n == 2)); then sub=${words[i]}; fi\n                ;;\n        esac\n    done\n\n");
+
+    script.push_str("    local -a flags=(\n");
+    for (flag, about) in global_flags() {
+        script.push_str(&format!("        '{}:{}'\n", flag, quoted(about, "'\\''")));
+    }
+    script.push_str("    )\n\n");
+
+    script.push_str("    if ((n == 0)); then\n        local -a commands=(\n");
+    for command in &commands {
+        script.push_str(&format!("            '{}:{}'\n", command.name, quoted(command.about, "'\\''")));
+    }
+    script.push_str("        )\n        _describe -t commands 'blast command' commands\n        _describe -t flags 'option' flags\n        return\n    fi\n\n");
+
+    script.push_str("    case \"$cmd:$sub:$n\" in\n");
+    for (command, subcommand, kind) in DYNAMIC_ARGS {
+        let n = if subcommand.is_some() { 2 } else { 1 };
+        script.push_str(&format!("        {}:{}:{}) _blast_values {}; return ;;\n", command, subcommand.unwrap_or(""), n, kind));
+    }
+    script.push_str("    esac\n\n");
+
+    script.push_str("    case $cmd in\n");
+    for command in commands.iter().filter(|command| !command.subcommands.is_empty() || !command.flags.is_empty()) {
+        let mut actions = Vec::new();
+        if !command.subcommands.is_empty() {
+            actions.push(format!("((n == 1)) && compadd -- {}", command.subcommands.join(" ")));
+        }
+        if !command.flags.is_empty() {
+            actions.push(format!("compadd -- {}", command.flags.join(" ")));
+        }
+        script.push_str(&format!("        {}) {} ;;\n", command.name, actions.join("; ")));
+    }
+    script.push_str("    esac\n    _describe -t flags 'option' flags\n}\n\n_blast \"$@\"\n");
+    script
+}
+
+fn fish() -> String {
+    let commands = commands();
+    let mut script = String::from("# fish completion for blast\n\nfunction __blast_values\n    blast __complete $argv[1] 2>/dev/null\nend\n\n");
+
+    // Positional words typed so far, skipping options and their values
+    script.push_str("function __blast_args\n    set -l tokens (commandline -opc)\n    set -e tokens[1]\n    set -l skip 0\n    for token in $tokens\n        if test $skip -eq 1\n            set skip 0\n            continue\n        end\n        switch $token\n");
+    script.push_str(&format!("            case {}\n                set skip 1\n", value_flag_names(" ")));
+    script.push_str("            case '-*'\n            case '*'\n                echo $token\n        end\n    end\nend\n\n");
+
+    // `__blast_at N words...`: exactly N positional words so far, starting with the given ones
+    script.push_str("function __blast_at\n    set -l args (__blast_args)\n    test (count $args) -eq $argv[1]; or return 1\n    for i in (seq 2 (count $argv))\n        test \"$args[(math $i - 1)]\" = \"$argv[$i]\"; or return 1\n    end\nend\n\n");
+
+    script.push_str("complete -c blast -f\n\n");
+    for (flag, about) in global_flags() {
+        let spec = match flag.strip_prefix("--") {
+            Some(long) => format!("-l {}", long),
+            None => format!("-o {}", &flag[1..]),
+        };
+        script.push_str(&format!("complete -c blast {}{} -d '{}'\n", spec, fish_flag_value(flag), quoted(about, "\\'")));
+    }
+    script.push('\n');
+
+    for command in &commands {
+        script.push_str(&format!("complete -c blast -n '__blast_at 0' -a {} -d '{}'\n", command.name, quoted(command.about, "\\'")));
+    }
+    script.push('\n');
+
+    for command in &commands {
+        if !command.subcommands.is_empty() {
+            script.push_str(&format!("complete -c blast -n '__blast_at 1 {}' -a '{}'\n", command.name, command.subcommands.join(" ")));
+        }
+        for flag in &command.flags {
+            script.push_str(&format!("complete -c blast -n '__fish_seen_subcommand_from {}' -l {}{}\n", command.name, flag.trim_start_matches("--"), fish_flag_value(flag)));
+        }
+    }
+    script.push('\n');
+
+    for (command, subcommand, kind) in DYNAMIC_ARGS {
+        let (n, words) = match subcommand {
+            Some(subcommand) => (2, format!("{} {}", command, subcommand)),
+            None => (1, command.to_string()),
+        };
+        script.push_str(&format!("complete -c blast -n '__blast_at {} {}' -a '(__blast_values {})'\n", n, words, kind));
+    }
+    script
+}
+
+// `-r -a ...` for flags that take a value
+fn fish_flag_value(flag: &str) -> String {
+    match VALUE_FLAGS.iter().find(|(name, _, _)| *name == flag) {
+        Some((_, _, Some(kind))) => format!(" -r -a '(__blast_values {})'", kind),
+        Some((_, fixed, None)) if !fixed.is_empty() => format!(" -r -a '{}'", fixed),
+        Some(_) => " -r".to_string(),
+        None => String::new(),
+    }
+}
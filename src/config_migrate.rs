@@ -0,0 +1,226 @@
+use std::fs;
+
+use toml::Value;
+use toml_edit::{DocumentMut, Item, Key};
+
+use crate::configs::Config;
+use crate::logger;
+
+// Version of the Catalyst.toml layout this blast expects, stored as [settings] config_version.
+// Bump it together with new STEPS entries whenever a release adds or moves config keys
+pub const CONFIG_VERSION: i64 = 5;
+
+enum Change {
+    // A key renamed within its section
+    Rename { section: &'static str, from: &'static str, to: &'static str },
+    // A section blast reads, written as commented defaults when missing. `requires` limits it to projects
+    // that have that section (asset settings only make sense with an asset pipeline)
+    Section { path: &'static str, requires: Option<&'static str> },
+    // A top-level key, written as a commented default when missing
+    RootKey { key: &'static str, requires: Option<&'static str> },
+}
+
+// What each config version introduced
+const STEPS: &[(i64, Change)] = &[
+    (1, Change::Rename { section: "codegen.structs", from: "ignored_structs", to: "ignore" }),
+    (1, Change::RootKey { key: "public_dir", requires: Some("assets") }),
+    (1, Change::Section { path: "codegen.models", requires: Some("codegen") }),
+    (1, Change::Section { path: "assets.hooks", requires: Some("assets") }),
+    (1, Change::Section { path: "assets.images", requires: Some("assets") }),
+    (1, Change::Section { path: "server", requires: None }),
+    (1, Change::Section { path: "build", requires: None }),
+    (1, Change::Section { path: "logging", requires: None }),
+    (1, Change::Section { path: "database", requires: None }),
+    (1, Change::Section { path: "cronjobs", requires: None }),
+    (2, Change::Section { path: "codegen.schema", requires: Some("codegen") }),
+    (3, Change::Section { path: "assets.scss", requires: Some("assets") }),
+    (4, Change::Section { path: "safety", requires: None }),
+    (5, Change::Section { path: "assets.js", requires: Some("assets") }),
+];
+
+// New name of a key that `blast config migrate` renames, for the unknown-key warnings
+pub fn renamed_key(section: &str, key: &str) -> Option<&'static str> {
+    STEPS.iter().find_map(|(_, change)| match change {
+        Change::Rename { section: renamed_section, from, to } if *renamed_section == section && *from == key => Some(*to),
+        _ => None,
+    })
+}
+
+// A concrete edit for one project's Catalyst.toml
+enum Action {
+    Rename { section: &'static str, from: &'static str, to: &'static str, keep_existing: bool },
+    Comment { label: String, text: String },
+}
+
+impl Action {
+    fn describe(&self) -> String {
+        match self {
+            Action::Rename { section, from, to, keep_existing: false } => format!("rename {}.{} to {}.{}", section, from, section, to),
+            Action::Rename { section, from, to, keep_existing: true } => format!("remove {}.{} ({}.{} is already set and takes precedence)", section, from, section, to),
+            Action::Comment { label, .. } => format!("add {} with commented defaults", label),
+        }
+    }
+}
+
+fn item_at<'a>(doc: &'a DocumentMut, path: &str) -> Option<&'a Item> {
+    path.split('.').try_fold(doc.as_item(), |item, key| item.get(key))
+}
+
+fn file_version(doc: &DocumentMut) -> i64 {
+    item_at(doc, "settings.config_version").and_then(|item| item.as_integer()).unwrap_or(0)
+}
+
+// A section header and its default `key = value` lines, all commented out
+fn commented_section(path: &str, defaults: &Value) -> String {
+    let mut text = format!("# [{}]\n", path);
+    if let Some(table) = defaults.as_table() {
+        for (key, value) in table {
+            if !value.is_table() {
+                text.push_str(&format!("# {} = {}\n", key, value));
+            }
+        }
+    }
+    text
+}
+
+// Edits needed to bring a Catalyst.toml from its config_version up to CONFIG_VERSION
+fn plan(doc: &DocumentMut, defaults: &Value) -> Vec<Action> {
+    let version = file_version(doc);
+    let trailing = doc.trailing().as_str().unwrap_or_default().to_string();
+    let lookup = |path: &str| path.split('.').try_fold(defaults, |value, key| value.get(key));
+    let present = |requires: &Option<&str>| requires.map(|section| item_at(doc, section).is_some()).unwrap_or(true);
+
+    let mut actions = Vec::new();
+    for (step_version, change) in STEPS {
+        if *step_version <= version {
+            continue;
+        }
+
+        match change {
+            Change::Rename { section, from, to } => {
+                let Some(table) = item_at(doc, section).and_then(|item| item.as_table_like()) else {
+                    continue;
+                };
+                if table.contains_key(from) {
+                    actions.push(Action::Rename { section, from, to, keep_existing: table.contains_key(to) });
+                }
+            }
+            Change::Section { path, requires } => {
+                // A previous migration may already have left the commented block behind
+                if item_at(doc, path).is_some() || !present(requires) || trailing.contains(&format!("# [{}]", path)) {
+                    continue;
+                }
+                if let Some(section_defaults) = lookup(path) {
+                    actions.push(Action::Comment { label: format!("[{}]", path), text: commented_section(path, section_defaults) });
+                }
+            }
+            Change::RootKey { key, requires } => {
+                if item_at(doc, key).is_some() || !present(requires) || trailing.contains(&format!("# {} =", key)) {
+                    continue;
+                }
+                if let Some(value) = lookup(key) {
+                    actions.push(Action::Comment { label: key.to_string(), text: format!("# {} = {}\n", key, value) });
+                }
+            }
+        }
+    }
+    actions
+}
+
+fn load(config: &Config) -> Result<DocumentMut, String> {
+    let path = config.project_dir.join("Catalyst.toml");
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    content.parse::<DocumentMut>().map_err(|e| format!("Failed to parse Catalyst.toml: {}", e))
+}
+
+// Read-only check run before commands: one line when Catalyst.toml is behind this blast version
+pub fn warn_if_behind(config: &Config) -> Result<(), String> {
+    let Ok(doc) = load(config) else {
+        return Ok(());
+    };
+    let Ok(defaults) = config.effective_config() else {
+        return Ok(());
+    };
+
+    let pending = plan(&doc, &defaults).len();
+    if pending == 0 {
+        return Ok(());
+    }
+    let plural = if pending == 1 { "" } else { "s" };
+    logger::warning(&format!("Catalyst.toml is behind this blast version ({} change{} pending). Run `blast config migrate` to update it", pending, plural))
+}
+
+// `blast config migrate`: upgrade Catalyst.toml in place, keeping comments and formatting
+pub fn migrate(config: &mut Config) -> Result<(), String> {
+    let mut doc = load(config)?;
+    let defaults = config.effective_config()?;
+    let actions = plan(&doc, &defaults);
+
+    for action in &actions {
+        match action {
+            Action::Rename { section, from, to, keep_existing } => {
+                let item = section.split('.').try_fold(doc.as_item_mut(), |item, key| item.get_mut(key)).ok_or_else(|| format!("[{}] is missing", section))?;
+                // Regular [tables] keep the comments above the key; dotted and inline ones just move the value
+                if let Some(table) = item.as_table_mut() {
+                    // Re-insert every entry so the renamed key keeps its place
+                    let names: Vec<String> = table.iter().map(|(name, _)| name.to_string()).collect();
+                    let entries: Vec<(Key, Item)> = names.iter().filter_map(|name| table.remove_entry(name)).collect();
+                    for (key, value) in entries {
+                        if key.get() != *from {
+                            table.insert_formatted(&key, value);
+                        } else if !keep_existing {
+                            table.insert_formatted(&Key::new(*to).with_leaf_decor(key.leaf_decor().clone()), value);
+                        }
+                    }
+                } else {
+                    let table = item.as_table_like_mut().ok_or_else(|| format!("[{}] is not a section", section))?;
+                    if let (Some(old), false) = (table.remove(from), *keep_existing) {
+                        table.insert(to, old);
+                    }
+                }
+            }
+            Action::Comment { text, .. } => {
+                let mut trailing = doc.trailing().as_str().unwrap_or_default().to_string();
+                if !trailing.is_empty() && !trailing.ends_with('\n') {
+                    trailing.push('\n');
+                }
+                trailing.push('\n');
+                trailing.push_str(text);
+                doc.set_trailing(trailing);
+            }
+        }
+    }
+
+    let version_changed = file_version(&doc) < CONFIG_VERSION;
+    if version_changed {
+        let settings = doc.entry("settings").or_insert_with(toml_edit::table);
+        let settings = settings.as_table_like_mut().ok_or("[settings] is not a section")?;
+        settings.insert("config_version", toml_edit::value(CONFIG_VERSION));
+    }
+
+    if !actions.is_empty() || version_changed {
+        let rendered = doc.to_string();
+        rendered.parse::<Value>().map_err(|e| format!("Migrated Catalyst.toml doesn't parse, nothing written: {}", e))?;
+        crate::file_writer::write(config.project_dir.join("Catalyst.toml"), &rendered)?;
+        *config = crate::configs::get_fresh_config(&config.project_dir)?;
+    }
+
+    for action in &actions {
+        logger::plain(&format!("  - {}", action.describe()))?;
+    }
+
+    // Whatever is left over is something this version of blast doesn't know about. Checked on the migrated
+    // document so --dry-run reports the same thing
+    let mut migrated = config.clone();
+    migrated.assets = doc.to_string().parse::<Value>().map_err(|e| e.to_string())?;
+    let unknown = migrated.unknown_key_warnings();
+    for warning in &unknown {
+        logger::warning(warning)?;
+    }
+
+    if actions.is_empty() && !version_changed {
+        logger::success(&format!("Catalyst.toml is already at config version {}", CONFIG_VERSION))
+    } else {
+        logger::success(&format!("Migrated Catalyst.toml to config version {} ({} change(s))", CONFIG_VERSION, actions.len()))
+    }
+}
@@ -1,14 +1,20 @@
 use crate::logger;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Once;
 use std::time::SystemTime;
 use toml::Value;
+use toml_edit::DocumentMut;
 
 // Type alias for consistent error handling
 type BlastResult = Result<(), String>;
 type ConfigResult<T> = Result<T, String>;
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct Config {
     pub environment: String,
     pub project_name: String,
@@ -18,7 +24,1003 @@ pub struct Config {
     pub last_modified: SystemTime,
 }
 
+// Connection settings from the [database] section of Catalyst.toml
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseSettings {
+    pub connect_timeout_secs: u64,
+    pub retries: u32,
+    pub retry_backoff_ms: u64,
+    // pg_dump into storage/backups before `blast migrate` and `blast refresh`
+    pub snapshot_before_migrate: bool,
+    // [database.connections.<name>]: settings of the DATABASE_URL_<NAME> connections
+    pub connections: BTreeMap<String, ConnectionSettings>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionSettings {
+    // Migrations of this connection; src/database/migrations_<name> when unset
+    pub migrations_dir: Option<String>,
+}
+
+// [codegen] section: where generated code goes and what to skip
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodegenConfig {
+    pub structs_dir: String,
+    pub models_dir: String,
+    // Passed to diesel_ext only when set, so its own default applies otherwise
+    pub schema_file: Option<String>,
+    // Tables no generator writes code for, as case-insensitive globs ("spatial_*"). The ignore lists of
+    // [codegen.structs], [codegen.structs.insertable] and [codegen.models] skip more tables on top of these
+    pub ignore: Vec<String>,
+    pub structs: StructsCodegenConfig,
+    pub models: ModelsCodegenConfig,
+    pub schema: SchemaCodegenConfig,
+    pub forms: FormsCodegenConfig,
+    // Regenerate schema.rs, structs and models after `blast migrate` applies migrations
+    pub auto_regen: bool,
+}
+
+impl Default for CodegenConfig {
+    fn default() -> Self {
+        CodegenConfig {
+            structs_dir: "src/structs".to_string(),
+            models_dir: "src/models/generated".to_string(),
+            schema_file: None,
+            ignore: Vec::new(),
+            structs: StructsCodegenConfig::default(),
+            models: ModelsCodegenConfig::default(),
+            schema: SchemaCodegenConfig::default(),
+            forms: FormsCodegenConfig::default(),
+            auto_regen: true,
+        }
+    }
+}
+
+impl CodegenConfig {
+    pub fn schema_path(&self) -> &str {
+        self.schema_file.as_deref().unwrap_or("src/database/schema.rs")
+    }
+}
+
+// [codegen.structs] section. Tables named after a struct ([codegen.structs.Users]) hold per-struct overrides
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StructsCodegenConfig {
+    pub derives: Vec<String>,
+    pub imports: Vec<String>,
+    pub ignore: Vec<String>,
+    pub insertable: InsertableCodegenConfig,
+    // Kept as raw values so a typo next to them can't break the whole section
+    #[serde(flatten)]
+    pub overrides: BTreeMap<String, Value>,
+}
+
+impl StructsCodegenConfig {
+    // Imports configured for one struct in addition to the global ones
+    pub fn imports_for(&self, struct_name: &str) -> Vec<String> {
+        self.overrides
+            .get(struct_name)
+            .and_then(|o| o.get("imports"))
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    }
+
+    // [codegen.structs.<table or StructName>.validate]: field = "rule" or ["rule", ...]
+    pub fn validations_for(&self, table_name: &str, struct_name: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+        let Some(validate) = [table_name, struct_name].iter().find_map(|name| self.overrides.get(*name).and_then(|o| o.get("validate"))) else {
+            return Ok(Vec::new());
+        };
+        let section = format!("codegen.structs.{}.validate", table_name);
+        let table = validate.as_table().ok_or_else(|| format!("[{}] must be a table of field = \"rule\" entries", section))?;
+
+        let mut rules = Vec::new();
+        for (field, value) in table {
+            let field_rules = match value {
+                Value::String(rule) => vec![rule.clone()],
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map(String::from).ok_or_else(|| format!("[{}] {}: rules must be strings", section, field)))
+                    .collect::<Result<Vec<_>, _>>()?,
+                _ => return Err(format!("[{}] {}: expected a rule name or a list of rule names", section, field)),
+            };
+            rules.push((field.clone(), field_rules));
+        }
+        Ok(rules)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InsertableCodegenConfig {
+    pub ignore: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelsCodegenConfig {
+    pub ignore: Vec<String>,
+    // "diesel-async" to generate models against an async connection pool; blocking Diesel when unset
+    pub async_backend: Option<String>,
+    // Module the models import establish_connection/get_connection from (default crate::database::db).
+    // `blast gen observability` points it at wrappers that count queries
+    pub connection_module: Option<String>,
+    // Tables named after a table ([codegen.models.roles]) hold per-table settings, e.g. cache = { ttl_secs = 300 }
+    #[serde(flatten)]
+    pub overrides: BTreeMap<String, Value>,
+}
+
+impl ModelsCodegenConfig {
+    // Seconds get_by_id/get_all results of `table` stay cached, from [codegen.models.<table>] cache. None when uncached
+    pub fn cache_ttl(&self, table: &str) -> Result<Option<u64>, String> {
+        let Some(cache) = self.overrides.get(table).and_then(|o| o.get("cache")) else {
+            return Ok(None);
+        };
+        match cache.get("ttl_secs").and_then(|ttl| ttl.as_integer()) {
+            Some(ttl) if ttl > 0 => Ok(Some(ttl as u64)),
+            _ => Err(format!("[codegen.models.{}] cache needs a positive ttl_secs, e.g. cache = {{ ttl_secs = 300 }}", table)),
+        }
+    }
+}
+
+// [codegen.forms]: Rocket form structs and Materialize form fields per table, written by `blast gen structs`
+// next to the insertables. Off by default, since API-only projects have no HTML forms
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormsCodegenConfig {
+    pub enabled: bool,
+    pub dir: String,
+}
+
+impl Default for FormsCodegenConfig {
+    fn default() -> Self {
+        FormsCodegenConfig { enabled: false, dir: "src/services/builders/forms".to_string() }
+    }
+}
+
+// [codegen.schema]: which tables `diesel print-schema` writes to schema.rs. Table entries are regular
+// expressions, as diesel takes them; tables left out are skipped by struct and model generation too
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "SchemaCodegenSection")]
+pub struct SchemaCodegenConfig {
+    pub only_tables: Vec<String>,
+    pub except_tables: Vec<String>,
+    // Postgres schemas to print, `public` when empty
+    pub schemas: Vec<String>,
+    // Rust merged into schema.rs after every regeneration, DEFAULT_SCHEMA_PATCH when not set
+    pub patch_file: Option<String>,
+    // only_tables and except_tables, compiled when the section is read since includes() runs for every table
+    #[serde(skip)]
+    only_patterns: Vec<TablePattern>,
+    #[serde(skip)]
+    except_patterns: Vec<TablePattern>,
+}
+
+// [codegen.schema] as written in Catalyst.toml
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct SchemaCodegenSection {
+    only_tables: Vec<String>,
+    except_tables: Vec<String>,
+    schemas: Vec<String>,
+    patch_file: Option<String>,
+}
+
+impl From<SchemaCodegenSection> for SchemaCodegenConfig {
+    fn from(section: SchemaCodegenSection) -> Self {
+        SchemaCodegenConfig {
+            only_patterns: section.only_tables.iter().map(|pattern| TablePattern::new(pattern)).collect(),
+            except_patterns: section.except_tables.iter().map(|pattern| TablePattern::new(pattern)).collect(),
+            only_tables: section.only_tables,
+            except_tables: section.except_tables,
+            schemas: section.schemas,
+            patch_file: section.patch_file,
+        }
+    }
+}
+
+// A table entry: a regular expression, or the exact name when it doesn't compile
+#[derive(Clone, Debug)]
+enum TablePattern {
+    Regex(Regex),
+    Name(String),
+}
+
+impl TablePattern {
+    fn new(pattern: &str) -> Self {
+        Regex::new(pattern).map(TablePattern::Regex).unwrap_or_else(|_| TablePattern::Name(pattern.to_string()))
+    }
+
+    fn matches(&self, table: &str) -> bool {
+        match self {
+            TablePattern::Regex(regex) => regex.is_match(table),
+            TablePattern::Name(name) => name == table,
+        }
+    }
+}
+
+pub const DEFAULT_SCHEMA_PATCH: &str = "src/database/schema_patch.rs";
+
+impl SchemaCodegenConfig {
+    pub fn patch_path(&self) -> &str {
+        self.patch_file.as_deref().unwrap_or(DEFAULT_SCHEMA_PATCH)
+    }
+
+    pub fn filters_tables(&self) -> bool {
+        !self.only_tables.is_empty() || !self.except_tables.is_empty()
+    }
+
+    // Whether a table (optionally schema-qualified) passes only_tables and except_tables
+    pub fn includes(&self, table: &str) -> bool {
+        let name = table.rsplit('.').next().unwrap_or(table);
+        let matches = |patterns: &[TablePattern]| patterns.iter().any(|pattern| pattern.matches(name));
+        (self.only_patterns.is_empty() || matches(&self.only_patterns)) && !matches(&self.except_patterns)
+    }
+}
+
+// The asset pipeline: the top-level public_dir plus the [assets.*] download sections.
+// Missing fields are reported by the downloader that needs them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetsConfig {
+    pub fontawesome: Option<FontAwesomeConfig>,
+    pub materialicons: Option<MaterialIconsConfig>,
+    pub htmx: Option<HtmxConfig>,
+    pub materialize: Option<MaterializeConfig>,
+    pub hooks: AssetHooksConfig,
+    pub images: ImagesConfig,
+    pub scss: ScssConfig,
+    pub js: JsConfig,
+}
+
+// [assets.scss], used by `blast scss`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScssConfig {
+    // Files in src/assets/sass to compile, e.g. ["app.scss", "pages/home.scss"]. Empty compiles every file in
+    // the tree that isn't a partial (_name.scss)
+    pub entrypoints: Vec<String>,
+}
+
+// [assets.js], used by `blast js`. Patterns are globs relative to src/assets/js: `*` stays within a directory,
+// `**` spans any number of them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsConfig {
+    // Files left out of public/ entirely, e.g. ["drafts/**"]. Takes precedence over copy_verbatim
+    pub exclude: Vec<String>,
+    // Copied under their own name with no minification, e.g. already minified ["vendor/**"]
+    pub copy_verbatim: Vec<String>,
+    // Copied to the root of public_dir under their own name instead of js/app/, e.g. ["sw.js"]
+    pub root_files: Vec<String>,
+}
+
+// [assets.images], used by `blast assets optimize`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImagesConfig {
+    // Also write a .webp variant next to every PNG and JPEG
+    pub webp: bool,
+}
+
+// [assets.hooks]: shell commands run before/after an asset step, e.g. pre_js = ["npx esbuild ..."]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetHooksConfig {
+    pub pre_scss: Vec<String>,
+    pub post_scss: Vec<String>,
+    pub pre_css: Vec<String>,
+    pub post_css: Vec<String>,
+    pub pre_js: Vec<String>,
+    pub post_js: Vec<String>,
+}
+
+impl AssetHooksConfig {
+    // Commands of a hook by its key, e.g. "pre_js"
+    pub fn commands(&self, hook: &str) -> &[String] {
+        match hook {
+            "pre_scss" => &self.pre_scss,
+            "post_scss" => &self.post_scss,
+            "pre_css" => &self.pre_css,
+            "post_css" => &self.post_css,
+            "pre_js" => &self.pre_js,
+            "post_js" => &self.post_js,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontAwesomeConfig {
+    pub base_url: Option<String>,
+    pub css: Option<Vec<String>>,
+    pub js: Option<Vec<String>>,
+    pub sprites: Option<Vec<String>>,
+    pub webfonts: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterialIconsConfig {
+    pub base_url: Option<String>,
+    pub woff2: Option<String>,
+    pub ttf: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HtmxConfig {
+    pub js_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterializeConfig {
+    pub repo_url: Option<String>,
+    pub version: Option<String>,
+    pub js_url: Option<String>,
+    // Release archive to download instead of the GitHub tag archive for `version`
+    pub archive_url: Option<String>,
+    // SHA-256 of the archive, hex with an optional "sha256:" prefix
+    pub checksum: Option<String>,
+    // Branch to clone instead of downloading a release
+    pub repo_ref: Option<String>,
+}
+
+// [cronjobs] section
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CronjobsConfig {
+    // Directory (relative to the project) for the cronjob execution and error logs
+    pub dir: String,
+}
+
+impl Default for CronjobsConfig {
+    fn default() -> Self {
+        CronjobsConfig { dir: "storage/cronjobs".to_string() }
+    }
+}
+
+// [server] section, exported to the app as ROCKET_ADDRESS/ROCKET_PORT (next to ROCKET_PUBLIC_DIR from public_dir)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub address: String,
+    pub port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        // Rocket's own defaults
+        ServerConfig { address: "127.0.0.1".to_string(), port: 8000 }
+    }
+}
+
+impl ServerConfig {
+    // Address to point a browser at; wildcard binds are reachable on localhost
+    pub fn url(&self) -> String {
+        let host = match self.address.as_str() {
+            "0.0.0.0" | "::" | "[::]" => "localhost",
+            address => address,
+        };
+        format!("http://{}:{}", host, self.port)
+    }
+}
+
+// [build] section used by `blast build`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildConfig {
+    // Where the deployable bundle is assembled
+    pub dist_dir: String,
+    // Fail the release build on any compiler warning (RUSTFLAGS=-D warnings)
+    pub deny_warnings: bool,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        BuildConfig { dist_dir: "dist".to_string(), deny_warnings: false }
+    }
+}
+
+// [logging] section
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    // Same as always passing --verbose
+    pub verbose: bool,
+}
+
+// [i18n] section: translations under locales/<lang>/, opted into by `blast new --i18n` or `blast gen locale`.
+// The app's fallback language is DEFAULT_LOCALE in .env
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct I18nConfig {
+    pub enabled: bool,
+}
+
+// [workspace] section of a monorepo's root Catalyst.toml: the app directories blast manages from there
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    pub members: Vec<String>,
+}
+
+// [safety] section: which database hosts destructive commands treat as remote
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyConfig {
+    // Hosts (globs allowed) that always need the typed confirmation, even when they look local
+    pub protected_hosts: Vec<String>,
+}
+
+// [environments.<name>] section. Unset keys fall back to what the environment's name implies:
+// prod/production are production environments, anything else (dev, staging, ...) is not
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentConfig {
+    // Run the server from a release build and guard destructive db commands
+    pub production: Option<bool>,
+    // Compress SCSS/CSS output when building assets
+    pub minify: Option<bool>,
+}
+
+// Set by `blast --env <name>` to override settings.environment for a single invocation
+pub const ENVIRONMENT_OVERRIDE_VAR: &str = "BLAST_ENV";
+
+// Environment names end up in file names (.env.<name>) and section headers
+pub fn validate_environment_name(name: &str) -> BlastResult {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid environment name '{}': use letters, digits, '-' or '_'", name));
+    }
+    Ok(())
+}
+
+// Type of a known Catalyst.toml key, used to reject values of the wrong shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyKind {
+    Str,
+    Int,
+    Bool,
+    StrArray,
+    Table,
+}
+
+impl KeyKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            KeyKind::Str => "a string",
+            KeyKind::Int => "an integer",
+            KeyKind::Bool => "a boolean",
+            KeyKind::StrArray => "an array of strings",
+            KeyKind::Table => "a section",
+        }
+    }
+}
+
+// Keys blast understands in each known section, used to flag typos and type-check `blast config set`.
+// The root ("") entry only lists keys blast reads; other top-level sections (sparks, ...) are left alone
+const KNOWN_KEYS: &[(&str, &[(&str, KeyKind)])] = &[
+    ("", &[("public_dir", KeyKind::Str)]),
+    (
+        "settings",
+        &[
+            ("environment", KeyKind::Str),
+            ("show_compiler_warnings", KeyKind::Bool),
+            ("project_name", KeyKind::Str),
+            ("template", KeyKind::Str),
+            ("catalyst_version", KeyKind::Str),
+            ("config_version", KeyKind::Int),
+            ("update_check", KeyKind::Bool),
+        ],
+    ),
+    (
+        "codegen",
+        &[("structs_dir", KeyKind::Str), ("models_dir", KeyKind::Str), ("schema_file", KeyKind::Str), ("ignore", KeyKind::StrArray), ("structs", KeyKind::Table), ("models", KeyKind::Table), ("schema", KeyKind::Table), ("forms", KeyKind::Table), ("auto_regen", KeyKind::Bool)],
+    ),
+    (
+        "codegen.structs",
+        &[("derives", KeyKind::StrArray), ("imports", KeyKind::StrArray), ("ignore", KeyKind::StrArray), ("insertable", KeyKind::Table)],
+    ),
+    ("codegen.structs.insertable", &[("ignore", KeyKind::StrArray)]),
+    ("codegen.models", &[("ignore", KeyKind::StrArray), ("async_backend", KeyKind::Str), ("connection_module", KeyKind::Str)]),
+    ("codegen.forms", &[("enabled", KeyKind::Bool), ("dir", KeyKind::Str)]),
+    ("codegen.schema", &[("only_tables", KeyKind::StrArray), ("except_tables", KeyKind::StrArray), ("schemas", KeyKind::StrArray), ("patch_file", KeyKind::Str)]),
+    ("assets", &[("fontawesome", KeyKind::Table), ("materialicons", KeyKind::Table), ("htmx", KeyKind::Table), ("materialize", KeyKind::Table), ("hooks", KeyKind::Table), ("images", KeyKind::Table), ("scss", KeyKind::Table), ("js", KeyKind::Table)]),
+    (
+        "assets.fontawesome",
+        &[("base_url", KeyKind::Str), ("css", KeyKind::StrArray), ("js", KeyKind::StrArray), ("sprites", KeyKind::StrArray), ("webfonts", KeyKind::StrArray)],
+    ),
+    ("assets.materialicons", &[("base_url", KeyKind::Str), ("woff2", KeyKind::Str), ("ttf", KeyKind::Str)]),
+    ("assets.htmx", &[("js_url", KeyKind::Str)]),
+    (
+        "assets.materialize",
+        &[("repo_url", KeyKind::Str), ("version", KeyKind::Str), ("js_url", KeyKind::Str), ("archive_url", KeyKind::Str), ("checksum", KeyKind::Str), ("repo_ref", KeyKind::Str)],
+    ),
+    ("assets.images", &[("webp", KeyKind::Bool)]),
+    ("assets.scss", &[("entrypoints", KeyKind::StrArray)]),
+    ("assets.js", &[("exclude", KeyKind::StrArray), ("copy_verbatim", KeyKind::StrArray), ("root_files", KeyKind::StrArray)]),
+    (
+        "assets.hooks",
+        &[
+            ("pre_scss", KeyKind::StrArray),
+            ("post_scss", KeyKind::StrArray),
+            ("pre_css", KeyKind::StrArray),
+            ("post_css", KeyKind::StrArray),
+            ("pre_js", KeyKind::StrArray),
+            ("post_js", KeyKind::StrArray),
+        ],
+    ),
+    ("cronjobs", &[("dir", KeyKind::Str)]),
+    ("server", &[("address", KeyKind::Str), ("port", KeyKind::Int)]),
+    ("build", &[("dist_dir", KeyKind::Str), ("deny_warnings", KeyKind::Bool)]),
+    ("logging", &[("verbose", KeyKind::Bool)]),
+    ("i18n", &[("enabled", KeyKind::Bool)]),
+    ("workspace", &[("members", KeyKind::StrArray)]),
+    ("safety", &[("protected_hosts", KeyKind::StrArray)]),
+    ("database", &[("connect_timeout_secs", KeyKind::Int), ("retries", KeyKind::Int), ("retry_backoff_ms", KeyKind::Int), ("snapshot_before_migrate", KeyKind::Bool), ("connections", KeyKind::Table)]),
+];
+
+// Keys of each [environments.<name>] table
+const ENVIRONMENT_KEYS: &[(&str, KeyKind)] = &[("production", KeyKind::Bool), ("minify", KeyKind::Bool)];
+
+// Keys of each [database.connections.<name>] table
+const CONNECTION_KEYS: &[(&str, KeyKind)] = &[("migrations_dir", KeyKind::Str)];
+
+fn unknown_key_message(key: &str, section: &str, names: &[&str]) -> String {
+    match nearest_key(key, names) {
+        Some(suggestion) => format!("Unknown key '{}' in [{}], did you mean '{}'?", key, section, suggestion),
+        None => format!("Unknown key '{}' in [{}] (valid keys: {})", key, section, names.join(", ")),
+    }
+}
+
+// Expected kind of a dotted key. Ok(None) means the key is outside the known schema (e.g. sparks.*);
+// Err means it sits in a known section but isn't a valid key there
+fn key_kind(dotted_key: &str) -> Result<Option<KeyKind>, String> {
+    let (section, key) = dotted_key.rsplit_once('.').unwrap_or(("", dotted_key));
+
+    // Per-struct overrides: [codegen.structs.<StructName>] imports = [...] and its [.validate] table
+    if let Some(struct_name) = section.strip_prefix("codegen.structs.") {
+        if let Some(struct_name) = struct_name.strip_suffix(".validate") {
+            if !struct_name.contains('.') {
+                return Ok(Some(KeyKind::Str));
+            }
+        }
+        if struct_name != "insertable" && !struct_name.contains('.') {
+            return match key {
+                "imports" => Ok(Some(KeyKind::StrArray)),
+                "validate" => Ok(Some(KeyKind::Table)),
+                _ => Err(format!("Unknown key '{}' in [{}]; struct sections only support 'imports' and 'validate'", key, section)),
+            };
+        }
+    }
+
+    // [environments.<name>] tables share one set of keys
+    if section == "environments" {
+        validate_environment_name(key)?;
+        return Ok(Some(KeyKind::Table));
+    }
+    if section == "database.connections" {
+        return Ok(Some(KeyKind::Table));
+    }
+    if section.starts_with("database.connections.") {
+        return match CONNECTION_KEYS.iter().find(|(name, _)| *name == key) {
+            Some((_, kind)) => Ok(Some(*kind)),
+            None => Err(unknown_key_message(key, section, &CONNECTION_KEYS.iter().map(|(name, _)| *name).collect::<Vec<_>>())),
+        };
+    }
+    if let Some(environment) = section.strip_prefix("environments.") {
+        validate_environment_name(environment)?;
+        return match ENVIRONMENT_KEYS.iter().find(|(name, _)| *name == key) {
+            Some((_, kind)) => Ok(Some(*kind)),
+            None => Err(unknown_key_message(key, section, &ENVIRONMENT_KEYS.iter().map(|(name, _)| *name).collect::<Vec<_>>())),
+        };
+    }
+
+    match KNOWN_KEYS.iter().find(|(path, _)| *path == section) {
+        Some((_, keys)) => match keys.iter().find(|(name, _)| *name == key) {
+            Some((_, kind)) => Ok(Some(*kind)),
+            // Unknown root keys may belong to sections blast doesn't manage
+            None if section.is_empty() => Ok(None),
+            None => Err(unknown_key_message(key, section, &keys.iter().map(|(name, _)| *name).collect::<Vec<_>>())),
+        },
+        None => Ok(None),
+    }
+}
+
+fn split_key(dotted_key: &str) -> (Vec<&str>, &str) {
+    let mut parts: Vec<&str> = dotted_key.split('.').collect();
+    let key = parts.pop().unwrap_or_default();
+    (parts, key)
+}
+
+// Walk to the table holding a key, creating missing sections as regular [tables]
+fn table_at<'a>(doc: &'a mut DocumentMut, parents: &[&str]) -> Result<&'a mut dyn toml_edit::TableLike, String> {
+    let mut item = doc.as_item_mut();
+    for (depth, part) in parents.iter().enumerate() {
+        let table = item.as_table_like_mut().ok_or_else(|| format!("{} is not a section", parents[..depth].join(".")))?;
+        if !table.contains_key(part) {
+            let mut new_table = toml_edit::Table::new();
+            new_table.set_implicit(depth + 1 < parents.len());
+            table.insert(part, toml_edit::Item::Table(new_table));
+        }
+        item = table.get_mut(part).ok_or_else(|| format!("Failed to create [{}]", parents[..=depth].join(".")))?;
+    }
+
+    item.as_table_like_mut().ok_or_else(|| format!("{} is not a section", parents.join(".")))
+}
+
+// Convert a command-line value into the TOML type the key expects
+fn parse_value_for(dotted_key: &str, raw: &str, kind: Option<KeyKind>) -> Result<toml_edit::Value, String> {
+    let mismatch = |kind: KeyKind| format!("{} expects {}, got '{}'", dotted_key, kind.describe(), raw);
+
+    match kind {
+        Some(KeyKind::Str) => Ok(raw.into()),
+        Some(KeyKind::Int) => raw.parse::<i64>().map(Into::into).map_err(|_| mismatch(KeyKind::Int)),
+        Some(KeyKind::Bool) => match raw {
+            "true" => Ok(true.into()),
+            "false" => Ok(false.into()),
+            _ => Err(mismatch(KeyKind::Bool)),
+        },
+        // Either a TOML array literal or a comma-separated list
+        Some(KeyKind::StrArray) if raw.trim_start().starts_with('[') => match raw.parse::<toml_edit::Value>() {
+            Ok(toml_edit::Value::Array(array)) if array.iter().all(|item| item.is_str()) => Ok(toml_edit::Value::Array(array)),
+            _ => Err(mismatch(KeyKind::StrArray)),
+        },
+        Some(KeyKind::StrArray) => Ok(toml_edit::Value::Array(raw.split(',').map(str::trim).filter(|item| !item.is_empty()).collect())),
+        Some(KeyKind::Table) => Err(format!("{} is a section; set one of its keys instead", dotted_key)),
+        // Outside the known schema: take TOML literals as-is and anything else as a string
+        None => Ok(raw.parse::<toml_edit::Value>().unwrap_or_else(|_| raw.into())),
+    }
+}
+
+// Levenshtein distance, for "did you mean" suggestions
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// Closest valid key within a small edit distance
+pub fn nearest_key<'a>(key: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates.iter().map(|candidate| (edit_distance(key, candidate), *candidate)).filter(|(distance, _)| *distance <= 3).min_by_key(|(distance, _)| *distance).map(|(_, candidate)| candidate)
+}
+
+static CONFIG_WARNINGS: Once = Once::new();
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        DatabaseSettings {
+            connect_timeout_secs: 5,
+            retries: 3,
+            retry_backoff_ms: 500,
+            snapshot_before_migrate: false,
+            connections: BTreeMap::new(),
+        }
+    }
+}
+
 impl Config {
+    // The defaults commands like `blast new` run with outside a project: dev environment, no Catalyst.toml
+    pub fn outside_project(project_name: &str, project_dir: PathBuf) -> Self {
+        Config {
+            environment: "dev".to_string(),
+            project_name: project_name.to_string(),
+            assets: Value::Table(toml::value::Table::new()),
+            project_dir,
+            show_compiler_warnings: true,
+            last_modified: SystemTime::now(),
+        }
+    }
+
+    // Deserialize one section, warning (and using defaults) when it has the wrong shape
+    fn section<T: for<'de> Deserialize<'de> + Default>(&self, name: &str) -> T {
+        match name.split('.').try_fold(&self.assets, |value, key| value.get(key)) {
+            Some(value) => value.clone().try_into().unwrap_or_else(|e| {
+                logger::warning(&format!("Invalid [{}] section in Catalyst.toml, using defaults: {}", name, e)).unwrap_or_default();
+                T::default()
+            }),
+            None => T::default(),
+        }
+    }
+
+    // [database] section
+    pub fn database_settings(&self) -> DatabaseSettings {
+        self.section("database")
+    }
+
+    // [codegen] section
+    pub fn codegen(&self) -> CodegenConfig {
+        self.section("codegen")
+    }
+
+    // [assets] section
+    pub fn assets_config(&self) -> AssetsConfig {
+        self.section("assets")
+    }
+
+    // Top-level public_dir, where processed and downloaded assets are published
+    pub fn public_dir(&self) -> String {
+        public_dir_of(&self.assets)
+    }
+
+    // [server] section, with the port from `blast run --port` taking precedence
+    pub fn server(&self, port_override: Option<u16>) -> ServerConfig {
+        let mut server: ServerConfig = self.section("server");
+        if let Some(port) = port_override {
+            server.port = port;
+        }
+        server
+    }
+
+    // Catalyst release the project was created from ([settings] catalyst_version), reported by /healthz
+    pub fn catalyst_version(&self) -> Option<String> {
+        self.assets.get("settings").and_then(|settings| settings.get("catalyst_version")).and_then(|version| version.as_str()).map(str::to_string)
+    }
+
+    // [settings] update_check: whether blast looks for a newer release once a day (default true)
+    pub fn update_check(&self) -> bool {
+        self.assets.get("settings").and_then(|settings| settings.get("update_check")).and_then(|value| value.as_bool()).unwrap_or(true)
+    }
+
+    // [build] section
+    pub fn build(&self) -> BuildConfig {
+        self.section("build")
+    }
+
+    // [cronjobs] section
+    pub fn cronjobs(&self) -> CronjobsConfig {
+        self.section("cronjobs")
+    }
+
+    // [logging] section
+    pub fn logging(&self) -> LoggingConfig {
+        self.section("logging")
+    }
+
+    // [i18n] section
+    pub fn i18n(&self) -> I18nConfig {
+        self.section("i18n")
+    }
+
+    // [safety] section
+    pub fn safety(&self) -> SafetyConfig {
+        self.section("safety")
+    }
+
+    // Environments declared under [environments], in file order
+    pub fn declared_environments(&self) -> Vec<String> {
+        self.assets.get("environments").and_then(|value| value.as_table()).map(|table| table.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    // [environments.<active>] with defaults implied by the environment's name filled in
+    pub fn environment_config(&self) -> EnvironmentConfig {
+        let declared: EnvironmentConfig = self.section(&format!("environments.{}", self.environment));
+        let production = declared.production.unwrap_or(matches!(self.environment.as_str(), "prod" | "production"));
+
+        EnvironmentConfig { production: Some(production), minify: Some(declared.minify.unwrap_or(production)) }
+    }
+
+    pub fn is_production(&self) -> bool {
+        self.environment_config().production.unwrap_or(false)
+    }
+
+    pub fn minify_assets(&self) -> bool {
+        self.environment_config().minify.unwrap_or(false)
+    }
+
+    // Unknown keys under known sections, each with the nearest valid key when there is one
+    pub fn unknown_key_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for (path, keys) in KNOWN_KEYS {
+            // The root holds sections blast doesn't know about (sparks, middleware, ...)
+            if path.is_empty() {
+                continue;
+            }
+
+            let section = path.split('.').try_fold(&self.assets, |value, key| value.get(key));
+            let Some(table) = section.and_then(|value| value.as_table()) else {
+                continue;
+            };
+            let known: Vec<&str> = keys.iter().map(|(name, _)| *name).collect();
+
+            for (key, value) in table {
+                // Tables under [codegen.structs] and [codegen.models] are per-struct and per-table overrides
+                if known.contains(&key.as_str()) || (matches!(*path, "codegen.structs" | "codegen.models") && value.is_table()) {
+                    continue;
+                }
+
+                match crate::config_migrate::renamed_key(path, key) {
+                    Some(new_name) => warnings.push(format!("'{}' in [{}] was renamed to '{}'. Run `blast config migrate` to update it", key, path, new_name)),
+                    None => warnings.push(unknown_key_message(key, path, &known)),
+                }
+            }
+        }
+
+        let environment_keys: Vec<&str> = ENVIRONMENT_KEYS.iter().map(|(name, _)| *name).collect();
+        if let Some(environments) = self.assets.get("environments").and_then(|value| value.as_table()) {
+            for (name, table) in environments {
+                let Some(table) = table.as_table() else {
+                    warnings.push(format!("[environments] entry '{}' should be a table like [environments.{}]", name, name));
+                    continue;
+                };
+                for key in table.keys().filter(|key| !environment_keys.contains(&key.as_str())) {
+                    warnings.push(unknown_key_message(key, &format!("environments.{}", name), &environment_keys));
+                }
+            }
+        }
+
+        let connection_keys: Vec<&str> = CONNECTION_KEYS.iter().map(|(name, _)| *name).collect();
+        if let Some(connections) = self.assets.get("database").and_then(|database| database.get("connections")).and_then(|value| value.as_table()) {
+            for (name, table) in connections {
+                let Some(table) = table.as_table() else {
+                    warnings.push(format!("[database.connections] entry '{}' should be a table like [database.connections.{}]", name, name));
+                    continue;
+                };
+                for key in table.keys().filter(|key| !connection_keys.contains(&key.as_str())) {
+                    warnings.push(unknown_key_message(key, &format!("database.connections.{}", name), &connection_keys));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    // The parsed configuration with defaults filled in, laid out like Catalyst.toml
+    pub fn effective_config(&self) -> ConfigResult<Value> {
+        let section = |value: Result<Value, toml::ser::Error>| value.map_err(|e| e.to_string());
+
+        let mut settings = toml::value::Table::new();
+        settings.insert("environment".to_string(), Value::String(self.environment.clone()));
+        settings.insert("show_compiler_warnings".to_string(), Value::Boolean(self.show_compiler_warnings));
+        settings.insert("update_check".to_string(), Value::Boolean(self.update_check()));
+
+        let mut root = toml::value::Table::new();
+        root.insert("public_dir".to_string(), Value::String(self.public_dir()));
+        root.insert("settings".to_string(), Value::Table(settings));
+        root.insert("codegen".to_string(), section(Value::try_from(self.codegen()))?);
+        root.insert("assets".to_string(), section(Value::try_from(self.assets_config()))?);
+        root.insert("cronjobs".to_string(), section(Value::try_from(self.cronjobs()))?);
+        root.insert("server".to_string(), section(Value::try_from(self.server(None)))?);
+        root.insert("build".to_string(), section(Value::try_from(self.build()))?);
+        root.insert("logging".to_string(), section(Value::try_from(self.logging()))?);
+        root.insert("i18n".to_string(), section(Value::try_from(self.i18n()))?);
+        root.insert("safety".to_string(), section(Value::try_from(self.safety()))?);
+        root.insert("database".to_string(), section(Value::try_from(self.database_settings()))?);
+
+        let mut environments = toml::value::Table::new();
+        environments.insert(self.environment.clone(), section(Value::try_from(self.environment_config()))?);
+        root.insert("environments".to_string(), Value::Table(environments));
+
+        Ok(Value::Table(root))
+    }
+
+    // `blast config get KEY`: print a dotted key, falling back to its default
+    pub fn get_key(&self, dotted_key: &str) -> BlastResult {
+        key_kind(dotted_key)?;
+
+        let lookup = |root: &Value| dotted_key.split('.').try_fold(root, |value, key| value.get(key)).cloned();
+        let (value, is_default) = match lookup(&self.assets) {
+            Some(value) => (value, false),
+            None => match lookup(&self.effective_config()?) {
+                Some(value) => (value, true),
+                None => return Err(format!("{} is not set in Catalyst.toml", dotted_key)),
+            },
+        };
+
+        let rendered = match &value {
+            Value::String(text) => text.clone(),
+            Value::Table(_) => toml::to_string_pretty(&value).map_err(|e| e.to_string())?.trim_end().to_string(),
+            other => other.to_string(),
+        };
+
+        if is_default {
+            println!("{} (default)", rendered);
+        } else {
+            println!("{}", rendered);
+        }
+        Ok(())
+    }
+
+    // `blast config set KEY VALUE`: write a dotted key, checking the value against the key's type
+    pub fn set_key(&mut self, dotted_key: &str, raw_value: &str) -> BlastResult {
+        let kind = key_kind(dotted_key)?;
+        let new_value = parse_value_for(dotted_key, raw_value, kind)?;
+
+        self.edit_document(|doc| {
+            let (parents, key) = split_key(dotted_key);
+            let table = table_at(doc, &parents)?;
+            table.insert(key, toml_edit::Item::Value(new_value));
+            Ok(())
+        })?;
+
+        logger::success(&format!("Set {} in Catalyst.toml", dotted_key))
+    }
+
+    // `blast config add KEY VALUE`: append a string to an array key, creating it if needed
+    pub fn add_to_key(&mut self, dotted_key: &str, item: &str) -> BlastResult {
+        match key_kind(dotted_key)? {
+            Some(KeyKind::StrArray) | None => {}
+            Some(kind) => return Err(format!("{} is {}, not an array", dotted_key, kind.describe())),
+        }
+
+        let mut already_present = false;
+        self.edit_document(|doc| {
+            let (parents, key) = split_key(dotted_key);
+            let table = table_at(doc, &parents)?;
+
+            if !table.contains_key(key) {
+                table.insert(key, toml_edit::value(toml_edit::Array::new()));
+            }
+            let array = table.get_mut(key).and_then(|item| item.as_array_mut()).ok_or_else(|| format!("{} is not an array", dotted_key))?;
+
+            already_present = array.iter().any(|existing| existing.as_str() == Some(item));
+            if !already_present {
+                array.push(item);
+            }
+            Ok(())
+        })?;
+
+        if already_present {
+            logger::warning(&format!("{} already contains '{}'", dotted_key, item))
+        } else {
+            logger::success(&format!("Added '{}' to {}", item, dotted_key))
+        }
+    }
+
+    // Edit Catalyst.toml with toml_edit so comments and formatting survive, then pick up the result.
+    // last_modified is refreshed here; other processes (the dashboard) see the new mtime and reload
+    fn edit_document(&mut self, edit: impl FnOnce(&mut DocumentMut) -> BlastResult) -> BlastResult {
+        let config_path = self.project_dir.join("Catalyst.toml");
+        let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+        let mut doc = content.parse::<DocumentMut>().map_err(|e| format!("Failed to parse Catalyst.toml: {}", e))?;
+
+        edit(&mut doc)?;
+
+        let rendered = doc.to_string();
+        let parsed = rendered.parse::<Value>().map_err(|e| e.to_string())?;
+        fs::write(&config_path, rendered).map_err(|e| e.to_string())?;
+
+        self.assets = parsed;
+        if let Ok(modified) = fs::metadata(&config_path).and_then(|metadata| metadata.modified()) {
+            self.last_modified = modified;
+        }
+
+        Ok(())
+    }
+
+    // `blast config validate`: report unknown keys and print the effective configuration
+    pub fn validate(&self) -> Result<(), String> {
+        let warnings = self.unknown_key_warnings();
+        for warning in &warnings {
+            logger::warning(warning)?;
+        }
+
+        println!("# Effective configuration for {} (defaults filled in)", self.project_name);
+        println!("{}", toml::to_string_pretty(&self.effective_config()?).map_err(|e| e.to_string())?);
+
+        if warnings.is_empty() {
+            logger::success("Catalyst.toml is valid")
+        } else {
+            Err(format!("{} unknown key(s) in Catalyst.toml", warnings.len()))
+        }
+    }
+
     // Create a config from path
     pub fn from_path(config_path: &Path, project_dir: &Path) -> ConfigResult<Self> {
         let config_str = fs::read_to_string(config_path).map_err(|e| e.to_string())?;
@@ -30,8 +1032,11 @@ impl Config {
         let cargo: Value = cargo_str.parse::<Value>().map_err(|e| e.to_string())?;
         let project_name = cargo.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()).unwrap_or("Unknown").to_string();
 
-        // Get environment from TOML or default to dev
-        let environment = config_val.get("settings").and_then(|s| s.get("environment")).and_then(|e| e.as_str()).unwrap_or("dev").to_string();
+        // Get environment from `blast --env`, then TOML, or default to dev
+        let environment = match std::env::var(ENVIRONMENT_OVERRIDE_VAR) {
+            Ok(name) if !name.is_empty() => name,
+            _ => config_val.get("settings").and_then(|s| s.get("environment")).and_then(|e| e.as_str()).unwrap_or("dev").to_string(),
+        };
 
         // Get show_compiler_warnings from TOML or default to true
         let show_compiler_warnings = config_val.get("settings").and_then(|s| s.get("show_compiler_warnings")).and_then(|v| v.as_bool()).unwrap_or(true);
@@ -52,7 +1057,8 @@ impl Config {
         let metadata = fs::metadata(&config_path).map_err(|e| e.to_string())?;
 
         if let Ok(modified) = metadata.modified() {
-            if modified > self.last_modified {
+            // Compare for inequality so edits within the same mtime tick aren't missed
+            if modified != self.last_modified {
                 // File has been modified, reload
                 logger::debug("Catalyst.toml modified, reloading configuration")?;
 
@@ -73,51 +1079,46 @@ impl Config {
     }
 
     // Helper method to update a setting in the TOML file
-    fn update_setting<T: Into<toml::Value>>(&mut self, key: &str, value: T) -> BlastResult {
-        // Update the config file
-        let config_path = self.project_dir.join("Catalyst.toml");
-        let toml_content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-
-        // Parse the TOML content
-        let mut parsed_toml: toml::Value = toml_content.parse::<toml::Value>().map_err(|e| e.to_string())?;
-
-        // Make sure the settings table exists
-        if !parsed_toml.as_table().unwrap().contains_key("settings") {
-            parsed_toml.as_table_mut().unwrap().insert("settings".to_string(), toml::Value::Table(toml::value::Table::new()));
-        }
-
-        // Update the setting
-        parsed_toml.as_table_mut().unwrap().get_mut("settings").unwrap().as_table_mut().unwrap().insert(key.to_string(), value.into());
-
-        // Format the TOML content and write it back
-        let formatted_toml = toml::to_string_pretty(&parsed_toml).map_err(|e| e.to_string())?;
-        fs::write(&config_path, formatted_toml).map_err(|e| e.to_string())?;
-
-        // Update last_modified and assets
-        if let Ok(metadata) = fs::metadata(&config_path) {
-            if let Ok(modified) = metadata.modified() {
-                self.last_modified = modified;
-                self.assets = parsed_toml;
-            }
-        }
+    fn update_setting<T: Into<toml_edit::Value>>(&mut self, key: &str, value: T) -> BlastResult {
+        let value = value.into();
+        self.edit_document(|doc| {
+            table_at(doc, &["settings"])?.insert(key, toml_edit::Item::Value(value));
+            Ok(())
+        })
+    }
 
-        Ok(())
+    // dev, any environments declared in [environments], and prod, in toggle order
+    pub fn environment_names(&self) -> Vec<String> {
+        let mut names = vec!["dev".to_string()];
+        names.extend(self.declared_environments().into_iter().filter(|name| !matches!(name.as_str(), "dev" | "development" | "prod" | "production")));
+        names.push("prod".to_string());
+        names
     }
 
-    // Toggle between dev and prod environment
+    // Cycle through dev, any environments declared in [environments], and prod
     pub fn toggle_environment(&mut self) -> Result<(), String> {
-        let old_env = self.environment.clone();
+        let cycle = self.environment_names();
 
-        // Toggle environment
-        self.environment = if self.environment == "dev" { "prod".to_string() } else { "dev".to_string() };
+        let next = match cycle.iter().position(|name| *name == self.environment) {
+            Some(index) => cycle[(index + 1) % cycle.len()].clone(),
+            None => "dev".to_string(),
+        };
 
-        // Update the setting
-        self.update_setting("environment", self.environment.clone())?;
+        let old_env = self.environment.clone();
+        self.use_environment(&next)?;
 
         logger::success(&format!("Environment toggled from {} to {}", old_env, self.environment))?;
         Ok(())
     }
 
+    // Persist the active environment in [settings]
+    pub fn use_environment(&mut self, name: &str) -> BlastResult {
+        validate_environment_name(name)?;
+
+        self.environment = name.to_string();
+        self.update_setting("environment", name)
+    }
+
     // Toggle compiler warnings
     #[allow(dead_code)]
     pub fn toggle_compiler_warnings(&mut self) -> Result<(), String> {
@@ -136,11 +1137,110 @@ impl Config {
     }
 }
 
-// Load project configuration from the current directory
+// The [workspace] of a Catalyst.toml, if it is a workspace root
+fn workspace_of(config_path: &Path) -> Option<WorkspaceConfig> {
+    let content = fs::read_to_string(config_path).ok()?;
+    let value: Value = content.parse().ok()?;
+    value.get("workspace")?.clone().try_into().ok()
+}
+
+fn public_dir_of(catalyst: &Value) -> String {
+    catalyst.get("public_dir").and_then(|v| v.as_str()).unwrap_or("public").to_string()
+}
+
+// public_dir from the Catalyst.toml in `project_dir`, for code that runs without a loaded Config (blast new, repairs)
+pub fn public_dir_at(project_dir: &Path) -> String {
+    let catalyst = fs::read_to_string(project_dir.join("Catalyst.toml")).ok().and_then(|content| content.parse::<Value>().ok());
+    catalyst.map(|catalyst| public_dir_of(&catalyst)).unwrap_or_else(|| "public".to_string())
+}
+
+// A path written against the default public/ moved into `public_dir`: "public/css" is "static/css" with public_dir = "static"
+pub fn in_public_dir(path: &str, public_dir: &str) -> String {
+    match path.strip_prefix("public") {
+        Some("") => public_dir.to_string(),
+        Some(rest) if rest.starts_with('/') => format!("{}{}", public_dir.trim_end_matches('/'), rest),
+        _ => path.to_string(),
+    }
+}
+
+// `dir` or the closest parent with a Catalyst.toml, the way cargo finds Cargo.toml
+pub fn find_project_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find(|ancestor| ancestor.join("Catalyst.toml").is_file()).map(Path::to_path_buf)
+}
+
+// The closest workspace root at or above `dir`, with its member directories
+fn find_workspace(dir: &Path) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    for ancestor in dir.ancestors() {
+        if let Some(workspace) = workspace_of(&ancestor.join("Catalyst.toml")) {
+            let members = workspace.members.iter().map(|member| ancestor.join(member)).collect();
+            return Ok((ancestor.to_path_buf(), members));
+        }
+    }
+    Err("No Catalyst.toml with a [workspace] section in this directory or its parents".to_string())
+}
+
+fn member_name(root: &Path, member: &Path) -> String {
+    member.strip_prefix(root).unwrap_or(member).display().to_string()
+}
+
+// Every member of the workspace around `dir`, in the order [workspace] lists them
+pub fn workspace_members(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let (root, members) = find_workspace(dir)?;
+    if members.is_empty() {
+        return Err(format!("[workspace] in {} has no members", root.join("Catalyst.toml").display()));
+    }
+    for member in &members {
+        if !member.join("Catalyst.toml").is_file() {
+            return Err(format!("Workspace member {} has no Catalyst.toml", member_name(&root, member)));
+        }
+    }
+    Ok(members)
+}
+
+// `-p <name>`: the member listed as `name`, or whose directory is called `name` ("apps/web" or "web")
+pub fn workspace_member(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let (root, members) = find_workspace(dir)?;
+    let name = name.trim_end_matches('/');
+    let matching: Vec<&PathBuf> = members.iter().filter(|member| member_name(&root, member) == name || member.file_name().is_some_and(|file| file == name)).collect();
+    match matching.as_slice() {
+        [member] if member.join("Catalyst.toml").is_file() => Ok(member.to_path_buf()),
+        [member] => Err(format!("Workspace member {} has no Catalyst.toml", member_name(&root, member))),
+        [] => Err(format!("No workspace member '{}'. Members: {}", name, members.iter().map(|member| member_name(&root, member)).collect::<Vec<_>>().join(", "))),
+        _ => Err(format!("'{}' matches more than one workspace member; use its full path from [workspace] members", name)),
+    }
+}
+
+// Load project configuration from the current directory or the closest parent with a Catalyst.toml
 pub fn get_project_info() -> ConfigResult<Config> {
     let cwd = std::env::current_dir().map_err(|e| e.to_string())?;
-    let config_path = cwd.join("Catalyst.toml");
-    Config::from_path(&config_path, &cwd)
+    get_project_info_in(&cwd)
+}
+
+// Load project configuration from `dir` or the closest parent with a Catalyst.toml
+pub fn get_project_info_in(dir: &Path) -> ConfigResult<Config> {
+    // Paths are joined onto project_dir and tools run in it, so it must not depend on the working directory
+    let dir = std::path::absolute(dir).map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
+    let dir = dir.as_path();
+    let project_dir = find_project_root(dir).ok_or_else(|| format!("No Catalyst.toml in {} or any parent directory", dir.display()))?;
+    let config_path = project_dir.join("Catalyst.toml");
+    // A workspace root only lists apps; commands run in one of them
+    if let Some(workspace) = workspace_of(&config_path) {
+        return Err(format!(
+            "{} is a workspace root. Pick a member with -p <member> ({}) or run across all of them with --workspace",
+            project_dir.display(),
+            workspace.members.join(", ")
+        ));
+    }
+    let config = Config::from_path(&config_path, &project_dir)?;
+
+    // Warn about typos once per run rather than on every load
+    CONFIG_WARNINGS.call_once(|| {
+        for warning in config.unknown_key_warnings() {
+            logger::warning(&warning).unwrap_or_default();
+        }
+    });
+
+    Ok(config)
 }
 
 // Load project configuration from a specific path
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use semver::{Op, Version, VersionReq};
+use serde::Deserialize;
+
+// crates.io sparse index, one JSON line per published version
+const INDEX_URL: &str = "https://index.crates.io";
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+// Where a crate's versions live in the sparse index
+fn index_path(crate_name: &str) -> String {
+    let name = crate_name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+// Requirement for the newest stable, non-yanked release, written the way `cargo add` does ("1.4.2" means ^1.4.2)
+pub fn latest_requirement(crate_name: &str) -> Result<String, String> {
+    let offline = |reason: String| {
+        format!(
+            "Couldn't look up the latest version of '{}' on crates.io ({}). The spark doesn't pin a version, so add it yourself with `{} = \"<version>\"` under [dependencies] in Cargo.toml",
+            crate_name, reason, crate_name
+        )
+    };
+
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(10)).build().map_err(|e| offline(e.to_string()))?;
+    let url = format!("{}/{}", INDEX_URL, index_path(crate_name));
+    let response = client.get(&url).send().and_then(|response| response.error_for_status()).map_err(|e| offline(e.to_string()))?;
+    let body = response.text().map_err(|e| offline(e.to_string()))?;
+
+    body.lines()
+        .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+        .filter(|entry| !entry.yanked)
+        .filter_map(|entry| Version::parse(&entry.vers).ok())
+        .filter(|version| version.pre.is_empty())
+        .max()
+        .map(|version| version.to_string())
+        .ok_or_else(|| offline("no stable release published".to_string()))
+}
+
+// Smallest version a comparator allows, or None when it sets no lower bound
+fn lower_bound(comparator: &semver::Comparator) -> Option<Version> {
+    let version = Version::new(comparator.major, comparator.minor.unwrap_or(0), comparator.patch.unwrap_or(0));
+    match comparator.op {
+        Op::Less | Op::LessEq => None,
+        // >1.2 is 1.3.0 and up, >1.2.3 is 1.2.4 and up
+        Op::Greater => Some(match (comparator.minor, comparator.patch) {
+            (None, _) => Version::new(comparator.major + 1, 0, 0),
+            (Some(minor), None) => Version::new(comparator.major, minor + 1, 0),
+            (Some(minor), Some(patch)) => Version::new(comparator.major, minor, patch + 1),
+        }),
+        _ => Some(version),
+    }
+}
+
+// Whether some release satisfies both requirements. Each requirement is a single range, so when the ranges overlap
+// the highest of their lower bounds is in both
+pub fn compatible(existing: &VersionReq, requested: &VersionReq) -> bool {
+    std::iter::once(Version::new(0, 0, 0))
+        .chain(existing.comparators.iter().chain(&requested.comparators).filter_map(lower_bound))
+        .any(|candidate| existing.matches(&candidate) && requested.matches(&candidate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(existing: &str, requested: &str) -> bool {
+        compatible(&VersionReq::parse(existing).unwrap(), &VersionReq::parse(requested).unwrap())
+    }
+
+    #[test]
+    fn overlapping_requirements_are_compatible() {
+        assert!(check("1.0", "1.5"));
+        assert!(check("^1.2.3", "~1.4"));
+        assert!(check("*", "0.3"));
+        assert!(check(">=0.11, <0.13", "0.12.4"));
+        assert!(check(">1.2", "1.3"));
+        assert!(check("<2", "1.9"));
+    }
+
+    #[test]
+    fn disjoint_requirements_conflict() {
+        assert!(!check("0.11", "0.12"));
+        assert!(!check("1", "2.0"));
+        assert!(!check("=1.4.0", "1.4.1"));
+        assert!(!check(">=2.0", "1.9"));
+        assert!(!check(">1.2.3", "=1.2.3"));
+        assert!(!check("<1.5", "1.5"));
+    }
+
+    #[test]
+    fn index_paths_follow_the_sparse_layout() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("io"), "2/io");
+        assert_eq!(index_path("url"), "3/u/url");
+        assert_eq!(index_path("Serde"), "se/rd/serde");
+    }
+}
@@ -1,14 +1,41 @@
 use crate::configs::Config;
+use crate::envfile;
 use crate::logger;
 use chrono::{Local, TimeZone, Utc};
+use console::style;
 use diesel::prelude::*;
 use diesel::sql_query;
 use diesel::sql_types::*;
 use diesel::{PgConnection, RunQueryDsl};
-use dotenv::dotenv;
+use serde::Deserialize;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+const SELECT_JOBS: &str = "SELECT id, name, timer, status, last_run, last_heartbeat, updated_at FROM cronjobs ORDER BY id";
+
+// A scheduler that hasn't written a heartbeat for this long is taken to be stopped
+const HEARTBEAT_STALE_SECS: i64 = 120;
+
+// blast's cronjobs migration. IF NOT EXISTS and the ALTERs adopt tables older blast versions created on the fly
+const MIGRATION_UP: &str = r#"-- Created by blast. `blast cronjobs` and the app's scheduler share this table: blast bumps
+-- updated_at on every change, and the scheduler reloads changed jobs and writes last_heartbeat each time it polls
+CREATE TABLE IF NOT EXISTS cronjobs (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL UNIQUE,
+    timer INT NOT NULL,
+    status VARCHAR NOT NULL DEFAULT 'active',
+    last_run BIGINT
+);
+
+ALTER TABLE cronjobs ADD COLUMN IF NOT EXISTS last_heartbeat BIGINT;
+ALTER TABLE cronjobs ADD COLUMN IF NOT EXISTS updated_at BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT;
+"#;
+
+const MIGRATION_DOWN: &str = "DROP TABLE IF EXISTS cronjobs;\n";
+
+// Job definitions `blast cronjobs import` looks for in the cronjobs directory when no file is given
+const JOB_FILES: &[&str] = &["jobs.toml", "jobs.json"];
 
 // Structure to hold cronjob information for database queries
 #[derive(Debug, QueryableByName)]
@@ -23,6 +50,12 @@ pub struct CronjobInfo {
     pub status: String,
     #[diesel(sql_type = Nullable<BigInt>)]
     pub last_run: Option<i64>,
+    // Written by the app's scheduler each time it polls the table
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub last_heartbeat: Option<i64>,
+    // Bumped by blast on every change, so a heartbeat older than this means the server hasn't reloaded the job yet
+    #[diesel(sql_type = BigInt)]
+    pub updated_at: i64,
 }
 
 // Boolean result type for database queries
@@ -51,7 +84,7 @@ pub struct CronjobDisplay {
 
 // Ensure cronjob directories exist
 fn ensure_cronjob_dirs(config: &Config) -> io::Result<()> {
-    let cronjob_dir = Path::new(&config.project_dir).join("storage").join("cronjobs");
+    let cronjob_dir = config.project_dir.join(config.cronjobs().dir);
     fs::create_dir_all(&cronjob_dir)?;
 
     // Create log files if they don't exist
@@ -122,7 +155,7 @@ fn calc_next_run(last_run: Option<i64>, timer: i32) -> String {
 
 // Log to the cronjob execution log
 fn log_to_execution(config: &Config, message: &str) -> Result<(), String> {
-    let log_path = Path::new(&config.project_dir).join("storage").join("cronjobs").join("execution.log");
+    let log_path = config.project_dir.join(config.cronjobs().dir).join("execution.log");
 
     let mut file = OpenOptions::new().create(true).append(true).open(log_path).map_err(|e| format!("Failed to open execution log: {}", e))?;
 
@@ -134,7 +167,7 @@ fn log_to_execution(config: &Config, message: &str) -> Result<(), String> {
 
 // Log to the cronjob errors log
 fn log_to_errors(config: &Config, message: &str) -> Result<(), String> {
-    let log_path = Path::new(&config.project_dir).join("storage").join("cronjobs").join("errors.log");
+    let log_path = config.project_dir.join(config.cronjobs().dir).join("errors.log");
 
     let mut file = OpenOptions::new().create(true).append(true).open(log_path).map_err(|e| format!("Failed to open errors log: {}", e))?;
 
@@ -144,20 +177,12 @@ fn log_to_errors(config: &Config, message: &str) -> Result<(), String> {
     Ok(())
 }
 
-// Establish database connection using connection string from .env
-fn establish_connection(config: &Config) -> Result<PgConnection, String> {
-    // Change to the project directory to ensure we pick up the correct .env file
-    let current_dir = std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
-    std::env::set_current_dir(&config.project_dir).map_err(|e| format!("Failed to change to project directory: {}", e))?;
-
-    // Load .env file
-    dotenv().ok();
-
-    // Get database URL
-    let database_url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL not found in .env file".to_string())?;
-
-    // Restore original directory
-    std::env::set_current_dir(current_dir).map_err(|e| format!("Failed to restore directory: {}", e))?;
+// Establish database connection using connection string from the project's env files
+pub fn establish_connection(config: &Config) -> Result<PgConnection, String> {
+    // Get database URL from .env, .env.<environment> and .env.local
+    let database_url = envfile::load_effective(&config.project_dir, &config.environment)
+        .get("DATABASE_URL")
+        .ok_or_else(|| "DATABASE_URL not found in env files".to_string())?;
 
     // Connect to database
     PgConnection::establish(&database_url).map_err(|e| format!("Error connecting to database: {}", e))
@@ -176,58 +201,59 @@ fn check_cronjobs_table(conn: &mut PgConnection) -> Result<bool, String> {
     }
 }
 
-// Ensure cronjobs table exists (create if needed)
-fn ensure_cronjobs_table(conn: &mut PgConnection) -> Result<(), String> {
-    // Check if table exists first
+// True once the cronjobs migration has run: the table exists and has the columns the scheduler syncs through.
+// Older blast versions created the table on the fly without them
+fn cronjobs_table_current(conn: &mut PgConnection) -> Result<bool, String> {
+    let results = sql_query("SELECT COUNT(*) = 2 as exists FROM information_schema.columns WHERE table_name = 'cronjobs' AND column_name IN ('last_heartbeat', 'updated_at')")
+        .load::<BoolResult>(conn)
+        .map_err(|e| format!("Failed to check the cronjobs table: {}", e))?;
+    Ok(results.first().is_some_and(|result| result.exists))
+}
+
+// The table is created by blast's cronjobs migration, never on the fly, so blast and the app's scheduler
+// always agree on its shape
+fn require_cronjobs_table(conn: &mut PgConnection) -> Result<(), String> {
     if !check_cronjobs_table(conn)? {
-        // Create table without IF NOT EXISTS since we already checked
-        sql_query(
-            r#"
-            CREATE TABLE cronjobs (
-                id SERIAL PRIMARY KEY,
-                name VARCHAR NOT NULL UNIQUE,
-                timer INT NOT NULL,
-                status VARCHAR NOT NULL DEFAULT 'active',
-                last_run BIGINT
-            );
-            
-            CREATE INDEX idx_cronjobs_name ON cronjobs(name);
-        "#,
-        )
-        .execute(conn)
-        .map_err(|e| format!("Failed to create cronjobs table: {}", e))?;
-
-        // Add example cronjobs (without ON CONFLICT since the table is new)
-        sql_query(
-            r#"
-            INSERT INTO cronjobs (name, timer, status) 
-            VALUES 
-                ('cleanup_temp_files', 3600, 'active'),
-                ('send_digest_emails', 86400, 'active'),
-                ('update_search_index', 43200, 'paused');
-        "#,
-        )
-        .execute(conn)
-        .map_err(|e| format!("Failed to insert example cronjobs: {}", e))?;
+        return Err("There is no cronjobs table yet. Run `blast cronjobs import` to add blast's cronjobs migration and apply it".to_string());
+    }
+    if !cronjobs_table_current(conn)? {
+        return Err("The cronjobs table predates blast's cronjobs migration. Run `blast cronjobs import` to upgrade it".to_string());
     }
-
     Ok(())
 }
 
-// List all cronjobs with their status
-pub fn list_cronjobs(config: &Config) -> Result<(), String> {
-    ensure_cronjob_dirs(config).map_err(|e| format!("Failed to create cronjob directories: {}", e))?;
+// Write blast's cronjobs migration unless one of the migrations already creates the table. Returns the up.sql path
+pub fn install_migration(config: &Config) -> Result<Option<String>, String> {
+    if crate::database::migrations_create_table(config, "cronjobs") {
+        return Ok(None);
+    }
+    let (up_file, _) = crate::database::create_migration_files(config, "create_cronjobs", MIGRATION_UP, MIGRATION_DOWN, None)?;
+    Ok(Some(up_file))
+}
 
-    // Connect to database
+// Every job with its sync state
+pub fn fetch_jobs(config: &Config) -> Result<Vec<CronjobInfo>, String> {
     let mut conn = establish_connection(config)?;
+    require_cronjobs_table(&mut conn)?;
+    sql_query(SELECT_JOBS).load::<CronjobInfo>(&mut conn).map_err(|e| format!("Failed to load cronjobs: {}", e))
+}
 
-    // Ensure cronjobs table exists
-    ensure_cronjobs_table(&mut conn)?;
+// Whether the running server has loaded the job as it is now, from the heartbeat its scheduler writes on every poll
+pub fn server_state(job: &CronjobInfo) -> String {
+    let now = Utc::now().timestamp();
+    match job.last_heartbeat {
+        None => "not seen".to_string(),
+        Some(heartbeat) if now - heartbeat > HEARTBEAT_STALE_SECS => format!("offline ({} ago)", format_duration((now - heartbeat).min(i32::MAX as i64) as i32)),
+        Some(heartbeat) if heartbeat < job.updated_at => "pending".to_string(),
+        Some(_) => "picked up".to_string(),
+    }
+}
+
+// List all cronjobs with their status
+pub fn list_cronjobs(config: &Config) -> Result<(), String> {
+    ensure_cronjob_dirs(config).map_err(|e| format!("Failed to create cronjob directories: {}", e))?;
 
-    // Fetch cronjobs
-    let jobs = sql_query("SELECT id, name, timer, status, last_run FROM cronjobs ORDER BY id")
-        .load::<CronjobInfo>(&mut conn)
-        .map_err(|e| format!("Failed to load cronjobs: {}", e))?;
+    let jobs = fetch_jobs(config)?;
 
     if jobs.is_empty() {
         println!("No scheduled jobs found.");
@@ -235,9 +261,9 @@ pub fn list_cronjobs(config: &Config) -> Result<(), String> {
     }
 
     // Format output
-    println!("╔═════╦════════════════════════╦══════════════╦══════════════╦═══════════════════════╦═══════════════════════╗");
-    println!("║ ID  ║ Name                   ║ Interval     ║ Status       ║ Last Run              ║ Next Run              ║");
-    println!("╠═════╬════════════════════════╬══════════════╬══════════════╬═══════════════════════╬═══════════════════════╣");
+    println!("╔═════╦════════════════════════╦══════════════╦══════════════╦═══════════════════════╦═══════════════════════╦══════════════════════╗");
+    println!("║ ID  ║ Name                   ║ Interval     ║ Status       ║ Last Run              ║ Next Run              ║ Server               ║");
+    println!("╠═════╬════════════════════════╬══════════════╬══════════════╬═══════════════════════╬═══════════════════════╬══════════════════════╣");
 
     for job in &jobs {
         let display = CronjobDisplay {
@@ -251,10 +277,10 @@ pub fn list_cronjobs(config: &Config) -> Result<(), String> {
 
         // Create colorized status while preserving padding
         let status_colorized = match display.status.as_str() {
-            "active" => format!("\x1b[32m{}\x1b[0m", display.status),    // Green for active
-            "paused" => format!("\x1b[33m{}\x1b[0m", display.status),    // Yellow for paused
-            "completed" => format!("\x1b[34m{}\x1b[0m", display.status), // Blue for completed
-            "failed" => format!("\x1b[31m{}\x1b[0m", display.status),    // Red for failed
+            "active" => style(&display.status).green().to_string(),    // Green for active
+            "paused" => style(&display.status).yellow().to_string(),   // Yellow for paused
+            "completed" => style(&display.status).blue().to_string(),  // Blue for completed
+            "failed" => style(&display.status).red().to_string(),      // Red for failed
             _ => display.status.clone(),
         };
 
@@ -264,16 +290,34 @@ pub fn list_cronjobs(config: &Config) -> Result<(), String> {
         let status_padding = " ".repeat(padding_needed);
 
         println!(
-            "║ {:3} ║ {:22} ║ {:12} ║ {}{} ║ {:21} ║ {:21} ║",
-            display.id, display.name, display.interval, status_colorized, status_padding, display.last_run, display.next_run
+            "║ {:3} ║ {:22} ║ {:12} ║ {}{} ║ {:21} ║ {:21} ║ {:20} ║",
+            display.id, display.name, display.interval, status_colorized, status_padding, display.last_run, display.next_run, server_state(job)
         );
     }
 
-    println!("╚═════╩════════════════════════╩══════════════╩══════════════╩═══════════════════════╩═══════════════════════╝");
+    println!("╚═════╩════════════════════════╩══════════════╩══════════════╩═══════════════════════╩═══════════════════════╩══════════════════════╝");
 
     Ok(())
 }
 
+// IDs and names of the scheduled jobs, without creating the table when it doesn't exist yet
+pub fn job_names(config: &Config) -> Result<Vec<(i32, String)>, String> {
+    let mut conn = establish_connection(config)?;
+    if !check_cronjobs_table(&mut conn)? {
+        return Ok(Vec::new());
+    }
+
+    #[derive(QueryableByName)]
+    struct JobName {
+        #[diesel(sql_type = Integer)]
+        id: i32,
+        #[diesel(sql_type = Text)]
+        name: String,
+    }
+    let jobs = sql_query("SELECT id, name FROM cronjobs ORDER BY id").load::<JobName>(&mut conn).map_err(|e| format!("Failed to load cronjobs: {}", e))?;
+    Ok(jobs.into_iter().map(|job| (job.id, job.name)).collect())
+}
+
 // Add a new cronjob
 pub fn add_cronjob(config: &Config, name: &str, interval: i32) -> Result<(), String> {
     ensure_cronjob_dirs(config).map_err(|e| format!("Failed to create cronjob directories: {}", e))?;
@@ -281,23 +325,13 @@ pub fn add_cronjob(config: &Config, name: &str, interval: i32) -> Result<(), Str
     // Connect to database
     let mut conn = establish_connection(config)?;
 
-    // Ensure cronjobs table exists
-    ensure_cronjobs_table(&mut conn)?;
+    require_cronjobs_table(&mut conn)?;
 
-    // Check if job with this name already exists
-    let exists_results = sql_query(&format!("SELECT EXISTS (SELECT 1 FROM cronjobs WHERE name = '{}') as exists", name))
-        .load::<BoolResult>(&mut conn)
-        .map_err(|e| format!("Database error: {}", e))?;
-
-    if !exists_results.is_empty() && exists_results[0].exists {
+    // The scheduler picks the new row up on its next poll
+    if !insert_job(&mut conn, name, interval, "active")? {
         return Err(format!("A job with name '{}' already exists", name));
     }
 
-    // Insert new cronjob
-    sql_query(&format!("INSERT INTO cronjobs (name, timer, status) VALUES ('{}', {}, 'active')", name, interval))
-        .execute(&mut conn)
-        .map_err(|e| format!("Failed to add cronjob: {}", e))?;
-
     // Log action
     log_to_execution(config, &format!("Added new job '{}' with interval of {}", name, format_duration(interval)))?;
 
@@ -313,8 +347,7 @@ pub fn toggle_cronjob(config: &Config, id: i32) -> Result<(), String> {
     // Connect to database
     let mut conn = establish_connection(config)?;
 
-    // Ensure cronjobs table exists
-    ensure_cronjobs_table(&mut conn)?;
+    require_cronjobs_table(&mut conn)?;
 
     // Check if the job exists
     let exists_results = sql_query(&format!("SELECT EXISTS (SELECT 1 FROM cronjobs WHERE id = {}) as exists", id))
@@ -340,7 +373,7 @@ pub fn toggle_cronjob(config: &Config, id: i32) -> Result<(), String> {
     let new_status = if current_status == "active" { "paused" } else { "active" };
 
     // Update status
-    sql_query(&format!("UPDATE cronjobs SET status = '{}' WHERE id = {}", new_status, id))
+    sql_query(format!("UPDATE cronjobs SET status = '{}', updated_at = EXTRACT(EPOCH FROM NOW())::BIGINT WHERE id = {}", new_status, id))
         .execute(&mut conn)
         .map_err(|e| format!("Failed to update job status: {}", e))?;
 
@@ -370,8 +403,7 @@ pub fn remove_cronjob(config: &Config, id: i32) -> Result<(), String> {
     // Connect to database
     let mut conn = establish_connection(config)?;
 
-    // Ensure cronjobs table exists
-    ensure_cronjobs_table(&mut conn)?;
+    require_cronjobs_table(&mut conn)?;
 
     // Check if the job exists
     let exists_results = sql_query(&format!("SELECT EXISTS (SELECT 1 FROM cronjobs WHERE id = {}) as exists", id))
@@ -406,3 +438,98 @@ pub fn remove_cronjob(config: &Config, id: i32) -> Result<(), String> {
     Ok(())
 }
 
+
+// Insert a job unless one with the same name exists. Returns whether it was inserted
+fn insert_job(conn: &mut PgConnection, name: &str, interval: i32, status: &str) -> Result<bool, String> {
+    let inserted = sql_query("INSERT INTO cronjobs (name, timer, status, updated_at) VALUES ($1, $2, $3, EXTRACT(EPOCH FROM NOW())::BIGINT) ON CONFLICT (name) DO NOTHING")
+        .bind::<Text, _>(name)
+        .bind::<Integer, _>(interval)
+        .bind::<Text, _>(status)
+        .execute(conn)
+        .map_err(|e| format!("Failed to add cronjob '{}': {}", name, e))?;
+    Ok(inserted > 0)
+}
+
+// A job from a file-based definition: `interval` or `timer` in seconds, `status` defaulting to active
+#[derive(Deserialize)]
+struct JobDefinition {
+    name: String,
+    #[serde(alias = "timer")]
+    interval: i32,
+    #[serde(default = "default_status")]
+    status: String,
+}
+
+fn default_status() -> String {
+    "active".to_string()
+}
+
+#[derive(Deserialize)]
+struct JobFile {
+    #[serde(default, alias = "job")]
+    jobs: Vec<JobDefinition>,
+}
+
+// jobs.toml holds [[jobs]] tables; jobs.json an array of jobs or {"jobs": [...]}
+fn read_job_file(path: &Path) -> Result<Vec<JobDefinition>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str::<Vec<JobDefinition>>(&content).or_else(|_| serde_json::from_str::<JobFile>(&content).map(|file| file.jobs)).map_err(|e| e.to_string())
+    } else {
+        toml::from_str::<JobFile>(&content).map(|file| file.jobs).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+// `blast cronjobs import [file]`: add and apply the cronjobs migration if needed, then copy job definitions from
+// `file` (or jobs.toml/jobs.json in the cronjobs directory) into the table. Imported files are renamed to *.imported
+pub fn import_jobs(config: &Config, file: Option<String>) -> Result<(), String> {
+    ensure_cronjob_dirs(config).map_err(|e| format!("Failed to create cronjob directories: {}", e))?;
+
+    if let Some(up_file) = install_migration(config)? {
+        logger::success(&format!("Created migration {}", up_file))?;
+    }
+    let mut conn = establish_connection(config)?;
+    if !check_cronjobs_table(&mut conn)? || !cronjobs_table_current(&mut conn)? {
+        logger::info("Applying the cronjobs migration...")?;
+        if !crate::database::migrate(config) {
+            return Err("Migrations failed; the cronjobs table isn't ready. Fix the error above and run `blast cronjobs import` again".to_string());
+        }
+    }
+    require_cronjobs_table(&mut conn)?;
+
+    let files: Vec<PathBuf> = match file {
+        Some(file) => vec![config.project_dir.join(file)],
+        None => {
+            let dir = config.project_dir.join(config.cronjobs().dir);
+            JOB_FILES.iter().map(|name| dir.join(name)).filter(|path| path.exists()).collect()
+        }
+    };
+    if files.is_empty() {
+        return logger::plain(&format!("No job definitions to import (looked for {} in {})", JOB_FILES.join(" and "), config.cronjobs().dir));
+    }
+
+    for path in files {
+        let jobs = read_job_file(&path)?;
+        let (mut imported, mut existing) = (Vec::new(), Vec::new());
+        for job in &jobs {
+            if job.interval <= 0 {
+                return Err(format!("{}: job '{}' needs a positive interval in seconds", path.display(), job.name));
+            }
+            if insert_job(&mut conn, &job.name, job.interval, &job.status)? {
+                imported.push(job.name.as_str());
+            } else {
+                existing.push(job.name.as_str());
+            }
+        }
+
+        let done = PathBuf::from(format!("{}.imported", path.display()));
+        fs::rename(&path, &done).map_err(|e| format!("Imported the jobs but failed to rename {}: {}", path.display(), e))?;
+        log_to_execution(config, &format!("Imported {} job(s) from {}", imported.len(), path.display()))?;
+        logger::success(&format!("Imported {} job(s) from {} and renamed it to {}", imported.len(), path.display(), done.display()))?;
+        if !existing.is_empty() {
+            logger::plain(&format!("Already in the table, left unchanged: {}", existing.join(", ")))?;
+        }
+    }
+    Ok(())
+}
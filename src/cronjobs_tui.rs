@@ -1,19 +1,13 @@
 use crate::configs::Config;
-use crate::cronjobs::{add_cronjob, remove_cronjob, toggle_cronjob, CronjobInfo};
+use crate::cronjobs::{add_cronjob, fetch_jobs, remove_cronjob, server_state, toggle_cronjob, CronjobInfo};
 use crate::logger;
 use chrono::{Local, TimeZone, Utc};
 use console::Style;
 use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input};
-use diesel::prelude::*;
-use diesel::sql_query;
-use diesel::sql_types::*;
-use diesel::{PgConnection, RunQueryDsl};
-use dotenv::dotenv;
 use indicatif::{ProgressBar, ProgressStyle};
 use prettytable::{format, Cell, Row, Table};
 use std::fs::create_dir_all;
 use std::io::Write;
-use std::path::Path;
 use std::thread;
 use std::time::Duration;
 
@@ -66,102 +60,17 @@ fn calc_next_run(last_run: Option<i64>, timer: i32) -> String {
     }
 }
 
-// Establish database connection using connection string from .env
-fn establish_connection(config: &Config) -> Result<PgConnection, String> {
-    // Change to the project directory to ensure we pick up the correct .env file
-    let current_dir = std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
-    std::env::set_current_dir(&config.project_dir).map_err(|e| format!("Failed to change to project directory: {}", e))?;
-
-    // Load .env file
-    dotenv().ok();
-
-    // Get database URL
-    let database_url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL not found in .env file".to_string())?;
-
-    // Restore original directory
-    std::env::set_current_dir(current_dir).map_err(|e| format!("Failed to restore directory: {}", e))?;
-
-    // Connect to database
-    PgConnection::establish(&database_url).map_err(|e| format!("Error connecting to database: {}", e))
-}
-
-// Check if cronjobs table exists
-fn check_cronjobs_table(conn: &mut PgConnection) -> Result<bool, String> {
-    #[derive(Debug, QueryableByName)]
-    struct BoolResult {
-        #[diesel(sql_type = Bool)]
-        pub exists: bool,
-    }
-
-    let results = sql_query("SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = 'cronjobs') as exists")
-        .load::<BoolResult>(conn)
-        .map_err(|e| format!("Failed to check if cronjobs table exists: {}", e))?;
-
-    if results.is_empty() {
-        Ok(false)
-    } else {
-        Ok(results[0].exists)
-    }
-}
-
-// Create cronjobs table if it doesn't exist
-fn ensure_cronjobs_table(conn: &mut PgConnection) -> Result<(), String> {
-    if !check_cronjobs_table(conn)? {
-        sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS cronjobs (
-                id SERIAL PRIMARY KEY,
-                name VARCHAR NOT NULL UNIQUE,
-                timer INT NOT NULL,
-                status VARCHAR NOT NULL DEFAULT 'active',
-                last_run BIGINT
-            );
-            
-            CREATE INDEX IF NOT EXISTS idx_cronjobs_name ON cronjobs(name);
-        "#,
-        )
-        .execute(conn)
-        .map_err(|e| format!("Failed to create cronjobs table: {}", e))?;
-
-        // Add some example cronjobs
-        sql_query(
-            r#"
-            INSERT INTO cronjobs (name, timer, status) 
-            VALUES 
-                ('cleanup_temp_files', 3600, 'active'),
-                ('send_digest_emails', 86400, 'active'),
-                ('update_search_index', 43200, 'paused')
-            ON CONFLICT DO NOTHING;
-        "#,
-        )
-        .execute(conn)
-        .map_err(|e| format!("Failed to insert example cronjobs: {}", e))?;
-    }
-
-    Ok(())
-}
-
 // Ensure cronjob directories exist
 fn ensure_cronjob_dirs(config: &Config) -> Result<(), String> {
-    let cronjob_dir = Path::new(&config.project_dir).join("storage").join("cronjobs");
+    let cronjob_dir = config.project_dir.join(config.cronjobs().dir);
     create_dir_all(&cronjob_dir).map_err(|e| format!("Failed to create cronjob directories: {}", e))?;
     Ok(())
 }
 
-// Fetch all cronjobs from the database
+// Fetch all cronjobs from the table the app's scheduler reads
 fn fetch_cronjobs(config: &Config) -> Result<Vec<CronjobInfo>, String> {
-    ensure_cronjob_dirs(config).map_err(|e| format!("Failed to create cronjob directories: {}", e))?;
-
-    // Connect to database
-    let mut conn = establish_connection(config)?;
-
-    // Ensure cronjobs table exists
-    ensure_cronjobs_table(&mut conn)?;
-
-    // Fetch cronjobs
-    sql_query("SELECT id, name, timer, status, last_run FROM cronjobs ORDER BY id")
-        .load::<CronjobInfo>(&mut conn)
-        .map_err(|e| format!("Failed to load cronjobs: {}", e))
+    ensure_cronjob_dirs(config)?;
+    fetch_jobs(config)
 }
 
 // Function to display the cronjobs table with auto-refresh
@@ -268,6 +177,7 @@ fn render_table(jobs: &[CronjobInfo]) -> Vec<String> {
         Cell::new("Interval"),
         Cell::new("Last Run"),
         Cell::new("Next Run"),
+        Cell::new("Server"),
     ]));
 
     // Add data rows
@@ -298,6 +208,7 @@ fn render_table(jobs: &[CronjobInfo]) -> Vec<String> {
             Cell::new(&interval),
             Cell::new(&last_run),
             Cell::new(&padded_next_run),
+            Cell::new(&server_state(job)),
         ]));
     }
 
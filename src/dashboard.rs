@@ -1,8 +1,10 @@
-use crate::configs::Config;
+use crate::configs::{Config, ServerConfig};
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Arc, Mutex};
@@ -12,6 +14,77 @@ lazy_static! {
     static ref SERVER_PROCESSES: Arc<Mutex<HashMap<String, u32>>> = Arc::new(Mutex::new(HashMap::new()));
 }
 
+// What `blast run` started, kept in storage/blast/server.toml for `blast status`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerState {
+    pub pid: u32,
+    pub environment: String,
+    pub address: String,
+    pub port: u16,
+    pub started_at: String,
+}
+
+impl ServerState {
+    pub fn url(&self) -> String {
+        ServerConfig { address: self.address.clone(), port: self.port }.url()
+    }
+}
+
+fn server_state_path(project_dir: &Path) -> PathBuf {
+    project_dir.join("storage").join("blast").join("server.toml")
+}
+
+pub fn read_server_state(project_dir: &Path) -> Option<ServerState> {
+    let content = fs::read_to_string(server_state_path(project_dir)).ok()?;
+    toml::from_str(&content).ok()
+}
+
+// Refuse to start a server on a port something else is already listening on
+pub fn ensure_port_free(server: &ServerConfig) -> Result<(), String> {
+    if TcpListener::bind((server.address.as_str(), server.port)).is_ok() {
+        return Ok(());
+    }
+
+    match crate::platform::port_owner(server.port) {
+        Some((pid, project)) => Err(format!(
+            "Port {} already in use by PID {} (project {}). Pick another with `blast run --port <port>` or [server] port in Catalyst.toml",
+            server.port, pid, project
+        )),
+        None => Err(format!("Port {} on {} is already in use. Pick another with `blast run --port <port>` or [server] port in Catalyst.toml", server.port, server.address)),
+    }
+}
+
+// `blast status`: report whether the server is running and where, and what blast is doing or last did
+pub fn server_status(config: &Config) -> Result<(), String> {
+    println!("Project:     {}", config.project_name);
+    println!("Environment: {}", config.environment);
+
+    match read_server_state(&config.project_dir) {
+        Some(state) if crate::platform::process_running(state.pid) => {
+            println!("Server:      running at {} (PID {}, {} environment, since {})", state.url(), state.pid, state.environment, state.started_at);
+            // Only apps with `blast gen observability` and METRICS_ENABLED=true answer /metrics
+            if let Some(summary) = crate::observability::traffic_summary(&state.url()) {
+                println!("Traffic:     {}", summary);
+            }
+        }
+        Some(state) => {
+            println!("Server:      not running (last started at {} with PID {})", state.url(), state.pid);
+        }
+        None => {
+            println!("Server:      not running (would listen on {})", config.server(None).url());
+        }
+    }
+
+    if let Some(operation) = crate::operation::read_current(&config.project_dir) {
+        println!("Running:     {} (PID {})", crate::operation::status_line(&operation), operation.pid);
+    }
+    if let Some(operation) = crate::operation::read_last(&config.project_dir) {
+        println!("Last run:    {}", crate::operation::outcome_line(&operation));
+    }
+
+    Ok(())
+}
+
 // Standard log file paths structure
 // Keeping this structure as it may be used in the future
 #[allow(dead_code)]
@@ -25,12 +98,7 @@ pub struct LogPaths {
 
 // Manage external dependencies
 pub fn check_zellij_installed() -> bool {
-    let output = Command::new("which").arg("zellij").output();
-
-    match output {
-        Ok(output) => output.status.success(),
-        Err(_) => false,
-    }
+    crate::platform::command_exists("zellij")
 }
 
 // Create or verify log files and directories
@@ -85,15 +153,40 @@ fn prepare_layout(project_dir: &Path) -> Result<String, String> {
     Ok(layout_path.to_string_lossy().to_string())
 }
 
-// Main function to launch the interactive dashboard
-pub fn launch_dashboard(config: &Config) -> Result<(), String> {
-    use crate::output::{self, OutputMode};
+// Put the server URL into the layout's pane titles. A `{server_url}` placeholder is replaced where present;
+// otherwise pane names mentioning logs get the URL appended. The template itself is left untouched
+fn render_layout(project_dir: &Path, layout_path: &str, server_url: &str) -> Result<String, String> {
+    let layout = fs::read_to_string(layout_path).map_err(|e| e.to_string())?;
 
-    // Check if zellij is installed
-    if !check_zellij_installed() {
-        return Err("Zellij terminal multiplexer is not installed. Install it with 'cargo install zellij'".into());
-    }
+    let rendered = if layout.contains("{server_url}") {
+        layout.replace("{server_url}", server_url)
+    } else {
+        layout
+            .lines()
+            .map(|line| match line.find("name=\"") {
+                Some(start) if line.trim_start().starts_with("pane") => {
+                    let name_start = start + "name=\"".len();
+                    match line[name_start..].find('"') {
+                        Some(len) if line[name_start..name_start + len].to_lowercase().contains("log") => {
+                            format!("{} - {}{}", &line[..name_start + len], server_url, &line[name_start + len..])
+                        }
+                        _ => line.to_string(),
+                    }
+                }
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
 
+    let rendered_path = project_dir.join("storage").join("blast").join("dashboard.rendered.kdl");
+    fs::write(&rendered_path, rendered).map_err(|e| e.to_string())?;
+    Ok(rendered_path.to_string_lossy().to_string())
+}
+
+// Set up the log files and send everything blast logs from here on to storage/blast/blast.log.
+// Both the zellij layout and the built-in dashboard show that file
+pub fn start_dashboard_log(config: &Config) -> Result<(), String> {
     // Get project directory
     let project_dir = &config.project_dir;
 
@@ -112,18 +205,22 @@ pub fn launch_dashboard(config: &Config) -> Result<(), String> {
     writeln!(log_file, "Environment: {}", config.environment).map_err(|e| e.to_string())?;
     writeln!(log_file, "-------------------------------------------").map_err(|e| e.to_string())?;
 
-    // Set output mode to log file for all operations
-    output::set_output_mode(OutputMode::LogFile);
-    output::set_log_file_path(&blast_log_path).map_err(|e| e.to_string())?;
+    // Everything blast logs from here on goes to the dashboard log file
+    crate::logger::init(crate::logger::RuntimeMode::Dashboard, Some(&blast_log_path))
+}
 
-    // Important: Set interactive mode and quiet mode for dashboard
-    std::env::set_var("BLAST_INTERACTIVE", "1");
-    output::set_quiet_mode(true);
+// Main function to launch the interactive dashboard
+pub fn launch_dashboard(config: &Config) -> Result<(), String> {
+    // Check if zellij is installed
+    if !check_zellij_installed() {
+        return Err("Zellij terminal multiplexer is not installed. Install it with 'cargo install zellij' or use 'blast dashboard --builtin'".into());
+    }
 
-    // We don't need the symlink anymore since we're using a single log file
+    let project_dir = &config.project_dir;
+    start_dashboard_log(config)?;
 
     // Prepare layout
-    let layout_path = prepare_layout(project_dir)?;
+    let layout_path = render_layout(project_dir, &prepare_layout(project_dir)?, &config.server(None).url())?;
 
     // Launch zellij with the layout and custom session name
     println!("Launching Blast interactive dashboard...");
@@ -145,21 +242,32 @@ pub fn launch_dashboard(config: &Config) -> Result<(), String> {
     // Create a fresh new session with our layout
     println!("Creating new Blast dashboard session...");
 
-    // Use std::process::Command::exec to replace the current process with Zellij
-    // This way the process won't exit until Zellij exits
-    use std::os::unix::process::CommandExt;
-
     println!("Launching Zellij with layout: {}", layout_path);
+    exec_zellij(project_dir, &layout_path)
+}
 
-    // This will replace the current process with Zellij
-    let err = Command::new("zellij").arg("-l").arg(&layout_path).exec();
+// Replace the current process with Zellij, so blast doesn't exit until Zellij does
+#[cfg(unix)]
+fn exec_zellij(project_dir: &Path, layout_path: &str) -> Result<(), String> {
+    use std::os::unix::process::CommandExt;
 
     // If exec() returns, it means it failed
-    return Err(format!("Failed to exec Zellij: {}", err).into());
+    let err = Command::new("zellij").arg("-l").arg(layout_path).current_dir(project_dir).exec();
+    Err(format!("Failed to exec Zellij: {}", err))
+}
+
+#[cfg(not(unix))]
+fn exec_zellij(project_dir: &Path, layout_path: &str) -> Result<(), String> {
+    let status = Command::new("zellij").arg("-l").arg(layout_path).current_dir(project_dir).status().map_err(|e| format!("Failed to run Zellij: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Zellij exited with {}", status))
+    }
 }
 
 // Start a server process and redirect output to standard log files
-pub fn start_server(config: &Config, is_dev: bool) -> Result<u32, String> {
+pub fn start_server(config: &Config, is_dev: bool, server: &ServerConfig) -> Result<u32, String> {
     // Ensure we're using the latest configuration
     let mut config_clone = config.clone();
     if let Err(e) = config_clone.reload_if_modified() {
@@ -170,7 +278,7 @@ pub fn start_server(config: &Config, is_dev: bool) -> Result<u32, String> {
     let config = &config_clone;
 
     // Kill any existing server process
-    stop_server().map_err(|e| e.to_string())?;
+    stop_server(&config.project_dir).map_err(|e| e.to_string())?;
 
     // Create logs directory if it doesn't exist
     let logs_dir = config.project_dir.join("storage").join("logs");
@@ -188,15 +296,47 @@ pub fn start_server(config: &Config, is_dev: bool) -> Result<u32, String> {
     let _ = OpenOptions::new().create(true).append(true).open(&server_log_path).map_err(|e| e.to_string())?;
     let _ = OpenOptions::new().create(true).append(true).open(&error_log_path).map_err(|e| e.to_string())?;
 
-    // Start the server process using script to preserve colors
-    // but still properly detach it and prevent interactive menu from affecting it
-    let mut cmd = Command::new("bash");
-
     // Determine cargo flags based on config setting
     // We still check environment variable as a way to override the config setting if needed
     let env_setting = std::env::var("BLAST_SHOW_WARNINGS").ok();
     let show_warnings = env_setting.map(|v| v == "true").unwrap_or(config.show_compiler_warnings);
 
+    let pid = spawn_server_process(config, is_dev, show_warnings, server, &server_log_path)?;
+
+    // Store the PID
+    let mut processes = SERVER_PROCESSES.lock().unwrap();
+    processes.insert(config.project_name.clone(), pid);
+
+    // Also store the PID in a file for the interactive menu to access
+    let pid_file_path = blast_dir.join("server.pid");
+    fs::write(&pid_file_path, pid.to_string()).map_err(|e| e.to_string())?;
+
+    // Record where it listens for `blast status`
+    let state = ServerState {
+        pid,
+        environment: config.environment.clone(),
+        address: server.address.clone(),
+        port: server.port,
+        started_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    let state_toml = toml::to_string(&state).map_err(|e| e.to_string())?;
+    fs::write(server_state_path(&config.project_dir), state_toml).map_err(|e| e.to_string())?;
+
+    // Log to the server log
+    let timestamp = chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]");
+    let mut server_log = OpenOptions::new().create(true).append(true).open(&server_log_path).map_err(|e| e.to_string())?;
+
+    writeln!(server_log, "{} Server started with PID: {} ({} environment) at {}", timestamp, pid, config.environment, state.url()).map_err(|e| e.to_string())?;
+
+    Ok(pid)
+}
+
+// Start the server process using script to preserve colors
+// but still properly detach it and prevent interactive menu from affecting it
+#[cfg(not(windows))]
+fn spawn_server_process(config: &Config, is_dev: bool, show_warnings: bool, server: &ServerConfig, _server_log_path: &Path) -> Result<u32, String> {
+    let mut cmd = Command::new("bash");
+
     // Set up command with appropriate flags to control warnings
     let (cargo_env, cargo_flags) = if show_warnings {
         // Show warnings (default behavior)
@@ -218,54 +358,56 @@ pub fn start_server(config: &Config, is_dev: bool) -> Result<u32, String> {
         )
     };
 
-    cmd.args(["-c", &run_command]);
+    cmd.args(["-c", &run_command]).current_dir(&config.project_dir);
+    cmd.env("ROCKET_ADDRESS", &server.address).env("ROCKET_PORT", server.port.to_string()).env("ROCKET_PUBLIC_DIR", config.public_dir()).env("CATALYST_ENV", &config.environment);
+    cmd.envs(crate::rocket_config::server_env(config));
 
     // Capture the PID from the output of the command
     let output = cmd.output().map_err(|e| e.to_string())?;
     let pid_str = String::from_utf8_lossy(&output.stdout);
-    let pid = pid_str.trim().parse::<u32>().map_err(|_| "Failed to parse PID".to_string())?;
-
-    // Store the PID
-    let mut processes = SERVER_PROCESSES.lock().unwrap();
-    processes.insert(config.project_name.clone(), pid);
-
-    // Also store the PID in a file for the interactive menu to access
-    let pid_file_path = blast_dir.join("server.pid");
-    fs::write(&pid_file_path, pid.to_string()).map_err(|e| e.to_string())?;
-
-    // Log to the server log
-    let timestamp = chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]");
-    let mut server_log = OpenOptions::new().create(true).append(true).open(&server_log_path).map_err(|e| e.to_string())?;
+    pid_str.trim().parse::<u32>().map_err(|_| "Failed to parse PID".to_string())
+}
 
-    writeln!(server_log, "{} Server started with PID: {}", timestamp, pid).map_err(|e| e.to_string())?;
+// No `script` or nohup on Windows: run cargo directly with its output appended to the server log
+#[cfg(windows)]
+fn spawn_server_process(config: &Config, is_dev: bool, show_warnings: bool, server: &ServerConfig, server_log_path: &Path) -> Result<u32, String> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("run");
+    if !show_warnings {
+        cmd.arg("--quiet").env("RUSTFLAGS", "-Awarnings");
+    }
+    if !is_dev {
+        cmd.arg("--release");
+    }
+    cmd.args(["--bin", &config.project_name]).current_dir(&config.project_dir);
+    cmd.env("ROCKET_ADDRESS", &server.address).env("ROCKET_PORT", server.port.to_string()).env("ROCKET_PUBLIC_DIR", config.public_dir()).env("CATALYST_ENV", &config.environment);
+    cmd.envs(crate::rocket_config::server_env(config));
 
-    Ok(pid)
+    crate::platform::spawn_logged(&mut cmd, server_log_path)
 }
 
-// Stop a running server process
-pub fn stop_server() -> Result<(), String> {
+// Stop a running server process of the project in `project_dir`
+pub fn stop_server(project_dir: &Path) -> Result<(), String> {
     let mut processes = SERVER_PROCESSES.lock().unwrap();
     let mut stopped = false;
 
     // Iterate through and stop each process
     for (name, pid) in processes.iter() {
         // Try to kill the process
-        let _ = Command::new("kill").arg(pid.to_string()).status();
+        crate::platform::terminate(*pid);
 
         // Give the process a short time to terminate gracefully
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         // Force kill if still running
-        let ps_output = Command::new("ps").arg("-p").arg(pid.to_string()).output().map_err(|e| e.to_string())?;
-
-        if ps_output.status.success() {
-            let _ = Command::new("kill").arg("-9").arg(pid.to_string()).status();
+        if crate::platform::process_running(*pid) {
+            crate::platform::force_kill(*pid);
         }
 
         // Remove PID file if it exists
-        let pid_file_path = "storage/blast/server.pid";
-        if Path::new(&pid_file_path).exists() {
-            let _ = fs::remove_file(pid_file_path);
+        let pid_file_path = project_dir.join("storage/blast/server.pid");
+        if pid_file_path.exists() {
+            let _ = fs::remove_file(&pid_file_path);
         }
 
         println!("Stopped server process '{}' with PID {}", name, pid);
@@ -274,23 +416,26 @@ pub fn stop_server() -> Result<(), String> {
 
     // If no processes were stopped, check for orphaned PID file
     if !stopped {
-        let pid_file_path = "storage/blast/server.pid";
-        if Path::new(pid_file_path).exists() {
+        let pid_file_path = project_dir.join("storage/blast/server.pid");
+        if pid_file_path.exists() {
             // Read the PID from the file
-            if let Ok(pid_str) = fs::read_to_string(pid_file_path) {
+            if let Ok(pid_str) = fs::read_to_string(&pid_file_path) {
                 if let Ok(pid) = pid_str.trim().parse::<u32>() {
                     // Try to kill it
-                    let _ = Command::new("kill").arg(pid.to_string()).status();
+                    crate::platform::terminate(pid);
 
                     println!("Stopped orphaned server process with PID {}", pid);
                 }
             }
 
             // Remove the PID file
-            let _ = fs::remove_file(pid_file_path);
+            let _ = fs::remove_file(&pid_file_path);
         }
     }
 
+    // The recorded port/URL no longer applies
+    let _ = fs::remove_file(server_state_path(project_dir));
+
     processes.clear();
     Ok(())
 }
@@ -0,0 +1,287 @@
+use crate::commands::{self, Command};
+use crate::configs::Config;
+use crate::dependencies::DependencyManager;
+use crate::interactive::{PaletteEntry, PALETTE};
+use crate::logger;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+// How much of the end of a log file the log pane reads
+const TAIL_BYTES: u64 = 64 * 1024;
+const SERVER_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const MIGRATION_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, PartialEq)]
+enum LogPane {
+    Server,
+    Blast,
+}
+
+struct DashboardState {
+    menu: ListState,
+    log_pane: LogPane,
+    server: String,
+    server_running: bool,
+    migrations: String,
+    // What blast is doing right now, or how the last operation ended
+    operation: String,
+    message: Option<String>,
+    migration_check: Option<Receiver<String>>,
+    last_server_check: Option<Instant>,
+    last_migration_check: Option<Instant>,
+}
+
+// Built-in dashboard for when zellij isn't available: server log, command menu and a status bar in one terminal.
+// Commands run through the same palette entries and `commands::execute` as `blast cli` in the zellij layout
+pub fn run_dashboard_tui(config: &mut Config, dep_manager: &mut DependencyManager, notice: Option<&str>) -> Result<(), String> {
+    crate::dashboard::start_dashboard_log(config)?;
+
+    let mut state = DashboardState {
+        menu: ListState::default().with_selected(Some(0)),
+        log_pane: LogPane::Server,
+        server: String::new(),
+        server_running: false,
+        migrations: "checking...".to_string(),
+        operation: String::new(),
+        message: notice.map(str::to_string),
+        migration_check: None,
+        last_server_check: None,
+        last_migration_check: None,
+    };
+
+    let mut terminal = enter_tui()?;
+    let result = event_loop(&mut terminal, config, dep_manager, &mut state);
+    leave_tui(&mut terminal)?;
+    result
+}
+
+fn enter_tui() -> Result<Tui, String> {
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(io::stdout(), EnterAlternateScreen).map_err(|e| e.to_string())?;
+    Terminal::new(CrosstermBackend::new(io::stdout())).map_err(|e| e.to_string())
+}
+
+fn leave_tui(terminal: &mut Tui) -> Result<(), String> {
+    disable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen).map_err(|e| e.to_string())?;
+    terminal.show_cursor().map_err(|e| e.to_string())
+}
+
+fn event_loop(terminal: &mut Tui, config: &mut Config, dep_manager: &mut DependencyManager, state: &mut DashboardState) -> Result<(), String> {
+    loop {
+        let _ = config.reload_if_modified();
+        refresh_status(config, state);
+
+        terminal.draw(|frame| draw(frame, config, state)).map_err(|e| e.to_string())?;
+
+        if !event::poll(Duration::from_millis(250)).map_err(|e| e.to_string())? {
+            continue;
+        }
+        let key = match event::read().map_err(|e| e.to_string())? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        let selected = state.menu.selected().unwrap_or(0);
+        let (entry, wait) = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.menu.select(Some(selected.checked_sub(1).unwrap_or(PALETTE.len() - 1)));
+                continue;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.menu.select(Some((selected + 1) % PALETTE.len()));
+                continue;
+            }
+            KeyCode::Tab => {
+                state.log_pane = if state.log_pane == LogPane::Server { LogPane::Blast } else { LogPane::Server };
+                continue;
+            }
+            // Same commands as :run / :stop and :refresh in the palette
+            KeyCode::Char('s') => (palette_entry(if state.server_running { ":stop" } else { ":run" }), false),
+            KeyCode::Char('r') => (palette_entry(":refresh"), false),
+            KeyCode::Enter => (&PALETTE[selected], true),
+            _ => continue,
+        };
+
+        match run_entry(terminal, config, dep_manager, entry, wait)? {
+            Some(message) => state.message = Some(message),
+            None => return Ok(()),
+        }
+
+        // Commands change what the status bar shows, so check again right away
+        state.last_server_check = None;
+        state.last_migration_check = None;
+    }
+}
+
+fn palette_entry(shortcut: &str) -> &'static PaletteEntry {
+    PALETTE.iter().find(|entry| entry.shortcut == shortcut).unwrap_or(&PALETTE[0])
+}
+
+// Leave the TUI while a command runs, so its prompts and output use the terminal as usual.
+// Returns the message for the status bar, or None when the command asks to exit the dashboard
+fn run_entry(terminal: &mut Tui, config: &mut Config, dep_manager: &mut DependencyManager, entry: &PaletteEntry, wait: bool) -> Result<Option<String>, String> {
+    leave_tui(terminal)?;
+    print!("\x1B[2J\x1B[1;1H");
+    println!("Running {} ({})...", entry.shortcut, entry.description);
+    io::stdout().flush().map_err(|e| e.to_string())?;
+
+    let message = match (entry.command)(config) {
+        Ok(Some(Command::Exit)) => None,
+        Ok(Some(cmd)) => {
+            if let Err(e) = crate::interactive::record_history(config, entry.shortcut) {
+                logger::warning(&format!("Failed to update command history: {}", e))?;
+            }
+            let message = match commands::execute(cmd, config, dep_manager) {
+                Ok(()) => format!("{} finished", entry.shortcut),
+                Err(e) => {
+                    logger::error(&format!("Command failed: {}", e))?;
+                    format!("{} failed: {}", entry.shortcut, e)
+                }
+            };
+            if wait {
+                println!("\nPress Enter to return to the dashboard...");
+                let mut buffer = String::new();
+                io::stdin().read_line(&mut buffer).map_err(|e| e.to_string())?;
+            }
+            Some(message)
+        }
+        Ok(None) => Some(format!("{} cancelled", entry.shortcut)),
+        Err(e) => Some(format!("{} failed: {}", entry.shortcut, e)),
+    };
+
+    *terminal = enter_tui()?;
+    Ok(message)
+}
+
+// Server state is cheap to read; pending migrations need a database round trip, so that runs on a thread
+fn refresh_status(config: &Config, state: &mut DashboardState) {
+    if state.last_server_check.is_none_or(|checked| checked.elapsed() >= SERVER_CHECK_INTERVAL) {
+        state.last_server_check = Some(Instant::now());
+        match crate::dashboard::read_server_state(&config.project_dir) {
+            Some(server) if crate::platform::process_running(server.pid) => {
+                state.server = format!("running at {} (PID {})", server.url(), server.pid);
+                state.server_running = true;
+            }
+            _ => {
+                state.server = format!("stopped ({})", config.server(None).url());
+                state.server_running = false;
+            }
+        }
+    }
+
+    // Written by blast commands running anywhere in the project, including other panes and terminals
+    state.operation = match crate::operation::read_current(&config.project_dir) {
+        Some(operation) => format!("running: {}", crate::operation::status_line(&operation)),
+        None => crate::operation::read_last(&config.project_dir).map(|operation| format!("last: {} {}", operation.name, operation.result.unwrap_or_default())).unwrap_or_default(),
+    };
+
+    if let Some(receiver) = &state.migration_check {
+        if let Ok(migrations) = receiver.try_recv() {
+            state.migrations = migrations;
+            state.migration_check = None;
+        }
+    }
+
+    if state.migration_check.is_none() && state.last_migration_check.is_none_or(|checked| checked.elapsed() >= MIGRATION_CHECK_INTERVAL) {
+        state.last_migration_check = Some(Instant::now());
+        let (sender, receiver) = mpsc::channel();
+        let config = config.clone();
+        thread::spawn(move || {
+            // Every connection with migrations, named once there's more than the default
+            let connections = crate::database::resolve_connections(&config, "all").unwrap_or_else(|_| vec!["default".to_string()]);
+            let statuses: Vec<String> = connections
+                .iter()
+                .map(|connection| {
+                    let status = match crate::database::pending_migrations(&config, connection) {
+                        Ok(0) => "up to date".to_string(),
+                        Ok(pending) => format!("{} pending", pending),
+                        Err(_) => "database unreachable".to_string(),
+                    };
+                    if connections.len() > 1 { format!("{}: {}", connection, status) } else { status }
+                })
+                .collect();
+            let _ = sender.send(statuses.join(", "));
+        });
+        state.migration_check = Some(receiver);
+    }
+}
+
+fn draw(frame: &mut Frame, config: &Config, state: &mut DashboardState) {
+    let [main, message_area, status_area] = Layout::vertical([Constraint::Min(3), Constraint::Length(1), Constraint::Length(1)]).areas(frame.area());
+    let [menu_area, log_area] = Layout::horizontal([Constraint::Length(44), Constraint::Min(20)]).areas(main);
+
+    let items: Vec<ListItem> = PALETTE.iter().map(|entry| ListItem::new(format!("{:<18} {}", entry.shortcut, entry.group))).collect();
+    let menu = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Commands "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(menu, menu_area, &mut state.menu);
+
+    let (title, path) = match state.log_pane {
+        LogPane::Server => (format!(" Server log - {} ", config.server(None).url()), log_path(config, "logs/server.log")),
+        LogPane::Blast => (" Blast log ".to_string(), log_path(config, "blast/blast.log")),
+    };
+    let visible = log_area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = tail(&path, visible).into_iter().map(Line::from).collect();
+    let keys = " enter run | s start/stop server | r refresh | tab switch log | q quit ";
+    frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title).title_bottom(keys)), log_area);
+
+    let selected = state.menu.selected().map(|index| PALETTE[index].description).unwrap_or_default();
+    let message = match &state.message {
+        Some(message) => format!(" {}", message),
+        None => format!(" {}", selected),
+    };
+    frame.render_widget(Paragraph::new(message), message_area);
+
+    let environment_color = if config.is_production() { Color::Green } else { Color::Yellow };
+    let mut status = format!(
+        " [{}] {} | server: {} | migrations: {}",
+        config.environment.to_uppercase(),
+        config.project_name,
+        state.server,
+        state.migrations
+    );
+    if !state.operation.is_empty() {
+        status.push_str(&format!(" | {}", state.operation));
+    }
+    frame.render_widget(Paragraph::new(status).style(Style::default().fg(Color::Black).bg(environment_color)), status_area);
+}
+
+fn log_path(config: &Config, relative: &str) -> PathBuf {
+    config.project_dir.join("storage").join(relative)
+}
+
+// Last `count` lines of a log file, reading only its end so large logs stay cheap to redraw
+fn tail(path: &Path, count: usize) -> Vec<String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return vec![format!("{} doesn't exist yet", path.display())],
+    };
+    let length = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    let _ = file.seek(SeekFrom::Start(length.saturating_sub(TAIL_BYTES)));
+
+    let mut bytes = Vec::new();
+    let _ = file.read_to_end(&mut bytes);
+    let content = String::from_utf8_lossy(&bytes);
+
+    // Server output may redraw progress lines with \r; keep what was drawn last
+    let lines: Vec<String> = content.lines().map(|line| line.split('\r').rfind(|part| !part.is_empty()).unwrap_or_default().to_string()).collect();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
@@ -0,0 +1,285 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect};
+
+use crate::build::format_size;
+use crate::configs::Config;
+use crate::logger;
+
+const BACKUPS_DIR: &str = "storage/backups";
+
+// pg_dump custom format, restored with pg_restore
+const SNAPSHOT_EXTENSION: &str = "dump";
+
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+impl Snapshot {
+    fn name(&self) -> String {
+        self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    fn created(&self) -> String {
+        chrono::DateTime::<chrono::Local>::from(self.modified).format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+}
+
+// `blast db dump`: pg_dump the connection's database into storage/backups/<database>-<time>.dump
+pub fn dump(config: &Config, conn_name: &str, data_only: bool, tables: &[String]) -> Result<PathBuf, String> {
+    dump_labeled(config, conn_name, data_only, tables, None)
+}
+
+fn dump_labeled(config: &Config, conn_name: &str, data_only: bool, tables: &[String], label: Option<&str>) -> Result<PathBuf, String> {
+    ensure_client_installed("pg_dump")?;
+    let database_url = crate::database::resolve_database_url(config, conn_name)?;
+    let database_name = database_name(&database_url)?;
+
+    let backups_dir = config.project_dir.join(BACKUPS_DIR);
+    fs::create_dir_all(&backups_dir).map_err(|e| format!("Failed to create {}: {}", backups_dir.display(), e))?;
+
+    let mut stem = format!("{}-{}", database_name, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    if data_only {
+        stem.push_str("-data");
+    }
+    if let Some(label) = label {
+        stem.push('-');
+        stem.push_str(label);
+    }
+    // Two dumps within a second must not overwrite each other
+    let mut path = backups_dir.join(format!("{}.{}", stem, SNAPSHOT_EXTENSION));
+    let mut copy = 1;
+    while path.exists() {
+        copy += 1;
+        path = backups_dir.join(format!("{}-{}.{}", stem, copy, SNAPSHOT_EXTENSION));
+    }
+
+    let mut args = vec!["--format=custom".to_string(), "--no-owner".to_string(), "--no-privileges".to_string()];
+    if data_only {
+        args.push("--data-only".to_string());
+    }
+    args.extend(tables.iter().map(|table| format!("--table={}", table)));
+    args.extend(["--file".to_string(), path.to_string_lossy().into_owned(), "--dbname".to_string(), database_url]);
+
+    let mut progress = logger::create_progress(None);
+    progress.set_message(&format!("Dumping database {}...", database_name));
+
+    let output = logger::command_output("pg_dump", Command::new("pg_dump").args(&args)).map_err(|e| format!("Failed to run pg_dump: {}", e))?;
+    if !output.status.success() {
+        // Don't leave a truncated file that would show up as a snapshot
+        let _ = fs::remove_file(&path);
+        progress.error("Database dump failed");
+        return Err(format!("pg_dump failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let size = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or_default();
+    progress.success(&format!("Snapshot written to {} ({})", path.strip_prefix(&config.project_dir).unwrap_or(&path).display(), format_size(size)));
+    Ok(path)
+}
+
+// Snapshot before `blast migrate` and `blast refresh` when [database] snapshot_before_migrate is set
+pub fn snapshot_before_migrate(config: &Config) -> Result<(), String> {
+    if !config.database_settings().snapshot_before_migrate {
+        return Ok(());
+    }
+
+    dump_labeled(config, "default", false, &[], Some("pre-migrate"))
+        .map(|_| ())
+        .map_err(|e| format!("Pre-migration snapshot failed, nothing was migrated: {}. Set [database] snapshot_before_migrate = false to migrate without one", e))
+}
+
+// Snapshots under storage/backups, newest first
+pub fn snapshots(config: &Config) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = fs::read_dir(config.project_dir.join(BACKUPS_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().extension().is_some_and(|extension| extension == SNAPSHOT_EXTENSION))
+                .filter_map(|entry| {
+                    let metadata = entry.metadata().ok()?;
+                    Some(Snapshot { path: entry.path(), size: metadata.len(), modified: metadata.modified().ok()? })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.modified));
+    snapshots
+}
+
+// `blast db snapshots`
+pub fn list_snapshots(config: &Config) -> Result<(), String> {
+    let snapshots = snapshots(config);
+    if snapshots.is_empty() {
+        return logger::plain(&format!("No snapshots in {}. Create one with `blast db dump`", BACKUPS_DIR));
+    }
+
+    let width = snapshots.iter().map(|snapshot| snapshot.name().len()).max().unwrap_or_default();
+    for snapshot in &snapshots {
+        logger::plain(&format!("  {:<width$}  {:>9}  {}", snapshot.name(), format_size(snapshot.size), snapshot.created(), width = width))?;
+    }
+    Ok(())
+}
+
+// `blast db restore [file]`: pg_restore a snapshot over the connection's database, picking one when no file is given
+pub fn restore(config: &Config, conn_name: &str, file: Option<&str>, skip_confirm: bool) -> Result<(), String> {
+    ensure_client_installed("pg_restore")?;
+    let database_url = crate::database::resolve_database_url(config, conn_name)?;
+    let database_name = database_name(&database_url)?;
+    crate::db_safety::confirm_destructive(config, "restore into", &database_url, skip_confirm)?;
+
+    let path = match file {
+        Some(file) => resolve_snapshot(config, file)?,
+        None => match pick_snapshot(config)? {
+            Some(path) => path,
+            None => return logger::info("Restore cancelled"),
+        },
+    };
+    let display = path.strip_prefix(&config.project_dir).unwrap_or(&path).display().to_string();
+
+    // Data-only snapshots hold rows but no tables to drop and recreate, so their tables are emptied first
+    let data_tables = data_only_tables(&path)?;
+    if !data_tables.is_empty() {
+        ensure_client_installed("psql")?;
+    }
+
+    if !skip_confirm {
+        let replaced = if data_tables.is_empty() {
+            "Objects in the snapshot replace their current versions".to_string()
+        } else {
+            format!("All rows of {} are replaced", data_tables.join(", "))
+        };
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Restore {} into database {} ({} connection)? {}", display, database_name, conn_name, replaced))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
+            return logger::info("Restore cancelled");
+        }
+    }
+
+    // The server's open connections would block dropping the tables it uses
+    let server_stopped = match crate::dashboard::read_server_state(&config.project_dir) {
+        Some(state) if crate::platform::process_running(state.pid) => {
+            logger::info(&format!("Stopping the server (PID {}) before restoring...", state.pid))?;
+            crate::dashboard::stop_server(&config.project_dir)?;
+            true
+        }
+        _ => false,
+    };
+
+    let mut progress = logger::create_progress(None);
+    progress.set_message(&format!("Restoring {} into {}...", display, database_name));
+
+    let restored = if data_tables.is_empty() {
+        logger::command_output(
+            "pg_restore",
+            Command::new("pg_restore").args(["--clean", "--if-exists", "--no-owner", "--no-privileges", "--single-transaction", "--dbname", &database_url]).arg(&path),
+        )
+        .map_err(|e| format!("Failed to run pg_restore: {}", e))
+        .and_then(|output| if output.status.success() { Ok(()) } else { Err(format!("pg_restore failed: {}", String::from_utf8_lossy(&output.stderr).trim())) })
+    } else {
+        restore_data(&database_url, &path, &data_tables)
+    };
+
+    if let Err(e) = restored {
+        progress.error("Restore failed; the database was left as it was");
+        return Err(e);
+    }
+
+    progress.success(&format!("Restored {} into {}", display, database_name));
+    if server_stopped {
+        logger::plain("The server was stopped for the restore. Start it again with `blast run`")?;
+    }
+    Ok(())
+}
+
+// Tables of a `--data-only` snapshot, schema-qualified; empty when the snapshot has table definitions too
+fn data_only_tables(path: &Path) -> Result<Vec<String>, String> {
+    let output = logger::command_output("pg_restore", Command::new("pg_restore").arg("--list").arg(path)).map_err(|e| format!("Failed to run pg_restore: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("{} isn't a pg_dump snapshot: {}", path.display(), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    // Entries look like `3353; 0 16386 TABLE DATA public users postgres`
+    let listing = String::from_utf8_lossy(&output.stdout).into_owned();
+    let entries: Vec<&str> = listing.lines().filter(|line| !line.starts_with(';')).collect();
+    if entries.iter().any(|line| line.contains(" TABLE ") && !line.contains(" TABLE DATA ")) {
+        return Ok(Vec::new());
+    }
+
+    Ok(entries
+        .iter()
+        .filter_map(|line| {
+            let mut fields = line.split(" TABLE DATA ").nth(1)?.split_whitespace();
+            Some(format!("{}.{}", quote_identifier(fields.next()?), quote_identifier(fields.next()?)))
+        })
+        .collect())
+}
+
+// Empty the tables and load the rows in one transaction, so a failure (say, a table other tables
+// reference) leaves everything as it was
+fn restore_data(database_url: &str, path: &Path, tables: &[String]) -> Result<(), String> {
+    let script = std::env::temp_dir().join(format!("blast-restore-{}.sql", std::process::id()));
+    let output = logger::command_output("pg_restore", Command::new("pg_restore").args(["--data-only", "--no-owner", "--file"]).arg(&script).arg(path))
+        .map_err(|e| format!("Failed to run pg_restore: {}", e))?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&script);
+        return Err(format!("pg_restore failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let truncate = format!("TRUNCATE {}", tables.join(", "));
+    let output = logger::command_output(
+        "psql",
+        Command::new("psql").args(["--quiet", "--no-psqlrc", "--single-transaction", "--set", "ON_ERROR_STOP=1", "--command", &truncate, "--file"]).arg(&script).args(["--dbname", database_url]),
+    );
+    let _ = fs::remove_file(&script);
+
+    let output = output.map_err(|e| format!("Failed to run psql: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("psql failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// A file name under storage/backups or a path to a snapshot anywhere
+fn resolve_snapshot(config: &Config, file: &str) -> Result<PathBuf, String> {
+    let candidates = [config.project_dir.join(BACKUPS_DIR).join(file), PathBuf::from(file)];
+    candidates.into_iter().find(|path| path.is_file()).ok_or_else(|| format!("Snapshot {} not found. List them with `blast db snapshots`", file))
+}
+
+fn pick_snapshot(config: &Config) -> Result<Option<PathBuf>, String> {
+    let snapshots = snapshots(config);
+    if snapshots.is_empty() {
+        return Err(format!("No snapshots in {}. Create one with `blast db dump`", BACKUPS_DIR));
+    }
+
+    let items: Vec<String> = snapshots.iter().map(|snapshot| format!("{}  ({}, {})", snapshot.name(), format_size(snapshot.size), snapshot.created())).collect();
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default()).with_prompt("Snapshot to restore").items(&items).default(0).interact_opt().map_err(|e| e.to_string())?;
+    Ok(selection.map(|index| snapshots[index].path.clone()))
+}
+
+fn database_name(database_url: &str) -> Result<String, String> {
+    let url = url::Url::parse(database_url).map_err(|e| format!("Invalid database URL: {}", e))?;
+    let name = url.path().trim_start_matches('/');
+    if name.is_empty() {
+        return Err("Database URL does not include a database name".to_string());
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_client_installed(tool: &str) -> Result<(), String> {
+    if crate::platform::command_exists(tool) {
+        return Ok(());
+    }
+    Err(format!("{} isn't installed. It comes with the PostgreSQL client tools: {}", tool, crate::platform::postgres_client_hint()))
+}
@@ -1,30 +1,129 @@
 use crate::logger;
-use dialoguer::Confirm;
-use std::collections::HashMap;
+use chrono::Local;
+use regex::Regex;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
 
+// An external tool blast installs with `cargo install` when it's missing or outside the versions blast works with
+pub struct Dependency {
+    // The binary blast runs
+    pub binary: &'static str,
+    // The crate that provides it
+    pub package: &'static str,
+    // Extra `cargo install` arguments, such as features
+    pub install_args: &'static [&'static str],
+    // Versions blast works with, also passed to `cargo install --version`
+    pub requirement: &'static str,
+    // Commands fall back to something else without it
+    pub optional: bool,
+}
+
+pub const DEPENDENCIES: &[Dependency] = &[
+    Dependency { binary: "diesel", package: "diesel_cli", install_args: &["--no-default-features", "--features", "postgres"], requirement: "^2.2", optional: false },
+    Dependency { binary: "zellij", package: "zellij", install_args: &[], requirement: ">=0.40", optional: true },
+    // Still used by `gen structs`
+    Dependency { binary: "diesel_ext", package: "diesel_cli_ext", install_args: &[], requirement: ">=0.3", optional: false },
+];
+
+// What `<binary> --version` says about an installed tool
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolStatus {
+    Missing,
+    // Installed, but its version couldn't be read; blast doesn't second-guess it
+    Unknown,
+    Ok(Version),
+    OutOfRange(Version),
+}
+
+// The last time blast installed a tool, kept per user since tools are installed per user too
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub date: String,
+    pub command: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Dependency {
+    pub fn install_command(&self) -> Vec<String> {
+        let mut command = vec!["cargo".to_string(), "install".to_string(), self.package.to_string()];
+        command.extend(self.install_args.iter().map(|arg| arg.to_string()));
+        command.extend(["--version".to_string(), self.requirement.to_string()]);
+        command
+    }
+
+    // The install command as it would be typed, with requirements like >=0.40 quoted for the shell
+    pub fn install_command_line(&self) -> String {
+        let words: Vec<String> = self.install_command().into_iter().map(|word| if word.contains(['<', '>', ' ', '*']) { format!("'{}'", word) } else { word }).collect();
+        words.join(" ")
+    }
+
+    pub fn status(&self) -> ToolStatus {
+        if !crate::platform::command_exists(self.binary) {
+            return ToolStatus::Missing;
+        }
+        let output = logger::command_output(self.binary, Command::new(self.binary).arg("--version")).ok();
+        let version = output.and_then(|output| parse_version(&String::from_utf8_lossy(&output.stdout)));
+        match (version, VersionReq::parse(self.requirement)) {
+            (Some(version), Ok(requirement)) if requirement.matches(&version) => ToolStatus::Ok(version),
+            (Some(version), Ok(_)) => ToolStatus::OutOfRange(version),
+            _ => ToolStatus::Unknown,
+        }
+    }
+}
+
+// The first x.y.z in a `--version` line, e.g. "diesel 2.2.4" or "zellij 0.41.2"
+fn parse_version(output: &str) -> Option<Version> {
+    let pattern = Regex::new(r"\d+\.\d+\.\d+").unwrap();
+    pattern.find(output).and_then(|found| Version::parse(found.as_str()).ok())
+}
+
+pub fn find(binary: &str) -> Option<&'static Dependency> {
+    DEPENDENCIES.iter().find(|dependency| dependency.binary == binary)
+}
+
+fn installs_path() -> Option<PathBuf> {
+    crate::platform::user_config_dir().map(|dir| dir.join("blast").join("installs.toml"))
+}
+
+// Install records by package
+pub fn install_records() -> BTreeMap<String, InstallRecord> {
+    installs_path().and_then(|path| fs::read_to_string(path).ok()).and_then(|content| toml::from_str(&content).ok()).unwrap_or_default()
+}
+
+// Failing to record an install never fails the install
+fn record_install(package: &str, record: InstallRecord) {
+    let Some(path) = installs_path() else { return };
+    let mut records = install_records();
+    records.insert(package.to_string(), record);
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(content) = toml::to_string_pretty(&records) {
+        let _ = fs::write(path, content);
+    }
+}
+
 // A centralized dependency manager for all external tools
 pub struct DependencyManager {
-    // Map of dependency name to install command
-    dependencies: HashMap<String, String>,
     // Cache of already checked dependencies
     checked: HashMap<String, bool>,
 }
 
+impl Default for DependencyManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DependencyManager {
-    // Create a new dependency manager with pre-configured dependencies
     pub fn new() -> Self {
-        let mut deps = HashMap::new();
-
-        // Register known dependencies with their installation commands
-        deps.insert("zellij".to_string(), "cargo install zellij".to_string());
-        deps.insert("diesel_cli_ext".to_string(), "cargo install diesel_cli_ext".to_string());
-        deps.insert("diesel".to_string(), "cargo install diesel_cli --no-default-features --features postgres".to_string());
-
-        DependencyManager {
-            dependencies: deps,
-            checked: HashMap::new(),
-        }
+        DependencyManager { checked: HashMap::new() }
     }
 
     // Check if a dependency is installed
@@ -34,91 +133,97 @@ impl DependencyManager {
             return *installed;
         }
 
-        // Run "which" command to check if tool is available
-        let output = Command::new("which").arg(name).output();
-        let check_result = match output {
-            Ok(output) => output.status.success(),
-            Err(_) => false,
-        };
+        // `which` on Unix, `where` on Windows
+        let check_result = crate::platform::command_exists(name);
 
         // Cache the result
         self.checked.insert(name.to_string(), check_result);
         check_result
     }
 
-    // Ensure that a dependency is installed
-    pub fn ensure_installed(&mut self, deps: &[&str], prompt: bool) -> Result<(), String> {
-        let mut missing = Vec::new();
-
-        // Find missing dependencies
-        for &dep in deps {
-            if !self.is_installed(dep) {
-                missing.push(dep);
+    // Make sure each tool is installed in a version blast works with. Missing or out-of-range tools are
+    // installed after asking; without a terminal to ask on, the error carries the command to run instead
+    pub fn ensure_installed(&mut self, deps: &[&str]) -> Result<(), String> {
+        for &name in deps {
+            if self.checked.get(name) == Some(&true) {
+                continue;
             }
-        }
-
-        // Return early if all dependencies are installed
-        if missing.is_empty() {
-            return Ok(());
-        }
-
-        // In prompt mode, ask user before installing
-        if prompt {
-            let deps_list = missing.join(", ");
-            let confirm = Confirm::new()
-                .with_prompt(format!("Missing dependencies: {}. Install now?", deps_list))
-                .default(true)
-                .interact()
-                .map_err(|e| e.to_string())?;
-
-            if !confirm {
-                return Err(format!("Required dependencies not installed: {}", deps_list));
+            let Some(dependency) = find(name) else {
+                return Err(format!("No installer found for dependency: {}", name));
+            };
+
+            let problem = match dependency.status() {
+                ToolStatus::Ok(_) | ToolStatus::Unknown => None,
+                ToolStatus::Missing => Some(format!("{} is not installed", dependency.package)),
+                ToolStatus::OutOfRange(version) => Some(format!("{} {} is installed, but blast needs {}", dependency.package, version, dependency.requirement)),
+            };
+            if let Some(problem) = problem {
+                let command = dependency.install_command_line();
+                if !crate::prompt::interactive() {
+                    return Err(format!("{}. Install it with: {}", problem, command));
+                }
+                let confirmed = crate::prompt::confirm(&format!("{}. Run `{}` now?", problem, command), true)?;
+                if !confirmed {
+                    return Err(format!("{}. Install it with: {}", problem, command));
+                }
+                self.install_dependency(dependency)?;
             }
+            self.checked.insert(name.to_string(), true);
         }
-
-        // Install missing dependencies
-        for dep in missing {
-            self.install_dependency(dep)?;
-        }
-
         Ok(())
     }
 
-    // Install a specific dependency
-    fn install_dependency(&mut self, name: &str) -> Result<(), String> {
-        if let Some(install_cmd) = self.dependencies.get(name) {
-            let mut progress = logger::create_progress(None);
-            progress.set_message(&format!("Installing {}...", name));
+    // Run the install recipe, with cargo's output streamed at debug level, then check the binary it left behind
+    fn install_dependency(&mut self, dependency: &Dependency) -> Result<(), String> {
+        let mut progress = logger::create_progress(None);
+        progress.set_message(&format!("Installing {} {}...", dependency.package, dependency.requirement));
 
-            // Split the install command into program and args
-            let parts: Vec<&str> = install_cmd.split_whitespace().collect();
-            if parts.is_empty() {
-                return Err(format!("Invalid install command for {}", name));
-            }
-
-            let program = parts[0];
-            let args = &parts[1..];
-
-            // Run the installation command
-            let status = Command::new(program).args(args).status().map_err(|e| e.to_string())?;
+        let command = dependency.install_command();
+        let output = logger::command_output_at("cargo", Command::new(&command[0]).args(&command[1..]), logger::Verbosity::Debug);
+        let installed = match &output {
+            Ok(output) if output.status.success() => dependency.status(),
+            _ => ToolStatus::Missing,
+        };
+        let version = match &installed {
+            ToolStatus::Ok(version) | ToolStatus::OutOfRange(version) => Some(version.to_string()),
+            _ => None,
+        };
+        let ok = matches!(installed, ToolStatus::Ok(_) | ToolStatus::Unknown);
+        record_install(dependency.package, InstallRecord { date: Local::now().format("%Y-%m-%d %H:%M").to_string(), command: dependency.install_command_line(), ok, version });
 
-            if status.success() {
-                // Mark as installed in the cache
-                self.checked.insert(name.to_string(), true);
-                progress.success(&format!("{} installed successfully", name));
-                Ok(())
-            } else {
-                progress.error(&format!("Failed to install {}", name));
-                Err(format!("Failed to install {}", name))
-            }
-        } else {
-            Err(format!("No installer found for dependency: {}", name))
+        if ok {
+            progress.success(&format!("{} installed successfully", dependency.package));
+            return Ok(());
         }
+        progress.error(&format!("Failed to install {}", dependency.package));
+        let reason = match (output, installed) {
+            (Err(e), _) => format!("cargo didn't run: {}", e),
+            (Ok(output), _) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let tail: Vec<&str> = stderr.lines().rev().take(10).collect();
+                tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+            }
+            (_, ToolStatus::OutOfRange(version)) => format!("`{} --version` still reports {}; check which {} comes first on your PATH", dependency.binary, version, dependency.binary),
+            _ => format!("{} isn't on your PATH after installing; add cargo's bin directory (~/.cargo/bin) to it", dependency.binary),
+        };
+        Err(format!("Failed to install {}: {}", dependency.package, reason))
     }
+}
 
-    // Register a new dependency
-    #[allow(dead_code)]
-    pub fn register(&mut self, name: &str, install_command: &str) {
-        self.dependencies.insert(name.to_string(), install_command.to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_and_install_recipes() {
+        assert_eq!(parse_version("diesel 2.2.4\nSupported Backends: postgres"), Some(Version::new(2, 2, 4)));
+        assert_eq!(parse_version("zellij"), None);
+
+        let diesel = find("diesel").unwrap();
+        assert_eq!(diesel.install_command_line(), "cargo install diesel_cli --no-default-features --features postgres --version ^2.2");
+        assert!(find("zellij").unwrap().install_command_line().ends_with("--version '>=0.40'"));
+        let requirement = VersionReq::parse(diesel.requirement).unwrap();
+        assert!(requirement.matches(&Version::new(2, 3, 0)));
+        assert!(!requirement.matches(&Version::new(2, 1, 0)));
     }
 }
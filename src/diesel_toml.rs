@@ -0,0 +1,141 @@
+use toml_edit::DocumentMut;
+
+use crate::configs::Config;
+use crate::logger;
+
+const FILE: &str = "diesel.toml";
+// Trailing comment on every key blast keeps in line with Catalyst.toml; everything else in the file is left alone
+const MARKER: &str = " # managed by blast (blast config sync-diesel)";
+const HEADER: &str = "# diesel CLI settings. Keys marked \"managed by blast\" follow [codegen] and [database] in Catalyst.toml\n# and are rewritten by `blast config sync-diesel`; anything else you add here is kept\n";
+
+// The diesel.toml keys that follow Catalyst.toml, as (table, key, value). None means the key must be absent
+fn managed(config: &Config) -> Vec<(&'static str, &'static str, Option<toml::Value>)> {
+    let codegen = config.codegen();
+    let strings = |values: &[String]| toml::Value::Array(values.iter().cloned().map(toml::Value::String).collect());
+    // diesel takes one of the two; with both set blast filters print-schema output itself and diesel keeps to only_tables
+    let filter = match (&codegen.schema.only_tables, &codegen.schema.except_tables) {
+        (only, _) if !only.is_empty() => Some(("only_tables", strings(only))),
+        (_, except) if !except.is_empty() => Some(("except_tables", strings(except))),
+        _ => None,
+    };
+    let schema = match codegen.schema.schemas.as_slice() {
+        [schema] => Some(toml::Value::String(schema.clone())),
+        _ => None,
+    };
+    vec![
+        ("print_schema", "file", Some(toml::Value::String(codegen.schema_path().to_string()))),
+        ("print_schema", "filter", filter.map(|(key, tables)| toml::Value::Table(toml::map::Map::from_iter([(key.to_string(), tables)])))),
+        ("print_schema", "schema", schema),
+        ("migrations_directory", "dir", Some(toml::Value::String(crate::database::connection_migrations_dir(config, "default")))),
+    ]
+}
+
+fn describe(value: Option<&toml::Value>) -> String {
+    value.map_or_else(|| "unset".to_string(), |value| value.to_string())
+}
+
+// "print_schema.file is \"src/schema.rs\" but Catalyst.toml expects \"src/database/schema.rs\"", one per key
+fn differences(content: &str, config: &Config) -> Vec<String> {
+    let Ok(current) = content.parse::<toml::Table>() else {
+        return vec![format!("{} isn't valid TOML", FILE)];
+    };
+    managed(config)
+        .into_iter()
+        .filter_map(|(table, key, expected)| {
+            let actual = current.get(table).and_then(|table| table.get(key));
+            (actual != expected.as_ref()).then(|| format!("{}.{} is {} but Catalyst.toml expects {}", table, key, describe(actual), describe(expected.as_ref())))
+        })
+        .collect()
+}
+
+// diesel.toml with the managed keys set from Catalyst.toml
+fn synced(content: &str, config: &Config) -> Result<String, String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("Failed to parse {}: {}", FILE, e))?;
+    if content.trim().is_empty() {
+        doc.decor_mut().set_prefix(HEADER);
+    }
+    for (table, key, expected) in managed(config) {
+        let section = doc.entry(table).or_insert_with(toml_edit::table).as_table_mut().ok_or_else(|| format!("[{}] in {} is not a table", table, FILE))?;
+        let Some(expected) = expected else {
+            section.remove(key);
+            continue;
+        };
+        let mut value: toml_edit::Value = expected.to_string().parse().map_err(|e| format!("Failed to write {}.{}: {}", table, key, e))?;
+        value.decor_mut().set_suffix(MARKER);
+        section.insert(key, toml_edit::Item::Value(value));
+    }
+    Ok(doc.to_string())
+}
+
+// Write the managed keys into the project's diesel.toml, creating it if needed. Returns what changed
+pub fn sync(config: &Config) -> Result<Vec<String>, String> {
+    let path = config.project_dir.join(FILE);
+    let content = crate::file_writer::read(&path).unwrap_or_default();
+    let changes = if content.trim().is_empty() { vec![format!("created {}", FILE)] } else { differences(&content, config) };
+    if !changes.is_empty() {
+        crate::file_writer::write(&path, &synced(&content, config)?)?;
+    }
+    Ok(changes)
+}
+
+// `blast config sync-diesel`
+pub fn run(config: &Config) -> Result<(), String> {
+    let changes = sync(config)?;
+    if changes.is_empty() {
+        return logger::success(&format!("{} already matches Catalyst.toml", FILE));
+    }
+    for change in &changes {
+        logger::info(change)?;
+    }
+    logger::success(&format!("Updated {} from Catalyst.toml", FILE))
+}
+
+// A project without diesel.toml uses diesel's defaults, which blast overrides on the command line anyway
+pub fn warn_if_out_of_sync(config: &Config) -> Result<(), String> {
+    let Ok(content) = std::fs::read_to_string(config.project_dir.join(FILE)) else {
+        return Ok(());
+    };
+    let Some(first) = differences(&content, config).into_iter().next() else {
+        return Ok(());
+    };
+    logger::warning(&format!("{} disagrees with Catalyst.toml: {}. Run `blast config sync-diesel` to update it", FILE, first))
+}
+
+// `--migration-dir` for the default connection, unless the project's diesel.toml already points there
+pub fn migration_dir_args(config: &Config, migrations_dir: &str) -> Vec<String> {
+    let configured = std::fs::read_to_string(config.project_dir.join(FILE)).ok().and_then(|content| content.parse::<toml::Table>().ok()).and_then(|doc| doc.get("migrations_directory")?.get("dir")?.as_str().map(str::to_string));
+    if configured.as_deref() == Some(migrations_dir) {
+        return Vec::new();
+    }
+    vec!["--migration-dir".to_string(), migrations_dir.to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_keeps_user_keys_and_marks_the_managed_ones() {
+        let dir = std::env::temp_dir().join(format!("blast-diesel-toml-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"shop\"\nversion = \"0.1.0\"\n").unwrap();
+        std::fs::write(dir.join("Catalyst.toml"), "[codegen]\nschema_file = \"src/schema.rs\"\n\n[codegen.schema]\nexcept_tables = [\"spatial_.*\"]\n").unwrap();
+        let config = crate::configs::get_fresh_config(&dir).unwrap();
+        let schema = config.codegen().schema;
+        assert!(!schema.includes("spatial_ref_sys") && schema.includes("public.users"));
+
+        let user = "# my settings\n[print_schema]\nfile = \"src/old.rs\"\ncustom_type_derives = [\"diesel::query_builder::QueryId\"]\n\n[migrations_directory]\ndir = \"migrations\"\n";
+        assert_eq!(differences(user, &config).len(), 3);
+
+        let updated = synced(user, &config).unwrap();
+        assert!(updated.starts_with("# my settings\n"));
+        assert!(updated.contains("custom_type_derives = [\"diesel::query_builder::QueryId\"]\n"));
+        assert!(updated.contains(&format!("file = \"src/schema.rs\"{}", MARKER)));
+        assert!(updated.contains(&format!("dir = \"src/database/migrations\"{}", MARKER)));
+        assert!(differences(&updated, &config).is_empty());
+        assert_eq!(synced(&updated, &config).unwrap(), updated);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
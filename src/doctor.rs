@@ -0,0 +1,225 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::configs::Config;
+use crate::dependencies::{ToolStatus, DEPENDENCIES};
+use crate::logger;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Each tool blast runs: its version against the range blast needs, and what happened when blast last installed it
+fn report_tools() -> Result<usize, String> {
+    let records = crate::dependencies::install_records();
+    let mut problems = 0;
+    for dependency in DEPENDENCIES {
+        let record = records.get(dependency.package);
+        let installed_by_blast = record.filter(|record| record.ok).map(|record| format!(" installed by blast on {}", record.date)).unwrap_or_default();
+        let (ok, line) = match dependency.status() {
+            ToolStatus::Ok(version) => (true, format!("{} {}{}", dependency.package, version, installed_by_blast)),
+            ToolStatus::Unknown => (true, format!("{} installed, version unknown{}", dependency.package, installed_by_blast)),
+            ToolStatus::OutOfRange(version) => (false, format!("{} {}{}, outside required {}", dependency.package, version, installed_by_blast, dependency.requirement)),
+            ToolStatus::Missing if dependency.optional => (true, format!("{} not installed (optional)", dependency.package)),
+            ToolStatus::Missing => (false, format!("{} not installed", dependency.package)),
+        };
+        logger::plain(&format!("  {} {}", logger::status_mark(ok), line))?;
+        if !ok {
+            problems += 1;
+            logger::plain(&format!("      {}", dependency.install_command_line()))?;
+        }
+        if let Some(record) = record.filter(|record| !record.ok) {
+            logger::plain(&format!("      Installing it with `{}` failed on {}", record.command, record.date))?;
+        }
+    }
+    Ok(problems)
+}
+
+// With a renamed public_dir, source files that still mount "public" themselves. The template reads Rocket's
+// `public_dir` setting, which blast writes to Rocket.toml and exports as ROCKET_PUBLIC_DIR
+fn report_public_dir(config: &Config) -> Result<usize, String> {
+    let public_dir = config.public_dir();
+    if public_dir == "public" {
+        return Ok(0);
+    }
+    logger::plain("Static files")?;
+    let mount = regex::Regex::new(r#"FileServer::from\(\s*(?:relative!\(\s*)?"public""#).map_err(|e| e.to_string())?;
+    let src = config.project_dir.join("src");
+    let hardcoded: Vec<String> = walkdir::WalkDir::new(&src)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|extension| extension == "rs"))
+        .filter(|entry| std::fs::read_to_string(entry.path()).is_ok_and(|source| mount.is_match(&source)))
+        .map(|entry| entry.path().strip_prefix(&config.project_dir).unwrap_or(entry.path()).display().to_string())
+        .collect();
+    if hardcoded.is_empty() {
+        logger::plain(&format!("  {} {} is served from Rocket's public_dir setting", logger::status_mark(true), public_dir))?;
+    }
+    for file in &hardcoded {
+        logger::plain(&format!("  {} {} mounts \"public\", not public_dir = \"{}\"", logger::status_mark(false), file, public_dir))?;
+        logger::plain("      mount rocket.figment().extract_inner::<String>(\"public_dir\") instead")?;
+    }
+    Ok(hardcoded.len())
+}
+
+// In a production environment Rocket needs a real secret_key, kept out of Rocket.toml
+fn report_secret_key(config: &Config) -> Result<usize, String> {
+    let Some(problem) = crate::rocket_config::secret_key_problem(config) else {
+        return Ok(0);
+    };
+    logger::plain("Rocket")?;
+    logger::plain(&format!("  {} {}", logger::status_mark(false), problem))?;
+    logger::plain(&format!("      generate one with `openssl rand -hex 32` and run `blast env set {} <key>`", crate::rocket_config::SECRET_KEY_ENV))?;
+    Ok(1)
+}
+
+// One command in redis' wire format, and its one-line reply: "+OK"/"+PONG", or the "-ERR ..." message
+fn redis_command(stream: &mut TcpStream, reader: &mut impl BufRead, args: &[&str]) -> Result<(), String> {
+    let mut command = format!("*{}\r\n", args.len());
+    for arg in args {
+        command.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+    }
+    stream.write_all(command.as_bytes()).map_err(|e| e.to_string())?;
+    let mut reply = String::new();
+    reader.read_line(&mut reply).map_err(|e| e.to_string())?;
+    match reply.trim_end().strip_prefix('-') {
+        Some(error) => Err(error.to_string()),
+        None if reply.starts_with('+') => Ok(()),
+        None => Err(format!("unexpected reply {:?}", reply.trim_end())),
+    }
+}
+
+// Connect to REDIS_URL, authenticate and select the database it names, then PING. rediss:// only gets as far
+// as the TCP connection, since blast has no TLS client for raw sockets
+fn ping_redis(redis_url: &str) -> Result<(), String> {
+    let url = url::Url::parse(redis_url).map_err(|e| format!("invalid URL: {}", e))?;
+    let host = url.host_str().ok_or("the URL has no host")?;
+    let address = (host, url.port().unwrap_or(6379)).to_socket_addrs().map_err(|e| format!("can't resolve {}: {}", host, e))?.next().ok_or_else(|| format!("can't resolve {}", host))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map_err(|e| format!("can't connect to {}: {}", address, e))?;
+    if url.scheme() == "rediss" {
+        return Ok(());
+    }
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+
+    if let Some(password) = url.password() {
+        let auth: Vec<&str> = if url.username().is_empty() { vec!["AUTH", password] } else { vec!["AUTH", url.username(), password] };
+        redis_command(&mut stream, &mut reader, &auth).map_err(|e| format!("AUTH failed: {}", e))?;
+    }
+    let database = url.path().trim_start_matches('/');
+    if !database.is_empty() {
+        redis_command(&mut stream, &mut reader, &["SELECT", database]).map_err(|e| format!("SELECT {} failed: {}", database, e))?;
+    }
+    redis_command(&mut stream, &mut reader, &["PING"]).map_err(|e| format!("PING failed: {}", e))
+}
+
+// With `[middleware] cache_backend = "redis"`, whether the app will reach REDIS_URL
+fn report_redis(config: &Config) -> Result<usize, String> {
+    if config.assets.get("middleware").and_then(|middleware| middleware.get("cache_backend")).and_then(|backend| backend.as_str()) != Some("redis") {
+        return Ok(0);
+    }
+    logger::plain("Cache")?;
+    let redis_url = crate::envfile::load_effective(&config.project_dir, &config.environment).get("REDIS_URL").filter(|url| !url.trim().is_empty() && !url.contains(crate::envfile::PLACEHOLDER));
+    let result = match &redis_url {
+        Some(redis_url) => ping_redis(redis_url),
+        None => Err("REDIS_URL is not set".to_string()),
+    };
+    match result {
+        Ok(()) => {
+            logger::plain(&format!("  {} redis at {} answers", logger::status_mark(true), crate::logger::redact(redis_url.as_deref().unwrap_or_default())))?;
+            Ok(0)
+        }
+        Err(e) => {
+            logger::plain(&format!("  {} redis: {}", logger::status_mark(false), e))?;
+            logger::plain("      start redis or point REDIS_URL at it with `blast env set REDIS_URL redis://host:6379`")?;
+            Ok(1)
+        }
+    }
+}
+
+// With STORAGE_BACKEND=s3, whether every generated bucket answers a signed HEAD with the configured credentials
+fn report_storage(config: &Config) -> Result<usize, String> {
+    let buckets = crate::storage::check_buckets(config);
+    if buckets.is_empty() {
+        return Ok(0);
+    }
+    logger::plain("Storage")?;
+    let mut problems = 0;
+    for (bucket, result) in &buckets {
+        match result {
+            Ok(()) => logger::plain(&format!("  {} bucket {} is reachable", logger::status_mark(true), bucket))?,
+            Err(e) => {
+                problems += 1;
+                logger::plain(&format!("  {} bucket {}: {}", logger::status_mark(false), bucket, e))?;
+            }
+        }
+    }
+    if problems > 0 {
+        logger::plain("      check the STORAGE_* values with `blast env check`")?;
+    }
+    Ok(problems)
+}
+
+// `blast doctor`: report what's wrong with the project without changing anything. Exits non-zero on problems
+pub fn run(config: &Config) -> Result<(), String> {
+    logger::plain("Tools")?;
+    let problems = report_tools()?;
+
+    logger::plain("Project files")?;
+    let file_problems = crate::integrity::report(&config.project_dir)?;
+    let mount_problems = report_public_dir(config)? + report_secret_key(config)?;
+    let service_problems = report_redis(config)? + report_storage(config)?;
+
+    if problems + file_problems + mount_problems + service_problems > 0 {
+        let mut fixes = Vec::new();
+        if problems > 0 {
+            fixes.push("run the install commands above");
+        }
+        if file_problems > 0 {
+            fixes.push("run `blast init` to repair the project files");
+        }
+        if mount_problems > 0 {
+            fixes.push("follow the hints under Static files and Rocket");
+        }
+        if service_problems > 0 {
+            fixes.push("make the services above reachable");
+        }
+        return Err(format!("{} problem(s) found. To fix them, {}", problems + file_problems + mount_problems + service_problems, fixes.join(" and ")));
+    }
+    logger::success("No problems found")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redis_check_authenticates_selects_and_pings() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut commands = Vec::new();
+            for reply in ["+OK\r\n", "+OK\r\n", "+PONG\r\n"] {
+                // *N, then $len and the argument for each of the N arguments
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let count: usize = line.trim()[1..].parse().unwrap();
+                let mut args = Vec::new();
+                for _ in 0..count {
+                    let (mut length, mut arg) = (String::new(), String::new());
+                    reader.read_line(&mut length).unwrap();
+                    reader.read_line(&mut arg).unwrap();
+                    args.push(arg.trim_end().to_string());
+                }
+                commands.push(args.join(" "));
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+            commands
+        });
+
+        ping_redis(&format!("redis://:secret@127.0.0.1:{}/2", port)).unwrap();
+        assert_eq!(server.join().unwrap(), ["AUTH secret", "SELECT 2", "PING"]);
+        assert!(ping_redis("not a url").is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use console::style;
+use similar::{ChangeTag, TextDiff};
+
+// Diffs longer than this are cut off and summarized
+const MAX_DIFF_LINES: usize = 200;
+
+// `--dry-run` collects generated files instead of writing them, `--diff` prints what changes either way
+#[derive(Clone, Copy, Default)]
+pub struct WriteMode {
+    pub dry_run: bool,
+    pub diff: bool,
+}
+
+static MODE: OnceLock<WriteMode> = OnceLock::new();
+
+// Every write from gen structs/models, schema generation and the mod.rs updates goes through here
+static WRITER: Mutex<FileWriter> = Mutex::new(FileWriter { pending: BTreeMap::new(), changed: 0 });
+
+pub fn set_mode(mode: WriteMode) {
+    MODE.get_or_init(|| mode);
+}
+
+pub fn mode() -> WriteMode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+struct FileWriter {
+    // Contents a dry run would have written; None for removed files
+    pending: BTreeMap<PathBuf, Option<String>>,
+    changed: usize,
+}
+
+impl FileWriter {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        match self.pending.get(path) {
+            Some(Some(content)) => Ok(content.clone()),
+            Some(None) => Err(io::Error::new(io::ErrorKind::NotFound, format!("{} was removed", path.display()))),
+            None => fs::read_to_string(path),
+        }
+    }
+
+    fn apply(&mut self, path: &Path, content: Option<&str>) -> Result<(), String> {
+        let existing = self.read(path).ok();
+        if existing.as_deref() == content {
+            return Ok(());
+        }
+
+        let mode = mode();
+        if mode.dry_run || mode.diff {
+            print_diff(path, existing.as_deref(), content);
+        }
+        self.changed += 1;
+
+        if mode.dry_run {
+            self.pending.insert(path.to_path_buf(), content.map(str::to_string));
+            return Ok(());
+        }
+        match content {
+            Some(content) => write_atomic(path, content),
+            None => fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+}
+
+fn writer() -> std::sync::MutexGuard<'static, FileWriter> {
+    WRITER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Read a file as the current run sees it, including changes a dry run has only collected
+pub fn read(path: impl AsRef<Path>) -> io::Result<String> {
+    writer().read(path.as_ref())
+}
+
+pub fn exists(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    match writer().pending.get(path) {
+        Some(content) => content.is_some(),
+        None => path.exists(),
+    }
+}
+
+// Write a generated file; unchanged content is left alone
+pub fn write(path: impl AsRef<Path>, content: &str) -> Result<(), String> {
+    writer().apply(path.as_ref(), Some(content))
+}
+
+pub fn remove(path: impl AsRef<Path>) -> Result<(), String> {
+    writer().apply(path.as_ref(), None)
+}
+
+pub fn create_dir_all(path: impl AsRef<Path>) -> Result<(), String> {
+    let path = path.as_ref();
+    if mode().dry_run {
+        return Ok(());
+    }
+    fs::create_dir_all(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))
+}
+
+// Bookkeeping under .blast/: written on real runs, skipped on dry runs and never diffed
+pub fn write_state(path: impl AsRef<Path>, content: &str) -> Result<(), String> {
+    if mode().dry_run {
+        return Ok(());
+    }
+    write_atomic(path.as_ref(), content)
+}
+
+// Summary line for the end of a dry run
+pub fn finish() {
+    if mode().dry_run {
+        let changed = writer().changed;
+        println!("{}", style(format!("Dry run: {} file(s) would change, nothing was written", changed)).yellow().bold());
+    }
+}
+
+// Write to a temporary file and rename it into place, so an interrupted run leaves the old file intact
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    fs::write(&temp_path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+fn print_diff(path: &Path, old: Option<&str>, new: Option<&str>) {
+    let name = path.display().to_string();
+    let old_header = if old.is_some() { format!("a/{}", name) } else { "/dev/null".to_string() };
+    let new_header = if new.is_some() { format!("b/{}", name) } else { "/dev/null".to_string() };
+
+    let diff = TextDiff::from_lines(old.unwrap_or_default(), new.unwrap_or_default());
+    let unified = diff.unified_diff().context_radius(3).header(&old_header, &new_header).to_string();
+
+    for (index, line) in unified.lines().enumerate() {
+        if index >= MAX_DIFF_LINES {
+            let added = diff.iter_all_changes().filter(|change| change.tag() == ChangeTag::Insert).count();
+            let removed = diff.iter_all_changes().filter(|change| change.tag() == ChangeTag::Delete).count();
+            let hidden = unified.lines().count() - MAX_DIFF_LINES;
+            println!("{}", style(format!("... {} more lines ({} total: +{} -{})", hidden, name, added, removed)).dim());
+            break;
+        }
+
+        // The first two lines are the ---/+++ file headers
+        let styled = match line.chars().next() {
+            _ if index < 2 => style(line).bold(),
+            Some('@') => style(line).cyan(),
+            Some('+') => style(line).green(),
+            Some('-') => style(line).red(),
+            _ => style(line),
+        };
+        println!("{}", styled);
+    }
+}
@@ -0,0 +1,502 @@
+use std::path::Path;
+
+use crate::codegen_ignore::Generator;
+use crate::codegen_manifest::CodegenManifest;
+use crate::configs::Config;
+use crate::models::{to_pascal, RelationshipInfo, TableInfo};
+
+// How a New<T> field is converted from the submitted text, and which input edits it
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+    Text,
+    Checkbox,
+    Integer,
+    Decimal,
+    Date,
+    DateTime,
+    DateTimeUtc,
+    Time,
+    // FromStr types such as Uuid
+    Parsed,
+    // Anything else (serde_json::Value, arrays) is edited as JSON
+    Json,
+    // A foreign key: a select of the parent's rows, by the parent struct and the column shown
+    Select { parent: String, label: Option<(String, bool)> },
+}
+
+impl FieldKind {
+    fn from_type(field_type: &str) -> Self {
+        let inner = field_type.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>')).unwrap_or(field_type).trim();
+        if inner.contains("DateTime<") {
+            return FieldKind::DateTimeUtc;
+        }
+        match inner.rsplit("::").next().unwrap_or(inner) {
+            "String" => FieldKind::Text,
+            "bool" => FieldKind::Checkbox,
+            "i16" | "i32" | "i64" => FieldKind::Integer,
+            "f32" | "f64" | "BigDecimal" => FieldKind::Decimal,
+            "NaiveDate" => FieldKind::Date,
+            "NaiveDateTime" => FieldKind::DateTime,
+            "NaiveTime" => FieldKind::Time,
+            "Uuid" => FieldKind::Parsed,
+            _ => FieldKind::Json,
+        }
+    }
+
+    // Function of the support module that converts the text
+    fn parser(&self) -> &'static str {
+        match self {
+            FieldKind::Text | FieldKind::Checkbox => "text",
+            FieldKind::Integer | FieldKind::Select { .. } => "integer",
+            FieldKind::Decimal => "decimal",
+            FieldKind::Date => "date",
+            FieldKind::DateTime => "datetime",
+            FieldKind::DateTimeUtc => "datetime_utc",
+            FieldKind::Time => "time",
+            FieldKind::Parsed => "parsed",
+            FieldKind::Json => "json",
+        }
+    }
+
+    fn input_type(&self) -> &'static str {
+        match self {
+            FieldKind::Integer => "number",
+            FieldKind::Decimal => "decimal",
+            FieldKind::Date => "date",
+            FieldKind::DateTime | FieldKind::DateTimeUtc => "datetime",
+            FieldKind::Time => "time",
+            FieldKind::Json => "textarea",
+            _ => "text",
+        }
+    }
+}
+
+// "author_id" -> "Author", "published_at" -> "Published at"
+fn label(field: &str) -> String {
+    let words = field.strip_suffix("_id").unwrap_or(field).replace('_', " ");
+    let mut chars = words.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+// The parent column a select shows: the first text column that reads like a name, with whether it is nullable
+fn label_column(parent: &TableInfo) -> Option<(String, bool)> {
+    ["name", "title", "label", "username", "email", "slug"].iter().find_map(|candidate| {
+        parent.columns.iter().find(|column| column.name == *candidate && matches!(column.column_type.as_str(), "Text" | "Varchar")).map(|column| (column.name.clone(), column.nullable))
+    })
+}
+
+// The kind of each insertable field; foreign keys to tables with a generated model become selects
+fn field_kinds(config: &Config, table_name: &str, fields: &[(String, String)], tables: &[TableInfo], relationships: &[RelationshipInfo]) -> Vec<(String, bool, FieldKind)> {
+    let codegen = config.codegen();
+    fields
+        .iter()
+        .map(|(name, field_type)| {
+            let nullable = field_type.starts_with("Option<");
+            let parent = relationships
+                .iter()
+                .find(|relationship| relationship.source_table == table_name && &relationship.source_column == name)
+                .filter(|relationship| crate::codegen_ignore::ignored_by(&codegen, Generator::Models, &relationship.target_table).is_none())
+                .filter(|_| FieldKind::from_type(field_type) == FieldKind::Integer);
+            let kind = match parent {
+                Some(relationship) => FieldKind::Select {
+                    parent: to_pascal(&relationship.target_table),
+                    label: tables.iter().find(|table| table.name == relationship.target_table).and_then(label_column),
+                },
+                None => FieldKind::from_type(field_type),
+            };
+            (name.clone(), nullable, kind)
+        })
+        .collect()
+}
+
+// <Struct>Form with its conversion into New<Struct>, the HTML of its fields and a From<&Struct> to edit a row
+fn form_source(struct_name: &str, fields: &[(String, bool, FieldKind)], validated: bool) -> String {
+    let form_fields: String = fields.iter().map(|(name, _, _)| format!("    pub {}: Option<String>,\n", name)).collect();
+
+    let mut conversions = String::new();
+    for (name, nullable, kind) in fields {
+        conversions.push_str(&match (kind, nullable) {
+            (FieldKind::Checkbox, false) => format!("        let {0} = checked(&self.{0});\n", name),
+            (FieldKind::Checkbox, true) => format!("        let {0} = Some(checked(&self.{0}));\n", name),
+            (_, false) => format!("        let {0} = errors.required(\"{0}\", &self.{0}, {1});\n", name, kind.parser()),
+            (_, true) => format!("        let {0} = errors.optional(\"{0}\", &self.{0}, {1});\n", name, kind.parser()),
+        });
+    }
+    let required: Vec<&str> = fields.iter().filter(|(_, nullable, kind)| !nullable && *kind != FieldKind::Checkbox).map(|(name, _, _)| name.as_str()).collect();
+    let unwrap_required = match required.as_slice() {
+        [] => String::new(),
+        [name] => format!("        let Some({0}) = {0} else {{\n            return Err(errors);\n        }};\n", name),
+        names => format!(
+            "        let ({}) = ({}) else {{\n            return Err(errors);\n        }};\n",
+            names.iter().map(|name| format!("Some({})", name)).collect::<Vec<_>>().join(", "),
+            names.join(", ")
+        ),
+    };
+    let field_names = fields.iter().map(|(name, _, _)| name.as_str()).collect::<Vec<_>>().join(", ");
+    let validate = if validated { "        new_record.validate()?;\n" } else { "" };
+
+    let mut html = String::new();
+    for (name, _, kind) in fields {
+        html.push_str(&match kind {
+            FieldKind::Checkbox => format!("        html.push_str(&checkbox_field(\"{}\", \"{}\", &self.{}, errors));\n", name, label(name), name),
+            FieldKind::Select { parent, label: shown } => {
+                let shown = match shown {
+                    Some((column, false)) => format!("row.{}.clone()", column),
+                    Some((column, true)) => format!("row.{}.clone().unwrap_or_default()", column),
+                    None => "row.id.to_string()".to_string(),
+                };
+                format!(
+                    "        let {0}_options: Vec<(String, String)> = crate::structs::{1}::get_all().await?.iter().map(|row| (row.id.to_string(), {2})).collect();\n        html.push_str(&select_field(\"{0}\", \"{3}\", &{0}_options, &self.{0}, errors));\n",
+                    name,
+                    parent,
+                    shown,
+                    label(name)
+                )
+            }
+            _ => format!("        html.push_str(&input_field(\"{}\", \"{}\", \"{}\", &self.{}, errors));\n", name, label(name), kind.input_type(), name),
+        });
+    }
+
+    let values: String = fields
+        .iter()
+        .map(|(name, nullable, kind)| {
+            let value = match (kind, nullable) {
+                (FieldKind::Parsed | FieldKind::Decimal, false) => format!("Some(record.{}.to_string())", name),
+                (FieldKind::Parsed | FieldKind::Decimal, true) => format!("record.{}.as_ref().map(|value| value.to_string())", name),
+                (FieldKind::Json, false) => format!("serde_json::to_string(&record.{}).ok()", name),
+                (FieldKind::Json, true) => format!("record.{}.as_ref().and_then(|value| serde_json::to_string(value).ok())", name),
+                _ => format!("record.{}.form_value()", name),
+            };
+            format!("            {}: {},\n", name, value)
+        })
+        .collect();
+
+    let validate_import = if validated { "\nuse crate::structs::insertable::Validate;".to_string() } else { String::new() };
+    format!(
+        r#"use rocket::FromForm;
+use crate::structs::{struct_name};
+use crate::structs::insertable::New{struct_name};{validate_import}
+use crate::meltdown::*;
+use super::support::*;
+
+// Form data for New{struct_name}. Every field arrives as text, and a missing field counts as an empty one
+#[derive(Debug, Clone, Default, FromForm)]
+pub struct {struct_name}Form {{
+{form_fields}}}
+
+impl {struct_name}Form {{
+    // New{struct_name} from the submitted values, or a message for each field that didn't convert
+    pub fn to_new(&self) -> Result<New{struct_name}, FormErrors> {{
+        let mut errors = FormErrors::default();
+{conversions}{unwrap_required}        if !errors.is_empty() {{
+            return Err(errors);
+        }}
+
+        let new_record = New{struct_name} {{ {field_names} }};
+{validate}        Ok(new_record)
+    }}
+
+    // Materialize fields for each value, showing the messages in `errors` next to their fields
+    pub async fn fields_html(&self, errors: &FormErrors) -> Result<String, ModelError> {{
+        let mut html = String::new();
+{html}        Ok(html)
+    }}
+}}
+
+// The values of an existing row, to fill in an edit form
+impl From<&{struct_name}> for {struct_name}Form {{
+    fn from(record: &{struct_name}) -> Self {{
+        {struct_name}Form {{
+{values}        }}
+    }}
+}}
+"#
+    )
+}
+
+// Shared conversions, errors and Materialize markup, rewritten on every run so it tracks the blast version
+const SUPPORT_SOURCE: &str = r##"// Helpers for the generated form structs
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::structs::insertable::ValidationError;
+
+// One field of a submitted form that didn't convert or validate
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+// Every field error of a submission, to re-render the form with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl FormErrors {
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.errors.push(FieldError { field, message: message.into() });
+    }
+
+    // The first message for a field
+    pub fn for_field(&self, field: &str) -> Option<&str> {
+        self.errors.iter().find(|error| error.field == field).map(|error| error.message.as_str())
+    }
+
+    // A value that has to be filled in
+    pub fn required<T>(&mut self, field: &'static str, value: &Option<String>, parse: impl Fn(&str) -> Result<T, &'static str>) -> Option<T> {
+        match value.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+            Some(value) => self.converted(field, parse(value)),
+            None => {
+                self.add(field, "is required");
+                None
+            }
+        }
+    }
+
+    // A value that may be left empty, which means None
+    pub fn optional<T>(&mut self, field: &'static str, value: &Option<String>, parse: impl Fn(&str) -> Result<T, &'static str>) -> Option<T> {
+        let value = value.as_deref().map(str::trim).filter(|value| !value.is_empty())?;
+        self.converted(field, parse(value))
+    }
+
+    fn converted<T>(&mut self, field: &'static str, result: Result<T, &'static str>) -> Option<T> {
+        result.map_err(|message| self.add(field, message)).ok()
+    }
+}
+
+impl fmt::Display for FormErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.errors.iter().map(|error| format!("{} {}", error.field, error.message)).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
+// The validate() rules of the insertable fail like a conversion does
+impl From<Vec<ValidationError>> for FormErrors {
+    fn from(errors: Vec<ValidationError>) -> Self {
+        FormErrors { errors: errors.into_iter().map(|error| FieldError { field: error.field, message: error.message }).collect() }
+    }
+}
+
+pub fn text(value: &str) -> Result<String, &'static str> {
+    Ok(value.to_string())
+}
+
+pub fn integer<T: FromStr>(value: &str) -> Result<T, &'static str> {
+    value.parse().map_err(|_| "must be a whole number")
+}
+
+pub fn decimal<T: FromStr>(value: &str) -> Result<T, &'static str> {
+    value.parse().map_err(|_| "must be a number")
+}
+
+pub fn parsed<T: FromStr>(value: &str) -> Result<T, &'static str> {
+    value.parse().map_err(|_| "is not valid")
+}
+
+pub fn json<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, &'static str> {
+    serde_json::from_str(value).map_err(|_| "must be valid JSON")
+}
+
+pub fn date(value: &str) -> Result<NaiveDate, &'static str> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| "must be a date")
+}
+
+// datetime-local inputs send 2024-05-01T14:30, with seconds when a step below a minute is set
+pub fn datetime(value: &str) -> Result<NaiveDateTime, &'static str> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or("must be a date and time")
+}
+
+pub fn datetime_utc(value: &str) -> Result<DateTime<Utc>, &'static str> {
+    datetime(value).map(|datetime| datetime.and_utc())
+}
+
+pub fn time(value: &str) -> Result<NaiveTime, &'static str> {
+    ["%H:%M:%S", "%H:%M"].iter().find_map(|format| NaiveTime::parse_from_str(value, format).ok()).ok_or("must be a time")
+}
+
+// Checkboxes send their value only when checked
+pub fn checked(value: &Option<String>) -> bool {
+    matches!(value.as_deref(), Some("on" | "true" | "1"))
+}
+
+// A column value as a form field shows it
+pub trait FormValue {
+    fn form_value(&self) -> Option<String>;
+}
+
+macro_rules! display_form_value {
+    ($($type:ty),*) => {
+        $(impl FormValue for $type {
+            fn form_value(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+        })*
+    };
+}
+
+display_form_value!(String, i16, i32, i64, f32, f64, NaiveDate);
+
+impl FormValue for bool {
+    fn form_value(&self) -> Option<String> {
+        self.then(|| "on".to_string())
+    }
+}
+
+impl FormValue for NaiveDateTime {
+    fn form_value(&self) -> Option<String> {
+        Some(self.format("%Y-%m-%dT%H:%M:%S").to_string())
+    }
+}
+
+impl FormValue for DateTime<Utc> {
+    fn form_value(&self) -> Option<String> {
+        self.naive_utc().form_value()
+    }
+}
+
+impl FormValue for NaiveTime {
+    fn form_value(&self) -> Option<String> {
+        Some(self.format("%H:%M:%S").to_string())
+    }
+}
+
+impl<T: FormValue> FormValue for Option<T> {
+    fn form_value(&self) -> Option<String> {
+        self.as_ref().and_then(FormValue::form_value)
+    }
+}
+
+pub fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+fn helper_text(field: &str, errors: &FormErrors) -> String {
+    match errors.for_field(field) {
+        Some(message) => format!(r#"<span class="helper-text red-text">{}</span>"#, escape(message)),
+        None => String::new(),
+    }
+}
+
+// An .input-field with its floating label. input_type is an HTML type, or decimal, datetime or textarea
+pub fn input_field(field: &str, label: &str, input_type: &str, value: &Option<String>, errors: &FormErrors) -> String {
+    let value = escape(value.as_deref().unwrap_or_default());
+    let invalid = if errors.for_field(field).is_some() { " invalid" } else { "" };
+    let control = match input_type {
+        "textarea" => format!(r#"<textarea id="{0}" name="{0}" class="materialize-textarea{1}">{2}</textarea>"#, field, invalid, value),
+        _ => {
+            let attributes = match input_type {
+                "decimal" => r#"type="number" step="any""#.to_string(),
+                "datetime" => r#"type="datetime-local" step="1""#.to_string(),
+                "time" => r#"type="time" step="1""#.to_string(),
+                other => format!(r#"type="{}""#, other),
+            };
+            format!(r#"<input id="{0}" name="{0}" {1} value="{2}" class="validate{3}">"#, field, attributes, value, invalid)
+        }
+    };
+    // Date and time inputs draw their own placeholder, which a resting label would cover
+    let active = if !value.is_empty() || matches!(input_type, "date" | "datetime" | "time") { r#" class="active""# } else { "" };
+    format!(r#"<div class="input-field">{}<label for="{}"{}>{}</label>{}</div>"#, control, field, active, escape(label), helper_text(field, errors))
+}
+
+pub fn checkbox_field(field: &str, label: &str, value: &Option<String>, errors: &FormErrors) -> String {
+    let checked = if checked(value) { " checked" } else { "" };
+    format!(r#"<p><label><input type="checkbox" name="{}" value="on"{}><span>{}</span></label>{}</p>"#, field, checked, escape(label), helper_text(field, errors))
+}
+
+// A Materialize select; initialise it with M.FormSelect.init once the form is on the page
+pub fn select_field(field: &str, label: &str, options: &[(String, String)], value: &Option<String>, errors: &FormErrors) -> String {
+    let selected = value.as_deref().unwrap_or_default();
+    let mut html = format!(r#"<div class="input-field"><select id="{0}" name="{0}"><option value=""{1}>Choose {2}</option>"#, field, if selected.is_empty() { " selected" } else { "" }, escape(&label.to_lowercase()));
+    for (option, text) in options {
+        let is_selected = if option == selected { " selected" } else { "" };
+        html.push_str(&format!(r#"<option value="{}"{}>{}</option>"#, escape(option), is_selected, escape(text)));
+    }
+    html.push_str(&format!(r#"</select><label>{}</label>{}</div>"#, escape(label), helper_text(field, errors)));
+    html
+}
+"##;
+
+// Write the form of one table, from the fields of its insertable. Ok(false) means the file was edited and kept
+pub fn write_form(config: &Config, table_name: &str, struct_name: &str, fields: &[(String, String)], validated: bool, manifest: &mut CodegenManifest) -> Result<bool, String> {
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
+    let tables = crate::models::load_schema_table_info(config, schema_path).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+    let relationships = crate::models::load_schema_relationships(config, schema_path).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+
+    let kinds = field_kinds(config, table_name, fields, &tables, &relationships);
+    let path = format!("{}/{}.rs", codegen.forms.dir, table_name);
+    crate::file_writer::create_dir_all(config.project_dir.join(&codegen.forms.dir))?;
+    let source = crate::codegen_format::render(&config.project_dir, &path, &form_source(struct_name, &kinds, validated), schema_path, Some(table_name))?;
+    manifest.write(&path, &source, Some(table_name))
+}
+
+// support.rs, then the generated section of mod.rs from every table with a form
+pub fn write_support(config: &Config, manifest: &mut CodegenManifest) -> Result<(), String> {
+    let codegen = config.codegen();
+    let dir = codegen.forms.dir.as_str();
+    crate::file_writer::create_dir_all(config.project_dir.join(dir))?;
+    let support_path = format!("{}/support.rs", dir);
+    let source = crate::codegen_format::render(&config.project_dir, &support_path, SUPPORT_SOURCE, codegen.schema_path(), None)?;
+    manifest.write(&support_path, &source, None)?;
+    Ok(())
+}
+
+pub fn sync_mod(config: &Config, manifest: &CodegenManifest) -> Result<(), String> {
+    let codegen = config.codegen();
+    let dir = codegen.forms.dir.as_str();
+    let mut modules = vec!["support".to_string()];
+    modules.extend(manifest.tables_in(dir));
+    crate::mod_file::sync(&config.project_dir.join(dir).join("mod.rs"), &modules, true)?;
+
+    // The parent module is the project's; say so when it doesn't declare the forms module yet
+    let parent_mod = Path::new(dir).parent().map(|parent| config.project_dir.join(parent).join("mod.rs"));
+    let module = Path::new(dir).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    if let Some(parent_mod) = parent_mod.filter(|path| path.exists()) {
+        let content = crate::file_writer::read(&parent_mod).unwrap_or_default();
+        if !content.lines().any(|line| line.trim().trim_start_matches("pub ").starts_with(&format!("mod {};", module))) {
+            crate::logger::warning(&format!("Declare the generated forms in {} with `pub mod {};`", parent_mod.strip_prefix(&config.project_dir).unwrap_or(&parent_mod).display(), module))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_each_field_by_column_type() {
+        let fields = vec![
+            ("title".to_string(), false, FieldKind::Text),
+            ("published".to_string(), false, FieldKind::Checkbox),
+            ("published_at".to_string(), true, FieldKind::DateTime),
+            ("author_id".to_string(), false, FieldKind::Select { parent: "Users".to_string(), label: Some(("name".to_string(), false)) }),
+        ];
+        let source = form_source("Posts", &fields, false);
+
+        assert!(source.contains("pub struct PostsForm {\n    pub title: Option<String>,"));
+        assert!(source.contains("let published = checked(&self.published);"));
+        assert!(source.contains("let published_at = errors.optional(\"published_at\", &self.published_at, datetime);"));
+        assert!(source.contains("let (Some(title), Some(author_id)) = (title, author_id) else {"));
+        assert!(source.contains("crate::structs::Users::get_all().await?.iter().map(|row| (row.id.to_string(), row.name.clone()))"));
+        assert!(source.contains("select_field(\"author_id\", \"Author\""));
+        assert!(!source.contains("Validate"));
+
+        assert_eq!(FieldKind::from_type("Option<chrono::NaiveDateTime>"), FieldKind::DateTime);
+        assert_eq!(FieldKind::from_type("DateTime<Utc>"), FieldKind::DateTimeUtc);
+        assert_eq!(FieldKind::from_type("serde_json::Value"), FieldKind::Json);
+        assert_eq!(label("published_at"), "Published at");
+    }
+}
@@ -0,0 +1,352 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::auth::{describe, is_text};
+use crate::codegen_ignore::Generator;
+use crate::configs::Config;
+use crate::logger;
+use crate::models::{RelationshipInfo, TableInfo};
+
+// Columns a role is most likely named by, in order of preference
+const NAME_COLUMNS: &[&str] = &["name", "slug", "code", "key", "title"];
+
+const MISSING_TABLES_HELP: &str = "Add them with a migration such as:
+
+  CREATE TABLE roles (
+      id SERIAL PRIMARY KEY,
+      name VARCHAR(64) NOT NULL UNIQUE
+  );
+  CREATE TABLE user_roles (
+      user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+      role_id INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+      PRIMARY KEY (user_id, role_id)
+  );
+
+then run `blast migrate` and try again";
+
+// What the generated guards read, as the schema names it
+#[derive(Debug, PartialEq)]
+struct RoleSchema {
+    name_column: String,
+    user_column: String,
+    role_column: String,
+    user_id_type: &'static str,
+    role_id_type: &'static str,
+}
+
+// `blast gen guards`: role guards over the roles and user_roles tables, plus an example admin controller
+pub fn generate(config: &Config, force: bool) -> Result<(), String> {
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
+    let tables = crate::models::load_schema_table_info(config, schema_path).map_err(|e| format!("Failed to read {}: {}. Run `blast schema` first", schema_path, e))?;
+    let relationships = crate::models::load_schema_relationships(config, schema_path).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+    let roles = detect(&tables, &relationships)?;
+
+    let auth = crate::auth::module(config).ok_or("The guards check roles of the user behind the AuthUser guard. Run `blast gen auth` first")?;
+
+    let src = config.project_dir.join("src");
+    let (path, module): (PathBuf, &str) = if src.join("middleware").is_dir() {
+        (src.join("middleware").join("guards.rs"), "crate::middleware::guards")
+    } else {
+        (src.join("guards.rs"), "crate::guards")
+    };
+    let (admin_path, admin_module): (PathBuf, &str) = if src.join("routes").is_dir() {
+        (src.join("routes").join("admin.rs"), "crate::routes::admin")
+    } else {
+        (src.join("admin.rs"), "crate::admin")
+    };
+    for existing in [&path, &admin_path] {
+        if existing.exists() && !force {
+            return Err(format!("{} already exists. Re-run with --force to regenerate it", existing.display()));
+        }
+    }
+
+    // Role names come through the cached Roles model when `blast gen models` caches roles
+    let cached = roles.role_id_type == "i32"
+        && codegen.schema.includes("roles")
+        && !crate::codegen_ignore::skips(&codegen, Generator::Models, "roles")
+        && codegen.models.cache_ttl("roles")?.is_some();
+
+    for (file, source) in [(&path, guards_source(&roles, auth, cached)), (&admin_path, admin_source(module))] {
+        fs::write(file, source).map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+        logger::success(&format!("Generated {}", file.strip_prefix(&config.project_dir).unwrap_or(file).display()))?;
+    }
+
+    let name = |module: &str| module.rsplit("::").next().unwrap_or_default().to_string();
+    logger::plain("Register it in your Rocket setup:")?;
+    logger::plain(&format!("  - declare the modules (`pub mod {};`, `pub mod {};`)", name(module), name(admin_module)))?;
+    logger::plain(&format!("  - mount the example: `.mount(\"/\", routes![{0}::dashboard, {0}::my_roles])`", admin_module))?;
+    logger::plain(&format!("  - guard your own routes with `{0}::RequireRole<{0}::Admin>`, and add roles to the `roles!` list in {1}", module, name(module)))?;
+    if !cached {
+        logger::plain("  - set `[codegen.models.roles] cache` and re-run with --force to read role names through the model cache")?;
+    }
+    Ok(())
+}
+
+fn detect(tables: &[TableInfo], relationships: &[RelationshipInfo]) -> Result<RoleSchema, String> {
+    let table = |name: &str| tables.iter().find(|table| table.name == name);
+    let missing: Vec<&str> = ["users", "roles", "user_roles"].into_iter().filter(|name| table(name).is_none()).collect();
+    let (Some(users), Some(roles), Some(user_roles)) = (table("users"), table("roles"), table("user_roles")) else {
+        return Err(format!("`blast gen guards` needs users, roles and user_roles tables, and the schema has no {}. {}", missing.join(" or "), MISSING_TABLES_HELP));
+    };
+
+    let mut problems = Vec::new();
+    let mut id_type = |table: &TableInfo| match table.columns.iter().find(|column| column.name == "id") {
+        Some(column) if column.column_type == "Int4" && !column.nullable => "i32",
+        Some(column) if column.column_type == "Int8" && !column.nullable => "i64",
+        _ => {
+            problems.push(format!("{} needs a NOT NULL integer id", table.name));
+            "i32"
+        }
+    };
+    let user_id_type = id_type(users);
+    let role_id_type = id_type(roles);
+
+    let names: Vec<&str> = roles.columns.iter().filter(|column| is_text(&column.column_type) && !column.nullable).map(|column| column.name.as_str()).collect();
+    let name_column = match NAME_COLUMNS.iter().find(|preferred| names.contains(preferred)) {
+        Some(name) => name.to_string(),
+        None if names.len() == 1 => names[0].to_string(),
+        None if names.is_empty() => {
+            problems.push("roles has no NOT NULL text column to name a role by (such as name)".to_string());
+            String::new()
+        }
+        None => {
+            problems.push(format!("roles has several text columns ({}) and none is called {}; rename the one that names the role", names.join(", "), NAME_COLUMNS.join(", ")));
+            String::new()
+        }
+    };
+
+    // The join table's keys, from joinable! and the migrations rather than assumed names
+    let mut key = |target: &TableInfo, expected: &str| {
+        let keys: Vec<&RelationshipInfo> = relationships.iter().filter(|relationship| relationship.source_table == "user_roles" && relationship.target_table == target.name).collect();
+        match keys.as_slice() {
+            [relationship] => match user_roles.columns.iter().find(|column| column.name == relationship.source_column) {
+                Some(column) if column.nullable || !matches!(column.column_type.as_str(), "Int4" | "Int8") || (column.column_type == "Int8") != (expected == "i64") => {
+                    problems.push(format!("user_roles.{} is {}, expected the NOT NULL type of {}.id", column.name, describe(&column.column_type, column.nullable), target.name));
+                    String::new()
+                }
+                _ => relationship.source_column.clone(),
+            },
+            [] => {
+                problems.push(format!("user_roles has no column referencing {} (such as {}_id INTEGER NOT NULL REFERENCES {}(id))", target.name, crate::models::singular(&target.name), target.name));
+                String::new()
+            }
+            several => {
+                let columns: Vec<&str> = several.iter().map(|relationship| relationship.source_column.as_str()).collect();
+                problems.push(format!("user_roles references {} through several columns ({}); keep one", target.name, columns.join(", ")));
+                String::new()
+            }
+        }
+    };
+    let user_column = key(users, user_id_type);
+    let role_column = key(roles, role_id_type);
+
+    if !problems.is_empty() {
+        return Err(format!("The roles tables can't back `blast gen guards`:\n  - {}\nAdd a migration that fixes these, run `blast migrate`, then try again", problems.join("\n  - ")));
+    }
+    Ok(RoleSchema { name_column, user_column, role_column, user_id_type, role_id_type })
+}
+
+// How AuthUser::permissions reads the user's role names
+fn load_source(roles: &RoleSchema, cached: bool) -> String {
+    if cached {
+        format!(
+            r#"let role_ids: Vec<i32> = user_roles::table
+            .filter(user_roles::{user}.eq(self.id))
+            .select(user_roles::{role})
+            .load(&mut conn)
+            .map_err(|_| Status::InternalServerError)?;
+        // Each lookup is answered from ROLES_CACHE while it's fresh
+        let mut roles = BTreeSet::new();
+        for id in role_ids {{
+            roles.insert(Roles::get_by_id(id).await.map_err(|_| Status::InternalServerError)?.{name});
+        }}"#,
+            user = roles.user_column,
+            role = roles.role_column,
+            name = roles.name_column,
+        )
+    } else {
+        format!(
+            r#"let roles: BTreeSet<String> = user_roles::table
+            .inner_join(roles::table.on(roles::id.eq(user_roles::{role})))
+            .filter(user_roles::{user}.eq(self.id))
+            .select(roles::{name})
+            .load::<String>(&mut conn)
+            .map_err(|_| Status::InternalServerError)?
+            .into_iter()
+            .collect();"#,
+            user = roles.user_column,
+            role = roles.role_column,
+            name = roles.name_column,
+        )
+    }
+}
+
+fn guards_source(roles: &RoleSchema, auth_module: &str, cached: bool) -> String {
+    format!(
+        r#"// Generated by `blast gen guards` from the roles and user_roles tables. Regenerate with `blast gen guards --force`.
+// A user's roles are the roles.{name} of their user_roles rows, loaded once per request.
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::request::{{FromRequest, Outcome, Request}};
+
+use crate::database::db::establish_connection;
+use crate::database::schema::{schema_imports};{roles_import}
+use {auth}::AuthUser;
+
+// A role routes can require, by its roles.{name} value
+pub trait Role: Send + Sync + 'static {{
+    const NAME: &'static str;
+}}
+
+macro_rules! roles {{
+    ($($role:ident => $name:literal),* $(,)?) => {{
+        $(
+            pub struct $role;
+
+            impl Role for $role {{
+                const NAME: &'static str = $name;
+            }}
+        )*
+    }};
+}}
+
+// Add a line per role you guard routes with
+roles! {{
+    Admin => "admin",
+}}
+
+pub struct Permissions {{
+    pub user_id: {user_id_type},
+    pub roles: BTreeSet<String>,
+}}
+
+impl Permissions {{
+    pub fn has_role(&self, name: &str) -> bool {{
+        self.roles.contains(name)
+    }}
+
+    pub fn is<R: Role>(&self) -> bool {{
+        self.has_role(R::NAME)
+    }}
+}}
+
+impl AuthUser {{
+    pub async fn permissions(&self) -> Result<Permissions, Status> {{
+        let mut conn = establish_connection();
+        {load}
+        Ok(Permissions {{ user_id: self.id, roles }})
+    }}
+}}
+
+// Signed-in user's permissions: `permissions: &Permissions`. Signed-out requests get 401
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r Permissions {{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {{
+        let loaded: &Result<Permissions, Status> = request
+            .local_cache_async(async {{
+                match request.guard::<AuthUser>().await {{
+                    Outcome::Success(user) => user.permissions().await,
+                    _ => Err(Status::Unauthorized),
+                }}
+            }})
+            .await;
+        match loaded {{
+            Ok(permissions) => Outcome::Success(permissions),
+            Err(status) => Outcome::Error((*status, ())),
+        }}
+    }}
+}}
+
+// Lets only users with role R through: `admin: RequireRole<Admin>`. Signed-in users without it get 403
+pub struct RequireRole<R: Role> {{
+    pub user_id: {user_id_type},
+    role: PhantomData<R>,
+}}
+
+#[rocket::async_trait]
+impl<'r, R: Role> FromRequest<'r> for RequireRole<R> {{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {{
+        match request.guard::<&Permissions>().await {{
+            Outcome::Success(permissions) if permissions.is::<R>() => Outcome::Success(RequireRole {{ user_id: permissions.user_id, role: PhantomData }}),
+            Outcome::Success(_) => Outcome::Error((Status::Forbidden, ())),
+            Outcome::Error(error) => Outcome::Error(error),
+            Outcome::Forward(status) => Outcome::Forward(status),
+        }}
+    }}
+}}
+"#,
+        name = roles.name_column,
+        schema_imports = if cached { "user_roles" } else { "{roles, user_roles}" },
+        roles_import = if cached { "\nuse crate::structs::Roles;" } else { "" },
+        auth = auth_module,
+        user_id_type = roles.user_id_type,
+        load = load_source(roles, cached),
+    )
+}
+
+fn admin_source(guards_module: &str) -> String {
+    format!(
+        r#"// Generated by `blast gen guards`. Example routes behind the role guards; change them freely.
+use rocket::serde::json::{{json, Value}};
+
+use {guards}::{{Admin, Permissions, RequireRole}};
+
+// Only users with the admin role get here
+#[get("/admin")]
+pub async fn dashboard(admin: RequireRole<Admin>) -> Value {{
+    json!({{ "user_id": admin.user_id, "role": "admin" }})
+}}
+
+// Any signed-in user: the roles they have
+#[get("/admin/roles")]
+pub async fn my_roles(permissions: &Permissions) -> Value {{
+    json!({{ "user_id": permissions.user_id, "roles": permissions.roles }})
+}}
+"#,
+        guards = guards_module
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ColumnInfo;
+
+    fn table(name: &str, columns: &[(&str, &str)]) -> TableInfo {
+        TableInfo {
+            name: name.to_string(),
+            primary_key: vec!["id".to_string()],
+            columns: columns.iter().map(|(name, kind)| ColumnInfo { name: name.to_string(), column_type: kind.to_string(), nullable: false }).collect(),
+        }
+    }
+
+    fn key(column: &str, target: &str) -> RelationshipInfo {
+        RelationshipInfo { source_table: "user_roles".to_string(), source_column: column.to_string(), target_table: target.to_string(), target_column: "id".to_string(), nullable: false }
+    }
+
+    #[test]
+    fn role_columns_come_from_the_schema() {
+        let tables = [
+            table("users", &[("id", "Int8"), ("email", "Text")]),
+            table("roles", &[("id", "Int4"), ("description", "Text"), ("slug", "Varchar")]),
+            table("user_roles", &[("member_id", "Int8"), ("group_id", "Int4")]),
+        ];
+        let schema = detect(&tables, &[key("member_id", "users"), key("group_id", "roles")]).unwrap();
+        assert_eq!(
+            schema,
+            RoleSchema { name_column: "slug".to_string(), user_column: "member_id".to_string(), role_column: "group_id".to_string(), user_id_type: "i64", role_id_type: "i32" }
+        );
+
+        let error = detect(&tables, &[key("group_id", "roles")]).unwrap_err();
+        assert!(error.contains("user_roles has no column referencing users"), "{}", error);
+        assert!(detect(&tables[..2], &[]).unwrap_err().contains("the schema has no user_roles"));
+    }
+}
@@ -0,0 +1,258 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::configs::Config;
+use crate::logger;
+
+type BlastResult = Result<(), String>;
+
+// Exit codes for `blast status --remote`, so monitoring scripts can tell failures apart
+pub const EXIT_HEALTHY: i32 = 0;
+pub const EXIT_UNHEALTHY: i32 = 2;
+pub const EXIT_HTTP_ERROR: i32 = 3;
+pub const EXIT_TIMEOUT: i32 = 4;
+pub const EXIT_UNREACHABLE: i32 = 5;
+pub const EXIT_INVALID_RESPONSE: i32 = 6;
+
+// `blast gen health`: write the /healthz controller into the project
+pub fn generate_controller(config: &Config, force: bool) -> BlastResult {
+    // Projects with a routes module get it there, everything else at the top of src/
+    let routes_dir = config.project_dir.join("src").join("routes");
+    let (path, module): (PathBuf, &str) = if routes_dir.is_dir() {
+        (routes_dir.join("healthz.rs"), "crate::routes::healthz")
+    } else {
+        (config.project_dir.join("src").join("healthz.rs"), "crate::healthz")
+    };
+
+    if path.exists() && !force {
+        return Err(format!("{} already exists. Re-run with --force to regenerate it", path.display()));
+    }
+
+    let catalyst_version = config.catalyst_version().unwrap_or_else(|| "unknown".to_string());
+    fs::write(&path, controller_source(&catalyst_version)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    logger::success(&format!("Generated {}", path.strip_prefix(&config.project_dir).unwrap_or(&path).display()))?;
+
+    for line in registration(module) {
+        logger::plain(&line)?;
+    }
+    Ok(())
+}
+
+// What to add to the Rocket setup so the generated controller is served
+fn registration(module: &str) -> Vec<String> {
+    vec![
+        "Register it in your Rocket setup:".to_string(),
+        "  - declare the module (`pub mod healthz;`)".to_string(),
+        format!("  - call `{}::mark_started()` before launching so uptime counts from startup", module),
+        format!("  - mount it: `.mount(\"/\", routes![{}::healthz])`", module),
+    ]
+}
+
+fn controller_source(catalyst_version: &str) -> String {
+    format!(
+        r#"// Generated by `blast gen health`. Checked by `blast status --remote <url>`.
+// Regenerate with `blast gen health --force` after upgrading Catalyst so catalyst_version stays accurate.
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::serde::json::{{json, Value}};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+const CATALYST_VERSION: &str = "{catalyst_version}";
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+// Call once at startup; otherwise uptime counts from the first health check
+pub fn mark_started() {{
+    STARTED.get_or_init(Instant::now);
+}}
+
+// Cheap connectivity check that never panics: connect and run SELECT 1
+fn database_check() -> (bool, u128) {{
+    let started = Instant::now();
+    let ok = std::env::var("DATABASE_URL")
+        .ok()
+        .and_then(|url| PgConnection::establish(&url).ok())
+        .map(|mut conn| diesel::sql_query("SELECT 1").execute(&mut conn).is_ok())
+        .unwrap_or(false);
+    (ok, started.elapsed().as_millis())
+}}
+
+#[get("/healthz")]
+pub async fn healthz() -> (Status, Value) {{
+    let (database_ok, latency_ms) = rocket::tokio::task::spawn_blocking(database_check).await.unwrap_or((false, 0));
+    let environment = std::env::var("CATALYST_ENV").or_else(|_| std::env::var("ROCKET_PROFILE")).unwrap_or_else(|_| "unknown".to_string());
+    let status = if database_ok {{ Status::Ok }} else {{ Status::ServiceUnavailable }};
+
+    (
+        status,
+        json!({{
+            "status": if database_ok {{ "ok" }} else {{ "degraded" }},
+            "version": env!("CARGO_PKG_VERSION"),
+            "catalyst_version": CATALYST_VERSION,
+            "environment": environment,
+            "database": {{ "ok": database_ok, "latency_ms": latency_ms }},
+            "uptime_secs": STARTED.get_or_init(Instant::now).elapsed().as_secs(),
+        }}),
+    )
+}}
+"#,
+        catalyst_version = catalyst_version
+    )
+}
+
+// `blast status --remote <url>`: hit /healthz, validate the response and print a summary. Returns the exit code
+pub fn check_remote(config: &Config, url: &str, timeout_secs: u64) -> i32 {
+    let endpoint = health_url(url);
+    logger::plain(&format!("Checking {}", endpoint)).unwrap_or_default();
+
+    let client = match reqwest::blocking::Client::builder().timeout(Duration::from_secs(timeout_secs)).build() {
+        Ok(client) => client,
+        Err(e) => return fail(EXIT_UNREACHABLE, &format!("Failed to create HTTP client: {}", e)),
+    };
+
+    let response = match client.get(&endpoint).send() {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => return fail(EXIT_TIMEOUT, &format!("No response within {}s", timeout_secs)),
+        Err(e) => return fail(EXIT_UNREACHABLE, &format!("Could not reach {}: {}", endpoint, e)),
+    };
+
+    let http_status = response.status();
+    let body = match response.text() {
+        Ok(body) => body,
+        Err(e) if e.is_timeout() => return fail(EXIT_TIMEOUT, &format!("Response body not received within {}s", timeout_secs)),
+        Err(e) => return fail(EXIT_UNREACHABLE, &format!("Failed to read response: {}", e)),
+    };
+
+    // A degraded app answers 503 with a valid body; anything else non-200 is an HTTP failure
+    let health: Option<Value> = serde_json::from_str(&body).ok();
+    if !(http_status.is_success() || (http_status.as_u16() == 503 && health.is_some())) {
+        return fail(EXIT_HTTP_ERROR, &format!("HTTP {}", http_status));
+    }
+
+    let Some(health) = health else {
+        return fail(EXIT_INVALID_RESPONSE, "Response is not JSON");
+    };
+    if let Err(e) = validate_shape(&health) {
+        return fail(EXIT_INVALID_RESPONSE, &e);
+    }
+
+    for line in report(&health, http_status.as_u16(), config.catalyst_version()) {
+        logger::plain(&line).unwrap_or_default();
+    }
+
+    let status = health["status"].as_str().unwrap_or_default();
+    let database_ok = health["database"]["ok"].as_bool().unwrap_or(false);
+    if status == "ok" && database_ok {
+        EXIT_HEALTHY
+    } else {
+        EXIT_UNHEALTHY
+    }
+}
+
+// The summary of a valid health response, compared against the local catalyst_version
+fn report(health: &Value, http_status: u16, local_catalyst: Option<String>) -> Vec<String> {
+    let database_ok = health["database"]["ok"].as_bool().unwrap_or(false);
+    let remote_catalyst = health["catalyst_version"].as_str().unwrap_or("unknown");
+
+    let database = match health["database"]["latency_ms"].as_u64() {
+        Some(latency) if database_ok => format!("connected ({} ms)", latency),
+        _ if database_ok => "connected".to_string(),
+        _ => "unreachable".to_string(),
+    };
+    let catalyst = match local_catalyst {
+        Some(local) if local == remote_catalyst => remote_catalyst.to_string(),
+        Some(local) => format!("{} (local Catalyst.toml has {})", remote_catalyst, local),
+        None => format!("{} (no [settings] catalyst_version locally to compare)", remote_catalyst),
+    };
+    vec![
+        format!("Status:      {} (HTTP {})", health["status"].as_str().unwrap_or_default().to_uppercase(), http_status),
+        format!("Version:     {}", health["version"].as_str().unwrap_or_default()),
+        format!("Environment: {}", health["environment"].as_str().unwrap_or_default()),
+        format!("Database:    {}", database),
+        format!("Uptime:      {}", format_uptime(health["uptime_secs"].as_u64().unwrap_or(0))),
+        format!("Catalyst:    {}", catalyst),
+    ]
+}
+
+// Accept a bare base URL or the full endpoint
+fn health_url(url: &str) -> String {
+    let trimmed = url.trim_end_matches('/');
+    if trimmed.ends_with("/healthz") {
+        trimmed.to_string()
+    } else {
+        format!("{}/healthz", trimmed)
+    }
+}
+
+fn validate_shape(health: &Value) -> Result<(), String> {
+    let expected = [
+        ("status", health["status"].is_string()),
+        ("version", health["version"].is_string()),
+        ("catalyst_version", health["catalyst_version"].is_string()),
+        ("environment", health["environment"].is_string()),
+        ("database.ok", health["database"]["ok"].is_boolean()),
+        ("uptime_secs", health["uptime_secs"].is_u64()),
+    ];
+
+    let missing: Vec<&str> = expected.iter().filter(|(_, present)| !present).map(|(field, _)| *field).collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Unexpected health response, missing or mistyped: {}", missing.join(", ")))
+    }
+}
+
+fn fail(code: i32, message: &str) -> i32 {
+    logger::plain(&format!("Status:      DOWN {}", message)).unwrap_or_default();
+    code
+}
+
+fn format_uptime(seconds: u64) -> String {
+    match seconds {
+        s if s >= 86400 => format!("{}d {}h", s / 86400, (s % 86400) / 3600),
+        s if s >= 3600 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}s", s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::{render, LogLevel};
+
+    #[test]
+    fn registration_renders_without_styling_in_plain_output() {
+        let lines: Vec<String> = registration("crate::routes::healthz").iter().map(|line| render(LogLevel::Plain, line, false)).collect();
+        assert_eq!(
+            lines.join("\n"),
+            "Register it in your Rocket setup:\n  \
+             - declare the module (`pub mod healthz;`)\n  \
+             - call `crate::routes::healthz::mark_started()` before launching so uptime counts from startup\n  \
+             - mount it: `.mount(\"/\", routes![crate::routes::healthz::healthz])`"
+        );
+    }
+
+    #[test]
+    fn report_compares_the_remote_catalyst_version() {
+        let health = serde_json::json!({
+            "status": "degraded", "version": "1.2.0", "catalyst_version": "0.9.0", "environment": "prod",
+            "database": { "ok": false, "latency_ms": null }, "uptime_secs": 3720,
+        });
+        assert_eq!(
+            report(&health, 503, Some("0.8.0".to_string())),
+            [
+                "Status:      DEGRADED (HTTP 503)",
+                "Version:     1.2.0",
+                "Environment: prod",
+                "Database:    unreachable",
+                "Uptime:      1h 2m",
+                "Catalyst:    0.9.0 (local Catalyst.toml has 0.8.0)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,992 @@
+use console::style;
+
+use crate::commands::Command;
+
+// One flag of a command, with its value placeholder: "--port <port>"
+pub struct Flag {
+    pub name: &'static str,
+    pub about: &'static str,
+}
+
+// Everything `blast help`, `blast help <command>`, the unknown-command hint and the completion scripts know about
+// a command. Usage lines are checked against parse_cli_args by the tests below
+pub struct CommandHelp {
+    pub name: &'static str,
+    pub group: &'static str,
+    pub summary: &'static str,
+    pub usage: &'static [&'static str],
+    pub flags: &'static [Flag],
+    pub examples: &'static [&'static str],
+}
+
+const fn flag(name: &'static str, about: &'static str) -> Flag {
+    Flag { name, about }
+}
+
+// Options main.rs accepts before or after any command
+pub const GLOBAL_FLAGS: &[Flag] = &[
+    flag("-v, --verbose", "Show info and debug messages"),
+    flag("-vv", "Also stream diesel, psql and git output live (BLAST_VERBOSE=0/1/2 overrides)"),
+    flag("-q, --quiet", "Only print warnings, errors and results: no info lines, progress bars or spinners"),
+    flag("--env <name>", "Use this environment for one command without changing Catalyst.toml"),
+    flag("-p <member>", "Run in one app of a [workspace], by its path or directory name"),
+    flag("--workspace", "Run the command in every [workspace] member, one after the other"),
+    flag("--force-overwrite <file|all>", "Let gen structs/models replace generated files you edited"),
+    flag("--dry-run", "Show what gen structs/models and schema would change as diffs, without writing"),
+    flag("--diff", "Show the same diffs while writing"),
+    flag("--log-format <text|json>", "Print log and progress lines as JSON objects, one per line"),
+    flag("--skip-hooks", "Run scss/css/js steps without their [assets.hooks] commands"),
+    flag("--profile", "Break down where a long command spent its time, by step and external tool"),
+    flag("--allow-remote-destructive", "Let --yes and scripts roll back, seed, drop, reset or restore a non-local database"),
+];
+
+// Summaries of the commands that only exist with a subcommand (`blast db`, `blast gen`)
+pub const FAMILIES: &[(&str, &str)] = &[
+    ("env", "Read and edit .env, switch environments"),
+    ("config", "Validate, read and edit Catalyst.toml"),
+    ("db", "Create, drop, reset, snapshot, baseline or open a console on the database"),
+    ("gen", "Generate structs, models, controllers, middleware, locales, an OpenAPI spec, Docker files or storage buckets"),
+    ("check", "Check templates, openapi.yaml or locales"),
+    ("spark", "Add, list, verify, create or lint spark plugins"),
+];
+
+const CONNECTION: Flag = flag("--connection <name>", "Use DATABASE_URL_<NAME> instead of DATABASE_URL");
+const YES: Flag = flag("--yes", "Skip the confirmation prompt");
+const PROD: Flag = flag("--i-know-what-im-doing", "Allow it while the environment is prod");
+const FORCE: Flag = flag("--force", "Overwrite files you've edited since they were generated");
+const TABLE_FLAGS: &[Flag] = &[
+    flag("--all", "Regenerate every table, not just the ones whose schema changed"),
+    flag("--only <tables>", "Only these tables, comma-separated, whether or not they changed"),
+    flag("--except <tables>", "Every table but these, comma-separated"),
+];
+
+pub const COMMANDS: &[CommandHelp] = &[
+    // App
+    CommandHelp {
+        name: "refresh",
+        group: "App",
+        summary: "Roll back, migrate, seed, regenerate schema, structs and models, and process assets",
+        usage: &["blast refresh [--all] [--no-drop] [--skip-seed] [--skip-assets] [--steps <steps>] [--connection <name>] [--only <tables>] [--except <tables>]"],
+        flags: &[
+            flag("--all", "Regenerate structs and models for every table, not just changed ones"),
+            flag("--no-drop", "Skip rollback and seeding: apply pending migrations and regenerate (default in prod)"),
+            flag("--skip-seed", "Skip seeding"),
+            flag("--skip-assets", "Skip SCSS/CSS/JS processing"),
+            flag("--steps <steps>", "Run only these steps, comma-separated: rollback, migrate, seed, schema, structs, models, assets"),
+            flag("--connection <name>", "Roll back, migrate and print the schema of this connection too, or all of them"),
+            flag("--only <tables>", "Limit the structs and models steps, as for gen structs/models"),
+            flag("--except <tables>", "Leave these tables out of the structs and models steps"),
+        ],
+        examples: &["blast refresh", "blast refresh --steps migrate,schema,structs", "blast refresh --no-drop --skip-assets"],
+    },
+    CommandHelp {
+        name: "run",
+        group: "App",
+        summary: "Run the development server",
+        usage: &["blast run [--port <port>]", "blast run --docker"],
+        flags: &[flag("--port <port>", "Listen on this port instead of [server] port (default 8000)"), flag("--docker", "Run the app and its database with docker compose up")],
+        examples: &["blast run --port 8080"],
+    },
+    CommandHelp {
+        name: "run-prod",
+        group: "App",
+        summary: "Run the production server",
+        usage: &["blast run-prod [--port <port>]"],
+        flags: &[flag("--port <port>", "Listen on this port instead of [server] port (default 8000)")],
+        examples: &[],
+    },
+    CommandHelp { name: "stop", group: "App", summary: "Stop the running server", usage: &["blast stop"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "watch",
+        group: "App",
+        summary: "Rebuild assets when src/assets changes and refresh the browser (see gen livereload)",
+        usage: &["blast watch"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "status",
+        group: "App",
+        summary: "Show whether the server is running, its URL and the last operation",
+        usage: &["blast status", "blast status --remote <url> [--timeout <secs>]"],
+        flags: &[
+            flag("--remote <url>", "Check a deployed app's /healthz instead (exit 2 unhealthy, 3 HTTP error, 4 timeout, 5 unreachable, 6 invalid response)"),
+            flag("--timeout <secs>", "Timeout for --remote (default 5)"),
+        ],
+        examples: &["blast status --remote https://shop.example.com --timeout 10"],
+    },
+    CommandHelp {
+        name: "build",
+        group: "App",
+        summary: "Release build and production assets, bundled into dist/ with a run.sh",
+        usage: &["blast build [--tar] [--no-cache]"],
+        flags: &[flag("--tar", "Also write a timestamped <project>-<time>.tar.gz of the bundle"), flag("--no-cache", "Rebuild assets even if their sources haven't changed")],
+        examples: &["blast build --tar"],
+    },
+    CommandHelp {
+        name: "test",
+        group: "App",
+        summary: "Drop, create and migrate the test database, then run cargo test against it",
+        usage: &["blast test [--keep-db] [--seed] [--db-suffix <suffix>] [-- <cargo test args>]"],
+        flags: &[
+            flag("--keep-db", "Reuse the test database, only running pending migrations"),
+            flag("--seed", "Also run the seed files in src/database/seeds/test/"),
+            flag("--db-suffix <suffix>", "Use <test database>_<suffix>, e.g. one per parallel run"),
+        ],
+        examples: &["blast test -- users::", "blast test --keep-db --db-suffix ci2"],
+    },
+    CommandHelp {
+        name: "routes",
+        group: "App",
+        summary: "List Rocket routes: method, mounted path, handler and file:line",
+        usage: &["blast routes [--grep <text>] [--output <table|json>]"],
+        flags: &[flag("--grep <text>", "Only routes whose path, handler or file fuzzy-matches the text"), flag("--output <table|json>", "Print the routes as a table (default) or JSON")],
+        examples: &["blast routes --grep users"],
+    },
+    CommandHelp {
+        name: "dashboard",
+        group: "App",
+        summary: "Launch the interactive dashboard (zellij, or the built-in one without it)",
+        usage: &["blast dashboard [--builtin]"],
+        flags: &[flag("--builtin", "Use the built-in single-terminal dashboard even if zellij is installed")],
+        examples: &["blast   # without arguments, blast launches the dashboard"],
+    },
+    CommandHelp { name: "cli", group: "App", summary: "Launch the interactive CLI", usage: &["blast cli"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "toggle-env",
+        group: "App",
+        summary: "Cycle through dev, the environments declared in [environments], and prod",
+        usage: &["blast toggle-env", "blast env toggle"],
+        flags: &[],
+        examples: &[],
+    },
+    // Environment
+    CommandHelp { name: "env get", group: "Environment", summary: "Print the value of a key from the env files", usage: &["blast env get <key>"], flags: &[], examples: &["blast env get DATABASE_URL"] },
+    CommandHelp {
+        name: "env set",
+        group: "Environment",
+        summary: "Set a key in .env (created if absent, comments are preserved)",
+        usage: &["blast env set <key> <value>"],
+        flags: &[],
+        examples: &["blast env set SMTP_HOST mail.example.com"],
+    },
+    CommandHelp { name: "env unset", group: "Environment", summary: "Remove a key from .env", usage: &["blast env unset <key>"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "env check",
+        group: "Environment",
+        summary: "Check required keys and placeholders, showing which env file each value comes from",
+        usage: &["blast env check"],
+        flags: &[],
+        examples: &["blast env check   # env files merge as .env < .env.development/.env.production < .env.local"],
+    },
+    CommandHelp { name: "env use", group: "Environment", summary: "Switch to a named environment (dev, staging, prod, ...)", usage: &["blast env use <name>"], flags: &[], examples: &["blast env use staging"] },
+    // Config
+    CommandHelp { name: "config validate", group: "Config", summary: "Warn about unknown keys and print the effective Catalyst.toml settings", usage: &["blast config validate"], flags: &[], examples: &[] },
+    CommandHelp { name: "config get", group: "Config", summary: "Print a dotted key (defaults are marked)", usage: &["blast config get <key>"], flags: &[], examples: &["blast config get codegen.models_dir"] },
+    CommandHelp { name: "config set", group: "Config", summary: "Set a dotted key, checked against the expected type", usage: &["blast config set <key> <value>"], flags: &[], examples: &["blast config set server.port 8080"] },
+    CommandHelp { name: "config add", group: "Config", summary: "Append a value to an array key", usage: &["blast config add <key> <value>"], flags: &[], examples: &["blast config add codegen.structs.ignore audit_log"] },
+    CommandHelp {
+        name: "config migrate",
+        group: "Config",
+        summary: "Upgrade Catalyst.toml to the layout this blast version expects, keeping comments and layout",
+        usage: &["blast config migrate"],
+        flags: &[],
+        examples: &["blast config migrate --dry-run"],
+    },
+    CommandHelp {
+        name: "config sync-diesel",
+        group: "Config",
+        summary: "Rewrite the diesel.toml keys that follow Catalyst.toml (schema file, table filter, migrations)",
+        usage: &["blast config sync-diesel"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "config rocket set",
+        group: "Config",
+        summary: "Set a key in Rocket.toml, in [default] or the profile of an environment",
+        usage: &["blast config rocket set <key> <value> [--profile <env>]"],
+        flags: &[flag("--profile <env>", "Environment whose profile to edit: dev is [debug], prod is [release], others their own table")],
+        examples: &["blast config rocket set log_level critical --profile prod", "blast config rocket set workers 8"],
+    },
+    // Cronjobs
+    CommandHelp { name: "cronjobs", group: "Cronjobs", summary: "Manage cronjobs in an interactive TUI", usage: &["blast cronjobs", "blast cronjobs interactive"], flags: &[], examples: &[] },
+    CommandHelp { name: "cronjobs table", group: "Cronjobs", summary: "Show a live, auto-refreshing table of cronjobs", usage: &["blast cronjobs table", "blast cronjobs live"], flags: &[], examples: &[] },
+    CommandHelp { name: "cronjobs list", group: "Cronjobs", summary: "List all scheduled jobs and their status", usage: &["blast cronjobs list"], flags: &[], examples: &[] },
+    CommandHelp { name: "cronjobs add", group: "Cronjobs", summary: "Add a cronjob that runs every <seconds>", usage: &["blast cronjobs add <name> <seconds>"], flags: &[], examples: &["blast cronjobs add cleanup_sessions 3600"] },
+    CommandHelp { name: "cronjobs remove", group: "Cronjobs", summary: "Remove a scheduled job by ID", usage: &["blast cronjobs remove <id>"], flags: &[], examples: &[] },
+    CommandHelp { name: "cronjobs toggle", group: "Cronjobs", summary: "Turn a job on or off", usage: &["blast cronjobs toggle <id>"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "cronjobs import",
+        group: "Cronjobs",
+        summary: "Add the cronjobs migration and move jobs from jobs.toml/jobs.json into the table",
+        usage: &["blast cronjobs import [<file>]"],
+        flags: &[],
+        examples: &[],
+    },
+    // Database
+    CommandHelp {
+        name: "migration",
+        group: "Database",
+        summary: "Create a new migration in the wizard, or reopen a saved spec",
+        usage: &["blast migration [--connection <name>]", "blast migration --edit <spec>"],
+        flags: &[flag("--connection <name>", "Write it to that connection's migrations directory"), flag("--edit <spec>", "Reopen a saved migration spec in the wizard")],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "migration from-spec",
+        group: "Database",
+        summary: "Create a migration from a spec in src/database/migration_specs",
+        usage: &["blast migration from-spec <spec>"],
+        flags: &[],
+        examples: &["blast migration from-spec create_orders"],
+    },
+    CommandHelp {
+        name: "migration list",
+        group: "Database",
+        summary: "List migrations with applied/pending state, when they ran and problems found",
+        usage: &["blast migration list [--connection <name>] [--output <table|json>] [--check]", "blast migrate --status"],
+        flags: &[
+            flag("--connection <name>", "Only this connection (default: all of them)"),
+            flag("--output <table|json>", "Print a table (default) or JSON"),
+            flag("--check", "Exit 1 when migrations are pending"),
+        ],
+        examples: &["blast migration list --check"],
+    },
+    CommandHelp {
+        name: "migration verify",
+        group: "Database",
+        summary: "Run pending migrations up, down and up again on a scratch database and report schema differences",
+        usage: &["blast migration verify [--connection <name|all>] [--all]"],
+        flags: &[
+            flag("--connection <name|all>", "Connection to verify (default: default)"),
+            flag("--all", "Verify every migration, not only the pending ones"),
+        ],
+        examples: &["blast migration verify", "blast migration verify --all --connection all"],
+    },
+    CommandHelp {
+        name: "migrate",
+        group: "Database",
+        summary: "Run all pending migrations",
+        usage: &["blast migrate [--connection <name>]"],
+        flags: &[flag("--connection <name>", "Migrate DATABASE_URL_<NAME>, or every connection with migrations for all")],
+        examples: &["blast migrate --connection all"],
+    },
+    CommandHelp {
+        name: "rollback",
+        group: "Database",
+        summary: "Roll back all migrations",
+        usage: &["blast rollback [--connection <name>]"],
+        flags: &[flag("--connection <name>", "Roll back DATABASE_URL_<NAME>, or every connection with migrations for all")],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "seed",
+        group: "Database",
+        summary: "Run database seeds (all or one file), SQL and Rust in file name order",
+        usage: &["blast seed [<file>] [--connection <name>]", "blast seed --list"],
+        flags: &[
+            flag("--connection <name>", "Only the seeds that target this connection (`-- blast:connection <name>` in the file)"),
+            flag("--list", "List the SQL and Rust seeds with their connection and last run"),
+        ],
+        examples: &["blast seed 002_products.sql", "blast seed --connection analytics"],
+    },
+    CommandHelp {
+        name: "seed gen",
+        group: "Database",
+        summary: "Write a seed file of fake rows for a table, from schema.rs and migrations",
+        usage: &["blast seed gen <table> [--count <n>] [--deterministic <seed>]"],
+        flags: &[flag("--count <n>", "Number of rows (default 50)"), flag("--deterministic <seed>", "Same seed, same rows")],
+        examples: &["blast seed gen users --count 200 --deterministic 7"],
+    },
+    CommandHelp { name: "schema", group: "Database", summary: "Generate the database schema", usage: &["blast schema"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "schema diff",
+        group: "Database",
+        summary: "Compare the database with schema.rs (exits 1 when they differ)",
+        usage: &["blast schema diff [--update]"],
+        flags: &[flag("--update", "Write the new schema and regenerate structs/models for changed tables")],
+        examples: &[],
+    },
+    CommandHelp { name: "db create", group: "Database", summary: "Create the database named in DATABASE_URL", usage: &["blast db create [--connection <name>]"], flags: &[CONNECTION], examples: &[] },
+    CommandHelp {
+        name: "db drop",
+        group: "Database",
+        summary: "Drop the database (asks for confirmation unless --yes)",
+        usage: &["blast db drop [--connection <name>] [--yes] [--i-know-what-im-doing]"],
+        flags: &[CONNECTION, YES, PROD],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "db reset",
+        group: "Database",
+        summary: "Drop, create, migrate, seed and regenerate schema, structs and models",
+        usage: &["blast db reset [--connection <name>] [--yes] [--i-know-what-im-doing]"],
+        flags: &[CONNECTION, YES, PROD],
+        examples: &["blast db reset --yes"],
+    },
+    CommandHelp {
+        name: "db baseline",
+        group: "Database",
+        summary: "Create a baseline migration from an existing database and mark it as applied",
+        usage: &["blast db baseline"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "db dump",
+        group: "Database",
+        summary: "Snapshot the database with pg_dump into storage/backups/",
+        usage: &["blast db dump [--connection <name>] [--data-only] [--tables <a,b>]"],
+        flags: &[CONNECTION, flag("--data-only", "Only the rows, not the schema"), flag("--tables <a,b>", "Only these tables")],
+        examples: &["blast db dump --data-only --tables users,orders"],
+    },
+    CommandHelp { name: "db snapshots", group: "Database", summary: "List the snapshots in storage/backups/ with their sizes", usage: &["blast db snapshots"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "db restore",
+        group: "Database",
+        summary: "Restore a snapshot with pg_restore (picks one when no file is given)",
+        usage: &["blast db restore [<file>] [--connection <name>] [--yes] [--i-know-what-im-doing]"],
+        flags: &[CONNECTION, YES, PROD],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "db console",
+        group: "Database",
+        summary: "Open psql (or mysql, sqlite3) on the database with the project's credentials",
+        usage: &["blast db console [--connection <name>] [--command <sql>]"],
+        flags: &[CONNECTION, flag("--command <sql>", "Run one statement, print the result and exit")],
+        examples: &["blast db console --command \"select count(*) from users\""],
+    },
+    // Code generation
+    CommandHelp {
+        name: "gen structs",
+        group: "Code generation",
+        summary: "Generate structs for tables whose schema changed",
+        usage: &["blast gen structs [--all] [--only <tables>] [--except <tables>]"],
+        flags: TABLE_FLAGS,
+        examples: &["blast gen structs --only users,orders"],
+    },
+    CommandHelp {
+        name: "gen models",
+        group: "Code generation",
+        summary: "Generate model implementations for tables whose schema changed",
+        usage: &["blast gen models [--all] [--only <tables>] [--except <tables>]"],
+        flags: TABLE_FLAGS,
+        examples: &["blast gen models --all --dry-run"],
+    },
+    CommandHelp {
+        name: "gen --explain",
+        group: "Code generation",
+        summary: "Show why structs, insertables and models were or weren't generated for a table",
+        usage: &["blast gen --explain <table>"],
+        flags: &[],
+        examples: &["blast gen --explain users"],
+    },
+    CommandHelp {
+        name: "gen auth",
+        group: "Code generation",
+        summary: "Generate register/login/refresh endpoints with JWTs and a refresh_tokens migration",
+        usage: &["blast gen auth [--force]"],
+        flags: &[FORCE],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "gen guards",
+        group: "Code generation",
+        summary: "Generate RequireRole guards from the roles and user_roles tables, and an example admin controller",
+        usage: &["blast gen guards [--force]"],
+        flags: &[FORCE],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "gen health",
+        group: "Code generation",
+        summary: "Generate a GET /healthz controller (version, environment, database, uptime)",
+        usage: &["blast gen health [--force]"],
+        flags: &[FORCE],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "gen observability",
+        group: "Code generation",
+        summary: "Generate an access log fairing, request/query metrics and GET /metrics",
+        usage: &["blast gen observability [--force]"],
+        flags: &[FORCE],
+        examples: &["blast gen observability   # blast status then shows req/s, p95 latency and error rate"],
+    },
+    CommandHelp {
+        name: "gen livereload",
+        group: "Code generation",
+        summary: "Generate a debug-only module: no-store caching and a browser refresh after blast watch rebuilds",
+        usage: &["blast gen livereload [--force]"],
+        flags: &[FORCE],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "gen locale",
+        group: "Code generation",
+        summary: "Add locales/<lang>/ with a starter file, and the locale service the first time",
+        usage: &["blast gen locale <lang> [--force]"],
+        flags: &[FORCE],
+        examples: &["blast gen locale de"],
+    },
+    CommandHelp {
+        name: "gen openapi",
+        group: "Code generation",
+        summary: "Write openapi.yaml from schema.rs, blast's controllers and routes annotated /// @openapi",
+        usage: &["blast gen openapi"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "gen storage-bucket",
+        group: "Code generation",
+        summary: "Generate a typed storage bucket accessor and its .env placeholders",
+        usage: &["blast gen storage-bucket <name> [--force]"],
+        flags: &[FORCE],
+        examples: &["blast gen storage-bucket avatars"],
+    },
+    CommandHelp {
+        name: "gen docker",
+        group: "Code generation",
+        summary: "Generate Dockerfile, .dockerignore and docker-compose.yml",
+        usage: &["blast gen docker [--force] [--no-compose]"],
+        flags: &[FORCE, flag("--no-compose", "Only generate the Dockerfile and .dockerignore")],
+        examples: &[],
+    },
+    // Assets
+    CommandHelp { name: "scss", group: "Assets", summary: "Transpile SCSS files", usage: &["blast scss"], flags: &[], examples: &[] },
+    CommandHelp { name: "css", group: "Assets", summary: "Minify CSS files", usage: &["blast css"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "publish-css",
+        group: "Assets",
+        summary: "Copy CSS files from src/assets/css to <public_dir>/css with optional minification",
+        usage: &["blast publish-css"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp { name: "js", group: "Assets", summary: "Process JS files", usage: &["blast js"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "assets optimize",
+        group: "Assets",
+        summary: "Optimize PNG/JPEG, add .webp variants and .gz/.br siblings in <public_dir>",
+        usage: &["blast assets optimize"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "check templates",
+        group: "Assets",
+        summary: "Parse every template and check the assets and routes it references",
+        usage: &["blast check templates [--watch]"],
+        flags: &[flag("--watch", "Check again whenever templates, routes or <public_dir> change")],
+        examples: &[],
+    },
+    CommandHelp { name: "check openapi", group: "Assets", summary: "Fail when openapi.yaml differs from what gen openapi would write", usage: &["blast check openapi"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "check locales",
+        group: "Assets",
+        summary: "Report keys some languages lack and t() keys in templates no language has",
+        usage: &["blast check locales"],
+        flags: &[],
+        examples: &[],
+    },
+    CommandHelp { name: "cdn", group: "Assets", summary: "Download assets (Materialize release archive, CDN for others)", usage: &["blast cdn"], flags: &[], examples: &[] },
+    // Logs
+    CommandHelp { name: "log truncate", group: "Logs", summary: "Truncate log files (all or one file)", usage: &["blast log truncate [<file>]"], flags: &[], examples: &["blast log truncate server.log"] },
+    // Sparks
+    CommandHelp {
+        name: "spark add",
+        group: "Sparks",
+        summary: "Add a spark plugin from a git repository, or from a directory (a path or file: URL)",
+        usage: &["blast spark add <source> [--review] [--link]"],
+        flags: &[
+            flag("--review", "Show every change (dependencies, env vars, files, migrations) and ask before applying"),
+            flag("--link", "Symlink a spark directory instead of copying it, so edits show up without reinstalling"),
+        ],
+        examples: &["blast spark add https://github.com/catalyst-framework/plznohac", "blast spark add ../my-spark --link   # recorded in Catalyst.toml [sparks], installed by blast init"],
+    },
+    CommandHelp { name: "spark list", group: "Sparks", summary: "List the sparks in Catalyst.toml with their installed version and source", usage: &["blast spark list"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "spark verify",
+        group: "Sparks",
+        summary: "Cross-check Catalyst.toml [sparks], spark directories and .env for drift",
+        usage: &["blast spark verify [--fix]"],
+        flags: &[flag("--fix", "Apply non-destructive fixes and ask before removing anything")],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "spark new",
+        group: "Sparks",
+        summary: "Create a spark skeleton: manifest, entry point, example migration and README",
+        usage: &["blast spark new <name> [--dir <path>]"],
+        flags: &[flag("--dir <path>", "Directory to create it in (default: ./<name>)")],
+        examples: &[],
+    },
+    CommandHelp {
+        name: "spark lint",
+        group: "Sparks",
+        summary: "Check a spark before publishing: manifest, migration paths, env names, versions",
+        usage: &["blast spark lint [<path>]"],
+        flags: &[],
+        examples: &[],
+    },
+    // Project
+    CommandHelp {
+        name: "new",
+        group: "Project",
+        summary: "Create a new project, or finish one blast new left half-created",
+        usage: &["blast new <name> [--template <name>] [--cache <backend>] [--from <git-url>] [--dev] [--yes] [--i18n] [--no-git]", "blast new --repair <dir> [--template <name>]"],
+        flags: &[
+            flag("--template <name>", "Project template: full, api or minimal (asks when omitted)"),
+            flag("--cache <backend>", "Response cache backend: memory or redis (asks when omitted)"),
+            flag("--from <git-url>", "Create the project from your own starter repository"),
+            flag("--dev", "Use the dev branch of the template repository"),
+            flag("--yes", "Don't prompt: full template and memory cache unless given, no .env editing"),
+            flag("--i18n", "Add the locale service and an English locale (see gen locale)"),
+            flag("--no-git", "Don't create a git repository, .gitignore or first commit"),
+            flag("--repair <dir>", "Restore only what's missing; --template picks the template (default: [settings] template, or full)"),
+        ],
+        examples: &["blast new shop --template api --cache redis --yes", "blast new --repair shop"],
+    },
+    CommandHelp {
+        name: "init",
+        group: "Project",
+        summary: "Initialize the project (migrations, seeds, assets, codegen), repairing and resuming as needed",
+        usage: &["blast init [--no-baseline] [--resume] [--only <phases>]"],
+        flags: &[
+            flag("--no-baseline", "Don't offer a baseline migration for an existing database"),
+            flag("--resume", "Skip the phases that finished in the last init (recorded in .blast/init-state)"),
+            flag("--only <phases>", "Run only these phases, comma-separated: database, assets, sparks, or migrate, schema, codegen, download, process"),
+        ],
+        examples: &["blast init --only assets", "blast init --profile"],
+    },
+    CommandHelp {
+        name: "verify",
+        group: "Project",
+        summary: "Create a project from the template in a temp directory, init it and cargo check it",
+        usage: &["blast verify [--template <name>] [--keep]"],
+        flags: &[flag("--template <name>", "Template to verify (default: full)"), flag("--keep", "Keep the temp project for debugging")],
+        examples: &["BLAST_VERIFY_DATABASE_URL=postgres://localhost/scratch blast verify   # the database is reset; without it init is skipped"],
+    },
+    CommandHelp { name: "doctor", group: "Project", summary: "Check the project for missing files, .env keys and an unfinished init", usage: &["blast doctor"], flags: &[], examples: &[] },
+    CommandHelp {
+        name: "report",
+        group: "Project",
+        summary: "Write a bug report: versions, redacted config, the last tool runs and blast's log",
+        usage: &["blast report [--last <n>] [--output <file>]"],
+        flags: &[
+            flag("--last <n>", "Include this many tool runs from storage/logs/tools.jsonl (default 10)"),
+            flag("--output <file>", "Write it here instead of storage/logs/blast-report-<time>.md, or - for stdout"),
+        ],
+        examples: &["blast report --last 25 --output -"],
+    },
+    CommandHelp {
+        name: "clean",
+        group: "Project",
+        summary: "List generated and downloaded files to delete; nothing is deleted without --yes",
+        usage: &["blast clean [--codegen] [--assets] [--downloads] [--logs] [--all] [--yes]"],
+        flags: &[
+            flag("--codegen", "Generated structs and models, except files you edited"),
+            flag("--assets", "Compiled CSS/JS in public_dir and the downloaded fonts"),
+            flag("--downloads", "src/assets/materialize and the JS bundles blast cdn fetched"),
+            flag("--logs", "storage/logs and storage/blast/blast.log"),
+            flag("--all", "All of the above"),
+            flag("--yes", "Delete them"),
+        ],
+        examples: &["blast clean --all --yes"],
+    },
+    CommandHelp {
+        name: "self update",
+        group: "Project",
+        summary: "Install the latest blast release for this platform, checksum-verified",
+        usage: &["blast self update [--check]"],
+        flags: &[flag("--check", "Only report whether a newer version exists")],
+        examples: &["BLAST_NO_UPDATE_CHECK=1 blast migrate   # blast checks once a day by itself unless told not to"],
+    },
+    CommandHelp {
+        name: "completions",
+        group: "Project",
+        summary: "Print a completion script for bash, zsh or fish",
+        usage: &["blast completions <shell>"],
+        flags: &[],
+        examples: &["blast completions zsh > ~/.zsh/completions/_blast"],
+    },
+    CommandHelp {
+        name: "help",
+        group: "Project",
+        summary: "Show this overview, or the flags and examples of one command",
+        usage: &["blast help [<command>]", "blast <command> --help"],
+        flags: &[],
+        examples: &["blast help db reset", "blast gen models --help"],
+    },
+];
+
+// The help entry of a command. The match is exhaustive, so a new Command variant doesn't compile until it has one
+pub fn topic(cmd: &Command) -> Option<&'static str> {
+    Some(match cmd {
+        Command::NewProject(..) | Command::RepairProject(..) => "new",
+        Command::Verify(..) => "verify",
+        Command::Doctor => "doctor",
+        Command::SelfUpdate(_) => "self update",
+        Command::Report(..) => "report",
+        Command::Clean(_) => "clean",
+        Command::InitProject(..) => "init",
+        Command::NewMigration(_) | Command::EditMigrationSpec(_) => "migration",
+        Command::MigrationFromSpec(_) => "migration from-spec",
+        Command::Migrate(_) => "migrate",
+        Command::MigrationStatus(..) => "migration list",
+        Command::MigrationVerify(..) => "migration verify",
+        Command::Rollback(_) => "rollback",
+        Command::Seed(..) | Command::SeedList => "seed",
+        Command::SeedGen(..) => "seed gen",
+        Command::GenerateSchema => "schema",
+        Command::SchemaDiff(_) => "schema diff",
+        Command::DbCreate(_) => "db create",
+        Command::DbDrop(..) => "db drop",
+        Command::DbReset(..) => "db reset",
+        Command::DbBaseline => "db baseline",
+        Command::DbDump(..) => "db dump",
+        Command::DbSnapshots => "db snapshots",
+        Command::DbRestore(..) => "db restore",
+        Command::DbConsole(..) => "db console",
+        Command::GenerateStructs(..) => "gen structs",
+        Command::GenerateModels(..) => "gen models",
+        Command::ExplainCodegen(_) => "gen --explain",
+        Command::TranspileScss => "scss",
+        Command::MinifyCss => "css",
+        Command::PublishCss => "publish-css",
+        Command::ProcessJs => "js",
+        Command::OptimizeAssets => "assets optimize",
+        Command::CheckTemplates(_) => "check templates",
+        Command::CheckOpenApi => "check openapi",
+        Command::CheckLocales => "check locales",
+        Command::DownloadCdn => "cdn",
+        Command::RunDevServer(_) | Command::RunDocker => "run",
+        Command::RunProdServer(_) => "run-prod",
+        Command::ServerStatus(..) => "status",
+        Command::Build(..) => "build",
+        Command::Routes(..) => "routes",
+        Command::Test(..) => "test",
+        Command::GenerateDocker(..) => "gen docker",
+        Command::GenerateAuth(_) => "gen auth",
+        Command::GenerateGuards(_) => "gen guards",
+        Command::GenerateHealth(_) => "gen health",
+        Command::GenerateObservability(_) => "gen observability",
+        Command::GenerateLiveReload(_) => "gen livereload",
+        Command::GenerateLocale(..) => "gen locale",
+        Command::GenerateOpenApi => "gen openapi",
+        Command::GenerateStorageBucket(..) => "gen storage-bucket",
+        Command::StopServer => "stop",
+        Command::Watch => "watch",
+        Command::LaunchDashboard(_) => "dashboard",
+        Command::RunInteractiveCLI => "cli",
+        Command::ToggleEnvironment => "toggle-env",
+        Command::EnvGet(_) => "env get",
+        Command::EnvSet(..) => "env set",
+        Command::EnvUnset(_) => "env unset",
+        Command::EnvCheck => "env check",
+        Command::EnvUse(_) => "env use",
+        Command::ConfigValidate => "config validate",
+        Command::ConfigGet(_) => "config get",
+        Command::ConfigSet(..) => "config set",
+        Command::ConfigMigrate => "config migrate",
+        Command::ConfigSyncDiesel => "config sync-diesel",
+        Command::ConfigRocketSet(..) => "config rocket set",
+        Command::ConfigAdd(..) => "config add",
+        Command::LogTruncate(_) => "log truncate",
+        Command::AddSpark(..) => "spark add",
+        Command::SparkList => "spark list",
+        Command::SparkVerify(_) => "spark verify",
+        Command::SparkNew(..) => "spark new",
+        Command::SparkLint(_) => "spark lint",
+        Command::CronjobsList => "cronjobs list",
+        Command::CronjobsAdd(..) => "cronjobs add",
+        Command::CronjobsRemove(_) => "cronjobs remove",
+        Command::CronjobsToggle(_) => "cronjobs toggle",
+        Command::CronjobsImport(_) => "cronjobs import",
+        Command::CronjobsInteractive => "cronjobs",
+        Command::CronjobsLiveTable => "cronjobs table",
+        Command::RefreshApp(_) => "refresh",
+        Command::Completions(_) => "completions",
+        Command::Help(_) => "help",
+        // Internal: the completion scripts' value lookups, and the interactive CLI's way out
+        Command::CompleteValues(_) | Command::InvalidArgument(_) | Command::Exit => return None,
+    })
+}
+
+// The words of each usage line after `blast`, without the brackets of optional parts
+fn usage_words(usage: &str) -> Vec<&str> {
+    usage.split_whitespace().skip(1).map(|word| word.trim_matches(|c| c == '[' || c == ']')).filter(|word| !word.is_empty()).collect()
+}
+
+fn is_literal(word: &str) -> bool {
+    !word.starts_with(['-', '<'])
+}
+
+// Command words in the order of the table: "refresh", "run", ..., "db", "gen"
+pub fn command_words() -> Vec<&'static str> {
+    let mut words: Vec<&str> = Vec::new();
+    for usage in COMMANDS.iter().flat_map(|command| command.usage) {
+        if let Some(&word) = usage_words(usage).first().filter(|word| is_literal(word)) {
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+    }
+    words
+}
+
+// Literal second words after a command word: "db" -> create, drop, ...
+pub fn subcommands(word: &str) -> Vec<&'static str> {
+    let mut subcommands: Vec<&str> = Vec::new();
+    for usage in COMMANDS.iter().flat_map(|command| command.usage) {
+        let words = usage_words(usage);
+        if words.first() == Some(&word) {
+            if let Some(&sub) = words.get(1).filter(|sub| is_literal(sub)) {
+                if !subcommands.contains(&sub) {
+                    subcommands.push(sub);
+                }
+            }
+        }
+    }
+    subcommands
+}
+
+// Flags any usage of a command word mentions
+pub fn flags(word: &str) -> Vec<&'static str> {
+    let mut flags: Vec<&str> = Vec::new();
+    for usage in COMMANDS.iter().flat_map(|command| command.usage) {
+        let words = usage_words(usage);
+        if words.first() == Some(&word) {
+            for flag in words.into_iter().filter(|flag| flag.starts_with("--") && *flag != "--") {
+                if !flags.contains(&flag) {
+                    flags.push(flag);
+                }
+            }
+        }
+    }
+    flags
+}
+
+// One line about a command word, for the completion scripts
+pub fn summary(word: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|command| command.name == word)
+        .map(|command| command.summary)
+        .or_else(|| FAMILIES.iter().find(|(family, _)| *family == word).map(|(_, summary)| *summary))
+        .or_else(|| COMMANDS.iter().find(|command| command.name.split(' ').next() == Some(word)).map(|command| command.summary))
+        .unwrap_or_default()
+}
+
+fn styled() -> bool {
+    !crate::logger::plain_output()
+}
+
+fn heading(text: &str) -> String {
+    if styled() { style(text).bold().to_string() } else { text.to_string() }
+}
+
+fn highlight(text: &str) -> String {
+    if styled() { style(text).cyan().to_string() } else { text.to_string() }
+}
+
+// Aligned "  left   right" rows; the left column is padded before styling so colors don't break the alignment
+fn print_rows(rows: &[(String, &str)]) {
+    let width = rows.iter().map(|(left, _)| left.chars().count()).max().unwrap_or(0);
+    for (left, right) in rows {
+        println!("  {}  {}", highlight(&format!("{:<width$}", left)), right);
+    }
+}
+
+// "db restore [<file>]": the command and its positional arguments from the first usage line
+fn synopsis(command: &CommandHelp) -> String {
+    let words = command.usage.first().map(|usage| usage.split_whitespace().skip(1).take_while(|word| !word.starts_with('-') && !word.starts_with("[-")).collect::<Vec<_>>().join(" "));
+    words.unwrap_or_else(|| command.name.to_string())
+}
+
+// `blast help`
+pub fn print_overview() {
+    println!("{} - Suckless Web Framework CLI", heading("blast"));
+    println!();
+    println!("{}", heading("USAGE:"));
+    println!("  blast [OPTIONS] <COMMAND>");
+    println!();
+    println!("{}", heading("OPTIONS:"));
+    print_rows(&GLOBAL_FLAGS.iter().map(|flag| (flag.name.to_string(), flag.about)).collect::<Vec<_>>());
+
+    let mut groups: Vec<&str> = Vec::new();
+    for command in COMMANDS {
+        if !groups.contains(&command.group) {
+            groups.push(command.group);
+        }
+    }
+    // One width for every group, so the summaries line up down the whole page
+    let width = COMMANDS.iter().map(|command| synopsis(command).chars().count()).max().unwrap_or(0);
+    for group in groups {
+        println!();
+        println!("{}", heading(&format!("{} COMMANDS:", group.to_uppercase())));
+        for command in COMMANDS.iter().filter(|command| command.group == group) {
+            println!("  {}  {}", highlight(&format!("{:<width$}", synopsis(command))), command.summary);
+        }
+    }
+    println!();
+    println!("Run `blast help <command>` or `blast <command> --help` for its flags and examples.");
+    println!("Running `blast` without arguments launches the interactive dashboard.");
+}
+
+// `blast help <words>` and `blast <words> --help`. Extra words (values, flags) are ignored: the longest
+// known command at the start wins, so `blast help env set KEY` shows `env set`
+pub fn print_topic(topic: &str) -> Result<(), String> {
+    let words: Vec<&str> = topic.split_whitespace().collect();
+    for count in (1..=words.len()).rev() {
+        let name = words[..count].join(" ");
+        if let Some(command) = COMMANDS.iter().find(|command| command.name == name) {
+            print_page(command);
+            return Ok(());
+        }
+    }
+    // `blast help db` lists the db commands
+    if let [family] = words.as_slice() {
+        if FAMILIES.iter().any(|(name, _)| name == family) {
+            print_family(family);
+            return Ok(());
+        }
+    }
+    Err(unknown_command(&words))
+}
+
+fn print_page(command: &CommandHelp) {
+    println!("{} - {}", heading(&format!("blast {}", command.name)), command.summary);
+    println!();
+    println!("{}", heading("USAGE:"));
+    for usage in command.usage {
+        println!("  {}", usage);
+    }
+    if !command.flags.is_empty() {
+        println!();
+        println!("{}", heading("FLAGS:"));
+        print_rows(&command.flags.iter().map(|flag| (flag.name.to_string(), flag.about)).collect::<Vec<_>>());
+    }
+    if !command.examples.is_empty() {
+        println!();
+        println!("{}", heading("EXAMPLES:"));
+        for example in command.examples {
+            println!("  {}", example);
+        }
+    }
+
+    // `blast help seed` also points at `seed gen`
+    let related: Vec<String> = COMMANDS.iter().filter(|other| other.name.starts_with(&format!("{} ", command.name))).map(|other| format!("blast help {}", other.name)).collect();
+    if !related.is_empty() {
+        println!();
+        println!("See also: {}", related.join(", "));
+    }
+    println!();
+    println!("Global options such as -v, --env and --dry-run are listed by `blast help`.");
+}
+
+fn print_family(family: &str) {
+    println!("{} - {}", heading(&format!("blast {}", family)), summary(family));
+    println!();
+    println!("{}", heading("COMMANDS:"));
+    let prefix = format!("{} ", family);
+    print_rows(&COMMANDS.iter().filter(|command| command.name.starts_with(&prefix)).map(|command| (synopsis(command), command.summary)).collect::<Vec<_>>());
+    println!();
+    println!("Run `blast help {} <command>` for its flags and examples.", family);
+}
+
+// The candidate a typo most likely meant; short words need a close match, or "frob" would suggest "run"
+fn closest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates.iter().map(|candidate| (crate::configs::edit_distance(word, candidate), *candidate)).filter(|(distance, _)| *distance <= (word.len() / 2).clamp(1, 3)).min_by_key(|(distance, _)| *distance).map(|(_, candidate)| candidate)
+}
+
+// Why parse_cli_args didn't understand the words: the closest command when the first word is unknown,
+// the closest subcommand when the second is, otherwise the usage lines of the command
+pub fn unknown_command(args: &[&str]) -> String {
+    let words: Vec<&str> = args.iter().copied().filter(|word| !word.starts_with('-')).collect();
+    let Some(&first) = words.first() else {
+        return "Unknown option. Run `blast help` for usage information.".to_string();
+    };
+
+    let known = command_words();
+    if !known.contains(&first) {
+        let hint = match closest(first, &known) {
+            Some(suggestion) => format!(" Did you mean `blast {}`?", suggestion),
+            None => String::new(),
+        };
+        return format!("Unknown command '{}'.{} Run `blast help` for usage information.", first, hint);
+    }
+
+    let subcommands = subcommands(first);
+    if let Some(&second) = words.get(1).filter(|_| !subcommands.is_empty()) {
+        if !subcommands.contains(&second) {
+            let hint = match closest(second, &subcommands) {
+                Some(suggestion) => format!(" Did you mean `blast {} {}`?", first, suggestion),
+                None => format!(" `blast {}` takes {}.", first, subcommands.join(", ")),
+            };
+            return format!("Unknown command '{} {}'.{}", first, second, hint);
+        }
+    }
+
+    let typed: Vec<&str> = words.iter().take(2).copied().collect();
+    let usages: Vec<&str> = COMMANDS
+        .iter()
+        .flat_map(|command| command.usage)
+        .copied()
+        .filter(|usage| {
+            let usage = usage_words(usage);
+            usage.first() == Some(&first) && typed.iter().enumerate().skip(1).all(|(index, word)| usage.get(index).is_some_and(|usage_word| usage_word == word || !is_literal(usage_word)))
+        })
+        .collect();
+    let usages = if usages.is_empty() { COMMANDS.iter().flat_map(|command| command.usage).copied().filter(|usage| usage_words(usage).first() == Some(&first)).collect() } else { usages };
+    format!("Invalid arguments for `blast {}`. Usage:\n  {}\nRun `blast help {}` for details.", first, usages.join("\n  "), first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "blast db restore [<file>] [--yes]" -> ["blast", "db", "restore", "x", "--yes"]
+    fn sample_args(usage: &str) -> Vec<String> {
+        let filled = regex::Regex::new(r"<[^>]*>").unwrap().replace_all(usage, "1");
+        filled.split_whitespace().map(|word| word.trim_matches(|c| c == '[' || c == ']').to_string()).filter(|word| !word.is_empty()).collect()
+    }
+
+    #[test]
+    fn every_usage_parses_to_its_command() {
+        for command in COMMANDS {
+            assert!(!command.usage.is_empty(), "{} has no usage", command.name);
+            for usage in command.usage {
+                let parsed = crate::commands::parse_cli_args(&sample_args(usage)).unwrap_or_else(|| panic!("`{}` doesn't parse", usage));
+                assert_eq!(topic(&parsed), Some(command.name), "`{}` parses to {:?}", usage, parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_has_metadata() {
+        let mut names: Vec<&str> = COMMANDS.iter().map(|command| command.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), COMMANDS.len(), "duplicate help entries");
+
+        // Every command variant parse_cli_args can produce maps to an entry through topic()
+        for usage in COMMANDS.iter().flat_map(|command| command.usage) {
+            let parsed = crate::commands::parse_cli_args(&sample_args(usage)).unwrap();
+            assert!(topic(&parsed).is_some_and(|name| names.contains(&name)), "no help for {:?}", parsed);
+        }
+        for word in command_words() {
+            assert!(!summary(word).is_empty(), "no summary for {}", word);
+        }
+    }
+
+    #[test]
+    fn unknown_commands_suggest_the_closest_one() {
+        assert_eq!(unknown_command(&["mirgate"]), "Unknown command 'mirgate'. Did you mean `blast migrate`? Run `blast help` for usage information.");
+        assert_eq!(unknown_command(&["db", "restor"]), "Unknown command 'db restor'. Did you mean `blast db restore`?");
+        assert!(unknown_command(&["env", "set", "KEY"]).contains("  blast env set <key> <value>"));
+    }
+}
@@ -0,0 +1,413 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::configs::Config;
+use crate::dependencies::DependencyManager;
+use crate::logger::{self, Progress};
+use crate::operation::StepState;
+use crate::platform;
+
+// Which phases of the last `blast init` finished, for --resume
+const STATE_PATH: &str = ".blast/init-state";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Phase {
+    Migrate,
+    Schema,
+    Codegen,
+    Download,
+    Process,
+    Sparks,
+}
+
+// Phases run in order within a lane. The database and asset lanes run side by side; sparks waits for both
+// because installing a spark changes the schema again
+const DATABASE_LANE: &[Phase] = &[Phase::Migrate, Phase::Schema, Phase::Codegen];
+const ASSETS_LANE: &[Phase] = &[Phase::Download, Phase::Process];
+const SPARKS_LANE: &[Phase] = &[Phase::Sparks];
+
+// What `--only` accepts: a lane name for all of its phases, or a single phase
+pub const ONLY_VALUES: &str = "database assets sparks migrate schema codegen download process";
+
+impl Phase {
+    const ALL: [Phase; 6] = [Phase::Migrate, Phase::Schema, Phase::Codegen, Phase::Download, Phase::Process, Phase::Sparks];
+
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Migrate => "migrate",
+            Phase::Schema => "schema",
+            Phase::Codegen => "codegen",
+            Phase::Download => "download",
+            Phase::Process => "process",
+            Phase::Sparks => "sparks",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Phase::Migrate => "Running migrations and seeds...",
+            Phase::Schema => "Generating database schema...",
+            Phase::Codegen => "Generating structs and models...",
+            Phase::Download => "Downloading assets...",
+            Phase::Process => "Processing SCSS, CSS and JS...",
+            Phase::Sparks => "Installing spark plugins...",
+        }
+    }
+}
+
+// Phases named by --only, or every phase without it
+fn select(only: &[String]) -> Result<Vec<Phase>, String> {
+    if only.is_empty() {
+        return Ok(Phase::ALL.to_vec());
+    }
+
+    let mut selected = Vec::new();
+    for name in only {
+        let phases: &[Phase] = match name.as_str() {
+            "database" => DATABASE_LANE,
+            "assets" => ASSETS_LANE,
+            _ => match Phase::ALL.iter().find(|phase| phase.name() == name) {
+                Some(phase) => std::slice::from_ref(phase),
+                None => return Err(format!("Unknown init phase '{}'. Use one of: {}", name, ONLY_VALUES.replace(' ', ", "))),
+            },
+        };
+        selected.extend(phases.iter().filter(|phase| !selected.contains(*phase)).copied().collect::<Vec<_>>());
+    }
+    Ok(selected)
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct InitState {
+    #[serde(default)]
+    completed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    failed: Vec<String>,
+    // Cut short by Ctrl-C, so whatever they did may be incomplete
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    interrupted: Vec<String>,
+}
+
+impl InitState {
+    fn forget(&mut self, phase: Phase) {
+        for list in [&mut self.completed, &mut self.failed, &mut self.interrupted] {
+            list.retain(|name| name != phase.name());
+        }
+    }
+}
+
+fn state_path(config: &Config) -> PathBuf {
+    config.project_dir.join(STATE_PATH)
+}
+
+fn load(config: &Config) -> Option<InitState> {
+    load_from(&state_path(config))
+}
+
+fn load_from(path: &Path) -> Option<InitState> {
+    fs::read_to_string(path).ok().and_then(|content| toml::from_str(&content).ok())
+}
+
+// Phases the last `blast init` in `project_dir` left failed or cut short
+pub fn unfinished_phases(project_dir: &Path) -> Vec<String> {
+    load_from(&project_dir.join(STATE_PATH)).map(|state| state.failed.into_iter().chain(state.interrupted).collect()).unwrap_or_default()
+}
+
+enum Outcome {
+    Done,
+    Failed,
+    Interrupted,
+}
+
+// The state file, shared by the lanes and rewritten after every phase
+struct Tracker {
+    path: PathBuf,
+    state: Mutex<InitState>,
+}
+
+impl Tracker {
+    fn save(&self, state: &InitState) {
+        let result = toml::to_string_pretty(state).map_err(|e| e.to_string()).and_then(|content| crate::file_writer::write_state(&self.path, &content));
+        if let Err(e) = result {
+            logger::warning(&format!("Failed to update {}: {}", STATE_PATH, e)).unwrap_or_default();
+        }
+    }
+
+    fn record(&self, phase: Phase, outcome: &Outcome) {
+        let mut state = self.state.lock().unwrap();
+        state.forget(phase);
+        let list = match outcome {
+            Outcome::Done => &mut state.completed,
+            Outcome::Failed => &mut state.failed,
+            Outcome::Interrupted => &mut state.interrupted,
+        };
+        list.push(phase.name().to_string());
+        self.save(&state);
+    }
+}
+
+// Phases of a lane this run goes through. With --resume the ones that finished last time are skipped,
+// up to the first one that has to run again: everything after it depends on it
+fn pending(lane: &[Phase], selected: &[Phase], resume: bool, state: &InitState) -> Vec<Phase> {
+    let mut rerun = false;
+    lane.iter()
+        .filter(|phase| selected.contains(phase))
+        .filter(|phase| {
+            rerun = rerun || !resume || !state.completed.iter().any(|name| name == phase.name());
+            rerun
+        })
+        .copied()
+        .collect()
+}
+
+// `blast init`: database and asset lanes run concurrently, sparks after both. Each finished phase is recorded in
+// .blast/init-state; `--resume` skips what already finished and `--only` picks phases or lanes to run
+pub fn run(config: &Config, dep_manager: &mut DependencyManager, no_baseline: bool, resume: bool, only: &[String]) -> Result<(), String> {
+    let selected = select(only)?;
+
+    // A `blast new` or `blast init` that stopped part way: restore what's missing, then pick up where init left off
+    let findings = crate::integrity::repair(&config.project_dir, None)?;
+    let unfinished = findings.iter().find_map(|finding| match &finding.repair {
+        crate::integrity::Repair::ResumeInit(phases) => Some(phases),
+        _ => None,
+    });
+    let resume = match unfinished {
+        Some(phases) if !resume && only.is_empty() => {
+            logger::plain(&format!("The last blast init didn't finish {}; resuming from there", phases.join(", ")))?;
+            true
+        }
+        _ => resume,
+    };
+    let previous = load(config);
+    if resume && previous.is_none() {
+        logger::info("No earlier blast init recorded, running every phase")?;
+    }
+    // A plain `blast init` starts over; --resume and --only build on what the last run recorded
+    let mut state = if resume || !only.is_empty() { previous.unwrap_or_default() } else { InitState::default() };
+
+    let database = pending(DATABASE_LANE, &selected, resume, &state);
+    let mut assets = pending(ASSETS_LANE, &selected, resume, &state);
+    let sparks = pending(SPARKS_LANE, &selected, resume, &state);
+
+    // API-only and minimal projects have no [assets] section and skip the asset steps
+    if !assets.is_empty() && !crate::assets::has_asset_pipeline(config) {
+        logger::info("No [assets] section in Catalyst.toml, skipping asset downloads and processing")?;
+        assets.clear();
+    }
+
+    let skipped: Vec<&str> = selected.iter().filter(|phase| !database.contains(phase) && !assets.contains(phase) && !sparks.contains(phase) && state.completed.iter().any(|name| name == phase.name())).map(|phase| phase.name()).collect();
+    if database.is_empty() && assets.is_empty() && sparks.is_empty() {
+        return logger::success("Nothing to do: every selected phase finished in the last blast init");
+    }
+    if !skipped.is_empty() {
+        logger::info(&format!("Skipping {} (finished in the last blast init)", skipped.join(", ")))?;
+    }
+
+    logger::plain("Initializing project...")?;
+
+    if !database.is_empty() || !sparks.is_empty() {
+        dep_manager.ensure_installed(&["diesel"])?;
+        // diesel rewrites schema.rs and finds the migrations through diesel.toml
+        for change in crate::diesel_toml::sync(config)? {
+            logger::info(&format!("diesel.toml: {}", change))?;
+        }
+    }
+
+    // Reuse one database connection across the baseline and sparks steps; the database lane opens its own
+    let _db_connection = crate::database::SharedConnection::begin();
+
+    // Brownfield project: the database already has tables but there are no migrations yet
+    if !no_baseline && database.first() == Some(&Phase::Migrate) {
+        if let Some(table_count) = crate::database::brownfield_table_count(config) {
+            let create_baseline = dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                .with_prompt(format!("Found {} existing table(s) but no migrations. Create a baseline migration from the live schema?", table_count))
+                .default(true)
+                .interact()
+                .unwrap_or(false);
+
+            if create_baseline && !crate::database::baseline(config) {
+                logger::warning("Baseline migration failed - continuing without it")?;
+            }
+        }
+    }
+
+    // Phases about to run no longer count as finished until they finish again
+    for phase in database.iter().chain(&assets).chain(&sparks) {
+        state.forget(*phase);
+    }
+    let tracker = Tracker { path: state_path(config), state: Mutex::new(state) };
+    let running: Vec<&str> = database.iter().chain(&assets).chain(&sparks).map(|phase| phase.name()).collect();
+    crate::operation::set_steps(&running);
+    tracker.save(&tracker.state.lock().unwrap());
+
+    let _interrupts = platform::handle_interrupts();
+    let lanes = logger::create_lanes();
+    let database_bar = (!database.is_empty()).then(|| lanes.add("database", database.len() as u64));
+    let assets_bar = (!assets.is_empty()).then(|| lanes.add("assets  ", assets.len() as u64));
+    let mut sparks_bar = (!sparks.is_empty()).then(|| lanes.add("sparks  ", sparks.len() as u64));
+    if let Some(bar) = sparks_bar.as_mut() {
+        bar.set_message("Waiting for the other lanes...");
+    }
+
+    thread::scope(|scope| {
+        if let Some(bar) = database_bar {
+            let (phases, tracker) = (&database, &tracker);
+            scope.spawn(move || {
+                // The shared connection is per thread
+                let _db_connection = crate::database::SharedConnection::begin();
+                run_lane(config, phases, bar, tracker);
+            });
+        }
+        if let Some(bar) = assets_bar {
+            let (phases, tracker) = (&assets, &tracker);
+            scope.spawn(move || run_lane(config, phases, bar, tracker));
+        }
+    });
+    if let Some(bar) = sparks_bar {
+        run_lane(config, &sparks, bar, &tracker);
+    }
+    drop(lanes);
+
+    let state = tracker.state.into_inner().unwrap();
+    if platform::interrupted() {
+        return Err(format!("Initialization cancelled. Finished phases are recorded in {}; run `blast init --resume` to continue", STATE_PATH));
+    }
+
+    if !state.failed.is_empty() {
+        logger::warning(&format!("Finished with problems in: {}. Fix them and run `blast init --resume` to retry those phases", state.failed.join(", ")))?;
+        return Ok(());
+    }
+
+    logger::success("Project initialization complete! Your project is ready to run")?;
+    logger::plain("\nNext steps:")?;
+    logger::plain("  1. Run 'blast run' to start the development server")?;
+    logger::plain("  2. Run 'blast dashboard' to launch the interactive dashboard")
+}
+
+fn run_lane(config: &Config, phases: &[Phase], mut bar: Progress, tracker: &Tracker) {
+    for phase in phases {
+        if platform::interrupted() {
+            bar.finish("cancelled");
+            return;
+        }
+
+        bar.set_message(phase.description());
+        crate::operation::step(phase.name(), StepState::Running);
+        let span = crate::profile::span(phase.name());
+        let mut outcome = run_phase(config, *phase, &mut bar);
+        drop(span);
+        // Whatever the phase was running when Ctrl-C came was stopped part way
+        if platform::interrupted() {
+            outcome = Outcome::Interrupted;
+        }
+        tracker.record(*phase, &outcome);
+        crate::operation::step(phase.name(), if matches!(outcome, Outcome::Done) { StepState::Done } else { StepState::Failed });
+        bar.inc(1);
+    }
+
+    bar.finish(if platform::interrupted() { "cancelled" } else { "done" });
+}
+
+// Problems are reported as warnings and the lane carries on, as the rest may still be useful
+fn run_phase(config: &Config, phase: Phase, bar: &mut Progress) -> Outcome {
+    let mut problems = Vec::new();
+
+    match phase {
+        Phase::Migrate => {
+            // The cronjobs table the app's scheduler and `blast cronjobs` share
+            match crate::cronjobs::install_migration(config) {
+                Ok(Some(up_file)) => {
+                    let _ = logger::success(&format!("Added the cronjobs migration ({})", up_file));
+                }
+                Ok(None) => {}
+                Err(e) => problems.push(format!("Failed to add the cronjobs migration: {}", e)),
+            }
+            if !crate::database::migrate(config) {
+                problems.push("Some migration issues occurred - check database configuration".to_string());
+            }
+            bar.set_message("Seeding database...");
+            // All seed files: a lane running next to others can't stop for the seed picker
+            if !crate::database::seed(config, Some(0), Some("default")) {
+                // Not a failure: new projects often have nothing to seed yet
+                let _ = bar.warning("Some seeding issues occurred - this may be normal for new projects");
+            }
+        }
+        Phase::Schema => {
+            if !crate::database::force_regenerate_main_schema(config) {
+                problems.push("Some schema generation issues occurred".to_string());
+            }
+        }
+        Phase::Codegen => {
+            // Warnings only: an empty or unchanged schema also produces no structs
+            for warning in generate_code(config, bar) {
+                let _ = bar.warning(&warning);
+            }
+        }
+        Phase::Download => {
+            if let Err(e) = crate::assets::download_assets(config) {
+                problems.push(format!("Some asset downloads failed: {}", e));
+            }
+        }
+        Phase::Process => {
+            let results = [
+                ("SCSS processing", crate::assets::transpile_all_scss(config)),
+                ("CSS publishing", crate::assets::publish_css(config)),
+                ("JS processing", crate::assets::process_js(config)),
+            ];
+            for (name, result) in results {
+                if let Err(e) = result {
+                    problems.push(format!("{} error: {}", name, e));
+                }
+            }
+        }
+        Phase::Sparks => {
+            if let Err(e) = crate::sparks::install_sparks_from_config(config) {
+                problems.push(format!("Some issues with spark installation: {}", e));
+            }
+            // Also picks up spark directories added or removed by hand
+            if let Err(e) = crate::sparks::sync_generated_files(&config.project_dir) {
+                problems.push(e);
+            }
+
+            // Sparks may add migrations of their own, so the schema comes from the main DATABASE_URL once more
+            bar.set_message("Regenerating schema, structs and models...");
+            if !crate::database::force_regenerate_main_schema(config) {
+                problems.push("Failed to force-regenerate schema from main database. The schema may be incorrect.".to_string());
+            }
+            // Unchanged structs count as "not generated" here, so this only warns
+            let structs_ok = crate::structs::generate(config, false, &Default::default());
+            let models_ok = crate::models::generate(config, false, &Default::default());
+            if !structs_ok || !models_ok {
+                let _ = bar.warning("Failed to regenerate some structs or models. You may need to run 'blast gen structs' and 'blast gen models' manually.");
+            }
+        }
+    }
+
+    for problem in &problems {
+        let _ = bar.warning(problem);
+    }
+    if problems.is_empty() {
+        Outcome::Done
+    } else {
+        Outcome::Failed
+    }
+}
+
+// Structs then models, each retried once
+fn generate_code(config: &Config, bar: &mut Progress) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    bar.set_message("Generating structs...");
+    if !crate::structs::generate(config, false, &Default::default()) && !crate::structs::generate(config, false, &Default::default()) {
+        problems.push("Struct generation issues persisted - may be normal for empty schemas".to_string());
+    }
+
+    bar.set_message("Generating models...");
+    if !crate::models::generate(config, false, &Default::default()) && !crate::models::generate(config, false, &Default::default()) {
+        problems.push("Model generation issues persisted - may be normal for empty schemas".to_string());
+    }
+    problems
+}
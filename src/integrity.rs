@@ -0,0 +1,312 @@
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::Command;
+
+use dialoguer::{theme::ColorfulTheme, Confirm};
+
+use crate::envfile::EnvFile;
+use crate::logger;
+use crate::project::{self, ProjectTemplate};
+
+// Directories a finished `blast new` leaves behind that blast can recreate empty
+const SKELETON_DIRS: &[&str] = &["src/database/migrations", "storage/logs", "storage/blast"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repair {
+    // Copied from a fresh clone of the template
+    RestoreFromTemplate(String),
+    CreateDir(String),
+    WriteProjectName,
+    CreateEnv,
+    AddJwtSecret,
+    InitGit,
+    // Left for `blast init` to re-run
+    ResumeInit(Vec<String>),
+    // Nothing blast can safely do, e.g. a Cargo.toml that doesn't parse
+    Manual,
+}
+
+impl Repair {
+    fn describe(&self) -> String {
+        match self {
+            Repair::RestoreFromTemplate(path) => format!("copy {} from the Catalyst template", path),
+            Repair::CreateDir(path) => format!("create {}/", path),
+            Repair::WriteProjectName => "write the project name into Cargo.toml and Catalyst.toml".to_string(),
+            Repair::CreateEnv => "create .env from .env.example with a new JWT_SECRET".to_string(),
+            Repair::AddJwtSecret => "add a JWT_SECRET to .env".to_string(),
+            Repair::InitGit => "run git init".to_string(),
+            Repair::ResumeInit(phases) => format!("re-run only {} in blast init", phases.join(", ")),
+            Repair::Manual => "fix it by hand".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub problem: String,
+    pub repair: Repair,
+}
+
+// Template files blast can't write by itself. Only the full template has views and assets
+fn template_paths(template: ProjectTemplate) -> &'static [&'static str] {
+    match template {
+        ProjectTemplate::Full => &["Cargo.toml", "Catalyst.toml", "src/main.rs", "templates", "src/assets", "public"],
+        ProjectTemplate::Api | ProjectTemplate::Minimal => &["Cargo.toml", "Catalyst.toml", "src/main.rs"],
+    }
+}
+
+fn read_toml(path: &Path) -> Option<Result<toml::Value, String>> {
+    let content = fs::read_to_string(path).ok()?;
+    Some(content.parse::<toml::Value>().map_err(|e| e.to_string()))
+}
+
+// The template the project was created from: `template` when given, else [settings] template, else full
+pub fn project_template(project_dir: &Path, template: Option<ProjectTemplate>) -> ProjectTemplate {
+    template
+        .or_else(|| {
+            let catalyst = read_toml(&project_dir.join("Catalyst.toml"))?.ok()?;
+            ProjectTemplate::from_name(catalyst.get("settings")?.get("template")?.as_str()?)
+        })
+        .unwrap_or(ProjectTemplate::Full)
+}
+
+// Everything that marks a project `blast new` didn't finish, or a `blast init` that stopped part way
+pub fn check(project_dir: &Path, template: ProjectTemplate) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut finding = |problem: String, repair: Repair| findings.push(Finding { problem, repair });
+
+    let public_dir = crate::configs::public_dir_at(project_dir);
+    for path in template_paths(template) {
+        let in_project = crate::configs::in_public_dir(path, &public_dir);
+        if !project_dir.join(&in_project).exists() {
+            finding(format!("{} is missing", in_project), Repair::RestoreFromTemplate(path.to_string()));
+        }
+    }
+
+    match read_toml(&project_dir.join("Cargo.toml")) {
+        Some(Err(e)) => finding(format!("Cargo.toml doesn't parse: {}", e.trim()), Repair::Manual),
+        Some(Ok(cargo)) if cargo.get("package").and_then(|package| package.get("name")).is_none() => finding("Cargo.toml has no [package] name".to_string(), Repair::Manual),
+        _ => {}
+    }
+
+    // blast new writes [settings] project_name right after unpacking the template, so without it the project was never configured
+    match read_toml(&project_dir.join("Catalyst.toml")) {
+        Some(Err(e)) => finding(format!("Catalyst.toml doesn't parse: {}", e.trim()), Repair::Manual),
+        Some(Ok(catalyst)) if catalyst.get("settings").and_then(|settings| settings.get("project_name")).is_none() => {
+            finding("Catalyst.toml has no [settings] project_name, so the project was never configured".to_string(), Repair::WriteProjectName)
+        }
+        None => finding("Catalyst.toml needs the project name once it's restored".to_string(), Repair::WriteProjectName),
+        _ => {}
+    }
+
+    for dir in SKELETON_DIRS {
+        if !project_dir.join(dir).is_dir() {
+            finding(format!("{}/ is missing", dir), Repair::CreateDir(dir.to_string()));
+        }
+    }
+
+    let env_path = project_dir.join(".env");
+    match EnvFile::load(&env_path) {
+        _ if !env_path.exists() => finding(".env is missing".to_string(), Repair::CreateEnv),
+        Ok(env) if !env.contains("JWT_SECRET") && !env.contains("JWT_SECRETS") => finding(".env has no JWT_SECRET".to_string(), Repair::AddJwtSecret),
+        _ => {}
+    }
+
+    // Only when git is installed and the project wasn't created with --no-git: blast new skips the repository then
+    let inside_repository = logger::command_output("git", Command::new("git").args(["rev-parse", "--is-inside-work-tree"]).current_dir(project_dir)).map(|output| output.status.success());
+    if matches!(inside_repository, Ok(false)) && !crate::state::no_git(project_dir) {
+        finding("the project isn't a git repository".to_string(), Repair::InitGit);
+    }
+
+    let unfinished = crate::init::unfinished_phases(project_dir);
+    if !unfinished.is_empty() {
+        finding(format!("the last blast init didn't finish {}", unfinished.join(", ")), Repair::ResumeInit(unfinished));
+    }
+
+    findings
+}
+
+// Check `project_dir`, list what will be touched, and after confirmation repair it. Init phases are left to the caller.
+// Returns the findings, including the ones that still need a hand
+pub fn repair(project_dir: &Path, template: Option<ProjectTemplate>) -> Result<Vec<Finding>, String> {
+    let template = project_template(project_dir, template);
+    let findings = check(project_dir, template);
+    let repairs: Vec<&Finding> = findings.iter().filter(|finding| !matches!(finding.repair, Repair::ResumeInit(_))).collect();
+    if repairs.is_empty() {
+        return Ok(findings);
+    }
+
+    logger::warning(&format!("{} looks partially created. Repair plan ({} template):", project_dir.display(), template.name()))?;
+    for finding in &repairs {
+        logger::plain(&format!("  - {}: {}", finding.problem, finding.repair.describe()))?;
+    }
+    // Nothing here overwrites an existing file, so scripts go ahead without asking
+    if std::io::stdin().is_terminal() {
+        let proceed = Confirm::with_theme(&ColorfulTheme::default()).with_prompt("Repair the project now?").default(true).interact().unwrap_or(false);
+        if !proceed {
+            return Err("Repair cancelled; nothing was changed. `blast doctor` lists the problems again".to_string());
+        }
+    }
+
+    apply(project_dir, template, &repairs)?;
+    let manual: Vec<&str> = repairs.iter().filter(|finding| finding.repair == Repair::Manual).map(|finding| finding.problem.as_str()).collect();
+    if !manual.is_empty() {
+        return Err(format!("Repaired what blast could. Fix these by hand and run it again: {}", manual.join("; ")));
+    }
+    logger::success("Project repaired")?;
+    Ok(findings)
+}
+
+// Local repairs first, so they stick even when the template can't be fetched
+fn apply(project_dir: &Path, template: ProjectTemplate, repairs: &[&Finding]) -> Result<(), String> {
+    for finding in repairs {
+        match &finding.repair {
+            Repair::CreateDir(dir) => fs::create_dir_all(project_dir.join(dir)).map_err(|e| format!("Failed to create {}: {}", dir, e))?,
+            Repair::CreateEnv | Repair::AddJwtSecret => {
+                let env_path = project::create_env(project_dir).map_err(|e| format!("Failed to create .env: {}", e))?;
+                let mut env = EnvFile::load(&env_path)?;
+                if !env.contains("JWT_SECRET") && !env.contains("JWT_SECRETS") {
+                    env.set("JWT_SECRET", &project::generate_jwt_secret());
+                    env.save()?;
+                }
+            }
+            Repair::InitGit => {
+                let output = logger::command_output("git", Command::new("git").arg("init").current_dir(project_dir)).map_err(|e| format!("Failed to run git init: {}", e))?;
+                if !output.status.success() {
+                    return Err(format!("git init failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+                }
+            }
+            Repair::RestoreFromTemplate(_) | Repair::WriteProjectName | Repair::ResumeInit(_) | Repair::Manual => {}
+        }
+    }
+
+    let write_name = repairs.iter().any(|finding| finding.repair == Repair::WriteProjectName);
+    if write_name && project_dir.join("Catalyst.toml").exists() {
+        write_project_name(project_dir)?;
+    }
+
+    let restore: Vec<&str> = repairs
+        .iter()
+        .filter_map(|finding| match &finding.repair {
+            Repair::RestoreFromTemplate(path) => Some(path.as_str()),
+            _ => None,
+        })
+        .collect();
+    if !restore.is_empty() {
+        restore_from_template(project_dir, template, &restore)?;
+        if write_name && restore.contains(&"Catalyst.toml") {
+            write_project_name(project_dir)?;
+        }
+    }
+    Ok(())
+}
+
+// blast new names the project after its directory
+fn write_project_name(project_dir: &Path) -> Result<(), String> {
+    let canonical = project_dir.canonicalize().unwrap_or_else(|_| project_dir.to_path_buf());
+    let name = canonical.file_name().map(|name| name.to_string_lossy().into_owned()).ok_or("Can't tell the project name from the directory")?;
+    project::set_project_name(project_dir, &name, None).map_err(|e| format!("Failed to write the project name: {}", e))
+}
+
+// Clone the template once and copy the missing paths over, never replacing anything that exists
+fn restore_from_template(project_dir: &Path, template: ProjectTemplate, paths: &[&str]) -> Result<(), String> {
+    let clone_dir = std::env::temp_dir().join(format!("blast_repair_{}", std::process::id()));
+    if clone_dir.exists() {
+        fs::remove_dir_all(&clone_dir).map_err(|e| format!("Failed to clean up {}: {}", clone_dir.display(), e))?;
+    }
+    logger::info("Fetching the Catalyst template...")?;
+    let result = project::create_and_dump_template(&clone_dir, false).map_err(|e| format!("Failed to fetch the template: {}", e)).and_then(|_| {
+        // Template paths name public/; each side keeps it under its own public_dir
+        let (template_public, project_public) = (crate::configs::public_dir_at(&clone_dir), crate::configs::public_dir_at(project_dir));
+        for path in paths {
+            let source = clone_dir.join(crate::configs::in_public_dir(path, &template_public));
+            let target = project_dir.join(crate::configs::in_public_dir(path, &project_public));
+            if target.exists() {
+                continue;
+            }
+            if !source.exists() {
+                return Err(format!("The template has no {} to restore", path));
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            let copied = if source.is_dir() { crate::sparks::copy_dir_all(&source, &target) } else { fs::copy(&source, &target).map(|_| ()) };
+            copied.map_err(|e| format!("Failed to restore {}: {}", path, e))?;
+        }
+        Ok(())
+    });
+    let _ = fs::remove_dir_all(&clone_dir);
+    result?;
+
+    // A restored Catalyst.toml is the template's own, so trim it the way blast new would have
+    if paths.contains(&"Catalyst.toml") {
+        project::write_template_settings(project_dir, template).map_err(|e| format!("Failed to update Catalyst.toml: {}", e))?;
+    }
+    Ok(())
+}
+
+// `blast new --repair <dir>`
+pub fn repair_directory(dir: &str, template: Option<String>) -> Result<(), String> {
+    let project_dir = Path::new(dir);
+    if !project_dir.is_dir() {
+        return Err(format!("{} doesn't exist; create it with `blast new {}`", dir, dir));
+    }
+    let template = match template {
+        Some(name) => Some(ProjectTemplate::from_name(&name).ok_or_else(|| format!("Unknown template '{}'", name))?),
+        None => None,
+    };
+
+    let findings = repair(project_dir, template)?;
+    if findings.is_empty() {
+        logger::success(&format!("{} looks complete; nothing to repair", dir))?;
+    }
+    project::print_next_steps(dir, None)
+}
+
+// `blast doctor`: the integrity part
+pub fn report(project_dir: &Path) -> Result<usize, String> {
+    let template = project_template(project_dir, None);
+    let findings = check(project_dir, template);
+    if findings.is_empty() {
+        logger::success(&format!("Project files are complete ({} template)", template.name()))?;
+        return Ok(0);
+    }
+    for finding in &findings {
+        logger::warning(&format!("{}: `blast init` will {}", finding.problem, finding.repair.describe()))?;
+    }
+    Ok(findings.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupted_projects_get_a_repair_plan() {
+        let dir = std::env::temp_dir().join(format!("blast_integrity_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/database/migrations")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"catalyst\"\n").unwrap();
+        fs::write(dir.join("Catalyst.toml"), "[settings]\ntemplate = \"api\"\n").unwrap();
+        fs::write(dir.join(".env"), "DATABASE_URL=postgres://localhost/app\n").unwrap();
+
+        let template = project_template(&dir, None);
+        assert_eq!(template, ProjectTemplate::Api);
+        let repairs: Vec<Repair> = check(&dir, template).into_iter().map(|finding| finding.repair).filter(|repair| *repair != Repair::InitGit).collect();
+        assert_eq!(
+            repairs,
+            vec![
+                Repair::RestoreFromTemplate("src/main.rs".to_string()),
+                Repair::WriteProjectName,
+                Repair::CreateDir("storage/logs".to_string()),
+                Repair::CreateDir("storage/blast".to_string()),
+                Repair::AddJwtSecret,
+            ]
+        );
+
+        fs::write(dir.join("Cargo.toml"), "[package\n").unwrap();
+        assert!(check(&dir, template).iter().any(|finding| finding.repair == Repair::Manual && finding.problem.starts_with("Cargo.toml doesn't parse")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
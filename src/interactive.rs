@@ -2,57 +2,159 @@ use crate::commands::Command;
 use crate::configs::Config;
 use crate::dependencies::DependencyManager;
 use crate::logger;
+use crate::refresh::RefreshOptions;
 use console::Style;
-use dialoguer::{theme::ColorfulTheme, FuzzySelect};
-use std::env;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
+use std::fs;
 use std::io::Write;
+use std::path::PathBuf;
+
+// How many executed commands .blast/history keeps for the "recent" section
+const HISTORY_LIMIT: usize = 20;
+
+// One leaf command in the palette. Typing its shortcut (e.g. `:migrate`) in the fuzzy search selects it;
+// `command` asks for any arguments it needs and returns None when the user backs out. The built-in
+// dashboard lists the same entries
+pub struct PaletteEntry {
+    pub shortcut: &'static str,
+    pub group: &'static str,
+    pub description: &'static str,
+    pub command: fn(&Config) -> Result<Option<Command>, String>,
+}
+
+impl PaletteEntry {
+    fn label(&self) -> String {
+        format!("{:<18} {:<11} {}", self.shortcut, self.group, self.description)
+    }
+}
+
+pub const PALETTE: &[PaletteEntry] = &[
+    // APP commands first (most important)
+    PaletteEntry {
+        shortcut: ":run",
+        group: "[APP]",
+        description: "Run the server for the current environment",
+        command: |config| Ok(Some(if config.is_production() { Command::RunProdServer(None) } else { Command::RunDevServer(None) })),
+    },
+    PaletteEntry { shortcut: ":stop", group: "[APP]", description: "Stop the running server", command: |_| Ok(Some(Command::StopServer)) },
+    PaletteEntry { shortcut: ":watch", group: "[APP]", description: "Rebuild assets on changes and refresh the browser", command: |_| Ok(Some(Command::Watch)) },
+    PaletteEntry { shortcut: ":status", group: "[APP]", description: "Show whether the server is running and its URL", command: |_| Ok(Some(Command::ServerStatus(None, 5))) },
+    PaletteEntry { shortcut: ":refresh", group: "[APP]", description: "Rollback, migrate, seed and regenerate changed tables", command: |_| Ok(Some(Command::RefreshApp(RefreshOptions::default()))) },
+    PaletteEntry { shortcut: ":refresh --all", group: "[APP]", description: "Refresh and regenerate every table", command: |_| Ok(Some(Command::RefreshApp(RefreshOptions { all: true, ..RefreshOptions::default() }))) },
+    PaletteEntry { shortcut: ":refresh --no-drop", group: "[APP]", description: "Apply pending migrations and regenerate, keeping data", command: |_| Ok(Some(Command::RefreshApp(RefreshOptions { no_drop: true, ..RefreshOptions::default() }))) },
+    PaletteEntry { shortcut: ":build", group: "[APP]", description: "Release build and production assets bundled into dist/", command: |_| Ok(Some(Command::Build(false, false))) },
+    PaletteEntry { shortcut: ":toggle-env", group: "[APP]", description: "Cycle through the environments", command: |_| Ok(Some(Command::ToggleEnvironment)) },
+    PaletteEntry { shortcut: ":env use", group: "[APP]", description: "Switch to a named environment", command: prompt_env_use },
+    PaletteEntry { shortcut: ":env check", group: "[APP]", description: "Check required env keys and placeholders", command: |_| Ok(Some(Command::EnvCheck)) },
+    PaletteEntry { shortcut: ":config validate", group: "[APP]", description: "Print the effective Catalyst.toml settings", command: |_| Ok(Some(Command::ConfigValidate)) },
+    PaletteEntry { shortcut: ":doctor", group: "[APP]", description: "Check for missing files and an unfinished init", command: |_| Ok(Some(Command::Doctor)) },
+    PaletteEntry { shortcut: ":clean", group: "[APP]", description: "List the generated and downloaded files blast clean --all would delete", command: |_| Ok(Some(Command::Clean(crate::clean::CleanOptions { codegen: true, assets: true, downloads: true, logs: true, yes: false }))) },
+    PaletteEntry { shortcut: ":report", group: "[APP]", description: "Write a bug report to storage/logs", command: |_| Ok(Some(Command::Report(crate::report::DEFAULT_RUNS, None))) },
+    PaletteEntry { shortcut: ":config migrate", group: "[APP]", description: "Upgrade Catalyst.toml to the current layout", command: |_| Ok(Some(Command::ConfigMigrate)) },
+    PaletteEntry { shortcut: ":config sync-diesel", group: "[APP]", description: "Rewrite diesel.toml from Catalyst.toml", command: |_| Ok(Some(Command::ConfigSyncDiesel)) },
+    PaletteEntry { shortcut: ":config rocket set", group: "[APP]", description: "Set a Rocket.toml key for an environment's profile", command: prompt_rocket_set },
+    PaletteEntry { shortcut: ":test", group: "[APP]", description: "Recreate the test database and run cargo test", command: |_| Ok(Some(Command::Test(false, false, None, Vec::new()))) },
+    PaletteEntry { shortcut: ":routes", group: "[APP]", description: "List Rocket routes with their handlers", command: |_| Ok(Some(Command::Routes(None, None))) },
+    // Code generation group
+    PaletteEntry { shortcut: ":schema", group: "[CODEGEN]", description: "Generate the database schema", command: |_| Ok(Some(Command::GenerateSchema)) },
+    PaletteEntry { shortcut: ":schema diff", group: "[CODEGEN]", description: "Compare the database with schema.rs", command: |_| Ok(Some(Command::SchemaDiff(false))) },
+    PaletteEntry { shortcut: ":gen structs", group: "[CODEGEN]", description: "Generate structs for tables whose schema changed", command: |_| Ok(Some(Command::GenerateStructs(false, Default::default()))) },
+    PaletteEntry { shortcut: ":gen models", group: "[CODEGEN]", description: "Generate models for tables whose schema changed", command: |_| Ok(Some(Command::GenerateModels(false, Default::default()))) },
+    PaletteEntry { shortcut: ":gen auth", group: "[CODEGEN]", description: "Generate register/login/refresh endpoints", command: |_| Ok(Some(Command::GenerateAuth(false))) },
+    PaletteEntry { shortcut: ":gen guards", group: "[CODEGEN]", description: "Generate role guards and an example admin controller", command: |_| Ok(Some(Command::GenerateGuards(false))) },
+    PaletteEntry { shortcut: ":gen health", group: "[CODEGEN]", description: "Generate a GET /healthz controller", command: |_| Ok(Some(Command::GenerateHealth(false))) },
+    PaletteEntry { shortcut: ":gen observability", group: "[CODEGEN]", description: "Generate an access log, metrics and GET /metrics", command: |_| Ok(Some(Command::GenerateObservability(false))) },
+    PaletteEntry { shortcut: ":gen livereload", group: "[CODEGEN]", description: "Generate no-store caching and browser live reload for dev", command: |_| Ok(Some(Command::GenerateLiveReload(false))) },
+    PaletteEntry { shortcut: ":gen openapi", group: "[CODEGEN]", description: "Write openapi.yaml for the API routes", command: |_| Ok(Some(Command::GenerateOpenApi)) },
+    PaletteEntry { shortcut: ":gen docker", group: "[CODEGEN]", description: "Generate Dockerfile and docker-compose.yml", command: |_| Ok(Some(Command::GenerateDocker(false, false))) },
+    PaletteEntry { shortcut: ":gen storage-bucket", group: "[CODEGEN]", description: "Generate a storage bucket accessor", command: |_| prompt_storage_bucket() },
+    // DB commands
+    PaletteEntry { shortcut: ":migration", group: "[DB]", description: "Create a new migration", command: |_| Ok(Some(Command::NewMigration("default".to_string()))) },
+    PaletteEntry { shortcut: ":migrate", group: "[DB]", description: "Run all pending migrations", command: |_| Ok(Some(Command::Migrate("default".to_string()))) },
+    PaletteEntry { shortcut: ":migration list", group: "[DB]", description: "List migrations with their applied/pending state", command: |_| Ok(Some(Command::MigrationStatus("all".to_string(), None, false))) },
+    PaletteEntry { shortcut: ":migration verify", group: "[DB]", description: "Check pending migrations run up, down and up again cleanly", command: |_| Ok(Some(Command::MigrationVerify("default".to_string(), false))) },
+    PaletteEntry { shortcut: ":rollback", group: "[DB]", description: "Rollback all migrations", command: |_| Ok(Some(Command::Rollback("default".to_string()))) },
+    PaletteEntry { shortcut: ":seed", group: "[DB]", description: "Run all seed files or pick one", command: prompt_seed },
+    PaletteEntry { shortcut: ":seed gen", group: "[DB]", description: "Write a seed file of fake rows for a table", command: prompt_seed_gen },
+    PaletteEntry { shortcut: ":seed list", group: "[DB]", description: "List SQL and Rust seeds with their last run", command: |_| Ok(Some(Command::SeedList)) },
+    PaletteEntry { shortcut: ":db create", group: "[DB]", description: "Create the database named in DATABASE_URL", command: |config| prompt_connection(config).map(|connection| connection.map(Command::DbCreate)) },
+    PaletteEntry {
+        shortcut: ":db drop",
+        group: "[DB]",
+        description: "Drop the database (asks for confirmation)",
+        command: |config| prompt_connection(config).map(|connection| connection.map(|connection| Command::DbDrop(connection, false, false))),
+    },
+    PaletteEntry {
+        shortcut: ":db reset",
+        group: "[DB]",
+        description: "Drop, create, migrate, seed and regenerate",
+        command: |config| prompt_connection(config).map(|connection| connection.map(|connection| Command::DbReset(connection, false, false))),
+    },
+    PaletteEntry {
+        shortcut: ":db dump",
+        group: "[DB]",
+        description: "Snapshot the database into storage/backups",
+        command: |config| prompt_connection(config).map(|connection| connection.map(|connection| Command::DbDump(connection, false, Vec::new()))),
+    },
+    PaletteEntry {
+        shortcut: ":db restore",
+        group: "[DB]",
+        description: "Restore a snapshot from storage/backups (asks for confirmation)",
+        command: |config| prompt_connection(config).map(|connection| connection.map(|connection| Command::DbRestore(connection, None, false, false))),
+    },
+    PaletteEntry {
+        shortcut: ":db console",
+        group: "[DB]",
+        description: "Open psql on the database",
+        command: |config| prompt_connection(config).map(|connection| connection.map(|connection| Command::DbConsole(connection, None))),
+    },
+    PaletteEntry { shortcut: ":db baseline", group: "[DB]", description: "Baseline migration from an existing database", command: |_| Ok(Some(Command::DbBaseline)) },
+    // Assets management
+    PaletteEntry { shortcut: ":scss", group: "[Assets]", description: "Transpile SCSS files", command: |_| Ok(Some(Command::TranspileScss)) },
+    PaletteEntry { shortcut: ":css", group: "[Assets]", description: "Minify CSS files", command: |_| Ok(Some(Command::MinifyCss)) },
+    PaletteEntry { shortcut: ":publish-css", group: "[Assets]", description: "Copy CSS files to <public_dir>/css", command: |_| Ok(Some(Command::PublishCss)) },
+    PaletteEntry { shortcut: ":js", group: "[Assets]", description: "Publish JS files", command: |_| Ok(Some(Command::ProcessJs)) },
+    PaletteEntry { shortcut: ":assets optimize", group: "[Assets]", description: "Optimize images and pre-compress text assets in <public_dir>", command: |_| Ok(Some(Command::OptimizeAssets)) },
+    PaletteEntry { shortcut: ":check templates", group: "[Assets]", description: "Check templates for parse errors and broken references", command: |_| Ok(Some(Command::CheckTemplates(false))) },
+    PaletteEntry { shortcut: ":check locales", group: "[Assets]", description: "Report missing translations and unknown t() keys", command: |_| Ok(Some(Command::CheckLocales)) },
+    PaletteEntry { shortcut: ":check openapi", group: "[Assets]", description: "Check openapi.yaml is up to date", command: |_| Ok(Some(Command::CheckOpenApi)) },
+    PaletteEntry { shortcut: ":cdn", group: "[Assets]", description: "Download CDN assets", command: |_| Ok(Some(Command::DownloadCdn)) },
+    // Spark plugins
+    PaletteEntry { shortcut: ":spark add", group: "[Sparks]", description: "Add a spark plugin from a git repository", command: prompt_spark_url },
+    PaletteEntry { shortcut: ":spark verify", group: "[Sparks]", description: "Check sparks against Catalyst.toml and .env", command: |_| Ok(Some(Command::SparkVerify(false))) },
+    PaletteEntry { shortcut: ":spark list", group: "[Sparks]", description: "List sparks with their version and source", command: |_| Ok(Some(Command::SparkList)) },
+    // Cronjob management
+    PaletteEntry { shortcut: ":cronjobs", group: "[Cronjobs]", description: "Interactive cronjob manager", command: |_| Ok(Some(Command::CronjobsInteractive)) },
+    PaletteEntry { shortcut: ":cronjobs list", group: "[Cronjobs]", description: "List jobs and their status", command: |_| Ok(Some(Command::CronjobsList)) },
+    PaletteEntry { shortcut: ":cronjobs add", group: "[Cronjobs]", description: "Add a job with a name and interval", command: prompt_cronjob_add },
+    PaletteEntry {
+        shortcut: ":cronjobs toggle",
+        group: "[Cronjobs]",
+        description: "Pause or resume a job",
+        command: |config| prompt_cronjob(config, "Job to toggle").map(|id| id.map(Command::CronjobsToggle)),
+    },
+    PaletteEntry {
+        shortcut: ":cronjobs remove",
+        group: "[Cronjobs]",
+        description: "Remove a job",
+        command: |config| prompt_cronjob(config, "Job to remove").map(|id| id.map(Command::CronjobsRemove)),
+    },
+    PaletteEntry { shortcut: ":cronjobs import", group: "[Cronjobs]", description: "Move file-based jobs into the cronjobs table", command: |_| Ok(Some(Command::CronjobsImport(None))) },
+    // Log management
+    PaletteEntry { shortcut: ":log truncate", group: "[LOG]", description: "Truncate all log files", command: |_| Ok(Some(Command::LogTruncate(None))) },
+    // Exit is always last
+    PaletteEntry { shortcut: ":exit", group: "[Exit]", description: "Kill the dashboard session", command: |_| Ok(Some(Command::Exit)) },
+];
 
 // Main interactive CLI function
 pub fn run_interactive_cli(mut config: Config, dep_manager: &mut DependencyManager) -> Result<(), String> {
     // Set up logging for interactive mode
     logger::setup_for_mode(&config, true)?;
 
-    // Set the environment variable to indicate we're in interactive mode
-    env::set_var("BLAST_INTERACTIVE", "1");
-
     // Clear screen
     print!("\x1B[2J\x1B[1;1H");
     std::io::stdout().flush().map_err(|e| e.to_string())?;
 
-    // Define the menu items - place server commands at the top as requested
-    let commands = vec![
-        // APP commands first (most important) - Run and Stop Server moved to the top
-        "[APP] Run Server",
-        "[APP] Stop Server",
-        "[APP] Refresh",
-        "[APP] Toggle Dev/Prod",
-        // Code generation group
-        "[CODEGEN] Schema",
-        "[CODEGEN] Structs",
-        "[CODEGEN] Models",
-        // DB commands
-        "[DB] New Migration",
-        "[DB] Migrate",
-        "[DB] Rollback",
-        "[DB] Seed",
-        // Assets management
-        "[Assets] Transpile SCSS",
-        "[Assets] Minify CSS",
-        "[Assets] Publish CSS",
-        "[Assets] Publish JS",
-        "[Assets] Download CDN",
-        // Cronjob management
-        "[Cronjobs] Interactive Manager",
-        "[Cronjobs] List Jobs",
-        "[Cronjobs] Add Job",
-        "[Cronjobs] Toggle Job",
-        "[Cronjobs] Remove Job",
-        // Log management
-        "[LOG] Truncate Logs",
-        // Exit is always last
-        "[Exit] Kill Session",
-    ];
-
     // Initialize console styles
     let prod_style = Style::new().bold().fg(console::Color::Green);
     let dev_style = Style::new().bold().fg(console::Color::Yellow);
@@ -66,122 +168,59 @@ pub fn run_interactive_cli(mut config: Config, dep_manager: &mut DependencyManag
         std::io::stdout().flush().map_err(|e| e.to_string())?;
 
         // Create prompt based on environment
-        let prompt = if config.environment == "prod" {
+        let prompt = if config.is_production() {
             format!("{}->[{}] ", prod_style.apply_to(format!("[🚀{}]", config.environment.to_uppercase())), config.project_name)
         } else {
             format!("{}->[{}] ", dev_style.apply_to(format!("[🔧{}]", config.environment.to_uppercase())), config.project_name)
         };
 
+        // Recently executed commands first, then the whole palette
+        let recent: Vec<usize> = load_history(&config).iter().filter_map(|shortcut| PALETTE.iter().position(|entry| entry.shortcut == shortcut)).collect();
+        let mut entries = recent.clone();
+        entries.extend(0..PALETTE.len());
+        let labels: Vec<String> = entries
+            .iter()
+            .enumerate()
+            .map(|(row, &index)| if row < recent.len() { format!("↺ {}", PALETTE[index].label()) } else { format!("  {}", PALETTE[index].label()) })
+            .collect();
+
         // Show the menu
         let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
             .with_prompt(prompt)
-            .items(&commands)
+            .items(&labels)
             .default(0)
             .interact()
             .map_err(|e| e.to_string())?;
+        let entry = &PALETTE[entries[selection]];
 
-        // Convert selection to command
-        let cmd = match commands[selection] {
-            "[APP] Refresh" => Command::RefreshApp,
-            "[APP] Run Server" => {
-                if config.environment == "prod" || config.environment == "production" {
-                    Command::RunProdServer
-                } else {
-                    Command::RunDevServer
-                }
-            }
-            "[APP] Stop Server" => Command::StopServer,
-            "[APP] Toggle Dev/Prod" => Command::ToggleEnvironment,
-
-            "[CODEGEN] Schema" => Command::GenerateSchema,
-            "[CODEGEN] Structs" => Command::GenerateStructs,
-            "[CODEGEN] Models" => Command::GenerateModels,
-
-            "[DB] New Migration" => Command::NewMigration,
-            "[DB] Migrate" => Command::Migrate,
-            "[DB] Rollback" => Command::Rollback,
-            "[DB] Seed" => Command::Seed(None),
-
-            "[Assets] Transpile SCSS" => Command::TranspileScss,
-            "[Assets] Minify CSS" => Command::MinifyCss,
-            "[Assets] Publish CSS" => Command::PublishCss,
-            "[Assets] Publish JS" => Command::ProcessJs,
-            "[Assets] Download CDN" => Command::DownloadCdn,
-            
-            "[Cronjobs] Interactive Manager" => Command::CronjobsInteractive,
-            "[Cronjobs] List Jobs" => Command::CronjobsList,
-            "[Cronjobs] Add Job" => {
-                print!("\x1B[2J\x1B[1;1H"); // Clear screen
-                
-                // Get job name
-                println!("Enter job name:");
-                let mut name = String::new();
-                std::io::stdin().read_line(&mut name).unwrap_or_default();
-                let name = name.trim().to_string();
-                
-                // Get interval
-                println!("Enter interval in seconds:");
-                let mut interval_str = String::new();
-                std::io::stdin().read_line(&mut interval_str).unwrap_or_default();
-                let interval = interval_str.trim().parse::<i32>().unwrap_or(60);
-                
-                Command::CronjobsAdd(name, interval)
-            },
-            "[Cronjobs] Toggle Job" => {
-                print!("\x1B[2J\x1B[1;1H"); // Clear screen
-                
-                // List jobs first
-                if let Err(e) = crate::cronjobs::list_cronjobs(&config) {
-                    logger::warning(&format!("Failed to list jobs: {}", e))?;
-                }
-                
-                // Get job ID
-                println!("\nEnter job ID to toggle:");
-                let mut id_str = String::new();
-                std::io::stdin().read_line(&mut id_str).unwrap_or_default();
-                let id = id_str.trim().parse::<i32>().unwrap_or(0);
-                
-                Command::CronjobsToggle(id)
-            },
-            "[Cronjobs] Remove Job" => {
-                print!("\x1B[2J\x1B[1;1H"); // Clear screen
-                
-                // List jobs first
-                if let Err(e) = crate::cronjobs::list_cronjobs(&config) {
-                    logger::warning(&format!("Failed to list jobs: {}", e))?;
-                }
-                
-                // Get job ID
-                println!("\nEnter job ID to remove:");
-                let mut id_str = String::new();
-                std::io::stdin().read_line(&mut id_str).unwrap_or_default();
-                let id = id_str.trim().parse::<i32>().unwrap_or(0);
-                
-                Command::CronjobsRemove(id)
-            },
-
-            "[LOG] Truncate Logs" => Command::LogTruncate(None),
-
-            "[Exit] Kill Session" => {
-                // Log the exit
-                logger::info("Killing Zellij session...")?;
-
-                // Try to use zellij to exit the session
-                let _ = std::process::Command::new("zellij").args(["kill-session"]).spawn();
-
-                // If that doesn't work, kill all sessions
-                let _ = std::process::Command::new("zellij").args(["kill-all-sessions", "-y"]).spawn();
-
-                Command::Exit
+        // Ask for arguments inline; backing out of a prompt returns to the palette
+        let cmd = match (entry.command)(&config) {
+            Ok(Some(cmd)) => cmd,
+            Ok(None) => continue,
+            Err(e) => {
+                logger::error(&e)?;
+                pause()?;
+                continue;
             }
-            _ => continue,
         };
 
         // Exit early if Exit command
         if cmd == Command::Exit {
+            // Log the exit
+            logger::info("Killing Zellij session...")?;
+
+            // Try to use zellij to exit the session
+            let _ = std::process::Command::new("zellij").args(["kill-session"]).spawn();
+
+            // If that doesn't work, kill all sessions
+            let _ = std::process::Command::new("zellij").args(["kill-all-sessions", "-y"]).spawn();
             break;
         }
 
+        if let Err(e) = record_history(&config, entry.shortcut) {
+            logger::warning(&format!("Failed to update command history: {}", e))?;
+        }
+
         // Clear screen before executing command
         print!("\x1B[2J\x1B[1;1H");
         std::io::stdout().flush().map_err(|e| e.to_string())?;
@@ -197,12 +236,175 @@ pub fn run_interactive_cli(mut config: Config, dep_manager: &mut DependencyManag
                 logger::error(&format!("Command failed: {}", e))?;
 
                 // Make sure the user sees the error
-                println!("\nPress Enter to continue...");
-                let mut buffer = String::new();
-                std::io::stdin().read_line(&mut buffer).map_err(|e| e.to_string())?;
+                pause()?;
             }
         }
     }
 
     Ok(())
 }
+
+fn pause() -> Result<(), String> {
+    println!("\nPress Enter to continue...");
+    let mut buffer = String::new();
+    std::io::stdin().read_line(&mut buffer).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn history_path(config: &Config) -> PathBuf {
+    config.project_dir.join(".blast").join("history")
+}
+
+// Shortcuts of recently executed commands, most recent first
+fn load_history(config: &Config) -> Vec<String> {
+    fs::read_to_string(history_path(config)).map(|content| content.lines().map(str::to_string).filter(|line| !line.is_empty()).collect()).unwrap_or_default()
+}
+
+pub fn record_history(config: &Config, shortcut: &str) -> Result<(), String> {
+    let mut history = load_history(config);
+    history.retain(|entry| entry != shortcut);
+    history.insert(0, shortcut.to_string());
+    history.truncate(HISTORY_LIMIT);
+
+    let path = history_path(config);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, format!("{}\n", history.join("\n"))).map_err(|e| e.to_string())
+}
+
+// Fuzzy pick from `items`; Esc backs out
+fn pick(prompt: &str, items: &[String]) -> Result<Option<usize>, String> {
+    FuzzySelect::with_theme(&ColorfulTheme::default()).with_prompt(prompt).items(items).default(0).interact_opt().map_err(|e| e.to_string())
+}
+
+fn prompt_env_use(config: &Config) -> Result<Option<Command>, String> {
+    let names = config.environment_names();
+    Ok(pick("Environment", &names)?.map(|index| Command::EnvUse(names[index].clone())))
+}
+
+fn prompt_seed(config: &Config) -> Result<Option<Command>, String> {
+    let seed_dir = config.project_dir.join("src/database/seeds");
+    if !seed_dir.is_dir() {
+        return Err("Failed to read src/database/seeds: no such directory".to_string());
+    }
+    let groups = crate::database::seeds_by_connection(&seed_dir);
+
+    // With seeds for several connections, each gets an "all" entry and its files are listed under it
+    let grouped = groups.len() > 1;
+    let mut items = vec!["All seed files".to_string()];
+    let mut commands = vec![Command::Seed(None, None)];
+    for (connection, files) in &groups {
+        if grouped {
+            items.push(format!("All seeds of the {} connection", connection));
+            commands.push(Command::Seed(None, Some(connection.clone())));
+        }
+        for file in files {
+            items.push(if grouped { format!("  {}", file) } else { file.clone() });
+            commands.push(Command::Seed(Some(file.clone()), None));
+        }
+    }
+    Ok(pick("Seed file", &items)?.map(|index| commands.swap_remove(index)))
+}
+
+fn prompt_seed_gen(config: &Config) -> Result<Option<Command>, String> {
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
+    let schema = crate::file_writer::read(config.project_dir.join(schema_path)).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+    let tables: Vec<String> = crate::models::parse_schema_tables(&schema).into_iter().map(|table| table.name).collect();
+    let Some(index) = pick("Table", &tables)? else {
+        return Ok(None);
+    };
+    let count: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Rows")
+        .default(50)
+        .validate_with(|input: &usize| -> Result<(), String> { if *input > 0 { Ok(()) } else { Err("Generate at least one row".to_string()) } })
+        .interact_text()
+        .map_err(|e| e.to_string())?;
+    Ok(Some(Command::SeedGen(tables[index].clone(), count, None)))
+}
+
+fn prompt_rocket_set(config: &Config) -> Result<Option<Command>, String> {
+    let mut profiles = vec!["default (every environment)".to_string()];
+    profiles.extend(config.environment_names());
+    let Some(index) = pick("Profile", &profiles)? else {
+        return Ok(None);
+    };
+    let key: String = Input::with_theme(&ColorfulTheme::default()).with_prompt("Key").interact_text().map_err(|e| e.to_string())?;
+    let value: String = Input::with_theme(&ColorfulTheme::default()).with_prompt("Value").interact_text().map_err(|e| e.to_string())?;
+    Ok(Some(Command::ConfigRocketSet(key.trim().to_string(), value.trim().to_string(), (index > 0).then(|| profiles[index].clone()))))
+}
+
+fn prompt_storage_bucket() -> Result<Option<Command>, String> {
+    let name: String = Input::with_theme(&ColorfulTheme::default()).with_prompt("Bucket name").interact_text().map_err(|e| e.to_string())?;
+    Ok(Some(Command::GenerateStorageBucket(name.trim().to_string(), false)))
+}
+
+// Which DATABASE_URL to act on; only asks when the project has more than one
+fn prompt_connection(config: &Config) -> Result<Option<String>, String> {
+    let connections = crate::database::get_connection_names(config);
+    if connections.len() == 1 {
+        return Ok(connections.into_iter().next());
+    }
+    Ok(pick("Database connection", &connections)?.map(|index| connections[index].clone()))
+}
+
+fn prompt_spark_url(_config: &Config) -> Result<Option<Command>, String> {
+    let url: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Spark repository URL")
+        .validate_with(|input: &String| -> Result<(), String> {
+            let input = input.trim();
+            if input.starts_with("git@") || url::Url::parse(input).map(|url| !url.host_str().unwrap_or_default().is_empty()).unwrap_or(false) {
+                Ok(())
+            } else {
+                Err("Enter a git URL, e.g. https://github.com/catalyst-framework/plznohac".to_string())
+            }
+        })
+        .interact_text()
+        .map_err(|e| e.to_string())?;
+    Ok(Some(Command::AddSpark(url.trim().to_string(), false, false)))
+}
+
+fn prompt_cronjob_add(_config: &Config) -> Result<Option<Command>, String> {
+    let theme = ColorfulTheme::default();
+    let name: String = Input::with_theme(&theme)
+        .with_prompt("Job name")
+        .validate_with(|input: &String| -> Result<(), String> {
+            if !input.trim().is_empty() && input.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                Ok(())
+            } else {
+                Err("Use letters, digits, _ and -".to_string())
+            }
+        })
+        .interact_text()
+        .map_err(|e| e.to_string())?;
+    let interval: i32 = Input::with_theme(&theme)
+        .with_prompt("Interval in seconds")
+        .default(60)
+        .validate_with(|input: &i32| -> Result<(), String> {
+            if *input > 0 {
+                Ok(())
+            } else {
+                Err("The interval must be a positive number of seconds".to_string())
+            }
+        })
+        .interact_text()
+        .map_err(|e| e.to_string())?;
+    Ok(Some(Command::CronjobsAdd(name.trim().to_string(), interval)))
+}
+
+// Pick a job by name; falls back to typing its ID when the jobs can't be listed
+fn prompt_cronjob(config: &Config, prompt: &str) -> Result<Option<i32>, String> {
+    match crate::cronjobs::job_names(config) {
+        Ok(jobs) if jobs.is_empty() => Err("No cronjobs yet. Add one with :cronjobs add".to_string()),
+        Ok(jobs) => {
+            let labels: Vec<String> = jobs.iter().map(|(id, name)| format!("{:>4}  {}", id, name)).collect();
+            Ok(pick(prompt, &labels)?.map(|index| jobs[index].0))
+        }
+        Err(e) => {
+            logger::warning(&format!("Failed to list jobs: {}", e))?;
+            let id: i32 = Input::with_theme(&ColorfulTheme::default()).with_prompt(format!("{} (ID)", prompt)).interact_text().map_err(|e| e.to_string())?;
+            Ok(Some(id))
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! blast as a library: the core operations of the `blast` CLI, for tools that create and maintain Catalyst
+//! projects without shelling out to it. [`api`] is the supported interface and follows semver; the hidden
+//! modules exist for the `blast` binary and may change in any release.
+
+pub mod api;
+#[doc(hidden)]
+pub mod assets;
+mod auth;
+mod build;
+mod clean;
+mod codegen_format;
+mod codegen_ignore;
+#[doc(hidden)]
+pub mod codegen_manifest;
+mod codegen_snapshot;
+#[doc(hidden)]
+pub mod commands;
+mod completions;
+mod config_migrate;
+#[doc(hidden)]
+pub mod configs;
+mod crate_versions;
+mod cronjobs;
+mod cronjobs_tui; // Interactive TUI for cronjob management
+mod dashboard;
+mod dashboard_tui;
+mod database;
+mod diesel_toml;
+mod db_console;
+#[doc(hidden)]
+pub mod db_safety;
+mod db_snapshot;
+#[doc(hidden)]
+pub mod dependencies;
+mod docker;
+mod doctor;
+mod envfile;
+#[doc(hidden)]
+pub mod file_writer;
+mod forms;
+mod guards;
+mod health;
+#[doc(hidden)]
+pub mod help;
+mod init;
+#[doc(hidden)]
+pub mod integrity;
+mod interactive;
+// Locale module removed
+mod livereload;
+mod locales;
+#[doc(hidden)]
+pub mod logger;
+mod migration_columns;
+mod migration_spec;
+mod migration_status;
+mod migration_verify;
+mod mod_file;
+mod models;
+mod observability;
+mod openapi;
+mod operation;
+mod optimize;
+#[doc(hidden)]
+pub mod profile;
+#[doc(hidden)]
+pub mod platform;
+mod progress; // Keep temporarily until we migrate references
+mod prompt;
+mod project;
+mod refresh;
+mod report;
+mod rocket_config;
+mod routes;
+mod rust_seeds;
+mod schema_filter;
+mod schema_patch;
+mod seed_gen;
+#[doc(hidden)]
+pub mod self_update;
+mod spark_scaffold;
+mod sparks;
+mod state;
+mod storage;
+mod structs;
+mod table_selection;
+mod templates;
+mod test_runner;
+mod tool_log;
+mod verify;
@@ -0,0 +1,288 @@
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use toml_edit::DocumentMut;
+use walkdir::WalkDir;
+
+use crate::configs::Config;
+use crate::logger;
+use crate::templates::{fingerprint, TEMPLATES_DIR, WATCH_INTERVAL};
+
+type BlastResult = Result<(), String>;
+
+// Server-sent events for the browser on GET, the rebuild ping from `blast watch` on POST
+const ENDPOINT: &str = "/__livereload";
+const GENERATED_HEADER: &str = "// Generated by `blast gen livereload`";
+const ASSET_SOURCES: &str = "src/assets";
+// The call the project's Rocket setup makes to attach the module
+const STAGE_CALL: &str = "livereload::stage()";
+
+const KNOB_COMMENT: &str = "# Live reload (blast gen livereload): debug builds add a script to HTML pages that refreshes them when\n# `blast watch` has rebuilt the assets or a template changed. Release builds compile it out; false turns it off\n";
+
+const MODULE_SOURCE: &str = r##"// Generated by `blast gen livereload`. Development conveniences, all behind #[cfg(debug_assertions)]:
+// - every response is sent with Cache-Control: no-store, so edited files in public/ are never served stale
+// - with `live_reload = true` in Rocket.toml's [debug] profile, HTML pages get a small script listening on
+//   GET /__livereload (server-sent events), and `blast watch` POSTs there after each rebuild to refresh them
+// Template::fairing() already re-reads changed templates in debug builds. Release builds compile all of this out
+// and stage() attaches nothing. Regenerate with `blast gen livereload --force`.
+use rocket::fairing::AdHoc;
+
+#[cfg(debug_assertions)]
+pub fn stage() -> AdHoc {
+    dev::stage()
+}
+
+#[cfg(not(debug_assertions))]
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Live reload (release build, compiled out)", |rocket| async { rocket })
+}
+
+#[cfg(debug_assertions)]
+mod dev {
+    use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+    use rocket::http::{Header, Status};
+    use rocket::response::stream::{Event, EventStream};
+    use rocket::tokio::select;
+    use rocket::tokio::sync::broadcast::{channel, error::RecvError, Sender};
+    use rocket::{Request, Response, Shutdown, State};
+    use std::io::Cursor;
+    use std::net::SocketAddr;
+
+    const SCRIPT: &str = r#"<script>new EventSource("/__livereload").addEventListener("reload", () => location.reload());</script>"#;
+
+    struct Reloads(Sender<()>);
+
+    #[rocket::get("/__livereload")]
+    fn events(reloads: &State<Reloads>, mut shutdown: Shutdown) -> EventStream![] {
+        let mut receiver = reloads.0.subscribe();
+        EventStream! {
+            loop {
+                select! {
+                    message = receiver.recv() => match message {
+                        Ok(()) | Err(RecvError::Lagged(_)) => yield Event::data("reload").event("reload"),
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = &mut shutdown => break,
+                }
+            }
+        }
+    }
+
+    // `blast watch` pings this after a rebuild; only from this machine
+    #[rocket::post("/__livereload")]
+    fn reload(reloads: &State<Reloads>, remote: SocketAddr) -> Status {
+        if !remote.ip().is_loopback() {
+            return Status::Forbidden;
+        }
+        let _ = reloads.0.send(());
+        Status::NoContent
+    }
+
+    struct NoStore;
+
+    #[rocket::async_trait]
+    impl Fairing for NoStore {
+        fn info(&self) -> Info {
+            Info { name: "No-store caching (debug)", kind: Kind::Response }
+        }
+
+        async fn on_response<'r>(&self, _: &'r Request<'_>, response: &mut Response<'r>) {
+            response.set_header(Header::new("Cache-Control", "no-store"));
+        }
+    }
+
+    struct InjectScript;
+
+    #[rocket::async_trait]
+    impl Fairing for InjectScript {
+        fn info(&self) -> Info {
+            Info { name: "Live reload script", kind: Kind::Response }
+        }
+
+        async fn on_response<'r>(&self, _: &'r Request<'_>, response: &mut Response<'r>) {
+            if !response.content_type().is_some_and(|content_type| content_type.is_html()) {
+                return;
+            }
+            let Ok(body) = response.body_mut().to_string().await else { return };
+            let body = match body.rfind("</body>") {
+                Some(end) => format!("{}{}{}", &body[..end], SCRIPT, &body[end..]),
+                None => body + SCRIPT,
+            };
+            response.set_sized_body(body.len(), Cursor::new(body));
+        }
+    }
+
+    pub fn stage() -> AdHoc {
+        AdHoc::on_ignite("Live reload", |rocket| async {
+            let rocket = rocket.attach(NoStore);
+            if !rocket.figment().extract_inner::<bool>("live_reload").unwrap_or(false) {
+                return rocket;
+            }
+            rocket.manage(Reloads(channel(16).0)).attach(InjectScript).mount("/", rocket::routes![events, reload])
+        })
+    }
+}
+"##;
+
+// Where the module goes: next to the template's other middleware when it has a middleware module
+fn module_location(config: &Config) -> (PathBuf, &'static str) {
+    let middleware_dir = config.project_dir.join("src").join("middleware");
+    if middleware_dir.is_dir() {
+        (middleware_dir.join("livereload.rs"), "crate::middleware::livereload")
+    } else {
+        (config.project_dir.join("src").join("livereload.rs"), "crate::livereload")
+    }
+}
+
+// Add `live_reload = true`, documented, to Rocket.toml's [debug] profile. A value that's already there is left alone
+fn enable_knob(content: &str) -> Result<String, String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("Failed to parse Rocket.toml: {}", e))?;
+    let debug = doc.entry("debug").or_insert_with(toml_edit::table).as_table_mut().ok_or("[debug] in Rocket.toml is not a table")?;
+    if !debug.contains_key("live_reload") {
+        debug.insert("live_reload", toml_edit::value(true));
+        if let Some(mut key) = debug.key_mut("live_reload") {
+            key.leaf_decor_mut().set_prefix(KNOB_COMMENT);
+        }
+    }
+    Ok(doc.to_string())
+}
+
+// `blast gen livereload`: the debug-only module, and the knob in Rocket.toml
+pub fn generate(config: &Config, force: bool) -> BlastResult {
+    let (path, module) = module_location(config);
+    if path.exists() && !force {
+        return Err(format!("{} already exists. Re-run with --force to regenerate it", path.display()));
+    }
+    fs::write(&path, MODULE_SOURCE).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    logger::success(&format!("Generated {}", path.strip_prefix(&config.project_dir).unwrap_or(&path).display()))?;
+
+    let rocket_toml = config.project_dir.join("Rocket.toml");
+    let content = fs::read_to_string(&rocket_toml).unwrap_or_default();
+    let updated = enable_knob(&content)?;
+    if updated != content {
+        fs::write(&rocket_toml, updated).map_err(|e| format!("Failed to write Rocket.toml: {}", e))?;
+        logger::success("Set live_reload = true in Rocket.toml's [debug] profile")?;
+    }
+
+    let name = module.rsplit("::").next().unwrap_or(module);
+    logger::plain("Register it in your Rocket setup:")?;
+    logger::plain(&format!("  - declare the module (`pub mod {};`)", name))?;
+    logger::plain(&format!("  - attach it: `.attach({}::stage())`", module))?;
+    logger::plain("  - keep `blast watch` running next to `blast run`; it rebuilds assets and refreshes the browser")?;
+    logger::plain("Template::fairing() already reloads changed templates in debug builds, so there's nothing to set up for them")
+}
+
+// Why live reload wouldn't work for `blast run`, or None when it would
+fn inactive_reason(config: &Config) -> Option<String> {
+    if config.is_production() {
+        return Some(format!("the {} environment runs a release build, which compiles it out", config.environment));
+    }
+    let (path, module) = module_location(config);
+    let generated = fs::read_to_string(&path).is_ok_and(|source| source.starts_with(GENERATED_HEADER));
+    if !generated {
+        return Some("run `blast gen livereload` to set it up".to_string());
+    }
+    let attached = WalkDir::new(config.project_dir.join("src"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path() != path && entry.path().extension().is_some_and(|extension| extension == "rs"))
+        .any(|entry| fs::read_to_string(entry.path()).is_ok_and(|source| source.contains(STAGE_CALL)));
+    if !attached {
+        return Some(format!("attach `{}::stage()` in your Rocket setup", module));
+    }
+    let rocket_toml = fs::read_to_string(config.project_dir.join("Rocket.toml")).unwrap_or_default();
+    let enabled = toml::from_str::<toml::Value>(&rocket_toml).ok().and_then(|doc| doc.get("debug")?.get("live_reload")?.as_bool()) == Some(true);
+    if !enabled {
+        return Some("set live_reload = true in Rocket.toml's [debug] profile".to_string());
+    }
+    None
+}
+
+// One line for `blast run`
+pub fn status_line(config: &Config) -> String {
+    match inactive_reason(config) {
+        None => "Live reload: active. Run `blast watch` to rebuild assets and refresh the browser on changes".to_string(),
+        Some(reason) => format!("Live reload: off ({})", reason),
+    }
+}
+
+// Tell the running dev server's pages to refresh
+fn ping(config: &Config) -> BlastResult {
+    let server_url = match crate::dashboard::read_server_state(&config.project_dir) {
+        Some(state) if crate::platform::process_running(state.pid) => state.url(),
+        _ => config.server(None).url(),
+    };
+    let url = format!("{}{}", server_url.trim_end_matches('/'), ENDPOINT);
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(2)).build().map_err(|e| e.to_string())?;
+    match client.post(&url).send() {
+        Ok(response) if response.status().is_success() => logger::success("Refreshed the browser"),
+        Ok(response) => logger::warning(&format!("{} answered {}; is live_reload on in Rocket.toml's [debug] profile?", url, response.status())),
+        Err(_) => logger::warning(&format!("No dev server answered at {}; start it with `blast run`", url)),
+    }
+}
+
+// The asset steps of `blast refresh`; false when any of them failed
+fn rebuild_assets(config: &Config) -> Result<bool, String> {
+    let mut ok = true;
+    for result in [crate::assets::transpile_all_scss(config), crate::assets::publish_css(config), crate::assets::process_js(config)] {
+        if let Err(e) = result {
+            logger::error(&format!("Asset processing error: {}", e))?;
+            ok = false;
+        }
+    }
+    Ok(ok)
+}
+
+// `blast watch`: rebuild the assets when src/assets changes and refresh the browser after each successful rebuild,
+// or right away when a template changes
+pub fn watch(config: &Config) -> BlastResult {
+    match inactive_reason(config) {
+        None => logger::plain("Live reload: active, open pages refresh after each rebuild")?,
+        Some(reason) => logger::warning(&format!("Live reload: off ({}). Assets are still rebuilt", reason))?,
+    }
+    logger::plain(&format!("Watching {} and {}/ for changes (Ctrl+C to stop)", ASSET_SOURCES, TEMPLATES_DIR))?;
+    let assets = [PathBuf::from(ASSET_SOURCES)];
+    let templates = [PathBuf::from(TEMPLATES_DIR)];
+    let mut last_assets = fingerprint(config, &assets);
+    let mut last_templates = fingerprint(config, &templates);
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let mut reload = false;
+
+        let current = fingerprint(config, &assets);
+        if current != last_assets {
+            last_assets = current;
+            // A failed rebuild leaves the page as it is; the errors are already on screen
+            reload = rebuild_assets(config)?;
+        }
+        let current = fingerprint(config, &templates);
+        if current != last_templates {
+            last_templates = current;
+            reload = true;
+        }
+
+        if reload && inactive_reason(config).is_none() {
+            ping(config)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knob_is_added_to_the_debug_profile_once() {
+        let updated = enable_knob("[default]\ntemplate_dir = \"templates\"\n\n[debug]\nlog_level = \"normal\"\n").unwrap();
+        let doc: toml::Value = toml::from_str(&updated).unwrap();
+        assert_eq!(doc["debug"]["live_reload"].as_bool(), Some(true));
+        assert_eq!(doc["debug"]["log_level"].as_str(), Some("normal"));
+        assert!(updated.contains("# Live reload (blast gen livereload)"));
+
+        let turned_off = "[debug]\nlive_reload = false\n";
+        assert_eq!(enable_knob(turned_off).unwrap(), turned_off);
+        assert!(enable_knob("").unwrap().contains("[debug]\n# Live reload"));
+    }
+}
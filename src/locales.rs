@@ -0,0 +1,391 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::configs::Config;
+use crate::envfile;
+use crate::logger;
+
+type BlastResult = Result<(), String>;
+
+const LOCALES_DIR: &str = "locales";
+const STARTER_FILE: &str = "main.toml";
+
+const SERVICE_SOURCE: &str = r##"// Generated by `blast gen locale`. Translations from locales/<lang>/*.toml and *.ftl, loaded once at startup:
+// - `{{ t(key="app.welcome", lang=locale, name=user.name) }}` in templates; the other arguments fill {name} in
+//   TOML messages and { $name } in Fluent ones. A key no language has renders as the key itself
+// - the `Locale` request guard picks the best loaded language from Accept-Language
+// - `translate(lang, key, &args)` does the same lookup in Rust
+// DEFAULT_LOCALE in .env is the fallback language. `blast check locales` reports missing keys.
+// Regenerate with `blast gen locale <lang> --force`; translation files are never overwritten.
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::value::{Dict, Value};
+use rocket::figment::Figment;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket_dyn_templates::tera::{self, Tera};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::OnceLock;
+
+const LOCALES_DIR: &str = "locales";
+
+// Messages by language, then by dotted key
+type Catalog = BTreeMap<String, HashMap<String, String>>;
+
+fn catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(load)
+}
+
+pub fn default_locale() -> String {
+    std::env::var("DEFAULT_LOCALE").unwrap_or_else(|_| "en".to_string())
+}
+
+// Nested TOML tables become dotted keys
+fn flatten(prefix: &str, dict: &Dict, messages: &mut HashMap<String, String>) {
+    for (key, value) in dict {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Dict(_, nested) => flatten(&key, nested, messages),
+            Value::String(_, text) => {
+                messages.insert(key, text.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+// Simple Fluent messages: `id = text`, with indented lines continuing the message above
+fn parse_fluent(source: &str, messages: &mut HashMap<String, String>) {
+    let mut current: Option<String> = None;
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            current = None;
+        } else if line.starts_with(char::is_whitespace) {
+            if let Some(text) = current.as_ref().and_then(|id| messages.get_mut(id)) {
+                text.push(' ');
+                text.push_str(trimmed);
+            }
+        } else if let Some((id, text)) = line.split_once('=') {
+            let id = id.trim().to_string();
+            messages.insert(id.clone(), text.trim().to_string());
+            current = Some(id);
+        }
+    }
+}
+
+fn load() -> Catalog {
+    let mut catalog = Catalog::new();
+    let Ok(languages) = fs::read_dir(LOCALES_DIR) else { return catalog };
+    for language in languages.flatten().filter(|entry| entry.path().is_dir()) {
+        let mut files: Vec<_> = fs::read_dir(language.path()).into_iter().flatten().flatten().map(|entry| entry.path()).collect();
+        files.sort();
+        let mut messages = HashMap::new();
+        for file in files {
+            match file.extension().and_then(|extension| extension.to_str()) {
+                Some("toml") => match Figment::from(Toml::file_exact(&file)).extract::<Dict>() {
+                    Ok(dict) => flatten("", &dict, &mut messages),
+                    Err(e) => eprintln!("Skipping {}: {}", file.display(), e),
+                },
+                Some("ftl") => {
+                    if let Ok(source) = fs::read_to_string(&file) {
+                        parse_fluent(&source, &mut messages);
+                    }
+                }
+                _ => {}
+            }
+        }
+        catalog.insert(language.file_name().to_string_lossy().into_owned(), messages);
+    }
+    catalog
+}
+
+// The message for `key` in `lang`, its primary language ("de" for "de-AT") or the default one, with the arguments filled in
+pub fn translate(lang: &str, key: &str, args: &HashMap<String, String>) -> String {
+    let primary = lang.split('-').next().unwrap_or(lang);
+    let fallback = default_locale();
+    let Some(message) = [lang, primary, fallback.as_str()].into_iter().find_map(|candidate| catalog().get(candidate)?.get(key)) else {
+        return key.to_string();
+    };
+    let mut text = message.clone();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value).replace(&format!("{{ ${} }}", name), value);
+    }
+    text
+}
+
+// Registers t(); call it from `Template::custom(|engines| i18n::register(&mut engines.tera))`
+pub fn register(tera: &mut Tera) {
+    catalog();
+    tera.register_function("t", |args: &HashMap<String, tera::Value>| {
+        let key = args.get("key").and_then(|value| value.as_str()).ok_or_else(|| tera::Error::msg("t() needs a key, e.g. t(key=\"app.title\")"))?;
+        let lang = args.get("lang").and_then(|value| value.as_str()).map(str::to_string).unwrap_or_else(default_locale);
+        let values = args
+            .iter()
+            .filter(|(name, _)| !matches!(name.as_str(), "key" | "lang"))
+            .map(|(name, value)| (name.clone(), value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string())))
+            .collect();
+        Ok(tera::Value::String(translate(&lang, key, &values)))
+    });
+}
+
+// The request's language: the first Accept-Language entry, by quality, that a loaded language matches on its full
+// tag or its primary subtag, else DEFAULT_LOCALE
+pub struct Locale(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Locale {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Locale(negotiate(request.headers().get_one("Accept-Language").unwrap_or_default())))
+    }
+}
+
+fn negotiate(header: &str) -> String {
+    let mut wanted: Vec<(f32, &str)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.trim().split(';');
+            let tag = pieces.next()?.trim();
+            let quality = pieces.find_map(|piece| piece.trim().strip_prefix("q=")?.parse().ok()).unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*").then_some((quality, tag))
+        })
+        .collect();
+    wanted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    wanted
+        .iter()
+        .find_map(|(_, tag)| {
+            let primary = tag.split('-').next().unwrap_or(tag);
+            [*tag, primary].into_iter().find(|candidate| catalog().contains_key(*candidate)).map(str::to_string)
+        })
+        .unwrap_or_else(default_locale)
+}
+"##;
+
+// Keys of one language, from its .toml and .ftl files
+type Keys = BTreeSet<String>;
+
+fn starter_file(lang: &str, project_name: &str) -> String {
+    format!(
+        "# {} translations. Nested tables become dotted keys: {{{{ t(key=\"app.welcome\", lang=locale, name=user.name) }}}}\n# fills {{name}} from the argument of the same name. `blast check locales` compares the languages\n[app]\ntitle = \"{}\"\nwelcome = \"Welcome, {{name}}!\"\n",
+        lang, project_name
+    )
+}
+
+// Language tags like en, pt-BR or zh-Hant; they name the directory under locales/
+fn valid_tag(lang: &str) -> bool {
+    Regex::new(r"^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$").unwrap().is_match(lang)
+}
+
+// Where the service goes: next to the template's other services when it has a services module
+fn service_location(project_dir: &Path) -> (PathBuf, &'static str) {
+    let services_dir = project_dir.join("src").join("services");
+    if services_dir.is_dir() {
+        (services_dir.join("i18n.rs"), "crate::services::i18n")
+    } else {
+        (project_dir.join("src").join("i18n.rs"), "crate::i18n")
+    }
+}
+
+// Write the service (unless it exists and force is off) and the starter file for `lang` (never over translations).
+// Returns the service's module path, or None when it was left as it was
+fn scaffold(project_dir: &Path, project_name: &str, lang: &str, force: bool) -> Result<Option<&'static str>, String> {
+    if !valid_tag(lang) {
+        return Err(format!("'{}' isn't a language tag. Use one like en, de or pt-BR", lang));
+    }
+    let lang_dir = project_dir.join(LOCALES_DIR).join(lang);
+    if lang_dir.exists() && !force {
+        return Err(format!("{}/{} already exists. Re-run with --force to regenerate the locale service", LOCALES_DIR, lang));
+    }
+    fs::create_dir_all(&lang_dir).map_err(|e| format!("Failed to create {}: {}", lang_dir.display(), e))?;
+    let starter = lang_dir.join(STARTER_FILE);
+    if !starter.exists() {
+        fs::write(&starter, starter_file(lang, project_name)).map_err(|e| format!("Failed to write {}: {}", starter.display(), e))?;
+        logger::success(&format!("Created {}/{}/{}", LOCALES_DIR, lang, STARTER_FILE))?;
+    }
+
+    let (path, module) = service_location(project_dir);
+    if path.exists() && !force {
+        return Ok(None);
+    }
+    fs::write(&path, SERVICE_SOURCE).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    logger::success(&format!("Generated {}", path.strip_prefix(project_dir).unwrap_or(&path).display()))?;
+    Ok(Some(module))
+}
+
+fn print_registration(module: &str) -> BlastResult {
+    let name = module.rsplit("::").next().unwrap_or(module);
+    logger::plain("Register it in your Rocket setup:")?;
+    logger::plain(&format!("  - declare the module (`pub mod {};`)", name))?;
+    logger::plain(&format!("  - register t(): `.attach(Template::custom(|engines| {}::register(&mut engines.tera)))`", module))?;
+    logger::plain(&format!("  - take `locale: {}::Locale` in handlers and pass `locale.0` to templates as `locale`", module))
+}
+
+// `blast gen locale <lang>`: a language directory with a starter file, plus the locale service the first time
+pub fn generate(config: &mut Config, lang: &str, force: bool) -> BlastResult {
+    let first = languages(&config.project_dir).is_empty();
+    let module = scaffold(&config.project_dir, &config.project_name, lang, force)?;
+    if !config.i18n().enabled {
+        config.set_key("i18n.enabled", "true")?;
+    }
+    if first {
+        let added = envfile::append_missing(config, "Translations (blast gen locale): the language pages fall back to", &[("DEFAULT_LOCALE".to_string(), lang.to_string())])?;
+        if !added.is_empty() {
+            logger::success(&format!("Added {} to .env", added.join(", ")))?;
+        }
+    }
+    if let Some(module) = module {
+        print_registration(module)?;
+    } else if !first {
+        logger::plain("Run `blast check locales` to see which keys the new language still needs")?;
+    }
+    Ok(())
+}
+
+// `blast new --i18n`: the service and an English starter file, switched on in Catalyst.toml
+pub fn scaffold_new_project(project_dir: &Path, project_name: &str) -> BlastResult {
+    let module = scaffold(project_dir, project_name, "en", false)?;
+
+    let catalyst = project_dir.join("Catalyst.toml");
+    let content = fs::read_to_string(&catalyst).map_err(|e| format!("Failed to read Catalyst.toml: {}", e))?;
+    let mut doc: toml_edit::DocumentMut = content.parse().map_err(|e| format!("Failed to parse Catalyst.toml: {}", e))?;
+    doc["i18n"]["enabled"] = toml_edit::value(true);
+    fs::write(&catalyst, doc.to_string()).map_err(|e| format!("Failed to write Catalyst.toml: {}", e))?;
+
+    envfile::append_missing_at(&project_dir.join(".env"), "Translations (blast gen locale): the language pages fall back to", &[("DEFAULT_LOCALE".to_string(), "en".to_string())])?;
+    if let Some(module) = module {
+        print_registration(module)?;
+    }
+    Ok(())
+}
+
+// Language directories under locales/
+fn languages(project_dir: &Path) -> Vec<String> {
+    let mut languages: Vec<String> = fs::read_dir(project_dir.join(LOCALES_DIR))
+        .map(|entries| entries.flatten().filter(|entry| entry.path().is_dir()).map(|entry| entry.file_name().to_string_lossy().into_owned()).collect())
+        .unwrap_or_default();
+    languages.sort();
+    languages
+}
+
+fn flatten(prefix: &str, table: &toml::value::Table, keys: &mut Keys) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(nested) => flatten(&key, nested, keys),
+            _ => {
+                keys.insert(key);
+            }
+        }
+    }
+}
+
+// Message ids of a Fluent file: unindented `id = ...` lines
+fn fluent_keys(source: &str, keys: &mut Keys) {
+    for line in source.lines().filter(|line| !line.starts_with(char::is_whitespace) && !line.starts_with('#')) {
+        if let Some((id, _)) = line.split_once('=') {
+            keys.insert(id.trim().to_string());
+        }
+    }
+}
+
+// Keys by language, read the way the generated service reads them
+fn load_keys(project_dir: &Path) -> Result<BTreeMap<String, Keys>, String> {
+    let mut catalog = BTreeMap::new();
+    for lang in languages(project_dir) {
+        let mut keys = Keys::new();
+        for entry in fs::read_dir(project_dir.join(LOCALES_DIR).join(&lang)).map_err(|e| e.to_string())?.flatten() {
+            let path = entry.path();
+            let display = format!("{}/{}/{}", LOCALES_DIR, lang, entry.file_name().to_string_lossy());
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("toml") => {
+                    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", display, e))?;
+                    let table: toml::value::Table = toml::from_str(&content).map_err(|e| format!("{}: {}", display, e))?;
+                    flatten("", &table, &mut keys);
+                }
+                Some("ftl") => fluent_keys(&fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", display, e))?, &mut keys),
+                _ => {}
+            }
+        }
+        catalog.insert(lang, keys);
+    }
+    Ok(catalog)
+}
+
+// Keys each language lacks that another language has
+fn missing_keys(catalog: &BTreeMap<String, Keys>) -> Vec<(String, Vec<String>)> {
+    let all: Keys = catalog.values().flatten().cloned().collect();
+    catalog
+        .iter()
+        .map(|(lang, keys)| (lang.clone(), all.difference(keys).cloned().collect::<Vec<_>>()))
+        .filter(|(_, missing)| !missing.is_empty())
+        .collect()
+}
+
+// (line number, key) of each t(key="...") call in a template
+fn referenced_keys(source: &str) -> Vec<(usize, String)> {
+    let pattern = Regex::new(r#"\bt\(\s*key\s*=\s*["']([^"']+)["']"#).unwrap();
+    source.lines().enumerate().flat_map(|(index, line)| pattern.captures_iter(line).map(move |captures| (index + 1, captures[1].to_string())).collect::<Vec<_>>()).collect()
+}
+
+// `blast check locales`: keys some languages lack (warnings) and t() keys no language has (errors)
+pub fn check(config: &Config) -> BlastResult {
+    let catalog = load_keys(&config.project_dir)?;
+    if catalog.is_empty() {
+        return Err(format!("No languages in {}/. Add one with `blast gen locale <lang>`", LOCALES_DIR));
+    }
+
+    let mut warnings = 0;
+    for (lang, missing) in missing_keys(&catalog) {
+        logger::warning(&format!("{} is missing {} key(s): {}", lang, missing.len(), missing.join(", ")))?;
+        warnings += missing.len();
+    }
+
+    let all: Keys = catalog.values().flatten().cloned().collect();
+    let templates_dir = config.project_dir.join(crate::templates::TEMPLATES_DIR);
+    let mut errors = 0;
+    for entry in WalkDir::new(&templates_dir).sort_by_file_name().into_iter().filter_map(Result::ok).filter(|entry| entry.file_type().is_file()) {
+        let Ok(source) = fs::read_to_string(entry.path()) else { continue };
+        let display = entry.path().strip_prefix(&config.project_dir).unwrap_or(entry.path()).display().to_string();
+        for (line, key) in referenced_keys(&source) {
+            if !all.contains(&key) {
+                logger::error(&format!("{}:{}: t(key=\"{}\") is in no locale file", display, line, key))?;
+                errors += 1;
+            }
+        }
+    }
+
+    if errors > 0 {
+        return Err(format!("{} template key(s) missing from every language", errors));
+    }
+    if warnings > 0 {
+        return logger::warning(&format!("{} language(s), {} key(s); {} translation(s) missing", catalog.len(), all.len(), warnings));
+    }
+    logger::success(&format!("{} language(s), {} key(s), all translated", catalog.len(), all.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_untranslated_and_unknown_keys() {
+        let mut catalog = BTreeMap::new();
+        let mut en = Keys::new();
+        flatten("", &toml::from_str("[app]\ntitle = \"Shop\"\nwelcome = \"Hi {name}\"\n").unwrap(), &mut en);
+        let mut de = Keys::new();
+        fluent_keys("# Deutsch\napp.title = Laden\n    continued\n", &mut de);
+        catalog.insert("en".to_string(), en);
+        catalog.insert("de".to_string(), de);
+        assert_eq!(missing_keys(&catalog), vec![("de".to_string(), vec!["app.welcome".to_string()])]);
+
+        let template = "<h1>{{ t(key=\"app.title\", lang=locale) }}</h1>\n<p>{{ t( key='cart.empty' ) }} {{ format(key=\"x\") }}</p>";
+        assert_eq!(referenced_keys(template), vec![(1, "app.title".to_string()), (2, "cart.empty".to_string())]);
+        assert!(valid_tag("pt-BR") && !valid_tag("../etc"));
+    }
+}
@@ -1,40 +1,88 @@
 use crate::configs::Config;
 use chrono::Local;
 use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle, WeakProgressBar};
 use lazy_static::lazy_static;
 use std::env;
 
 // Type alias for consistent error handling
 type BlastResult = Result<(), String>;
 use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::io::{self, BufReader, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
 use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 
 // Runtime mode enum for determining where output should go
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RuntimeMode {
     Cli,       // Standard CLI mode - print to stdout with colors
     Dashboard, // Dashboard mode - log to file only
+    Json,      // Machine-readable mode - one JSON object per line on stdout
+    Sink,      // Library mode - every line goes to the installed LogSink, nothing is printed
+}
+
+// How much blast prints: warnings and results by default, -v is Debug, -vv is Trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet, // --quiet: no progress bars or spinners either
+    Warn,
+    Info,
+    Debug,
+    Trace, // Also streams the output of diesel, psql, git and other child processes
+}
+
+impl Verbosity {
+    // Number of -v flags
+    pub fn from_count(count: usize) -> Self {
+        match count {
+            0 => Verbosity::Warn,
+            1 => Verbosity::Debug,
+            _ => Verbosity::Trace,
+        }
+    }
+
+    // BLAST_VERBOSE values: 1/2 like -v/-vv, or a level name
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "quiet" => Some(Verbosity::Quiet),
+            "0" | "warn" => Some(Verbosity::Warn),
+            "info" => Some(Verbosity::Info),
+            "1" | "debug" => Some(Verbosity::Debug),
+            "2" | "trace" => Some(Verbosity::Trace),
+            _ => None,
+        }
+    }
 }
 
 // Log level for message categorization
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum LogLevel {
+    Trace,
     Debug,
     Info,
+    Plain, // Output the user asked for (previews, listings): always shown, no icon
     Warning,
     Error,
     Success,
 }
 
+// Where log lines go when blast runs as a library (api::set_log_sink). It gets every level; filtering is its call
+pub trait LogSink: Send + Sync {
+    fn log(&self, level: LogLevel, message: &str);
+}
+
 // Global state
 lazy_static! {
     static ref RUNTIME_MODE: Arc<Mutex<RuntimeMode>> = Arc::new(Mutex::new(RuntimeMode::Cli));
     static ref LOG_FILE_PATH: Arc<Mutex<Option<PathBuf>>> = Arc::new(Mutex::new(None));
-    static ref QUIET_MODE: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
-    static ref VERBOSE_MODE: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+    static ref VERBOSITY: Arc<Mutex<Verbosity>> = Arc::new(Mutex::new(Verbosity::Warn));
+    static ref LANES: Arc<Mutex<Option<MultiProgress>>> = Arc::new(Mutex::new(None));
+    static ref SINK: Mutex<Option<Arc<dyn LogSink>>> = Mutex::new(None);
+    // No colors or emoji when NO_COLOR is set or stdout isn't a terminal (pipes, CI logs)
+    static ref PLAIN_OUTPUT: bool = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) || !io::stdout().is_terminal();
 }
 
 // Standard log files
@@ -46,6 +94,11 @@ pub fn init(mode: RuntimeMode, log_path: Option<&Path>) -> BlastResult {
     let mut current_mode = RUNTIME_MODE.lock().unwrap();
     *current_mode = mode;
 
+    // console's own detection misses NO_COLOR on some terminals; every style() call follows this
+    if plain_output() {
+        console::set_colors_enabled(false);
+    }
+
     // If log path provided, initialize log file
     if let Some(path) = log_path {
         // Ensure directory exists
@@ -68,92 +121,266 @@ pub fn init(mode: RuntimeMode, log_path: Option<&Path>) -> BlastResult {
     Ok(())
 }
 
+// Send every line to `sink` from now on; progress bars are hidden like in the other non-terminal modes
+pub fn set_sink(sink: Arc<dyn LogSink>) {
+    *SINK.lock().unwrap() = Some(sink);
+    *RUNTIME_MODE.lock().unwrap() = RuntimeMode::Sink;
+}
+
 // Environment checks
-pub fn set_quiet_mode(quiet: bool) {
-    let mut quiet_mode = QUIET_MODE.lock().unwrap();
-    *quiet_mode = quiet;
+pub fn set_level(level: Verbosity) {
+    let mut verbosity = VERBOSITY.lock().unwrap();
+    *verbosity = level;
 }
 
-pub fn set_verbose_mode(verbose: bool) {
-    let mut verbose_mode = VERBOSE_MODE.lock().unwrap();
-    *verbose_mode = verbose;
+// The verbosity from the flags, unless BLAST_VERBOSE overrides it for scripts
+pub fn level() -> Verbosity {
+    if let Some(level) = env::var("BLAST_VERBOSE").ok().and_then(|value| Verbosity::parse(&value)) {
+        return level;
+    }
+    *VERBOSITY.lock().unwrap()
 }
 
-fn is_quiet() -> bool {
-    let quiet_mode = QUIET_MODE.lock().unwrap();
-    *quiet_mode
+pub fn get_mode() -> RuntimeMode {
+    let mode = RUNTIME_MODE.lock().unwrap();
+    *mode
 }
 
-pub fn is_verbose() -> bool {
-    let verbose_mode = VERBOSE_MODE.lock().unwrap();
-    *verbose_mode || env::var("BLAST_VERBOSE").unwrap_or_else(|_| String::from("0")) == "1"
+// True when output should carry neither colors nor emoji
+pub fn plain_output() -> bool {
+    *PLAIN_OUTPUT
 }
 
-fn get_mode() -> RuntimeMode {
-    let mode = RUNTIME_MODE.lock().unwrap();
-    *mode
+// Get icon for log level, or its ASCII marker for plain output
+fn get_icon(level: LogLevel, styled: bool) -> &'static str {
+    match (level, styled) {
+        (LogLevel::Trace, _) => "  ",
+        (LogLevel::Plain, _) => "",
+        (LogLevel::Debug, true) => "🔍",
+        (LogLevel::Info, true) => "ℹ️",
+        (LogLevel::Warning, true) => "⚠️",
+        (LogLevel::Error, true) => "❌",
+        (LogLevel::Success, true) => "✅",
+        (LogLevel::Debug, false) => "[debug]",
+        (LogLevel::Info, false) => "[info]",
+        (LogLevel::Warning, false) => "[warn]",
+        (LogLevel::Error, false) => "[error]",
+        (LogLevel::Success, false) => "[ok]",
+    }
 }
 
-// Get icon for log level
-fn get_icon(level: LogLevel) -> &'static str {
+// A console line for `message`: icon and color when styled, an ASCII marker and no escape codes otherwise
+pub fn render(level: LogLevel, message: &str, styled: bool) -> String {
+    let icon = get_icon(level, styled);
+    let text = style(message).force_styling(styled);
     match level {
-        LogLevel::Debug => "🔍",
-        LogLevel::Info => "ℹ️",
-        LogLevel::Warning => "⚠️",
-        LogLevel::Error => "❌",
-        LogLevel::Success => "✅",
+        LogLevel::Trace => format!("{} {}", icon, text.dim()),
+        LogLevel::Debug | LogLevel::Info => format!("{} {}", icon, message),
+        LogLevel::Plain => message.to_string(),
+        LogLevel::Warning => format!("{} {}", icon, text.yellow()),
+        LogLevel::Error => format!("{} {}", icon, text.red().bold()),
+        LogLevel::Success => format!("{} {}", icon, text.green()),
+    }
+}
+
+// A pass/fail mark for lines of tool output: a colored check or cross, or [ok]/[fail] for plain output
+pub fn status_mark(ok: bool) -> String {
+    match (ok, plain_output()) {
+        (true, false) => style("✔").green().to_string(),
+        (false, false) => style("✖").red().to_string(),
+        (true, true) => "[ok]".to_string(),
+        (false, true) => "[fail]".to_string(),
     }
 }
 
-// Simple logging function
+// The single log sink: every message, including progress lines, ends up here
 pub fn log(level: LogLevel, message: &str) -> BlastResult {
     let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-    let icon = get_icon(level);
-    
-    // Format log message
-    let log_msg = format!("[{}] [{}] {}", timestamp, level_to_string(level), message);
-    
-    // Write to log file if in dashboard mode
-    if get_mode() == RuntimeMode::Dashboard {
-        if let Some(log_path) = &*LOG_FILE_PATH.lock().unwrap() {
-            if let Ok(mut file) = OpenOptions::new().create(true).write(true).append(true).open(log_path) {
-                writeln!(file, "{}", log_msg).map_err(|e| e.to_string())?;
+    if matches!(level, LogLevel::Warning | LogLevel::Error) {
+        collect(level, message);
+    }
+
+    match get_mode() {
+        // Dashboard mode writes everything to the interactive log file
+        RuntimeMode::Dashboard => {
+            if let Some(log_path) = &*LOG_FILE_PATH.lock().unwrap() {
+                if let Ok(mut file) = OpenOptions::new().create(true).write(true).append(true).open(log_path) {
+                    writeln!(file, "[{}] [{}] {}", timestamp, level_to_string(level), message).map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        RuntimeMode::Sink => {
+            let sink = SINK.lock().unwrap().clone();
+            if let Some(sink) = sink {
+                sink.log(level, message);
+            }
+        }
+        RuntimeMode::Json => {
+            if is_visible(level, message) {
+                let line = serde_json::json!({ "timestamp": timestamp.to_string(), "level": level_to_string(level).to_lowercase(), "message": message });
+                println!("{}", line);
+            }
+        }
+        RuntimeMode::Cli => {
+            if !is_visible(level, message) {
+                return Ok(());
+            }
+
+            // Print to console with appropriate styling
+            let line = render(level, message, !plain_output());
+            let print = || println!("{}", line);
+
+            // Lines go above the lane bars instead of through them
+            let lanes = LANES.lock().unwrap().clone();
+            match lanes {
+                Some(multi) => multi.suspend(print),
+                None => print(),
             }
         }
-        return Ok(());
     }
-    
-    // CLI mode output handling
-    if is_quiet() {
-        return Ok(());
+
+    Ok(())
+}
+
+// Trace, debug and info need the matching verbosity; info marked critical always shows
+fn is_visible(level: LogLevel, message: &str) -> bool {
+    match level {
+        LogLevel::Trace => self::level() >= Verbosity::Trace,
+        LogLevel::Debug => self::level() >= Verbosity::Debug,
+        LogLevel::Info => self::level() >= Verbosity::Info || message.contains("critical"),
+        _ => true,
     }
-    
-    // Only show debug in verbose mode
-    if level == LogLevel::Debug && !is_verbose() {
-        return Ok(());
+}
+
+// A warning or error raised while a command ran, counted once per step and message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub step: String,
+    pub level: LogLevel,
+    pub message: String,
+    pub count: usize,
+}
+
+struct Summary {
+    command: String,
+    step: Option<String>,
+    issues: Vec<Issue>,
+    depth: usize,
+}
+
+// Collects the warnings and errors of the running command. Nested commands (the dashboard running refresh,
+// init running its phases) share the outermost collector, which prints once when that command ends
+static SUMMARY: Mutex<Option<Summary>> = Mutex::new(None);
+
+pub fn begin_summary(command: &str) {
+    let mut summary = SUMMARY.lock().unwrap();
+    match summary.as_mut() {
+        Some(summary) => summary.depth += 1,
+        None => *summary = Some(Summary { command: command.to_string(), step: None, issues: Vec::new(), depth: 1 }),
     }
-    
-    // Only show info in verbose mode unless critical
-    if level == LogLevel::Info && !is_verbose() && !message.contains("critical") {
-        return Ok(());
+}
+
+// Group what is logged from now on under `step`; operation::step calls this when a step starts
+pub fn summary_step(step: &str) {
+    if let Some(summary) = SUMMARY.lock().unwrap().as_mut() {
+        summary.step = Some(step.to_string());
     }
-    
-    // Print to console with appropriate styling
-    match level {
-        LogLevel::Debug => println!("{} {}", icon, message),
-        LogLevel::Info => println!("{} {}", icon, message),
-        LogLevel::Warning => println!("{} {}", icon, style(message).yellow()),
-        LogLevel::Error => println!("{} {}", icon, style(message).red().bold()),
-        LogLevel::Success => println!("{} {}", icon, style(message).green()),
+}
+
+fn collect(level: LogLevel, message: &str) {
+    let mut summary = SUMMARY.lock().unwrap();
+    let Some(summary) = summary.as_mut() else { return };
+    let step = summary.step.clone().unwrap_or_else(|| summary.command.clone());
+    match summary.issues.iter_mut().find(|issue| issue.step == step && issue.level == level && issue.message == message) {
+        Some(issue) => issue.count += 1,
+        None => summary.issues.push(Issue { step, level, message: message.to_string(), count: 1 }),
     }
-    
-    Ok(())
+}
+
+// End the command started by begin_summary and, for the outermost one, print what it collected: a block
+// below the final line in the terminal, a `warnings` array in JSON mode. Nothing when the run was clean.
+// The error a command failed with is counted under the step it failed in
+pub fn finish_summary(failure: Option<&String>) {
+    if let Some(failure) = failure {
+        collect(LogLevel::Error, failure);
+    }
+    let summary = {
+        let mut summary = SUMMARY.lock().unwrap();
+        match summary.as_mut() {
+            Some(current) if current.depth > 1 => {
+                current.depth -= 1;
+                None
+            }
+            _ => summary.take(),
+        }
+    };
+    let Some(summary) = summary.filter(|summary| !summary.issues.is_empty()) else { return };
+
+    match get_mode() {
+        RuntimeMode::Cli => {
+            clear_progress();
+            println!("\n{}", summary_lines(&summary.command, &summary.issues, !plain_output(), self::level() < Verbosity::Debug).join("\n"));
+        }
+        RuntimeMode::Json => {
+            let warnings: Vec<_> = summary
+                .issues
+                .iter()
+                .map(|issue| serde_json::json!({ "step": issue.step, "level": level_to_string(issue.level).to_lowercase(), "message": issue.message, "count": issue.count }))
+                .collect();
+            let line = serde_json::json!({ "timestamp": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(), "level": "summary", "message": count_label(&summary.issues), "warnings": warnings });
+            println!("{}", line);
+        }
+        RuntimeMode::Dashboard | RuntimeMode::Sink => {
+            for line in summary_lines(&summary.command, &summary.issues, false, false) {
+                log(LogLevel::Plain, &line).unwrap_or_default();
+            }
+        }
+    }
+}
+
+// "2 warnings, 1 error"
+fn count_label(issues: &[Issue]) -> String {
+    let total = |level| issues.iter().filter(|issue| issue.level == level).map(|issue| issue.count).sum::<usize>();
+    let plural = |count: usize, word: &str| format!("{} {}{}", count, word, if count == 1 { "" } else { "s" });
+    let (warnings, errors) = (total(LogLevel::Warning), total(LogLevel::Error));
+    match (warnings, errors) {
+        (_, 0) => plural(warnings, "warning"),
+        (0, _) => plural(errors, "error"),
+        _ => format!("{}, {}", plural(warnings, "warning"), plural(errors, "error")),
+    }
+}
+
+// The summary block: a header with the totals, then each step's issues in the order they were first raised
+fn summary_lines(command: &str, issues: &[Issue], styled: bool, hint: bool) -> Vec<String> {
+    let header = format!("{} during {}:", count_label(issues), command);
+    let mut lines = vec![style(header).force_styling(styled).bold().to_string()];
+
+    let mut steps: Vec<&str> = Vec::new();
+    for issue in issues {
+        if !steps.contains(&issue.step.as_str()) {
+            steps.push(&issue.step);
+        }
+    }
+    for step in steps {
+        let step_issues: Vec<Issue> = issues.iter().filter(|issue| issue.step == step).cloned().collect();
+        lines.push(format!("  {} ({})", step, count_label(&step_issues)));
+        for issue in &step_issues {
+            let message = if issue.count > 1 { format!("{} (x{})", issue.message, issue.count) } else { issue.message.clone() };
+            lines.push(format!("    {}", render(issue.level, &message, styled)));
+        }
+    }
+
+    if hint {
+        lines.push("Run again with -v for details".to_string());
+    }
+    lines
 }
 
 fn level_to_string(level: LogLevel) -> &'static str {
     match level {
+        LogLevel::Trace => "TRACE",
         LogLevel::Debug => "DEBUG",
-        LogLevel::Info => "INFO",
+        LogLevel::Info | LogLevel::Plain => "INFO",
         LogLevel::Warning => "WARNING",
         LogLevel::Error => "ERROR",
         LogLevel::Success => "SUCCESS",
@@ -161,6 +388,10 @@ fn level_to_string(level: LogLevel) -> &'static str {
 }
 
 // Helper functions for specific log levels
+pub fn trace(message: &str) -> BlastResult {
+    log(LogLevel::Trace, message)
+}
+
 pub fn debug(message: &str) -> BlastResult {
     log(LogLevel::Debug, message)
 }
@@ -169,6 +400,10 @@ pub fn info(message: &str) -> BlastResult {
     log(LogLevel::Info, message)
 }
 
+pub fn plain(message: &str) -> BlastResult {
+    log(LogLevel::Plain, message)
+}
+
 pub fn warning(message: &str) -> BlastResult {
     log(LogLevel::Warning, message)
 }
@@ -181,6 +416,117 @@ pub fn success(message: &str) -> BlastResult {
     log(LogLevel::Success, message)
 }
 
+// Credentials in URLs masked, keeping scheme, host and database visible so the message still says where
+// blast was connecting: postgres://app:pw@db:5432/shop becomes postgres://<masked>@db:5432/shop.
+// URLs without credentials and any other text pass through untouched
+pub fn redact(text: &str) -> String {
+    lazy_static! {
+        static ref CREDENTIAL_URL: regex::Regex = regex::Regex::new(r#"([A-Za-z][A-Za-z0-9+.-]*://)([^\s'"<>]*)@([^\s'"<>@/]*)"#).unwrap();
+    }
+    CREDENTIAL_URL
+        .replace_all(text, |captures: &regex::Captures| {
+            let userinfo = &captures[2];
+            // github.com/user@branch is a path, not credentials; a password may contain '/' but then follows a ':'
+            let before_slash = userinfo.split('/').next().unwrap_or_default();
+            if userinfo.contains('/') && !before_slash.contains(':') {
+                captures[0].to_string()
+            } else {
+                format!("{}<masked>@{}", &captures[1], &captures[3])
+            }
+        })
+        .into_owned()
+}
+
+// A command line as it would be typed, with credentials in its arguments redacted
+pub fn command_line(command: &Command) -> String {
+    let words = std::iter::once(command.get_program()).chain(command.get_args()).map(|word| {
+        let word = word.to_string_lossy();
+        if word.is_empty() || word.contains(char::is_whitespace) { format!("'{}'", word) } else { word.into_owned() }
+    });
+    redact(&words.collect::<Vec<_>>().join(" "))
+}
+
+// Log a child process about to be started, visible with -v
+pub fn debug_command(tool: &str, command: &Command) {
+    let _ = debug(&format!("[{}] $ {}", tool, command_line(command)));
+}
+
+// Run a child process and capture its output. At trace level every line is also logged as it
+// arrives, prefixed with the tool name, instead of only being available once the process exits
+pub fn command_output(tool: &str, command: &mut Command) -> io::Result<Output> {
+    command_output_at(tool, command, Verbosity::Trace)
+}
+
+// Same as command_output, streaming the lines from `stream_level` (Debug or Trace) up.
+// Every run is recorded in storage/logs/tools.jsonl for `blast report`
+pub fn command_output_at(tool: &str, command: &mut Command, stream_level: Verbosity) -> io::Result<Output> {
+    debug_command(tool, command);
+    let started = std::time::Instant::now();
+    let result = run_captured(tool, command, stream_level);
+    crate::tool_log::record(tool, command, started, &result);
+    result
+}
+
+// Run a child process attached to the terminal (installers that prompt, cargo's own progress), recording its exit code
+pub fn command_status(tool: &str, command: &mut Command) -> io::Result<std::process::ExitStatus> {
+    debug_command(tool, command);
+    let started = std::time::Instant::now();
+    let result = command.status();
+    crate::tool_log::record_status(tool, command, started, &result);
+    result
+}
+
+fn run_captured(tool: &str, command: &mut Command, stream_level: Verbosity) -> io::Result<Output> {
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    crate::platform::isolate_child(command);
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    crate::platform::track_child(pid);
+    if level() < stream_level {
+        let output = child.wait_with_output();
+        crate::platform::untrack_child(pid);
+        return output;
+    }
+
+    let stdout = child.stdout.take().map(|pipe| stream_lines(tool, pipe, stream_level));
+    let stderr = child.stderr.take().map(|pipe| stream_lines(tool, pipe, stream_level));
+    let status = child.wait();
+    crate::platform::untrack_child(pid);
+    let status = status?;
+
+    let collect = |handle: Option<JoinHandle<Vec<u8>>>| handle.and_then(|handle| handle.join().ok()).unwrap_or_default();
+    Ok(Output { status, stdout: collect(stdout), stderr: collect(stderr) })
+}
+
+// Log each line of a pipe and hand back everything that was read.
+// Carriage returns end a line too, so git's progress counters show up as they update
+fn stream_lines(tool: &str, pipe: impl Read + Send + 'static, stream_level: Verbosity) -> JoinHandle<Vec<u8>> {
+    let tool = tool.to_string();
+    thread::spawn(move || {
+        let mut captured = Vec::new();
+        let mut line = Vec::new();
+        for byte in BufReader::new(pipe).bytes().map_while(Result::ok) {
+            captured.push(byte);
+            if byte == b'\n' || byte == b'\r' {
+                stream_line(&tool, &line, stream_level);
+                line.clear();
+            } else {
+                line.push(byte);
+            }
+        }
+        stream_line(&tool, &line, stream_level);
+        captured
+    })
+}
+
+fn stream_line(tool: &str, line: &[u8], stream_level: Verbosity) {
+    let line = String::from_utf8_lossy(line);
+    if !line.trim().is_empty() {
+        let message = format!("[{}] {}", tool, redact(line.trim_end()));
+        let _ = if stream_level == Verbosity::Trace { trace(&message) } else { debug(&message) };
+    }
+}
+
 // Simple progress bar implementation
 pub fn create_progress(steps: Option<u64>) -> Progress {
     Progress::new(steps)
@@ -214,83 +560,119 @@ impl Progress {
             }
         };
 
+        // Bars are terminal-only; the other modes just get the log lines. Lane bars stand in for any
+        // bar started by the work they track, and --quiet draws none
+        if get_mode() != RuntimeMode::Cli || level() == Verbosity::Quiet || LANES.lock().unwrap().is_some() {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+
+        let mut live_bars = LIVE_BARS.lock().unwrap();
+        live_bars.retain(|bar| bar.upgrade().is_some());
+        live_bars.push(bar.downgrade());
+        drop(live_bars);
         Progress {
             bar,
         }
     }
 
     pub fn set_message(&mut self, msg: &str) -> &mut Self {
-        // Dashboard mode - log to file
-        if get_mode() == RuntimeMode::Dashboard {
-            let _ = info(msg);
-            return self;
-        }
-        
-        // CLI mode - update progress bar
-        if !is_quiet() {
+        crate::operation::message(msg);
+        if get_mode() == RuntimeMode::Cli {
             self.bar.set_message(msg.to_string());
+        } else {
+            let _ = info(msg);
         }
-        
+
         self
     }
 
     pub fn inc(&mut self, delta: u64) -> &mut Self {
-        // Dashboard mode - just log
-        if get_mode() == RuntimeMode::Dashboard {
-            return self;
-        }
-        
-        // CLI mode - update progress bar
-        if !is_quiet() {
-            self.bar.inc(delta);
-        }
-        
+        self.bar.inc(delta);
         self
     }
 
-    pub fn success(&mut self, msg: &str) {
-        // Dashboard mode - log to file
-        if get_mode() == RuntimeMode::Dashboard {
-            let _ = success(msg);
-            return;
-        }
-        
-        // CLI mode - finish and show message
-        if !is_quiet() {
-            self.bar.finish_and_clear();
-            println!("{} {}", get_icon(LogLevel::Success), msg);
+    // Stop the bar and leave it on screen with a closing message
+    pub fn finish(&mut self, msg: &str) {
+        if get_mode() == RuntimeMode::Cli {
+            self.bar.finish_with_message(msg.to_string());
+        } else {
+            let _ = info(msg);
         }
     }
 
+    pub fn success(&mut self, msg: &str) {
+        self.bar.finish_and_clear();
+        let _ = success(msg);
+    }
+
     pub fn error(&mut self, msg: &str) {
-        // Dashboard mode - log to file
-        if get_mode() == RuntimeMode::Dashboard {
-            let _ = error(msg);
-            return;
-        }
-        
-        // CLI mode - finish and show error
-        if !is_quiet() {
-            self.bar.finish_and_clear();
-            eprintln!("{} {}", get_icon(LogLevel::Error), style(msg).red().bold());
-        }
+        self.bar.finish_and_clear();
+        let _ = error(msg);
     }
 
     pub fn warning(&mut self, msg: &str) -> BlastResult {
-        // Dashboard mode - log to file
-        if get_mode() == RuntimeMode::Dashboard {
-            warning(msg)?;
-            return Ok(());
+        // log already makes room above lane bars, and suspending twice would deadlock
+        if LANES.lock().unwrap().is_some() {
+            return warning(msg);
         }
-        
-        // CLI mode - suspend and show warning
-        if !is_quiet() {
-            self.bar.suspend(|| {
-                println!("{} {}", get_icon(LogLevel::Warning), style(msg).yellow());
-            });
+
+        // Keep the bar running underneath the warning
+        let mut result = Ok(());
+        self.bar.suspend(|| result = warning(msg));
+        result
+    }
+
+    // Hide the bar while `f` runs, so prompts aren't drawn over
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        if LANES.lock().unwrap().is_some() {
+            return f();
         }
-        
-        Ok(())
+        self.bar.suspend(f)
+    }
+}
+
+// Every bar started so far, so an interrupted command's can be cleared before blast exits
+static LIVE_BARS: Mutex<Vec<WeakProgressBar>> = Mutex::new(Vec::new());
+
+// Clear the bars and spinners still on screen
+pub fn clear_progress() {
+    for bar in LIVE_BARS.lock().unwrap().drain(..).filter_map(|bar| bar.upgrade()) {
+        bar.finish_and_clear();
+    }
+    if let Some(multi) = LANES.lock().unwrap().as_ref() {
+        let _ = multi.clear();
+    }
+}
+
+// Bars for work running side by side, one line per lane. While they're drawn, log lines print above them
+pub struct Lanes {
+    multi: MultiProgress,
+}
+
+pub fn create_lanes() -> Lanes {
+    let multi = MultiProgress::new();
+    if get_mode() != RuntimeMode::Cli || level() == Verbosity::Quiet {
+        multi.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    *LANES.lock().unwrap() = Some(multi.clone());
+    Lanes { multi }
+}
+
+impl Lanes {
+    pub fn add(&self, label: &str, steps: u64) -> Progress {
+        let bar = self.multi.add(ProgressBar::new(steps));
+        let style = ProgressStyle::default_bar().template("{spinner:.green} {prefix:.bold} {wide_msg} [{pos}/{len}]").unwrap();
+        bar.set_style(style);
+        bar.set_prefix(label.to_string());
+        bar.enable_steady_tick(std::time::Duration::from_millis(100));
+        Progress { bar }
+    }
+}
+
+impl Drop for Lanes {
+    fn drop(&mut self) {
+        *LANES.lock().unwrap() = None;
+        let _ = self.multi.clear();
     }
 }
 
@@ -326,19 +708,11 @@ pub fn setup_for_mode(config: &Config, interactive: bool) -> BlastResult {
     // Ensure log files exist
     ensure_log_files_exist(config)?;
     
-    // Set environment variable
-    if interactive {
-        env::set_var("BLAST_INTERACTIVE", "1");
-    }
-    
-    // Check verbose mode
-    let verbose = env::var("BLAST_VERBOSE").unwrap_or_else(|_| String::from("0")) == "1";
-    set_verbose_mode(verbose);
-    
-    // Set mode and log path
+    // The dashboard logs to its own file; otherwise keep the mode main (or a library's sink) picked
     let mode = if interactive {
-        set_quiet_mode(true);
         RuntimeMode::Dashboard
+    } else if matches!(get_mode(), RuntimeMode::Json | RuntimeMode::Sink) {
+        get_mode()
     } else {
         RuntimeMode::Cli
     };
@@ -448,4 +822,69 @@ pub fn truncate_specific_log(config: &Config, file_name: Option<String>) -> Blas
     // Not found
     error(&format!("Log file not found: {}", file_name))?;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What `blast migrate` prints when one migration needs attention
+    const MIGRATE: &[(LogLevel, &str)] = &[
+        (LogLevel::Plain, "Running pending migrations"),
+        (LogLevel::Warning, "2024-05-01-120000_add_users has no down.sql"),
+        (LogLevel::Error, "2024-05-02-090000_add_posts failed"),
+        (LogLevel::Success, "Applied 1 migration"),
+    ];
+
+    fn rendered(styled: bool) -> String {
+        MIGRATE.iter().map(|(level, message)| render(*level, message, styled)).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn output_renders_styled_for_terminals_and_plain_otherwise() {
+        assert_eq!(
+            rendered(true),
+            "Running pending migrations\n\
+             ⚠️ \x1b[33m2024-05-01-120000_add_users has no down.sql\x1b[0m\n\
+             ❌ \x1b[31m\x1b[1m2024-05-02-090000_add_posts failed\x1b[0m\n\
+             ✅ \x1b[32mApplied 1 migration\x1b[0m"
+        );
+        assert_eq!(
+            rendered(false),
+            "Running pending migrations\n\
+             [warn] 2024-05-01-120000_add_users has no down.sql\n\
+             [error] 2024-05-02-090000_add_posts failed\n\
+             [ok] Applied 1 migration"
+        );
+    }
+
+    #[test]
+    fn summary_groups_issues_by_step_with_counts() {
+        let issue = |step: &str, level, message: &str, count| Issue { step: step.to_string(), level, message: message.to_string(), count };
+        let issues = [
+            issue("seed", LogLevel::Warning, "02_posts.sql failed", 2),
+            issue("assets", LogLevel::Error, "Font download failed", 1),
+            issue("seed", LogLevel::Warning, "03_tags.sql has no rows", 1),
+        ];
+        assert_eq!(
+            summary_lines("init", &issues, false, true).join("\n"),
+            "3 warnings, 1 error during init:\n  \
+             seed (3 warnings)\n    \
+             [warn] 02_posts.sql failed (x2)\n    \
+             [warn] 03_tags.sql has no rows\n  \
+             assets (1 error)\n    \
+             [error] Font download failed\n\
+             Run again with -v for details"
+        );
+    }
+
+    #[test]
+    fn redact_masks_only_url_credentials() {
+        assert_eq!(redact("postgres://app:p%40ss:w/rd#1@db.internal:5432/shop"), "postgres://<masked>@db.internal:5432/shop");
+        assert_eq!(redact("mysql://root:s3cr@t!@localhost/app and more"), "mysql://<masked>@localhost/app and more");
+        assert_eq!(redact("psql --dbname 'postgres://app:x@db/shop'"), "psql --dbname 'postgres://<masked>@db/shop'");
+        assert_eq!(redact("postgres://localhost:5432/shop"), "postgres://localhost:5432/shop");
+        assert_eq!(redact("https://github.com/Arete-Innovations/blast@main"), "https://github.com/Arete-Innovations/blast@main");
+        assert_eq!(redact("user@example.com ran migrations: 3 applied"), "user@example.com ran migrations: 3 applied");
+        assert_eq!(redact("storage/app.db"), "storage/app.db");
+    }
+}
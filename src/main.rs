@@ -1,51 +1,140 @@
 use std::env;
 use std::process;
+use std::path::Path;
 
-mod assets;
-mod commands;
-mod configs;
-mod cronjobs;
-mod cronjobs_tui; // Interactive TUI for cronjob management
-mod dashboard;
-mod database;
-mod dependencies;
-mod interactive;
-// Locale module removed
-mod logger;
-mod models;
-mod output; // Keep temporarily until we migrate references
-mod progress; // Keep temporarily until we migrate references
-mod project;
-mod sparks;
-mod structs;
+use blast::{assets, codegen_manifest, commands, configs, db_safety, dependencies, file_writer, integrity, help, logger, platform, profile, self_update};
 
 fn main() {
     // Initialize components
     let mut dep_manager = dependencies::DependencyManager::new();
 
-    // Get command line arguments
-    let args: Vec<String> = env::args().collect();
+    // Get command line arguments. Everything after `--` belongs to the tool blast runs (`blast test -- users`),
+    // so global flags are only taken from the part before it
+    let mut args: Vec<String> = env::args().collect();
+    let passthrough = args.iter().position(|arg| arg == "--").map(|pos| args.split_off(pos)).unwrap_or_default();
     
-    // Check for verbose flag
-    let verbose_mode = args.iter().any(|arg| arg == "-v" || arg == "--verbose");
-    let filtered_args: Vec<String> = args.iter()
-        .filter(|arg| *arg != "-v" && *arg != "--verbose")
+    // -v/--verbose shows debug output, -vv (or -v twice) also streams child process output
+    let verbosity_flags: usize = args.iter().map(|arg| match arg.as_str() {
+        "-v" | "--verbose" => 1,
+        "-vv" => 2,
+        _ => 0,
+    }).sum();
+    // -q/--quiet leaves only warnings, errors and results: no info lines, progress bars or spinners
+    let quiet = args.iter().any(|arg| matches!(arg.as_str(), "-q" | "--quiet"));
+    let mut filtered_args: Vec<String> = args.iter()
+        .filter(|arg| !matches!(arg.as_str(), "-v" | "-vv" | "--verbose" | "-q" | "--quiet"))
         .cloned()
         .collect();
+
+    // --env <name> overrides the environment for this invocation only (never written to Catalyst.toml)
+    if let Some(pos) = filtered_args.iter().position(|arg| arg == "--env") {
+        let Some(name) = filtered_args.get(pos + 1).cloned() else {
+            eprintln!("--env requires an environment name, e.g. blast --env staging run");
+            process::exit(1);
+        };
+        if let Err(e) = configs::validate_environment_name(&name) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        filtered_args.drain(pos..=pos + 1);
+        // Exported so processes blast spawns (dashboard panes, servers) see the same environment
+        env::set_var(configs::ENVIRONMENT_OVERRIDE_VAR, name);
+    }
+
+    // --force-overwrite <file|all> lets codegen replace generated files that were edited by hand
+    if let Some(pos) = filtered_args.iter().position(|arg| arg == "--force-overwrite") {
+        let Some(target) = filtered_args.get(pos + 1).cloned() else {
+            eprintln!("--force-overwrite requires a file path or 'all', e.g. blast gen models --force-overwrite all");
+            process::exit(1);
+        };
+        filtered_args.drain(pos..=pos + 1);
+        codegen_manifest::set_force_overwrite(target);
+    }
+
+    // --dry-run previews generated files as diffs without writing them; --diff shows the same diffs while writing
+    let dry_run = filtered_args.iter().any(|arg| arg == "--dry-run");
+    let show_diff = filtered_args.iter().any(|arg| arg == "--diff");
+    filtered_args.retain(|arg| arg != "--dry-run" && arg != "--diff");
+    file_writer::set_mode(file_writer::WriteMode { dry_run, diff: show_diff });
+
+    // --skip-hooks runs the asset steps without their [assets.hooks] commands
+    let skip_hooks = filtered_args.iter().any(|arg| arg == "--skip-hooks");
+    filtered_args.retain(|arg| arg != "--skip-hooks");
+    assets::set_skip_hooks(skip_hooks);
+
+    // --profile breaks the total time of long commands down by step and external tool. In `config rocket`
+    // it names the Rocket profile to edit instead
+    let rocket_config = filtered_args.get(1).map(String::as_str) == Some("config") && filtered_args.get(2).map(String::as_str) == Some("rocket");
+    let profile = !rocket_config && filtered_args.iter().any(|arg| arg == "--profile");
+    if !rocket_config {
+        filtered_args.retain(|arg| arg != "--profile");
+    }
+    profile::set_enabled(profile);
+
+    // --allow-remote-destructive lets --yes and scripts drop, reset, roll back or seed a non-local database
+    let allow_remote_destructive = filtered_args.iter().any(|arg| arg == "--allow-remote-destructive");
+    filtered_args.retain(|arg| arg != "--allow-remote-destructive");
+    db_safety::set_allow_remote_destructive(allow_remote_destructive);
     
-    // Initialize logger in CLI mode
-    logger::init(logger::RuntimeMode::Cli, None).unwrap_or_default();
+    // -p <member> runs the command in one app of a [workspace]; --workspace runs it in each of them
+    let mut start_dir = env::current_dir().unwrap_or_default();
+    if let Some(pos) = filtered_args.iter().position(|arg| arg == "-p" || arg == "--package") {
+        let Some(member) = filtered_args.get(pos + 1).cloned() else {
+            eprintln!("-p requires a workspace member, e.g. blast -p web migrate");
+            process::exit(1);
+        };
+        filtered_args.drain(pos..=pos + 1);
+        start_dir = match configs::workspace_member(&start_dir, &member) {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+    }
+    let across_workspace = filtered_args.iter().any(|arg| arg == "--workspace");
+    filtered_args.retain(|arg| arg != "--workspace");
+
+    // --log-format json turns every log and progress line into a JSON object on stdout
+    let mut runtime_mode = logger::RuntimeMode::Cli;
+    if let Some(pos) = filtered_args.iter().position(|arg| arg == "--log-format") {
+        runtime_mode = match filtered_args.get(pos + 1).map(|s| s.as_str()) {
+            Some("json") => logger::RuntimeMode::Json,
+            Some("text") => logger::RuntimeMode::Cli,
+            _ => {
+                eprintln!("--log-format requires 'text' or 'json', e.g. blast --log-format json migrate");
+                process::exit(1);
+            }
+        };
+        filtered_args.drain(pos..=pos + 1);
+    }
+
+    // Initialize the logger once; setup_for_mode keeps this mode for one-shot commands
+    logger::init(runtime_mode, None).unwrap_or_default();
     
-    // Set verbose mode if flag is present
-    logger::set_verbose_mode(verbose_mode);
+    // Set the verbosity from the flags; --quiet wins over -v
+    let verbosity = if quiet { logger::Verbosity::Quiet } else { logger::Verbosity::from_count(verbosity_flags) };
+    logger::set_level(verbosity);
+
+    filtered_args.extend(passthrough);
 
     // Parse CLI arguments (using filtered args without verbose flags)
     if filtered_args.len() > 1 {
         match commands::parse_cli_args(&filtered_args) {
+            Some(commands::Command::InvalidArgument(message)) => {
+                eprintln!("{}", message);
+                process::exit(1);
+            }
+            Some(cmd) if across_workspace => run_across_workspace(cmd, &start_dir, &mut dep_manager),
             Some(cmd) => {
                 // Load project config if needed
-                match configs::get_project_info() {
+                match configs::get_project_info_in(&start_dir) {
                     Ok(mut config) => {
+                        // [logging] verbose = true behaves like --verbose
+                        if config.logging().verbose && !quiet {
+                            logger::set_level(verbosity.max(logger::Verbosity::Debug));
+                        }
+
                         // Setup proper logging for one-shot commands
                         // Use CLI mode (not interactive dashboard mode)
                         if let Err(e) = logger::setup_for_mode(&config, false) {
@@ -53,27 +142,44 @@ fn main() {
                             // Continue anyway as this shouldn't be fatal
                         }
                         
-                        // Execute the command
-                        if let Err(e) = commands::execute(cmd.clone(), &mut config, &mut dep_manager) {
+                        // Execute the command. After Ctrl-C blast exits with 130 instead of reporting what the interruption broke
+                        let result = commands::execute(cmd.clone(), &mut config, &mut dep_manager);
+                        platform::exit_if_interrupted();
+                        if let Err(e) = result {
                             eprintln!("Error executing command: {}", e);
-                            process::exit(1);
+                            // A failed asset hook passes its own exit code on
+                            process::exit(assets::failed_hook_exit_code().unwrap_or(1));
+                        }
+                        file_writer::finish();
+                        // Completion output must stay machine-readable
+                        if !matches!(cmd, commands::Command::SelfUpdate(_) | commands::Command::Completions(_) | commands::Command::CompleteValues(_)) {
+                            self_update::notify_if_outdated(config.update_check());
                         }
                     }
                     Err(e) => {
-                        // NewProject and Help don't need a project config
-                        if matches!(cmd, commands::Command::NewProject(..)) || cmd == commands::Command::Help {
+                        // A `blast new` that stopped before Catalyst.toml was written: repair the project, then init it
+                        if let commands::Command::InitProject(..) = cmd {
+                            let repaired = integrity::repair(&start_dir, None).and_then(|_| configs::get_project_info_in(&start_dir));
+                            match repaired {
+                                Ok(mut config) => {
+                                    if let Err(e) = commands::execute(cmd, &mut config, &mut dep_manager) {
+                                        eprintln!("Error executing command: {}", e);
+                                        process::exit(1);
+                                    }
+                                }
+                                Err(e) => {
+                                    eprintln!("Failed to read project info: {}", e);
+                                    process::exit(1);
+                                }
+                            }
+                        // Outside a project these run with a default config
+                        } else if runs_without_project(&cmd) {
                             // Create a default config for these commands
-                            let mut default_config = configs::Config {
-                                environment: "dev".to_string(),
-                                project_name: match cmd {
-                                    commands::Command::NewProject(ref name, _) => name.clone(),
-                                    _ => "unknown".to_string(),
-                                },
-                                assets: toml::Value::Table(toml::value::Table::new()),
-                                project_dir: std::env::current_dir().unwrap_or_default(),
-                                show_compiler_warnings: true,
-                                last_modified: std::time::SystemTime::now(),
+                            let project_name = match cmd {
+                                commands::Command::NewProject(ref name, ..) => name.as_str(),
+                                _ => "unknown",
                             };
+                            let mut default_config = configs::Config::outside_project(project_name, start_dir.clone());
 
                             // For NewProject and Help, we can just use the default logger init
                             // No need to setup_for_mode as these don't write to project-specific logs
@@ -91,20 +197,25 @@ fn main() {
                 process::exit(0);
             }
             None => {
-                eprintln!("Unknown command. Run 'blast help' for usage information.");
+                let words: Vec<&str> = filtered_args.iter().skip(1).map(String::as_str).collect();
+                eprintln!("{}", help::unknown_command(&words));
                 process::exit(1);
             }
         }
     }
 
     // If no arguments provided, launch dashboard by default
-    match configs::get_project_info() {
+    if across_workspace {
+        eprintln!("--workspace needs a command to run in every member, e.g. blast --workspace migrate");
+        process::exit(1);
+    }
+    match configs::get_project_info_in(&start_dir) {
         Ok(mut config) => {
             // Set up logging for interactive mode
             logger::setup_for_mode(&config, true).unwrap_or_default();
 
             // Launch dashboard
-            if let Err(e) = commands::execute(commands::Command::LaunchDashboard, &mut config, &mut dep_manager) {
+            if let Err(e) = commands::execute(commands::Command::LaunchDashboard(false), &mut config, &mut dep_manager) {
                 eprintln!("Error launching dashboard: {}", e);
                 process::exit(1);
             }
@@ -116,3 +227,45 @@ fn main() {
         }
     }
 }
+
+// NewProject, Verify, Help, completions, spark authoring, doctor and remote health checks don't need a project config
+fn runs_without_project(cmd: &commands::Command) -> bool {
+    matches!(cmd, commands::Command::NewProject(..) | commands::Command::RepairProject(..) | commands::Command::Doctor | commands::Command::SelfUpdate(_) | commands::Command::Verify(..) | commands::Command::ServerStatus(Some(_), _) | commands::Command::Completions(_) | commands::Command::SparkNew(..) | commands::Command::SparkLint(_) | commands::Command::Help(_))
+}
+
+// `--workspace`: run the command in every member of the workspace, one after the other, and summarize
+fn run_across_workspace(cmd: commands::Command, start_dir: &Path, dep_manager: &mut dependencies::DependencyManager) -> ! {
+    let members = match configs::workspace_members(start_dir) {
+        Ok(members) => members,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut failed = Vec::new();
+    for member in &members {
+        let name = member.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        logger::plain(&format!("{} {}", console::style("==>").bold(), member.display())).unwrap_or_default();
+        let result = configs::get_project_info_in(member).and_then(|mut config| {
+            if let Err(e) = logger::setup_for_mode(&config, false) {
+                eprintln!("Warning: Failed to set up logging: {}", e);
+            }
+            commands::execute(cmd.clone(), &mut config, dep_manager)
+        });
+        // Ctrl-C stops the whole run, not just this member
+        platform::exit_if_interrupted();
+        if let Err(e) = result {
+            logger::error(&format!("{}: {}", name, e)).unwrap_or_default();
+            failed.push(name);
+        }
+    }
+    file_writer::finish();
+
+    if failed.is_empty() {
+        logger::success(&format!("Done in all {} workspace members", members.len())).unwrap_or_default();
+        process::exit(0);
+    }
+    logger::error(&format!("Failed in {} of {} workspace members: {}", failed.len(), members.len(), failed.join(", "))).unwrap_or_default();
+    process::exit(assets::failed_hook_exit_code().unwrap_or(1));
+}
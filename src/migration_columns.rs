@@ -0,0 +1,361 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::configs::Config;
+
+// Columns blast's templates fill in on the database side whatever the migrations say
+const CONVENTIONAL: &[&str] = &["id", "created_at", "updated_at"];
+
+const SERIAL_TYPES: &[&str] = &["SERIAL", "SERIAL2", "SERIAL4", "SERIAL8", "SMALLSERIAL", "BIGSERIAL"];
+const TABLE_CONSTRAINTS: &[&str] = &["CONSTRAINT", "PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "EXCLUDE", "LIKE"];
+
+// Compiled once, not per migration file or column
+lazy_static! {
+    static ref CREATE_TABLE: Regex = Regex::new(r"(?is)^CREATE\s+(?:(?:GLOBAL|LOCAL)\s+)?(?:(?:TEMP|TEMPORARY|UNLOGGED)\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?([^\s(]+)\s*\(").unwrap();
+    static ref ALTER_TABLE: Regex = Regex::new(r"(?is)^ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:ONLY\s+)?(\S+)\s+(.*)$").unwrap();
+    static ref DROP_TABLE: Regex = Regex::new(r"(?is)^DROP\s+TABLE\s+(?:IF\s+EXISTS\s+)?(.*?)(?:\s+(?:CASCADE|RESTRICT))?$").unwrap();
+    static ref GENERATED: Regex = Regex::new(r"\bGENERATED\b").unwrap();
+    static ref DEFAULT: Regex = Regex::new(r"\bDEFAULT\b").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Plain,
+    // Has a DEFAULT: an insert may leave it out, but may also set it
+    Defaulted,
+    // SERIAL, identity or generated: the database always fills it in
+    Generated,
+}
+
+// What the migrations of a connection leave each table's columns as. Built once per generation run by replaying
+// every up.sql in version order, so a later ALTER TABLE or DROP COLUMN wins over the CREATE TABLE
+#[derive(Debug, Default)]
+pub struct MigrationColumns {
+    tables: HashMap<String, BTreeMap<String, Column>>,
+}
+
+impl MigrationColumns {
+    // The default connection's migrations; a project without any yields only the conventional columns
+    pub fn load(config: &Config) -> Self {
+        let dir = config.project_dir.join(crate::database::connection_migrations_dir(config, "default"));
+        Self::from_dir(&dir)
+    }
+
+    fn from_dir(dir: &Path) -> Self {
+        let mut columns = Self::default();
+        let mut migrations: Vec<_> = fs::read_dir(dir).map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect()).unwrap_or_default();
+        // Diesel's directory names start with the version, so name order is the order they run in
+        migrations.sort();
+        for migration in migrations {
+            if let Ok(sql) = fs::read_to_string(migration.join("up.sql")) {
+                columns.apply(&sql);
+            }
+        }
+        columns
+    }
+
+    // Columns an insert can't set: generated ones plus id, created_at and updated_at
+    pub fn generated(&self, table: &str) -> Vec<String> {
+        let mut columns = self.with_kind(table, Column::Generated);
+        let conventional: Vec<String> = CONVENTIONAL.iter().filter(|name| !columns.iter().any(|column| column == *name)).map(|name| name.to_string()).collect();
+        columns.extend(conventional);
+        columns
+    }
+
+    // Columns with a DEFAULT, which the insertable struct takes as an Option so None leaves it to the database
+    pub fn defaulted(&self, table: &str) -> Vec<String> {
+        self.with_kind(table, Column::Defaulted)
+    }
+
+    fn with_kind(&self, table: &str, kind: Column) -> Vec<String> {
+        self.tables.get(table).map(|columns| columns.iter().filter(|(_, column)| **column == kind).map(|(name, _)| name.clone()).collect()).unwrap_or_default()
+    }
+
+    fn apply(&mut self, sql: &str) {
+        for statement in statements(sql) {
+            if let Some(caps) = CREATE_TABLE.captures(&statement) {
+                let open = caps.get(0).map(|m| m.end()).unwrap_or_default();
+                let body = &statement[open..closing_paren(&statement, open)];
+                let columns = split_top_level(body).into_iter().filter_map(column_definition).collect();
+                self.tables.insert(identifier(&caps[1]), columns);
+            } else if let Some(caps) = ALTER_TABLE.captures(&statement) {
+                let table = identifier(&caps[1]);
+                for action in split_top_level(&caps[2]) {
+                    self.alter(&table, action);
+                }
+            } else if let Some(caps) = DROP_TABLE.captures(&statement) {
+                for table in caps[1].split(',') {
+                    self.tables.remove(&identifier(table));
+                }
+            }
+        }
+    }
+
+    fn alter(&mut self, table: &str, action: &str) {
+        let words: Vec<String> = action.split_whitespace().map(str::to_uppercase).collect();
+        let word = |index: usize| words.get(index).map(String::as_str).unwrap_or_default();
+
+        if word(0) == "RENAME" && word(1) == "TO" {
+            if let Some(columns) = self.tables.remove(table) {
+                self.tables.insert(identifier(action.split_whitespace().nth(2).unwrap_or_default()), columns);
+            }
+            return;
+        }
+        let columns = self.tables.entry(table.to_string()).or_default();
+        // The column name follows the action and its optional COLUMN / IF [NOT] EXISTS words
+        let skip = |mut index: usize| {
+            if word(index) == "COLUMN" {
+                index += 1;
+            }
+            if word(index) == "IF" {
+                index += if word(index + 1) == "NOT" { 3 } else { 2 };
+            }
+            index
+        };
+
+        match word(0) {
+            "ADD" => {
+                let start = skip(1);
+                if let Some((name, column)) = column_definition(&action.split_whitespace().skip(start).collect::<Vec<_>>().join(" ")) {
+                    columns.insert(name, column);
+                }
+            }
+            "DROP" if !TABLE_CONSTRAINTS.contains(&word(1)) => {
+                if let Some(name) = action.split_whitespace().nth(skip(1)) {
+                    columns.remove(&identifier(name));
+                }
+            }
+            "RENAME" => {
+                let start = skip(1);
+                let names: Vec<&str> = action.split_whitespace().skip(start).collect();
+                if let [from, to_keyword, to] = names[..] {
+                    if to_keyword.eq_ignore_ascii_case("TO") {
+                        if let Some(column) = columns.remove(&identifier(from)) {
+                            columns.insert(identifier(to), column);
+                        }
+                    }
+                }
+            }
+            "ALTER" => {
+                let start = skip(1);
+                let Some(name) = action.split_whitespace().nth(start).map(identifier) else {
+                    return;
+                };
+                let change = words.get(start + 1..).unwrap_or_default().join(" ");
+                let current = columns.get(&name).copied().unwrap_or(Column::Plain);
+                let updated = if change.starts_with("SET DEFAULT") && current != Column::Generated {
+                    Column::Defaulted
+                } else if change.starts_with("DROP DEFAULT") || change.starts_with("DROP IDENTITY") || change.starts_with("DROP EXPRESSION") {
+                    Column::Plain
+                } else if change.starts_with("ADD GENERATED") {
+                    Column::Generated
+                } else {
+                    current
+                };
+                columns.insert(name, updated);
+            }
+            _ => {}
+        }
+    }
+}
+
+// `name type [constraints]` from a CREATE TABLE body or ADD COLUMN; None for table constraints
+fn column_definition(definition: &str) -> Option<(String, Column)> {
+    let mut words = definition.split_whitespace();
+    let name = words.next()?;
+    if TABLE_CONSTRAINTS.contains(&name.to_uppercase().as_str()) {
+        return None;
+    }
+    let rest = without_literals(&words.collect::<Vec<_>>().join(" ")).to_uppercase();
+    let column_type = rest.split(|c: char| c.is_whitespace() || c == '(').next().unwrap_or_default();
+    // DEFAULT inside a CHECK or string literal doesn't count, only the keyword itself
+    let column = if SERIAL_TYPES.contains(&column_type) || GENERATED.is_match(&rest) {
+        Column::Generated
+    } else if DEFAULT.is_match(&rest) {
+        Column::Defaulted
+    } else {
+        Column::Plain
+    };
+    Some((identifier(name), column))
+}
+
+// Unquoted identifiers fold to lower case; a schema prefix is dropped
+fn identifier(name: &str) -> String {
+    let name = name.trim();
+    let name = name.rsplit('.').next().unwrap_or(name);
+    match name.strip_prefix('"').and_then(|name| name.strip_suffix('"')) {
+        Some(quoted) => quoted.to_string(),
+        None => name.to_lowercase(),
+    }
+}
+
+// The text with the contents of '...' literals removed
+fn without_literals(text: &str) -> String {
+    let mut result = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        if c == '\'' {
+            quoted = !quoted;
+            result.push(c);
+        } else if !quoted {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Statements without comments, split on semicolons outside quotes and $$ bodies
+fn statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut chars = sql.chars().peekable();
+    let mut quote: Option<char> = None;
+    // The tag of an open $$ body and where the body starts in `current`
+    let mut dollar_tag: Option<(String, usize)> = None;
+
+    while let Some(c) = chars.next() {
+        if let Some((tag, body)) = &dollar_tag {
+            current.push(c);
+            if current[*body..].ends_with(tag.as_str()) {
+                dollar_tag = None;
+            }
+            continue;
+        }
+        if let Some(q) = quote {
+            current.push(c);
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                current.push('\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                current.push(' ');
+            }
+            '\'' | '"' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            '$' => {
+                let mut tag = String::from("$");
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' {
+                        tag.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if chars.peek() == Some(&'$') {
+                    chars.next();
+                    tag.push('$');
+                    current.push_str(&tag);
+                    dollar_tag = Some((tag, current.len()));
+                } else {
+                    // A positional parameter such as $1
+                    current.push_str(&tag);
+                }
+            }
+            ';' => statements.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    statements.push(current);
+    statements.into_iter().map(|statement| statement.trim().to_string()).filter(|statement| !statement.is_empty()).collect()
+}
+
+// The index of the ')' that closes the '(' just before `start`, or the end of the text
+fn closing_paren(text: &str, start: usize) -> usize {
+    let mut depth = 1;
+    let mut quoted = false;
+    for (index, c) in text[start..].char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => {
+                depth -= 1;
+                if depth == 0 {
+                    return start + index;
+                }
+            }
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+// Split on commas that aren't inside parentheses or quotes, e.g. between column definitions
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quoted, mut start) = (0, false, 0);
+    for (index, c) in text.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                parts.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(migrations: &[(&str, &str)]) -> MigrationColumns {
+        let dir = std::env::temp_dir().join(format!("blast-migration-columns-{}-{}", std::process::id(), migrations.len()));
+        let _ = fs::remove_dir_all(&dir);
+        for (name, sql) in migrations {
+            fs::create_dir_all(dir.join(name)).unwrap();
+            fs::write(dir.join(name).join("up.sql"), sql).unwrap();
+        }
+        let columns = MigrationColumns::from_dir(&dir);
+        let _ = fs::remove_dir_all(&dir);
+        columns
+    }
+
+    #[test]
+    fn later_migrations_change_what_the_create_table_set_up() {
+        // Written out of order on purpose: the version prefix decides
+        let columns = fixture(&[
+            ("2024-03-01-000000_alter_posts", "ALTER TABLE posts ADD COLUMN published BOOLEAN NOT NULL DEFAULT false, ADD COLUMN slug TEXT;\nALTER TABLE public.posts DROP COLUMN views;\nALTER TABLE posts ALTER COLUMN rank SET DEFAULT 0;\nALTER TABLE posts RENAME COLUMN legacy_id TO import_id;"),
+            (
+                "2024-01-01-000000_create_posts",
+                "-- posts; the DEFAULT in this comment doesn't count\nCREATE TABLE IF NOT EXISTS posts (\n    id SERIAL PRIMARY KEY,\n    title VARCHAR(200) NOT NULL CHECK (title <> 'DEFAULT'),\n    views INTEGER NOT NULL DEFAULT 0,\n    rank INTEGER NOT NULL,\n    legacy_id BIGINT GENERATED ALWAYS AS IDENTITY,\n    body TEXT DEFAULT 'a, b; c',\n    CONSTRAINT posts_title UNIQUE (title)\n);\nCREATE FUNCTION touch() RETURNS trigger AS $$ BEGIN NEW.updated_at = now(); RETURN NEW; END; $$ LANGUAGE plpgsql;",
+            ),
+            ("2024-02-01-000000_create_drafts", "CREATE TABLE drafts (id SERIAL PRIMARY KEY, note TEXT DEFAULT '');"),
+            ("2024-04-01-000000_drop_drafts", "DROP TABLE IF EXISTS drafts CASCADE;"),
+        ]);
+
+        assert_eq!(columns.generated("posts"), vec!["id", "import_id", "created_at", "updated_at"]);
+        assert_eq!(columns.defaulted("posts"), vec!["body", "published", "rank"]);
+        assert!(columns.defaulted("drafts").is_empty());
+        assert_eq!(columns.generated("unknown"), vec!["id", "created_at", "updated_at"]);
+    }
+}
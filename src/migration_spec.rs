@@ -0,0 +1,314 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::configs::Config;
+use crate::logger;
+
+// Where `blast migration` records the definitions it collects
+pub const SPECS_DIR: &str = "src/database/migration_specs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpecKind {
+    Create,
+    Alter,
+}
+
+// A table migration as the wizard collects it. Rendering it always yields the same SQL, so a spec can be
+// reviewed before the migration exists and regenerated after tweaking it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationSpec {
+    pub kind: SpecKind,
+    pub table: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<ColumnSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub foreign_keys: Vec<ForeignKeySpec>,
+    // ALTER mode changes besides added columns, applied after them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<AlterAction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: String,
+    #[serde(default)]
+    pub nullable: bool,
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub default: String,
+    #[serde(default)]
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignKeySpec {
+    pub column: String,
+    pub ref_table: String,
+    pub ref_column: String,
+}
+
+// A schema change recorded in ALTER mode besides plain ADD COLUMN
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AlterAction {
+    DropColumn {
+        column: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        column_type: Option<String>,
+    },
+    RenameColumn {
+        from: String,
+        to: String,
+    },
+    AddIndex {
+        name: String,
+        columns: Vec<String>,
+        #[serde(default)]
+        unique: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        predicate: Option<String>,
+    },
+    AddForeignKey {
+        name: String,
+        column: String,
+        ref_table: String,
+        ref_column: String,
+    },
+    AddCheck {
+        name: String,
+        expression: String,
+    },
+}
+
+impl AlterAction {
+    fn up_sql(&self, table: &str) -> String {
+        match self {
+            AlterAction::DropColumn { column, .. } => format!("ALTER TABLE {} DROP COLUMN {};\n", table, column),
+            AlterAction::RenameColumn { from, to } => format!("ALTER TABLE {} RENAME COLUMN {} TO {};\n", table, from, to),
+            AlterAction::AddIndex { name, columns, unique, predicate } => format!(
+                "CREATE {}INDEX {} ON {} ({}){};\n",
+                if *unique { "UNIQUE " } else { "" },
+                name,
+                table,
+                columns.join(", "),
+                predicate.as_ref().map(|p| format!(" WHERE {}", p)).unwrap_or_default()
+            ),
+            AlterAction::AddForeignKey { name, column, ref_table, ref_column } => {
+                format!("ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}({});\n", table, name, column, ref_table, ref_column)
+            }
+            AlterAction::AddCheck { name, expression } => format!("ALTER TABLE {} ADD CONSTRAINT {} CHECK ({});\n", table, name, expression),
+        }
+    }
+
+    // Reverse of up_sql. Dropped columns can be re-created but their data is gone, so say so loudly
+    fn down_sql(&self, table: &str) -> String {
+        match self {
+            AlterAction::DropColumn { column, column_type } => {
+                let mut sql = format!(
+                    "-- WARNING: DATA LOSS. The up migration dropped column '{}' from '{}'.\n\
+                     -- This re-creates the column as nullable, but its previous values and constraints cannot be restored.\n",
+                    column, table
+                );
+                match column_type {
+                    Some(column_type) => sql.push_str(&format!("ALTER TABLE {} ADD COLUMN {} {};\n", table, column, column_type)),
+                    None => {
+                        sql.push_str("-- The column type could not be recovered from existing migrations; fill it in before running this.\n");
+                        sql.push_str(&format!("-- ALTER TABLE {} ADD COLUMN {} <TYPE>;\n", table, column));
+                    }
+                }
+                sql
+            }
+            AlterAction::RenameColumn { from, to } => format!("ALTER TABLE {} RENAME COLUMN {} TO {};\n", table, to, from),
+            AlterAction::AddIndex { name, .. } => format!("DROP INDEX IF EXISTS {};\n", name),
+            AlterAction::AddForeignKey { name, .. } | AlterAction::AddCheck { name, .. } => format!("ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};\n", table, name),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            AlterAction::DropColumn { column, .. } => format!("Drop column {}", column),
+            AlterAction::RenameColumn { from, to } => format!("Rename column {} to {}", from, to),
+            AlterAction::AddIndex { name, columns, unique, .. } => format!("Add {}index {} on ({})", if *unique { "unique " } else { "" }, name, columns.join(", ")),
+            AlterAction::AddForeignKey { column, ref_table, ref_column, .. } => format!("Add foreign key: {} references {}({})", column, ref_table, ref_column),
+            AlterAction::AddCheck { name, expression } => format!("Add check constraint {}: {}", name, expression),
+        }
+    }
+}
+
+impl ColumnSpec {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {}{}{}{}{}",
+            self.name,
+            self.column_type,
+            if self.nullable { "" } else { " NOT NULL" },
+            if self.unique { " UNIQUE" } else { "" },
+            if self.default.is_empty() { String::new() } else { format!(" DEFAULT {}", self.default) },
+            if self.primary_key { " PRIMARY KEY" } else { "" }
+        )
+    }
+}
+
+impl MigrationSpec {
+    pub fn new(kind: SpecKind, table: &str) -> Self {
+        MigrationSpec { kind, table: table.to_string(), columns: Vec::new(), foreign_keys: Vec::new(), changes: Vec::new() }
+    }
+
+    // `diesel migration generate` name: create_posts, alter_posts
+    pub fn migration_name(&self) -> String {
+        format!("{}_{}", if self.kind == SpecKind::Create { "create" } else { "alter" }, self.table)
+    }
+
+    // src/database/migration_specs/<migration name>.toml
+    pub fn default_path(&self) -> PathBuf {
+        Path::new(SPECS_DIR).join(format!("{}.toml", self.migration_name()))
+    }
+}
+
+// The up and down SQL of a spec
+pub fn render(spec: &MigrationSpec) -> (String, String) {
+    let table = &spec.table;
+    let mut up_sql = String::new();
+
+    if spec.kind == SpecKind::Create {
+        let mut definitions: Vec<String> = spec.columns.iter().map(|column| format!("    {}", column.describe())).collect();
+        definitions.extend(spec.foreign_keys.iter().map(|fk| format!("    FOREIGN KEY ({}) REFERENCES {}({})", fk.column, fk.ref_table, fk.ref_column)));
+        up_sql.push_str(&format!("CREATE TABLE IF NOT EXISTS {} (\n{}\n);\n", table, definitions.join(",\n")));
+    } else if !spec.columns.is_empty() || !spec.foreign_keys.is_empty() {
+        // PRIMARY KEY is only offered for new tables
+        let mut clauses: Vec<String> = spec.columns.iter().map(|column| format!("ADD COLUMN {}", ColumnSpec { primary_key: false, ..column.clone() }.describe())).collect();
+        clauses.extend(spec.foreign_keys.iter().map(|fk| format!("ADD FOREIGN KEY ({}) REFERENCES {}({})", fk.column, fk.ref_table, fk.ref_column)));
+        up_sql.push_str(&format!("ALTER TABLE {} {};\n", table, clauses.join(", ")));
+    }
+
+    // Remaining ALTER actions run after the added columns so they can refer to them
+    for action in &spec.changes {
+        up_sql.push_str(&action.up_sql(table));
+    }
+
+    let down_sql = if spec.kind == SpecKind::Create {
+        format!("DROP TABLE {};\n", table)
+    } else if spec.columns.is_empty() && spec.changes.is_empty() {
+        "-- reverse changes here\n".to_string()
+    } else {
+        // Undo in reverse order; dropping the added columns also removes their foreign keys
+        let mut down_sql = String::new();
+        for action in spec.changes.iter().rev() {
+            down_sql.push_str(&action.down_sql(table));
+        }
+        for column in spec.columns.iter().rev() {
+            down_sql.push_str(&format!("ALTER TABLE {} DROP COLUMN IF EXISTS {};\n", table, column.name));
+        }
+        down_sql
+    };
+
+    (up_sql, down_sql)
+}
+
+// A spec file path, or the name of one in the project's src/database/migration_specs
+pub fn resolve_path(config: &Config, file: &str) -> PathBuf {
+    let path = PathBuf::from(file);
+    if path.exists() || path.components().count() > 1 {
+        return path;
+    }
+    let name = file.strip_suffix(".toml").unwrap_or(file);
+    config.project_dir.join(SPECS_DIR).join(format!("{}.toml", name))
+}
+
+pub fn load(path: &Path) -> Result<MigrationSpec, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let spec: MigrationSpec = toml::from_str(&content).map_err(|e| format!("Invalid migration spec {}: {}", path.display(), e))?;
+    if spec.table.trim().is_empty() {
+        return Err(format!("Migration spec {} has no table", path.display()));
+    }
+    if spec.kind == SpecKind::Create && spec.columns.is_empty() {
+        return Err(format!("Migration spec {} creates '{}' without any columns", path.display(), spec.table));
+    }
+    Ok(spec)
+}
+
+// The header names the spec by its path in `project_dir`
+pub fn save(project_dir: &Path, path: &Path, spec: &MigrationSpec) -> Result<(), String> {
+    let body = toml::to_string_pretty(spec).map_err(|e| format!("Failed to serialize migration spec: {}", e))?;
+    let content = format!("# Written by `blast migration`. Render it with `blast migration from-spec {}`\n\n{}", path.strip_prefix(project_dir).unwrap_or(path).display(), body);
+    crate::file_writer::write(path, &content)
+}
+
+// `blast migration from-spec <file>`: a new migration rendered from a recorded spec
+pub fn from_spec(config: &Config, file: &str) -> Result<(), String> {
+    let path = resolve_path(config, file);
+    let spec = load(&path)?;
+    let (up_sql, down_sql) = render(&spec);
+
+    let (up_file, down_file) = crate::database::create_migration_files(config, &spec.migration_name(), &up_sql, &down_sql, None)?;
+    logger::success(&format!("Migration for table '{}' created from {}", spec.table, path.display()))?;
+    logger::plain(&format!("Migration files created at:\n- {}\n- {}", up_file, down_file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, render, save, MigrationSpec};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn fixtures() -> Vec<PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/migration_specs");
+        let mut specs: Vec<PathBuf> = fs::read_dir(dir).unwrap().flatten().map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|ext| ext == "toml")).collect();
+        specs.sort();
+        specs
+    }
+
+    #[test]
+    fn fixtures_render_their_expected_sql() {
+        let specs = fixtures();
+        assert!(!specs.is_empty());
+
+        for path in specs {
+            let spec = load(&path).unwrap();
+            let (up_sql, down_sql) = render(&spec);
+            assert_eq!(up_sql, fs::read_to_string(path.with_extension("up.sql")).unwrap(), "up.sql of {}", path.display());
+            assert_eq!(down_sql, fs::read_to_string(path.with_extension("down.sql")).unwrap(), "down.sql of {}", path.display());
+            // Rendering is deterministic
+            assert_eq!(render(&spec), (up_sql, down_sql));
+        }
+    }
+
+    #[test]
+    fn saved_specs_load_unchanged() {
+        let dir = std::env::temp_dir().join(format!("blast-test-specs-{}", std::process::id()));
+        for path in fixtures() {
+            let spec = load(&path).unwrap();
+            let copy = dir.join(path.file_name().unwrap());
+            save(&dir, &copy, &spec).unwrap();
+            let reloaded: MigrationSpec = load(&copy).unwrap();
+            assert_eq!(reloaded, spec, "{}", path.display());
+        }
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn invalid_specs_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("blast-test-bad-specs-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let empty_table = dir.join("empty_table.toml");
+        fs::write(&empty_table, "kind = \"create\"\ntable = \"\"\n").unwrap();
+        assert!(load(&empty_table).unwrap_err().contains("has no table"));
+
+        let no_columns = dir.join("no_columns.toml");
+        fs::write(&no_columns, "kind = \"create\"\ntable = \"posts\"\n").unwrap();
+        assert!(load(&no_columns).unwrap_err().contains("without any columns"));
+
+        let unknown_action = dir.join("unknown_action.toml");
+        fs::write(&unknown_action, "kind = \"alter\"\ntable = \"posts\"\n\n[[changes]]\naction = \"truncate\"\n").unwrap();
+        assert!(load(&unknown_action).unwrap_err().contains("Invalid migration spec"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
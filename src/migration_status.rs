@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use prettytable::{format, Cell, Row, Table};
+use serde::Serialize;
+
+use crate::codegen_manifest::content_hash;
+use crate::configs::Config;
+use crate::database::migration_version;
+use crate::logger;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationRow {
+    pub connection: String,
+    pub version: String,
+    // Directory name without the version; empty for applied versions with no local directory
+    pub name: String,
+    pub applied: bool,
+    pub applied_on: Option<String>,
+    pub problem: Option<String>,
+}
+
+fn up_sql_hash(dir: &Path, migration: &str) -> Option<String> {
+    fs::read_to_string(dir.join(migration).join("up.sql")).ok().map(|content| content_hash(&content))
+}
+
+fn hash_key(dir: &str, migration: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), migration)
+}
+
+// After `diesel migration run`: remember what each migration's up.sql looked like when it was applied
+pub fn record_hashes(config: &Config, dir: &str, migrations: &[String]) {
+    let entries: Vec<(String, Option<String>)> = migrations.iter().map(|migration| (hash_key(dir, migration), up_sql_hash(&config.project_dir.join(dir), migration))).collect();
+    crate::state::record_migration_hashes(&config.project_dir, &entries);
+}
+
+// One row per local migration directory plus one per applied version without a directory, in version order.
+// `recorded` and `current` are up.sql hashes by directory name
+fn build_rows(connection: &str, local: &[String], applied: &BTreeMap<String, String>, recorded: &BTreeMap<String, String>, current: &BTreeMap<String, String>) -> Vec<MigrationRow> {
+    let mut rows: Vec<MigrationRow> = local
+        .iter()
+        .map(|migration| {
+            let version = migration_version(migration);
+            let applied_on = applied.get(&version).cloned();
+            let changed = applied_on.is_some() && matches!((recorded.get(migration), current.get(migration)), (Some(recorded), Some(current)) if recorded != current);
+            MigrationRow {
+                connection: connection.to_string(),
+                name: migration.split_once('_').map(|(_, name)| name.to_string()).unwrap_or_default(),
+                version,
+                applied: applied_on.is_some(),
+                applied_on,
+                problem: changed.then(|| "up.sql changed after it was applied".to_string()),
+            }
+        })
+        .collect();
+
+    for (version, run_on) in applied {
+        if !rows.iter().any(|row| &row.version == version) {
+            rows.push(MigrationRow {
+                connection: connection.to_string(),
+                version: version.clone(),
+                name: String::new(),
+                applied: true,
+                applied_on: Some(run_on.clone()),
+                problem: Some("applied, but there is no local directory".to_string()),
+            });
+        }
+    }
+    rows.sort_by(|a, b| a.version.cmp(&b.version));
+    rows
+}
+
+// Rows of one connection. Applied migrations blast never saw run get their current hash recorded, and
+// pending ones lose theirs, so a migration reverted and edited isn't reported once it runs again
+fn connection_rows(config: &Config, connection: &str) -> Result<Vec<MigrationRow>, String> {
+    let dir = crate::database::connection_migrations_dir(config, connection);
+    // No directory yet just means nothing local, which makes every applied version an anomaly worth seeing
+    let local = if config.project_dir.join(&dir).exists() { crate::database::local_migrations(config, connection)? } else { Vec::new() };
+    let applied = crate::database::applied_migrations(config, connection).map_err(|e| format!("Failed to read the applied migrations of the {} connection: {}", connection, e))?;
+
+    let hashes = crate::state::migration_hashes(&config.project_dir);
+    let mut recorded = BTreeMap::new();
+    let mut current = BTreeMap::new();
+    let mut updates = Vec::new();
+    for migration in &local {
+        let key = hash_key(&dir, migration);
+        let Some(hash) = up_sql_hash(&config.project_dir.join(&dir), migration) else { continue };
+        let is_applied = applied.contains_key(&migration_version(migration));
+        match hashes.get(&key) {
+            Some(previous) if is_applied => {
+                recorded.insert(migration.clone(), previous.clone());
+            }
+            Some(_) => updates.push((key, None)),
+            None if is_applied => updates.push((key, Some(hash.clone()))),
+            None => {}
+        }
+        current.insert(migration.clone(), hash);
+    }
+    crate::state::record_migration_hashes(&config.project_dir, &updates);
+
+    Ok(build_rows(connection, &local, &applied, &recorded, &current))
+}
+
+// `blast migration list` / `blast migrate --status [--connection <name|all>] [--output json] [--check]`
+pub fn run(config: &Config, connection: &str, output: Option<String>, check: bool) -> Result<(), String> {
+    let json = match output.as_deref() {
+        None | Some("table") => false,
+        Some("json") => true,
+        Some(other) => return Err(format!("Unknown output format '{}'. Use table or json", other)),
+    };
+
+    let connections = crate::database::resolve_connections(config, connection)?;
+    let mut rows = Vec::new();
+    for connection in &connections {
+        rows.extend(connection_rows(config, connection)?);
+    }
+    let pending = rows.iter().filter(|row| !row.applied).count();
+    let problems = rows.iter().filter(|row| row.problem.is_some()).count();
+
+    if json {
+        let content = serde_json::to_string_pretty(&rows).map_err(|e| e.to_string())?;
+        println!("{}", content);
+    } else if rows.is_empty() {
+        logger::plain("No migrations")?;
+    } else {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BOX_CHARS);
+        let mut header = vec![Cell::new("Version"), Cell::new("Name"), Cell::new("Status"), Cell::new("Applied on"), Cell::new("Notes")];
+        if connections.len() > 1 {
+            header.insert(0, Cell::new("Connection"));
+        }
+        table.add_row(Row::new(header));
+        for row in &rows {
+            let status = if row.applied { Cell::new("applied").style_spec("Fg") } else { Cell::new("pending").style_spec("Fy") };
+            let mut cells = vec![
+                Cell::new(&row.version),
+                Cell::new(&row.name),
+                status,
+                Cell::new(row.applied_on.as_deref().unwrap_or("")),
+                Cell::new(row.problem.as_deref().unwrap_or("")).style_spec("Fr"),
+            ];
+            if connections.len() > 1 {
+                cells.insert(0, Cell::new(&row.connection));
+            }
+            table.add_row(Row::new(cells));
+        }
+        table.printstd();
+        if problems > 0 {
+            logger::warning(&format!("{} migration(s) need attention", problems))?;
+        }
+        logger::plain(&format!("{} applied, {} pending", rows.len() - pending, pending))?;
+    }
+
+    if check && pending > 0 {
+        return Err(format!("{} pending migration(s)", pending));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_edited_and_deleted_migrations() {
+        let local = vec!["2024-01-01-000000_create_users".to_string(), "2024-02-01-000000_add_posts".to_string(), "2024-03-01-000000_add_tags".to_string()];
+        let applied = BTreeMap::from([
+            ("20240101000000".to_string(), "2024-01-02 10:00:00".to_string()),
+            ("20240201000000".to_string(), "2024-02-02 10:00:00".to_string()),
+            ("20231201000000".to_string(), "2023-12-02 10:00:00".to_string()),
+        ]);
+        let recorded = BTreeMap::from([("2024-01-01-000000_create_users".to_string(), "a".to_string()), ("2024-02-01-000000_add_posts".to_string(), "b".to_string())]);
+        let current = BTreeMap::from([("2024-01-01-000000_create_users".to_string(), "a".to_string()), ("2024-02-01-000000_add_posts".to_string(), "changed".to_string())]);
+
+        let rows = build_rows("default", &local, &applied, &recorded, &current);
+        assert_eq!(rows.iter().map(|row| row.version.as_str()).collect::<Vec<_>>(), ["20231201000000", "20240101000000", "20240201000000", "20240301000000"]);
+        assert!(rows[0].applied && rows[0].name.is_empty() && rows[0].problem.as_deref() == Some("applied, but there is no local directory"));
+        assert_eq!((rows[1].name.as_str(), rows[1].problem.as_deref()), ("create_users", None));
+        assert_eq!(rows[2].problem.as_deref(), Some("up.sql changed after it was applied"));
+        assert!(!rows[3].applied && rows[3].applied_on.is_none());
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use crate::file_writer;
+
+// blast owns everything between these markers in a mod.rs and rewrites it on every run
+const GENERATED_START: &str = "// <blast:generated>";
+const GENERATED_END: &str = "// </blast:generated>";
+
+// Rewrite the generated section of a mod.rs from `modules` (with `pub use x::*;` when `reexport`),
+// keeping whatever the user wrote around it. Files without markers get a section appended; module
+// declarations outside it that are now generated, or whose file no longer exists, are dropped
+pub fn sync(mod_path: &Path, modules: &[String], reexport: bool) -> Result<(), String> {
+    let content = file_writer::read(mod_path).unwrap_or_default();
+    let dir = mod_path.parent().unwrap_or(Path::new("."));
+
+    let mut section = vec![GENERATED_START.to_string()];
+    for module in modules {
+        section.push(format!("pub mod {};", module));
+        if reexport {
+            section.push(format!("pub use {}::*;", module));
+        }
+    }
+    section.push(GENERATED_END.to_string());
+
+    let updated = match (content.find(GENERATED_START), content.find(GENERATED_END)) {
+        (Some(start), Some(end)) if start < end => format!("{}{}{}", &content[..start], section.join("\n"), &content[end + GENERATED_END.len()..]),
+        _ => {
+            // Declarations whose module file is gone would no longer compile
+            let dangling: Vec<String> = content
+                .lines()
+                .filter_map(declared_module)
+                .filter(|module| !(file_writer::exists(dir.join(format!("{}.rs", module))) || dir.join(module).is_dir()))
+                .map(str::to_string)
+                .collect();
+            let kept: Vec<&str> = content
+                .lines()
+                .filter(|line| !referenced_module(line).map(|module| modules.iter().chain(&dangling).any(|name| name == module)).unwrap_or(false))
+                .collect();
+            let user_part = kept.join("\n");
+            if user_part.trim().is_empty() {
+                format!("{}\n", section.join("\n"))
+            } else {
+                format!("{}\n\n{}\n", user_part.trim_end(), section.join("\n"))
+            }
+        }
+    };
+
+    file_writer::write(mod_path, &updated)
+}
+
+// Whether sync would drop a `pub mod x;` line: always inside the generated section, and outside it
+// only while the file has no markers yet. Everything else belongs to the user
+pub fn owns_declaration(content: &str, module: &str) -> bool {
+    match (content.find(GENERATED_START), content.find(GENERATED_END)) {
+        (Some(start), Some(end)) if start < end => content[start..end].lines().any(|line| declared_module(line) == Some(module)),
+        _ => true,
+    }
+}
+
+fn declared_module(line: &str) -> Option<&str> {
+    line.trim().strip_prefix("pub mod ").and_then(|rest| rest.strip_suffix(';')).map(str::trim)
+}
+
+// Module a `pub mod x;` or `pub use x::...;` line refers to
+fn referenced_module(line: &str) -> Option<&str> {
+    declared_module(line).or_else(|| line.trim().strip_prefix("pub use ").filter(|rest| rest.ends_with(';')).and_then(|rest| rest.split("::").next()).map(str::trim))
+}
@@ -1,48 +1,206 @@
+use crate::codegen_ignore::Generator;
+use crate::codegen_manifest::CodegenManifest;
+use crate::codegen_snapshot::SchemaChanges;
 use crate::configs::Config;
+use crate::file_writer;
+use crate::mod_file;
 use crate::progress::ProgressManager;
+use crate::table_selection::TableSelection;
 use regex::Regex;
 use std::collections::HashMap;
-use std::fs;
 use std::io;
-use std::path::Path;
 
 // Structure to hold column information
 #[derive(Debug, Clone)]
-struct ColumnInfo {
-    name: String,
-    column_type: String,
-    nullable: bool,
+pub struct ColumnInfo {
+    pub name: String,
+    pub column_type: String,
+    pub nullable: bool,
 }
 
 // Structure to hold relationships
 #[derive(Debug, Clone)]
-struct RelationshipInfo {
-    source_table: String,
-    source_column: String,
-    target_table: String,
-    target_column: String,
+pub struct RelationshipInfo {
+    pub source_table: String,
+    pub source_column: String,
+    pub target_table: String,
+    pub target_column: String,
+    // The foreign key column is Nullable, so a row may have no parent
+    pub nullable: bool,
+}
+
+// Diesel API the generated models are written against, from [codegen.models] async_backend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModelBackend {
+    Blocking,
+    DieselAsync,
+}
+
+// Where establish_connection and the async get_connection live unless [codegen.models] connection_module says otherwise
+pub const DEFAULT_CONNECTION_MODULE: &str = "crate::database::db";
+
+// db.rs section holding the async pool; the markers let blast add and remove it
+const ASYNC_POOL_START: &str = "// --- blast: diesel-async pool (generated, do not edit) ---";
+const ASYNC_POOL_END: &str = "// --- end blast: diesel-async pool ---";
+
+const ASYNC_POOL_SOURCE: &str = r#"use diesel_async::pooled_connection::bb8::{Pool, PooledConnection};
+use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use diesel_async::AsyncPgConnection;
+
+static ASYNC_POOL: rocket::tokio::sync::OnceCell<Pool<AsyncPgConnection>> = rocket::tokio::sync::OnceCell::const_new();
+
+// Pooled async connection for generated models; the pool is created on first use
+pub async fn get_connection() -> Result<PooledConnection<'static, AsyncPgConnection>, diesel::result::Error> {
+    let pool = ASYNC_POOL
+        .get_or_try_init(|| async {
+            dotenv::dotenv().ok();
+            let database_url = std::env::var("DATABASE_URL").map_err(|e| pool_error(format!("DATABASE_URL: {}", e)))?;
+            Pool::builder().build(AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url)).await.map_err(|e| pool_error(e.to_string()))
+        })
+        .await?;
+
+    pool.get().await.map_err(|e| pool_error(e.to_string()))
+}
+
+fn pool_error(message: String) -> diesel::result::Error {
+    diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UnableToSendCommand, Box::new(message))
+}
+"#;
+
+impl ModelBackend {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        match config.codegen().models.async_backend.as_deref() {
+            None | Some("blocking") => Ok(ModelBackend::Blocking),
+            Some("diesel-async") => Ok(ModelBackend::DieselAsync),
+            Some(other) => Err(format!("Unknown [codegen.models] async_backend '{}' (expected \"diesel-async\" or \"blocking\")", other)),
+        }
+    }
+
+    fn db_import(&self, module: &str) -> String {
+        match self {
+            ModelBackend::Blocking => format!("use {}::establish_connection;", module),
+            ModelBackend::DieselAsync => format!("use {}::get_connection;", module),
+        }
+    }
+
+    fn diesel_imports(&self) -> &'static str {
+        match self {
+            ModelBackend::Blocking => "use diesel::prelude::*;\nuse diesel::result::Error;\nuse diesel::Connection;",
+            ModelBackend::DieselAsync => "use diesel::prelude::*;\nuse diesel::result::Error;\nuse diesel_async::scoped_futures::ScopedFutureExt;\nuse diesel_async::{AsyncConnection, RunQueryDsl};",
+        }
+    }
+
+    fn connection(&self, entity: &str) -> String {
+        match self {
+            ModelBackend::Blocking => "let mut conn = establish_connection();".to_string(),
+            ModelBackend::DieselAsync => format!("let mut conn = get_connection().await.map_err(|e| ModelError::new({}, e))?;", entity),
+        }
+    }
+
+    fn await_suffix(&self) -> &'static str {
+        match self {
+            ModelBackend::Blocking => "",
+            ModelBackend::DieselAsync => ".await",
+        }
+    }
+
+    fn transaction_open(&self) -> &'static str {
+        match self {
+            ModelBackend::Blocking => "conn.transaction::<_, Error, _>(|conn| {",
+            ModelBackend::DieselAsync => "conn.transaction::<_, Error, _>(|conn| async move {",
+        }
+    }
+
+    fn transaction_close(&self) -> &'static str {
+        match self {
+            ModelBackend::Blocking => ")",
+            ModelBackend::DieselAsync => ".scope_boxed())\n        .await",
+        }
+    }
+}
+
+// Add or remove the async pool in db.rs and the diesel-async dependency so the project matches the backend
+fn sync_backend_support(config: &Config, backend: ModelBackend) -> Result<(), String> {
+    let db_path = "src/database/db.rs";
+    let db_content = file_writer::read(config.project_dir.join(db_path)).map_err(|e| format!("Failed to read {}: {}", db_path, e))?;
+    let has_pool = db_content.contains(ASYNC_POOL_START);
+
+    let new_db_content = match backend {
+        ModelBackend::DieselAsync if !has_pool => {
+            let block = format!("{}\n{}{}\n\n", ASYNC_POOL_START, ASYNC_POOL_SOURCE, ASYNC_POOL_END);
+            // Keep it above the marker schema generation rewrites from
+            match db_content.find("// Additional connection functions") {
+                Some(index) => format!("{}{}{}", &db_content[..index], block, &db_content[index..]),
+                None => format!("{}\n{}", db_content.trim_end(), block),
+            }
+        }
+        ModelBackend::Blocking if has_pool => {
+            let start = db_content.find(ASYNC_POOL_START).unwrap_or_default();
+            let end = db_content.find(ASYNC_POOL_END).map(|index| index + ASYNC_POOL_END.len()).unwrap_or(db_content.len());
+            format!("{}{}", &db_content[..start], db_content[end..].trim_start_matches('\n'))
+        }
+        _ => return Ok(()),
+    };
+    file_writer::write(config.project_dir.join(db_path), &new_db_content)?;
+
+    let cargo_path = config.project_dir.join("Cargo.toml");
+    let cargo_content = file_writer::read(&cargo_path).map_err(|e| format!("Failed to read Cargo.toml: {}", e))?;
+    let mut cargo_doc = cargo_content.parse::<toml_edit::DocumentMut>().map_err(|e| format!("Failed to parse Cargo.toml: {}", e))?;
+    let dependencies = cargo_doc["dependencies"].as_table_mut().ok_or("Cargo.toml has no [dependencies] table")?;
+
+    match backend {
+        ModelBackend::DieselAsync => {
+            if !dependencies.contains_key("diesel-async") {
+                let mut features = toml_edit::Array::new();
+                features.push("postgres");
+                features.push("bb8");
+                let mut dependency = toml_edit::InlineTable::new();
+                dependency.insert("version", "0.5".into());
+                dependency.insert("features", toml_edit::Value::Array(features));
+                dependencies.insert("diesel-async", toml_edit::value(dependency));
+            }
+            crate::logger::info("Added the diesel-async pool to src/database/db.rs and diesel-async to Cargo.toml")?;
+        }
+        ModelBackend::Blocking => {
+            dependencies.remove("diesel-async");
+            crate::logger::info("Removed the diesel-async pool from src/database/db.rs and diesel-async from Cargo.toml")?;
+        }
+    }
+
+    file_writer::write(&cargo_path, &cargo_doc.to_string())
 }
 
 // Structure to hold table information
 #[derive(Debug)]
-struct TableInfo {
-    name: String,
-    columns: Vec<ColumnInfo>,
+pub struct TableInfo {
+    pub name: String,
+    pub primary_key: Vec<String>,
+    pub columns: Vec<ColumnInfo>,
 }
 
-fn load_schema_table_info(schema_path: &str) -> io::Result<Vec<TableInfo>> {
-    let content = fs::read_to_string(schema_path)?;
+pub fn load_schema_table_info(config: &Config, schema_path: &str) -> io::Result<Vec<TableInfo>> {
+    let content = crate::schema_patch::read_schema(config, schema_path)?;
+    let tables = parse_schema_tables(&content);
+
+    if tables.is_empty() {
+        crate::logger::warning(&format!("No tables found in schema file for models at {}", schema_path)).unwrap_or_default();
+    }
 
+    Ok(tables)
+}
+
+// Parse table! blocks from schema.rs content into tables and columns
+pub fn parse_schema_tables(content: &str) -> Vec<TableInfo> {
     // Extract table declarations
-    let table_re = Regex::new(r"table!\s*\{\s*([A-Za-z0-9_]+)\s*\([^)]+\)\s*\{([^}]+)\}").unwrap();
+    let table_re = Regex::new(r"table!\s*\{\s*([A-Za-z0-9_]+)\s*\(([^)]+)\)\s*\{([^}]+)\}").unwrap();
     let column_re = Regex::new(r"([A-Za-z0-9_]+)\s*->\s*([^,]+)").unwrap();
-    let nullable_re = Regex::new(r"Nullable<([^>]+)>").unwrap();
 
     let mut tables = Vec::new();
 
-    for table_cap in table_re.captures_iter(&content) {
+    for table_cap in table_re.captures_iter(content) {
         let table_name = table_cap.get(1).unwrap().as_str().to_string();
-        let columns_section = table_cap.get(2).unwrap().as_str();
+        let primary_key = table_cap.get(2).unwrap().as_str().split(',').map(|column| column.trim().to_string()).collect();
+        let columns_section = table_cap.get(3).unwrap().as_str();
 
         let mut columns = Vec::new();
 
@@ -50,19 +208,10 @@ fn load_schema_table_info(schema_path: &str) -> io::Result<Vec<TableInfo>> {
             let column_name = column_cap.get(1).unwrap().as_str().to_string();
             let column_type = column_cap.get(2).unwrap().as_str().trim().to_string();
 
-            // Check if column is nullable
-            let nullable = column_type.contains("Nullable");
-
-            // Extract the inner type if nullable
-            let clean_type = if nullable {
-                if let Some(inner_cap) = nullable_re.captures(&column_type) {
-                    inner_cap.get(1).unwrap().as_str().trim().to_string()
-                } else {
-                    column_type.clone()
-                }
-            } else {
-                column_type.clone()
-            };
+            // Only an outer Nullable makes the column nullable; Array<Nullable<Text>> is a non-null array
+            let inner_type = column_type.strip_prefix("Nullable<").and_then(|inner| inner.strip_suffix('>'));
+            let nullable = inner_type.is_some();
+            let clean_type = inner_type.map(|inner| inner.trim().to_string()).unwrap_or_else(|| column_type.clone());
 
             columns.push(ColumnInfo {
                 name: column_name,
@@ -71,24 +220,20 @@ fn load_schema_table_info(schema_path: &str) -> io::Result<Vec<TableInfo>> {
             });
         }
 
-        tables.push(TableInfo { name: table_name, columns });
+        tables.push(TableInfo { name: table_name, primary_key, columns });
     }
 
-    if tables.is_empty() {
-        crate::logger::warning(&format!("No tables found in schema file for models at {}", schema_path)).unwrap_or_default();
-    }
-
-    Ok(tables)
+    tables
 }
 
-fn load_schema_table_names(schema_path: &str) -> io::Result<Vec<String>> {
-    let tables = load_schema_table_info(schema_path)?;
+fn load_schema_table_names(config: &Config, schema_path: &str) -> io::Result<Vec<String>> {
+    let tables = load_schema_table_info(config, schema_path)?;
     Ok(tables.into_iter().map(|t| t.name).collect())
 }
 
 // Parse schema for relationships (joinable! macros)
-fn load_schema_relationships(schema_path: &str) -> io::Result<Vec<RelationshipInfo>> {
-    let content = fs::read_to_string(schema_path)?;
+pub fn load_schema_relationships(config: &Config, schema_path: &str) -> io::Result<Vec<RelationshipInfo>> {
+    let content = crate::schema_patch::read_schema(config, schema_path)?;
 
     // We'll use this to track which relationships we've already detected
     // to prevent duplicates from different detection methods
@@ -110,12 +255,13 @@ fn load_schema_relationships(schema_path: &str) -> io::Result<Vec<RelationshipIn
                 source_column,
                 target_table,
                 target_column: "id".to_string(), // Default assumption for joinable! macros
+                nullable: false,
             },
         );
     }
 
     // Also check for columns with _id suffix as potential foreign keys
-    let tables = load_schema_table_info(schema_path)?;
+    let tables = load_schema_table_info(config, schema_path)?;
     let table_map: HashMap<String, TableInfo> = tables.into_iter().map(|t| (t.name.clone(), t)).collect();
 
     for (table_name, table_info) in &table_map {
@@ -142,6 +288,7 @@ fn load_schema_relationships(schema_path: &str) -> io::Result<Vec<RelationshipIn
                                 source_column: column.name.clone(),
                                 target_table,
                                 target_column: "id".to_string(),
+                                nullable: column.nullable,
                             },
                         );
                     }
@@ -150,13 +297,33 @@ fn load_schema_relationships(schema_path: &str) -> io::Result<Vec<RelationshipIn
         }
     }
 
-    // Convert the HashMap values to a Vec
-    let relationships: Vec<RelationshipInfo> = relationship_map.into_values().collect();
+    // Keys only the migrations know about, such as parent_id referencing its own table
+    for (source_table, source_column, target_table) in crate::database::migration_foreign_keys(config) {
+        if table_map.contains_key(&source_table) && table_map.contains_key(&target_table) {
+            relationship_map.entry((source_table.clone(), source_column.clone())).or_insert(RelationshipInfo {
+                source_table,
+                source_column,
+                target_table,
+                target_column: "id".to_string(),
+                nullable: false,
+            });
+        }
+    }
+
+    // joinable! doesn't say whether the key is nullable; the column does
+    for relationship in relationship_map.values_mut() {
+        let column = table_map.get(&relationship.source_table).and_then(|table| table.columns.iter().find(|column| column.name == relationship.source_column));
+        relationship.nullable = column.is_some_and(|column| column.nullable);
+    }
+
+    // Convert the HashMap values to a Vec, in a stable order so regenerating doesn't reorder methods
+    let mut relationships: Vec<RelationshipInfo> = relationship_map.into_values().collect();
+    relationships.sort_by(|a, b| (&a.source_table, &a.source_column).cmp(&(&b.source_table, &b.source_column)));
 
     Ok(relationships)
 }
 
-fn to_pascal(s: &str) -> String {
+pub fn to_pascal(s: &str) -> String {
     s.split('_')
         .map(|w| {
             let mut chars = w.chars();
@@ -168,7 +335,7 @@ fn to_pascal(s: &str) -> String {
         .collect()
 }
 
-fn singular(table_name: &str) -> String {
+pub fn singular(table_name: &str) -> String {
     if table_name.ends_with('s') {
         table_name[..table_name.len() - 1].to_string()
     } else {
@@ -176,12 +343,378 @@ fn singular(table_name: &str) -> String {
     }
 }
 
-// Generate methods for boolean fields
-fn generate_bool_methods(table: &TableInfo, singular_name: &str) -> String {
+// Variant of the generated Entity enum for a table, as used in the model templates
+fn entity_path(table_name: &str) -> String {
+    format!("Entity::{}", to_pascal(table_name))
+}
+
+// Typed errors for the generated models, kept in their own file next to the user's meltdown code
+fn write_meltdown_extension(config: &Config, tables: &[TableInfo], schema_path: &str, manifest: &mut CodegenManifest) -> Result<(), String> {
+    let meltdown_mod = ["src/meltdown/mod.rs", "src/meltdown.rs"].into_iter().map(|path| config.project_dir.join(path)).find(|path| path.exists()).ok_or("No src/meltdown module found; generated models report errors through it")?;
+
+    let variants: String = tables.iter().map(|table| format!("    {},\n", to_pascal(&table.name))).collect();
+    let names: String = tables.iter().map(|table| format!("            Entity::{} => \"{}\",\n", to_pascal(&table.name), table.name)).collect();
+
+    file_writer::create_dir_all(config.project_dir.join("src/meltdown"))?;
+    let path = "src/meltdown/generated.rs";
+    let source = crate::codegen_format::render(&config.project_dir, path, &MELTDOWN_EXTENSION_SOURCE.replace("{variants}", &variants).replace("{names}", &names), schema_path, None)?;
+    manifest.write(path, &source, None)?;
+
+    // Only the re-export is added to the user's module; everything else there is left alone
+    let mut mod_content = file_writer::read(&meltdown_mod).map_err(|e| format!("Failed to read {}: {}", meltdown_mod.display(), e))?;
+    if !mod_content.contains("pub mod generated;") {
+        mod_content = format!("{}\n\n// Typed model errors, regenerated by `blast gen models`\npub mod generated;\npub use generated::*;\n", mod_content.trim_end());
+        file_writer::write(&meltdown_mod, &mod_content)?;
+    }
+
+    Ok(())
+}
+
+// Where cached models ([codegen.models.<table>] cache) find their cache type
+const MODEL_CACHE_MODULE: &str = "crate::services::model_cache";
+
+// The cache type behind [codegen.models.<table>] cache, written once for every cached table to share
+fn write_model_cache(config: &Config, schema_path: &str, manifest: &mut CodegenManifest) -> Result<(), String> {
+    let services_mod = ["src/services/mod.rs", "src/services.rs"]
+        .into_iter()
+        .map(|path| config.project_dir.join(path))
+        .find(|path| path.exists())
+        .ok_or("No src/services module found; cached models ([codegen.models.<table>] cache) keep their cache in src/services/model_cache.rs")?;
+    file_writer::create_dir_all(config.project_dir.join("src/services"))?;
+    let path = "src/services/model_cache.rs";
+    let source = crate::codegen_format::render(&config.project_dir, path, MODEL_CACHE_SOURCE, schema_path, None)?;
+    manifest.write(path, &source, None)?;
+
+    let mut mod_content = file_writer::read(&services_mod).map_err(|e| format!("Failed to read {}: {}", services_mod.display(), e))?;
+    if !mod_content.contains("pub mod model_cache;") {
+        mod_content = format!("{}\n\n// Cache of the models with [codegen.models.<table>] cache, regenerated by `blast gen models`\npub mod model_cache;\n", mod_content.trim_end());
+        file_writer::write(&services_mod, &mod_content)?;
+    }
+
+    // Hit and miss counts reach /metrics only through an observability module that knows about the cache
+    let observability = ["src/middleware/observability.rs", "src/observability.rs"].into_iter().find_map(|path| file_writer::read(config.project_dir.join(path)).ok());
+    if observability.is_some_and(|source| !source.contains("model_cache::metrics()")) {
+        crate::logger::plain("Run `blast gen observability --force` to add the model cache hit/miss counts to /metrics")?;
+    }
+    Ok(())
+}
+
+const MODEL_CACHE_SOURCE: &str = r##"// Process-local cache for the models with [codegen.models.<table>] cache. Each cached model declares a
+// <TABLE>_CACHE static that get_by_id/get_all read and its writes invalidate. Call invalidate(id) or clear()
+// on it after changing the table some other way (raw SQL, another model, another process).
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, Once, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+pub struct ModelCache<T> {
+    table: &'static str,
+    ttl: Duration,
+    rows: RwLock<BTreeMap<i32, (Instant, T)>>,
+    all: RwLock<Option<(Instant, Vec<T>)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    registered: Once,
+}
+
+// What /metrics reports for each cache, once it has been used
+trait Counts: Sync {
+    fn table(&self) -> &'static str;
+    fn counts(&self) -> (u64, u64);
+}
+
+impl<T: Send + Sync + 'static> Counts for ModelCache<T> {
+    fn table(&self) -> &'static str {
+        self.table
+    }
+
+    fn counts(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+}
+
+fn registry() -> &'static Mutex<Vec<&'static dyn Counts>> {
+    static REGISTRY: OnceLock<Mutex<Vec<&'static dyn Counts>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+impl<T: Clone + Send + Sync + 'static> ModelCache<T> {
+    pub const fn new(table: &'static str, ttl_secs: u64) -> Self {
+        ModelCache {
+            table,
+            ttl: Duration::from_secs(ttl_secs),
+            rows: RwLock::new(BTreeMap::new()),
+            all: RwLock::new(None),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            registered: Once::new(),
+        }
+    }
+
+    fn record<V>(&'static self, value: Option<V>) -> Option<V> {
+        self.registered.call_once(|| registry().lock().unwrap_or_else(|e| e.into_inner()).push(self));
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub fn get(&'static self, id: i32) -> Option<T> {
+        let rows = self.rows.read().unwrap_or_else(|e| e.into_inner());
+        self.record(rows.get(&id).filter(|(stored, _)| stored.elapsed() < self.ttl).map(|(_, row)| row.clone()))
+    }
+
+    pub fn put(&self, id: i32, row: T) {
+        self.rows.write().unwrap_or_else(|e| e.into_inner()).insert(id, (Instant::now(), row));
+    }
+
+    pub fn get_all(&'static self) -> Option<Vec<T>> {
+        let all = self.all.read().unwrap_or_else(|e| e.into_inner());
+        self.record(all.as_ref().filter(|(stored, _)| stored.elapsed() < self.ttl).map(|(_, rows)| rows.clone()))
+    }
+
+    pub fn put_all(&self, rows: Vec<T>) {
+        *self.all.write().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), rows));
+    }
+
+    // One row changed or went away: drop it and the cached get_all
+    pub fn invalidate(&self, id: i32) {
+        self.rows.write().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        self.invalidate_all();
+    }
+
+    // A row was added: only the cached get_all is stale
+    pub fn invalidate_all(&self) {
+        *self.all.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    pub fn clear(&self) {
+        self.rows.write().unwrap_or_else(|e| e.into_inner()).clear();
+        self.invalidate_all();
+    }
+}
+
+// Hit and miss counters in Prometheus text format, for the observability module's /metrics
+pub fn metrics() -> String {
+    let caches = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::from("# HELP model_cache_hits_total Model lookups answered from the cache, by table\n# TYPE model_cache_hits_total counter\n");
+    for cache in caches.iter() {
+        out.push_str(&format!("model_cache_hits_total{{table=\"{}\"}} {}\n", cache.table(), cache.counts().0));
+    }
+    out.push_str("# HELP model_cache_misses_total Model lookups that went to the database, by table\n# TYPE model_cache_misses_total counter\n");
+    for cache in caches.iter() {
+        out.push_str(&format!("model_cache_misses_total{{table=\"{}\"}} {}\n", cache.table(), cache.counts().1));
+    }
+    out
+}
+"##;
+
+// What a cached table's model adds to the plain one; all empty for tables without [codegen.models.<table>] cache,
+// which keeps their generated code unchanged
+#[derive(Default)]
+struct CacheHooks {
+    declaration: String,
+    all_lookup: String,
+    all_store: String,
+    id_lookup: String,
+    id_store: String,
+    created: String,
+    // Also used by the bool setters, which have an `id` in scope too
+    changed: String,
+}
+
+impl CacheHooks {
+    fn new(table_name: &str, struct_name: &str, ttl: Option<u64>) -> Self {
+        let Some(ttl) = ttl else {
+            return CacheHooks::default();
+        };
+        let cache = format!("{}_CACHE", table_name.to_uppercase());
+        CacheHooks {
+            declaration: format!(
+                "\n// get_by_id/get_all results, kept {ttl}s ([codegen.models.{table}] cache). Writes through this model invalidate them;\n// call {cache}.invalidate(id) or {cache}.clear() after changing {table} another way\npub static {cache}: {module}::ModelCache<{struct_name}> = {module}::ModelCache::new(\"{table}\", {ttl});\n",
+                table = table_name,
+                module = MODEL_CACHE_MODULE,
+            ),
+            all_lookup: format!("if let Some(rows) = {}.get_all() {{\n            return Ok(rows);\n        }}\n        ", cache),
+            all_store: format!("\n            .inspect(|rows| {}.put_all(rows.clone()))", cache),
+            id_lookup: format!("if let Some(row) = {}.get(id) {{\n            return Ok(row);\n        }}\n        ", cache),
+            id_store: format!("\n            .inspect(|row| {}.put(id, row.clone()))", cache),
+            created: format!("\n        .inspect(|_| {}.invalidate_all())", cache),
+            changed: format!("\n        .inspect(|_| {}.invalidate(id))", cache),
+        }
+    }
+}
+
+const MELTDOWN_EXTENSION_SOURCE: &str = r#"// Put your own error handling in the rest of the meltdown module.
+use super::MeltDown;
+use diesel::result::{DatabaseErrorKind, Error};
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use std::fmt;
+
+// Tables known to the generated models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Entity {
+{variants}}
+
+impl Entity {
+    pub fn table_name(&self) -> &'static str {
+        match self {
+{names}        }
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.table_name())
+    }
+}
+
+// Error returned by the generated models; match on it instead of parsing MeltDown messages
+#[derive(Debug)]
+pub enum ModelError {
+    NotFound { entity: Entity, id: Option<i32> },
+    Conflict { entity: Entity, constraint: Option<String> },
+    // CHECK constraint or Validate failure
+    Invalid { entity: Entity, message: String },
+    Database { entity: Entity, source: Error, context: Vec<(String, String)> },
+}
+
+impl ModelError {
+    pub fn new(entity: Entity, error: Error) -> Self {
+        match error {
+            Error::NotFound => ModelError::NotFound { entity, id: None },
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => ModelError::Conflict { entity, constraint: info.constraint_name().map(str::to_string) },
+            Error::DatabaseError(DatabaseErrorKind::CheckViolation, info) => ModelError::Invalid { entity, message: info.message().to_string() },
+            source => ModelError::Database { entity, source, context: Vec::new() },
+        }
+    }
+
+    // Same shape as MeltDown::with_context; an "id" fills in NotFound, the rest is kept for the MeltDown conversion
+    pub fn with_context(mut self, key: &str, value: impl Into<String>) -> Self {
+        let value = value.into();
+        match &mut self {
+            ModelError::NotFound { id, .. } if key == "id" => *id = value.parse().ok(),
+            ModelError::Database { context, .. } => context.push((key.to_string(), value)),
+            _ => {}
+        }
+        self
+    }
+
+    pub fn entity(&self) -> Entity {
+        match self {
+            ModelError::NotFound { entity, .. } | ModelError::Conflict { entity, .. } | ModelError::Invalid { entity, .. } | ModelError::Database { entity, .. } => *entity,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            ModelError::NotFound { .. } => Status::NotFound,
+            ModelError::Conflict { .. } => Status::Conflict,
+            ModelError::Invalid { .. } => Status::UnprocessableEntity,
+            ModelError::Database { .. } => Status::InternalServerError,
+        }
+    }
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::NotFound { entity, id: Some(id) } => write!(f, "{} {} not found", entity, id),
+            ModelError::NotFound { entity, id: None } => write!(f, "{} not found", entity),
+            ModelError::Conflict { entity, constraint: Some(constraint) } => write!(f, "{} conflicts with an existing row ({})", entity, constraint),
+            ModelError::Conflict { entity, constraint: None } => write!(f, "{} conflicts with an existing row", entity),
+            ModelError::Invalid { entity, message } => write!(f, "invalid {}: {}", entity, message),
+            ModelError::Database { entity, source, .. } => write!(f, "{}: {}", entity, source),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+// Direction for the generated <Struct>Query order_by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+// Existing code that returns MeltDown keeps working with `?`
+impl From<ModelError> for MeltDown {
+    fn from(error: ModelError) -> Self {
+        let entity = error.entity().table_name();
+        match error {
+            ModelError::NotFound { id, .. } => MeltDown::from(Error::NotFound).with_context("entity", entity).with_context("id", id.map(|id| id.to_string()).unwrap_or_default()),
+            ModelError::Conflict { constraint, .. } => {
+                let message = constraint.clone().unwrap_or_else(|| "unique violation".to_string());
+                MeltDown::from(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, Box::new(message))).with_context("entity", entity).with_context("constraint", constraint.unwrap_or_default())
+            }
+            ModelError::Invalid { message, .. } => MeltDown::from(Error::DatabaseError(DatabaseErrorKind::CheckViolation, Box::new(message))).with_context("entity", entity),
+            ModelError::Database { source, context, .. } => context.into_iter().fold(MeltDown::from(source).with_context("entity", entity), |meltdown, (key, value)| meltdown.with_context(&key, value)),
+        }
+    }
+}
+
+// Returning a ModelError from a route hands 404/409/422/500 to the matching registered catcher
+impl<'r> Responder<'r, 'static> for ModelError {
+    fn respond_to(self, _request: &'r Request<'_>) -> response::Result<'static> {
+        if let ModelError::Database { .. } = self {
+            eprintln!("{}", self);
+        }
+        Err(self.status())
+    }
+}
+"#;
+
+// How a created_at/updated_at column stores time: epoch seconds, or diesel's Timestamp/Timestamptz. It decides the
+// Rust type generated methods compare and set the column with
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimestampKind {
+    Epoch,
+    Naive,
+    Utc,
+}
+
+impl TimestampKind {
+    fn of(table: &TableInfo, column_name: &str) -> Option<TimestampKind> {
+        match table.columns.iter().find(|column| column.name == column_name)?.column_type.as_str() {
+            "Int8" => Some(TimestampKind::Epoch),
+            "Timestamp" => Some(TimestampKind::Naive),
+            "Timestamptz" => Some(TimestampKind::Utc),
+            _ => None,
+        }
+    }
+
+    fn rust_type(self) -> &'static str {
+        match self {
+            TimestampKind::Epoch => "i64",
+            TimestampKind::Naive => "chrono::NaiveDateTime",
+            TimestampKind::Utc => "chrono::DateTime<Utc>",
+        }
+    }
+
+    fn now(self) -> &'static str {
+        match self {
+            TimestampKind::Epoch => "Utc::now().timestamp()",
+            TimestampKind::Naive => "Utc::now().naive_utc()",
+            TimestampKind::Utc => "Utc::now()",
+        }
+    }
+}
+
+// Generate methods for boolean fields. `invalidate` follows each setter's result (see CacheHooks)
+fn generate_bool_methods(table: &TableInfo, singular_name: &str, invalidate: &str, backend: ModelBackend) -> String {
     let mut bool_methods = String::new();
+    let entity = entity_path(&table.name);
+    // Setting a flag also touches updated_at, when the table has one
+    let (touch, assignment) = match TimestampKind::of(table, "updated_at") {
+        Some(kind) => (format!("let current_timestamp = {};\n        ", kind.now()), "({1}_dsl::{0}.eq(value), {1}_dsl::updated_at.eq(current_timestamp))"),
+        None => (String::new(), "{1}_dsl::{0}.eq(value)"),
+    };
 
+    // Nullable booleans have no single true/false to return; filter them with <Struct>Query instead
     for column in &table.columns {
-        if column.column_type == "Bool" {
+        if column.column_type == "Bool" && !column.nullable {
             let column_name = &column.name;
 
             // Generate is_VALUE getter
@@ -191,30 +724,39 @@ fn generate_bool_methods(table: &TableInfo, singular_name: &str) -> String {
         self.{0}
     }}
 
-    pub async fn set_{0}(&mut self, value: bool) -> Result<Self, MeltDown> {{
-        let mut conn = establish_connection();
-        let current_timestamp = Utc::now().timestamp();
+    pub async fn set_{0}(&mut self, value: bool) -> Result<Self, ModelError> {{
+        {conn}
+        {touch}let id = self.id;
         
-        conn.transaction(|conn| {{
-            let updated = diesel::update({1}_dsl::{2}.filter({1}_dsl::id.eq(self.id)))
-                .set(({1}_dsl::{0}.eq(value), {1}_dsl::updated_at.eq(current_timestamp)))
-                .get_result::<Self>(conn)
-                .map_err(|_| Error::RollbackTransaction)?;
+        {tx_open}
+            let updated = diesel::update({1}_dsl::{2}.filter({1}_dsl::id.eq(id)))
+                .set({assignment})
+                .get_result::<Self>(conn){aw}?;
                 
             Ok(updated)
-        }})
-        .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "set_{0}").with_context("id", self.id.to_string()))
+        }}{tx_close}
+        .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "set_{0}").with_context("id", id.to_string())){invalidate}
     }}
 
-    pub async fn set_{0}_true(&mut self) -> Result<Self, MeltDown> {{
+    pub async fn set_{0}_true(&mut self) -> Result<Self, ModelError> {{
         self.set_{0}(true).await
     }}
 
-    pub async fn set_{0}_false(&mut self) -> Result<Self, MeltDown> {{
+    pub async fn set_{0}_false(&mut self) -> Result<Self, ModelError> {{
         self.set_{0}(false).await
     }}
 "#,
-                column_name, singular_name, table.name
+                column_name,
+                singular_name,
+                table.name,
+                conn = backend.connection(&entity),
+                entity = entity,
+                aw = backend.await_suffix(),
+                tx_open = backend.transaction_open(),
+                tx_close = backend.transaction_close(),
+                touch = touch,
+                invalidate = invalidate,
+                assignment = assignment.replace("{0}", column_name).replace("{1}", singular_name)
             ));
         }
     }
@@ -223,84 +765,91 @@ fn generate_bool_methods(table: &TableInfo, singular_name: &str) -> String {
 }
 
 // Generate methods for timestamp fields
-fn generate_timestamp_methods(table: &TableInfo, singular_name: &str) -> String {
+fn generate_timestamp_methods(table: &TableInfo, singular_name: &str, backend: ModelBackend) -> String {
     let mut timestamp_methods = String::new();
+    let entity = entity_path(&table.name);
 
-    let has_created_at = table.columns.iter().any(|c| c.name == "created_at" && (c.column_type == "Int8" || c.column_type == "Timestamp"));
-
-    let has_updated_at = table.columns.iter().any(|c| c.name == "updated_at" && (c.column_type == "Int8" || c.column_type == "Timestamp"));
-
-    if has_created_at {
+    if let Some(kind) = TimestampKind::of(table, "created_at") {
         timestamp_methods.push_str(&format!(
             r#"
-    pub async fn created_after(timestamp: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn created_after(timestamp: {ty}) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {0}_dsl::{1}
             .filter({0}_dsl::created_at.gt(timestamp))
             .order({0}_dsl::created_at.desc())
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "created_after").with_context("timestamp", timestamp.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "created_after").with_context("timestamp", timestamp.to_string()))
     }}
 
-    pub async fn created_before(timestamp: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn created_before(timestamp: {ty}) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {0}_dsl::{1}
             .filter({0}_dsl::created_at.lt(timestamp))
             .order({0}_dsl::created_at.desc())
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "created_before").with_context("timestamp", timestamp.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "created_before").with_context("timestamp", timestamp.to_string()))
     }}
 
-    pub async fn created_between(start: i64, end: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn created_between(start: {ty}, end: {ty}) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {0}_dsl::{1}
             .filter({0}_dsl::created_at.ge(start).and({0}_dsl::created_at.le(end)))
             .order({0}_dsl::created_at.desc())
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "created_between").with_context("start", start.to_string()).with_context("end", end.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "created_between").with_context("start", start.to_string()).with_context("end", end.to_string()))
     }}
 
-    pub async fn recent(limit: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn recent(limit: i64) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {0}_dsl::{1}
             .order({0}_dsl::created_at.desc())
             .limit(limit)
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "recent").with_context("limit", limit.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "recent").with_context("limit", limit.to_string()))
     }}
 "#,
-            singular_name, table.name
+            singular_name,
+            table.name,
+            conn = backend.connection(&entity),
+            entity = entity,
+            aw = backend.await_suffix(),
+            ty = kind.rust_type()
         ));
     }
 
-    if has_updated_at {
+    if let Some(kind) = TimestampKind::of(table, "updated_at") {
         timestamp_methods.push_str(&format!(
             r#"
-    pub async fn updated_after(timestamp: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn updated_after(timestamp: {ty}) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {0}_dsl::{1}
             .filter({0}_dsl::updated_at.gt(timestamp))
             .order({0}_dsl::updated_at.desc())
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "updated_after").with_context("timestamp", timestamp.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "updated_after").with_context("timestamp", timestamp.to_string()))
     }}
 
-    pub async fn recently_updated(limit: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn recently_updated(limit: i64) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {0}_dsl::{1}
             .order({0}_dsl::updated_at.desc())
             .limit(limit)
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "recently_updated").with_context("limit", limit.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "recently_updated").with_context("limit", limit.to_string()))
     }}
 "#,
-            singular_name, table.name
+            singular_name,
+            table.name,
+            conn = backend.connection(&entity),
+            entity = entity,
+            aw = backend.await_suffix(),
+            ty = kind.rust_type()
         ));
     }
 
@@ -308,8 +857,9 @@ fn generate_timestamp_methods(table: &TableInfo, singular_name: &str) -> String
 }
 
 // Generate methods for relationships
-fn generate_relationship_methods(table_name: &str, singular_name: &str, relationships: &[RelationshipInfo]) -> String {
+fn generate_relationship_methods(table_name: &str, singular_name: &str, relationships: &[RelationshipInfo], created_at: Option<TimestampKind>, backend: ModelBackend) -> String {
     let mut relationship_methods = String::new();
+    let entity = entity_path(table_name);
 
     // Find relationships where this table is the source
     for relationship in relationships.iter().filter(|r| r.source_table == table_name) {
@@ -319,54 +869,403 @@ fn generate_relationship_methods(table_name: &str, singular_name: &str, relation
 
         relationship_methods.push_str(&format!(
             r#"
-    pub async fn get_by_{0}({0}: i32) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn get_by_{0}({0}: i32) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {1}_dsl::{2}
             .filter({1}_dsl::{0}.eq({0}))
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "get_by_{0}").with_context("{0}", {0}.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "get_by_{0}").with_context("{0}", {0}.to_string()))
     }}
+"#,
+            foreign_key,
+            singular_name,
+            &table_name,
+            conn = backend.connection(&entity),
+            entity = entity,
+            aw = backend.await_suffix()
+        ));
 
-    pub async fn get_by_{0}_created_before({0}: i32, timestamp: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+        let Some(kind) = created_at else { continue };
+        relationship_methods.push_str(&format!(
+            r#"
+    pub async fn get_by_{0}_created_before({0}: i32, timestamp: {ty}) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {1}_dsl::{2}
             .filter({1}_dsl::{0}.eq({0}))
             .filter({1}_dsl::created_at.lt(timestamp))
             .order({1}_dsl::created_at.desc())
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "get_by_{0}_created_before").with_context("{0}", {0}.to_string()).with_context("timestamp", timestamp.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "get_by_{0}_created_before").with_context("{0}", {0}.to_string()).with_context("timestamp", timestamp.to_string()))
     }}
 
-    pub async fn get_by_{0}_created_after({0}: i32, timestamp: i64) -> Result<Vec<Self>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn get_by_{0}_created_after({0}: i32, timestamp: {ty}) -> Result<Vec<Self>, ModelError> {{
+        {conn}
         
         {1}_dsl::{2}
             .filter({1}_dsl::{0}.eq({0}))
             .filter({1}_dsl::created_at.gt(timestamp))
             .order({1}_dsl::created_at.desc())
-            .load::<Self>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "get_by_{0}_created_after").with_context("{0}", {0}.to_string()).with_context("timestamp", timestamp.to_string()))
+            .load::<Self>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "get_by_{0}_created_after").with_context("{0}", {0}.to_string()).with_context("timestamp", timestamp.to_string()))
+    }}
+"#,
+            foreign_key,
+            singular_name,
+            &table_name,
+            conn = backend.connection(&entity),
+            entity = entity,
+            aw = backend.await_suffix(),
+            ty = kind.rust_type()
+        ));
+    }
+
+    // belongs_to accessors: the parent row a foreign key points at
+    let mut names: Vec<String> = Vec::new();
+    for relationship in relationships.iter().filter(|r| r.source_table == table_name) {
+        let name = parent_accessor_name(relationship);
+        let parent = format!("crate::structs::{}", to_pascal(&relationship.target_table));
+        let parent_entity = entity_path(&relationship.target_table);
+        let (signature, key, found) = if relationship.nullable {
+            (format!("Option<{}>", parent), format!("let Some(id) = self.{} else {{\n            return Ok(None);\n        }};\n        ", relationship.source_column), ".map(Some)")
+        } else {
+            (parent.clone(), format!("let id = self.{};\n        ", relationship.source_column), "")
+        };
+
+        relationship_methods.push_str(&format!(
+            r#"
+    // The {target} row {column} refers to
+    pub async fn {name}(&self) -> Result<{signature}, ModelError> {{
+        {key}{conn}
+
+        crate::database::schema::{target}::table
+            .find(id)
+            .first::<{parent}>(&mut conn){aw}{found}
+            .map_err(|e: diesel::result::Error| ModelError::new({parent_entity}, e).with_context("operation", "{name}").with_context("id", id.to_string()))
+    }}
+"#,
+            target = relationship.target_table,
+            column = relationship.source_column,
+            conn = backend.connection(&parent_entity),
+            aw = backend.await_suffix(),
+        ));
+        names.push(name);
+    }
+
+    // has_many accessors and their batched eager loaders, on the parent side
+    for relationship in relationships.iter().filter(|r| r.target_table == table_name) {
+        let mut name = children_accessor_name(relationship, relationships);
+        if names.contains(&name) {
+            name = format!("{}_by_{}", relationship.source_table, key_stem(&relationship.source_column));
+        }
+        let child_table = &relationship.source_table;
+        let child = format!("crate::structs::{}", to_pascal(child_table));
+        let child_entity = entity_path(child_table);
+        let key = &relationship.source_column;
+        // A nullable key groups only the children that have a parent
+        let group_key = if relationship.nullable { format!("let Some(key) = child.{} else {{\n                continue;\n            }};", key) } else { format!("let key = child.{};", key) };
+
+        relationship_methods.push_str(&format!(
+            r#"
+    // {child_table} rows whose {key} refers to this row
+    pub async fn {name}(&self) -> Result<Vec<{child}>, ModelError> {{
+        {conn}
+
+        crate::database::schema::{child_table}::table
+            .filter(crate::database::schema::{child_table}::{key}.eq(self.id))
+            .load::<{child}>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({child_entity}, e).with_context("operation", "{name}").with_context("id", self.id.to_string()))
+    }}
+
+    // Each row with its {child_table}, loaded with one `{key} = ANY(...)` query instead of one per row
+    pub async fn with_{name}(parents: Vec<Self>) -> Result<Vec<(Self, Vec<{child}>)>, ModelError> {{
+        if parents.is_empty() {{
+            return Ok(Vec::new());
+        }}
+        {conn}
+
+        let ids: Vec<i32> = parents.iter().map(|parent| parent.id).collect();
+        let children = crate::database::schema::{child_table}::table
+            .filter(crate::database::schema::{child_table}::{key}.eq_any(ids))
+            .load::<{child}>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({child_entity}, e).with_context("operation", "with_{name}"))?;
+
+        let mut grouped: std::collections::HashMap<i32, Vec<{child}>> = std::collections::HashMap::new();
+        for child in children {{
+            {group_key}
+            grouped.entry(key).or_default().push(child);
+        }}
+        Ok(parents
+            .into_iter()
+            .map(|parent| {{
+                let children = grouped.remove(&parent.id).unwrap_or_default();
+                (parent, children)
+            }})
+            .collect())
     }}
 "#,
-            foreign_key, singular_name, &table_name
+            conn = backend.connection(&child_entity),
+            aw = backend.await_suffix(),
         ));
+        names.push(name);
     }
 
     relationship_methods
 }
 
-fn write_model_file(config: &Config, table: &TableInfo, relationships: &[RelationshipInfo]) -> bool {
-    let output_dir = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("models_dir"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("src/models/generated");
+// Filters the generated <Struct>Query offers for a column, from its SQL type
+enum FilterKind {
+    // eq and like
+    Text,
+    // eq, gt, lt and between, with the Rust type the struct field uses
+    Ordered(&'static str),
+    // eq only
+    Equality(&'static str),
+}
+
+// None for Jsonb, arrays, Numeric and custom types: the builder leaves those out
+fn filter_kind(column_type: &str) -> Option<FilterKind> {
+    let kind = match column_type {
+        "Text" | "Varchar" | "VarChar" | "Bpchar" => FilterKind::Text,
+        "Int2" | "SmallInt" => FilterKind::Ordered("i16"),
+        "Int4" | "Integer" => FilterKind::Ordered("i32"),
+        "Int8" | "BigInt" => FilterKind::Ordered("i64"),
+        "Float4" | "Float" => FilterKind::Ordered("f32"),
+        "Float8" | "Double" => FilterKind::Ordered("f64"),
+        "Timestamp" => FilterKind::Ordered("chrono::NaiveDateTime"),
+        "Timestamptz" => FilterKind::Ordered("chrono::DateTime<chrono::Utc>"),
+        "Date" => FilterKind::Ordered("chrono::NaiveDate"),
+        "Time" => FilterKind::Ordered("chrono::NaiveTime"),
+        "Bool" => FilterKind::Equality("bool"),
+        "Uuid" => FilterKind::Equality("uuid::Uuid"),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+// <Struct>Query: typed filters, ordering and paging for one table, run through the same connection and
+// ModelError mapping as the other model methods
+fn generate_query_builder(table: &TableInfo, struct_name: &str, singular_name: &str, backend: ModelBackend) -> String {
+    let entity = entity_path(&table.name);
+    let query = format!("{}Query", struct_name);
+    let column_enum = format!("{}Column", struct_name);
+    let dsl = format!("{}_dsl", singular_name);
+
+    let mut variants = String::new();
+    let mut order_arms = String::new();
+    let mut filters = String::new();
+    for column in &table.columns {
+        let name = &column.name;
+        let Some(kind) = filter_kind(&column.column_type) else {
+            filters.push_str(&format!("\n    // {}: {} columns get no generated filters; use .filter_with() for them\n", name, column.column_type));
+            continue;
+        };
+
+        let variant = to_pascal(name);
+        variants.push_str(&format!("    {},\n", variant));
+        order_arms.push_str(&format!(
+            "                ({column_enum}::{variant}, SortOrder::Asc) => query.then_order_by({dsl}::{name}.asc()),\n                ({column_enum}::{variant}, SortOrder::Desc) => query.then_order_by({dsl}::{name}.desc()),\n"
+        ));
+
+        // The closures run once per query built, so owned text is cloned each time; the other types are Copy
+        let (value_type, convert, value) = match kind {
+            FilterKind::Text => ("impl Into<String>", "let value = value.into();\n        ", "value.clone()"),
+            FilterKind::Ordered(rust_type) | FilterKind::Equality(rust_type) => (rust_type, "", "value"),
+        };
+        filters.push_str(&format!(
+            r#"
+    pub fn {name}_eq(self, value: {value_type}) -> Self {{
+        {convert}self.filter_with(move |query| query.filter({dsl}::{name}.eq({value})))
+    }}
+"#
+        ));
+        match kind {
+            FilterKind::Text => filters.push_str(&format!(
+                r#"
+    // SQL LIKE: % matches any run of characters, _ a single one
+    pub fn {name}_like(self, pattern: impl Into<String>) -> Self {{
+        let pattern = pattern.into();
+        self.filter_with(move |query| query.filter({dsl}::{name}.like(pattern.clone())))
+    }}
+"#
+            )),
+            FilterKind::Ordered(rust_type) => filters.push_str(&format!(
+                r#"
+    pub fn {name}_gt(self, value: {rust_type}) -> Self {{
+        self.filter_with(move |query| query.filter({dsl}::{name}.gt(value)))
+    }}
+
+    pub fn {name}_lt(self, value: {rust_type}) -> Self {{
+        self.filter_with(move |query| query.filter({dsl}::{name}.lt(value)))
+    }}
+
+    // Inclusive on both ends
+    pub fn {name}_between(self, start: {rust_type}, end: {rust_type}) -> Self {{
+        self.filter_with(move |query| query.filter({dsl}::{name}.between(start, end)))
+    }}
+"#
+            )),
+            FilterKind::Equality(_) => {}
+        }
+        if column.nullable {
+            filters.push_str(&format!(
+                r#"
+    pub fn {name}_is_null(self) -> Self {{
+        self.filter_with(|query| query.filter({dsl}::{name}.is_null()))
+    }}
+
+    pub fn {name}_is_not_null(self) -> Self {{
+        self.filter_with(|query| query.filter({dsl}::{name}.is_not_null()))
+    }}
+"#
+            ));
+        }
+    }
+
+    format!(
+        r#"
+// Columns {query} can order by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum {column_enum} {{
+{variants}}}
+
+type {struct_name}Boxed = crate::database::schema::{table}::BoxedQuery<'static, diesel::pg::Pg>;
+type {struct_name}Filter = Box<dyn Fn({struct_name}Boxed) -> {struct_name}Boxed + Send + Sync>;
+
+// Filters, ordering and paging for {table}, built up and then run with load(), first(), count() or paginate()
+#[derive(Default)]
+pub struct {query} {{
+    filters: Vec<{struct_name}Filter>,
+    order: Vec<({column_enum}, SortOrder)>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}}
+
+impl {query} {{
+    pub fn new() -> Self {{
+        Self::default()
+    }}
+
+    // Any other diesel filter on the table, for conditions the typed methods don't cover
+    pub fn filter_with(mut self, filter: impl Fn({struct_name}Boxed) -> {struct_name}Boxed + Send + Sync + 'static) -> Self {{
+        self.filters.push(Box::new(filter));
+        self
+    }}
+{filters}
+    // Later calls break ties left by earlier ones; without any, rows come back by id
+    pub fn order_by(mut self, column: {column_enum}, order: SortOrder) -> Self {{
+        self.order.push((column, order));
+        self
+    }}
+
+    pub fn limit(mut self, limit: i64) -> Self {{
+        self.limit = Some(limit);
+        self
+    }}
+
+    pub fn offset(mut self, offset: i64) -> Self {{
+        self.offset = Some(offset);
+        self
+    }}
+
+    // Pages start at 1
+    pub fn page(self, page: i64, per_page: i64) -> Self {{
+        self.limit(per_page).offset((page.max(1) - 1) * per_page)
+    }}
+
+    fn filtered(&self) -> {struct_name}Boxed {{
+        self.filters.iter().fold({dsl}::{table}.into_boxed(), |query, filter| filter(query))
+    }}
+
+    fn ordered(&self) -> {struct_name}Boxed {{
+        let mut query = self.filtered();
+        if self.order.is_empty() {{
+            query = query.order({dsl}::id.asc());
+        }}
+        for (column, order) in &self.order {{
+            query = match (column, order) {{
+{order_arms}            }};
+        }}
+        if let Some(limit) = self.limit {{
+            query = query.limit(limit);
+        }}
+        if let Some(offset) = self.offset {{
+            query = query.offset(offset);
+        }}
+        query
+    }}
+
+    pub async fn load(self) -> Result<Vec<{struct_name}>, ModelError> {{
+        {conn}
+
+        self.ordered()
+            .load::<{struct_name}>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "query_load"))
+    }}
+
+    pub async fn first(self) -> Result<{struct_name}, ModelError> {{
+        {conn}
+
+        self.ordered()
+            .first::<{struct_name}>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "query_first"))
+    }}
+
+    // Rows matching the filters; ordering, limit and offset don't apply
+    pub async fn count(&self) -> Result<i64, ModelError> {{
+        {conn}
+
+        self.filtered()
+            .count()
+            .get_result::<i64>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "query_count"))
+    }}
+
+    // One page of matching rows plus the number of matching rows across all pages
+    pub async fn paginate(self, page: i64, per_page: i64) -> Result<(Vec<{struct_name}>, i64), ModelError> {{
+        let total = self.count().await?;
+        let rows = self.page(page, per_page).load().await?;
+        Ok((rows, total))
+    }}
+}}
+"#,
+        table = table.name,
+        conn = backend.connection(&entity),
+        aw = backend.await_suffix(),
+    )
+}
+
+// A foreign key without its `_id` suffix: user_id -> user, created_by stays created_by
+fn key_stem(column: &str) -> &str {
+    column.strip_suffix("_id").filter(|stem| !stem.is_empty()).unwrap_or(column)
+}
+
+// Name of the belongs_to accessor for a foreign key: user_id -> user(), created_by -> created_by_user()
+fn parent_accessor_name(relationship: &RelationshipInfo) -> String {
+    match relationship.source_column.strip_suffix("_id").filter(|stem| !stem.is_empty()) {
+        Some(stem) => stem.to_string(),
+        None => format!("{}_{}", relationship.source_column, singular(&relationship.target_table)),
+    }
+}
+
+// Name of the has_many accessor on the parent: the child table's name, or <child>_by_<key> when the child table
+// has several keys to the parent (created_by/updated_by -> users) or refers to itself (parent_id)
+fn children_accessor_name(relationship: &RelationshipInfo, relationships: &[RelationshipInfo]) -> String {
+    let keys_to_parent = relationships.iter().filter(|other| other.source_table == relationship.source_table && other.target_table == relationship.target_table).count();
+    if keys_to_parent > 1 || relationship.source_table == relationship.target_table {
+        format!("{}_by_{}", relationship.source_table, key_stem(&relationship.source_column))
+    } else {
+        relationship.source_table.clone()
+    }
+}
+
+fn write_model_file(config: &Config, table: &TableInfo, relationships: &[RelationshipInfo], backend: ModelBackend, manifest: &mut CodegenManifest) -> bool {
+    let codegen = config.codegen();
+    let output_dir = codegen.models_dir.as_str();
 
     // Create the output directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(output_dir) {
+    if let Err(e) = file_writer::create_dir_all(config.project_dir.join(output_dir)) {
         crate::logger::error(&format!("Error creating directory {}: {}", output_dir, e)).unwrap_or_default();
         return false;
     }
@@ -381,101 +1280,157 @@ fn write_model_file(config: &Config, table: &TableInfo, relationships: &[Relatio
     let singular_name = singular(table_name);
 
     // Generate specialized methods
-    let bool_methods = generate_bool_methods(table, &singular_name);
-    let timestamp_methods = generate_timestamp_methods(table, &singular_name);
-    let relationship_methods = generate_relationship_methods(table_name, &singular_name, relationships);
+    let cache = match codegen.models.cache_ttl(table_name).and_then(|ttl| {
+        if ttl.is_some() && !codegen.structs.derives.iter().any(|derive| derive.trim() == "Clone") {
+            return Err(format!("[codegen.models.{}] cache returns copies of cached rows, so add \"Clone\" to [codegen.structs] derives", table_name));
+        }
+        Ok(CacheHooks::new(table_name, &struct_name, ttl))
+    }) {
+        Ok(cache) => cache,
+        Err(e) => {
+            crate::logger::error(&e).unwrap_or_default();
+            return false;
+        }
+    };
+    let bool_methods = generate_bool_methods(table, &singular_name, &cache.changed, backend);
+    let timestamp_methods = generate_timestamp_methods(table, &singular_name, backend);
+    let relationship_methods = generate_relationship_methods(table_name, &singular_name, relationships, TimestampKind::of(table, "created_at"), backend);
+    let query_builder = generate_query_builder(table, &struct_name, &singular_name, backend);
+    let entity = entity_path(table_name);
+
+    // Run the insertable's validate() first when gen structs emitted one for this table
+    let structs_config = &codegen.structs;
+    let validated = crate::codegen_ignore::ignored_by(&codegen, Generator::Insertable, table_name).is_none()
+        && structs_config.validations_for(table_name, &struct_name).map(|rules| !rules.is_empty()).unwrap_or(false);
+    let (validate_import, validate_create, validate_update) = if validated {
+        (
+            "\nuse crate::structs::insertable::{validation_failed, Validate};".to_string(),
+            format!("new_record.validate().map_err(|errors| ModelError::new({}, validation_failed(errors)).with_context(\"operation\", \"create\"))?;\n        ", entity),
+            format!("updates.validate().map_err(|errors| ModelError::new({}, validation_failed(errors)).with_context(\"operation\", \"update_by_id\").with_context(\"id\", id.to_string()))?;\n        ", entity),
+        )
+    } else {
+        (String::new(), String::new(), String::new())
+    };
 
     let model_template = format!(
-        r#"use crate::database::db::establish_connection;
+        r#"{db_import}
 use crate::database::schema::{0}::dsl::{{self as {2}_dsl}};
 use crate::structs::{1};
-use crate::structs::insertable::New{1};
+use crate::structs::insertable::New{1};{validate_import}
 use crate::meltdown::*;
-use diesel::prelude::*;
-use diesel::result::Error;
-use diesel::Connection;
+{diesel_imports}
 use chrono::Utc;
-
+{cache_declaration}
 impl {1} {{
-    pub async fn get_all() -> Result<Vec<{1}>, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn get_all() -> Result<Vec<{1}>, ModelError> {{
+        {all_lookup}{conn}
 
         {2}_dsl::{0}
             .order({2}_dsl::id.asc())
-            .load::<{1}>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "get_all"))
+            .load::<{1}>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "get_all")){all_store}
     }}
 
-    pub async fn get_by_id(id: i32) -> Result<{1}, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn get_by_id(id: i32) -> Result<{1}, ModelError> {{
+        {id_lookup}{conn}
 
         {2}_dsl::{0}
             .filter({2}_dsl::id.eq(id))
-            .first::<{1}>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "get_by_id").with_context("id", id.to_string()))
+            .first::<{1}>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "get_by_id").with_context("id", id.to_string())){id_store}
     }}
 
 
-    pub async fn create(new_record: New{1}) -> Result<{1}, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn create(new_record: New{1}) -> Result<{1}, ModelError> {{
+        {validate_create}{conn}
         
-        conn.transaction(|conn| {{
+        {tx_open}
             let result = diesel::insert_into({2}_dsl::{0})
                 .values(&new_record)
-                .get_result::<{1}>(conn)
-                .map_err(|_| Error::RollbackTransaction)?;
+                .get_result::<{1}>(conn){aw}?;
                 
             Ok(result)
-        }})
-        .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "create"))
+        }}{tx_close}
+        .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "create")){created}
     }}
 
-    pub async fn update_by_id(id: i32, updates: &New{1}) -> Result<{1}, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn update_by_id(id: i32, updates: &New{1}) -> Result<{1}, ModelError> {{
+        {validate_update}{conn}
         
-        conn.transaction(|conn| {{
+        {tx_open}
             let updated = diesel::update({2}_dsl::{0}.filter({2}_dsl::id.eq(id)))
                 .set(updates)
-                .get_result::<{1}>(conn)
-                .map_err(|_| Error::RollbackTransaction)?;
+                .get_result::<{1}>(conn){aw}?;
                 
             Ok(updated)
-        }})
-        .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "update_by_id").with_context("id", id.to_string()))
+        }}{tx_close}
+        .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "update_by_id").with_context("id", id.to_string())){changed}
     }}
 
-    pub async fn delete_by_id(id: i32) -> Result<(), MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn delete_by_id(id: i32) -> Result<(), ModelError> {{
+        {conn}
 
-        conn.transaction(|conn| {{
+        {tx_open}
             let _ = {2}_dsl::{0}
                 .filter({2}_dsl::id.eq(id))
-                .first::<{1}>(conn)
-                .map_err(|_| Error::RollbackTransaction)?;
+                .first::<{1}>(conn){aw}?;
                 
             diesel::delete({2}_dsl::{0}.filter({2}_dsl::id.eq(id)))
-                .execute(conn)
-                .map_err(|_| Error::RollbackTransaction)?;
+                .execute(conn){aw}?;
                 
             Ok(())
-        }})
-        .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "delete_by_id").with_context("id", id.to_string()))
+        }}{tx_close}
+        .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "delete_by_id").with_context("id", id.to_string())){changed}
     }}
 
-    pub async fn count() -> Result<i64, MeltDown> {{
-        let mut conn = establish_connection();
+    pub async fn count() -> Result<i64, ModelError> {{
+        {conn}
         
         {2}_dsl::{0}
             .count()
-            .get_result::<i64>(&mut conn)
-            .map_err(|e: diesel::result::Error| MeltDown::from(e).with_context("operation", "count"))
+            .get_result::<i64>(&mut conn){aw}
+            .map_err(|e: diesel::result::Error| ModelError::new({entity}, e).with_context("operation", "count"))
+    }}
+
+    // Filters, ordering and paging beyond the methods below; see {1}Query
+    pub fn query() -> {1}Query {{
+        {1}Query::new()
+    }}
+
+    // One page of rows by id, plus the total row count. Pages start at 1
+    pub async fn paginate(page: i64, per_page: i64) -> Result<(Vec<{1}>, i64), ModelError> {{
+        Self::query().paginate(page, per_page).await
     }}{3}{4}{5}
 }}
-"#,
-        table_name, struct_name, singular_name, bool_methods, timestamp_methods, relationship_methods
+{query_builder}"#,
+        table_name,
+        struct_name,
+        singular_name,
+        bool_methods,
+        timestamp_methods,
+        relationship_methods,
+        db_import = backend.db_import(codegen.models.connection_module.as_deref().unwrap_or(DEFAULT_CONNECTION_MODULE)),
+        diesel_imports = backend.diesel_imports(),
+        conn = backend.connection(&entity),
+        entity = entity,
+        aw = backend.await_suffix(),
+        tx_open = backend.transaction_open(),
+        tx_close = backend.transaction_close(),
+        validate_import = validate_import,
+        validate_create = validate_create,
+        validate_update = validate_update,
+        cache_declaration = cache.declaration,
+        all_lookup = cache.all_lookup,
+        all_store = cache.all_store,
+        id_lookup = cache.id_lookup,
+        id_store = cache.id_store,
+        created = cache.created,
+        changed = cache.changed,
+        query_builder = query_builder
     );
 
-    if let Err(e) = fs::write(&file_path, model_template) {
+    // A file the user edited is skipped with a warning but still counts as generated
+    if let Err(e) = crate::codegen_format::render(&config.project_dir, &file_path, &model_template, codegen.schema_path(), Some(table_name)).and_then(|source| manifest.write(&file_path, &source, Some(table_name))) {
         crate::logger::error(&format!("Error writing model file {}: {}", file_path, e)).unwrap_or_default();
         false
     } else {
@@ -483,75 +1438,103 @@ impl {1} {{
     }
 }
 
-fn update_mod_file(config: &Config, processed_tables: &[String]) -> bool {
-    if processed_tables.is_empty() {
-        return true; // Nothing to do, but not an error
+// Rewrite the generated section of the models mod.rs from every table the manifest has a model for
+fn update_mod_file(config: &Config, manifest: &CodegenManifest) -> bool {
+    let codegen = config.codegen();
+    let output_dir = codegen.models_dir.as_str();
+
+    if let Err(e) = mod_file::sync(&config.project_dir.join(output_dir).join("mod.rs"), &manifest.tables_in(output_dir), true) {
+        crate::logger::error(&format!("Error writing mod.rs file: {}", e)).unwrap_or_default();
+        return false;
     }
 
-    let output_dir = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("models_dir"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("src/models/generated");
+    true
+}
+
+// Regenerate models for tables that changed since the last run (every table with `all`), limited to `selection`
+pub fn generate(config: &Config, all: bool, selection: &TableSelection) -> bool {
+    generate_changes(config, all, selection).0
+}
+
+// generate(), plus the changes it planned from. None when there was no schema to plan from, or the
+// selection named unknown tables
+pub fn generate_changes(config: &Config, all: bool, selection: &TableSelection) -> (bool, Option<SchemaChanges>) {
+    let codegen = config.codegen();
+    let models_dir = codegen.models_dir.clone();
+
+    let ignored = |table: &str| crate::codegen_ignore::ignored_by(&codegen, Generator::Models, table).is_some();
+    if let Err(e) = selection.check_schema(config) {
+        crate::logger::error(&e).unwrap_or_default();
+        return (false, None);
+    }
+    let (snapshot, changes) = match crate::codegen_snapshot::plan(config, "models", ignored, all, selection, |table| !config.project_dir.join(format!("{}/{}.rs", models_dir, table)).exists()) {
+        Ok(plan) => plan,
+        // Without a readable schema there is nothing to compare; let the full run report the problem
+        Err(_) => return (generate_tables(config, None), None),
+    };
 
-    let mod_file_path = Path::new(output_dir).join("mod.rs");
-    let mut mod_file_content = fs::read_to_string(&mod_file_path).unwrap_or_default();
+    let mut ok = changes.changed.is_empty() || generate_tables(config, Some(&changes.changed));
 
-    let mut updated = false;
-    for table_name in processed_tables {
-        let mod_declaration = format!("pub mod {};", table_name);
-        if !mod_file_content.contains(&mod_declaration) {
-            mod_file_content.push_str(&format!("\n{}", mod_declaration));
-            updated = true;
+    // Models of tables dropped from the schema go too, unless they were edited; the Entity enum loses them
+    if !changes.removed.is_empty() {
+        let mut manifest = CodegenManifest::load(&config.project_dir);
+        // Models of ignored tables are left alone, like ignored tables themselves
+        let kept: Vec<String> = snapshot.table_names().into_iter().chain(manifest.tables_in(&models_dir).into_iter().filter(|table| ignored(table))).collect();
+        if let Err(e) = manifest.remove_stale(&models_dir, &kept) {
+            crate::logger::warning(&e).unwrap_or_default();
+            ok = false;
         }
-        if !mod_file_content.contains(&format!("pub use {}::*;", table_name)) {
-            mod_file_content.push_str(&format!("\npub use {}::*;", table_name));
-            updated = true;
+        ok &= update_mod_file(config, &manifest);
+        if changes.changed.is_empty() {
+            let tables = load_schema_table_info(config, codegen.schema_path()).unwrap_or_default();
+            if let Err(e) = write_meltdown_extension(config, &tables, codegen.schema_path(), &mut manifest) {
+                crate::logger::warning(&e).unwrap_or_default();
+                ok = false;
+            }
+        }
+        if let Err(e) = manifest.save() {
+            crate::logger::warning(&e).unwrap_or_default();
         }
     }
 
-    if updated {
-        if let Err(e) = fs::write(&mod_file_path, mod_file_content) {
-            crate::logger::error(&format!("Error writing mod.rs file: {}", e)).unwrap_or_default();
-            return false;
+    if ok {
+        if let Err(e) = crate::codegen_snapshot::save(config, "models", &snapshot) {
+            crate::logger::warning(&e).unwrap_or_default();
         }
     }
-
-    true
+    crate::logger::success(&format!("Models: {}", changes.summary())).unwrap_or_default();
+    (ok, Some(changes))
 }
 
-pub fn generate(config: &Config) -> bool {
+// Generate models, optionally restricted to the given tables
+pub fn generate_tables(config: &Config, only: Option<&[String]>) -> bool {
     let progress = ProgressManager::new_spinner();
     progress.set_message("Generating enhanced model implementations...");
 
     // Get schema file path
-    let schema_path = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("schema_file"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("src/database/schema.rs");
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
 
     // Check if schema file exists
-    if !Path::new(schema_path).exists() {
+    if !config.project_dir.join(schema_path).exists() {
         progress.error(&format!("Schema file not found at {}", schema_path));
         return false;
     }
 
-    // Get the ignored models list from Catalyst.toml
-    // First check the proper ignore path in [codegen.models]
-    let ignore_list: Vec<String> = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("models"))
-        .and_then(|s| s.get("ignore"))
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-        .unwrap_or_default();
+    let backend = match ModelBackend::from_config(config) {
+        Ok(backend) => backend,
+        Err(e) => {
+            progress.error(&e);
+            return false;
+        }
+    };
+    if let Err(e) = sync_backend_support(config, backend) {
+        progress.error(&format!("Failed to set up the {:?} model backend: {}", backend, e));
+        return false;
+    }
 
     // Load detailed schema information
-    let tables = match load_schema_table_info(schema_path) {
+    let tables = match load_schema_table_info(config, schema_path) {
         Ok(tables) => {
             if tables.is_empty() {
                 progress.error("No tables found in schema file");
@@ -565,9 +1548,24 @@ pub fn generate(config: &Config) -> bool {
         }
     };
 
-    // Load relationship information
-    let relationships = match load_schema_relationships(schema_path) {
-        Ok(rels) => rels,
+    let mut manifest = CodegenManifest::load(&config.project_dir);
+    if let Err(e) = write_meltdown_extension(config, &tables, codegen.schema_path(), &mut manifest) {
+        progress.error(&e);
+        return false;
+    }
+    let cached = tables.iter().any(|table| codegen.schema.includes(&table.name) && !crate::codegen_ignore::skips(&codegen, Generator::Models, &table.name) && matches!(codegen.models.cache_ttl(&table.name), Ok(Some(_))));
+    if cached {
+        if let Err(e) = write_model_cache(config, codegen.schema_path(), &mut manifest) {
+            progress.error(&e);
+            return false;
+        }
+    }
+
+    // Load relationship information. Accessors need a struct on both sides, so keys to or from tables without
+    // one are left out
+    let has_struct = |name: &str| tables.iter().any(|table| table.name == name) && codegen.schema.includes(name) && crate::codegen_ignore::ignored_by(&codegen, Generator::Structs, name).is_none();
+    let relationships = match load_schema_relationships(config, schema_path) {
+        Ok(rels) => rels.into_iter().filter(|r| has_struct(&r.source_table) && has_struct(&r.target_table)).collect(),
         Err(e) => {
             crate::logger::warning(&format!("Error loading relationship information: {}. Continuing without relationship methods.", e)).unwrap_or_default();
             Vec::new()
@@ -578,23 +1576,39 @@ pub fn generate(config: &Config) -> bool {
 
     // Process each table
     for table in &tables {
-        // Skip ignored tables - properly handle case sensitivity
-        if ignore_list.iter().any(|ignored| ignored.to_lowercase() == table.name.to_lowercase()) {
+        // Tables [codegen.schema] leaves out of schema.rs
+        if !codegen.schema.includes(&table.name) {
+            continue;
+        }
+
+        // Skip tables matching [codegen] ignore or [codegen.models] ignore
+        if crate::codegen_ignore::skips(&codegen, Generator::Models, &table.name) {
             progress.set_message(&format!("Skipping ignored table: {}", table.name));
             continue;
         }
 
-        if write_model_file(config, table, &relationships) {
+        // Skip tables outside the requested subset
+        if let Some(only) = only {
+            if !only.iter().any(|name| name == &table.name) {
+                continue;
+            }
+        }
+
+        if write_model_file(config, table, &relationships, backend, &mut manifest) {
             processed_tables.push(table.name.clone());
         }
     }
 
+    if let Err(e) = manifest.save() {
+        crate::logger::warning(&e).unwrap_or_default();
+    }
+
     if processed_tables.is_empty() {
         progress.error("No models were generated");
         false
     } else {
         // Update mod.rs file
-        if update_mod_file(config, &processed_tables) {
+        if update_mod_file(config, &manifest) {
             progress.success(&format!("Generated {} enhanced model files with specialized methods", processed_tables.len()));
             true
         } else {
@@ -604,3 +1618,86 @@ pub fn generate(config: &Config) -> bool {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(source_table: &str, source_column: &str, target_table: &str) -> RelationshipInfo {
+        RelationshipInfo { source_table: source_table.to_string(), source_column: source_column.to_string(), target_table: target_table.to_string(), target_column: "id".to_string(), nullable: false }
+    }
+
+    #[test]
+    fn relationship_accessors_get_distinct_names() {
+        let relationships = vec![key("posts", "user_id", "users"), key("documents", "created_by", "users"), key("documents", "updated_by", "users"), key("categories", "parent_id", "categories")];
+        let parents: Vec<String> = relationships.iter().map(parent_accessor_name).collect();
+        let children: Vec<String> = relationships.iter().map(|relationship| children_accessor_name(relationship, &relationships)).collect();
+
+        assert_eq!(parents, ["user", "created_by_user", "updated_by_user", "parent"]);
+        assert_eq!(children, ["posts", "documents_by_created_by", "documents_by_updated_by", "categories_by_parent"]);
+    }
+
+    #[test]
+    fn timestamp_methods_follow_the_column_type() {
+        let tables = parse_schema_tables(include_str!("../tests/fixtures/timestamps_schema.rs"));
+        let relationships = vec![key("posts", "event_id", "events"), key("audits", "event_id", "events")];
+        let generated = |name: &str| {
+            let table = tables.iter().find(|table| table.name == name).unwrap();
+            let singular_name = singular(name);
+            [
+                generate_bool_methods(table, &singular_name, "", ModelBackend::Blocking),
+                generate_timestamp_methods(table, &singular_name, ModelBackend::Blocking),
+                generate_relationship_methods(name, &singular_name, &relationships, TimestampKind::of(table, "created_at"), ModelBackend::Blocking),
+            ]
+            .concat()
+        };
+
+        let events = generated("events");
+        assert!(events.contains("created_between(start: i64, end: i64)") && events.contains("updated_after(timestamp: i64)"));
+        assert!(events.contains("let current_timestamp = Utc::now().timestamp();"));
+
+        let posts = generated("posts");
+        assert!(posts.contains("created_between(start: chrono::NaiveDateTime, end: chrono::NaiveDateTime)"));
+        assert!(posts.contains("get_by_event_id_created_after(event_id: i32, timestamp: chrono::NaiveDateTime)"));
+        assert!(posts.contains("let current_timestamp = Utc::now().naive_utc();"));
+
+        let audits = generated("audits");
+        assert!(audits.contains("created_before(timestamp: chrono::DateTime<Utc>)") && audits.contains("updated_after(timestamp: chrono::DateTime<Utc>)"));
+        assert!(audits.contains("get_by_event_id_created_before(event_id: i32, timestamp: chrono::DateTime<Utc>)"));
+
+        let flags = generated("flags");
+        assert!(!flags.contains("created_") && !flags.contains("updated_at"));
+        assert!(flags.contains(".set(flag_dsl::enabled.eq(value))"));
+    }
+
+    #[test]
+    fn query_builder_filters_follow_column_types() {
+        let schema = "diesel::table! {\n    users (id) {\n        id -> Int4,\n        email -> Nullable<Text>,\n        seen_at -> Timestamptz,\n        active -> Bool,\n        settings -> Jsonb,\n        tags -> Array<Nullable<Text>>,\n    }\n}\n";
+        let tables = parse_schema_tables(schema);
+        let tags = tables[0].columns.iter().find(|column| column.name == "tags").unwrap();
+        assert_eq!((tags.column_type.as_str(), tags.nullable), ("Array<Nullable<Text>>", false));
+
+        let builder = generate_query_builder(&tables[0], "Users", "user", ModelBackend::Blocking);
+        for method in ["id_between", "email_like", "email_is_null", "seen_at_gt", "active_eq"] {
+            assert!(builder.contains(&format!("pub fn {}(", method)), "missing {}", method);
+        }
+        for method in ["active_gt", "seen_at_like", "id_is_null", "settings_eq", "tags_eq"] {
+            assert!(!builder.contains(&format!("pub fn {}(", method)), "unexpected {}", method);
+        }
+        assert!(builder.contains("// tags: Array<Nullable<Text>> columns get no generated filters"));
+    }
+
+    #[test]
+    fn cached_tables_invalidate_on_writes() {
+        let tables = parse_schema_tables(include_str!("../tests/fixtures/timestamps_schema.rs"));
+        let flags = tables.iter().find(|table| table.name == "flags").unwrap();
+        assert!(CacheHooks::new("flags", "Flags", None).declaration.is_empty());
+
+        let hooks = CacheHooks::new("flags", "Flags", Some(60));
+        assert!(hooks.declaration.contains("pub static FLAGS_CACHE: crate::services::model_cache::ModelCache<Flags> = crate::services::model_cache::ModelCache::new(\"flags\", 60);"));
+        let plain = generate_bool_methods(flags, "flag", "", ModelBackend::Blocking);
+        let cached = generate_bool_methods(flags, "flag", &hooks.changed, ModelBackend::Blocking);
+        assert!(cached.contains(".inspect(|_| FLAGS_CACHE.invalidate(id))"));
+        assert_eq!(cached.replace(&hooks.changed, ""), plain);
+    }
+}
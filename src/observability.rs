@@ -0,0 +1,296 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::configs::Config;
+use crate::envfile;
+use crate::logger;
+use crate::models::ModelBackend;
+
+type BlastResult = Result<(), String>;
+
+// Gauges the generated module keeps over a rolling minute, read by `blast status`
+const REQUESTS_GAUGE: &str = "http_requests_last_minute";
+const ERRORS_GAUGE: &str = "http_server_errors_last_minute";
+const P95_GAUGE: &str = "http_request_duration_p95_last_minute_seconds";
+
+const MODULE_SOURCE: &str = r##"// Generated by `blast gen observability`. Access log, request metrics and GET /metrics (Prometheus text format).
+// `blast status` reads the *_last_minute gauges. Regenerate with `blast gen observability --force`.
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::json;
+use rocket::{Data, Request, Response};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const ACCESS_LOG: &str = "storage/logs/access.log";
+// Upper bounds of the latency histogram buckets, in seconds
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+const WINDOW: Duration = Duration::from_secs(60);
+
+static DB_QUERIES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct RouteMetrics {
+    statuses: BTreeMap<u16, u64>,
+    // Cumulative, as Prometheus expects: a request counts in every bucket its latency fits under
+    buckets: [u64; BUCKETS.len()],
+    sum_seconds: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    // Keyed by method and route pattern, so /users/<id> is one series rather than one per id
+    routes: BTreeMap<(String, String), RouteMetrics>,
+    // (finished at, latency in seconds, server error) for the last minute
+    recent: VecDeque<(Instant, f64, bool)>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+// Called by the connection helper below, which the generated models use ([codegen.models] connection_module)
+pub fn count_query() {
+    DB_QUERIES.fetch_add(1, Ordering::Relaxed);
+}
+
+__CONNECTION_HELPER__
+
+__USER_ID__
+
+struct RequestStart(Option<Instant>);
+
+// Attach with `.attach(AccessLog)`
+pub struct AccessLog;
+
+#[rocket::async_trait]
+impl Fairing for AccessLog {
+    fn info(&self) -> Info {
+        Info { name: "Access log and metrics", kind: Kind::Request | Kind::Response }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let latency = request.local_cache(|| RequestStart(None)).0.map(|started| started.elapsed().as_secs_f64()).unwrap_or(0.0);
+        let method = request.method().as_str().to_string();
+        let route = request.route().map(|route| route.uri.to_string()).unwrap_or_else(|| "unmatched".to_string());
+        let status = response.status().code;
+        record(&method, &route, status, latency);
+        write_access_log(request, &method, &route, status, latency);
+    }
+}
+
+fn record(method: &str, route: &str, status: u16, latency: f64) {
+    let Ok(mut registry) = registry().lock() else { return };
+    let metrics = registry.routes.entry((method.to_string(), route.to_string())).or_default();
+    *metrics.statuses.entry(status).or_default() += 1;
+    for (bucket, bound) in metrics.buckets.iter_mut().zip(BUCKETS) {
+        if latency <= bound {
+            *bucket += 1;
+        }
+    }
+    metrics.sum_seconds += latency;
+    metrics.count += 1;
+
+    let now = Instant::now();
+    registry.recent.push_back((now, latency, status >= 500));
+    prune(&mut registry.recent, now);
+}
+
+fn prune(recent: &mut VecDeque<(Instant, f64, bool)>, now: Instant) {
+    while recent.front().is_some_and(|(finished, _, _)| now.duration_since(*finished) > WINDOW) {
+        recent.pop_front();
+    }
+}
+
+// One JSON object per line: time, method, path, route, status, latency_ms and user_id
+fn write_access_log(request: &Request<'_>, method: &str, route: &str, status: u16, latency: f64) {
+    static WRITER: Mutex<()> = Mutex::new(());
+    let line = json!({
+        "time": chrono::Utc::now().to_rfc3339(),
+        "method": method,
+        "path": request.uri().path().to_string(),
+        "route": route,
+        "status": status,
+        "latency_ms": (latency * 10_000.0).round() / 10.0,
+        "user_id": user_id(request),
+    });
+    let _guard = WRITER.lock();
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(ACCESS_LOG) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn render() -> String {
+    let Ok(mut registry) = registry().lock() else { return String::new() };
+    let mut out = String::new();
+
+    out.push_str("# HELP http_requests_total Requests handled, by route and status\n# TYPE http_requests_total counter\n");
+    for ((method, route), metrics) in &registry.routes {
+        for (status, count) in &metrics.statuses {
+            out.push_str(&format!("http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}\n", method, label(route), status, count));
+        }
+    }
+
+    out.push_str("# HELP http_request_duration_seconds Request latency, by route\n# TYPE http_request_duration_seconds histogram\n");
+    for ((method, route), metrics) in &registry.routes {
+        let labels = format!("method=\"{}\",route=\"{}\"", method, label(route));
+        for (bound, count) in BUCKETS.iter().zip(metrics.buckets) {
+            out.push_str(&format!("http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}\n", labels, bound, count));
+        }
+        out.push_str(&format!("http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}\n", labels, metrics.count));
+        out.push_str(&format!("http_request_duration_seconds_sum{{{}}} {}\n", labels, metrics.sum_seconds));
+        out.push_str(&format!("http_request_duration_seconds_count{{{}}} {}\n", labels, metrics.count));
+    }
+
+    out.push_str("# HELP db_queries_total Connections the generated models took, about one per query\n# TYPE db_queries_total counter\n");
+    out.push_str(&format!("db_queries_total {}\n", DB_QUERIES.load(Ordering::Relaxed)));
+__CACHE_METRICS__
+    prune(&mut registry.recent, Instant::now());
+    let mut latencies: Vec<f64> = registry.recent.iter().map(|(_, latency, _)| *latency).collect();
+    latencies.sort_by(|a, b| a.total_cmp(b));
+    let p95 = latencies.get((latencies.len() * 95).div_ceil(100).saturating_sub(1)).copied().unwrap_or(0.0);
+    let errors = registry.recent.iter().filter(|(_, _, error)| *error).count();
+    out.push_str(&format!("# TYPE http_requests_last_minute gauge\nhttp_requests_last_minute {}\n", latencies.len()));
+    out.push_str(&format!("# TYPE http_server_errors_last_minute gauge\nhttp_server_errors_last_minute {}\n", errors));
+    out.push_str(&format!("# TYPE http_request_duration_p95_last_minute_seconds gauge\nhttp_request_duration_p95_last_minute_seconds {}\n", p95));
+    out
+}
+
+// Off unless METRICS_ENABLED=true, so production doesn't publish its traffic by accident
+#[get("/metrics")]
+pub fn metrics() -> Result<(ContentType, String), Status> {
+    let enabled = std::env::var("METRICS_ENABLED").map(|value| value == "true" || value == "1").unwrap_or(false);
+    if !enabled {
+        return Err(Status::NotFound);
+    }
+    Ok((ContentType::Plain, render()))
+}
+"##;
+
+const BLOCKING_CONNECTION: &str = r#"// Generated models import establish_connection from here, so each one is counted
+pub fn establish_connection() -> diesel::pg::PgConnection {
+    count_query();
+    crate::database::db::establish_connection()
+}"#;
+
+const ASYNC_CONNECTION: &str = r#"// Generated models import get_connection from here, so each one is counted
+pub async fn get_connection() -> Result<diesel_async::pooled_connection::bb8::PooledConnection<'static, diesel_async::AsyncPgConnection>, diesel::result::Error> {
+    count_query();
+    crate::database::db::get_connection().await
+}"#;
+
+fn user_id_source(auth_module: Option<&str>) -> String {
+    match auth_module {
+        Some(module) => format!(
+            r#"// The user the JWT guard would resolve for this request
+fn user_id(request: &Request<'_>) -> Option<String> {{
+    let token = request.headers().get_one("Authorization")?.strip_prefix("Bearer ")?;
+    {}::decode_access_token(token).map(|claims| claims.sub.to_string())
+}}"#,
+            module
+        ),
+        None => "// Run `blast gen observability --force` after `blast gen auth` to log user ids\nfn user_id(_: &Request<'_>) -> Option<String> {\n    None\n}".to_string(),
+    }
+}
+
+// Hit and miss counts of the generated model cache, when `blast gen models` wrote one
+fn cache_metrics_source(config: &Config) -> &'static str {
+    if config.project_dir.join("src/services/model_cache.rs").exists() {
+        "    out.push_str(&crate::services::model_cache::metrics());\n"
+    } else {
+        ""
+    }
+}
+
+// `blast gen observability`: access log fairing, metrics registry and /metrics route. Points the generated
+// models at the counting connection helper
+pub fn generate(config: &mut Config, force: bool) -> BlastResult {
+    // Next to the template's other middleware when it has a middleware module
+    let middleware_dir = config.project_dir.join("src").join("middleware");
+    let (path, module): (PathBuf, &str) = if middleware_dir.is_dir() {
+        (middleware_dir.join("observability.rs"), "crate::middleware::observability")
+    } else {
+        (config.project_dir.join("src").join("observability.rs"), "crate::observability")
+    };
+    if path.exists() && !force {
+        return Err(format!("{} already exists. Re-run with --force to regenerate it", path.display()));
+    }
+
+    let connection_helper = match ModelBackend::from_config(config)? {
+        ModelBackend::Blocking => BLOCKING_CONNECTION,
+        ModelBackend::DieselAsync => ASYNC_CONNECTION,
+    };
+    let auth = crate::auth::module(config);
+    let source = MODULE_SOURCE.replace("__CONNECTION_HELPER__", connection_helper).replace("__USER_ID__", &user_id_source(auth)).replace("__CACHE_METRICS__", cache_metrics_source(config));
+    fs::write(&path, source).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    logger::success(&format!("Generated {}", path.strip_prefix(&config.project_dir).unwrap_or(&path).display()))?;
+
+    if config.codegen().models.connection_module.as_deref() != Some(module) {
+        config.set_key("codegen.models.connection_module", module)?;
+    }
+    let added = envfile::append_missing(config, "Metrics (blast gen observability). GET /metrics answers 404 unless this is true", &[("METRICS_ENABLED".to_string(), "true".to_string())])?;
+    if !added.is_empty() {
+        logger::success(&format!("Added {} to .env", added.join(", ")))?;
+    }
+
+    let name = module.rsplit("::").next().unwrap_or(module);
+    logger::plain("Register it in your Rocket setup:")?;
+    logger::plain(&format!("  - declare the module (`pub mod {};`)", name))?;
+    logger::plain(&format!("  - attach the fairing: `.attach({}::AccessLog)`", module))?;
+    logger::plain(&format!("  - mount the endpoint: `.mount(\"/\", routes![{}::metrics])`", module))?;
+    logger::plain(&format!("  - run `blast gen models` so the models count queries through {}", module))?;
+    if auth.is_none() {
+        logger::plain("  - after `blast gen auth`, re-run with --force to log user ids")?;
+    }
+    Ok(())
+}
+
+// Value of an unlabelled metric in Prometheus text output
+fn metric_value(body: &str, name: &str) -> Option<f64> {
+    body.lines().filter(|line| !line.starts_with('#')).find_map(|line| {
+        let (metric, value) = line.split_once(' ')?;
+        (metric == name).then(|| value.trim().parse().ok()).flatten()
+    })
+}
+
+// One line for `blast status` from the running server's /metrics, or None when it has none to offer
+pub fn traffic_summary(server_url: &str) -> Option<String> {
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(2)).build().ok()?;
+    let body = client.get(format!("{}/metrics", server_url.trim_end_matches('/'))).send().ok()?.error_for_status().ok()?.text().ok()?;
+    summarize(&body)
+}
+
+fn summarize(body: &str) -> Option<String> {
+    let requests = metric_value(body, REQUESTS_GAUGE)?;
+    let errors = metric_value(body, ERRORS_GAUGE).unwrap_or(0.0);
+    let p95 = metric_value(body, P95_GAUGE).unwrap_or(0.0);
+    let error_rate = if requests > 0.0 { errors / requests * 100.0 } else { 0.0 };
+    Some(format!("{:.2} req/s, p95 {:.0} ms, {:.1}% errors (last minute)", requests / 60.0, p95 * 1000.0, error_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_the_last_minute_gauges() {
+        let body = "# TYPE http_requests_last_minute gauge\nhttp_requests_last_minute 120\nhttp_server_errors_last_minute 3\nhttp_request_duration_p95_last_minute_seconds 0.042\n";
+        assert_eq!(summarize(body).as_deref(), Some("2.00 req/s, p95 42 ms, 2.5% errors (last minute)"));
+        assert_eq!(summarize("db_queries_total 4\n"), None);
+    }
+}
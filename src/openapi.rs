@@ -0,0 +1,576 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::codegen_ignore::Generator;
+use crate::configs::Config;
+use crate::logger;
+use crate::models::{to_pascal, TableInfo};
+use crate::routes::RouteInfo;
+
+// Written next to Cargo.toml so it can be committed and diffed
+pub const OPENAPI_PATH: &str = "openapi.yaml";
+
+// Controllers blast wrote start with this line (`blast gen auth`, `blast gen health`, ...)
+const GENERATED_MARKER: &str = "// Generated by `blast gen";
+
+// Responses a ModelError turns into through the app's registered catchers
+const MODEL_ERRORS: &[(&str, &str)] = &[("404", "NotFound"), ("409", "Conflict"), ("422", "UnprocessableEntity"), ("500", "InternalServerError")];
+
+// What a handler's doc comment can say. `/// @openapi [summary]` includes a hand-written route
+#[derive(Debug, Default, PartialEq)]
+struct Annotations {
+    included: bool,
+    skipped: bool,
+    summary: Option<String>,
+    description: Vec<String>,
+    tags: Vec<String>,
+    body: Option<String>,
+    responses: Vec<(String, Option<String>)>,
+}
+
+// The parts of a handler the spec is built from, read from its source
+#[derive(Debug, Default)]
+struct Signature {
+    params: Vec<(String, String)>,
+    returns: Option<String>,
+    data: Option<String>,
+    annotations: Annotations,
+}
+
+// `blast gen openapi`
+pub fn generate(config: &Config) -> Result<(), String> {
+    let (spec, operations) = render(config)?;
+    crate::file_writer::write(config.project_dir.join(OPENAPI_PATH), &spec)?;
+    logger::success(&format!("Wrote {} with {} operation(s)", OPENAPI_PATH, operations))
+}
+
+// `blast check openapi`: fails when openapi.yaml doesn't match what `blast gen openapi` would write now
+pub fn check(config: &Config) -> Result<(), String> {
+    let (spec, _) = render(config)?;
+    match fs::read_to_string(config.project_dir.join(OPENAPI_PATH)) {
+        Ok(current) if current == spec => logger::success(&format!("{} is up to date", OPENAPI_PATH)),
+        Ok(_) => Err(format!("{} is stale. Run 'blast gen openapi' and commit the result", OPENAPI_PATH)),
+        Err(_) => Err(format!("{} doesn't exist. Run 'blast gen openapi' to create it", OPENAPI_PATH)),
+    }
+}
+
+// The spec as YAML plus the number of operations in it
+fn render(config: &Config) -> Result<(String, usize), String> {
+    let codegen = config.codegen();
+    let tables: Vec<TableInfo> = crate::models::load_schema_table_info(config, codegen.schema_path())
+        .map_err(|e| format!("Failed to read {}: {}", codegen.schema_path(), e))?
+        .into_iter()
+        .filter(|table| codegen.schema.includes(&table.name) && crate::codegen_ignore::ignored_by(&codegen, Generator::Structs, &table.name).is_none())
+        .collect();
+
+    let migration_columns = crate::migration_columns::MigrationColumns::load(config);
+    let mut schemas = Map::new();
+    schemas.insert("Error".to_string(), error_schema());
+    for table in &tables {
+        let name = to_pascal(&table.name);
+        schemas.insert(name.clone(), table_schema(table, false, &[], &[]));
+        if !crate::codegen_ignore::skips(&codegen, Generator::Insertable, &table.name) {
+            let skipped: Vec<String> = table.primary_key.iter().cloned().chain(migration_columns.generated(&table.name)).collect();
+            schemas.insert(format!("New{}", name), table_schema(table, true, &skipped, &migration_columns.defaulted(&table.name)));
+        }
+    }
+
+    let mut sources: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let mut paths = Map::new();
+    let mut operations = 0;
+    let mut unmounted = 0;
+    for route in crate::routes::scan(config)? {
+        let source = sources.entry(route.file.clone()).or_insert_with(|| fs::read_to_string(config.project_dir.join(&route.file)).ok());
+        let Some(source) = source.as_deref() else {
+            continue;
+        };
+        let signature = read_signature(source, route.line);
+        let generated = source.lines().take_while(|text| text.starts_with("//") || text.trim().is_empty()).any(|text| text.starts_with(GENERATED_MARKER));
+        if !(generated || signature.annotations.included) || signature.annotations.skipped {
+            continue;
+        }
+        if !route.mounted {
+            unmounted += 1;
+            continue;
+        }
+
+        let (path, path_params, query_params) = openapi_path(&route.path);
+        let entry = paths.entry(path).or_insert_with(|| Value::Object(Map::new()));
+        let method = route.method.to_lowercase();
+        if entry.get(&method).is_some() {
+            // Ranked routes share a path; the first one describes it
+            continue;
+        }
+        entry[&method] = operation(&route, &signature, &path_params, &query_params, &schemas);
+        operations += 1;
+    }
+    if unmounted > 0 {
+        logger::warning(&format!("{} documented route(s) aren't mounted and were left out of {}. See `blast routes`", unmounted, OPENAPI_PATH))?;
+    }
+
+    let mut responses = Map::new();
+    for (status, name) in MODEL_ERRORS {
+        responses.insert(name.to_string(), json!({ "description": status_description(status), "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }));
+    }
+
+    let version = fs::read_to_string(config.project_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Value>().ok())
+        .and_then(|cargo| cargo.get("package")?.get("version")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "0.0.0".to_string());
+
+    let mut out = String::from("# Generated by `blast gen openapi` from schema.rs and the routes under src/. `blast check openapi` fails when it's stale\n");
+    out.push_str(&to_yaml(&json!({ "openapi": "3.0.3" }), 0));
+    out.push_str(&to_yaml(&json!({ "info": { "title": config.project_name, "version": version } }), 0));
+    out.push_str(&to_yaml(&json!({ "paths": paths }), 0));
+    out.push_str(&to_yaml(&json!({ "components": { "schemas": schemas, "responses": responses } }), 0));
+    Ok((out, operations))
+}
+
+// The body the registered catchers send for a failed request
+fn error_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["status", "message"],
+        "properties": {
+            "status": { "type": "integer", "format": "int32" },
+            "message": { "type": "string" },
+        },
+    })
+}
+
+// Object schema for a table's struct, or with `skipped` columns for its insertable (which drops keys and defaults)
+fn table_schema(table: &TableInfo, insertable: bool, skipped: &[String], defaulted: &[String]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for column in table.columns.iter().filter(|column| !skipped.contains(&column.name)) {
+        let mut schema = diesel_type_schema(&column.column_type);
+        if column.nullable {
+            schema["nullable"] = json!(true);
+        }
+        // Struct fields are always serialized; an insertable's Option fields, nullable or with a DEFAULT, may be left out
+        if !insertable || !(column.nullable || defaulted.contains(&column.name)) {
+            required.push(json!(column.name));
+        }
+        properties.insert(column.name.clone(), schema);
+    }
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+// Int4 -> integer/int32, Timestamptz -> string/date-time, Array<Text> -> array of strings
+fn diesel_type_schema(column_type: &str) -> Value {
+    let column_type = column_type.trim();
+    if let Some(inner) = column_type.strip_prefix("Array<").and_then(|rest| rest.strip_suffix('>')) {
+        let inner = inner.trim().strip_prefix("Nullable<").and_then(|rest| rest.strip_suffix('>')).unwrap_or(inner);
+        return json!({ "type": "array", "items": diesel_type_schema(inner) });
+    }
+
+    let name = column_type.rsplit("::").next().unwrap_or(column_type);
+    match name {
+        "Int2" | "SmallInt" | "Int4" | "Integer" => json!({ "type": "integer", "format": "int32" }),
+        "Int8" | "BigInt" => json!({ "type": "integer", "format": "int64" }),
+        "Float4" | "Float" => json!({ "type": "number", "format": "float" }),
+        "Float8" | "Double" => json!({ "type": "number", "format": "double" }),
+        "Numeric" => json!({ "type": "string", "format": "decimal" }),
+        "Bool" => json!({ "type": "boolean" }),
+        "Text" | "Varchar" | "VarChar" | "Char" | "Bpchar" | "Citext" => json!({ "type": "string" }),
+        "Uuid" => json!({ "type": "string", "format": "uuid" }),
+        "Date" => json!({ "type": "string", "format": "date" }),
+        "Time" => json!({ "type": "string", "format": "time" }),
+        "Timestamp" | "Timestamptz" => json!({ "type": "string", "format": "date-time" }),
+        "Json" | "Jsonb" => json!({}),
+        "Bytea" | "Binary" => json!({ "type": "array", "items": { "type": "integer", "format": "int32" } }),
+        "Inet" | "Cidr" | "MacAddr" => json!({ "type": "string" }),
+        other => json!({ "description": format!("Diesel type {}", other) }),
+    }
+}
+
+// Schema for a Rust type in a handler signature. Generated structs and insertables become $refs
+fn rust_type_schema(rust_type: &str, schemas: &Map<String, Value>) -> Value {
+    let rust_type = rust_type.trim().trim_start_matches('&').trim_start_matches("'static ").trim();
+    let (outer, args) = split_generic(rust_type);
+    let name = outer.rsplit("::").next().unwrap_or(outer);
+    match (name, args.as_slice()) {
+        ("Option", [inner]) => {
+            let mut schema = rust_type_schema(inner, schemas);
+            if schema.get("$ref").is_some() {
+                schema = json!({ "allOf": [schema] });
+            }
+            schema["nullable"] = json!(true);
+            schema
+        }
+        ("Vec", [inner]) => json!({ "type": "array", "items": rust_type_schema(inner, schemas) }),
+        ("Json" | "Form" | "Box" | "Arc", [inner]) => rust_type_schema(inner, schemas),
+        ("i8" | "i16" | "i32" | "u8" | "u16" | "u32", _) => json!({ "type": "integer", "format": "int32" }),
+        ("i64" | "u64" | "isize" | "usize", _) => json!({ "type": "integer", "format": "int64" }),
+        ("f32", _) => json!({ "type": "number", "format": "float" }),
+        ("f64", _) => json!({ "type": "number", "format": "double" }),
+        ("bool", _) => json!({ "type": "boolean" }),
+        ("String" | "str", _) => json!({ "type": "string" }),
+        ("Uuid", _) => json!({ "type": "string", "format": "uuid" }),
+        ("NaiveDate", _) => json!({ "type": "string", "format": "date" }),
+        ("NaiveDateTime" | "DateTime", _) => json!({ "type": "string", "format": "date-time" }),
+        ("Value", _) => json!({}),
+        _ if schemas.contains_key(name) => json!({ "$ref": format!("#/components/schemas/{}", name) }),
+        _ => json!({ "type": "object" }),
+    }
+}
+
+// "Result<Json<Users>, ModelError>" -> ("Result", ["Json<Users>", "ModelError"]); "(Status, Value)" -> ("", ["Status", "Value"])
+fn split_generic(rust_type: &str) -> (&str, Vec<&str>) {
+    let (outer, inner) = if let Some(inner) = rust_type.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        ("", inner)
+    } else {
+        match rust_type.find('<').filter(|_| rust_type.ends_with('>')) {
+            Some(open) => (rust_type[..open].trim(), &rust_type[open + 1..rust_type.len() - 1]),
+            None => return (rust_type, Vec::new()),
+        }
+    };
+
+    let mut args = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (index, ch) in inner.char_indices() {
+        match ch {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(inner[start..].trim());
+    args.retain(|arg| !arg.is_empty() && !arg.starts_with('\''));
+    (outer, args)
+}
+
+// "/users/<id>?<page>&<q..>" -> ("/users/{id}", ["id"], ["page", "q"])
+fn openapi_path(route_path: &str) -> (String, Vec<String>, Vec<String>) {
+    let (path, query) = route_path.split_once('?').unwrap_or((route_path, ""));
+    let dynamic = |segment: &str| segment.strip_prefix('<').and_then(|rest| rest.strip_suffix('>')).map(|name| name.trim_end_matches("..").to_string());
+
+    let mut path_params = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| match dynamic(segment) {
+            Some(name) => {
+                path_params.push(name.clone());
+                format!("{{{}}}", name)
+            }
+            None => segment.to_string(),
+        })
+        .collect();
+    let query_params = query.split('&').filter_map(dynamic).collect();
+
+    let path = segments.join("/");
+    (if path.is_empty() { "/".to_string() } else { path }, path_params, query_params)
+}
+
+fn operation(route: &RouteInfo, signature: &Signature, path_params: &[String], query_params: &[String], schemas: &Map<String, Value>) -> Value {
+    let annotations = &signature.annotations;
+    let param_type = |name: &str| signature.params.iter().find(|(param, _)| param == name).map(|(_, rust_type)| rust_type.as_str());
+
+    let mut operation = Map::new();
+    operation.insert("operationId".to_string(), json!(format!("{}_{}", route.module.replace("::", "_"), route.handler)));
+    let tags = if annotations.tags.is_empty() { vec![route.module.rsplit("::").next().unwrap_or("crate").to_string()] } else { annotations.tags.clone() };
+    operation.insert("tags".to_string(), json!(tags));
+    if let Some(summary) = &annotations.summary {
+        operation.insert("summary".to_string(), json!(summary));
+    }
+    if !annotations.description.is_empty() {
+        operation.insert("description".to_string(), json!(annotations.description.join("\n")));
+    }
+
+    let mut parameters = Vec::new();
+    for (location, names) in [("path", path_params), ("query", query_params)] {
+        for name in names {
+            let rust_type = param_type(name).unwrap_or("String");
+            let optional = location == "query" && split_generic(rust_type).0 == "Option";
+            let inner = if optional { split_generic(rust_type).1.first().copied().unwrap_or("String") } else { rust_type };
+            parameters.push(json!({ "name": name, "in": location, "required": !optional, "schema": rust_type_schema(inner, schemas) }));
+        }
+    }
+    if !parameters.is_empty() {
+        operation.insert("parameters".to_string(), Value::Array(parameters));
+    }
+
+    let body = annotations.body.clone().or_else(|| signature.data.as_deref().and_then(param_type).map(str::to_string));
+    if let Some(body) = body {
+        let content_type = if split_generic(&body).0.ends_with("Form") { "application/x-www-form-urlencoded" } else { "application/json" };
+        operation.insert("requestBody".to_string(), json!({ "required": true, "content": { content_type: { "schema": rust_type_schema(&body, schemas) } } }));
+    }
+
+    let mut responses = Map::new();
+    if annotations.responses.is_empty() {
+        success_and_errors(signature.returns.as_deref().unwrap_or("()"), schemas, &mut responses);
+    } else {
+        for (status, rust_type) in &annotations.responses {
+            let mut response = json!({ "description": status_description(status) });
+            if let Some(rust_type) = rust_type {
+                response["content"] = json!({ "application/json": { "schema": rust_type_schema(rust_type, schemas) } });
+            }
+            responses.insert(status.clone(), response);
+        }
+    }
+    operation.insert("responses".to_string(), Value::Object(responses));
+    Value::Object(operation)
+}
+
+// Responses implied by a handler's return type
+fn success_and_errors(returns: &str, schemas: &Map<String, Value>, responses: &mut Map<String, Value>) {
+    let (outer, args) = split_generic(returns.trim());
+    match (outer.rsplit("::").next().unwrap_or(outer), args.as_slice()) {
+        ("Result", [ok, error]) => {
+            success_and_errors(ok, schemas, responses);
+            if split_generic(error).0.ends_with("ModelError") {
+                for (status, name) in MODEL_ERRORS {
+                    responses.insert(status.to_string(), json!({ "$ref": format!("#/components/responses/{}", name) }));
+                }
+            } else {
+                responses.insert("default".to_string(), json!({ "description": "Error", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }));
+            }
+        }
+        ("Option", [inner]) => {
+            success_and_errors(inner, schemas, responses);
+            responses.insert("404".to_string(), json!({ "$ref": "#/components/responses/NotFound" }));
+        }
+        // (Status, body): the status varies, the body doesn't
+        ("", [status, body]) if status.ends_with("Status") => success_and_errors(body, schemas, responses),
+        ("Template", _) => {
+            responses.insert("200".to_string(), json!({ "description": "OK", "content": { "text/html": { "schema": { "type": "string" } } } }));
+        }
+        ("Redirect", _) => {
+            responses.insert("303".to_string(), json!({ "description": "Redirect" }));
+        }
+        ("Status" | "()", _) | ("", []) => {
+            responses.insert("200".to_string(), json!({ "description": "OK" }));
+        }
+        ("String" | "&str" | "&'static str" | "RawHtml", _) => {
+            responses.insert("200".to_string(), json!({ "description": "OK", "content": { "text/plain": { "schema": { "type": "string" } } } }));
+        }
+        _ => {
+            responses.insert("200".to_string(), json!({ "description": "OK", "content": { "application/json": { "schema": rust_type_schema(returns, schemas) } } }));
+        }
+    }
+}
+
+fn status_description(status: &str) -> &'static str {
+    match status {
+        "200" => "OK",
+        "201" => "Created",
+        "204" => "No Content",
+        "400" => "Bad Request",
+        "401" => "Unauthorized",
+        "403" => "Forbidden",
+        "404" => "Not Found",
+        "409" => "Conflict",
+        "422" => "Unprocessable Entity",
+        "500" => "Internal Server Error",
+        "503" => "Service Unavailable",
+        _ => "Response",
+    }
+}
+
+// Doc comment, parameters, return type and data guard of the handler whose route attribute is on `line` (1-based)
+fn read_signature(source: &str, line: usize) -> Signature {
+    let lines: Vec<&str> = source.lines().collect();
+    let start = line.saturating_sub(1).min(lines.len());
+
+    // Doc comments and other attributes above the route attribute, nearest last
+    let mut docs = Vec::new();
+    for text in lines[..start].iter().rev().map(|text| text.trim()) {
+        if let Some(doc) = text.strip_prefix("///") {
+            docs.push(doc.strip_prefix(' ').unwrap_or(doc).trim_end());
+        } else if !text.starts_with("#[") {
+            break;
+        }
+    }
+    docs.reverse();
+
+    // Everything from the attribute to the body's opening brace
+    let rest = lines[start..].join("\n");
+    let Some(fn_start) = Regex::new(r"\bfn\s+\w+").ok().and_then(|re| re.find(&rest)) else {
+        return Signature { annotations: parse_annotations(&docs), ..Default::default() };
+    };
+    let data = Regex::new(r#"data\s*=\s*"<(\w+)>""#).ok().and_then(|re| re.captures(&rest[..fn_start.start()])).map(|capture| capture[1].to_string());
+
+    let after = &rest[fn_start.end()..];
+    let (params, returns) = match after.find('(') {
+        Some(open) => {
+            let mut depth = 0;
+            let close = after[open..].char_indices().find_map(|(index, ch)| {
+                match ch {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(open + index)
+            });
+            let close = close.unwrap_or(after.len() - 1);
+            let tail = &after[close + 1..];
+            let returns = tail[..tail.find(['{', ';']).unwrap_or(tail.len())].split(" where ").next().unwrap_or_default().trim().strip_prefix("->").map(|ty| ty.split_whitespace().collect::<Vec<_>>().join(" "));
+            let (_, params) = split_generic(&after[open..=close]);
+            let params = params
+                .into_iter()
+                .filter_map(|param| {
+                    let (name, rust_type) = param.split_once(':')?;
+                    Some((name.trim().trim_start_matches("mut ").to_string(), rust_type.split_whitespace().collect::<Vec<_>>().join(" ")))
+                })
+                .collect();
+            (params, returns)
+        }
+        None => (Vec::new(), None),
+    };
+
+    Signature { params, returns, data, annotations: parse_annotations(&docs) }
+}
+
+// `@openapi [summary]`, `@summary`, `@tag`, `@body <Type>` and `@response <status> [Type]`; other lines are the description
+fn parse_annotations(docs: &[&str]) -> Annotations {
+    let mut annotations = Annotations::default();
+    for doc in docs {
+        let (keyword, value) = doc.split_once(char::is_whitespace).map(|(keyword, value)| (keyword, value.trim())).unwrap_or((doc, ""));
+        match keyword {
+            "@openapi" if value == "skip" => annotations.skipped = true,
+            "@openapi" => {
+                annotations.included = true;
+                if !value.is_empty() {
+                    annotations.summary = Some(value.to_string());
+                }
+            }
+            "@summary" => annotations.summary = Some(value.to_string()),
+            "@tag" => annotations.tags.push(value.to_string()),
+            "@body" => annotations.body = Some(value.to_string()),
+            "@response" => {
+                let (status, rust_type) = value.split_once(char::is_whitespace).map(|(status, ty)| (status, Some(ty.trim().to_string()))).unwrap_or((value, None));
+                annotations.responses.push((status.to_string(), rust_type));
+            }
+            _ if keyword.starts_with('@') => {}
+            _ if !doc.is_empty() => annotations.description.push(doc.to_string()),
+            _ => {}
+        }
+    }
+    annotations
+}
+
+// Block-style YAML with keys in the order serde_json keeps them (sorted), so the same input gives the same file
+fn to_yaml(value: &Value, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let mut out = String::new();
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match child {
+                    Value::Object(inner) if !inner.is_empty() => out.push_str(&format!("{}{}:\n{}", pad, yaml_key(key), to_yaml(child, indent + 2))),
+                    Value::Array(items) if !items.is_empty() => out.push_str(&format!("{}{}:\n{}", pad, yaml_key(key), to_yaml(child, indent))),
+                    _ => out.push_str(&format!("{}{}: {}\n", pad, yaml_key(key), yaml_scalar(child))),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Object(inner) if !inner.is_empty() => {
+                        let nested = to_yaml(item, indent + 2);
+                        out.push_str(&format!("{}- {}", pad, &nested[indent + 2..]));
+                    }
+                    _ => out.push_str(&format!("{}- {}\n", pad, yaml_scalar(item))),
+                }
+            }
+        }
+        _ => out.push_str(&format!("{}{}\n", pad, yaml_scalar(value))),
+    }
+    out
+}
+
+fn yaml_key(key: &str) -> String {
+    let plain = key.chars().next().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_' || ch == '$') && key.chars().all(|ch| ch.is_ascii_alphanumeric() || "_-.$".contains(ch));
+    if plain {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        Value::String(text) => {
+            let plain = text.chars().next().is_some_and(|ch| ch.is_ascii_alphabetic())
+                && text.chars().all(|ch| ch.is_ascii_alphanumeric() || "_-./ ".contains(ch))
+                && !text.ends_with(' ')
+                && !matches!(text.to_lowercase().as_str(), "true" | "false" | "null" | "yes" | "no" | "on" | "off" | "y" | "n");
+            if plain {
+                text.clone()
+            } else {
+                value.to_string()
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handler_signatures_become_operations() {
+        let source = r#"use rocket::serde::json::Json;
+
+/// @openapi List a user's invoices
+/// Newest first.
+/// @tag billing
+#[get("/users/<id>/invoices?<page>")]
+pub async fn invoices(id: i32, page: Option<i64>) -> Result<Json<Vec<Invoices>>, ModelError> {
+    todo!()
+}
+"#;
+        let signature = read_signature(source, 6);
+        assert_eq!(signature.params, vec![("id".to_string(), "i32".to_string()), ("page".to_string(), "Option<i64>".to_string())]);
+        assert_eq!(signature.returns.as_deref(), Some("Result<Json<Vec<Invoices>>, ModelError>"));
+        assert!(signature.annotations.included);
+        assert_eq!(signature.annotations.summary.as_deref(), Some("List a user's invoices"));
+        assert_eq!(signature.annotations.description, vec!["Newest first."]);
+
+        let mut schemas = Map::new();
+        schemas.insert("Invoices".to_string(), json!({ "type": "object" }));
+        let route = RouteInfo {
+            method: "GET".to_string(),
+            path: "/api/users/<id>/invoices?<page>".to_string(),
+            handler: "invoices".to_string(),
+            module: "routes::billing".to_string(),
+            file: "src/routes/billing.rs".to_string(),
+            line: 6,
+            mounted: true,
+            missing_models: Vec::new(),
+        };
+        let (path, path_params, query_params) = openapi_path(&route.path);
+        assert_eq!(path, "/api/users/{id}/invoices");
+
+        let operation = operation(&route, &signature, &path_params, &query_params, &schemas);
+        assert_eq!(operation["tags"], json!(["billing"]));
+        assert_eq!(operation["parameters"][1], json!({ "name": "page", "in": "query", "required": false, "schema": { "type": "integer", "format": "int64" } }));
+        assert_eq!(operation["responses"]["200"]["content"]["application/json"]["schema"], json!({ "type": "array", "items": { "$ref": "#/components/schemas/Invoices" } }));
+        assert_eq!(operation["responses"]["404"], json!({ "$ref": "#/components/responses/NotFound" }));
+    }
+
+    #[test]
+    fn yaml_output_quotes_what_needs_quoting() {
+        let value = json!({ "paths": { "/users/{id}": { "get": { "responses": { "200": { "$ref": "#/components/responses/NotFound" } }, "tags": ["users", "yes"] } } } });
+        assert_eq!(
+            to_yaml(&value, 0),
+            "paths:\n  \"/users/{id}\":\n    get:\n      responses:\n        \"200\":\n          $ref: \"#/components/responses/NotFound\"\n      tags:\n      - users\n      - \"yes\"\n"
+        );
+    }
+}
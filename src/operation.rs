@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+// The operation running right now, rewritten as it progresses and removed when it ends. The dashboard,
+// `blast status` and external tools (editors, CI wrappers) read these
+const CURRENT_PATH: &str = ".blast/current-operation.json";
+// How the last operation ended
+const LAST_PATH: &str = ".blast/last-operation.json";
+
+// Progress messages arrive far more often than anyone reads the file
+const MESSAGE_WRITE_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepState {
+    Pending,
+    Running,
+    Done,
+    Failed,
+    Skipped,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Step {
+    pub name: String,
+    pub status: StepState,
+}
+
+// Where the time went, from profile::finish
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Timing {
+    pub step: String,
+    pub wall_secs: f64,
+    pub subprocess_secs: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Operation {
+    pub name: String,
+    pub pid: u32,
+    pub started_at: String,
+    pub updated_at: String,
+    pub steps: Vec<Step>,
+    // Share of the steps that finished; unknown for operations without steps
+    pub percent: Option<u8>,
+    pub message: String,
+    // Set once the operation ends: "success", "failed" or "interrupted"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subprocess_secs: Option<f64>,
+    // Slowest first; nested steps are named "assets/scss"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<Timing>,
+}
+
+struct Tracked {
+    project_dir: PathBuf,
+    operation: Operation,
+    started: Instant,
+    last_message_write: Option<Instant>,
+}
+
+lazy_static! {
+    static ref CURRENT: Mutex<Option<Tracked>> = Mutex::new(None);
+}
+
+fn now() -> String {
+    chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+}
+
+fn write(path: &Path, operation: &Operation) {
+    // A status file that can't be written must never fail the operation it describes
+    if let Ok(content) = serde_json::to_string_pretty(operation) {
+        let _ = crate::file_writer::write_state(path, &content);
+    }
+}
+
+// Apply a change to the running operation and write it out. Only message updates are throttled
+fn update(is_message: bool, change: impl FnOnce(&mut Operation)) {
+    let mut current = CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(tracked) = current.as_mut() else {
+        return;
+    };
+
+    change(&mut tracked.operation);
+    if is_message {
+        if tracked.last_message_write.is_some_and(|written| written.elapsed() < MESSAGE_WRITE_INTERVAL) {
+            return;
+        }
+        tracked.last_message_write = Some(Instant::now());
+    }
+
+    let operation = &mut tracked.operation;
+    operation.updated_at = now();
+    if !operation.steps.is_empty() {
+        let finished = operation.steps.iter().filter(|step| !matches!(step.status, StepState::Pending | StepState::Running)).count();
+        operation.percent = Some((finished * 100 / operation.steps.len()) as u8);
+    }
+    write(&tracked.project_dir.join(CURRENT_PATH), operation);
+}
+
+// Start tracking a command. A command started from inside another one (the dashboard running `refresh`)
+// becomes the tracked operation until it ends
+pub fn begin(project_dir: &Path, name: &str) {
+    let started_at = now();
+    let operation = Operation {
+        name: name.to_string(),
+        pid: std::process::id(),
+        started_at: started_at.clone(),
+        updated_at: started_at,
+        steps: Vec::new(),
+        percent: None,
+        message: String::new(),
+        result: None,
+        error: None,
+        finished_at: None,
+        duration_secs: None,
+        subprocess_secs: None,
+        timings: Vec::new(),
+    };
+    write(&project_dir.join(CURRENT_PATH), &operation);
+    *CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Tracked {
+        project_dir: project_dir.to_path_buf(),
+        operation,
+        started: Instant::now(),
+        last_message_write: None,
+    });
+}
+
+// Declare the steps of the running operation, all pending
+pub fn set_steps(steps: &[&str]) {
+    update(false, |operation| operation.steps = steps.iter().map(|name| Step { name: name.to_string(), status: StepState::Pending }).collect());
+}
+
+pub fn step(name: &str, status: StepState) {
+    if status == StepState::Running {
+        crate::logger::summary_step(name);
+    }
+    update(false, |operation| {
+        if let Some(step) = operation.steps.iter_mut().find(|step| step.name == name) {
+            step.status = status;
+        }
+    });
+}
+
+// Latest progress message, from logger::Progress
+pub fn message(message: &str) {
+    update(true, |operation| operation.message = message.to_string());
+}
+
+pub fn set_timings(report: &crate::profile::Report) {
+    update(false, |operation| {
+        operation.subprocess_secs = Some((report.subprocess.as_secs_f64() * 10.0).round() / 10.0);
+        operation.timings = report.steps.clone();
+    });
+}
+
+// Record the outcome in last-operation.json and remove current-operation.json
+pub fn finish(result: &Result<(), String>) {
+    let Some(mut tracked) = CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() else {
+        return;
+    };
+
+    let operation = &mut tracked.operation;
+    let outcome = match result {
+        _ if crate::platform::interrupted() => "interrupted",
+        Ok(()) if operation.steps.iter().any(|step| step.status == StepState::Failed) => "failed",
+        Ok(()) => "success",
+        Err(_) => "failed",
+    };
+    operation.result = Some(outcome.to_string());
+    operation.error = result.as_ref().err().cloned();
+    operation.finished_at = Some(now());
+    operation.updated_at = now();
+    operation.duration_secs = Some((tracked.started.elapsed().as_secs_f64() * 10.0).round() / 10.0);
+    if outcome == "success" {
+        operation.percent = Some(100);
+    }
+
+    write(&tracked.project_dir.join(LAST_PATH), operation);
+    if !crate::file_writer::mode().dry_run {
+        let _ = fs::remove_file(tracked.project_dir.join(CURRENT_PATH));
+    }
+}
+
+fn read(path: &Path) -> Option<Operation> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+// The operation in progress. A file left behind by a blast that was killed doesn't count
+pub fn read_current(project_dir: &Path) -> Option<Operation> {
+    read(&project_dir.join(CURRENT_PATH)).filter(|operation| crate::platform::process_running(operation.pid))
+}
+
+pub fn read_last(project_dir: &Path) -> Option<Operation> {
+    read(&project_dir.join(LAST_PATH))
+}
+
+// One line for status bars: "refresh 42% · structs · Generating structs..."
+pub fn status_line(operation: &Operation) -> String {
+    let mut parts = vec![match operation.percent {
+        Some(percent) => format!("{} {}%", operation.name, percent),
+        None => operation.name.clone(),
+    }];
+    if let Some(step) = operation.steps.iter().find(|step| step.status == StepState::Running) {
+        parts.push(step.name.clone());
+    }
+    if !operation.message.is_empty() {
+        parts.push(operation.message.clone());
+    }
+    parts.join(" · ")
+}
+
+// "refresh failed at 2026-01-05 14:03:10 after 12.4s: ..."
+pub fn outcome_line(operation: &Operation) -> String {
+    let finished = operation.finished_at.as_deref().and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok()).map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string());
+    let mut line = format!("{} {}", operation.name, operation.result.as_deref().unwrap_or("unknown"));
+    if let Some(finished) = finished {
+        line.push_str(&format!(" at {}", finished));
+    }
+    if let Some(duration) = operation.duration_secs {
+        line.push_str(&format!(" after {:.1}s", duration));
+    }
+    if let Some(slowest) = operation.timings.first() {
+        line.push_str(&format!(", slowest step {} ({:.1}s)", slowest.step, slowest.wall_secs));
+    }
+    let failed: Vec<&str> = operation.steps.iter().filter(|step| step.status == StepState::Failed).map(|step| step.name.as_str()).collect();
+    if !failed.is_empty() {
+        line.push_str(&format!(" (failed steps: {})", failed.join(", ")));
+    }
+    if let Some(error) = &operation.error {
+        line.push_str(&format!(": {}", error));
+    }
+    line
+}
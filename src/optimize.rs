@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::fs;
+use std::io::Write;
+use std::os::raw::{c_int, c_ulong, c_void};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use mozjpeg_sys::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::build::format_size;
+use crate::configs::Config;
+use crate::logger;
+
+// Hash of every file under public/ as it was after the last `blast assets optimize`
+const CACHE_PATH: &str = ".blast/assets-cache";
+
+// Re-downloaded by `blast cdn`, so optimizing it would only be undone
+const SKIPPED_DIR: &str = "fonts/fontawesome";
+
+// Text assets served pre-compressed as .gz/.br siblings
+const TEXT_EXTENSIONS: &[&str] = &["css", "js", "svg", "json"];
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct CacheEntry {
+    sha256: String,
+    // Whether a .webp variant was wanted when the file was processed
+    #[serde(default)]
+    webp: bool,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct AssetsCache {
+    #[serde(default)]
+    files: BTreeMap<String, CacheEntry>,
+}
+
+// Files, bytes before and bytes after for one report line
+#[derive(Default)]
+struct Savings {
+    files: usize,
+    before: u64,
+    after: u64,
+}
+
+impl Savings {
+    fn add(&mut self, before: usize, after: usize) {
+        self.files += 1;
+        self.before += before as u64;
+        self.after += after as u64;
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    png: Savings,
+    jpeg: Savings,
+    webp: Savings,
+    gzip: Savings,
+    brotli: Savings,
+}
+
+// `blast assets optimize`: lossless PNG/JPEG recompression, optional .webp variants and .gz/.br siblings
+// for text assets, for everything under public/ that changed since the last run
+pub fn optimize_assets(config: &Config) -> Result<(), String> {
+    let _span = crate::profile::span("optimize");
+    let public_dir = config.project_dir.join(config.public_dir());
+    if !public_dir.exists() {
+        return logger::info(&format!("{} doesn't exist, nothing to optimize", public_dir.display()));
+    }
+
+    let webp = config.assets_config().images.webp;
+    let cache_path = config.project_dir.join(CACHE_PATH);
+    let mut cache: AssetsCache = fs::read_to_string(&cache_path).ok().and_then(|content| toml::from_str(&content).ok()).unwrap_or_default();
+    let mut report = Report::default();
+    let mut unchanged = 0;
+
+    let mut progress = logger::create_progress(None);
+    progress.set_message("Optimizing assets...");
+
+    let files = WalkDir::new(&public_dir).sort_by_file_name().into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file());
+    for entry in files {
+        let path = entry.path();
+        let relative = path.strip_prefix(&public_dir).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        if relative.starts_with(SKIPPED_DIR) {
+            continue;
+        }
+
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        let is_image = matches!(extension.as_str(), "png" | "jpg" | "jpeg");
+        if !is_image && !TEXT_EXTENSIONS.contains(&extension.as_str()) {
+            continue;
+        }
+
+        let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if let Some(cached) = cache.files.get(&relative) {
+            if cached.sha256 == content_hash(&data) && (!is_image || cached.webp == webp) {
+                unchanged += 1;
+                continue;
+            }
+        }
+
+        progress.set_message(&format!("Optimizing {}", relative));
+        let optimized = if is_image {
+            optimize_image(path, &extension, data, webp, &mut report)?
+        } else {
+            precompress(path, &data, &mut report)?;
+            data
+        };
+
+        cache.files.insert(relative, CacheEntry { sha256: content_hash(&optimized), webp });
+    }
+
+    // Forget files that were deleted since the last run
+    cache.files.retain(|relative, _| public_dir.join(relative).exists());
+
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = toml::to_string_pretty(&cache).map_err(|e| format!("Failed to serialize {}: {}", CACHE_PATH, e))?;
+    fs::write(&cache_path, content).map_err(|e| format!("Failed to write {}: {}", CACHE_PATH, e))?;
+
+    progress.success("Assets optimized");
+    for (category, savings) in [("PNG", &report.png), ("JPEG", &report.jpeg), ("WebP", &report.webp), ("gzip", &report.gzip), ("brotli", &report.brotli)] {
+        if savings.files > 0 {
+            logger::plain(&format!(
+                "  {:<7} {:>4} files  {} -> {}  ({} saved)",
+                category,
+                savings.files,
+                format_size(savings.before),
+                format_size(savings.after),
+                format_size(savings.before.saturating_sub(savings.after))
+            ))?;
+        }
+    }
+    if unchanged > 0 {
+        logger::info(&format!("{} files unchanged since the last optimization", unchanged))?;
+    }
+
+    Ok(())
+}
+
+fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+// Recompress an image in place when that makes it smaller and write its .webp variant if wanted.
+// Returns the image as it is now on disk
+fn optimize_image(path: &Path, extension: &str, data: Vec<u8>, webp: bool, report: &mut Report) -> Result<Vec<u8>, String> {
+    let (optimized, savings) = if extension == "png" {
+        (oxipng::optimize_from_memory(&data, &oxipng::Options::from_preset(2)).map_err(|e| e.to_string()), &mut report.png)
+    } else {
+        (optimize_jpeg(&data), &mut report.jpeg)
+    };
+
+    let data = match optimized {
+        Ok(optimized) if optimized.len() < data.len() => {
+            fs::write(path, &optimized).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            savings.add(data.len(), optimized.len());
+            optimized
+        }
+        Ok(_) => data,
+        Err(e) => {
+            logger::warning(&format!("Skipping {}: {}", path.display(), e))?;
+            data
+        }
+    };
+
+    if webp {
+        match encode_webp(extension, &data) {
+            Ok(encoded) => {
+                fs::write(sibling(path, "webp"), &encoded).map_err(|e| format!("Failed to write WebP variant of {}: {}", path.display(), e))?;
+                report.webp.add(data.len(), encoded.len());
+            }
+            Err(e) => logger::warning(&format!("No WebP variant for {}: {}", path.display(), e))?,
+        }
+    }
+
+    Ok(data)
+}
+
+fn encode_webp(extension: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    let image = image::load_from_memory(data).map_err(|e| e.to_string())?;
+    let encoder = webp::Encoder::from_image(&image)?;
+    // PNGs stay lossless; JPEGs are lossy already, so their variant is too
+    let encoded = if extension == "png" { encoder.encode_lossless() } else { encoder.encode(85.0) };
+    Ok(encoded.to_vec())
+}
+
+// Write .gz and .br siblings, each only when it is smaller than the file itself
+fn precompress(path: &Path, data: &[u8], report: &mut Report) -> Result<(), String> {
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::best());
+    gzip.write_all(data).map_err(|e| e.to_string())?;
+    let gzipped = gzip.finish().map_err(|e| e.to_string())?;
+
+    let mut brotli = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+    brotli.write_all(data).map_err(|e| e.to_string())?;
+    let brotlied = brotli.into_inner();
+
+    for (compressed, extension, savings) in [(gzipped, "gz", &mut report.gzip), (brotlied, "br", &mut report.brotli)] {
+        let sibling = sibling(path, extension);
+        if compressed.len() < data.len() {
+            fs::write(&sibling, &compressed).map_err(|e| format!("Failed to write {}: {}", sibling.display(), e))?;
+            savings.add(data.len(), compressed.len());
+        } else if sibling.exists() {
+            // An older sibling would be served instead of the changed file
+            fs::remove_file(&sibling).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+// app.css -> app.css.gz, logo.png -> logo.png.webp
+fn sibling(path: &Path, extension: &str) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    name.into()
+}
+
+const JPEG_APP1: c_int = 0xE1; // EXIF, including the orientation
+const JPEG_APP2: c_int = 0xE2; // ICC color profile
+
+// Lossless JPEG recompression like `jpegtran -optimize -progressive`: the DCT coefficients are copied
+// as they are and only the entropy coding is redone
+fn optimize_jpeg(data: &[u8]) -> Result<Vec<u8>, String> {
+    std::panic::catch_unwind(|| unsafe { transcode_jpeg(data) }).map_err(|e| e.downcast_ref::<String>().cloned().unwrap_or_else(|| "invalid JPEG".to_string()))
+}
+
+// libjpeg reports errors through error_exit, which must not return; unwinding out of it
+// lets optimize_jpeg turn the error into a Result
+unsafe extern "C-unwind" fn jpeg_error_exit(cinfo: &mut jpeg_common_struct) {
+    let buffer = [0u8; 80];
+    if let Some(format_message) = (*cinfo.err).format_message {
+        format_message(cinfo, &buffer);
+    }
+    let message = CStr::from_bytes_until_nul(&buffer).map(|message| message.to_string_lossy().into_owned()).unwrap_or_default();
+    std::panic::resume_unwind(Box::new(message));
+}
+
+// Corrupt-data warnings would otherwise go straight to stderr
+unsafe extern "C-unwind" fn jpeg_ignore_message(_cinfo: &mut jpeg_common_struct) {}
+
+// Owns both libjpeg objects so they are destroyed even when a libjpeg error unwinds
+struct JpegTranscoder {
+    src: Box<jpeg_decompress_struct>,
+    dst: Box<jpeg_compress_struct>,
+    errors: Box<[jpeg_error_mgr; 2]>,
+}
+
+impl Drop for JpegTranscoder {
+    fn drop(&mut self) {
+        unsafe {
+            jpeg_destroy_decompress(&mut self.src);
+            jpeg_destroy_compress(&mut self.dst);
+        }
+    }
+}
+
+unsafe fn transcode_jpeg(data: &[u8]) -> Vec<u8> {
+    let mut jpeg = JpegTranscoder { src: Box::new(std::mem::zeroed()), dst: Box::new(std::mem::zeroed()), errors: Box::new(std::mem::zeroed()) };
+    for error in jpeg.errors.iter_mut() {
+        jpeg_std_error(error);
+        error.error_exit = Some(jpeg_error_exit);
+        error.output_message = Some(jpeg_ignore_message);
+    }
+    jpeg.src.common.err = &mut jpeg.errors[0];
+    jpeg.dst.common.err = &mut jpeg.errors[1];
+    jpeg_create_decompress(&mut *jpeg.src);
+    jpeg_create_compress(&mut *jpeg.dst);
+
+    jpeg_mem_src(&mut jpeg.src, data.as_ptr(), data.len() as c_ulong);
+    for marker in [JPEG_APP1, JPEG_APP2] {
+        jpeg_save_markers(&mut jpeg.src, marker, 0xFFFF);
+    }
+    jpeg_read_header(&mut jpeg.src, 1);
+    let coefficients = jpeg_read_coefficients(&mut jpeg.src);
+
+    jpeg_copy_critical_parameters(&jpeg.src, &mut jpeg.dst);
+    jpeg.dst.optimize_coding = 1;
+    jpeg_simple_progression(&mut jpeg.dst);
+
+    let mut buffer: *mut u8 = std::ptr::null_mut();
+    let mut size: c_ulong = 0;
+    jpeg_mem_dest(&mut jpeg.dst, &mut buffer, &mut size);
+    jpeg_write_coefficients(&mut jpeg.dst, coefficients);
+
+    let mut marker = jpeg.src.marker_list;
+    while !marker.is_null() {
+        jpeg_write_marker(&mut jpeg.dst, (*marker).marker as c_int, (*marker).data, (*marker).data_length);
+        marker = (*marker).next;
+    }
+
+    jpeg_finish_compress(&mut jpeg.dst);
+    jpeg_finish_decompress(&mut jpeg.src);
+
+    let optimized = std::slice::from_raw_parts(buffer, size as usize).to_vec();
+    libc::free(buffer as *mut c_void);
+    optimized
+}
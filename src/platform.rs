@@ -0,0 +1,417 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::logger;
+
+// Whether a program is on PATH: `which` on Unix, `where` on Windows
+pub fn command_exists(name: &str) -> bool {
+    let finder = if cfg!(windows) { "where" } else { "which" };
+    logger::command_output(finder, Command::new(finder).arg(name)).map(|output| output.status.success()).unwrap_or(false)
+}
+
+// Full path of a program on PATH, as the finder reports it
+pub fn command_path(name: &str) -> Option<String> {
+    let finder = if cfg!(windows) { "where" } else { "which" };
+    let output = logger::command_output(finder, Command::new(finder).arg(name)).ok().filter(|output| output.status.success())?;
+    String::from_utf8_lossy(&output.stdout).lines().next().map(|line| line.trim().to_string())
+}
+
+#[cfg(not(windows))]
+pub fn process_running(pid: u32) -> bool {
+    Command::new("kill").args(["-0", &pid.to_string()]).output().map(|output| output.status.success()).unwrap_or(false)
+}
+
+// There are no signals on Windows, so ask tasklist whether the PID still exists
+#[cfg(windows)]
+pub fn process_running(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(false)
+}
+
+// Ask a process to exit (SIGTERM, or taskkill without /F)
+pub fn terminate(pid: u32) {
+    if cfg!(windows) {
+        let _ = Command::new("taskkill").args(["/PID", &pid.to_string(), "/T"]).output();
+    } else {
+        let _ = Command::new("kill").arg(pid.to_string()).status();
+    }
+}
+
+// Kill a process that ignored terminate, along with its children on Windows
+pub fn force_kill(pid: u32) {
+    if cfg!(windows) {
+        let _ = Command::new("taskkill").args(["/PID", &pid.to_string(), "/T", "/F"]).output();
+    } else {
+        let _ = Command::new("kill").args(["-9", &pid.to_string()]).status();
+    }
+}
+
+// Start `command` detached from the console with stdout and stderr appended to `log_path`, since
+// Windows has no `script` to do it. Returns its PID
+#[cfg(windows)]
+pub fn spawn_logged(command: &mut Command, log_path: &Path) -> Result<u32, String> {
+    use std::fs::{File, OpenOptions};
+    use std::os::windows::process::CommandExt;
+    use std::process::Stdio;
+
+    // Keep the server alive after the console that started it closes
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+
+    let open_log = || -> Result<File, String> { OpenOptions::new().create(true).append(true).open(log_path).map_err(|e| format!("Failed to open {}: {}", log_path.display(), e)) };
+    command.stdin(Stdio::null()).stdout(open_log()?).stderr(open_log()?).creation_flags(CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS);
+
+    command.spawn().map(|child| child.id()).map_err(|e| format!("Failed to start {:?}: {}", command.get_program(), e))
+}
+
+// Which package provides pg_dump/pg_restore on this machine and how to install it
+pub fn postgres_client_hint() -> String {
+    if cfg!(target_os = "macos") {
+        return "brew install libpq && brew link --force libpq".to_string();
+    }
+    if cfg!(windows) {
+        return "winget install PostgreSQL.PostgreSQL, then add its bin folder to PATH".to_string();
+    }
+
+    // ID and ID_LIKE from os-release, e.g. "ubuntu debian"
+    let distro = std::fs::read_to_string("/etc/os-release")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.strip_prefix("ID=").or_else(|| line.strip_prefix("ID_LIKE=")))
+        .map(|value| value.trim_matches('"').to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let is = |id: &str| distro.split_whitespace().any(|word| word == id);
+
+    if is("debian") || is("ubuntu") {
+        "sudo apt install postgresql-client".to_string()
+    } else if is("fedora") || is("rhel") || is("centos") {
+        "sudo dnf install postgresql".to_string()
+    } else if is("arch") {
+        "sudo pacman -S postgresql-libs".to_string()
+    } else if is("alpine") {
+        "sudo apk add postgresql-client".to_string()
+    } else if is("suse") || is("opensuse") {
+        "sudo zypper install postgresql".to_string()
+    } else {
+        "install your distribution's PostgreSQL client package (usually postgresql-client)".to_string()
+    }
+}
+
+// Per-user configuration directory: %APPDATA% on Windows, ~/Library/Application Support on macOS,
+// $XDG_CONFIG_HOME or ~/.config elsewhere
+pub fn user_config_dir() -> Option<PathBuf> {
+    let from_env = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    if cfg!(windows) {
+        from_env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        from_env("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        from_env("XDG_CONFIG_HOME").or_else(|| from_env("HOME").map(|home| home.join(".config")))
+    }
+}
+
+#[cfg(unix)]
+pub fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+// Directory symlinks need developer mode or an elevated prompt on Windows
+#[cfg(windows)]
+pub fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(original, link)
+}
+
+// PID listening on a port and the project it was started from (its working directory's Cargo.toml)
+pub fn port_owner(port: u16) -> Option<(u32, String)> {
+    let pid = listening_pid(port)?;
+    let project = process_cwd(pid)
+        .map(|cwd| {
+            std::fs::read_to_string(cwd.join("Cargo.toml"))
+                .ok()
+                .and_then(|content| content.parse::<toml::Value>().ok())
+                .and_then(|cargo| cargo.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()).map(str::to_string))
+                .unwrap_or_else(|| cwd.display().to_string())
+        })
+        .unwrap_or_else(|| "unknown project".to_string());
+    Some((pid, project))
+}
+
+#[cfg(unix)]
+fn listening_pid(port: u16) -> Option<u32> {
+    let output = Command::new("lsof").args(["-nP", "-t", &format!("-iTCP:{}", port), "-sTCP:LISTEN"]).output().ok()?;
+    String::from_utf8_lossy(&output.stdout).lines().next()?.trim().parse().ok()
+}
+
+// netstat -ano lines are: protocol, local address, foreign address, state, PID
+#[cfg(windows)]
+fn listening_pid(port: u16) -> Option<u32> {
+    let output = Command::new("netstat").args(["-ano", "-p", "TCP"]).output().ok()?;
+    let suffix = format!(":{}", port);
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [_, local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+        _ => None,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+// No /proc elsewhere; lsof -Fn prints the directory on a line starting with n
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let output = Command::new("lsof").args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"]).output().ok()?;
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| line.strip_prefix('n')).map(PathBuf::from)
+}
+
+// Windows doesn't expose another process's working directory without reading its memory
+#[cfg(windows)]
+fn process_cwd(_pid: u32) -> Option<PathBuf> {
+    None
+}
+
+// How to start PostgreSQL on this machine when it isn't running; the project's compose file comes first
+pub fn postgres_start_hint(project_dir: &Path) -> &'static str {
+    let has_compose_file = ["docker-compose.yml", "docker-compose.yaml", "compose.yml", "compose.yaml"].iter().any(|file| project_dir.join(file).exists());
+
+    if has_compose_file {
+        "docker compose up -d"
+    } else if cfg!(target_os = "macos") {
+        "brew services start postgresql"
+    } else if cfg!(windows) {
+        "pg_ctl start -D \"%PGDATA%\", or start the postgresql-x64-<version> service from services.msc"
+    } else if command_exists("systemctl") {
+        "sudo systemctl start postgresql"
+    } else {
+        "sudo service postgresql start"
+    }
+}
+
+// Ctrl-C handling for long commands (`blast init`, migrate, cdn, spark add...). While the guard is alive
+// the signal only sets a flag, children started through logger::command_output get a process group
+// of their own, and a watcher stops those groups so the command can record where it got to and its
+// TempPath guards clean up on the way out
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static HANDLING_INTERRUPTS: AtomicBool = AtomicBool::new(false);
+// Guards alive; a command that takes one inside another's (init running migrate) shares the outer handler
+static GUARDS: AtomicUsize = AtomicUsize::new(0);
+static CHILDREN: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+pub struct InterruptGuard;
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(_: libc::c_int) {
+    // A second Ctrl-C gives up on the clean shutdown
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        unsafe { libc::_exit(130) };
+    }
+}
+
+#[cfg(unix)]
+pub fn handle_interrupts() -> InterruptGuard {
+    if GUARDS.fetch_add(1, Ordering::SeqCst) > 0 {
+        return InterruptGuard;
+    }
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    HANDLING_INTERRUPTS.store(true, Ordering::SeqCst);
+    unsafe {
+        libc::signal(libc::SIGINT, on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        libc::signal(libc::SIGTERM, on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+
+    std::thread::spawn(|| {
+        while HANDLING_INTERRUPTS.load(Ordering::SeqCst) {
+            if INTERRUPTED.load(Ordering::SeqCst) {
+                stop_children();
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    });
+    InterruptGuard
+}
+
+// Ctrl-C already reaches every process attached to the console on Windows
+#[cfg(windows)]
+pub fn handle_interrupts() -> InterruptGuard {
+    if GUARDS.fetch_add(1, Ordering::SeqCst) == 0 {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+    InterruptGuard
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        if GUARDS.fetch_sub(1, Ordering::SeqCst) > 1 {
+            return;
+        }
+        HANDLING_INTERRUPTS.store(false, Ordering::SeqCst);
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+            libc::signal(libc::SIGTERM, libc::SIG_DFL);
+        }
+    }
+}
+
+// Whether Ctrl-C was pressed since handle_interrupts
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+// For the points between steps where a long command can stop: `platform::check_interrupted()?`
+pub fn check_interrupted() -> Result<(), String> {
+    if interrupted() {
+        return Err("Interrupted".to_string());
+    }
+    Ok(())
+}
+
+// After a command returned: when Ctrl-C stopped it, clear its progress bars, give the cursor back and exit with 130
+pub fn exit_if_interrupted() {
+    if !interrupted() {
+        return;
+    }
+    logger::clear_progress();
+    for term in [console::Term::stdout(), console::Term::stderr()] {
+        if term.is_term() {
+            let _ = term.show_cursor();
+        }
+    }
+    eprintln!("Interrupted");
+    std::process::exit(130);
+}
+
+// A temporary file or directory, removed when the guard drops: on success, on an early error return and
+// when Ctrl-C unwinds the command alike. Renaming it into place first keeps it
+pub struct TempPath {
+    path: PathBuf,
+}
+
+impl TempPath {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        TempPath { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // Gone already when it was renamed into place
+        let _ = match self.path.symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => std::fs::remove_dir_all(&self.path),
+            Ok(_) => std::fs::remove_file(&self.path),
+            Err(_) => Ok(()),
+        };
+    }
+}
+
+// Run a client that takes over the terminal until it exits, e.g. psql. Ctrl-C belongs to the client then (psql
+// cancels the running query), so blast ignores it meanwhile instead of exiting underneath it
+pub fn run_attached(tool: &str, command: &mut Command) -> std::io::Result<std::process::ExitStatus> {
+    crate::logger::debug_command(tool, command);
+    let started = std::time::Instant::now();
+    let result = command.spawn().and_then(|mut child| {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_IGN);
+            libc::signal(libc::SIGQUIT, libc::SIG_IGN);
+        }
+        let status = child.wait();
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+            libc::signal(libc::SIGQUIT, libc::SIG_DFL);
+        }
+        status
+    });
+    crate::tool_log::record_status(tool, command, started, &result);
+    result
+}
+
+// Give a child about to be spawned its own process group, so stopping it also stops whatever it started
+pub fn isolate_child(command: &mut Command) {
+    #[cfg(unix)]
+    if HANDLING_INTERRUPTS.load(Ordering::SeqCst) {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    let _ = command;
+}
+
+pub fn track_child(pid: u32) {
+    if !HANDLING_INTERRUPTS.load(Ordering::SeqCst) {
+        return;
+    }
+    CHILDREN.lock().unwrap().push(pid);
+    // Spawned just as the watcher finished stopping the others
+    if interrupted() {
+        stop_children();
+    }
+}
+
+pub fn untrack_child(pid: u32) {
+    CHILDREN.lock().unwrap().retain(|child| *child != pid);
+}
+
+// SIGTERM every tracked process group, then SIGKILL the ones still around two seconds later
+#[cfg(unix)]
+fn stop_children() {
+    let signal_all = |signal: libc::c_int| {
+        for pid in CHILDREN.lock().unwrap().iter() {
+            unsafe { libc::kill(-(*pid as libc::pid_t), signal) };
+        }
+    };
+    signal_all(libc::SIGTERM);
+    for _ in 0..40 {
+        if CHILDREN.lock().unwrap().is_empty() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    signal_all(libc::SIGKILL);
+}
+
+#[cfg(windows)]
+fn stop_children() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_paths_are_removed_unless_moved_into_place() {
+        let root = std::env::temp_dir().join(format!("blast-temp-path-{}", std::process::id()));
+        let dir = root.join("_temp_spark_demo");
+        {
+            let guard = TempPath::new(&dir);
+            std::fs::create_dir_all(guard.path().join("nested")).unwrap();
+            std::fs::write(guard.path().join("nested/manifest.toml"), "").unwrap();
+        }
+        assert!(!dir.exists());
+
+        let file = root.join("seed.sql");
+        {
+            let guard = TempPath::new(root.join("seed.sql.part"));
+            std::fs::write(guard.path(), "SELECT 1;").unwrap();
+            std::fs::rename(guard.path(), &file).unwrap();
+        }
+        assert!(file.exists());
+
+        // A guard for something never created has nothing to do
+        drop(TempPath::new(root.join("missing")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
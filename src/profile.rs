@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::logger;
+use crate::operation::Timing;
+
+// --profile prints the breakdown table after the total line
+static SHOW_TABLE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    SHOW_TABLE.store(enabled, Ordering::Relaxed);
+}
+
+struct Span {
+    name: String,
+    wall: Duration,
+    subprocess: Duration,
+}
+
+struct Profile {
+    started: Instant,
+    // Commands started from inside another one report as part of the outer one
+    depth: usize,
+    spans: Vec<Span>,
+    // Runs and total time per external tool
+    tools: BTreeMap<String, (usize, Duration)>,
+}
+
+lazy_static! {
+    static ref PROFILE: Mutex<Option<Profile>> = Mutex::new(None);
+}
+
+thread_local! {
+    // Spans open on this thread, innermost last. Lanes running in their own threads start from the top
+    static OPEN: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Profile>> {
+    PROFILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Start timing a command
+pub fn begin() {
+    let mut profile = lock();
+    match profile.as_mut() {
+        Some(profile) => profile.depth += 1,
+        None => *profile = Some(Profile { started: Instant::now(), depth: 1, spans: Vec::new(), tools: BTreeMap::new() }),
+    }
+}
+
+// Times a step until dropped. Steps inside another step are named after it: "assets/scss"
+pub struct SpanGuard {
+    index: Option<usize>,
+    started: Instant,
+}
+
+pub fn span(name: &str) -> SpanGuard {
+    let index = lock().as_mut().map(|profile| {
+        let name = match OPEN.with(|open| open.borrow().last().copied()) {
+            Some(parent) => format!("{}/{}", profile.spans[parent].name, name),
+            None => name.to_string(),
+        };
+        profile.spans.push(Span { name, wall: Duration::ZERO, subprocess: Duration::ZERO });
+        profile.spans.len() - 1
+    });
+    if let Some(index) = index {
+        OPEN.with(|open| open.borrow_mut().push(index));
+    }
+    SpanGuard { index, started: Instant::now() }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let Some(index) = self.index else { return };
+        OPEN.with(|open| open.borrow_mut().retain(|&open| open != index));
+        if let Some(span) = lock().as_mut().and_then(|profile| profile.spans.get_mut(index)) {
+            span.wall = self.started.elapsed();
+        }
+    }
+}
+
+// An external tool finished, from tool_log. Its time counts toward every step open on this thread
+pub fn subprocess(tool: &str, elapsed: Duration) {
+    let mut profile = lock();
+    let Some(profile) = profile.as_mut() else { return };
+    let entry = profile.tools.entry(tool.to_string()).or_default();
+    entry.0 += 1;
+    entry.1 += elapsed;
+    OPEN.with(|open| {
+        for &index in open.borrow().iter() {
+            profile.spans[index].subprocess += elapsed;
+        }
+    });
+}
+
+pub struct Report {
+    pub total: Duration,
+    pub subprocess: Duration,
+    // Slowest first
+    pub steps: Vec<Timing>,
+    tools: Vec<(String, usize, Duration)>,
+}
+
+// End the command started by begin. Only the outermost one gets a report
+pub fn finish() -> Option<Report> {
+    let profile = {
+        let mut profile = lock();
+        match profile.as_mut() {
+            Some(current) if current.depth > 1 => {
+                current.depth -= 1;
+                None
+            }
+            _ => profile.take(),
+        }
+    }?;
+
+    let mut steps: Vec<Timing> = profile.spans.into_iter().map(|span| Timing { step: span.name, wall_secs: secs(span.wall), subprocess_secs: secs(span.subprocess) }).collect();
+    steps.sort_by(|a, b| b.wall_secs.total_cmp(&a.wall_secs));
+    let mut tools: Vec<_> = profile.tools.into_iter().map(|(tool, (runs, time))| (tool, runs, time)).collect();
+    tools.sort_by_key(|tool| std::cmp::Reverse(tool.2));
+    Some(Report { total: profile.started.elapsed(), subprocess: tools.iter().map(|tool| tool.2).sum(), steps, tools })
+}
+
+fn secs(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 10.0).round() / 10.0
+}
+
+// "12.4s", "3m 12s"
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if seconds < 3600 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60)
+    }
+}
+
+// "init finished in 3m 12s (1m 40s in subprocesses)", then the breakdown with --profile
+pub fn print(command: &str, report: &Report) {
+    if logger::level() == logger::Verbosity::Quiet && !SHOW_TABLE.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut total = format!("{} finished in {}", command, format_duration(report.total));
+    if !report.subprocess.is_zero() {
+        total.push_str(&format!(" ({} in subprocesses)", format_duration(report.subprocess)));
+    }
+    let _ = logger::plain(&total);
+    if !SHOW_TABLE.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut rows: Vec<(String, f64, Option<f64>)> = report.steps.iter().map(|step| (step.step.clone(), step.wall_secs, Some(step.subprocess_secs))).collect();
+    rows.extend(report.tools.iter().map(|(tool, runs, time)| (format!("$ {} ({} run{})", tool, runs, if *runs == 1 { "" } else { "s" }), secs(*time), None)));
+    rows.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let total_secs = report.total.as_secs_f64().max(0.001);
+    let width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0).max("step".len());
+    let _ = logger::plain(&format!("{:<width$}  {:>9}  {:>10}  {:>5}", "step", "wall", "subprocess", "share"));
+    for (name, wall, subprocess) in &rows {
+        let subprocess = subprocess.map_or_else(String::new, |secs| format_duration(Duration::from_secs_f64(secs)));
+        let share = (wall / total_secs * 100.0).round();
+        let _ = logger::plain(&format!("{:<width$}  {:>9}  {:>10}  {:>4}%", name, format_duration(Duration::from_secs_f64(*wall)), subprocess, share));
+    }
+    // init runs its database and asset lanes side by side
+    let top_level: f64 = report.steps.iter().filter(|step| !step.step.contains('/')).map(|step| step.wall_secs).sum();
+    if top_level > total_secs * 1.05 {
+        let _ = logger::plain("Steps ran in parallel, so their shares add up to more than 100%");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subprocess_time_counts_toward_the_open_steps() {
+        begin();
+        {
+            let _assets = span("assets");
+            let _scss = span("scss");
+            subprocess("sass", Duration::from_millis(300));
+        }
+        subprocess("diesel", Duration::from_millis(100));
+        let report = finish().unwrap();
+
+        let step = |name: &str| report.steps.iter().find(|step| step.step == name).unwrap();
+        assert_eq!(step("assets").subprocess_secs, 0.3);
+        assert_eq!(step("assets/scss").subprocess_secs, 0.3);
+        assert!(report.subprocess >= Duration::from_millis(400));
+        assert_eq!(format_duration(Duration::from_secs(192)), "3m 12s");
+    }
+}
@@ -39,6 +39,10 @@ impl ProgressManager {
         let mut progress = self.progress.clone();
         progress.error(msg);
     }
+
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.progress.suspend(f)
+    }
 }
 
 // Stub functions for backward compatibility
@@ -0,0 +1,66 @@
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select};
+
+// Answers the questions blast asks while it works. The CLI asks on the terminal; library users install their
+// own with api::set_prompter and answer programmatically
+pub trait Prompter: Send + Sync {
+    // A yes/no question
+    fn confirm(&self, prompt: &str, default: bool) -> Result<bool, String>;
+    // One of `items` by index, or None to cancel
+    fn select(&self, prompt: &str, items: &[String], default: usize) -> Result<Option<usize>, String>;
+    // Free text; `default` is what an empty answer means
+    fn input(&self, prompt: &str, default: Option<&str>) -> Result<String, String>;
+}
+
+// dialoguer on the terminal blast runs in
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn confirm(&self, prompt: &str, default: bool) -> Result<bool, String> {
+        Confirm::with_theme(&ColorfulTheme::default()).with_prompt(prompt).default(default).interact().map_err(|e| e.to_string())
+    }
+
+    fn select(&self, prompt: &str, items: &[String], default: usize) -> Result<Option<usize>, String> {
+        Select::with_theme(&ColorfulTheme::default()).with_prompt(prompt).items(items).default(default).interact_opt().map_err(|e| e.to_string())
+    }
+
+    fn input(&self, prompt: &str, default: Option<&str>) -> Result<String, String> {
+        let theme = ColorfulTheme::default();
+        let mut input = Input::<String>::with_theme(&theme).with_prompt(prompt);
+        if let Some(default) = default {
+            input = input.default(default.to_string());
+        }
+        input.interact_text().map_err(|e| e.to_string())
+    }
+}
+
+static PROMPTER: Mutex<Option<Arc<dyn Prompter>>> = Mutex::new(None);
+
+pub fn set_prompter(prompter: Arc<dyn Prompter>) {
+    *PROMPTER.lock().unwrap() = Some(prompter);
+}
+
+fn current() -> Arc<dyn Prompter> {
+    PROMPTER.lock().unwrap().clone().unwrap_or_else(|| Arc::new(TerminalPrompter))
+}
+
+// Whether there is anyone to ask: an installed prompter, or a terminal on stdin. Without either, commands
+// take their non-interactive path (defaults, or an error naming the flag to pass)
+pub fn interactive() -> bool {
+    PROMPTER.lock().unwrap().is_some() || std::io::stdin().is_terminal()
+}
+
+pub fn confirm(prompt: &str, default: bool) -> Result<bool, String> {
+    current().confirm(prompt, default)
+}
+
+pub fn select(prompt: &str, items: &[String], default: usize) -> Result<Option<usize>, String> {
+    current().select(prompt, items, default)
+}
+
+pub fn input(prompt: &str, default: Option<&str>) -> Result<String, String> {
+    current().input(prompt, default)
+}
@@ -0,0 +1,159 @@
+use console::style;
+
+use crate::configs::Config;
+use crate::dependencies::DependencyManager;
+use crate::logger;
+use crate::operation::{self, StepState};
+
+// Steps of `blast refresh`, in the order they run
+pub const STEPS: &[&str] = &["rollback", "migrate", "seed", "schema", "structs", "models", "assets"];
+
+// Flags of `blast refresh`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefreshOptions {
+    // Regenerate structs and models for every table, not just changed ones (--all)
+    pub all: bool,
+    // Skip rollback and seeding (--no-drop)
+    pub no_drop: bool,
+    pub skip_seed: bool,
+    pub skip_assets: bool,
+    // Explicit step list (--steps migrate,schema,structs)
+    pub steps: Option<Vec<String>>,
+    // Another connection, or "all", whose migrations and schema are refreshed alongside the default (--connection)
+    pub connection: Option<String>,
+    // Tables the structs and models steps are limited to (--only, --except)
+    pub tables: crate::table_selection::TableSelection,
+}
+
+enum StepResult {
+    Ok,
+    Failed,
+    Skipped(String),
+}
+
+// Why a step doesn't run, or None when it does
+fn skip_reason(step: &str, options: &RefreshOptions, safe_mode: bool, has_assets: bool) -> Option<String> {
+    if let Some(steps) = &options.steps {
+        if !steps.iter().any(|selected| selected == step) {
+            return Some("not in --steps".to_string());
+        }
+    }
+    match step {
+        "rollback" | "seed" if safe_mode => Some(if options.no_drop { "--no-drop" } else { "production default" }.to_string()),
+        "seed" if options.skip_seed => Some("--skip-seed".to_string()),
+        "assets" if options.skip_assets => Some("--skip-assets".to_string()),
+        "assets" if !has_assets => Some("no [assets] section".to_string()),
+        _ => None,
+    }
+}
+
+// `blast refresh`: rollback, migrate, seed, regenerate schema/structs/models and process assets, minus the
+// steps the flags leave out. Prints which steps ran, failed or were skipped
+pub fn run(config: &Config, dep_manager: &mut DependencyManager, options: &RefreshOptions) -> Result<(), String> {
+    if let Some(steps) = &options.steps {
+        if let Some(unknown) = steps.iter().find(|step| !STEPS.contains(&step.as_str())) {
+            return Err(format!("Unknown refresh step '{}'. Use one of: {}", unknown, STEPS.join(", ")));
+        }
+    }
+
+    // Rolling back drops every table, so production only does it when the steps are named explicitly
+    let safe_mode = options.no_drop || (config.is_production() && options.steps.is_none());
+    if safe_mode && !options.no_drop {
+        logger::warning(&format!("{} is a production environment: skipping rollback and seeding (--no-drop). Pass --steps to choose the steps yourself", config.environment))?;
+    }
+
+    let has_assets = crate::assets::has_asset_pipeline(config);
+    let plan: Vec<(&str, Option<String>)> = STEPS.iter().map(|step| (*step, skip_reason(step, options, safe_mode, has_assets))).collect();
+    let runs = |name: &str| plan.iter().any(|(step, skipped)| *step == name && skipped.is_none());
+
+    // Seeds, structs and models stay on the default connection; the others only get migrations and a schema file
+    let mut connections = vec!["default".to_string()];
+    if let Some(selection) = &options.connection {
+        connections.extend(crate::database::resolve_connections(config, selection)?.into_iter().filter(|connection| connection != "default"));
+    }
+
+    operation::set_steps(STEPS);
+    let mut progress = logger::create_progress(None);
+    if ["rollback", "migrate", "seed", "schema"].iter().any(|step| runs(step)) {
+        dep_manager.ensure_installed(&["diesel"])?;
+    }
+    // Asked before anything runs; the rollback and seed steps check again but don't repeat the question
+    if runs("rollback") || runs("seed") {
+        crate::db_safety::confirm_connection(config, "refresh (roll back and seed)", "default", false)?;
+    }
+    if runs("rollback") || runs("migrate") {
+        crate::db_snapshot::snapshot_before_migrate(config)?;
+    }
+    let _db_connection = crate::database::SharedConnection::begin();
+
+    let mut results = Vec::new();
+    for (step, skipped) in plan {
+        crate::platform::check_interrupted()?;
+        if let Some(reason) = skipped {
+            operation::step(step, StepState::Skipped);
+            results.push((step, StepResult::Skipped(reason)));
+            continue;
+        }
+
+        operation::step(step, StepState::Running);
+        let span = crate::profile::span(step);
+        let ok = match step {
+            "rollback" => {
+                progress.set_message("Rolling back migrations...");
+                connections.iter().map(|connection| crate::database::rollback_connection(config, connection)).filter(|ok| !ok).count() == 0
+            }
+            "migrate" => {
+                progress.set_message("Running migrations...");
+                connections.iter().map(|connection| crate::database::migrate_connection(config, connection)).filter(|ok| !ok).count() == 0
+            }
+            "seed" => {
+                progress.set_message("Seeding database...");
+                connections.iter().map(|connection| crate::database::seed(config, Some(0), Some(connection))).filter(|ok| !ok).count() == 0
+            }
+            "schema" => {
+                progress.set_message("Generating schema...");
+                let others = connections.iter().skip(1).map(|connection| crate::database::generate_schema_for_connection(config, connection)).filter(|ok| !ok).count() == 0;
+                crate::database::generate_schema(config) && others
+            }
+            "structs" => {
+                progress.set_message("Generating structs...");
+                crate::structs::generate(config, options.all, &options.tables)
+            }
+            "models" => {
+                progress.set_message("Generating models...");
+                crate::models::generate(config, options.all, &options.tables)
+            }
+            _ => {
+                progress.set_message("Processing assets...");
+                let mut ok = true;
+                for result in [crate::assets::transpile_all_scss(config), crate::assets::publish_css(config), crate::assets::process_js(config)] {
+                    if let Err(e) = result {
+                        logger::warning(&format!("Asset processing error: {}", e))?;
+                        ok = false;
+                    }
+                }
+                ok
+            }
+        };
+        drop(span);
+        operation::step(step, if ok { StepState::Done } else { StepState::Failed });
+        results.push((step, if ok { StepResult::Ok } else { StepResult::Failed }));
+    }
+
+    let failed = results.iter().filter(|(_, result)| matches!(result, StepResult::Failed)).count();
+    if failed == 0 {
+        progress.success("App refresh complete!");
+    } else {
+        progress.error("App refresh completed with some issues");
+    }
+
+    for (step, result) in &results {
+        let line = match result {
+            StepResult::Ok => format!("  {} {}", style("✓").green(), step),
+            StepResult::Failed => format!("  {} {} (failed)", style("✗").red(), step),
+            StepResult::Skipped(reason) => format!("  {} {} (skipped: {})", style("-").dim(), step, reason),
+        };
+        logger::plain(&line)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::Local;
+
+use crate::configs::Config;
+use crate::logger;
+use crate::tool_log::{self, ToolRun};
+
+pub const DEFAULT_RUNS: usize = 10;
+const LOG_TAIL_LINES: usize = 50;
+
+fn fenced(language: &str, text: &str) -> String {
+    // A fence longer than any backtick run inside, so tool output can't close it early
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}\n", fence, language, text.trim_end(), fence)
+}
+
+fn describe_run(index: usize, run: &ToolRun) -> String {
+    let outcome = match (&run.error, run.exit_code) {
+        (Some(error), _) => format!("failed to start: {}", error),
+        (None, Some(code)) => format!("exit {}", code),
+        (None, None) => "killed by a signal".to_string(),
+    };
+    let mut section = format!("### {}. `{}` ({}, {:.1}s)\n\n", index, run.command, outcome, run.duration_ms as f64 / 1000.0);
+    section.push_str(&format!("- Tool: {}\n- Started: {}\n- Working directory: {}\n\n", run.tool, run.time, run.cwd));
+
+    // Successful runs only get their summary line, to keep the report short enough to paste
+    if run.failed() {
+        for (name, output) in [("stderr", &run.stderr), ("stdout", &run.stdout)] {
+            if !output.trim().is_empty() {
+                section.push_str(&format!("{}:\n\n{}\n", name, fenced("", output)));
+            }
+        }
+    }
+    section
+}
+
+fn log_tail(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let lines: Vec<&str> = content.lines().collect();
+    Some(lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n"))
+}
+
+// The report as markdown: versions, the redacted config, the last `runs` tool invocations and the end of blast's log
+pub fn render(config: &Config, runs: usize) -> String {
+    let mut report = String::from("# blast report\n\n");
+    report.push_str(&format!("- blast: {}\n", env!("CARGO_PKG_VERSION")));
+    report.push_str(&format!("- OS: {} {} ({})\n", std::env::consts::OS, std::env::consts::ARCH, std::env::consts::FAMILY));
+    report.push_str(&format!("- Environment: {}\n", config.environment));
+    if let Some(version) = config.catalyst_version() {
+        report.push_str(&format!("- Catalyst: {}\n", version));
+    }
+    report.push_str(&format!("- Generated: {}\n\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
+
+    report.push_str("## Configuration\n\nEffective Catalyst.toml settings, with credentials and .env values masked.\n\n");
+    let effective = config.effective_config().and_then(|value| toml::to_string_pretty(&value).map_err(|e| e.to_string()));
+    match effective {
+        Ok(toml) => report.push_str(&fenced("toml", &tool_log::scrub(&config.project_dir, &toml))),
+        Err(e) => report.push_str(&format!("Couldn't read the configuration: {}\n", e)),
+    }
+
+    let recorded = tool_log::recent(&config.project_dir, runs);
+    report.push_str(&format!("\n## Last {} tool runs\n\n", recorded.len()));
+    if recorded.is_empty() {
+        report.push_str(&format!("Nothing recorded in {} yet.\n\n", tool_log::TOOLS_LOG));
+    }
+    // Newest first: the failure being reported is usually the last one
+    for (index, run) in recorded.iter().rev().enumerate() {
+        report.push_str(&describe_run(index + 1, run));
+    }
+
+    for log in [Path::new("storage/logs/info.log"), Path::new("storage/blast/blast.log")] {
+        if let Some(tail) = log_tail(&config.project_dir.join(log)) {
+            report.push_str(&format!("## {} (last {} lines)\n\n{}\n", log.display(), LOG_TAIL_LINES, fenced("", &tool_log::scrub(&config.project_dir, &tail))));
+        }
+    }
+    report
+}
+
+// `blast report`: write the report to `output` ("-" for stdout), or a timestamped file in storage/logs
+pub fn run(config: &Config, runs: usize, output: Option<String>) -> Result<(), String> {
+    let report = render(config, runs);
+    if output.as_deref() == Some("-") {
+        print!("{}", report);
+        return Ok(());
+    }
+
+    let path = match output {
+        Some(path) => config.project_dir.join(path),
+        None => config.project_dir.join("storage").join("logs").join(format!("blast-report-{}.md", Local::now().format("%Y%m%d-%H%M%S"))),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&path, report).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    logger::success(&format!("Wrote {}", path.display()))?;
+    logger::plain("Read it over, then attach it to your bug report")
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use toml_edit::DocumentMut;
+
+use crate::configs::Config;
+use crate::envfile;
+use crate::logger;
+
+const FILE: &str = "Rocket.toml";
+// Rocket reads every ROCKET_* variable over Rocket.toml, so the secret lives in .env under this name
+pub const SECRET_KEY_ENV: &str = "ROCKET_SECRET_KEY";
+const HEADER: &str = "# Rocket settings. [default] applies everywhere, then the profile blast exports as ROCKET_PROFILE:\n# [release] for production environments, [debug] for the others, or a table named after the blast\n# environment (e.g. [staging]) when there is one. Edit with `blast config rocket set <key> <value> --profile <env>`.\n# secret_key is not kept here: Rocket reads it from ROCKET_SECRET_KEY in .env\n";
+
+// What each profile gets unless Rocket.toml already sets it
+const PROFILE_DEFAULTS: &[(&str, &str, &str)] = &[("debug", "log_level", "normal"), ("release", "log_level", "critical")];
+
+// A 256-bit secret_key, hex encoded as Rocket accepts it
+pub fn generate_secret_key() -> String {
+    (0..32).map(|_| format!("{:02x}", rand::random::<u8>())).collect()
+}
+
+// The Rocket.toml table a blast environment name (or Rocket profile name) edits
+fn profile_table(environment: &str) -> String {
+    match environment {
+        "dev" | "development" => "debug".to_string(),
+        "prod" | "production" => "release".to_string(),
+        other => other.to_string(),
+    }
+}
+
+// ROCKET_PROFILE for the active environment: its own table when Rocket.toml has one, else release or debug
+pub fn profile(config: &Config) -> String {
+    let table = profile_table(&config.environment);
+    let content = std::fs::read_to_string(config.project_dir.join(FILE)).unwrap_or_default();
+    let has_table = content.parse::<toml::Table>().is_ok_and(|doc| doc.get(&table).is_some_and(toml::Value::is_table));
+    if matches!(table.as_str(), "debug" | "release") || has_table {
+        return table;
+    }
+    if config.is_production() { "release" } else { "debug" }.to_string()
+}
+
+// Rocket.toml with the header, the debug/release defaults and no committed secret_key
+fn managed(content: &str) -> Result<String, String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("Failed to parse {}: {}", FILE, e))?;
+    if !content.contains(HEADER) {
+        let prefix = doc.decor().prefix().and_then(|prefix| prefix.as_str()).unwrap_or_default().to_string();
+        doc.decor_mut().set_prefix(format!("{}{}", HEADER, prefix));
+    }
+    doc.entry("default").or_insert_with(toml_edit::table);
+    for (profile, key, value) in PROFILE_DEFAULTS {
+        let table = doc.entry(profile).or_insert_with(toml_edit::table).as_table_mut().ok_or_else(|| format!("[{}] in {} is not a table", profile, FILE))?;
+        if !table.contains_key(key) {
+            table.insert(key, toml_edit::value(*value));
+        }
+    }
+    for (_, item) in doc.iter_mut() {
+        if let Some(table) = item.as_table_mut() {
+            table.remove("secret_key");
+        }
+    }
+    Ok(doc.to_string())
+}
+
+// `blast new`: profiles in Rocket.toml and a fresh secret_key in .env
+pub fn setup_new_project(project_path: &Path) -> Result<(), String> {
+    let path = project_path.join(FILE);
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    std::fs::write(&path, managed(&content)?).map_err(|e| format!("Failed to write {}: {}", FILE, e))?;
+
+    let mut env = envfile::EnvFile::load(&project_path.join(".env"))?;
+    env.set(SECRET_KEY_ENV, &generate_secret_key());
+    env.save()
+}
+
+// A value as TOML when it parses as one (8000, true, ["a"]), otherwise a string
+fn parse_value(raw: &str) -> toml_edit::Value {
+    raw.parse::<toml_edit::Value>().unwrap_or_else(|_| toml_edit::Value::from(raw))
+}
+
+// `blast config rocket set <key> <value> [--profile <env>]`. Without a profile the key goes to [default]
+pub fn set(config: &Config, key: &str, raw_value: &str, profile: Option<&str>) -> Result<(), String> {
+    if key == "secret_key" {
+        return Err(format!("secret_key doesn't belong in {}, which is committed. Set it in .env instead: blast env set {} <64 hex characters>", FILE, SECRET_KEY_ENV));
+    }
+    let table_name = profile.map(profile_table).unwrap_or_else(|| "default".to_string());
+    let path = config.project_dir.join(FILE);
+    let content = crate::file_writer::read(&path).unwrap_or_default();
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("Failed to parse {}: {}", FILE, e))?;
+    let table = doc.entry(&table_name).or_insert_with(toml_edit::table).as_table_mut().ok_or_else(|| format!("[{}] in {} is not a table", table_name, FILE))?;
+    table.insert(key, toml_edit::Item::Value(parse_value(raw_value)));
+    crate::file_writer::write(&path, &doc.to_string())?;
+    logger::success(&format!("Set {} in [{}] of {}", key, table_name, FILE))
+}
+
+// What blast exports to the app it starts: the profile of the active environment, and the secret_key from the
+// env files, since Rocket only reads ROCKET_* from the process environment
+pub fn server_env(config: &Config) -> Vec<(String, String)> {
+    let mut vars = vec![("ROCKET_PROFILE".to_string(), profile(config))];
+    if let Some(secret) = envfile::load_effective(&config.project_dir, &config.environment).get(SECRET_KEY_ENV).filter(|secret| !secret.is_empty()) {
+        vars.push((SECRET_KEY_ENV.to_string(), secret));
+    }
+    vars
+}
+
+// For `blast doctor` in a production environment: whether Rocket gets a usable secret_key
+pub fn secret_key_problem(config: &Config) -> Option<String> {
+    if !config.is_production() {
+        return None;
+    }
+    let rocket_toml = std::fs::read_to_string(config.project_dir.join(FILE)).unwrap_or_default();
+    if rocket_toml.parse::<toml::Table>().is_ok_and(|doc| doc.values().any(|profile| profile.get("secret_key").is_some())) {
+        return Some(format!("{} sets secret_key, which is then committed; move it to {} in .env", FILE, SECRET_KEY_ENV));
+    }
+    let secret = envfile::load_effective(&config.project_dir, &config.environment).get(SECRET_KEY_ENV).unwrap_or_default();
+    if secret.trim().is_empty() {
+        return Some(format!("{} is not set; Rocket with the secrets feature won't start without a secret_key outside debug", SECRET_KEY_ENV));
+    }
+    // 256 or 512 bits, hex or base64
+    let valid = (matches!(secret.len(), 64 | 128) && secret.chars().all(|c| c.is_ascii_hexdigit())) || matches!(secret.len(), 44 | 88);
+    if secret.contains(envfile::PLACEHOLDER) || !valid {
+        return Some(format!("{} isn't a 256- or 512-bit key; `blast new` generates 64 hex characters", SECRET_KEY_ENV));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn managed_rocket_toml_adds_profiles_and_drops_the_secret() {
+        let content = "[default]\npublic_dir = \"public\"\nsecret_key = \"hPRYyVRiMyxpw5sBB1XeCMN1kFsDCqKvBi2QJxBVHQk=\"\n\n[release]\nlog_level = \"off\"\n";
+        let updated = managed(content).unwrap();
+        assert!(updated.starts_with(HEADER));
+        assert!(updated.contains("public_dir = \"public\"\n"));
+        assert!(!updated.contains("secret_key ="));
+        assert!(updated.contains("[debug]\nlog_level = \"normal\"\n"));
+        assert!(updated.contains("[release]\nlog_level = \"off\"\n"));
+        assert_eq!(managed(&updated).unwrap(), updated);
+
+        assert_eq!(profile_table("prod"), "release");
+        assert_eq!(profile_table("staging"), "staging");
+        assert_eq!(generate_secret_key().len(), 64);
+    }
+}
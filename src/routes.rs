@@ -0,0 +1,754 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use lazy_static::lazy_static;
+use prettytable::{format, Cell, Row, Table};
+use regex::Regex;
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::configs::Config;
+use crate::logger;
+
+// A Rocket route as `blast routes` prints it
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteInfo {
+    pub method: String,
+    // Full path with the mount prefix, or the declared path when nothing mounts the handler
+    pub path: String,
+    pub handler: String,
+    pub module: String,
+    pub file: String,
+    pub line: usize,
+    pub mounted: bool,
+    // Models the handler uses whose table or generated file is gone
+    pub missing_models: Vec<String>,
+}
+
+// A Rust file under src/ with its comments blanked out, so offsets and line numbers still match the original
+struct SourceFile {
+    relative: String,
+    module: Vec<String>,
+    code: String,
+}
+
+struct Handler<'a> {
+    method: String,
+    path: String,
+    name: String,
+    file: &'a SourceFile,
+    line: usize,
+    body: &'a str,
+}
+
+// fn routes() -> Vec<Route> { routes![...] }, so `.mount("/users", users::routes())` can be followed
+struct RouteList<'a> {
+    name: String,
+    file: &'a SourceFile,
+    items: Vec<String>,
+}
+
+enum MountTarget {
+    Routes(Vec<String>),
+    Call(Vec<String>),
+}
+
+struct Mount<'a> {
+    prefix: String,
+    target: MountTarget,
+    file: &'a SourceFile,
+}
+
+// Path segments that say where to look rather than what to look for
+const PATH_KEYWORDS: &[&str] = &["crate", "self", "super"];
+
+// Module names under models/ and structs/ that are never a table
+const SHARED_MODULES: &[&str] = &["generated", "insertable", "helpers", "validate", "mod"];
+
+// Compiled once, not per handler or imported name
+lazy_static! {
+    static ref USE_STATEMENT: Regex = Regex::new(r"\buse\s+([^;]+);").unwrap();
+    static ref IDENTIFIER: Regex = Regex::new(r"[A-Za-z_]\w*").unwrap();
+    static ref INLINE_PATH: Regex = Regex::new(r"\b(?:models|structs)\s*::\s*(?:(?:generated|insertable)\s*::\s*)?([A-Za-z_]\w*)").unwrap();
+}
+
+// `blast routes [--grep <text>] [--output json]`
+pub fn list_routes(config: &Config, grep: Option<String>, output: Option<String>) -> Result<(), String> {
+    let json = match output.as_deref() {
+        None | Some("table") => false,
+        Some("json") => true,
+        Some(other) => return Err(format!("Unknown output format '{}'. Use table or json", other)),
+    };
+
+    let mut routes = scan(config)?;
+    if let Some(query) = &grep {
+        routes.retain(|route| [&route.method, &route.path, &format!("{}::{}", route.module, route.handler), &route.file].iter().any(|field| fuzzy_match(query, field)));
+    }
+
+    if json {
+        let content = serde_json::to_string_pretty(&routes).map_err(|e| e.to_string())?;
+        println!("{}", content);
+        return Ok(());
+    }
+
+    if routes.is_empty() {
+        return logger::plain(match grep {
+            Some(_) => "No routes match",
+            None => "No Rocket routes found under src/",
+        });
+    }
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.add_row(Row::new(vec![Cell::new("Method"), Cell::new("Path"), Cell::new("Handler"), Cell::new("Source"), Cell::new("Notes")]));
+    for route in &routes {
+        let notes = if !route.missing_models.is_empty() {
+            Cell::new(&format!("missing model: {}", route.missing_models.join(", "))).style_spec("Fr")
+        } else if !route.mounted {
+            Cell::new("not mounted").style_spec("Fy")
+        } else {
+            Cell::new("")
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&route.method),
+            Cell::new(&route.path),
+            Cell::new(&format!("{}::{}", route.module, route.handler)),
+            Cell::new(&format!("{}:{}", route.file, route.line)),
+            notes,
+        ]));
+    }
+    table.printstd();
+
+    let flagged = routes.iter().filter(|route| !route.missing_models.is_empty()).count();
+    if flagged > 0 {
+        logger::warning(&format!("{} route(s) use models that no longer exist", flagged))?;
+    }
+    logger::plain(&format!("{} route(s)", routes.len()))
+}
+
+// Every route declared under src/, with the mount prefixes applied. Nothing is compiled
+pub fn scan(config: &Config) -> Result<Vec<RouteInfo>, String> {
+    let src_dir = config.project_dir.join("src");
+    let mut sources = Vec::new();
+    for entry in WalkDir::new(&src_dir).sort_by_file_name().into_iter().filter_map(|entry| entry.ok()) {
+        if entry.path().extension().is_some_and(|ext| ext == "rs") {
+            let content = fs::read_to_string(entry.path()).map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+            let relative = entry.path().strip_prefix(&config.project_dir).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+            sources.push((relative, content));
+        }
+    }
+
+    let models = ModelIndex::load(config);
+    Ok(collect(&sources, &|name| models.exists(name)))
+}
+
+// Routes from (path relative to the project, content) pairs; `model_exists` decides which model references to flag
+fn collect(sources: &[(String, String)], model_exists: &dyn Fn(&str) -> bool) -> Vec<RouteInfo> {
+    let files: Vec<SourceFile> = sources.iter().map(|(relative, content)| SourceFile { relative: relative.clone(), module: module_path(relative), code: strip_comments(content) }).collect();
+
+    let handlers: Vec<Handler> = files.iter().flat_map(parse_handlers).collect();
+    let lists: Vec<RouteList> = files.iter().flat_map(parse_route_lists).collect();
+    let mounts: Vec<Mount> = files.iter().flat_map(parse_mounts).collect();
+
+    let mut routes = Vec::new();
+    let mut mounted = BTreeSet::new();
+    for mount in &mounts {
+        let targets: Vec<(&str, &SourceFile)> = match &mount.target {
+            MountTarget::Routes(items) => items.iter().map(|item| (item.as_str(), mount.file)).collect(),
+            MountTarget::Call(path) => resolve_lists(path, &lists).into_iter().flat_map(|list| list.items.iter().map(move |item| (item.as_str(), list.file))).collect(),
+        };
+        for (item, scope) in targets {
+            for index in resolve(item, &scope.module, &handlers) {
+                mounted.insert(index);
+                routes.push(route_info(&handlers[index], join_path(&mount.prefix, &handlers[index].path), true, model_exists));
+            }
+        }
+    }
+    for (index, handler) in handlers.iter().enumerate() {
+        if !mounted.contains(&index) {
+            routes.push(route_info(handler, handler.path.clone(), false, model_exists));
+        }
+    }
+
+    routes.sort_by(|a, b| (&a.path, &a.method, &a.module, &a.handler).cmp(&(&b.path, &b.method, &b.module, &b.handler)));
+    routes.dedup_by(|a, b| (&a.path, &a.method, &a.module, &a.handler) == (&b.path, &b.method, &b.module, &b.handler));
+    routes
+}
+
+fn route_info(handler: &Handler, path: String, mounted: bool, model_exists: &dyn Fn(&str) -> bool) -> RouteInfo {
+    RouteInfo {
+        method: handler.method.clone(),
+        path,
+        handler: handler.name.clone(),
+        module: if handler.file.module.is_empty() { "crate".to_string() } else { handler.file.module.join("::") },
+        file: handler.file.relative.clone(),
+        line: handler.line,
+        mounted,
+        missing_models: model_references(handler).into_iter().filter(|name| !model_exists(name)).collect(),
+    }
+}
+
+// src/routes/users.rs -> routes::users, src/routes/mod.rs -> routes, src/main.rs -> the crate root
+fn module_path(relative: &str) -> Vec<String> {
+    let relative = relative.strip_prefix("src/").unwrap_or(relative).trim_end_matches(".rs");
+    let mut module: Vec<String> = relative.split('/').map(str::to_string).collect();
+    if matches!(module.last().map(String::as_str), Some("mod" | "main" | "lib")) {
+        module.pop();
+    }
+    module
+}
+
+// Rocket joins "/users" and "/" as "/users", and "/" and "/about" as "/about"
+fn join_path(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    match path {
+        "" | "/" if !prefix.is_empty() => prefix.to_string(),
+        _ if path.starts_with("/?") && !prefix.is_empty() => format!("{}{}", prefix, &path[1..]),
+        _ => format!("{}/{}", prefix, path.trim_start_matches('/')),
+    }
+}
+
+// Rocket segments: <id> matches one segment, <path..> everything after it
+pub fn route_matches(route: &str, path: &str) -> bool {
+    let route = route.split('?').next().unwrap_or_default();
+    let route: Vec<&str> = route.split('/').filter(|segment| !segment.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    for (index, segment) in route.iter().enumerate() {
+        if segment.starts_with('<') && segment.ends_with("..>") {
+            return true;
+        }
+        match path.get(index) {
+            Some(part) if segment.starts_with('<') || segment == part => continue,
+            _ => return false,
+        }
+    }
+    route.len() == path.len()
+}
+
+// Case-insensitive subsequence match, so "usrshow" finds users::show
+fn fuzzy_match(query: &str, text: &str) -> bool {
+    let text = text.to_lowercase();
+    let mut chars = text.chars();
+    query.to_lowercase().chars().filter(|c| !c.is_whitespace()).all(|wanted| chars.any(|c| c == wanted))
+}
+
+// Handlers an item of routes![...] refers to, as indexes into `handlers`. Items are matched by name, narrowed down
+// by the module path they are written with, or by the module of the routes! call when they have none
+fn resolve(item: &str, scope: &[String], handlers: &[Handler]) -> Vec<usize> {
+    let segments: Vec<&str> = item.split("::").filter(|segment| !segment.is_empty() && !PATH_KEYWORDS.contains(segment)).collect();
+    let Some((name, modules)) = segments.split_last() else {
+        return Vec::new();
+    };
+
+    let candidates: Vec<usize> = handlers.iter().enumerate().filter(|(_, handler)| handler.name == *name).map(|(index, _)| index).collect();
+    let narrowed: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&index| {
+            let module = &handlers[index].file.module;
+            if modules.is_empty() {
+                module.as_slice() == scope
+            } else {
+                module.len() >= modules.len() && module[module.len() - modules.len()..].iter().zip(modules.iter()).all(|(a, b)| a == b)
+            }
+        })
+        .collect();
+
+    if narrowed.is_empty() {
+        candidates
+    } else {
+        narrowed
+    }
+}
+
+fn resolve_lists<'a, 'b>(path: &[String], lists: &'b [RouteList<'a>]) -> Vec<&'b RouteList<'a>> {
+    let segments: Vec<&str> = path.iter().map(String::as_str).filter(|segment| !PATH_KEYWORDS.contains(segment)).collect();
+    let Some((name, modules)) = segments.split_last() else {
+        return Vec::new();
+    };
+
+    let candidates: Vec<&RouteList> = lists.iter().filter(|list| list.name == *name).collect();
+    let narrowed: Vec<&RouteList> = candidates.iter().copied().filter(|list| list.file.module.ends_with(&modules.iter().map(|m| m.to_string()).collect::<Vec<_>>())).collect();
+    if narrowed.is_empty() {
+        candidates
+    } else {
+        narrowed
+    }
+}
+
+fn parse_handlers(file: &SourceFile) -> Vec<Handler<'_>> {
+    let Ok(attribute) = Regex::new(r"#\s*\[\s*(?:rocket\s*::\s*)?(get|put|post|delete|head|patch|options|route)\s*\(") else {
+        return Vec::new();
+    };
+    let Ok(fn_name) = Regex::new(r#"^(?:pub\s*(?:\([^)]*\))?\s*)?(?:(?:async|const|unsafe|extern\s*"[^"]*")\s+)*fn\s+(\w+)"#) else {
+        return Vec::new();
+    };
+    let code = file.code.as_str();
+
+    let mut handlers = Vec::new();
+    for capture in attribute.captures_iter(code) {
+        let whole = capture.get(0).map(|m| (m.start(), m.end())).unwrap_or_default();
+        let Some(close) = matching_close(code, whole.1 - 1) else {
+            continue;
+        };
+        let Some((method, path)) = attribute_route(&capture[1], &code[whole.1..close]) else {
+            continue;
+        };
+
+        // Skip to the end of this attribute, then past any others (#[allow(...)], doc attributes) to the fn
+        let mut position = close + 1;
+        let mut after_attributes = true;
+        loop {
+            position += code[position..].len() - code[position..].trim_start().len();
+            let rest = &code[position..];
+            if rest.starts_with(']') && after_attributes {
+                position += 1;
+                after_attributes = false;
+            } else if rest.starts_with('#') {
+                match rest.find('[').and_then(|open| matching_close(code, position + open)) {
+                    Some(end) => position = end + 1,
+                    None => break,
+                }
+            } else {
+                break;
+            }
+        }
+
+        let Some(name) = fn_name.captures(&code[position..]) else {
+            continue;
+        };
+        let body = code[position..]
+            .find('(')
+            .and_then(|open| matching_close(code, position + open))
+            .and_then(|params_end| code[params_end..].find('{').map(|brace| params_end + brace))
+            .and_then(|open| matching_close(code, open).map(|close| &code[open..=close]))
+            .unwrap_or_default();
+
+        handlers.push(Handler { method, path, name: name[1].to_string(), file, line: line_of(code, whole.0), body });
+    }
+    handlers
+}
+
+// ("get", "\"/users/<id>\", format = \"json\"") -> ("GET", "/users/<id>"); route(POST, uri = "/x") works too
+fn attribute_route(kind: &str, args: &str) -> Option<(String, String)> {
+    let literal = Regex::new(r#""((?:[^"\\]|\\.)*)""#).ok()?;
+    if kind != "route" {
+        let path = literal.captures(args.trim_start()).filter(|capture| capture.get(0).is_some_and(|m| m.start() == 0))?;
+        return Some((kind.to_uppercase(), path[1].to_string()));
+    }
+
+    let method = Regex::new(r"^\s*([A-Za-z]+)").ok()?.captures(args)?[1].to_uppercase();
+    let uri = Regex::new(r#"uri\s*=\s*"((?:[^"\\]|\\.)*)""#).ok()?.captures(args).or_else(|| literal.captures(args))?;
+    Some((method, uri[1].to_string()))
+}
+
+fn parse_route_lists(file: &SourceFile) -> Vec<RouteList<'_>> {
+    let Ok(signature) = Regex::new(r"fn\s+(\w+)\s*\(\s*\)\s*->\s*Vec\s*<\s*(?:[\w:]*::)?Route\s*>") else {
+        return Vec::new();
+    };
+    let code = file.code.as_str();
+
+    signature
+        .captures_iter(code)
+        .filter_map(|capture| {
+            let end = capture.get(0)?.end();
+            let open = end + code[end..].find('{')?;
+            let close = matching_close(code, open)?;
+            Some(RouteList { name: capture[1].to_string(), file, items: routes_macro_items(&code[open..=close]) })
+        })
+        .collect()
+}
+
+fn parse_mounts(file: &SourceFile) -> Vec<Mount<'_>> {
+    let (Ok(mount), Ok(prefix), Ok(call)) = (
+        Regex::new(r"\.\s*mount\s*\("),
+        Regex::new(r#"^\s*"((?:[^"\\]|\\.)*)"\s*,"#),
+        Regex::new(r"^\s*((?:\w+\s*::\s*)*\w+)\s*\(\s*\)\s*$"),
+    ) else {
+        return Vec::new();
+    };
+    let code = file.code.as_str();
+
+    let mut mounts = Vec::new();
+    for found in mount.find_iter(code) {
+        let Some(close) = matching_close(code, found.end() - 1) else {
+            continue;
+        };
+        let args = &code[found.end()..close];
+        // Prefixes that aren't literals can't be known without running the app
+        let Some(prefix_capture) = prefix.captures(args) else {
+            continue;
+        };
+        let target_code = &args[prefix_capture.get(0).map(|m| m.end()).unwrap_or_default()..];
+
+        let target = if target_code.contains("routes!") {
+            MountTarget::Routes(routes_macro_items(target_code))
+        } else if let Some(path) = call.captures(target_code) {
+            MountTarget::Call(path[1].split("::").map(|segment| segment.trim().to_string()).collect())
+        } else {
+            continue;
+        };
+        mounts.push(Mount { prefix: prefix_capture[1].to_string(), target, file });
+    }
+    mounts
+}
+
+// Items of every routes![...] in `code`, with whitespace removed: "users::show"
+fn routes_macro_items(code: &str) -> Vec<String> {
+    let Ok(macro_call) = Regex::new(r"routes\s*!\s*\[") else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    for found in macro_call.find_iter(code) {
+        if let Some(close) = matching_close(code, found.end() - 1) {
+            items.extend(code[found.end()..close].split(',').map(|item| item.split_whitespace().collect::<String>()).filter(|item| !item.is_empty()));
+        }
+    }
+    items
+}
+
+// Types and modules the handler body uses from models/ or structs/, imported at the top of its file or spelled out
+fn model_references(handler: &Handler) -> Vec<String> {
+    let mut imported = BTreeSet::new();
+    for statement in USE_STATEMENT.captures_iter(&handler.file.code) {
+        let path = &statement[1];
+        let Some(start) = ["models", "structs"].iter().filter_map(|root| path.find(&format!("{}::", root)).map(|index| index + root.len() + 2)).min() else {
+            continue;
+        };
+        let mut tokens = IDENTIFIER.find_iter(&path[start..]).map(|token| token.as_str()).peekable();
+        while let Some(token) = tokens.next() {
+            // `Posts as Post`: the original name is the one that has to exist
+            if tokens.peek() == Some(&"as") {
+                tokens.next();
+                tokens.next();
+            }
+            if !PATH_KEYWORDS.contains(&token) && !SHARED_MODULES.contains(&token) {
+                imported.insert(token.to_string());
+            }
+        }
+    }
+
+    // Every identifier in the body once, instead of a word-boundary regex per imported name
+    let words: BTreeSet<&str> = IDENTIFIER.find_iter(handler.body).map(|word| word.as_str()).collect();
+    let mut references: BTreeSet<String> = imported.into_iter().filter(|name| words.contains(name.as_str())).collect();
+    for capture in INLINE_PATH.captures_iter(handler.body) {
+        if !SHARED_MODULES.contains(&&capture[1]) {
+            references.insert(capture[1].to_string());
+        }
+    }
+    references.into_iter().collect()
+}
+
+// Tables in schema.rs and what gen structs/models left on disk
+struct ModelIndex {
+    tables: BTreeSet<String>,
+    types: BTreeSet<String>,
+    modules: BTreeSet<String>,
+}
+
+impl ModelIndex {
+    fn load(config: &Config) -> Self {
+        let codegen = config.codegen();
+        let schema = fs::read_to_string(config.project_dir.join(codegen.schema_path())).unwrap_or_default();
+        let tables: BTreeSet<String> = crate::models::parse_schema_tables(&schema).into_iter().map(|table| table.name).collect();
+
+        let mut types = BTreeSet::new();
+        let mut modules = BTreeSet::new();
+        let definition = Regex::new(r"\b(?:struct|enum|type)\s+([A-Za-z_]\w*)").ok();
+        for dir in [&codegen.structs_dir, &codegen.models_dir] {
+            for entry in WalkDir::new(config.project_dir.join(dir)).into_iter().filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    modules.insert(stem.to_string());
+                }
+                if path.extension().is_some_and(|ext| ext == "rs") {
+                    let content = fs::read_to_string(path).unwrap_or_default();
+                    if let Some(definition) = &definition {
+                        types.extend(definition.captures_iter(&content).map(|capture| capture[1].to_string()));
+                    }
+                }
+            }
+        }
+        for table in &tables {
+            let model = crate::models::to_pascal(table);
+            types.insert(format!("New{}", model));
+            types.insert(model);
+        }
+
+        ModelIndex { tables, types, modules }
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        // Projects without generated code have nothing to compare against
+        if self.tables.is_empty() && self.types.is_empty() {
+            return true;
+        }
+        if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            self.types.contains(name)
+        } else {
+            self.tables.contains(name) || self.modules.contains(name)
+        }
+    }
+}
+
+fn line_of(code: &str, offset: usize) -> usize {
+    code[..offset].matches('\n').count() + 1
+}
+
+// Index just past the string, raw string or char literal starting at `index`, if one does
+fn literal_end(bytes: &[u8], index: usize) -> Option<usize> {
+    match bytes[index] {
+        b'"' => {
+            let mut i = index + 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b'"' => return Some(i + 1),
+                    _ => i += 1,
+                }
+            }
+            Some(bytes.len())
+        }
+        b'r' if index == 0 || !(bytes[index - 1].is_ascii_alphanumeric() || bytes[index - 1] == b'_') => {
+            let hashes = bytes[index + 1..].iter().take_while(|&&b| b == b'#').count();
+            if bytes.get(index + 1 + hashes) != Some(&b'"') {
+                return None;
+            }
+            let closing: Vec<u8> = std::iter::once(b'"').chain(std::iter::repeat_n(b'#', hashes)).collect();
+            let start = index + 2 + hashes;
+            let end = bytes[start..].windows(closing.len()).position(|window| window == closing.as_slice()).map(|position| start + position + closing.len());
+            Some(end.unwrap_or(bytes.len()))
+        }
+        // 'a' and '\n' are chars, 'a in a signature is a lifetime
+        b'\'' => {
+            if bytes.get(index + 1) == Some(&b'\\') {
+                return bytes[index + 2..].iter().position(|&b| b == b'\'').map(|position| index + 3 + position);
+            }
+            let width = match bytes.get(index + 1) {
+                Some(&lead) if lead >= 0xF0 => 4,
+                Some(&lead) if lead >= 0xE0 => 3,
+                Some(&lead) if lead >= 0xC0 => 2,
+                Some(_) => 1,
+                None => return None,
+            };
+            (bytes.get(index + 1 + width) == Some(&b'\'')).then_some(index + 2 + width)
+        }
+        _ => None,
+    }
+}
+
+// Blank out // and /* */ comments, keeping newlines and byte offsets
+fn strip_comments(source: &str) -> String {
+    let bytes = source.as_bytes();
+    let mut output = bytes.to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = literal_end(bytes, i) {
+            i = end;
+        } else if bytes[i..].starts_with(b"//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                output[i] = b' ';
+                i += 1;
+            }
+        } else if bytes[i..].starts_with(b"/*") {
+            // Block comments nest in Rust
+            let mut depth = 0;
+            while i < bytes.len() {
+                if bytes[i..].starts_with(b"/*") {
+                    depth += 1;
+                    output[i] = b' ';
+                    output[i + 1] = b' ';
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    depth -= 1;
+                    output[i] = b' ';
+                    output[i + 1] = b' ';
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    if bytes[i] != b'\n' {
+                        output[i] = b' ';
+                    }
+                    i += 1;
+                }
+            }
+        } else {
+            i += 1;
+        }
+    }
+    // Only whole comments were replaced, so this stays valid UTF-8
+    String::from_utf8(output).unwrap_or_else(|_| source.to_string())
+}
+
+// Index of the bracket closing the one at `open`, skipping over literals
+fn matching_close(code: &str, open: usize) -> Option<usize> {
+    let bytes = code.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        if let Some(end) = literal_end(bytes, i) {
+            i = end;
+            continue;
+        }
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect, join_path, route_matches, strip_comments, RouteInfo};
+
+    fn routes(sources: &[(&str, &str)]) -> Vec<RouteInfo> {
+        let sources: Vec<(String, String)> = sources.iter().map(|(path, content)| (path.to_string(), content.to_string())).collect();
+        collect(&sources, &|name| name != "Posts" && name != "posts")
+    }
+
+    fn summary(routes: &[RouteInfo]) -> Vec<String> {
+        routes.iter().map(|route| format!("{} {} {}::{} {}:{}", route.method, route.path, route.module, route.handler, route.file, route.line)).collect()
+    }
+
+    const USERS: &str = r#"use rocket::serde::json::Json;
+use rocket_dyn_templates::{context, Template};
+use crate::structs::Users;
+
+/// Lists every user
+#[get("/")]
+pub async fn index() -> Template {
+    let users = Users::get_all().await.unwrap_or_default();
+    Template::render("users/index", context! { users })
+}
+
+#[get("/<id>", format = "json")]
+#[allow(unused_variables)]
+pub async fn show(id: i32) -> Option<Json<Users>> {
+    // "}" in a comment and a string shouldn't end the body early
+    let _closing = "}";
+    Users::get_by_id(id).await.ok().map(Json)
+}
+
+#[post(
+    "/",
+    data = "<form>"
+)]
+pub async fn create(form: Form<NewUsers>) -> Redirect {
+    Redirect::to("/users")
+}
+
+// #[get("/commented-out")]
+// pub fn disabled() {}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![index, show, create]
+}
+"#;
+
+    const POSTS: &str = r#"use crate::structs::Posts;
+use crate::models::generated::posts;
+
+#[rocket::get("/posts?<page>")]
+pub fn list<'r>(page: Option<usize>) -> String {
+    let quote = '"';
+    format!("{}{:?}", quote, Posts::recent(10))
+}
+
+#[route(DELETE, uri = "/posts/<id>")]
+pub fn remove(id: i32) {
+    let _ = posts::delete(id);
+}
+
+#[get("/about")]
+fn unused() {}
+"#;
+
+    const MAIN: &str = r#"#[macro_use]
+extern crate rocket;
+
+mod routes;
+
+#[launch]
+fn rocket() -> _ {
+    rocket::build()
+        .mount("/users", routes::users::routes())
+        .mount(
+            "/",
+            routes![
+                routes::posts::list,
+                crate::routes::posts::remove,
+            ],
+        )
+        /* .mount("/old", routes![routes::posts::unused]) */
+        .attach(Template::fairing())
+}
+"#;
+
+    #[test]
+    fn parses_a_catalyst_style_project() {
+        let found = routes(&[("src/main.rs", MAIN), ("src/routes/users.rs", USERS), ("src/routes/posts.rs", POSTS)]);
+        assert_eq!(
+            summary(&found),
+            vec![
+                "GET /about routes::posts::unused src/routes/posts.rs:15",
+                "DELETE /posts/<id> routes::posts::remove src/routes/posts.rs:10",
+                "GET /posts?<page> routes::posts::list src/routes/posts.rs:4",
+                "GET /users routes::users::index src/routes/users.rs:6",
+                "POST /users routes::users::create src/routes/users.rs:20",
+                "GET /users/<id> routes::users::show src/routes/users.rs:12",
+            ]
+        );
+        assert!(!found[0].mounted);
+        assert!(found[1..].iter().all(|route| route.mounted));
+    }
+
+    #[test]
+    fn flags_handlers_using_dropped_models() {
+        let found = routes(&[("src/main.rs", MAIN), ("src/routes/users.rs", USERS), ("src/routes/posts.rs", POSTS)]);
+        let missing: Vec<(&str, Vec<String>)> = found.iter().map(|route| (route.handler.as_str(), route.missing_models.clone())).collect();
+        assert_eq!(missing[1], ("remove", vec!["posts".to_string()]));
+        assert_eq!(missing[2], ("list", vec!["Posts".to_string()]));
+        assert!(missing[3..].iter().all(|(_, missing)| missing.is_empty()));
+    }
+
+    #[test]
+    fn mounts_routes_in_mod_rs_under_every_prefix() {
+        let module = "#[get(\"/\")] pub fn home() {}\n#[get(\"/ping\")] pub async fn ping() {}\n";
+        let main = "fn main() { rocket::build().mount(\"/\", routes![routes::home]).mount(\"/api/\", routes![routes::home, routes::ping]); }";
+        let found = routes(&[("src/main.rs", main), ("src/routes/mod.rs", module)]);
+        assert_eq!(summary(&found), vec!["GET / routes::home src/routes/mod.rs:1", "GET /api routes::home src/routes/mod.rs:1", "GET /api/ping routes::ping src/routes/mod.rs:2"]);
+    }
+
+    #[test]
+    fn strips_comments_but_not_strings() {
+        let source = "let url = \"http://x\"; // trailing\n/* outer /* nested */ still */ let x = 1;";
+        let stripped = strip_comments(source);
+        assert_eq!(stripped.len(), source.len());
+        assert!(stripped.starts_with("let url = \"http://x\";"));
+        assert!(!stripped.contains("trailing") && !stripped.contains("nested") && !stripped.contains("still"));
+        assert!(stripped.ends_with("let x = 1;"));
+    }
+
+    #[test]
+    fn joins_and_matches_paths_like_rocket() {
+        assert_eq!(join_path("/", "/"), "/");
+        assert_eq!(join_path("/users", "/"), "/users");
+        assert_eq!(join_path("/users/", "/<id>"), "/users/<id>");
+        assert_eq!(join_path("/search", "/?<q>"), "/search?<q>");
+        assert!(route_matches("/users/<id>", "/users/3"));
+        assert!(route_matches("/files/<path..>", "/files/a/b"));
+        assert!(!route_matches("/users/<id>", "/users"));
+        assert!(route_matches("/posts?<page>", "/posts"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::configs::Config;
+use crate::file_writer;
+use crate::logger;
+
+const SEEDS_DIR: &str = "src/database/seeds";
+// Generated from the .rs seeds; it is the registry the project binary looks seeds up in
+const REGISTRY_PATH: &str = "src/database/seeds/mod.rs";
+const DATABASE_MOD_PATH: &str = "src/database/mod.rs";
+// What the project's entry point has to call for `--seed <name>` to work
+const ENTRY_CALL: &str = "database::seeds::run_from_args()";
+
+// A seed file written in Rust: anything ending in .rs except the registry itself
+pub fn is_rust_seed(file: &str) -> bool {
+    file.ends_with(".rs") && Path::new(file).file_name().is_some_and(|name| name != "mod.rs")
+}
+
+// "003_admin.rs" becomes seed_003_admin and "test/01 users.rs" test_seed_01_users; file names may start with a digit, modules may not
+fn module_name(file: &str) -> String {
+    let (prefix, name) = match file.strip_prefix("test/") {
+        Some(name) => ("test_seed", name),
+        None => ("seed", file),
+    };
+    let stem = name.trim_end_matches(".rs");
+    format!("{}_{}", prefix, stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect::<String>())
+}
+
+fn registry_source(files: &[String]) -> String {
+    let mut source = String::from("// Generated by blast from the .rs files in src/database/seeds and seeds/test. Rewritten before every `blast seed`; don't edit\n");
+    source.push_str("use diesel::pg::PgConnection;\nuse diesel::Connection;\n\n");
+    for file in files {
+        source.push_str(&format!("#[path = \"{}\"]\nmod {};\n", file, module_name(file)));
+    }
+    source.push_str("\n// Every Rust seed by file name. Each file has `pub fn run(conn: &mut PgConnection) -> Result<(), Box<dyn std::error::Error>>`\n");
+    source.push_str("pub const SEEDS: &[(&str, fn(&mut PgConnection) -> Result<(), Box<dyn std::error::Error>>)] = &[\n");
+    for file in files {
+        source.push_str(&format!("    (\"{}\", {}::run),\n", file, module_name(file)));
+    }
+    source.push_str("];\n");
+    source.push_str(
+        r#"
+// `<binary> --seed <file>` runs one seed against DATABASE_URL and exits; without --seed it returns and the app starts.
+// `blast seed` calls the binary this way, so call this first thing in main (or rocket())
+pub fn run_from_args() {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(position) = args.iter().position(|arg| arg == "--seed") else { return };
+    let name = args.get(position + 1).map(String::as_str).unwrap_or_default();
+    let Some((_, run)) = SEEDS.iter().find(|(file, _)| *file == name) else {
+        eprintln!("No Rust seed named '{}' in src/database/seeds", name);
+        std::process::exit(2);
+    };
+    let database_url = std::env::var("DATABASE_URL").unwrap_or_default();
+    let mut connection = match PgConnection::establish(&database_url) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Failed to connect to DATABASE_URL: {}", e);
+            std::process::exit(1);
+        }
+    };
+    match run(&mut connection) {
+        Ok(()) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+"#,
+    );
+    source
+}
+
+// Rewrite the registry from the .rs files in seeds/ and seeds/test/, and declare it in src/database/mod.rs
+pub fn sync_registry(project_dir: &Path) -> Result<(), String> {
+    let seeds_dir = project_dir.join(SEEDS_DIR);
+    let mut files: Vec<String> = crate::database::seed_files(&seeds_dir).into_iter().filter(|file| is_rust_seed(file)).collect();
+    files.extend(crate::database::seed_files(&seeds_dir.join("test")).into_iter().filter(|file| is_rust_seed(file)).map(|file| format!("test/{}", file)));
+    let registry_path = project_dir.join(REGISTRY_PATH);
+    if files.is_empty() && !file_writer::exists(&registry_path) {
+        return Ok(());
+    }
+    file_writer::write(&registry_path, &registry_source(&files))?;
+
+    let database_mod_path = project_dir.join(DATABASE_MOD_PATH);
+    let database_mod = file_writer::read(&database_mod_path).unwrap_or_default();
+    if !database_mod.lines().any(|line| line.trim() == "pub mod seeds;") {
+        file_writer::write(&database_mod_path, &format!("{}\npub mod seeds;\n", database_mod.trim_end()))?;
+    }
+    Ok(())
+}
+
+// Without the entry call `cargo run -- --seed` would start the server instead of seeding
+fn check_entry(project_dir: &Path) -> Result<(), String> {
+    let main = file_writer::read(project_dir.join("src/main.rs")).unwrap_or_default();
+    if main.contains(ENTRY_CALL) {
+        return Ok(());
+    }
+    Err(format!("Rust seeds run through the project binary, but src/main.rs never calls the seed entry point. Add `{};` as the first line of main (or rocket()), then run the seed again", ENTRY_CALL))
+}
+
+// Compile the project and run one Rust seed (`file` relative to seeds/) against `database_url`.
+// On failure the error carries what the seed, or the compiler, wrote to stderr
+pub fn run(config: &Config, file: &str, database_url: &str) -> Result<(), String> {
+    sync_registry(&config.project_dir)?;
+    check_entry(&config.project_dir)?;
+    let mut command = Command::new("cargo");
+    command
+        .args(["run", "--quiet", "--bin", &config.project_name, "--", "--seed", file])
+        .env("RUSTFLAGS", "-Awarnings")
+        .env("DATABASE_URL", database_url)
+        .current_dir(&config.project_dir);
+    let output = logger::command_output_at("cargo", &mut command, logger::Verbosity::Debug).map_err(|e| format!("Failed to run cargo: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        return Err(format!("Seed {} failed with {}", file, output.status));
+    }
+    Err(format!("Seed {} failed:\n{}", file, stderr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_lists_rust_seeds_under_valid_module_names() {
+        assert!(is_rust_seed("003_admin.rs"));
+        assert!(!is_rust_seed("mod.rs"));
+        assert!(!is_rust_seed("001_users.sql"));
+
+        let source = registry_source(&["003_admin.rs".to_string(), "test/01 users.rs".to_string()]);
+        assert!(source.contains("#[path = \"003_admin.rs\"]\nmod seed_003_admin;"));
+        assert!(source.contains("#[path = \"test/01 users.rs\"]\nmod test_seed_01_users;"));
+        assert!(source.contains("(\"test/01 users.rs\", test_seed_01_users::run),"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::io;
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::configs::{Config, SchemaCodegenConfig};
+use crate::logger;
+
+// `diesel print-schema` for a database URL, limited by the project's [codegen.schema].
+// Each entry of `schemas` is printed separately and the outputs are joined
+pub fn print_schema(config: &Config, database_url: &str) -> io::Result<Output> {
+    let filter = config.codegen().schema;
+
+    // diesel takes either --only-tables or --except-tables, and needs a recent enough version for either
+    let native = filter.only_tables.is_empty() != filter.except_tables.is_empty() && diesel_filters_tables();
+    if filter.filters_tables() && !native {
+        logger::debug("Filtering diesel print-schema output against [codegen.schema]").unwrap_or_default();
+    }
+
+    let schemas: Vec<Option<&str>> = if filter.schemas.is_empty() { vec![None] } else { filter.schemas.iter().map(|schema| Some(schema.as_str())).collect() };
+    let mut combined: Option<Output> = None;
+    for schema in schemas {
+        let mut command = Command::new("diesel");
+        command.args(["print-schema", "--database-url", database_url]).current_dir(&config.project_dir);
+        if let Some(schema) = schema {
+            command.args(["--schema", schema]);
+        }
+        if native && !filter.only_tables.is_empty() {
+            command.arg("--only-tables").args(&filter.only_tables);
+        } else if native {
+            command.arg("--except-tables").args(&filter.except_tables);
+        }
+
+        let output = logger::command_output("diesel", &mut command)?;
+        if !output.status.success() {
+            return Ok(output);
+        }
+        match combined.as_mut() {
+            Some(combined) => {
+                combined.stdout.push(b'\n');
+                combined.stdout.extend(output.stdout);
+                combined.stderr.extend(output.stderr);
+            }
+            None => combined = Some(output),
+        }
+    }
+
+    let mut output = combined.expect("at least one schema is printed");
+    if filter.filters_tables() && !native {
+        output.stdout = filter_output(&String::from_utf8_lossy(&output.stdout), &filter).into_bytes();
+    }
+    Ok(output)
+}
+
+// diesel_cli matches --only-tables/--except-tables as regular expressions since 2.0
+fn diesel_filters_tables() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        let Ok(output) = logger::command_output("diesel", Command::new("diesel").arg("--version")) else {
+            return false;
+        };
+        let version = String::from_utf8_lossy(&output.stdout).to_string();
+        let major = Regex::new(r"(\d+)\.\d+").unwrap().captures(&version).and_then(|captures| captures[1].parse::<u32>().ok());
+        major.is_some_and(|major| major >= 2)
+    })
+}
+
+// Index of the line closing the block opened on `start`
+fn block_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0i32;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        if depth <= 0 && index > start {
+            return index;
+        }
+    }
+    lines.len() - 1
+}
+
+// Drop the table! blocks of excluded tables from print-schema output, along with the joinable! and
+// allow_tables_to_appear_in_same_query! entries that mention them
+fn filter_output(schema: &str, filter: &SchemaCodegenConfig) -> String {
+    let lines: Vec<&str> = schema.lines().collect();
+    let mut kept: Vec<String> = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let trimmed = line.trim();
+
+        if trimmed == "diesel::table! {" || trimmed == "table! {" {
+            let end = block_end(&lines, index);
+            // The first line that isn't a doc comment or an import names the table: `users (id) {`
+            let table = lines[index + 1..end]
+                .iter()
+                .map(|line| line.trim())
+                .find(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("use "))
+                .and_then(|line| line.split([' ', '(']).next());
+            if table.is_none_or(|table| filter.includes(table)) {
+                kept.extend(lines[index..=end].iter().map(|line| line.to_string()));
+            }
+            index = end + 1;
+            continue;
+        }
+
+        if trimmed.starts_with("diesel::joinable!(") || trimmed.starts_with("joinable!(") {
+            // diesel::joinable!(posts -> users (user_id));
+            let arguments = trimmed.split_once('(').map(|(_, rest)| rest).unwrap_or_default();
+            let tables: Vec<&str> = arguments.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).filter(|word| !word.is_empty()).take(2).collect();
+            if tables.iter().all(|table| filter.includes(table)) {
+                kept.push(line.to_string());
+            }
+            index += 1;
+            continue;
+        }
+
+        if trimmed.starts_with("diesel::allow_tables_to_appear_in_same_query!(") || trimmed.starts_with("allow_tables_to_appear_in_same_query!(") {
+            let end = (index..lines.len()).find(|i| lines[*i].trim_end().ends_with(");")).unwrap_or(lines.len() - 1);
+            let text = lines[index..=end].join("\n");
+            let arguments = text.split_once('(').and_then(|(_, rest)| rest.rsplit_once(')')).map(|(arguments, _)| arguments).unwrap_or_default();
+            let tables: Vec<&str> = arguments.split(',').map(|table| table.trim()).filter(|table| !table.is_empty() && filter.includes(table)).collect();
+            // The macro needs at least two tables
+            if tables.len() >= 2 {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                kept.push(line.trim_end().split_once('(').map(|(head, _)| format!("{}(", head)).unwrap_or_default());
+                kept.extend(tables.iter().map(|table| format!("{}    {},", indent, table)));
+                kept.push(format!("{});", indent));
+            }
+            index = end + 1;
+            continue;
+        }
+
+        kept.push(line.to_string());
+        index += 1;
+    }
+
+    // Removed blocks leave their separating blank lines behind
+    let mut output = String::new();
+    let mut previous_blank = false;
+    for line in &kept {
+        let blank = line.trim().is_empty();
+        if !(blank && previous_blank) {
+            output.push_str(line);
+            output.push('\n');
+        }
+        previous_blank = blank;
+    }
+    output.trim_end().to_string() + "\n"
+}
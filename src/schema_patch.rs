@@ -0,0 +1,173 @@
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::process::Command;
+
+use regex::Regex;
+
+use crate::codegen_manifest::content_hash;
+use crate::configs::Config;
+use crate::file_writer;
+use crate::logger;
+
+// Starts the part of schema.rs copied from the patch file, which runs to the end of the file
+const MERGED_MARKER: &str = "// Merged by blast from ";
+
+// The project's patch file, when it exists: its path relative to the project and its content
+fn patch_file(config: &Config) -> Option<(String, String)> {
+    let path = config.codegen().schema.patch_path().to_string();
+    file_writer::read(config.project_dir.join(&path)).ok().map(|content| (path, content))
+}
+
+// Each table! block of a schema file: the table name and the lines the block spans
+fn table_blocks(content: &str) -> Vec<(String, Range<usize>)> {
+    let table_re = Regex::new(r"table!\s*\{").unwrap();
+    let mut blocks = Vec::new();
+    let mut search = 0;
+
+    while let Some(found) = table_re.find_at(content, search) {
+        let open = found.end() - 1;
+        let mut depth = 0;
+        let Some(close) = content[open..].char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(open + i + 1)
+        }) else {
+            break;
+        };
+
+        // The table name is the first line that isn't an attribute or comment, e.g. `#[sql_name = "..."]`
+        let name = content[open + 1..close]
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("#[") && !line.starts_with("//"))
+            .and_then(|line| line.split('(').next())
+            .map(|name| name.trim().rsplit('.').next().unwrap_or_default().to_string())
+            .unwrap_or_default();
+        let start = content[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+        let end = if content[close..].starts_with('\n') { close + 1 } else { close };
+        blocks.push((name, start..end));
+        search = end;
+    }
+
+    blocks
+}
+
+// Merge the patch into diesel's output: a table! block in the patch replaces the generated block of the same
+// table, and everything else (views, joinable! lines, ...) is appended. Merging an already merged schema again
+// gives the same result
+pub fn merge(generated: &str, patch: &str, patch_path: &str) -> String {
+    let mut schema = generated.split(MERGED_MARKER).next().unwrap_or_default().trim_end().to_string();
+    schema.push('\n');
+
+    let overrides = table_blocks(patch);
+    let generated_blocks = table_blocks(&schema);
+    for (name, range) in generated_blocks.iter().rev() {
+        if let Some((_, replacement)) = overrides.iter().find(|(patch_name, _)| patch_name == name) {
+            schema.replace_range(range.clone(), &patch[replacement.clone()]);
+        }
+    }
+
+    let mut rest = String::new();
+    let mut last = 0;
+    for (name, range) in &overrides {
+        if generated_blocks.iter().any(|(generated_name, _)| generated_name == name) {
+            rest.push_str(&patch[last..range.start]);
+            last = range.end;
+        }
+    }
+    rest.push_str(&patch[last..]);
+
+    if !rest.trim().is_empty() {
+        schema = format!("{}\n{}{}; edit it there\n{}\n", schema, MERGED_MARKER, patch_path, rest.trim());
+    }
+    schema
+}
+
+// diesel's output for the main schema with the patch file merged in
+pub fn with_patch(config: &Config, generated: &str) -> String {
+    match patch_file(config) {
+        Some((path, patch)) => merge(generated, &patch, &path),
+        None => generated.to_string(),
+    }
+}
+
+// A schema file as structs and models read it: the main schema.rs gets the patch merged in even when it was
+// written before the patch was, so joinable! lines in the patch always produce relationship methods
+pub fn read_schema(config: &Config, schema_path: &str) -> io::Result<String> {
+    let content = file_writer::read(config.project_dir.join(schema_path))?;
+    let main_schema = config.codegen().schema_path() == schema_path.trim_start_matches("./");
+    Ok(if main_schema { with_patch(config, &content) } else { content })
+}
+
+// Changes to the file git hasn't seen committed. Outside a repository, or for an untracked file, nothing counts
+fn has_uncommitted_changes(project_dir: &Path, path: &str) -> bool {
+    logger::command_output("git", Command::new("git").args(["status", "--porcelain", "--", path]).current_dir(project_dir))
+        .map(|output| {
+            let status = String::from_utf8_lossy(&output.stdout);
+            output.status.success() && !status.trim().is_empty() && !status.starts_with("??")
+        })
+        .unwrap_or(false)
+}
+
+// Write a regenerated schema file. Uncommitted edits blast didn't make are only overwritten after asking, or
+// with --force-overwrite; without a terminal they are kept. Ok(false) means the file was left as it was
+pub fn write_schema(config: &Config, path: &str, content: &str) -> Result<bool, String> {
+    let full_path = config.project_dir.join(path);
+    if let Ok(existing) = file_writer::read(&full_path) {
+        let written_by_blast = crate::state::schema_hash(&config.project_dir, path).as_deref() == Some(content_hash(&existing).as_str());
+        if existing != content && !written_by_blast && !crate::codegen_manifest::force_overwrite(path) && has_uncommitted_changes(&config.project_dir, path) {
+            let question = format!("{} has uncommitted changes blast didn't make. Overwrite them?", path);
+            let overwrite = crate::prompt::interactive() && crate::prompt::confirm(&question, false)?;
+            if !overwrite {
+                logger::warning(&format!(
+                    "Kept the uncommitted edits in {}; it was not regenerated. Move them into {} or pass --force-overwrite {}",
+                    path,
+                    patch_file(config).map(|(patch_path, _)| patch_path).unwrap_or_else(|| crate::configs::DEFAULT_SCHEMA_PATCH.to_string()),
+                    path
+                ))?;
+                return Ok(false);
+            }
+        }
+    }
+
+    file_writer::write(&full_path, content)?;
+    crate::state::record_schema_hash(&config.project_dir, path, &content_hash(content));
+    Ok(true)
+}
+
+// `diesel migration run` rewrites schema.rs through diesel.toml's [print_schema]; put the patch back
+pub fn reapply(config: &Config) {
+    let schema_path = config.codegen().schema_path().to_string();
+    let Ok(content) = file_writer::read(config.project_dir.join(&schema_path)) else { return };
+    let merged = with_patch(config, &content);
+    if merged == content {
+        return;
+    }
+    match file_writer::write(config.project_dir.join(&schema_path), &merged) {
+        Ok(()) => crate::state::record_schema_hash(&config.project_dir, &schema_path, &content_hash(&merged)),
+        Err(e) => logger::warning(&format!("Failed to merge the schema patch into {}: {}", schema_path, e)).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_replaces_tables_and_appends_the_rest_once() {
+        let generated = "diesel::table! {\n    users (id) {\n        id -> Int4,\n        type_ -> Text,\n    }\n}\n\ndiesel::table! {\n    posts (id) {\n        id -> Int4,\n    }\n}\n";
+        let patch = "// Our tweaks\ndiesel::table! {\n    users (id) {\n        id -> Int4,\n        #[sql_name = \"type\"]\n        kind -> Text,\n    }\n}\n\ndiesel::joinable!(posts -> users (author_id));\n";
+
+        let merged = merge(generated, patch, "src/database/schema_patch.rs");
+        assert!(merged.contains("#[sql_name = \"type\"]\n        kind -> Text"));
+        assert!(!merged.contains("type_ -> Text"));
+        assert!(merged.contains("posts (id)"));
+        assert!(merged.ends_with("// Merged by blast from src/database/schema_patch.rs; edit it there\n// Our tweaks\n\ndiesel::joinable!(posts -> users (author_id));\n"));
+        assert_eq!(merge(&merged, patch, "src/database/schema_patch.rs"), merged);
+        assert_eq!(table_blocks(&merged).into_iter().map(|(name, _)| name).collect::<Vec<_>>(), ["users", "posts"]);
+    }
+}
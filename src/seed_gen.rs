@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::configs::Config;
+use crate::file_writer;
+use crate::logger;
+use crate::models::{ColumnInfo, RelationshipInfo, TableInfo};
+
+const SEEDS_DIR: &str = "src/database/seeds";
+
+// "Now" for --deterministic runs, so the same seed gives the same timestamps on any day (2025-01-01 UTC)
+const DETERMINISTIC_NOW: i64 = 1_735_689_600;
+const YEAR_SECONDS: i64 = 365 * 24 * 60 * 60;
+
+// Share of nullable values left NULL
+const NULL_PROBABILITY: f64 = 0.1;
+
+const FIRST_NAMES: &[&str] = &["Ada", "Alan", "Grace", "Linus", "Margaret", "Dennis", "Barbara", "Ken", "Frances", "Edsger", "Radia", "Donald", "Hedy", "John", "Katherine", "Niklaus"];
+const LAST_NAMES: &[&str] = &["Lovelace", "Turing", "Hopper", "Torvalds", "Hamilton", "Ritchie", "Liskov", "Thompson", "Allen", "Dijkstra", "Perlman", "Knuth", "Lamarr", "Backus", "Johnson", "Wirth"];
+const CITIES: &[&str] = &["Amsterdam", "Berlin", "Lisbon", "Oslo", "Prague", "Vienna", "Zurich", "Dublin", "Madrid", "Helsinki"];
+const COUNTRIES: &[&str] = &["Netherlands", "Germany", "Portugal", "Norway", "Czechia", "Austria", "Switzerland", "Ireland", "Spain", "Finland"];
+const WORDS: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit", "sed", "do", "eiusmod", "tempor", "incididunt", "labore", "dolore", "magna",
+    "aliqua", "enim", "minim", "veniam", "quis", "nostrud", "exercitation", "ullamco", "laboris", "nisi", "aliquip", "commodo", "consequat",
+];
+
+// How the value of one column is produced
+enum Source<'a> {
+    Fake { sql_type: &'static str },
+    // Index into the referenced table's rows, resolved by the seed's CTE when it runs
+    ForeignKey { relationship: &'a RelationshipInfo, sql_type: &'static str },
+}
+
+struct ColumnPlan<'a> {
+    column: &'a ColumnInfo,
+    source: Source<'a>,
+    unique: bool,
+    // Base for unique integers, so reruns of the seed don't start at the same value
+    offset: i64,
+}
+
+// `blast seed gen <table> [--count N] [--deterministic <seed>]`
+pub fn generate_seed(config: &Config, table_name: &str, count: usize, deterministic: Option<u64>) -> Result<(), String> {
+    let schema_path = config.codegen().schema_path().to_string();
+    let tables = crate::models::load_schema_table_info(config, &schema_path).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+    let table = tables.iter().find(|table| table.name == table_name).ok_or_else(|| {
+        let names: Vec<&str> = tables.iter().map(|table| table.name.as_str()).collect();
+        format!("Table '{}' isn't in {}. Tables: {}", table_name, schema_path, names.join(", "))
+    })?;
+    if count == 0 {
+        return Err("--count must be at least 1".to_string());
+    }
+
+    let relationships: Vec<RelationshipInfo> = crate::models::load_schema_relationships(config, &schema_path)
+        .map_err(|e| format!("Failed to read {}: {}", schema_path, e))?
+        .into_iter()
+        .filter(|relationship| relationship.source_table == table.name)
+        .collect();
+    let unique = crate::database::unique_columns(config, &table.name);
+
+    let mut rng = match deterministic {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    let now = match deterministic {
+        Some(_) => DETERMINISTIC_NOW,
+        None => SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs() as i64).unwrap_or(DETERMINISTIC_NOW),
+    };
+
+    let plans = plan_columns(table, &relationships, &unique, &mut rng)?;
+    let command = match deterministic {
+        Some(seed) => format!("blast seed gen {} --count {} --deterministic {}", table.name, count, seed),
+        None => format!("blast seed gen {} --count {}", table.name, count),
+    };
+    let sql = render_seed(table, &plans, count, now, &command, &mut rng);
+
+    let seeds_dir = config.project_dir.join(SEEDS_DIR);
+    let path = seeds_dir.join(next_seed_file_name(&seeds_dir, &table.name));
+    file_writer::create_dir_all(&seeds_dir)?;
+    file_writer::write(&path, &sql)?;
+
+    logger::success(&format!("Wrote {} rows for {} to {}", count, table.name, path.strip_prefix(&config.project_dir).unwrap_or(&path).display()))?;
+    let parents: Vec<&str> = plans
+        .iter()
+        .filter_map(|plan| match plan.source {
+            Source::ForeignKey { relationship, .. } if relationship.target_table != table.name => Some(relationship.target_table.as_str()),
+            _ => None,
+        })
+        .collect();
+    if !parents.is_empty() {
+        logger::info(&format!("Foreign keys are picked from existing rows of {} when the seed runs", parents.join(", ")))?;
+    }
+    Ok(())
+}
+
+fn plan_columns<'a>(table: &'a TableInfo, relationships: &'a [RelationshipInfo], unique: &HashSet<String>, rng: &mut StdRng) -> Result<Vec<ColumnPlan<'a>>, String> {
+    let mut plans = Vec::new();
+    for column in &table.columns {
+        let Some(sql_type) = sql_type(&column.column_type) else {
+            // Enums, arrays and other custom types get their column default
+            if !column.nullable {
+                logger::warning(&format!("{}.{} is a {}, which seed gen can't fake; it's left to its column default", table.name, column.name, column.column_type))?;
+            }
+            continue;
+        };
+
+        // Serial keys are filled in by the database
+        if table.primary_key.contains(&column.name) && matches!(sql_type, "smallint" | "integer" | "bigint") {
+            continue;
+        }
+
+        let source = match relationships.iter().find(|relationship| relationship.source_column == column.name) {
+            Some(relationship) => Source::ForeignKey { relationship, sql_type },
+            None => Source::Fake { sql_type },
+        };
+        plans.push(ColumnPlan { column, source, unique: unique.contains(&column.name) || table.primary_key.contains(&column.name), offset: rng.random_range(1..1_000_000) * 1000 });
+    }
+
+    if plans.is_empty() {
+        return Err(format!("{} has no columns seed gen can fill", table.name));
+    }
+    Ok(plans)
+}
+
+// Diesel SQL type (Int4, Nullable inner types, diesel::sql_types::Text) to the Postgres type cast in the seed
+fn sql_type(diesel_type: &str) -> Option<&'static str> {
+    let name = diesel_type.rsplit("::").next().unwrap_or(diesel_type).trim();
+    Some(match name {
+        "Int2" | "SmallInt" => "smallint",
+        "Int4" | "Integer" => "integer",
+        "Int8" | "BigInt" => "bigint",
+        "Float4" | "Float" => "real",
+        "Float8" | "Double" => "double precision",
+        "Numeric" | "Decimal" => "numeric",
+        "Bool" => "boolean",
+        "Text" => "text",
+        "Varchar" | "VarChar" => "varchar",
+        "Timestamp" => "timestamp",
+        "Timestamptz" => "timestamptz",
+        "Date" => "date",
+        "Time" => "time",
+        "Uuid" => "uuid",
+        "Jsonb" => "jsonb",
+        "Json" => "json",
+        "Bytea" | "Binary" => "bytea",
+        _ => return None,
+    })
+}
+
+// Numbered after the last seed file so it runs after the seeds its foreign keys depend on
+fn next_seed_file_name(seeds_dir: &Path, table_name: &str) -> String {
+    let numbers: Vec<(u64, usize)> = fs::read_dir(seeds_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+                    digits.parse::<u64>().ok().map(|number| (number, digits.len()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let next = numbers.iter().map(|(number, _)| number + 1).max().unwrap_or(1);
+    let width = numbers.iter().map(|(_, width)| *width).max().unwrap_or(2);
+    format!("{:0width$}_{}.sql", next, table_name, width = width)
+}
+
+fn render_seed(table: &TableInfo, plans: &[ColumnPlan], count: usize, now: i64, command: &str, rng: &mut StdRng) -> String {
+    let columns: Vec<String> = plans.iter().map(|plan| quote_ident(&plan.column.name)).collect();
+    let rows: Vec<String> = (0..count).map(|row| format!("    ({})", plans.iter().map(|plan| fake_value(plan, row, now, rng)).collect::<Vec<_>>().join(", "))).collect();
+
+    let mut sql = format!("-- Generated by `{}`\n", command);
+    let foreign_keys: Vec<&ColumnPlan> = plans.iter().filter(|plan| matches!(plan.source, Source::ForeignKey { .. })).collect();
+    if foreign_keys.is_empty() {
+        sql.push_str(&format!("INSERT INTO {} ({}) VALUES\n{};\n", quote_ident(&table.name), columns.join(", "), rows.join(",\n")));
+        return sql;
+    }
+
+    // Foreign key columns hold an index into the referenced table's rows; the CTE turns it into a key that exists
+    sql.push_str("-- Foreign keys are picked from the rows their tables have when this seed runs\n");
+    let pools: Vec<String> = foreign_keys
+        .iter()
+        .filter_map(|plan| match plan.source {
+            Source::ForeignKey { relationship, .. } => Some(format!(
+                "    {} AS (SELECT {} AS key, row_number() OVER (ORDER BY {}) - 1 AS n, count(*) OVER () AS total FROM {})",
+                pool_name(plan),
+                quote_ident(&relationship.target_column),
+                quote_ident(&relationship.target_column),
+                quote_ident(&relationship.target_table)
+            )),
+            Source::Fake { .. } => None,
+        })
+        .collect();
+    let select: Vec<String> = plans
+        .iter()
+        .map(|plan| {
+            let value = format!("v.{}", quote_ident(&plan.column.name));
+            match plan.source {
+                Source::ForeignKey { sql_type, .. } => format!("(SELECT key FROM {0} WHERE {0}.n = {1} % {0}.total)::{2}", pool_name(plan), value, sql_type),
+                Source::Fake { sql_type } => format!("{}::{}", value, sql_type),
+            }
+        })
+        .collect();
+
+    sql.push_str(&format!(
+        "WITH\n{}\nINSERT INTO {} ({})\nSELECT\n    {}\nFROM (VALUES\n{}\n) AS v({});\n",
+        pools.join(",\n"),
+        quote_ident(&table.name),
+        columns.join(", "),
+        select.join(",\n    "),
+        rows.join(",\n"),
+        columns.join(", ")
+    ));
+    sql
+}
+
+fn pool_name(plan: &ColumnPlan) -> String {
+    format!("{}_pool", plan.column.name)
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+// One SQL literal for `plan` in row `row`, based on the column's type and what its name suggests
+fn fake_value(plan: &ColumnPlan, row: usize, now: i64, rng: &mut StdRng) -> String {
+    if plan.column.nullable && !plan.unique && rng.random_bool(NULL_PROBABILITY) {
+        return "NULL".to_string();
+    }
+
+    let sql_type = match plan.source {
+        // Unique foreign keys (one-to-one) take the referenced rows in order instead of at random
+        Source::ForeignKey { .. } if plan.unique => return row.to_string(),
+        Source::ForeignKey { .. } => return rng.random_range(0..1_000_000).to_string(),
+        Source::Fake { sql_type } => sql_type,
+    };
+    let name = plan.column.name.to_lowercase();
+    let has = |parts: &[&str]| parts.iter().any(|part| name == *part || name.starts_with(&format!("{}_", part)) || name.ends_with(&format!("_{}", part)));
+    let suffix = if plan.unique { format!("{}", row + 1) } else { String::new() };
+
+    match sql_type {
+        "smallint" | "integer" | "bigint" => {
+            if plan.unique {
+                return (plan.offset + row as i64).to_string();
+            }
+            let value: i64 = if sql_type == "bigint" && name.ends_with("_at") {
+                // Unix timestamps, as the Catalyst template stores created_at/updated_at
+                now - rng.random_range(0..YEAR_SECONDS)
+            } else if has(&["age"]) {
+                rng.random_range(18..=80)
+            } else if has(&["count", "quantity", "stock", "position", "rank"]) {
+                rng.random_range(0..=100)
+            } else if has(&["price", "amount", "total", "cents", "cost"]) {
+                rng.random_range(100..=100_000)
+            } else if has(&["year"]) {
+                rng.random_range(1990..=2025)
+            } else if sql_type == "smallint" {
+                rng.random_range(1..=100)
+            } else {
+                rng.random_range(1..=1000)
+            };
+            value.to_string()
+        }
+        "real" | "double precision" | "numeric" => format!("{:.2}", rng.random_range(1.0..1000.0)),
+        "boolean" => rng.random_bool(0.5).to_string(),
+        "timestamp" | "timestamptz" => quote(&format_timestamp(now - rng.random_range(0..YEAR_SECONDS), "%Y-%m-%d %H:%M:%S")),
+        "date" if has(&["birthday", "birthdate", "dob", "born"]) || name.starts_with("birth") => quote(&format_timestamp(now - rng.random_range(18 * YEAR_SECONDS..80 * YEAR_SECONDS), "%Y-%m-%d")),
+        "date" => quote(&format_timestamp(now - rng.random_range(0..YEAR_SECONDS), "%Y-%m-%d")),
+        "time" => quote(&format!("{:02}:{:02}:00", rng.random_range(0..24), rng.random_range(0..60))),
+        "uuid" => quote(&fake_uuid(rng)),
+        "json" | "jsonb" => quote("{}"),
+        "bytea" => quote(&format!("\\x{}", hex(rng, 8))),
+        _ => quote(&fake_text(&has, &suffix, rng)),
+    }
+}
+
+fn fake_text(has: &dyn Fn(&[&str]) -> bool, suffix: &str, rng: &mut StdRng) -> String {
+    let first = FIRST_NAMES.choose(rng).copied().unwrap_or("Ada");
+    let last = LAST_NAMES.choose(rng).copied().unwrap_or("Lovelace");
+
+    if has(&["email", "mail"]) {
+        return format!("{}.{}{}@example.com", first.to_lowercase(), last.to_lowercase(), suffix);
+    }
+    let text = if has(&["first_name", "firstname", "given_name"]) {
+        first.to_string()
+    } else if has(&["last_name", "lastname", "surname", "family_name"]) {
+        last.to_string()
+    } else if has(&["username", "login", "handle", "nickname"]) {
+        format!("{}{}", first.to_lowercase(), last.to_lowercase())
+    } else if has(&["name", "full_name", "author", "owner"]) {
+        format!("{} {}", first, last)
+    } else if has(&["password", "hash", "digest", "encrypted_password", "password_hash"]) {
+        // Not a usable password; seed real accounts through the app
+        "$2b$12$seedseedseedseedseedseOqvzl0gTz3w5fQJb7Zr4oRkQ0JzG6Yy".to_string()
+    } else if has(&["token", "secret", "key", "api_key"]) {
+        return format!("{}{}", hex(rng, 16), suffix);
+    } else if has(&["slug"]) {
+        words(rng, 2..4).join("-")
+    } else if has(&["url", "website", "link", "homepage"]) {
+        format!("https://example.com/{}", words(rng, 1..3).join("-"))
+    } else if has(&["avatar", "image", "photo", "picture", "thumbnail"]) {
+        format!("https://example.com/images/{}.jpg", rng.random_range(1..1000))
+    } else if has(&["phone", "mobile", "telephone"]) {
+        // 555-01xx numbers are reserved for fiction
+        format!("+1-555-01{:02}", rng.random_range(0..100))
+    } else if has(&["city", "town"]) {
+        CITIES.choose(rng).copied().unwrap_or("Berlin").to_string()
+    } else if has(&["country"]) {
+        COUNTRIES.choose(rng).copied().unwrap_or("Germany").to_string()
+    } else if has(&["address", "street"]) {
+        format!("{} {} Street", rng.random_range(1..200), capitalize(WORDS.choose(rng).copied().unwrap_or("main")))
+    } else if has(&["zip", "zipcode", "postcode", "postal_code"]) {
+        format!("{:05}", rng.random_range(1000..99999))
+    } else if has(&["color", "colour"]) {
+        format!("#{}", hex(rng, 3))
+    } else if has(&["title", "subject", "headline", "label"]) {
+        capitalize(&words(rng, 3..7).join(" "))
+    } else if has(&["description", "body", "content", "bio", "summary", "text", "message", "comment", "notes", "excerpt"]) {
+        (0..rng.random_range(1..4)).map(|_| format!("{}.", capitalize(&words(rng, 6..14).join(" ")))).collect::<Vec<_>>().join(" ")
+    } else {
+        words(rng, 1..3).join(" ")
+    };
+
+    if suffix.is_empty() {
+        text
+    } else {
+        format!("{}-{}", text, suffix)
+    }
+}
+
+fn words(rng: &mut StdRng, count: std::ops::Range<usize>) -> Vec<&'static str> {
+    let count = rng.random_range(count);
+    (0..count).map(|_| WORDS.choose(rng).copied().unwrap_or("lorem")).collect()
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn hex(rng: &mut StdRng, bytes: usize) -> String {
+    (0..bytes).map(|_| format!("{:02x}", rng.random::<u8>())).collect()
+}
+
+// Version 4 layout, from the seeded generator rather than the OS so --deterministic covers it
+fn fake_uuid(rng: &mut StdRng) -> String {
+    let mut bytes: [u8; 16] = rng.random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+fn format_timestamp(seconds: i64, format: &str) -> String {
+    chrono::DateTime::from_timestamp(seconds, 0).map(|time| time.format(format).to_string()).unwrap_or_default()
+}
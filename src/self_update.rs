@@ -0,0 +1,284 @@
+use std::env;
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use console::style;
+use flate2::read::GzDecoder;
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::logger;
+
+const REPOSITORY: &str = "Arete-Innovations/blast";
+// Set to anything to turn off the once-a-day check
+pub const NO_UPDATE_CHECK_VAR: &str = "BLAST_NO_UPDATE_CHECK";
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// Checksum files a release may publish: one per artifact (<artifact>.sha256) or one for all of them
+const CHECKSUM_FILES: &[&str] = &["SHA256SUMS", "SHA256SUMS.txt", "sha256sums.txt", "checksums.txt"];
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("CARGO_PKG_VERSION is semver")
+}
+
+fn release_version(release: &Release) -> Result<Version, String> {
+    Version::parse(release.tag_name.trim_start_matches('v')).map_err(|e| format!("Release tag '{}' isn't a version: {}", release.tag_name, e))
+}
+
+fn client(timeout: Duration) -> Result<reqwest::blocking::Client, String> {
+    // GitHub's API rejects requests without a User-Agent
+    reqwest::blocking::Client::builder().timeout(timeout).user_agent(concat!("blast/", env!("CARGO_PKG_VERSION"))).build().map_err(|e| e.to_string())
+}
+
+fn latest_release(timeout: Duration) -> Result<Release, String> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", REPOSITORY);
+    client(timeout)?
+        .get(&url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json::<Release>())
+        .map_err(|e| format!("Couldn't fetch the latest release from GitHub: {}", e))
+}
+
+// Rust target triple of the prebuilt artifacts this platform can run
+fn target_triple() -> Option<&'static str> {
+    match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+// A bare binary or a .tar.gz holding one, named after the target
+fn find_artifact<'a>(assets: &'a [ReleaseAsset], target: &str) -> Option<&'a ReleaseAsset> {
+    assets.iter().find(|asset| {
+        let name = asset.name.as_str();
+        name.starts_with("blast") && name.contains(target) && (name.ends_with(target) || name.ends_with(".exe") || name.ends_with(".tar.gz"))
+    })
+}
+
+// The hex digest for `artifact` in a checksum file: either a lone digest or `sha256sum` lines of "<digest>  <name>"
+fn expected_checksum(checksums: &str, artifact: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let digest = parts.next()?;
+        match parts.next() {
+            None => Some(digest.to_lowercase()),
+            Some(name) if name.trim_start_matches('*') == artifact => Some(digest.to_lowercase()),
+            Some(_) => None,
+        }
+    })
+}
+
+fn download(client: &reqwest::blocking::Client, url: &str) -> Result<Vec<u8>, String> {
+    client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes())
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Failed to download {}: {}", url, e))
+}
+
+fn checksum_for(client: &reqwest::blocking::Client, release: &Release, artifact: &str) -> Result<String, String> {
+    let per_artifact = format!("{}.sha256", artifact);
+    let asset = release
+        .assets
+        .iter()
+        .find(|asset| asset.name == per_artifact)
+        .or_else(|| release.assets.iter().find(|asset| CHECKSUM_FILES.contains(&asset.name.as_str())))
+        .ok_or_else(|| format!("Release {} publishes no checksum for {}, so it can't be verified", release.tag_name, artifact))?;
+    let content = String::from_utf8(download(client, &asset.browser_download_url)?).map_err(|_| format!("{} isn't a text file", asset.name))?;
+    expected_checksum(&content, artifact).ok_or_else(|| format!("{} has no checksum for {}", asset.name, artifact))
+}
+
+// The blast binary inside a .tar.gz artifact, or the artifact itself
+fn unpack(artifact: &str, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if !artifact.ends_with(".tar.gz") {
+        return Ok(bytes);
+    }
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes.as_slice()));
+    for entry in archive.entries().map_err(|e| format!("Failed to read {}: {}", artifact, e))? {
+        let mut entry = entry.map_err(|e| format!("Failed to read {}: {}", artifact, e))?;
+        let is_binary = entry.path().ok().and_then(|path| path.file_name().map(|name| name == "blast" || name == "blast.exe")).unwrap_or(false);
+        if is_binary {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary).map_err(|e| format!("Failed to unpack {}: {}", artifact, e))?;
+            return Ok(binary);
+        }
+    }
+    Err(format!("{} has no blast binary in it", artifact))
+}
+
+// Where a Windows update leaves the binary it replaced, since a running .exe can be renamed but not deleted
+fn previous_binary(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".old");
+    exe.with_file_name(name)
+}
+
+// Stage the new binary next to the running one and rename it into place, so an interrupted update never leaves half a binary
+fn replace_running_binary(binary: &[u8]) -> Result<PathBuf, String> {
+    let exe = env::current_exe().map_err(|e| format!("Can't find the running blast binary: {}", e))?;
+    // Replace the real file, not a symlink pointing at it
+    let exe = fs::canonicalize(&exe).unwrap_or(exe);
+    let staged = exe.with_file_name(format!(".{}.new", exe.file_name().unwrap_or_default().to_string_lossy()));
+    fs::write(&staged, binary).map_err(|e| format!("Can't write next to {} ({}). Re-run with permission to change it", exe.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)).map_err(|e| format!("Failed to make {} executable: {}", staged.display(), e))?;
+    }
+
+    #[cfg(windows)]
+    {
+        let previous = previous_binary(&exe);
+        let _ = fs::remove_file(&previous);
+        fs::rename(&exe, &previous).map_err(|e| format!("Failed to move {} aside: {}", exe.display(), e))?;
+        if let Err(e) = fs::rename(&staged, &exe) {
+            let _ = fs::rename(&previous, &exe);
+            return Err(format!("Failed to install the new binary: {}", e));
+        }
+    }
+
+    #[cfg(not(windows))]
+    if let Err(e) = fs::rename(&staged, &exe) {
+        let _ = fs::remove_file(&staged);
+        return Err(format!("Failed to install the new binary: {}", e));
+    }
+
+    Ok(exe)
+}
+
+fn cargo_install_hint(tag: &str) -> String {
+    format!("cargo install --git https://github.com/{} --tag {} --force", REPOSITORY, tag)
+}
+
+// `blast self update`: `check_only` reports the latest version without installing it
+pub fn run(check_only: bool) -> Result<(), String> {
+    if let Ok(exe) = env::current_exe() {
+        let _ = fs::remove_file(previous_binary(&exe));
+    }
+
+    let release = latest_release(Duration::from_secs(15))?;
+    let latest = release_version(&release)?;
+    let current = current_version();
+    record_check(Some(&latest));
+    if latest <= current {
+        return logger::success(&format!("blast {} is the latest version", current));
+    }
+    if check_only {
+        return logger::plain(&format!("blast {} is available (you have {}). Run `blast self update` to install it", latest, current));
+    }
+
+    let artifact = target_triple().and_then(|target| find_artifact(&release.assets, target));
+    let Some(artifact) = artifact else {
+        logger::warning(&format!("Release {} has no prebuilt binary for {}-{}", release.tag_name, env::consts::OS, env::consts::ARCH))?;
+        return logger::plain(&format!("Install it from source with: {}", cargo_install_hint(&release.tag_name)));
+    };
+
+    let client = client(Duration::from_secs(300))?;
+    let expected = checksum_for(&client, &release, &artifact.name)?;
+    logger::plain(&format!("Downloading {}...", artifact.name))?;
+    let bytes = download(&client, &artifact.browser_download_url)?;
+    let actual = format!("{:x}", Sha256::digest(&bytes));
+    if actual != expected {
+        return Err(format!("Checksum mismatch for {}: expected {}, got {}. Nothing was changed", artifact.name, expected, actual));
+    }
+
+    let binary = unpack(&artifact.name, bytes)?;
+    let exe = replace_running_binary(&binary)?;
+    logger::success(&format!("Updated blast {} to {} at {}", current, latest, exe.display()))
+}
+
+// Last check time and the version it found, one per line
+fn check_file() -> Option<PathBuf> {
+    crate::platform::user_config_dir().map(|dir| dir.join("blast").join("update-check"))
+}
+
+fn record_check(latest: Option<&Version>) {
+    let Some(path) = check_file() else { return };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+    let latest = latest.map(|version| version.to_string()).unwrap_or_default();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, format!("{}\n{}\n", now, latest));
+}
+
+// The latest version from the last check while it's under a day old, otherwise from GitHub.
+// A failed lookup is recorded too, so an offline machine doesn't retry on every command
+fn latest_known_version() -> Option<Version> {
+    let cached = check_file().and_then(|path| fs::read_to_string(path).ok()).unwrap_or_default();
+    let mut lines = cached.lines();
+    let checked_at = lines.next().and_then(|line| line.parse::<u64>().ok());
+    let fresh = checked_at.is_some_and(|secs| SystemTime::now().duration_since(UNIX_EPOCH + Duration::from_secs(secs)).map(|age| age < CHECK_INTERVAL).unwrap_or(true));
+    if fresh {
+        return lines.next().and_then(|line| Version::parse(line).ok());
+    }
+
+    let latest = latest_release(Duration::from_secs(2)).ok().and_then(|release| release_version(&release).ok());
+    record_check(latest.as_ref());
+    latest
+}
+
+// The once-a-day check: a single dim line on stderr when a newer blast is out. Off with
+// [settings] update_check = false, BLAST_NO_UPDATE_CHECK, or when stderr isn't a terminal
+pub fn notify_if_outdated(enabled: bool) {
+    if !enabled || env::var_os(NO_UPDATE_CHECK_VAR).is_some() || !std::io::stderr().is_terminal() || logger::get_mode() == logger::RuntimeMode::Json {
+        return;
+    }
+    let current = current_version();
+    if let Some(latest) = latest_known_version().filter(|latest| *latest > current) {
+        eprintln!("{}", style(format!("blast {} is available (you have {}). Run `blast self update`", latest, current)).dim());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str) -> ReleaseAsset {
+        ReleaseAsset { name: name.to_string(), browser_download_url: format!("https://example.com/{}", name) }
+    }
+
+    #[test]
+    fn picks_the_artifact_and_its_checksum() {
+        let assets = vec![
+            asset("blast-x86_64-unknown-linux-gnu.tar.gz.sha256"),
+            asset("blast-x86_64-unknown-linux-gnu.tar.gz"),
+            asset("blast-aarch64-apple-darwin"),
+            asset("blast-x86_64-pc-windows-msvc.exe"),
+            asset("SHA256SUMS"),
+        ];
+        assert_eq!(find_artifact(&assets, "x86_64-unknown-linux-gnu").map(|asset| asset.name.as_str()), Some("blast-x86_64-unknown-linux-gnu.tar.gz"));
+        assert_eq!(find_artifact(&assets, "aarch64-apple-darwin").map(|asset| asset.name.as_str()), Some("blast-aarch64-apple-darwin"));
+        assert_eq!(find_artifact(&assets, "x86_64-pc-windows-msvc").map(|asset| asset.name.as_str()), Some("blast-x86_64-pc-windows-msvc.exe"));
+        assert!(find_artifact(&assets, "aarch64-unknown-linux-gnu").is_none());
+
+        let sums = "ABC123  blast-aarch64-apple-darwin\ndef456 *blast-x86_64-pc-windows-msvc.exe\n";
+        assert_eq!(expected_checksum(sums, "blast-aarch64-apple-darwin").as_deref(), Some("abc123"));
+        assert_eq!(expected_checksum(sums, "blast-x86_64-pc-windows-msvc.exe").as_deref(), Some("def456"));
+        assert_eq!(expected_checksum(sums, "blast-x86_64-unknown-linux-gnu.tar.gz"), None);
+        assert_eq!(expected_checksum("0fab  \n", "anything").as_deref(), Some("0fab"));
+    }
+}
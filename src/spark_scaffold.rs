@@ -0,0 +1,216 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use console::style;
+
+use crate::file_writer;
+use crate::logger;
+
+const MANIFEST: &str = r#"# Spark manifest, read by `blast spark add`. Check it with `blast spark lint <dir>` before publishing
+
+# Required metadata
+[spark]
+name = "{name}"
+version = "0.1.0"
+description = "What {name} adds to a Catalyst app"
+author = "Your Name <you@example.com>"
+license = "MIT"
+
+# Crates added to the app's Cargo.toml: a version requirement, or a table with `version` and `features`.
+# When the app already needs an incompatible version, `blast spark add` asks which requirement to keep.
+# A crate without a version is pinned to its latest release on crates.io at install time
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+
+# Environment variables the spark reads. Each is added to the app's .env as <SPARK>_<NAME>, here
+# {upper}_API_KEY, and the text after `#` becomes its comment
+[config]
+required_env = ["API_KEY # Key for the service {name} talks to"]
+
+# Migrations run on install. `path` is relative to this directory and holds either up.sql/down.sql or
+# diesel-style <timestamp>_<name>/ directories
+[[migrations]]
+name = "create_{name}_items"
+path = "migrations"
+"#;
+
+const MOD_RS: &str = r#"// Module root once the spark is installed as src/services/sparks/{name}/. The app's registry_generated.rs
+// registers {name}::create_spark under the name "{name}"
+#[path = "src/lib.rs"]
+mod spark;
+
+pub use spark::*;
+"#;
+
+const LIB_RS: &str = r#"use crate::services::sparks::registry::Spark;
+
+// Entry point the app's registry calls. The return type is what register_spark in the app's
+// src/services/sparks/registry.rs takes
+pub fn create_spark() -> Box<dyn Spark> {
+    Box::new({camel})
+}
+
+pub struct {camel};
+
+impl Spark for {camel} {
+    fn name(&self) -> &str {
+        "{name}"
+    }
+}
+"#;
+
+const UP_SQL: &str = "CREATE TABLE {name}_items (\n    id SERIAL PRIMARY KEY,\n    name VARCHAR NOT NULL,\n    created_at TIMESTAMP NOT NULL DEFAULT NOW()\n);\n";
+
+const DOWN_SQL: &str = "DROP TABLE {name}_items;\n";
+
+const README: &str = r#"# {name}
+
+A spark for Catalyst apps.
+
+## Install
+
+```bash
+blast spark add https://github.com/<you>/{name}
+```
+
+blast installs the spark under the repository's name and uses it as the Rust module name, so keep the
+repository named `{name}`.
+
+Set `{upper}_API_KEY` in `.env` afterwards.
+
+## Develop
+
+- `manifest.toml`: metadata, dependencies, required env variables and migrations
+- `src/lib.rs`: `create_spark`, the entry point the app's registry calls
+- `migrations/`: run when the spark is installed
+
+Run `blast spark lint .` before publishing.
+"#;
+
+fn render(template: &str, name: &str) -> String {
+    let camel: String = name
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect();
+    let type_name = if camel.ends_with("Spark") { camel } else { format!("{}Spark", camel) };
+    template.replace("{name}", name).replace("{upper}", &name.to_uppercase()).replace("{camel}", &type_name)
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// `blast spark new <name> [--dir <path>]`: a spark skeleton in `dir`, ./<name> by default
+pub fn new(name: &str, dir: Option<&str>) -> Result<(), String> {
+    if !is_identifier(name) || name.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(format!("'{}' can't be a spark name: it becomes a Rust module, so use lowercase letters, digits and underscores", name));
+    }
+
+    let dir = dir.map(PathBuf::from).unwrap_or_else(|| PathBuf::from(name));
+    if fs::read_dir(&dir).map(|mut entries| entries.next().is_some()).unwrap_or(false) {
+        return Err(format!("{} already exists and isn't empty", dir.display()));
+    }
+
+    let migration = format!("{}_create_{}_items", chrono::Local::now().format("%Y-%m-%d-%H%M%S"), name);
+    let files = [
+        ("manifest.toml".to_string(), MANIFEST),
+        ("mod.rs".to_string(), MOD_RS),
+        ("src/lib.rs".to_string(), LIB_RS),
+        (format!("migrations/{}/up.sql", migration), UP_SQL),
+        (format!("migrations/{}/down.sql", migration), DOWN_SQL),
+        ("README.md".to_string(), README),
+    ];
+    for (path, template) in &files {
+        file_writer::write(dir.join(path), &render(template, name))?;
+    }
+
+    logger::success(&format!("Created spark '{}' in {}", name, dir.display()))?;
+    for (path, _) in &files {
+        logger::plain(&format!("  {}", path))?;
+    }
+    logger::plain(&format!("Check it with `blast spark lint {}`, then push it to a repository named {}", dir.display(), name))
+}
+
+// Whether a manifest migration path holds a migration: up.sql directly, or directories that each have one
+fn has_migrations(path: &Path) -> bool {
+    path.join("up.sql").is_file() || fs::read_dir(path).map(|entries| entries.flatten().any(|entry| entry.path().join("up.sql").is_file())).unwrap_or(false)
+}
+
+// Problems with a spark directory: (is an error, message). Warnings don't fail the lint
+fn lint_problems(dir: &Path) -> Vec<(bool, String)> {
+    let manifest = match crate::sparks::validate_manifest(&dir.join("manifest.toml")) {
+        Ok(manifest) => manifest,
+        Err(e) => return vec![(true, e)],
+    };
+
+    let mut problems = Vec::new();
+    if !is_identifier(&manifest.name) {
+        problems.push((false, format!("name '{}' isn't a valid Rust identifier; the installed spark is a module named after its repository, so give that a valid name", manifest.name)));
+    }
+    if semver::Version::parse(&manifest.version).is_err() {
+        problems.push((true, format!("version '{}' isn't a semver version", manifest.version)));
+    }
+
+    for dependency in &manifest.dependencies {
+        match &dependency.version {
+            Some(version) => {
+                if let Err(e) = semver::VersionReq::parse(version) {
+                    problems.push((true, format!("dependency {} has an invalid version requirement '{}': {}", dependency.crate_name, version, e)));
+                }
+            }
+            None => problems.push((false, format!("dependency {} has no version; installing pins whatever is latest at the time", dependency.crate_name))),
+        }
+    }
+
+    for env in &manifest.required_env {
+        let var = env.split('#').next().unwrap_or(env).trim();
+        if !is_identifier(var) {
+            problems.push((true, format!("required_env entry '{}' isn't a valid variable name (letters, digits and underscores)", var)));
+        }
+    }
+
+    for migration in &manifest.migrations {
+        let path = dir.join(&migration.path);
+        if !path.is_dir() {
+            problems.push((true, format!("migration '{}': {} doesn't exist", migration.name, migration.path)));
+        } else if !has_migrations(&path) {
+            problems.push((true, format!("migration '{}': {} has no up.sql", migration.name, migration.path)));
+        }
+    }
+
+    if !dir.join("mod.rs").is_file() {
+        problems.push((true, "mod.rs is missing; the installed spark directory is declared as a module and needs it".to_string()));
+    }
+    let defines_entry_point = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|extension| extension == "rs"))
+        .any(|entry| fs::read_to_string(entry.path()).map(|source| source.contains("fn create_spark")).unwrap_or(false));
+    if !defines_entry_point {
+        problems.push((true, "no create_spark function; the app's registry calls <spark>::create_spark".to_string()));
+    }
+
+    problems
+}
+
+// `blast spark lint <path>`: validate_manifest plus the checks that catch a broken spark before it's published
+pub fn lint(path: &str) -> Result<(), String> {
+    let path = Path::new(path);
+    let dir = if path.is_file() { path.parent().unwrap_or(Path::new(".")) } else { path };
+    let problems = lint_problems(dir);
+
+    for (is_error, message) in &problems {
+        let marker = if *is_error { style("✗").red() } else { style("!").yellow() };
+        logger::plain(&format!("  {} {}", marker, message))?;
+    }
+
+    let errors = problems.iter().filter(|(is_error, _)| *is_error).count();
+    if errors > 0 {
+        return Err(format!("{} has {} problem(s) to fix before publishing", dir.display(), errors));
+    }
+    logger::success(&format!("{} is ready to publish", dir.display()))
+}
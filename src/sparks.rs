@@ -1,33 +1,25 @@
 use std::fs;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::configs::Config;
+use crate::envfile;
+use crate::envfile::EnvFile;
 use crate::logger;
+use crate::mod_file;
 
-// Function to run migrations from a spark plugin
-fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
-    use dotenv::dotenv;
+// Function to run migrations from a spark plugin. Returns whether any migration was applied
+fn run_spark_migration(config: &Config, migration_path: &PathBuf) -> Result<bool, String> {
     use std::collections::HashMap;
     use std::env;
     use std::fs;
-    use std::path::Path;
-    use std::process::{Command, Stdio};
-
-    // Load environment variables from .env file to ensure they're available to migrations
-    dotenv().ok();
-
-    // Check if verbose mode is enabled using the logger's built-in mechanism
-    // This handles both the -v flag and the BLAST_VERBOSE environment variable
-    // Declare it as a function that wraps the logger's verbosity check
-    let is_verbose = || {
-        // Access the verbosity state from the logger
-        // This effectively uses the same check that logger::info uses internally
-        env::var("BLAST_VERBOSE").unwrap_or_else(|_| String::from("0")) == "1" || env::var("BLAST_INTERACTIVE").is_ok()
-    };
+    use std::process::Command;
+
+    // Load .env, .env.<environment> and .env.local to ensure they're available to migrations
+    envfile::load_project_env(config).apply();
 
-    if is_verbose() {
+    if logger::level() >= logger::Verbosity::Debug {
         logger::info("VERBOSE MODE ENABLED - Showing detailed migration debugging info")?;
     }
 
@@ -108,7 +100,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
     logger::info(&format!("Looking for database URL for spark: {}", spark_name))?;
 
     // Verbose debugging: List all the path components for easier diagnosis
-    if is_verbose() {
+    if logger::level() >= logger::Verbosity::Debug {
         logger::info("Path component analysis:")?;
         for component in migration_path.components() {
             if let Some(comp_str) = component.as_os_str().to_str() {
@@ -152,17 +144,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
         for (key, value) in env::vars() {
             if key.contains("DATABASE") || key.contains(spark_name.as_str()) {
                 // Mask the actual connection string for security
-                let masked_value = if value.contains("://") {
-                    let parts: Vec<&str> = value.splitn(2, "://").collect();
-                    if parts.len() == 2 {
-                        format!("{}://<masked>", parts[0])
-                    } else {
-                        "<masked>".to_string()
-                    }
-                } else {
-                    "<masked>".to_string()
-                };
-                logger::info(&format!("  - {}={}", key, masked_value))?;
+                logger::info(&format!("  - {}={}", key, crate::envfile::mask_secret(&value)))?;
             }
         }
     }
@@ -197,10 +179,10 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
     logger::info("Running diesel setup to prepare database")?;
 
     // In verbose mode, also examine the diesel.toml file if it exists
-    if is_verbose() {
-        let diesel_toml_path = Path::new("diesel.toml");
+    if logger::level() >= logger::Verbosity::Debug {
+        let diesel_toml_path = config.project_dir.join("diesel.toml");
         if diesel_toml_path.exists() {
-            match fs::read_to_string(diesel_toml_path) {
+            match fs::read_to_string(&diesel_toml_path) {
                 Ok(content) => {
                     logger::info("diesel.toml content:")?;
                     for line in content.lines() {
@@ -210,7 +192,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
                 Err(e) => logger::warning(&format!("Failed to read diesel.toml: {}", e))?,
             }
         } else {
-            logger::info("diesel.toml file not found in current directory")?;
+            logger::info("diesel.toml file not found in the project directory")?;
         }
 
         // Also check if the up.sql file exists and show its content
@@ -238,11 +220,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
     };
 
     // Always use the explicit --database-url parameter
-    let setup_output = Command::new("diesel")
-        .args(["setup", "--migration-dir", &migration_path.to_string_lossy(), "--database-url", &setup_db_url])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
+    let setup_output = logger::command_output("diesel", Command::new("diesel").args(["setup", "--migration-dir", &migration_path.to_string_lossy(), "--database-url", &setup_db_url]).current_dir(&config.project_dir));
 
     match setup_output {
         Ok(output) => {
@@ -259,39 +237,22 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
     // Step 2: First try running the migration normally
     logger::info("Running diesel migration")?;
 
-    if is_verbose() {
+    if logger::level() >= logger::Verbosity::Debug {
         // In verbose mode, display the exact diesel command and environment
         let cmd_str = format!("diesel migration run --migration-dir {}", migration_path.display());
         logger::info(&format!("Running command: {}", cmd_str))?;
 
         // Check if the DATABASE_URL is set correctly
         if let Some(db_url) = env_vars.get("DATABASE_URL") {
-            let masked_url = if db_url.contains("://") {
-                let parts: Vec<&str> = db_url.splitn(2, "://").collect();
-                if parts.len() == 2 {
-                    format!("{}://<masked>", parts[0])
-                } else {
-                    "<masked>".to_string()
-                }
-            } else {
-                "<masked>".to_string()
-            };
-            logger::info(&format!("Using DATABASE_URL={}", masked_url))?;
+            logger::info(&format!("Using DATABASE_URL={}", crate::envfile::mask_secret(db_url)))?;
         } else {
             logger::warning("DATABASE_URL is not set in environment!")?;
         }
 
         // Check for diesel binary
-        match Command::new("which").arg("diesel").output() {
-            Ok(output) => {
-                if output.status.success() {
-                    let diesel_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    logger::info(&format!("Diesel binary found at: {}", diesel_path))?;
-                } else {
-                    logger::warning("Diesel binary not found in PATH!")?;
-                }
-            }
-            Err(e) => logger::warning(&format!("Failed to check for diesel binary: {}", e))?,
+        match crate::platform::command_path("diesel") {
+            Some(diesel_path) => logger::info(&format!("Diesel binary found at: {}", diesel_path))?,
+            None => logger::warning("Diesel binary not found in PATH!")?,
         }
     }
 
@@ -305,11 +266,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
     };
 
     // Always use --database-url to ensure we're targeting the correct database
-    let output = Command::new("diesel")
-        .args(["migration", "run", "--migration-dir", &migration_path.to_string_lossy(), "--database-url", &db_url])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
+    let output = logger::command_output("diesel", Command::new("diesel").args(["migration", "run", "--migration-dir", &migration_path.to_string_lossy(), "--database-url", &db_url]).current_dir(&config.project_dir))
         .map_err(|e| format!("Failed to execute diesel migration: {}", e))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -321,7 +278,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
         logger::info(&format!("Diesel migration stderr: {}", stderr))?;
     }
 
-    if is_verbose() {
+    if logger::level() >= logger::Verbosity::Debug {
         // In verbose mode, add extra diagnostic information
         logger::info(&format!("Diesel command exit status: {}", output.status))?;
         logger::info(&format!("Diesel command success: {}", output.status.success()))?;
@@ -343,7 +300,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
 
     if !migrations.is_empty() {
         progress.success(&format!("Ran {} migrations: {}", migrations.len(), migrations.join(", ")));
-        return Ok(());
+        return Ok(true);
     }
 
     // Step 3: If no migrations were run but command succeeded, try forcing the migration
@@ -366,11 +323,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
         };
 
         // Always use the explicit --database-url parameter
-        let redo_output = Command::new("diesel")
-            .args(["migration", "redo", "--migration-dir", &migration_path.to_string_lossy(), "--database-url", &redo_db_url])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output();
+        let redo_output = logger::command_output("diesel", Command::new("diesel").args(["migration", "redo", "--migration-dir", &migration_path.to_string_lossy(), "--database-url", &redo_db_url]).current_dir(&config.project_dir));
 
         match redo_output {
             Ok(output) => {
@@ -384,7 +337,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
 
                 if output.status.success() && (stdout.contains("Rolling back") || stdout.contains("Running")) {
                     progress.success("Successfully ran migration via redo");
-                    return Ok(());
+                    return Ok(true);
                 } else {
                     logger::warning("Migration redo didn't run anything either")?;
                 }
@@ -400,7 +353,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
             Ok(content) => content,
             Err(e) => {
                 logger::warning(&format!("Failed to read up.sql file: {}", e))?;
-                return Ok(());
+                return Ok(false);
             }
         };
 
@@ -411,25 +364,26 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
             url.clone()
         } else {
             logger::warning("No database URL available for direct SQL execution")?;
-            return Ok(());
+            return Ok(false);
         };
 
         // Write SQL to a temporary file
         let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
-        let temp_sql_path = format!("/tmp/direct_migration_{}.sql", timestamp);
+        let temp_sql = crate::platform::TempPath::new(env::temp_dir().join(format!("direct_migration_{}.sql", timestamp)));
+        let temp_sql_path = temp_sql.path();
 
-        if let Err(e) = fs::write(&temp_sql_path, &up_sql_content) {
+        if let Err(e) = fs::write(temp_sql_path, &up_sql_content) {
             logger::warning(&format!("Failed to write temporary SQL file: {}", e))?;
-            return Ok(());
+            return Ok(false);
         }
 
         // Execute the SQL directly with psql
-        logger::info(&format!("Executing SQL with psql from: {}", temp_sql_path))?;
+        logger::info(&format!("Executing SQL with psql from: {}", temp_sql_path.display()))?;
 
-        let psql_output = Command::new("psql").arg(&db_url).arg("-f").arg(&temp_sql_path).stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let psql_output = logger::command_output("psql", Command::new("psql").arg(&db_url).arg("-f").arg(temp_sql_path));
 
         // Clean up temp file no matter what
-        let _ = fs::remove_file(&temp_sql_path);
+        drop(temp_sql);
 
         match psql_output {
             Ok(output) => {
@@ -451,14 +405,15 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
                     // Write SQL to insert the migration record
                     let migration_record_sql = format!("INSERT INTO __diesel_schema_migrations (version, run_on) VALUES ('{}', NOW()) ON CONFLICT DO NOTHING;", version);
 
-                    let record_sql_path = format!("/tmp/update_migrations_{}.sql", timestamp);
-                    if let Err(e) = fs::write(&record_sql_path, &migration_record_sql) {
+                    let record_sql = crate::platform::TempPath::new(env::temp_dir().join(format!("update_migrations_{}.sql", timestamp)));
+                    let record_sql_path = record_sql.path();
+                    if let Err(e) = fs::write(record_sql_path, &migration_record_sql) {
                         logger::warning(&format!("Failed to write migration record SQL file: {}", e))?;
                     } else {
-                        let update_output = Command::new("psql").arg(&db_url).arg("-f").arg(&record_sql_path).stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+                        let update_output = logger::command_output("psql", Command::new("psql").arg(&db_url).arg("-f").arg(record_sql_path));
 
                         // Clean up temp file
-                        let _ = fs::remove_file(&record_sql_path);
+                        drop(record_sql);
 
                         match update_output {
                             Ok(update_out) => {
@@ -474,7 +429,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
                     }
 
                     progress.success("Successfully executed migration SQL directly");
-                    return Ok(());
+                    return Ok(true);
                 } else {
                     logger::warning(&format!("Direct SQL execution failed: {}", stderr))?;
                 }
@@ -488,7 +443,7 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
     progress.success("No new migrations to run");
 
     // In verbose mode, check the database schema to see if tables were actually created
-    if is_verbose() {
+    if logger::level() >= logger::Verbosity::Debug {
         logger::info("Checking database schema to verify if tables exist...")?;
 
         // Get database URL for schema check - use the spark-specific URL
@@ -508,10 +463,10 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
 
         // Always use the explicit database URL parameter
         let schema_output = if !db_url.is_empty() {
-            Command::new("diesel").args(["print-schema", "--database-url", &db_url]).stdout(Stdio::piped()).stderr(Stdio::piped()).output()
+            crate::schema_filter::print_schema(config, &db_url)
         } else {
             logger::warning("No database URL available for schema check")?;
-            return Ok(());
+            return Ok(false);
         };
 
         match schema_output {
@@ -571,12 +526,13 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
 
                             // Create temporary SQL file to query tables
                             let temp_sql = "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public';";
-                            let temp_sql_path = Path::new("/tmp/check_tables.sql");
+                            let temp_sql_guard = crate::platform::TempPath::new(env::temp_dir().join("check_tables.sql"));
+                            let temp_sql_path = temp_sql_guard.path();
                             if let Err(e) = fs::write(temp_sql_path, temp_sql) {
                                 logger::warning(&format!("Failed to write temporary SQL file: {}", e))?;
                             } else {
                                 // Run psql to check tables
-                                let psql_output = Command::new("psql").arg(db_url).arg("-f").arg(temp_sql_path).stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+                                let psql_output = logger::command_output("psql", Command::new("psql").arg(db_url).arg("-f").arg(temp_sql_path));
 
                                 match psql_output {
                                     Ok(output) => {
@@ -589,8 +545,6 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
                                     Err(e) => logger::warning(&format!("Failed to query database tables: {}", e))?,
                                 }
 
-                                // Clean up temp file
-                                let _ = fs::remove_file(temp_sql_path);
                             }
                         }
                     }
@@ -599,35 +553,107 @@ fn run_spark_migration(migration_path: &PathBuf) -> Result<(), String> {
         }
     }
 
-    Ok(())
+    Ok(false)
+}
+
+// Where a spark is installed from: a git repository, or a directory on disk that's copied in, or symlinked with
+// `--link` so edits to it show up without reinstalling. Catalyst.toml records git sources as a URL string and
+// directories as `{ path = "../my-spark" }`, with `link = true` for symlinked ones
+#[derive(Debug, Clone, PartialEq)]
+pub enum SparkOrigin {
+    Git(String),
+    Path(PathBuf),
+    Link(PathBuf),
+}
+
+impl SparkOrigin {
+    // What `spark add` was given. `file:` URLs, and anything that's neither a URL nor an scp-style git address
+    // (git@host:org/repo), are directories
+    pub fn parse(source: &str, link: bool) -> Result<SparkOrigin, String> {
+        let path = match source.strip_prefix("file://").or_else(|| source.strip_prefix("file:")) {
+            Some(path) => Some(PathBuf::from(path)),
+            None if source.contains("://") || source.contains('@') => None,
+            None => Some(PathBuf::from(source)),
+        };
+        match path {
+            Some(path) if link => Ok(SparkOrigin::Link(path)),
+            Some(path) => Ok(SparkOrigin::Path(path)),
+            None if link => Err(format!("--link needs a spark directory on disk, and {} is a git URL", logger::redact(source))),
+            None => Ok(SparkOrigin::Git(source.to_string())),
+        }
+    }
+
+    // A [sparks] entry of Catalyst.toml
+    pub fn from_value(value: &toml::Value) -> Option<SparkOrigin> {
+        match value {
+            toml::Value::String(source) => SparkOrigin::parse(source, false).ok(),
+            toml::Value::Table(table) => {
+                let path = PathBuf::from(table.get("path")?.as_str()?);
+                let link = table.get("link").and_then(|link| link.as_bool()).unwrap_or(false);
+                Some(if link { SparkOrigin::Link(path) } else { SparkOrigin::Path(path) })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_item(&self) -> toml_edit::Item {
+        match self {
+            SparkOrigin::Git(url) => toml_edit::value(url.as_str()),
+            SparkOrigin::Path(path) | SparkOrigin::Link(path) => {
+                let mut table = toml_edit::InlineTable::new();
+                table.insert("path", path.to_string_lossy().as_ref().into());
+                if self.is_link() {
+                    table.insert("link", true.into());
+                }
+                toml_edit::value(table)
+            }
+        }
+    }
+
+    pub fn is_link(&self) -> bool {
+        matches!(self, SparkOrigin::Link(_))
+    }
+}
+
+impl std::fmt::Display for SparkOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SparkOrigin::Git(url) => f.write_str(&logger::redact(url)),
+            SparkOrigin::Path(path) => write!(f, "{}", path.display()),
+            SparkOrigin::Link(path) => write!(f, "{} (linked)", path.display()),
+        }
+    }
+}
+
+// The [sparks] entry Catalyst.toml has for a spark, if it has a valid one
+fn recorded_origin(catalyst: &str, spark_name: &str) -> Option<SparkOrigin> {
+    let doc = catalyst.parse::<toml::Value>().ok()?;
+    SparkOrigin::from_value(doc.get("sparks")?.get(spark_name)?)
 }
 
 // Function to update or add sparks in Catalyst.toml file
-pub fn update_sparks_toml(spark_name: &str, repo_url: &str) -> Result<bool, String> {
+pub fn update_sparks_toml(config: &Config, spark_name: &str, source: &SparkOrigin) -> Result<bool, String> {
     // Update the Catalyst.toml file to add the spark
-    let config_path = Path::new("Catalyst.toml");
+    let config_path = config.project_dir.join("Catalyst.toml");
     if !config_path.exists() {
-        return Err(format!("Catalyst.toml not found in the current directory"));
+        return Err(format!("Catalyst.toml not found in {}", config.project_dir.display()));
     }
 
     // Read and parse the TOML
-    let toml_content = fs::read_to_string(config_path).map_err(|e| format!("Failed to read Catalyst.toml: {}", e))?;
+    let toml_content = fs::read_to_string(&config_path).map_err(|e| format!("Failed to read Catalyst.toml: {}", e))?;
 
     // Parse the content as a document to preserve formatting and comments
     let mut doc = toml_content.parse::<toml_edit::DocumentMut>().map_err(|e| format!("Failed to parse Catalyst.toml: {}", e))?;
 
     // Check if the spark already exists
     let mut spark_already_exists = false;
-    if let Some(sparks_table) = doc.get("sparks").and_then(|s| s.as_table()) {
-        if sparks_table.contains_key(spark_name) {
-            let existing_url = sparks_table.get(spark_name).and_then(|v| v.as_str()).unwrap_or("");
-            if existing_url == repo_url {
-                logger::info(&format!("Spark {} is already in Catalyst.toml with the same URL", spark_name))?;
-                spark_already_exists = true;
-            } else {
-                logger::warning(&format!("Spark {} is already in Catalyst.toml but with a different URL. Updating...", spark_name))?;
-                // Will update the URL below
-            }
+    if doc.get("sparks").and_then(|s| s.as_table()).is_some_and(|sparks_table| sparks_table.contains_key(spark_name)) {
+        if recorded_origin(&toml_content, spark_name).as_ref() == Some(source) {
+            logger::info(&format!("Spark {} is already in Catalyst.toml with the same source", spark_name))?;
+            spark_already_exists = true;
+        } else {
+            logger::warning(&format!("Spark {} is already in Catalyst.toml but with a different source. Updating...", spark_name))?;
+            // Will update the source below
         }
     }
 
@@ -652,96 +678,270 @@ pub fn update_sparks_toml(spark_name: &str, repo_url: &str) -> Result<bool, Stri
 
     // Now add or update the spark in the sparks table
     let sparks_table = doc["sparks"].as_table_mut().unwrap();
-    sparks_table[spark_name] = toml_edit::value(repo_url);
+    sparks_table[spark_name] = source.to_item();
 
     // Write the updated TOML back to the file
     let updated_content = doc.to_string();
     fs::write(config_path, updated_content).map_err(|e| format!("Failed to write updated Catalyst.toml: {}", e))?;
 
-    logger::info(&format!("Updated Catalyst.toml with spark: {} -> {}", spark_name, repo_url))?;
+    logger::info(&format!("Updated Catalyst.toml with spark: {} -> {}", spark_name, source))?;
     Ok(true)
 }
 
-// Main function to add a spark plugin
-pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
-    let mut progress = logger::create_progress(None);
-    progress.set_message(&format!("Adding spark plugin from: {}", repo_url));
+// One [dependencies] change `spark add` would make to Cargo.toml
+#[derive(Debug, PartialEq)]
+enum DependencyChange {
+    // None: no version in the manifest, so the latest release is pinned at install
+    Add(Option<String>, Vec<String>),
+    AddFeatures(Vec<String>),
+    // Cargo.toml's requirement and the spark's, which no release satisfies together
+    Conflict(String, String),
+    Unchanged,
+}
+
+// A spark migration and the up.sql files it would run
+struct PlannedMigration {
+    name: String,
+    up_sql: Vec<(PathBuf, String)>,
+}
 
-    // Extract the repo name from the URL to use as the directory name
-    let repo_name = extract_repo_name(repo_url)?;
-    progress.set_message(&format!("Using repository name: {}", repo_name));
+// Everything `spark add` changes, worked out from the validated clone or directory before the project is touched.
+// Installing applies a plan; `spark add --review` prints it first
+pub struct SparkPlan {
+    source: SparkOrigin,
+    repo_name: String,
+    manifest: ManifestInfo,
+    // The clone of a git source, removed when the plan is dropped
+    clone: Option<crate::platform::TempPath>,
+    // Where the spark's files are read from: the clone, or the source directory itself
+    source_dir: PathBuf,
+    target_dir: PathBuf,
+    // The source Catalyst.toml has for the spark now, if any
+    recorded: Option<SparkOrigin>,
+    dependencies: Vec<(String, DependencyChange)>,
+    env_vars: Vec<(String, Option<String>)>,
+    migrations: Vec<PlannedMigration>,
+    files: Vec<PathBuf>,
+}
 
-    // Update Catalyst.toml with the spark information
-    let _ = update_sparks_toml(&repo_name, repo_url)?;
+fn plan_dependencies(deps_table: Option<&toml_edit::Table>, dependencies: &[Dependency]) -> Vec<(String, DependencyChange)> {
+    dependencies
+        .iter()
+        .map(|dep| {
+            let Some(existing) = deps_table.and_then(|table| table.get(&dep.crate_name)) else {
+                return (dep.crate_name.clone(), DependencyChange::Add(dep.version.clone(), dep.features.clone()));
+            };
+            if let (Some(existing), Some(requested)) = (existing_requirement(existing), &dep.version) {
+                if let (Ok(existing_req), Ok(requested_req)) = (semver::VersionReq::parse(&existing), semver::VersionReq::parse(requested)) {
+                    if !crate::crate_versions::compatible(&existing_req, &requested_req) {
+                        return (dep.crate_name.clone(), DependencyChange::Conflict(existing, requested.clone()));
+                    }
+                }
+            }
+            let enabled: Vec<&str> = existing.get("features").and_then(|features| features.as_array()).map(|features| features.iter().filter_map(|feature| feature.as_str()).collect()).unwrap_or_default();
+            let missing: Vec<String> = dep.features.iter().filter(|feature| !enabled.contains(&feature.as_str())).cloned().collect();
+            let change = if missing.is_empty() { DependencyChange::Unchanged } else { DependencyChange::AddFeatures(missing) };
+            (dep.crate_name.clone(), change)
+        })
+        .collect()
+}
 
-    // Step 1: Create the sparks directory if it doesn't exist
-    let services_dir = Path::new("src").join("services");
-    let sparks_dir = services_dir.join("sparks");
+// The up.sql files of a manifest migration: its own, or those of the versioned directories inside it
+fn migration_up_files(migration_path: &Path) -> Vec<PathBuf> {
+    if migration_path.join("up.sql").is_file() {
+        return vec![migration_path.join("up.sql")];
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(migration_path).into_iter().flatten().flatten().map(|entry| entry.path().join("up.sql")).filter(|path| path.is_file()).collect();
+    files.sort();
+    files
+}
 
-    if !services_dir.exists() {
-        return Err(format!("Services directory not found. Make sure you're in a Catalyst project."));
+// Clone or read the spark and work out what installing it changes, without writing to the project
+pub fn plan_spark(config: &Config, source: &SparkOrigin) -> Result<SparkPlan, String> {
+    let repo_name = extract_repo_name(source)?;
+    if !config.project_dir.join("src").join("services").exists() {
+        return Err("Services directory not found. Make sure you're in a Catalyst project.".to_string());
     }
 
-    if !sparks_dir.exists() {
-        fs::create_dir_all(&sparks_dir).map_err(|e| format!("Failed to create sparks directory: {}", e))?;
+    let (clone, source_dir, manifest) = fetch_spark(config, source, &repo_name)?;
+    let target_dir = Path::new("src").join("services").join("sparks").join(&repo_name);
+
+    let recorded = fs::read_to_string(config.project_dir.join("Catalyst.toml")).ok().and_then(|content| recorded_origin(&content, &repo_name));
+
+    let cargo = fs::read_to_string(config.project_dir.join("Cargo.toml")).ok().and_then(|content| content.parse::<toml_edit::DocumentMut>().ok());
+    let dependencies = plan_dependencies(cargo.as_ref().and_then(|doc| doc.get("dependencies")?.as_table()), &manifest.dependencies);
+
+    let env_path = config.project_dir.join(".env");
+    let env = EnvFile::load(&env_path).unwrap_or_else(|_| EnvFile::parse(&env_path, ""));
+    let env_vars = missing_env_vars(&env, &repo_name, &manifest.required_env);
+
+    let migrations = manifest
+        .migrations
+        .iter()
+        .map(|migration| PlannedMigration {
+            name: migration.name.clone(),
+            up_sql: migration_up_files(&source_dir.join(&migration.path))
+                .into_iter()
+                .map(|path| {
+                    let content = fs::read_to_string(&path).unwrap_or_default();
+                    (path.strip_prefix(&source_dir).map(Path::to_path_buf).unwrap_or(path), content)
+                })
+                .collect(),
+        })
+        .collect();
 
-        // If the module file doesn't exist, create it with a basic comment
-        let mod_rs_path = sparks_dir.join("mod.rs");
-        if !mod_rs_path.exists() {
-            fs::write(&mod_rs_path, "//here you include the modules you want to expose to the outside world\n").map_err(|e| format!("Failed to create mod.rs file: {}", e))?;
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(&source_dir)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(&source_dir).ok().map(Path::to_path_buf))
+        .collect();
+    files.sort();
+
+    Ok(SparkPlan { source: source.clone(), repo_name, manifest, clone, source_dir, target_dir, recorded, dependencies, env_vars, migrations, files })
+}
+
+// Print a plan: every file, setting and migration installing the spark would change
+fn print_plan(config: &Config, plan: &SparkPlan) -> Result<(), String> {
+    let manifest = &plan.manifest;
+    logger::plain(&format!("Spark {} {} by {}: {}", manifest.name, manifest.version, manifest.author, manifest.description))?;
+
+    logger::plain("\nCatalyst.toml [sparks]:")?;
+    let entry = |source: &SparkOrigin| source.to_item().to_string().trim().to_string();
+    match &plan.recorded {
+        None => logger::plain(&format!("  + {} = {}", plan.repo_name, entry(&plan.source)))?,
+        Some(recorded) if *recorded == plan.source => logger::plain(&format!("  {} is already recorded", plan.repo_name))?,
+        Some(recorded) => logger::plain(&format!("  ~ {} = {} (was {})", plan.repo_name, entry(&plan.source), entry(recorded)))?,
+    }
+
+    logger::plain("\nCargo.toml [dependencies]:")?;
+    if plan.dependencies.is_empty() {
+        logger::plain("  none")?;
+    }
+    for (crate_name, change) in &plan.dependencies {
+        match change {
+            DependencyChange::Add(version, features) => {
+                let features = if features.is_empty() { String::new() } else { format!(", features {}", features.join(", ")) };
+                logger::plain(&format!("  + {} = \"{}\"{}", crate_name, version.as_deref().unwrap_or("latest release"), features))?
+            }
+            DependencyChange::AddFeatures(features) => logger::plain(&format!("  ~ {}: add features {}", crate_name, features.join(", ")))?,
+            DependencyChange::Conflict(existing, requested) => logger::warning(&format!("  {}: Cargo.toml has \"{}\", the spark wants \"{}\"; you'll be asked which to keep", crate_name, existing, requested))?,
+            DependencyChange::Unchanged => logger::plain(&format!("    {} already satisfied", crate_name))?,
         }
     }
 
-    // Step 2: Create a temporary directory for cloning
-    let temp_dir = format!("_temp_spark_{}", repo_name);
-    let temp_path = Path::new(&temp_dir);
+    logger::plain("\n.env:")?;
+    if plan.env_vars.is_empty() {
+        logger::plain("  nothing to add")?;
+    }
+    for (name, comment) in &plan.env_vars {
+        logger::plain(&format!("  + {}={}{}", name, envfile::PLACEHOLDER, comment.as_ref().map(|comment| format!(" # {}", comment)).unwrap_or_default()))?;
+    }
 
-    // Clean up any existing temporary directory
-    if temp_path.exists() {
-        fs::remove_dir_all(temp_path).map_err(|e| format!("Failed to clean up temporary directory: {}", e))?;
+    let replacing = if config.project_dir.join(&plan.target_dir).symlink_metadata().is_ok() { " (replacing the existing directory)" } else { "" };
+    if plan.source.is_link() {
+        logger::plain(&format!("\n{} linked to {}{}, with these files:", plan.target_dir.display(), plan.source_dir.display(), replacing))?;
+    } else {
+        logger::plain(&format!("\nFiles copied to {}{}:", plan.target_dir.display(), replacing))?;
+    }
+    for file in &plan.files {
+        logger::plain(&format!("  {}", file.display()))?;
     }
 
-    // Step 3: Clone the repository
-    progress.set_message(&format!("Cloning repository: {}", repo_url));
-    let clone_result = Command::new("git")
-        .args(&["clone", "--depth=1", repo_url, &temp_dir])
-        .output()
-        .map_err(|e| format!("Failed to execute git clone: {}", e))?;
+    logger::plain("\nsparks/mod.rs and registry_generated.rs:")?;
+    logger::plain(&format!("  pub mod {}; and a registry entry for \"{}\" (regenerated from the installed sparks)", plan.repo_name, plan.repo_name))?;
 
-    if !clone_result.status.success() {
-        return Err(format!("Git clone failed: {}", String::from_utf8_lossy(&clone_result.stderr)));
+    logger::plain("\nMigrations:")?;
+    if plan.migrations.is_empty() {
+        logger::plain("  none")?;
+    }
+    for migration in &plan.migrations {
+        if migration.up_sql.is_empty() {
+            logger::warning(&format!("  {}: no up.sql found", migration.name))?;
+        }
+        for (path, content) in &migration.up_sql {
+            logger::plain(&format!("  {} ({}):", migration.name, path.display()))?;
+            for line in content.trim_end().lines() {
+                logger::plain(&format!("    {}", line))?;
+            }
+        }
     }
+    Ok(())
+}
 
-    // Step 4: Validate the manifest
-    progress.set_message("Validating spark manifest...");
-    let manifest_path = temp_path.join("manifest.toml");
-    if !manifest_path.exists() {
-        // Clean up before returning
-        let _ = fs::remove_dir_all(temp_path);
-        return Err(format!("Spark manifest not found in repository. Expected manifest.toml file."));
+// `blast spark add --review`: show the plan, then apply it as is, without its migrations, or not at all
+pub fn review_spark(source: &SparkOrigin, config: &Config) -> Result<(), String> {
+    let mut progress = logger::create_progress(None);
+    progress.set_message(&fetching_message(source));
+    let plan = plan_spark(config, source)?;
+    progress.success(&format!("Manifest validated: {}", plan.manifest.name));
+    print_plan(config, &plan)?;
+    logger::plain("")?;
+
+    if !crate::prompt::interactive() {
+        logger::plain("Nothing was changed. Run `blast spark add` without --review to install it")?;
+        return Ok(());
     }
-    let validation_result = validate_manifest(&manifest_path)?;
-    progress.set_message(&format!("Manifest validated: {}", validation_result.name));
 
-    // Step 5: Copy the spark to the final destination
-    let target_dir = sparks_dir.join(&repo_name);
+    let mut options = vec!["Apply".to_string(), "Abort".to_string()];
+    if !plan.migrations.is_empty() {
+        options.push("Apply without migrations".to_string());
+    }
+    match crate::prompt::select(&format!("Install {}?", plan.repo_name), &options, 0)? {
+        Some(0) => apply_plan(plan, config, true, logger::create_progress(None)).map(|_| ()),
+        Some(2) => apply_plan(plan, config, false, logger::create_progress(None)).map(|_| ()),
+        _ => logger::plain("Aborted; nothing was changed"),
+    }
+}
 
-    // If the target directory already exists, remove it
-    if target_dir.exists() {
-        fs::remove_dir_all(&target_dir).map_err(|e| format!("Failed to remove existing spark directory: {}", e))?;
+fn fetching_message(source: &SparkOrigin) -> String {
+    match source {
+        SparkOrigin::Git(_) => format!("Cloning repository: {}", source),
+        _ => format!("Reading spark directory: {}", source),
     }
+}
+
+// Main function to add a spark plugin; returns the installed spark's manifest
+pub fn add_spark(source: &SparkOrigin, config: &Config) -> Result<ManifestInfo, String> {
+    let mut progress = logger::create_progress(None);
+    progress.set_message(&fetching_message(source));
+    let plan = plan_spark(config, source)?;
+    progress.set_message(&format!("Manifest validated: {}", plan.manifest.name));
+    apply_plan(plan, config, true, progress)
+}
+
+// Install a planned spark. Nothing in the project changes before the plan exists, so a spark that fails to clone
+// or validate leaves no [sparks] entry behind
+fn apply_plan(plan: SparkPlan, config: &Config, run_migrations: bool, mut progress: logger::Progress) -> Result<ManifestInfo, String> {
+    let SparkPlan { source, repo_name, manifest: validation_result, clone, source_dir, target_dir, migrations: planned_migrations, .. } = plan;
+    progress.set_message(&format!("Adding spark plugin: {}", repo_name));
+
+    // Update Catalyst.toml with the spark information
+    let _ = update_sparks_toml(config, &repo_name, &source)?;
 
-    // Copy the repository to the sparks directory
-    copy_dir_all(temp_path, &target_dir).map_err(|e| format!("Failed to copy spark to target directory: {}", e))?;
+    // Step 1: Create the sparks directory if it doesn't exist
+    let sparks_dir = config.project_dir.join("src").join("services").join("sparks");
+    if !sparks_dir.exists() {
+        fs::create_dir_all(&sparks_dir).map_err(|e| format!("Failed to create sparks directory: {}", e))?;
 
-    // Clean up: Remove the temporary directory
-    fs::remove_dir_all(temp_path).map_err(|e| format!("Failed to clean up temporary directory: {}", e))?;
+        // If the module file doesn't exist, create it with a basic comment
+        let mod_rs_path = sparks_dir.join("mod.rs");
+        if !mod_rs_path.exists() {
+            fs::write(&mod_rs_path, "//here you include the modules you want to expose to the outside world\n").map_err(|e| format!("Failed to create mod.rs file: {}", e))?;
+        }
+    }
 
-    // Step 6: Update the mod.rs file to include the new spark
-    update_sparks_mod_rs(&sparks_dir, &repo_name)?;
+    // Steps 2-5: Copy the validated clone or directory into place, or link to the directory
+    crate::platform::check_interrupted()?;
+    install_files(&source_dir, &config.project_dir.join(&target_dir), source.is_link())?;
+    drop(clone);
 
-    // Step 6b: Update the registry.rs file if it exists
-    update_spark_registry(&config.project_dir, &repo_name)?;
+    // Step 6: Regenerate mod.rs and the registry from the installed sparks. A broken spark installed earlier
+    // shouldn't stop this one
+    if let Err(e) = sync_generated_files(&config.project_dir) {
+        logger::error(&e)?;
+    }
 
     // Step 7: Update the project's Cargo.toml with any required dependencies
     if !validation_result.dependencies.is_empty() {
@@ -757,7 +957,7 @@ pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
             logger::info(&format!("  - {}{}{}", dep.crate_name, version_str, features_str))?;
         }
 
-        update_cargo_toml(&validation_result.dependencies)?;
+        progress.suspend(|| update_cargo_toml(config, &validation_result.dependencies, &repo_name))?;
     }
 
     // Step 8: Check for required environment variables and update .env if needed
@@ -766,7 +966,7 @@ pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
         progress.success(&format!("Setting up environment variables for: {}", validation_result.name));
 
         // This will run without a spinner
-        let env_result = update_env_variables(&repo_name, &validation_result.required_env)?;
+        let env_result = update_env_variables(config, &repo_name, &validation_result.required_env)?;
 
         // Create a new progress for continuing after the editor
         progress = logger::create_progress(None);
@@ -780,19 +980,21 @@ pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
     // Give a moment for the new env vars to be available
     if env_updated {
         // Load the updated environment variables
-        dotenv::dotenv().ok();
+        envfile::load_project_env(config).apply();
         // Brief pause to allow environment to update
         std::thread::sleep(std::time::Duration::from_millis(500));
     }
 
     // Step 9: Run migrations if any are specified in the manifest
     // We do this after env variables are set so migrations can use them
-    if !validation_result.migrations.is_empty() {
+    if !run_migrations && !planned_migrations.is_empty() {
+        progress.warning(&format!("Skipped {} spark migration(s); they're in {}", planned_migrations.len(), target_dir.display()))?;
+    } else if !validation_result.migrations.is_empty() {
         progress.set_message(&format!("Running migrations for spark: {}", validation_result.name));
 
         for migration in &validation_result.migrations {
             // Construct the full path to the migration directory
-            let migration_path = target_dir.join(&migration.path);
+            let migration_path = config.project_dir.join(&target_dir).join(&migration.path);
 
             if !migration_path.exists() {
                 progress.warning(&format!("Migration path not found: {}", migration_path.display()))?;
@@ -800,30 +1002,17 @@ pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
             }
 
             // Check if migration directory structure is correct (up.sql/down.sql directly or in named subdirectories)
-            let mut direct_migration = false;
-            let up_sql = migration_path.join("up.sql");
-            if up_sql.exists() {
-                direct_migration = true;
-                logger::info(&format!("Found direct up.sql migration file: {}", up_sql.display()))?;
-            }
-
-            // If not direct, we need to use the parent directory which should contain versioned migration dirs
-            let actual_migration_path = if direct_migration {
-                // Use the migration path directly since it contains up.sql/down.sql
-                migration_path.clone()
-            } else {
-                // The path should be the parent directory that contains versioned migration dirs
-                logger::info(&format!("Using directory for versioned migrations: {}", migration_path.display()))?;
-                migration_path.clone()
-            };
-
+            let direct_migration = migration_path.join("up.sql").exists();
             progress.set_message(&format!("Running migration: {}", migration.name));
 
             // Log the path and structure for debugging
             logger::info(&format!("Migration structure: {}", if direct_migration { "direct up.sql/down.sql files" } else { "versioned directories" }))?;
 
-            match run_spark_migration(&actual_migration_path) {
-                Ok(_) => {
+            match run_spark_migration(config, &migration_path) {
+                Ok(applied) => {
+                    if applied {
+                        crate::state::mark_schema_stale(&config.project_dir, std::slice::from_ref(&migration.name));
+                    }
                     progress.success(&format!("Migration '{}' completed successfully", migration.name));
                 }
                 Err(e) => {
@@ -833,6 +1022,9 @@ pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
         }
     }
 
+    // The spark's tables need schema.rs, structs and models like the project's own
+    crate::database::regenerate_if_stale(config);
+
     // Success message
     progress.success(&format!("Successfully added spark plugin: {}", validation_result.name));
 
@@ -862,38 +1054,111 @@ pub fn add_spark(repo_url: &str, config: &Config) -> Result<(), String> {
         }
     }
 
-    Ok(())
+    Ok(validation_result)
+}
+
+// Clone a spark repository into a temporary directory and validate its manifest. The clone is removed when the
+// returned TempPath is dropped
+fn clone_validated(repo_url: &str, repo_name: &str) -> Result<(crate::platform::TempPath, ManifestInfo), String> {
+    let temp_dir = format!("_temp_spark_{}", repo_name);
+    let temp_guard = crate::platform::TempPath::new(&temp_dir);
+    let temp_path = temp_guard.path();
+
+    // Clean up any existing temporary directory
+    if temp_path.exists() {
+        fs::remove_dir_all(temp_path).map_err(|e| format!("Failed to clean up temporary directory: {}", e))?;
+    }
+
+    // Clone the repository; git only reports progress to a pipe when asked to
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--depth=1", repo_url, &temp_dir]);
+    if logger::level() >= logger::Verbosity::Trace {
+        clone.arg("--progress");
+    }
+    let clone_result = logger::command_output("git", &mut clone)
+        .map_err(|e| format!("Failed to execute git clone: {}", e))?;
+
+    if !clone_result.status.success() {
+        return Err(format!("Git clone failed: {}", String::from_utf8_lossy(&clone_result.stderr)));
+    }
+
+    // Validate the manifest
+    let manifest_path = temp_path.join("manifest.toml");
+    if !manifest_path.exists() {
+        return Err(format!("Spark manifest not found in repository. Expected manifest.toml file."));
+    }
+    let manifest = validate_manifest(&manifest_path)?;
+    crate::platform::check_interrupted()?;
+
+    Ok((temp_guard, manifest))
+}
+
+// The spark's files and validated manifest: a temporary clone for git sources, the directory itself otherwise
+fn fetch_spark(config: &Config, source: &SparkOrigin, repo_name: &str) -> Result<(Option<crate::platform::TempPath>, PathBuf, ManifestInfo), String> {
+    let path = match source {
+        SparkOrigin::Git(url) => {
+            let (clone, manifest) = clone_validated(url, repo_name)?;
+            let dir = clone.path().to_path_buf();
+            return Ok((Some(clone), dir, manifest));
+        }
+        SparkOrigin::Path(path) | SparkOrigin::Link(path) => path,
+    };
+
+    let dir = path.canonicalize().map_err(|e| format!("Spark directory {} not found: {}", path.display(), e))?;
+    // Installing replaces the spark's directory, which mustn't be its own source
+    if config.project_dir.join("src").join("services").join("sparks").canonicalize().is_ok_and(|sparks_dir| dir.starts_with(sparks_dir)) {
+        return Err(format!("{} is already inside src/services/sparks. Point `spark add` at the spark's own directory", path.display()));
+    }
+    let manifest_path = dir.join("manifest.toml");
+    if !manifest_path.exists() {
+        return Err(format!("Spark manifest not found in {}. Expected manifest.toml file.", path.display()));
+    }
+    let manifest = validate_manifest(&manifest_path)?;
+    Ok((None, dir, manifest))
+}
+
+// Put a spark's files in place: a copy, or for linked sparks a symlink to the source directory
+fn install_files(source_dir: &Path, target_dir: &Path, link: bool) -> Result<(), String> {
+    // symlink_metadata so a dangling link is replaced too; removing a link leaves the directory it points at alone
+    if target_dir.symlink_metadata().is_ok() {
+        fs::remove_dir_all(target_dir).map_err(|e| format!("Failed to remove existing spark directory: {}", e))?;
+    }
+    if link {
+        crate::platform::symlink_dir(source_dir, target_dir).map_err(|e| format!("Failed to link {} to {}: {}", target_dir.display(), source_dir.display(), e))
+    } else {
+        copy_dir_all(source_dir, target_dir).map_err(|e| format!("Failed to copy spark to target directory: {}", e))
+    }
 }
 
 // Helper struct for migration information
 #[derive(Debug)]
-struct MigrationInfo {
-    name: String,
-    path: String,
+pub struct MigrationInfo {
+    pub name: String,
+    pub path: String,
 }
 
 // Helper struct to hold manifest validation results
 #[derive(Debug)]
-struct ManifestInfo {
-    name: String,
-    version: String,
-    description: String,
-    author: String,
-    license: String,
-    required_env: Vec<String>,
-    dependencies: Vec<Dependency>,
-    migrations: Vec<MigrationInfo>,
+pub struct ManifestInfo {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub author: String,
+    pub license: String,
+    pub required_env: Vec<String>,
+    pub dependencies: Vec<Dependency>,
+    pub migrations: Vec<MigrationInfo>,
 }
 
-#[derive(Debug)]
-struct Dependency {
-    crate_name: String,
-    version: Option<String>,
-    features: Vec<String>,
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub crate_name: String,
+    pub version: Option<String>,
+    pub features: Vec<String>,
 }
 
 // Function to validate the manifest file
-fn validate_manifest(manifest_path: &Path) -> Result<ManifestInfo, String> {
+pub fn validate_manifest(manifest_path: &Path) -> Result<ManifestInfo, String> {
     // Read the manifest file
     let mut file = fs::File::open(manifest_path).map_err(|e| format!("Failed to open manifest file: {}", e))?;
 
@@ -1077,8 +1342,16 @@ fn validate_manifest(manifest_path: &Path) -> Result<ManifestInfo, String> {
     })
 }
 
-// Helper function to extract the repository name from the URL
-fn extract_repo_name(repo_url: &str) -> Result<String, String> {
+// Helper function to extract the spark's name: the repository's for git sources, the directory's otherwise
+fn extract_repo_name(source: &SparkOrigin) -> Result<String, String> {
+    let repo_url = match source {
+        SparkOrigin::Git(url) => url,
+        SparkOrigin::Path(path) | SparkOrigin::Link(path) => {
+            // `.` and `../my-spark/` are named after the directory they resolve to
+            let dir = path.canonicalize().map_err(|e| format!("Spark directory {} not found: {}", path.display(), e))?;
+            return dir.file_name().map(|name| name.to_string_lossy().to_string()).ok_or_else(|| format!("Could not extract a spark name from {}", path.display()));
+        }
+    };
     let url = url::Url::parse(repo_url).map_err(|e| format!("Invalid URL: {}", e))?;
 
     let path = url.path().trim_end_matches('/');
@@ -1095,7 +1368,7 @@ fn extract_repo_name(repo_url: &str) -> Result<String, String> {
 }
 
 // Helper function to copy a directory recursively
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
+pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
     fs::create_dir_all(&dst)?;
 
     for entry in fs::read_dir(src)? {
@@ -1119,16 +1392,108 @@ fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result
     Ok(())
 }
 
-// Helper function to update the project's Cargo.toml with the spark dependencies
-fn update_cargo_toml(dependencies: &[Dependency]) -> Result<(), String> {
-    let cargo_path = Path::new("Cargo.toml");
+// Version requirement of a [dependencies] entry; None for git, path and workspace dependencies
+fn existing_requirement(item: &toml_edit::Item) -> Option<String> {
+    item.as_str().or_else(|| item.as_table_like().and_then(|table| table.get("version")).and_then(|version| version.as_str())).map(str::to_string)
+}
+
+fn set_requirement(item: &mut toml_edit::Item, requirement: &str) {
+    if item.is_str() {
+        *item = toml_edit::value(requirement);
+    } else if let Some(table) = item.as_table_like_mut() {
+        table.insert("version", toml_edit::value(requirement));
+    }
+}
+
+// What the installed sparks' manifests ask for a crate, by spark name
+fn spark_requirements(project_dir: &Path, crate_name: &str) -> Vec<(String, String)> {
+    let sparks_dir = project_dir.join("src").join("services").join("sparks");
+    let mut wanted = Vec::new();
+    for entry in fs::read_dir(&sparks_dir).into_iter().flatten().flatten() {
+        let Ok(manifest) = validate_manifest(&entry.path().join("manifest.toml")) else {
+            continue;
+        };
+        for dep in manifest.dependencies.iter().filter(|dep| dep.crate_name == crate_name) {
+            wanted.push((entry.file_name().to_string_lossy().to_string(), dep.version.clone().unwrap_or_else(|| "any version".to_string())));
+        }
+    }
+    wanted.sort();
+    wanted
+}
+
+// Ask which requirement wins when Cargo.toml and the installer want versions no release satisfies together
+fn resolve_conflict(project_dir: &Path, crate_name: &str, existing: &str, requested: &str, requested_by: &str) -> Result<String, String> {
+    logger::warning(&format!("Dependency conflict for {}: no version satisfies both requirements", crate_name))?;
+    logger::plain(&format!("  Cargo.toml: {} = \"{}\"", crate_name, existing))?;
+    logger::plain(&format!("  {}: {} = \"{}\"", requested_by, crate_name, requested))?;
+    for (spark, requirement) in spark_requirements(project_dir, crate_name).into_iter().filter(|(spark, _)| spark != requested_by) {
+        logger::plain(&format!("  {} (installed spark): {} = \"{}\"", spark, crate_name, requirement))?;
+    }
+
+    if !crate::prompt::interactive() {
+        return Err(format!("Conflicting requirements for {} (Cargo.toml has \"{}\", {} wants \"{}\"). Cargo.toml was not changed; settle on one requirement there and run the command again", crate_name, existing, requested_by, requested));
+    }
+
+    let options = [format!("Keep \"{}\" ({} may not build)", existing, requested_by), format!("Use \"{}\" from {}", requested, requested_by), "Enter a custom requirement".to_string()];
+    let choice = crate::prompt::select(&format!("Which requirement should Cargo.toml use for {}?", crate_name), &options, 0)?;
+
+    match choice {
+        Some(0) | None => Ok(existing.to_string()),
+        Some(1) => Ok(requested.to_string()),
+        _ => {
+            let custom = crate::prompt::input(&format!("Requirement for {}", crate_name), Some(requested))?;
+            semver::VersionReq::parse(&custom).map_err(|e| format!("'{}' isn't a version requirement: {}. Cargo.toml was not changed", custom, e))?;
+            Ok(custom)
+        }
+    }
+}
+
+// Settle the requirement of every dependency before Cargo.toml is touched: conflicts with existing entries are resolved
+// (and applied to `deps_table`), and dependencies without a version are pinned to the latest release
+fn resolve_versions(project_dir: &Path, deps_table: &mut toml_edit::Table, dependencies: &[Dependency], requested_by: &str) -> Result<(Vec<Dependency>, Vec<String>), String> {
+    let mut resolved = Vec::new();
+    let mut changes = Vec::new();
+
+    for dep in dependencies {
+        let mut dep = dep.clone();
+        match (deps_table.get(&dep.crate_name).map(existing_requirement), &dep.version) {
+            // New dependency without a version: pin the latest release instead of "*"
+            (None, None) => {
+                let latest = crate::crate_versions::latest_requirement(&dep.crate_name)?;
+                logger::info(&format!("{} doesn't pin a version of {}, using the latest release ({})", requested_by, dep.crate_name, latest))?;
+                dep.version = Some(latest);
+            }
+            (Some(Some(existing)), Some(requested)) => {
+                // Requirements that don't parse (or git and path dependencies, which have none) are left to cargo
+                if let (Ok(existing_req), Ok(requested_req)) = (semver::VersionReq::parse(&existing), semver::VersionReq::parse(requested)) {
+                    if !crate::crate_versions::compatible(&existing_req, &requested_req) {
+                        let chosen = resolve_conflict(project_dir, &dep.crate_name, &existing, requested, requested_by)?;
+                        if chosen != existing {
+                            set_requirement(&mut deps_table[dep.crate_name.as_str()], &chosen);
+                            changes.push(format!("Changed {} from \"{}\" to \"{}\"", dep.crate_name, existing, chosen));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        resolved.push(dep);
+    }
+
+    Ok((resolved, changes))
+}
+
+// Helper function to update the project's Cargo.toml with the spark dependencies. `requested_by` names the spark
+// (or command) asking for them in conflict prompts
+pub fn update_cargo_toml(config: &Config, dependencies: &[Dependency], requested_by: &str) -> Result<(), String> {
+    let cargo_path = config.project_dir.join("Cargo.toml");
 
     if !cargo_path.exists() {
-        return Err(format!("Cargo.toml not found in the current directory. Make sure you're in a valid Catalyst project."));
+        return Err(format!("Cargo.toml not found in {}. Make sure you're in a valid Catalyst project.", config.project_dir.display()));
     }
 
     // Read the current Cargo.toml
-    let cargo_content = fs::read_to_string(cargo_path).map_err(|e| format!("Failed to read Cargo.toml: {}", e))?;
+    let cargo_content = fs::read_to_string(&cargo_path).map_err(|e| format!("Failed to read Cargo.toml: {}", e))?;
 
     // Parse Cargo.toml
     let mut cargo_doc = cargo_content.parse::<toml_edit::DocumentMut>().map_err(|e| format!("Failed to parse Cargo.toml: {}", e))?;
@@ -1144,11 +1509,11 @@ fn update_cargo_toml(dependencies: &[Dependency]) -> Result<(), String> {
     };
 
     // Track changes made to Cargo.toml
-    let mut changes_made = false;
-    let mut changes_summary = Vec::new();
+    let (dependencies, mut changes_summary) = resolve_versions(&config.project_dir, deps_table, dependencies, requested_by)?;
+    let mut changes_made = !changes_summary.is_empty();
 
     // Process each dependency
-    for dep in dependencies {
+    for dep in &dependencies {
         let dep_name = &dep.crate_name;
 
         if deps_table.contains_key(dep_name) {
@@ -1297,24 +1662,15 @@ fn update_cargo_toml(dependencies: &[Dependency]) -> Result<(), String> {
             // Dependency doesn't exist, add it
             if dep.features.is_empty() {
                 // Simple dependency without features
-                if let Some(version) = &dep.version {
-                    deps_table.insert(dep_name, toml_edit::Item::Value(toml_edit::Value::from(version.clone())));
-                } else {
-                    // If no version provided, use a default latest version
-                    deps_table.insert(dep_name, toml_edit::Item::Value(toml_edit::Value::from("*")));
-                }
+                deps_table.insert(dep_name, toml_edit::Item::Value(toml_edit::Value::from(dep.version.clone().unwrap_or_default())));
                 changes_made = true;
                 changes_summary.push(format!("Added dependency: {}", dep_name));
             } else {
                 // Dependency with features
                 let mut inline_table = toml_edit::InlineTable::new();
 
-                // Add version if available, otherwise use wildcard
-                if let Some(version) = &dep.version {
-                    inline_table.insert("version", version.clone().into());
-                } else {
-                    inline_table.insert("version", "*".into());
-                }
+                // resolve_versions gave every new dependency a version
+                inline_table.insert("version", dep.version.clone().unwrap_or_default().into());
 
                 // Add features
                 let mut features_array = toml_edit::Array::new();
@@ -1358,126 +1714,49 @@ fn update_cargo_toml(dependencies: &[Dependency]) -> Result<(), String> {
     Ok(())
 }
 
+// The spark's variables (SPARKNAME_VARNAME, with the manifest's comment) that .env lacks or only has a placeholder for
+fn missing_env_vars(env: &EnvFile, spark_name: &str, required_env: &[String]) -> Vec<(String, Option<String>)> {
+    let placeholder_vars = env.placeholders();
+    required_env
+        .iter()
+        .map(|env_var| {
+            let clean_var = env_var.split('#').next().unwrap_or(env_var).trim();
+            let comment = env_var.split('#').nth(1).map(|comment_part| comment_part.trim().to_string());
+            (format!("{}_{}", spark_name.to_uppercase(), clean_var.to_uppercase()), comment)
+        })
+        .filter(|(name, _)| !env.contains(name) || placeholder_vars.contains(name))
+        .collect()
+}
+
 // Helper function to check for and update required environment variables
-fn update_env_variables(spark_name: &str, required_env: &[String]) -> Result<bool, String> {
+fn update_env_variables(config: &Config, spark_name: &str, required_env: &[String]) -> Result<bool, String> {
     // Find the .env file
-    let env_path = Path::new(".env");
+    let env_path = config.project_dir.join(".env");
     if !env_path.exists() {
-        return Err(format!(".env file not found. Make sure you're in a valid Catalyst project."));
-    }
-
-    // Read the current .env file
-    let env_content = fs::read_to_string(env_path).map_err(|e| format!("Failed to read .env file: {}", e))?;
-
-    // Parse existing variables and check if they have placeholder values
-    let mut existing_vars = std::collections::HashSet::new();
-    let mut placeholder_vars = std::collections::HashSet::new();
-
-    for line in env_content.lines() {
-        if line.trim().is_empty() || line.trim().starts_with('#') {
-            continue;
-        }
-
-        // Extract variable name and value
-        let parts: Vec<&str> = line.splitn(2, '=').collect();
-        if parts.len() == 2 {
-            let var_name = parts[0].trim().to_string();
-            let var_value = parts[1].trim().to_string();
-
-            existing_vars.insert(var_name.clone());
-
-            // Check if this is a placeholder value
-            if var_value.contains("REPLACE_THIS_WITH_YOUR_VALUE") {
-                placeholder_vars.insert(var_name);
-            }
-        }
+        crate::envfile::ensure_env(config)?;
     }
 
-    // Create spark-specific variable name format (SPARKNAME_VARNAME)
+    let mut env = EnvFile::load(&env_path)?;
     let spark_prefix = format!("{}_", spark_name.to_uppercase());
-
-    // Check for missing or placeholder variables
-    let mut vars_to_update = Vec::new();
-    for env_var in required_env {
-        // Parse out any comments
-        let clean_var = env_var.split('#').next().unwrap_or(env_var).trim();
-        let comment = if let Some(comment_part) = env_var.split('#').nth(1) {
-            format!("# {}", comment_part.trim())
-        } else {
-            String::new()
-        };
-
-        // Create the spark-specific variable name
-        let spark_var_name = format!("{}{}", spark_prefix, clean_var.to_uppercase());
-
-        // Check if variable is missing OR has a placeholder value
-        if !existing_vars.contains(&spark_var_name) || placeholder_vars.contains(&spark_var_name) {
-            vars_to_update.push((spark_var_name, comment));
-        }
-    }
+    let placeholder_vars = env.placeholders();
+    let vars_to_update = missing_env_vars(&env, spark_name, required_env);
 
     // If there are variables to update, add them to .env and open editor
     if !vars_to_update.is_empty() {
-        // If we have placeholders, first remove them and their comment section from the file
-        if !placeholder_vars.is_empty() {
-            let spark_vars: Vec<_> = placeholder_vars.iter().filter(|v| v.starts_with(&spark_prefix)).collect();
-
-            if !spark_vars.is_empty() {
-                // Read all lines
-                let mut all_lines: Vec<String> = Vec::new();
-                let mut skip_next_comment = false;
-                let comment_str = format!("# Environment variables for {} spark", spark_name);
-
-                for line in env_content.lines() {
-                    // Skip any comment lines for this spark
-                    if line.trim() == comment_str {
-                        skip_next_comment = true;
-                        continue;
-                    }
-
-                    // Skip blank lines right after spark comments
-                    if skip_next_comment && line.trim().is_empty() {
-                        skip_next_comment = false;
-                        continue;
-                    }
-
-                    // Keep lines that don't start with our placeholder vars
-                    let parts: Vec<&str> = line.splitn(2, '=').collect();
-                    if parts.len() >= 1 {
-                        let var_name = parts[0].trim();
-                        if spark_vars.iter().any(|v| v == &var_name) {
-                            continue; // Skip this var
-                        }
-                    }
-
-                    all_lines.push(line.to_string());
-                }
-
-                // Write back without the placeholder lines and comments
-                fs::write(env_path, all_lines.join("\n")).map_err(|e| format!("Failed to update .env file: {}", e))?;
-            }
+        // Drop this spark's placeholders and section comment so the section is rewritten in one place
+        for var in placeholder_vars.iter().filter(|v| v.starts_with(&spark_prefix)) {
+            env.unset(var);
         }
+        env.remove_line(&format!("# Environment variables for {} spark", spark_name));
 
-        // Now append the new variables
-        let mut env_file = fs::OpenOptions::new().append(true).open(env_path).map_err(|e| format!("Failed to open .env file for writing: {}", e))?;
-
-        // Add a comment indicating these are for the spark
-        writeln!(env_file, "\n# Environment variables for {} spark", spark_name).map_err(|e| format!("Failed to write to .env file: {}", e))?;
-
-        // Add each variable with a placeholder value
+        // Add a comment indicating these are for the spark, then each variable with a placeholder value
+        env.push_line("");
+        env.push_line(&format!("# Environment variables for {} spark", spark_name));
         for (name, comment) in &vars_to_update {
-            // Write as NAME="YOUR_VALUE_HERE" format with comment if available
-            let line = if comment.is_empty() {
-                format!("{}=\"REPLACE_THIS_WITH_YOUR_VALUE\"", name)
-            } else {
-                format!("{}=\"REPLACE_THIS_WITH_YOUR_VALUE\" {}", name, comment)
-            };
-
-            writeln!(env_file, "{}", line).map_err(|e| format!("Failed to write to .env file: {}", e))?;
+            env.push_entry(name, envfile::PLACEHOLDER, comment.as_deref());
         }
 
-        // Close the file
-        drop(env_file);
+        env.save()?;
 
         // Inform the user what we've done
         logger::info(&format!("⚠️  Required environment variables for spark '{}' have been added to your .env file", spark_name))?;
@@ -1493,7 +1772,7 @@ fn update_env_variables(spark_name: &str, required_env: &[String]) -> Result<boo
             // Sleep to make sure the editor has our full attention
             std::thread::sleep(std::time::Duration::from_secs(1));
 
-            match Command::new(&editor).arg(env_path).status() {
+            match Command::new(&editor).arg(&env_path).status() {
                 Ok(status) if status.success() => {
                     logger::info("File has been successfully edited")?;
                     true
@@ -1509,23 +1788,12 @@ fn update_env_variables(spark_name: &str, required_env: &[String]) -> Result<boo
 
         // If EDITOR didn't work, try common editors
         if !editor_result {
-            open_with_common_editors(env_path)?;
+            open_with_common_editors(&env_path)?;
         }
 
-        // Check specifically for spark env vars with placeholder values
-        let updated_content = fs::read_to_string(env_path).map_err(|e| format!("Failed to read .env file after editing: {}", e))?;
-
-        // Look for our specific spark env variables that still have placeholder values
-        let spark_prefix = format!("{}_", spark_name.to_uppercase());
-        let mut placeholders_found = false;
-
-        // Check every line for the pattern SPARKNAME_VARNAME="REPLACE_THIS_WITH_YOUR_VALUE"
-        for line in updated_content.lines() {
-            if line.trim().starts_with(&spark_prefix) && line.contains("REPLACE_THIS_WITH_YOUR_VALUE") {
-                placeholders_found = true;
-                break;
-            }
-        }
+        // Check specifically for spark env vars that still have placeholder values
+        let updated_env = EnvFile::load(&env_path)?;
+        let placeholders_found = updated_env.placeholders().iter().any(|var| var.starts_with(&spark_prefix));
 
         if placeholders_found {
             logger::warning(&format!("Some environment variables for {} still have placeholder values!", spark_name))?;
@@ -1555,7 +1823,7 @@ fn open_with_common_editors(file_path: &Path) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         // On Windows, try notepad
-        if Command::new("which").arg("notepad").output().map(|o| o.status.success()).unwrap_or(false) {
+        if crate::platform::command_exists("notepad") {
             logger::info("Opening .env file with notepad...")?;
 
             // Open the editor and wait for it to complete
@@ -1575,7 +1843,7 @@ fn open_with_common_editors(file_path: &Path) -> Result<(), String> {
 
         for editor in editors {
             // Try to check if the editor is installed
-            if Command::new("which").arg(editor).output().map(|o| o.status.success()).unwrap_or(false) {
+            if crate::platform::command_exists(editor) {
                 // Editor exists, try to use it
                 logger::info(&format!("Opening .env file with {}...", editor))?;
 
@@ -1623,19 +1891,19 @@ pub fn install_sparks_from_config(config: &Config) -> Result<(), String> {
             progress.set_message("Installing sparks from Catalyst.toml...");
 
             let mut current = 0;
-            for (spark_name, spark_url) in sparks_table {
+            for (spark_name, spark_source) in sparks_table {
                 current += 1;
-                if let Some(url) = spark_url.as_str() {
+                if let Some(source) = SparkOrigin::from_value(spark_source) {
                     progress.set_message(&format!("Installing spark ({}/{}): {}", current, total_sparks, spark_name));
 
                     // Attempt to add the spark
-                    if let Err(e) = add_spark(url, config) {
+                    if let Err(e) = add_spark(&source, config) {
                         progress.warning(&format!("Failed to install spark {}: {}", spark_name, e))?;
                     } else {
                         progress.set_message(&format!("Installed spark ({}/{}): {}", current, total_sparks, spark_name));
                     }
                 } else {
-                    progress.warning(&format!("Invalid URL for spark: {}", spark_name))?;
+                    progress.warning(&format!("Invalid source for spark: {}", spark_name))?;
                 }
                 progress.inc(1);
             }
@@ -1649,76 +1917,513 @@ pub fn install_sparks_from_config(config: &Config) -> Result<(), String> {
     Ok(())
 }
 
-// Helper function to update the sparks/mod.rs file to include the new spark
-fn update_sparks_mod_rs(sparks_dir: &PathBuf, spark_name: &str) -> Result<(), String> {
-    let mod_rs_path = sparks_dir.join("mod.rs");
+// Dispatch for the installed sparks, included into register_by_name in registry.rs and rewritten from the installed
+// sparks on every sync. Hand-written registrations stay in registry.rs
+const REGISTRY_GENERATED: &str = "registry_generated.rs";
+const REGISTRY_INCLUDE: &str = "include!(\"registry_generated.rs\")";
+
+// Spark directories with a valid manifest, and the ones whose manifest is missing or invalid with the reason
+struct InstalledSparks {
+    valid: Vec<String>,
+    invalid: Vec<(String, String)>,
+}
+
+fn installed_sparks(sparks_dir: &Path) -> Result<InstalledSparks, String> {
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+    for entry in fs::read_dir(sparks_dir).map_err(|e| format!("Failed to read sparks directory: {}", e))?.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let manifest_path = entry.path().join("manifest.toml");
+        if !manifest_path.exists() {
+            invalid.push((name, "no manifest.toml".to_string()));
+            continue;
+        }
+        match validate_manifest(&manifest_path) {
+            Ok(_) => valid.push(name),
+            Err(e) => invalid.push((name, e)),
+        }
+    }
+    valid.sort();
+    invalid.sort();
+    Ok(InstalledSparks { valid, invalid })
+}
 
+// Regenerate the generated section of sparks/mod.rs and registry_generated.rs from the installed sparks. Directories
+// without a valid manifest are left out of both and reported as an error
+pub fn sync_generated_files(project_dir: &Path) -> Result<(), String> {
+    let sparks_dir = project_dir.join("src").join("services").join("sparks");
+    if !sparks_dir.is_dir() {
+        return Ok(());
+    }
+    let mod_rs_path = sparks_dir.join("mod.rs");
     if !mod_rs_path.exists() {
-        return Err(format!("mod.rs file not found in sparks directory"));
+        return Err("mod.rs file not found in sparks directory".to_string());
     }
 
-    // Read the current content
-    let mut content = fs::read_to_string(&mod_rs_path).map_err(|e| format!("Failed to read mod.rs file: {}", e))?;
+    let sparks = installed_sparks(&sparks_dir)?;
+    mod_file::sync(&mod_rs_path, &sparks.valid, false)?;
+    generate_registry(&sparks_dir, &sparks.valid)?;
 
-    // Check if the module is already included
-    let module_line = format!("pub mod {};", spark_name);
-    if !content.contains(&module_line) {
-        // Add the module line after the comment or at the end
-        if content.contains("//here you include the modules you want to expose to the outside world") {
-            content.push_str(&format!("\npub mod {};\n", spark_name));
-        } else {
-            content.push_str(&format!("\n//here you include the modules you want to expose to the outside world\npub mod {};\n", spark_name));
-        }
+    if sparks.invalid.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = sparks.invalid.iter().map(|(name, reason)| format!("{} ({})", name, reason)).collect();
+    Err(format!("Spark directories without a valid manifest were left out of mod.rs and the registry: {}. Fix their manifest.toml or remove them", details.join(", ")))
+}
+
+// Write registry_generated.rs and make sure registry.rs includes it
+fn generate_registry(sparks_dir: &Path, sparks: &[String]) -> Result<(), String> {
+    let registry_path = sparks_dir.join("registry.rs");
+    if !registry_path.exists() {
+        logger::info("Spark registry.rs not found. This will be created automatically when the project is initialized.")?;
+        return Ok(());
+    }
 
-        // Write the updated content
-        fs::write(&mod_rs_path, content).map_err(|e| format!("Failed to update mod.rs file: {}", e))?;
+    let mut content = String::from("// Generated by blast from the installed sparks; `blast spark add`, `blast spark verify --fix` and `blast init`\n// rewrite it. Register sparks by hand in registry.rs\nmatch name {\n");
+    for spark in sparks {
+        content.push_str(&format!("    \"{0}\" => {{\n        register_spark(name, {0}::create_spark);\n        true\n    }}\n", spark));
     }
+    content.push_str("    _ => false,\n}\n");
+    crate::file_writer::write(sparks_dir.join(REGISTRY_GENERATED), &content)?;
 
+    include_generated_registry(&registry_path)
+}
+
+// One-time change to registry.rs from before the generated registry: register_by_name tries the generated dispatch
+// first, and the match arms blast used to insert by hand are removed
+fn include_generated_registry(registry_path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(registry_path).map_err(|e| format!("Failed to read registry.rs file: {}", e))?;
+    if content.contains(REGISTRY_INCLUDE) {
+        return Ok(());
+    }
+
+    let body = content.find("fn register_by_name").and_then(|start| content[start..].find('{').map(|brace| start + brace + 1));
+    let Some(body) = body else {
+        return Err(format!("Couldn't find register_by_name in {}. Add `if {} {{ return true; }}` at the start of the function that registers sparks by name", registry_path.display(), REGISTRY_INCLUDE));
+    };
+
+    let include = format!("\n    // Sparks installed with blast\n    if {} {{\n        return true;\n    }}\n", REGISTRY_INCLUDE);
+    let updated = format!("{}{}{}", &content[..body], include, &content[body..]);
+    let inserted_arm = regex::Regex::new(r#"\n[ \t]*"\w+" => \{\s*register_spark\(name, \w+::create_spark\);\s*true\s*\},?"#).unwrap();
+    let updated = inserted_arm.replace_all(&updated, "").to_string();
+
+    crate::file_writer::write(registry_path, &updated)?;
+    logger::info("registry.rs now includes registry_generated.rs for the installed sparks")
+}
+
+// `blast spark list`: the sparks in Catalyst.toml, their installed version and where each comes from
+pub fn list_sparks(config: &Config) -> Result<(), String> {
+    let sparks_dir = config.project_dir.join("src").join("services").join("sparks");
+    let Some(sparks_table) = config.assets.get("sparks").and_then(|s| s.as_table()).filter(|sparks_table| !sparks_table.is_empty()) else {
+        return logger::plain("No sparks in Catalyst.toml. Add one with `blast spark add <repo_url|path>`");
+    };
+
+    for (name, source) in sparks_table {
+        let manifest_path = sparks_dir.join(name).join("manifest.toml");
+        let version = if manifest_path.exists() { validate_manifest(&manifest_path).map(|manifest| manifest.version).unwrap_or_else(|_| "invalid".to_string()) } else { "not installed".to_string() };
+        let Some(source) = SparkOrigin::from_value(source) else {
+            logger::warning(&format!("{}: no valid source in Catalyst.toml", name))?;
+            continue;
+        };
+        logger::plain(&format!("{:<24} {:<14} {}", name, version, source))?;
+        match &source {
+            SparkOrigin::Link(path) => logger::plain(&format!("{:<39} symlinked: edits in {} show up as you make them; blast relinks it and never clones or copies over it", "", path.display()))?,
+            SparkOrigin::Path(path) => logger::plain(&format!("{:<39} copied: run `blast spark add {}` again to pick up changes", "", path.display()))?,
+            SparkOrigin::Git(_) => {}
+        }
+    }
     Ok(())
 }
 
-// Helper function to update the registry.rs file to add a new match arm for the spark
-pub fn update_spark_registry(project_dir: &Path, spark_name: &str) -> Result<(), String> {
-    let registry_path = project_dir.join("src").join("services").join("sparks").join("registry.rs");
+// A single inconsistency found by verify_sparks
+struct SparkIssue {
+    description: String,
+    suggestion: String,
+    fix: SparkFix,
+}
+
+// The fix that can be applied for a spark issue
+enum SparkFix {
+    None,
+    SyncGenerated,
+    // Clone, copy or link the recorded source again
+    Reinstall(String, SparkOrigin),
+    AddEnvPlaceholders(String, Vec<String>),
+    RemoveEnvVar(String),
+    RemoveDirectory(PathBuf),
+}
 
-    // Check if registry.rs exists
-    if !registry_path.exists() {
-        logger::info("Spark registry.rs not found. This will be created automatically when the project is initialized.")?;
-        return Ok(());
+impl SparkFix {
+    // Destructive fixes always need individual confirmation
+    fn is_destructive(&self) -> bool {
+        matches!(self, SparkFix::RemoveEnvVar(_) | SparkFix::RemoveDirectory(_))
+    }
+}
+
+// Build the SPARKNAME_VARNAME names for a spark's required env entries
+fn spark_env_var_names(spark_name: &str, required_env: &[String]) -> Vec<String> {
+    required_env
+        .iter()
+        .map(|env| {
+            let clean_var = env.split('#').next().unwrap_or(env).trim();
+            format!("{}_{}", spark_name.to_uppercase(), clean_var.to_uppercase())
+        })
+        .filter(|name| !name.ends_with('_'))
+        .collect()
+}
+
+// Parse .env into (variable name, spark section it was written under)
+fn read_env_entries(env_path: &Path) -> Vec<(String, Option<String>)> {
+    let mut entries = Vec::new();
+    let env = match EnvFile::load(env_path) {
+        Ok(env) => env,
+        Err(_) => return entries,
+    };
+
+    let mut current_spark: Option<String> = None;
+    for line in env.lines() {
+        match line {
+            envfile::EnvLine::Entry { key, .. } => entries.push((key.clone(), current_spark.clone())),
+            envfile::EnvLine::Other { text, .. } => {
+                let trimmed = text.trim();
+                if trimmed.is_empty() {
+                    current_spark = None;
+                } else if let Some(rest) = trimmed.strip_prefix("# Environment variables for ") {
+                    current_spark = rest.strip_suffix(" spark").map(|name| name.to_string());
+                }
+            }
+        }
+    }
+
+    entries
+}
+
+// Append placeholder values for missing spark env variables without opening an editor
+fn add_env_placeholders(env_path: &Path, spark_name: &str, var_names: &[String]) -> Result<(), String> {
+    let mut env = EnvFile::load(env_path)?;
+
+    env.push_line("");
+    env.push_line(&format!("# Environment variables for {} spark", spark_name));
+    for name in var_names {
+        env.set(name, envfile::PLACEHOLDER);
+    }
+
+    env.save()
+}
+
+// Remove a single variable from .env, keeping everything else intact
+fn remove_env_var(env_path: &Path, var_name: &str) -> Result<(), String> {
+    let mut env = EnvFile::load(env_path)?;
+    env.unset(var_name);
+    env.save()
+}
+
+// Required env variables (SPARKNAME_VARNAME) for every installed spark with a valid manifest
+pub fn installed_spark_env_vars(config: &Config) -> Vec<(String, Vec<String>)> {
+    let sparks_dir = config.project_dir.join("src").join("services").join("sparks");
+    let mut result = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&sparks_dir) {
+        for entry in entries.flatten() {
+            let manifest_path = entry.path().join("manifest.toml");
+            if !manifest_path.exists() {
+                continue;
+            }
+
+            if let Ok(manifest) = validate_manifest(&manifest_path) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let vars = spark_env_var_names(&name, &manifest.required_env);
+                result.push((name, vars));
+            }
+        }
+    }
+
+    result.sort();
+    result
+}
+
+// Cross-check Catalyst.toml [sparks], installed spark directories and .env
+fn collect_spark_issues(config: &Config) -> Result<Vec<SparkIssue>, String> {
+    let mut issues = Vec::new();
+
+    let sparks_dir = config.project_dir.join("src").join("services").join("sparks");
+    let env_path = config.project_dir.join(".env");
+
+    // Sparks recorded in Catalyst.toml
+    let mut recorded: Vec<(String, SparkOrigin)> = Vec::new();
+    if let Some(sparks_table) = config.assets.get("sparks").and_then(|s| s.as_table()) {
+        for (name, source) in sparks_table {
+            match SparkOrigin::from_value(source) {
+                Some(source) => recorded.push((name.clone(), source)),
+                None => issues.push(SparkIssue {
+                    description: format!("Catalyst.toml entry for spark '{}' has no valid source", name),
+                    suggestion: format!("Set [sparks] {} = \"<repo_url>\" or {{ path = \"<dir>\" }} in Catalyst.toml", name),
+                    fix: SparkFix::None,
+                }),
+            }
+        }
+    }
+
+    // Spark directories present on disk
+    let mut installed: Vec<String> = Vec::new();
+    if let Ok(entries) = fs::read_dir(&sparks_dir) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                installed.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    installed.sort();
+
+    let mod_rs_content = fs::read_to_string(sparks_dir.join("mod.rs")).unwrap_or_default();
+    let registry_path = sparks_dir.join("registry.rs");
+    let registry_content = fs::read_to_string(&registry_path).ok();
+    let registry_generated = fs::read_to_string(sparks_dir.join(REGISTRY_GENERATED)).unwrap_or_default();
+    if registry_content.as_ref().is_some_and(|registry| !registry.contains(REGISTRY_INCLUDE)) {
+        issues.push(SparkIssue {
+            description: format!("sparks/registry.rs doesn't include {}", REGISTRY_GENERATED),
+            suggestion: format!("Call `if {} {{ return true; }}` at the start of register_by_name", REGISTRY_INCLUDE),
+            fix: SparkFix::SyncGenerated,
+        });
+    }
+
+    let env_entries = read_env_entries(&env_path);
+    let env_names: Vec<&str> = env_entries.iter().map(|(name, _)| name.as_str()).collect();
+
+    // Catalyst.toml entries whose directory is missing, or a copy where the spark should be linked
+    for (name, source) in &recorded {
+        let target = sparks_dir.join(name);
+        let description = if !installed.contains(name) {
+            format!("Spark '{}' is listed in Catalyst.toml but {} does not exist", name, target.display())
+        } else if source.is_link() && !target.is_symlink() {
+            format!("Spark '{}' is recorded as linked but {} is a copy", name, target.display())
+        } else {
+            continue;
+        };
+        let issue = match source {
+            SparkOrigin::Path(path) | SparkOrigin::Link(path) if !path.is_dir() => SparkIssue {
+                description: format!("{}, and its source directory {} is gone", description, path.display()),
+                suggestion: format!("Restore {} or point [sparks] {} at the spark's new location", path.display(), name),
+                fix: SparkFix::None,
+            },
+            SparkOrigin::Git(_) => SparkIssue { description, suggestion: format!("Re-clone it from {}", source), fix: SparkFix::Reinstall(name.clone(), source.clone()) },
+            SparkOrigin::Path(path) => SparkIssue { description, suggestion: format!("Copy it again from {}", path.display()), fix: SparkFix::Reinstall(name.clone(), source.clone()) },
+            SparkOrigin::Link(path) => SparkIssue { description, suggestion: format!("Link it again to {}", path.display()), fix: SparkFix::Reinstall(name.clone(), source.clone()) },
+        };
+        issues.push(issue);
+    }
+
+    // mod.rs declarations of sparks that were removed
+    for line in mod_rs_content.lines() {
+        if let Some(name) = line.trim().strip_prefix("pub mod ").and_then(|rest| rest.strip_suffix(';')) {
+            if !installed.iter().any(|installed| installed == name) && !sparks_dir.join(format!("{}.rs", name)).exists() {
+                issues.push(SparkIssue {
+                    description: format!("sparks/mod.rs declares '{}' but the spark is not installed", name),
+                    suggestion: format!("Remove 'pub mod {};' from sparks/mod.rs", name),
+                    fix: if mod_file::owns_declaration(&mod_rs_content, name) { SparkFix::SyncGenerated } else { SparkFix::None },
+                });
+            }
+        }
+    }
+
+    // Installed spark directories
+    for name in &installed {
+        if !recorded.iter().any(|(recorded_name, _)| recorded_name == name) {
+            issues.push(SparkIssue {
+                description: format!("Spark directory '{}' is not listed in Catalyst.toml", name),
+                suggestion: format!("Run 'blast spark add <repo_url|path>' to record it, or remove {}", sparks_dir.join(name).display()),
+                fix: SparkFix::RemoveDirectory(sparks_dir.join(name)),
+            });
+        }
+
+        let manifest_path = sparks_dir.join(name).join("manifest.toml");
+        if !manifest_path.exists() {
+            issues.push(SparkIssue {
+                description: format!("Spark '{}' has no manifest.toml", name),
+                suggestion: "Re-install the spark from its repository".to_string(),
+                fix: SparkFix::None,
+            });
+            continue;
+        }
+
+        match validate_manifest(&manifest_path) {
+            Ok(manifest) => {
+                // Only sparks with a valid manifest are generated into mod.rs and the registry
+                if !mod_rs_content.contains(&format!("pub mod {};", name)) {
+                    issues.push(SparkIssue {
+                        description: format!("Spark '{}' is missing from sparks/mod.rs", name),
+                        suggestion: format!("Add 'pub mod {};' to sparks/mod.rs", name),
+                        fix: SparkFix::SyncGenerated,
+                    });
+                }
+
+                if registry_content.is_some() && !registry_generated.contains(&format!("\"{}\" =>", name)) {
+                    issues.push(SparkIssue {
+                        description: format!("Spark '{}' is not registered in sparks/{}", name, REGISTRY_GENERATED),
+                        suggestion: format!("Regenerate {}", REGISTRY_GENERATED),
+                        fix: SparkFix::SyncGenerated,
+                    });
+                }
+
+                let missing: Vec<String> = spark_env_var_names(name, &manifest.required_env).into_iter().filter(|var| !env_names.contains(&var.as_str())).collect();
+
+                if !missing.is_empty() {
+                    issues.push(SparkIssue {
+                        description: format!("Spark '{}' is missing env variables: {}", name, missing.join(", ")),
+                        suggestion: "Add them to .env and set their values".to_string(),
+                        fix: SparkFix::AddEnvPlaceholders(name.clone(), missing),
+                    });
+                }
+            }
+            Err(e) => issues.push(SparkIssue {
+                description: format!("Spark '{}' has an invalid manifest: {}", name, e),
+                suggestion: "Fix manifest.toml or re-install the spark".to_string(),
+                fix: SparkFix::None,
+            }),
+        }
+    }
+
+    // Env variables left behind by sparks that are no longer present anywhere
+    let is_known = |spark: &str| installed.iter().any(|name| name == spark) || recorded.iter().any(|(name, _)| name == spark);
+    let stale_sparks: Vec<&String> = env_entries.iter().filter_map(|(_, spark)| spark.as_ref()).filter(|spark| !is_known(spark)).collect();
+
+    for (var_name, section) in &env_entries {
+        let stale_section = section.as_ref().map(|spark| !is_known(spark)).unwrap_or(false);
+        let stale_prefix = stale_sparks.iter().any(|spark| var_name.starts_with(&format!("{}_", spark.to_uppercase())));
+
+        if stale_section || stale_prefix {
+            issues.push(SparkIssue {
+                description: format!("Env variable {} belongs to a spark that is no longer installed", var_name),
+                suggestion: format!("Remove {} from .env", var_name),
+                fix: SparkFix::RemoveEnvVar(var_name.clone()),
+            });
+        }
     }
 
-    logger::info(&format!("Updating spark registry for: {}", spark_name))?;
+    Ok(issues)
+}
+
+// Apply a single fix for a spark issue
+fn apply_spark_fix(fix: &SparkFix, config: &Config) -> Result<(), String> {
+    let sparks_dir = config.project_dir.join("src").join("services").join("sparks");
+    let env_path = config.project_dir.join(".env");
+
+    match fix {
+        SparkFix::None => Ok(()),
+        SparkFix::SyncGenerated => sync_generated_files(&config.project_dir),
+        SparkFix::Reinstall(name, source) => {
+            let (clone, source_dir, manifest) = fetch_spark(config, source, name)?;
+            install_files(&source_dir, &sparks_dir.join(name), source.is_link())?;
+            drop(clone);
+            sync_generated_files(&config.project_dir)?;
+            if !manifest.dependencies.is_empty() {
+                update_cargo_toml(config, &manifest.dependencies, name)?;
+            }
+            Ok(())
+        }
+        SparkFix::AddEnvPlaceholders(name, vars) => add_env_placeholders(&env_path, name, vars),
+        SparkFix::RemoveEnvVar(var_name) => remove_env_var(&env_path, var_name),
+        SparkFix::RemoveDirectory(path) => {
+            fs::remove_dir_all(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            sync_generated_files(&config.project_dir)
+        }
+    }
+}
 
-    // Read the current content
-    let content = fs::read_to_string(&registry_path).map_err(|e| format!("Failed to read registry.rs file: {}", e))?;
+// Detect drift between .env, Catalyst.toml [sparks] and installed spark directories
+pub fn verify_sparks(config: &Config, fix: bool) -> Result<(), String> {
+    let issues = collect_spark_issues(config)?;
 
-    // Check if the spark is already registered
-    if content.contains(&format!("\"{spark_name}\" =>")) {
-        logger::info(&format!("Spark '{}' is already registered in registry.rs", spark_name))?;
+    if issues.is_empty() {
+        logger::success("Sparks are consistent across Catalyst.toml, spark directories and .env")?;
         return Ok(());
     }
 
-    // Find the match block in the register_by_name function
-    if let Some(match_start_pos) = content.find("match name {") {
-        // Find the first default case after the match name statement
-        if let Some(default_pos) = content[match_start_pos..].find("_ => {") {
-            let absolute_default_pos = match_start_pos + default_pos;
+    logger::warning(&format!("Found {} spark inconsistenc{}", issues.len(), if issues.len() == 1 { "y" } else { "ies" }))?;
+    for issue in &issues {
+        logger::plain(&format!("  {} {}", logger::status_mark(false), issue.description))?;
+        logger::plain(&format!("    {} {}", if logger::plain_output() { "->" } else { "→" }, issue.suggestion))?;
+    }
+
+    if !fix {
+        logger::info("Run 'blast spark verify --fix' to apply the non-destructive fixes")?;
+        return Ok(());
+    }
 
-            // Create the new match arm - we don't need to use the match content
-            let new_match_arm = format!("\n        \"{spark_name}\" => {{\n            register_spark(name, {spark_name}::create_spark);\n            true\n        }},");
+    let theme = dialoguer::theme::ColorfulTheme::default();
+    let mut fixed = 0;
+    for issue in &issues {
+        if matches!(issue.fix, SparkFix::None) {
+            continue;
+        }
 
-            // Construct the new content by inserting the match arm just before the default case
-            let mut updated_content = content.clone();
-            updated_content.insert_str(absolute_default_pos, &new_match_arm);
+        if issue.fix.is_destructive() {
+            let confirmed = dialoguer::Confirm::with_theme(&theme)
+                .with_prompt(format!("{}?", issue.suggestion))
+                .default(false)
+                .interact()
+                .map_err(|e| e.to_string())?;
 
-            // Write the updated content
-            fs::write(&registry_path, updated_content).map_err(|e| format!("Failed to update registry.rs file: {}", e))?;
+            if !confirmed {
+                continue;
+            }
+        }
 
-            logger::success(&format!("Updated registry.rs to include spark: {}", spark_name))?;
-            return Ok(());
+        match apply_spark_fix(&issue.fix, config) {
+            Ok(_) => fixed += 1,
+            Err(e) => logger::warning(&format!("Could not fix '{}': {}", issue.description, e))?,
         }
     }
 
-    logger::warning(&format!("Could not find register_by_name function in registry.rs. Manual update required."))?;
+    logger::success(&format!("Applied {} spark fix(es)", fixed))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_flags_new_dependencies_missing_features_and_conflicts() {
+        let cargo = "[dependencies]\nserde = { version = \"1.0\", features = [\"derive\"] }\nreqwest = \"0.11\"\nrand = \"0.9\"\n".parse::<toml_edit::DocumentMut>().unwrap();
+        let dep = |name: &str, version: Option<&str>, features: &[&str]| Dependency { crate_name: name.to_string(), version: version.map(str::to_string), features: features.iter().map(|f| f.to_string()).collect() };
+        let plan = plan_dependencies(
+            cargo["dependencies"].as_table(),
+            &[dep("serde", Some("1.0.100"), &["derive", "rc"]), dep("reqwest", Some("0.12"), &[]), dep("rand", None, &[]), dep("uuid", None, &["v4"])],
+        );
+        assert_eq!(
+            plan,
+            [
+                ("serde".to_string(), DependencyChange::AddFeatures(vec!["rc".to_string()])),
+                ("reqwest".to_string(), DependencyChange::Conflict("0.11".to_string(), "0.12".to_string())),
+                ("rand".to_string(), DependencyChange::Unchanged),
+                ("uuid".to_string(), DependencyChange::Add(None, vec!["v4".to_string()])),
+            ]
+        );
+    }
+
+    #[test]
+    fn path_sources_are_recorded_by_kind() {
+        assert_eq!(SparkOrigin::parse("https://github.com/you/mailer.git", false).unwrap(), SparkOrigin::Git("https://github.com/you/mailer.git".to_string()));
+        assert_eq!(SparkOrigin::parse("git@github.com:you/mailer.git", false).unwrap(), SparkOrigin::Git("git@github.com:you/mailer.git".to_string()));
+        assert_eq!(SparkOrigin::parse("file:///work/mailer", false).unwrap(), SparkOrigin::Path(PathBuf::from("/work/mailer")));
+        assert_eq!(SparkOrigin::parse("../mailer", true).unwrap(), SparkOrigin::Link(PathBuf::from("../mailer")));
+        assert!(SparkOrigin::parse("https://github.com/you/mailer", true).is_err());
+
+        let mut doc = toml_edit::DocumentMut::new();
+        doc["sparks"] = toml_edit::table();
+        for (name, source) in [("mailer", SparkOrigin::Git("https://github.com/you/mailer".to_string())), ("billing", SparkOrigin::Path(PathBuf::from("../billing"))), ("audit", SparkOrigin::Link(PathBuf::from("../audit")))] {
+            doc["sparks"][name] = source.to_item();
+            assert_eq!(recorded_origin(&doc.to_string(), name), Some(source));
+        }
+        assert!(doc.to_string().contains("audit = { path = \"../audit\", link = true }"), "{}", doc);
+
+        let dir = std::env::temp_dir().join(format!("blast-spark-origin-{}", std::process::id())).join("audit");
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(extract_repo_name(&SparkOrigin::Link(dir.join("."))).unwrap(), "audit");
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::logger;
+
+// Things blast has to remember between commands
+const STATE_PATH: &str = ".blast/state";
+
+#[derive(Default, Serialize, Deserialize)]
+struct State {
+    // Migrations applied since src/database/schema.rs was last generated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    stale_schema: Vec<String>,
+    // The last run of each seed file, by its path under src/database/seeds
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    seeds: BTreeMap<String, SeedRun>,
+    // SHA-256 of each migration's up.sql when it was applied, by migration directory path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    migration_hashes: BTreeMap<String, String>,
+    // SHA-256 of each schema file as blast last wrote it, so edits made since can be told apart
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    schema_hashes: BTreeMap<String, String>,
+    // Created with `blast new --no-git`, so a missing repository isn't a sign of a half-created project
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    no_git: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedRun {
+    pub time: String,
+    pub ok: bool,
+}
+
+fn load(project_dir: &Path) -> State {
+    fs::read_to_string(project_dir.join(STATE_PATH)).ok().and_then(|content| toml::from_str(&content).ok()).unwrap_or_default()
+}
+
+fn save(project_dir: &Path, state: &State) {
+    match toml::to_string_pretty(state) {
+        Ok(content) => {
+            if let Err(e) = crate::file_writer::write_state(project_dir.join(STATE_PATH), &content) {
+                logger::warning(&format!("Failed to update {}: {}", STATE_PATH, e)).unwrap_or_default();
+            }
+        }
+        Err(e) => logger::warning(&format!("Failed to serialize {}: {}", STATE_PATH, e)).unwrap_or_default(),
+    }
+}
+
+pub fn record_no_git(project_dir: &Path) {
+    let mut state = load(project_dir);
+    state.no_git = true;
+    save(project_dir, &state);
+}
+
+pub fn no_git(project_dir: &Path) -> bool {
+    load(project_dir).no_git
+}
+
+// Record migrations that changed the database after schema.rs was generated
+pub fn mark_schema_stale(project_dir: &Path, migrations: &[String]) {
+    let mut state = load(project_dir);
+    for migration in migrations {
+        if !state.stale_schema.contains(migration) {
+            state.stale_schema.push(migration.clone());
+        }
+    }
+    save(project_dir, &state);
+}
+
+// schema.rs was regenerated from the database
+pub fn mark_schema_fresh(project_dir: &Path) {
+    let mut state = load(project_dir);
+    if !state.stale_schema.is_empty() {
+        state.stale_schema.clear();
+        save(project_dir, &state);
+    }
+}
+
+pub fn record_seed_run(project_dir: &Path, file: &str, ok: bool) {
+    let mut state = load(project_dir);
+    state.seeds.insert(file.to_string(), SeedRun { time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(), ok });
+    save(project_dir, &state);
+}
+
+pub fn seed_runs(project_dir: &Path) -> BTreeMap<String, SeedRun> {
+    load(project_dir).seeds
+}
+
+pub fn schema_hash(project_dir: &Path, path: &str) -> Option<String> {
+    load(project_dir).schema_hashes.get(path.trim_start_matches("./")).cloned()
+}
+
+pub fn record_schema_hash(project_dir: &Path, path: &str, hash: &str) {
+    let mut state = load(project_dir);
+    state.schema_hashes.insert(path.trim_start_matches("./").to_string(), hash.to_string());
+    save(project_dir, &state);
+}
+
+pub fn migration_hashes(project_dir: &Path) -> BTreeMap<String, String> {
+    load(project_dir).migration_hashes
+}
+
+// Set (Some) or forget (None) the recorded up.sql hash of migration directories
+pub fn record_migration_hashes(project_dir: &Path, entries: &[(String, Option<String>)]) {
+    if entries.is_empty() {
+        return;
+    }
+    let mut state = load(project_dir);
+    for (path, hash) in entries {
+        match hash {
+            Some(hash) => state.migration_hashes.insert(path.clone(), hash.clone()),
+            None => state.migration_hashes.remove(path),
+        };
+    }
+    save(project_dir, &state);
+}
+
+pub fn stale_schema_migrations(project_dir: &Path) -> Vec<String> {
+    load(project_dir).stale_schema
+}
+
+// Shown after migrations when codegen didn't run or failed, and by every command until schema.rs is regenerated
+pub fn warn_if_schema_stale(project_dir: &Path) -> Result<(), String> {
+    let migrations = stale_schema_migrations(project_dir);
+    if migrations.is_empty() {
+        return Ok(());
+    }
+
+    let plural = if migrations.len() == 1 { "" } else { "s" };
+    logger::warning(&format!("schema.rs is stale: {} migration{} ran since it was generated ({})", migrations.len(), plural, migrations.join(", ")))?;
+    logger::plain("  Run `blast schema && blast gen structs && blast gen models` to catch up")
+}
@@ -1,13 +1,17 @@
+use crate::codegen_ignore::Generator;
+use crate::codegen_manifest::CodegenManifest;
 use crate::configs::Config;
+use crate::migration_columns::MigrationColumns;
+use crate::file_writer;
+use crate::mod_file;
 use crate::progress::ProgressManager;
+use crate::table_selection::TableSelection;
 use regex::Regex;
-use std::fs;
 use std::io::{self};
-use std::path::Path;
 use std::process::Command;
 
-fn load_schema_table_names(schema_path: &str) -> io::Result<Vec<String>> {
-    let content = fs::read_to_string(schema_path)?;
+fn load_schema_table_names(config: &Config, schema_path: &str) -> io::Result<Vec<String>> {
+    let content = crate::schema_patch::read_schema(config, schema_path)?;
 
     // IMPORTANT: Use a better regex that captures the actual table name correctly
     // This regex looks for table declarations like: table! { city_boundaries (id) {
@@ -30,42 +34,27 @@ fn load_schema_table_names(schema_path: &str) -> io::Result<Vec<String>> {
 
 fn run_diesel_ext(config: &Config) -> io::Result<String> {
     let mut command = Command::new("diesel_ext");
+    command.current_dir(&config.project_dir);
 
-    if let Some(derives) = config.assets.get("codegen").and_then(|codegen| codegen.get("structs")).and_then(|s| s.get("derives")).and_then(|v| v.as_array()) {
-        let derives_str = derives.iter().filter_map(|d| d.as_str()).collect::<Vec<_>>().join(", ");
-        command.arg("-d").arg(derives_str);
+    let codegen = config.codegen();
+
+    if !codegen.structs.derives.is_empty() {
+        command.arg("-d").arg(codegen.structs.derives.join(", "));
     }
 
-    if let Some(imports) = config.assets.get("codegen").and_then(|codegen| codegen.get("structs")).and_then(|s| s.get("imports")).and_then(|v| v.as_array()) {
-        for import in imports.iter().filter_map(|imp| imp.as_str()) {
-            command.arg("-I").arg(import);
-        }
+    for import in &codegen.structs.imports {
+        command.arg("-I").arg(import);
     }
 
-    if let Some(schema_path) = config.assets.get("codegen").and_then(|codegen| codegen.get("schema_file")).and_then(|v| v.as_str()) {
+    if let Some(schema_path) = &codegen.schema_file {
         command.arg("-s").arg(schema_path);
     }
     command.arg("-t");
 
-    let output = command.output()?.stdout;
+    let output = crate::logger::command_output("diesel_ext", &mut command)?.stdout;
     Ok(String::from_utf8_lossy(&output).into_owned())
 }
 
-fn camel_to_snake(name: &str) -> String {
-    let mut snake = String::new();
-    for (i, c) in name.chars().enumerate() {
-        if c.is_uppercase() {
-            if i != 0 {
-                snake.push('_');
-            }
-            snake.extend(c.to_lowercase());
-        } else {
-            snake.push(c);
-        }
-    }
-    snake
-}
-
 fn to_pascal(s: &str) -> String {
     s.split('_')
         .map(|w| {
@@ -78,97 +67,87 @@ fn to_pascal(s: &str) -> String {
         .collect()
 }
 
-fn fix_struct_name(generated_name: &str, schema_tables: &[String]) -> (String, String) {
-    let candidate = camel_to_snake(generated_name);
-
-    // First check: does this name exist exactly as-is in the schema tables?
-    if schema_tables.contains(&candidate) {
-        return (to_pascal(&candidate), candidate);
-    }
+// diesel_ext's struct definitions, each from its #[derive] line to the closing brace
+fn split_structs(content: &str) -> Vec<String> {
+    let mut structs = Vec::new();
+    let mut current_struct = String::new();
+    let mut inside_struct = false;
 
-    // Try pluralization if it doesn't end with 's'
-    if !candidate.ends_with('s') {
-        let candidate_plural = format!("{}s", candidate);
-        if schema_tables.contains(&candidate_plural) {
-            // IMPORTANT: Return the pluralized table name for both the PascalCase struct name
-            // and the exact table name to ensure consistency in imports and table_name attribute
-            return (to_pascal(&candidate_plural), candidate_plural);
+    for line in content.lines() {
+        if line.trim().starts_with("#[derive") {
+            inside_struct = true;
         }
-    }
-
-    // Finally, check all schema tables explicitly to find exact matches
-    // This ensures table names like "city_boundaries" are preserved exactly
-    for table_name in schema_tables {
-        // Convert both to lowercase for case-insensitive comparison
-        if table_name.to_lowercase().contains(&candidate.to_lowercase()) || candidate.to_lowercase().contains(&table_name.to_lowercase()) {
-            return (to_pascal(table_name), table_name.clone());
+        if inside_struct {
+            current_struct.push_str(line);
+            current_struct.push('\n');
+            if line.trim().ends_with('}') {
+                structs.push(std::mem::take(&mut current_struct));
+                inside_struct = false;
+            }
         }
     }
+    structs
+}
 
-    (generated_name.to_string(), candidate)
+// The table named by a struct's table_name attribute, which `diesel_ext -t` writes: #[diesel(table_name = users)],
+// or #[table_name = "users"] from older versions
+fn table_name_attribute(struct_def: &str) -> Option<String> {
+    let re = Regex::new(r#"#\[\s*(?:diesel\s*\(\s*)?table_name\s*=\s*"?([A-Za-z0-9_:]+)"?"#).unwrap();
+    re.captures(struct_def).and_then(|caps| caps[1].rsplit("::").next().map(String::from))
 }
 
-fn extract_struct_name(struct_def: &str) -> Option<&str> {
-    struct_def.lines().find(|line| line.trim().starts_with("pub struct")).and_then(|line| line.split_whitespace().nth(2))
+// Pair each diesel_ext struct with the schema table it was generated for. The table_name attribute decides when
+// present; without it, diesel_ext writes one struct per table in schema order, so the position does. Struct names
+// are never compared with table names: diesel_ext singularizes them, and `user` and `users` both become `User`
+fn assign_tables(struct_defs: Vec<String>, schema_tables: &[String]) -> Result<Vec<(String, String)>, String> {
+    let count = struct_defs.len();
+    let positional = count == schema_tables.len();
+    let mut assigned: Vec<(String, String)> = Vec::new();
+
+    for (index, struct_def) in struct_defs.into_iter().enumerate() {
+        let table = match table_name_attribute(&struct_def) {
+            Some(table) if schema_tables.contains(&table) => table,
+            Some(table) => return Err(format!("diesel_ext generated a struct for table '{}', which isn't in schema.rs; regenerate the schema with `blast schema`", table)),
+            None if positional => schema_tables[index].clone(),
+            None => return Err(format!("diesel_ext generated {} structs for the {} tables in schema.rs without table_name attributes, so they can't be matched to tables", count, schema_tables.len())),
+        };
+        if assigned.iter().any(|(existing, _)| existing == &table) {
+            return Err(format!("diesel_ext generated two structs for table '{}'", table));
+        }
+        assigned.push((table, struct_def));
+    }
+    Ok(assigned)
 }
 
-fn parse_and_process_structs(content: &str, config: &Config, schema_tables: &[String]) -> Option<Vec<String>> {
+fn parse_and_process_structs(content: &str, config: &Config, schema_tables: &[String], only: Option<&[String]>, manifest: &mut CodegenManifest) -> Option<Vec<String>> {
     // Single progress tracker for the entire operation
     let progress = ProgressManager::new_spinner();
     progress.set_message("Processing struct definitions...");
 
-    let output_dir = config.assets.get("codegen").and_then(|codegen| codegen.get("structs_dir")).and_then(|v| v.as_str()).unwrap_or("src/structs");
-
-    // Try the new naming convention first (ignore), then fall back to the old one (ignored_structs)
-    let ignore_list: Vec<String> = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("structs"))
-        .and_then(|s| s.get("ignore"))
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-        .unwrap_or_else(|| {
-            // Fallback to old naming convention
-            config
-                .assets
-                .get("codegen")
-                .and_then(|codegen| codegen.get("structs"))
-                .and_then(|s| s.get("ignored_structs"))
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-                .unwrap_or_default()
-        });
+    let codegen = config.codegen();
+    let output_dir = codegen.structs_dir.as_str();
 
-    let mut current_struct = String::new();
-    let mut inside_struct = false;
+    let structs = match assign_tables(split_structs(content), schema_tables) {
+        Ok(structs) => structs,
+        Err(e) => {
+            progress.error(&e);
+            return None;
+        }
+    };
 
+    // Every migration is read once for the whole run, not once per table
+    let migration_columns = MigrationColumns::load(config);
     let mut processed_tables = Vec::new();
 
-    for line in content.lines() {
-        if line.trim().starts_with("#[derive") {
-            inside_struct = true;
+    for (table_name, struct_def) in structs {
+        let excluded = only.map(|only| !only.contains(&table_name)).unwrap_or(false);
+        if excluded || !codegen.schema.includes(&table_name) || crate::codegen_ignore::skips(&codegen, Generator::Structs, &table_name) {
+            continue;
         }
-        if inside_struct {
-            current_struct.push_str(line);
-            current_struct.push('\n');
-            if line.trim().ends_with('}') {
-                if let Some(generated_name) = extract_struct_name(&current_struct) {
-                    let (_fixed_name, table_name) = fix_struct_name(generated_name, schema_tables);
 
-                    if ignore_list.iter().any(|ignored| ignored.eq_ignore_ascii_case(&table_name)) {
-                        current_struct.clear();
-                        inside_struct = false;
-                        continue;
-                    }
-                    let (fixed_name, table_name) = fix_struct_name(generated_name, schema_tables);
-
-                    if write_struct_file(config, &fixed_name, &table_name, &current_struct, output_dir) {
-                        processed_tables.push(table_name);
-                    }
-                }
-                current_struct.clear();
-                inside_struct = false;
-            }
+        // Files and struct names follow the exact table name: city_boundaries stays city_boundaries
+        if write_struct_file(config, &to_pascal(&table_name), &table_name, &struct_def, output_dir, &migration_columns, manifest) {
+            processed_tables.push(table_name);
         }
     }
 
@@ -177,7 +156,7 @@ fn parse_and_process_structs(content: &str, config: &Config, schema_tables: &[St
         None
     } else {
         // Update mod.rs file
-        if update_mod_file(config, &processed_tables) {
+        if update_mod_file(config, manifest) {
             // Show a single consolidated message
             progress.success(&format!("Generated {} struct files from schema", processed_tables.len()));
             Some(processed_tables)
@@ -188,71 +167,9 @@ fn parse_and_process_structs(content: &str, config: &Config, schema_tables: &[St
     }
 }
 
-fn check_migration_for_serial_fields(table_name: &str) -> Vec<String> {
-    // Find migration files for this table
-    let migrations_dir = "src/database/migrations";
-    let mut serial_fields = Vec::new();
-    let mut auto_fields = Vec::new();
-
-    // Try to find migration files
-    if let Ok(entries) = fs::read_dir(migrations_dir) {
-        for entry in entries.flatten() {
-            if let Ok(file_type) = entry.file_type() {
-                if file_type.is_dir() {
-                    // Check if this migration directory has an up.sql file
-                    let up_sql_path = entry.path().join("up.sql");
-                    if up_sql_path.exists() {
-                        // Read the up.sql file
-                        if let Ok(sql_content) = fs::read_to_string(&up_sql_path) {
-                            // Look for CREATE TABLE statements for this table
-                            let table_pattern = format!("CREATE TABLE {}[\\s\\n]*\\(", table_name);
-                            let table_re = Regex::new(&table_pattern).unwrap_or(Regex::new("this will never match").unwrap());
-
-                            if table_re.is_match(&sql_content) {
-                                // Found the migration file for this table
-                                // Extract SERIAL fields
-                                let lines: Vec<&str> = sql_content.lines().collect();
-                                for line in lines {
-                                    let trimmed = line.trim();
-
-                                    // Look for SERIAL keyword and extract field name
-                                    if trimmed.contains("SERIAL") {
-                                        if let Some(field_name) = trimmed.split_whitespace().next() {
-                                            serial_fields.push(field_name.to_string());
-                                        }
-                                    }
-
-                                    // Look for DEFAULT expressions
-                                    if trimmed.contains("DEFAULT") {
-                                        if let Some(field_name) = trimmed.split_whitespace().next() {
-                                            auto_fields.push(field_name.to_string());
-                                        }
-                                    }
-                                }
-
-                                // No need to check other migration files once we found the right one
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    // Add common auto-generated fields
-    auto_fields.extend(vec!["id".to_string(), "created_at".to_string(), "updated_at".to_string()]);
-
-    // Combine both lists
-    let mut result = Vec::new();
-    result.extend(serial_fields);
-    result.extend(auto_fields);
-    result
-}
-
-fn write_struct_file(config: &Config, fixed_struct_name: &str, table_name: &str, struct_def: &str, output_dir: &str) -> bool {
+fn write_struct_file(config: &Config, fixed_struct_name: &str, table_name: &str, struct_def: &str, output_dir: &str, migration_columns: &MigrationColumns, manifest: &mut CodegenManifest) -> bool {
     // Create the output directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(output_dir) {
+    if let Err(e) = file_writer::create_dir_all(config.project_dir.join(output_dir)) {
         crate::logger::error(&format!("Error creating directory {}: {}", output_dir, e)).unwrap_or_default();
         return false;
     }
@@ -262,26 +179,21 @@ fn write_struct_file(config: &Config, fixed_struct_name: &str, table_name: &str,
 
     // Create insertable directory for New* structs
     let insertable_dir = format!("{}/insertable", output_dir);
-    if let Err(_e) = fs::create_dir_all(&insertable_dir) {
+    if let Err(_e) = file_writer::create_dir_all(config.project_dir.join(&insertable_dir)) {
         crate::logger::error("Error creating insertable directory").unwrap_or_default();
         return false;
     }
 
     // Check if this struct should be skipped for insertable generation using nested config
-    let insertable_ignore_list: Vec<String> = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("structs"))
-        .and_then(|s| s.get("insertable"))
-        .and_then(|i| i.get("ignore"))
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-        .unwrap_or_default();
+    let codegen = config.codegen();
+    let structs_config = &codegen.structs;
 
-    let skip_insertable = insertable_ignore_list.iter().any(|ignored| ignored.eq_ignore_ascii_case(table_name));
+    let skip_insertable = crate::codegen_ignore::skips(&codegen, Generator::Insertable, table_name);
 
-    // Get the auto-generated fields for this table by examining migration files
-    let auto_fields = check_migration_for_serial_fields(table_name);
+    // What the migrations say about this table's columns: SERIAL/identity ones are left out of the insertable,
+    // ones with a DEFAULT become Options so None leaves them to the database
+    let auto_fields = migration_columns.generated(table_name);
+    let defaulted = migration_columns.defaulted(table_name);
 
     // Process the main struct definition
     let new_struct_def = struct_def
@@ -300,28 +212,30 @@ fn write_struct_file(config: &Config, fixed_struct_name: &str, table_name: &str,
 
     // Extract field definitions for insertable structs only
     let re = Regex::new(r"(?s)pub struct.*?\{(.*?)\}").unwrap(); // (?s) enables dot-all mode for regex
-    let mut insertable_fields = String::new();
+    let mut fields: Vec<(String, String)> = Vec::new();
+    let mut optional_defaults: Vec<String> = Vec::new();
 
     if let Some(captures) = re.captures(&new_struct_def) {
         if let Some(fields_match) = captures.get(1) {
-            let fields = fields_match.as_str();
-
             // Process each field
-            for line in fields.lines() {
+            for line in fields_match.as_str().lines() {
                 let trimmed = line.trim();
-                if trimmed.starts_with("pub ") {
-                    // Split the field into name and type
-                    let field_parts: Vec<&str> = trimmed.split(':').collect();
-                    if field_parts.len() > 1 {
-                        let field_name = field_parts[0].trim();
-                        let field_type = field_parts[1].trim().trim_end_matches(',');
+                if let Some(field) = trimmed.strip_prefix("pub ") {
+                    // Split the field into name and type; the type may itself contain paths (chrono::NaiveDateTime)
+                    if let Some((field_name, field_type)) = field.split_once(':') {
+                        let field_name = field_name.trim();
+                        let field_type = field_type.trim().trim_end_matches(',');
 
-                        // For insertable: skip auto-generated fields (SERIAL, DEFAULT, etc.)
-                        // Check if this field is in our auto-generated fields list
-                        let is_auto_field = auto_fields.iter().any(|af| field_name.ends_with(&format!(" {}", af)) || field_name.ends_with(&format!(":{}", af)));
+                        // For insertable: skip auto-generated fields (SERIAL, identity, etc.)
+                        let is_auto_field = auto_fields.iter().any(|af| af == field_name);
 
                         if !trimmed.contains("primary_key") && !is_auto_field {
-                            insertable_fields.push_str(&format!("    {}: {},\n", field_name, field_type));
+                            if defaulted.iter().any(|column| column == field_name) && !field_type.starts_with("Option<") {
+                                fields.push((field_name.to_string(), format!("Option<{}>", field_type)));
+                                optional_defaults.push(field_name.to_string());
+                            } else {
+                                fields.push((field_name.to_string(), field_type.to_string()));
+                            }
                         }
                     }
                 }
@@ -329,43 +243,21 @@ fn write_struct_file(config: &Config, fixed_struct_name: &str, table_name: &str,
         }
     }
 
-    // No ChangeSet structs - they're removed
+    // Rules from [codegen.structs.<table>.validate], checked against the insertable fields
+    let validations = match structs_config.validations_for(table_name, fixed_struct_name).and_then(|rules| resolve_validations(&rules, &fields, fixed_struct_name)) {
+        Ok(validations) => validations,
+        Err(e) => {
+            crate::logger::error(&e).unwrap_or_default();
+            return false;
+        }
+    };
 
     // Create the insertable struct definition (will go in a separate file)
-    // Create the insertable struct definition (will go in a separate file)
-    let insertable_struct = format!(
-        r#"use crate::database::schema::{0};
-use diesel::{{Insertable, Queryable, AsChangeset}};
-use serde::{{Serialize, Deserialize}};
+    let insertable_struct = insertable_source(table_name, fixed_struct_name, &fields, &optional_defaults, &validations);
 
-#[derive(Debug, Insertable, AsChangeset, Serialize, Deserialize)]
-#[diesel(table_name = {0})]
-pub struct New{1} {{
-{2}}}
-"#,
-        table_name, fixed_struct_name, insertable_fields
-    );
-
-    // Get global imports
-    let global_imports: Vec<String> = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("structs"))
-        .and_then(|s| s.get("imports"))
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-        .unwrap_or_default();
-
-    // Check for struct-specific imports
-    let struct_specific_imports: Vec<String> = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("structs"))
-        .and_then(|s| s.get(&format!("{}", fixed_struct_name))) // Look for a section with the struct name
-        .and_then(|s| s.get("imports"))
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-        .unwrap_or_default();
+    // Global imports plus any from a [codegen.structs.<StructName>] section
+    let global_imports = structs_config.imports.clone();
+    let struct_specific_imports = structs_config.imports_for(fixed_struct_name);
 
     // Combine global and struct-specific imports
     let mut imports = vec!["diesel::Insertable".to_string(), "diesel::AsChangeset".to_string()];
@@ -396,7 +288,9 @@ pub struct New{1} {{
 
     crate::logger::debug(&format!("Writing struct file: {} for table: {}", file_name, table_name)).unwrap_or_default(); // Debug log
 
-    let struct_write_ok = if let Err(e) = fs::write(&file_name, final_struct_def) {
+    // A file the user edited is skipped with a warning but still counts as generated
+    let schema_file = codegen.schema_path();
+    let struct_write_ok = if let Err(e) = crate::codegen_format::render(&config.project_dir, &file_name, &final_struct_def, schema_file, Some(table_name)).and_then(|source| manifest.write(&file_name, &source, Some(table_name))) {
         crate::logger::error(&format!("Error writing struct file {}: {}", file_name, e)).unwrap_or_default();
         false
     } else {
@@ -413,113 +307,334 @@ pub struct New{1} {{
 
         crate::logger::debug(&format!("Writing insertable struct file: {} for table: {}", insertable_file_name, table_name)).unwrap_or_default(); // Debug log
 
-        if let Err(e) = fs::write(&insertable_file_name, insertable_struct) {
+        if let Err(e) = crate::codegen_format::render(&config.project_dir, &insertable_file_name, &insertable_struct, schema_file, Some(table_name)).and_then(|source| manifest.write(&insertable_file_name, &source, Some(table_name))) {
             crate::logger::error(&format!("Error writing insertable struct file {}: {}", insertable_file_name, e)).unwrap_or_default();
             false
         } else {
-            // Update the insertable mod.rs file to include the new file
-            let insertable_mod_path = format!("{}/insertable/mod.rs", output_dir);
-            let mut mod_content = fs::read_to_string(&insertable_mod_path).unwrap_or_default();
-            let mod_declaration = format!("pub mod {};", table_name);
-            let pub_use = format!("pub use {}::*;", table_name);
-
-            if !mod_content.contains(&mod_declaration) {
-                mod_content.push_str(&format!("\n{}", mod_declaration));
-                mod_content.push_str(&format!("\n{}", pub_use));
-
-                if let Err(e) = fs::write(&insertable_mod_path, mod_content) {
-                    crate::logger::error(&format!("Error updating insertable mod.rs: {}", e)).unwrap_or_default();
-                    false
-                } else {
-                    true
-                }
-            } else {
-                true
-            }
+            true
         }
     };
 
-    struct_write_ok && insertable_write_ok
+    // The form builds on the insertable, so it is skipped with it
+    let form_write_ok = if skip_insertable || !codegen.forms.enabled {
+        true
+    } else if let Err(e) = crate::forms::write_form(config, table_name, fixed_struct_name, &fields, !validations.is_empty(), manifest) {
+        crate::logger::error(&format!("Error writing the form of {}: {}", table_name, e)).unwrap_or_default();
+        false
+    } else {
+        true
+    };
+
+    struct_write_ok && insertable_write_ok && form_write_ok
 }
 
-fn update_mod_file(config: &Config, struct_table_names: &[String]) -> bool {
-    if struct_table_names.is_empty() {
-        return true; // Nothing to do, but not an error
+// Checks that can be generated for a field listed in [codegen.structs.<table>.validate]
+#[derive(Debug, Clone, PartialEq)]
+enum ValidationRule {
+    Email,
+    NonEmpty,
+    MaxLen(usize),
+}
+
+impl ValidationRule {
+    fn parse(rule: &str) -> Result<Self, String> {
+        match rule.trim() {
+            "email" => Ok(ValidationRule::Email),
+            "non_empty" | "non-empty" => Ok(ValidationRule::NonEmpty),
+            other => match other.strip_prefix("max_len:").map(|limit| limit.trim().parse::<usize>()) {
+                Some(Ok(limit)) => Ok(ValidationRule::MaxLen(limit)),
+                Some(Err(_)) => Err(format!("Invalid validation rule '{}': max_len needs a length, e.g. \"max_len:255\"", rule)),
+                None => Err(format!("Unknown validation rule '{}' (expected email, non_empty or max_len:<n>)", rule)),
+            },
+        }
+    }
+
+    // Generated check against `value` (a &str) that records a ValidationError on failure
+    fn check(&self, field: &str) -> String {
+        let (failed, message) = match self {
+            ValidationRule::Email => ("!is_email(value)".to_string(), "must be a valid email address".to_string()),
+            ValidationRule::NonEmpty => ("value.trim().is_empty()".to_string(), "must not be empty".to_string()),
+            ValidationRule::MaxLen(limit) => (format!("value.chars().count() > {}", limit), format!("must be at most {} characters", limit)),
+        };
+        format!(
+            "        if {} {{\n            errors.push(ValidationError {{ field: \"{}\", message: \"{}\".to_string() }});\n        }}\n",
+            failed, field, message
+        )
     }
+}
 
-    let output_dir = config.assets.get("codegen").and_then(|codegen| codegen.get("structs_dir")).and_then(|v| v.as_str()).unwrap_or("src/structs");
+// Parse the configured rules and check they name text fields of the insertable struct
+fn resolve_validations(rules: &[(String, Vec<String>)], fields: &[(String, String)], struct_name: &str) -> Result<Vec<(String, bool, Vec<ValidationRule>)>, String> {
+    let mut validations = Vec::new();
 
-    let mod_file_path = Path::new(output_dir).join("mod.rs");
-    let mut mod_file_content = fs::read_to_string(&mod_file_path).unwrap_or_default();
+    for (field, field_rules) in rules {
+        let Some((_, field_type)) = fields.iter().find(|(name, _)| name == field) else {
+            return Err(format!("Validation configured for '{}', but New{} has no such field (auto-generated and primary key fields are not insertable)", field, struct_name));
+        };
 
-    let mut updated = false;
-    for table_name in struct_table_names {
-        let mod_declaration = format!("pub mod {};", table_name);
-        if !mod_file_content.contains(&mod_declaration) {
-            mod_file_content.push_str(&format!("\n{}", mod_declaration));
-            updated = true;
+        let nullable = field_type.starts_with("Option<");
+        if option_inner(field_type) != "String" {
+            return Err(format!("Validation rules apply to text fields, but New{}.{} is {}", struct_name, field, field_type));
         }
-        if !mod_file_content.contains(&format!("pub use {}::{};", table_name, to_pascal(table_name))) && !mod_file_content.contains(&format!("pub use {}::*;", table_name)) {
-            mod_file_content.push_str(&format!("\npub use {}::*;", table_name));
-            updated = true;
+
+        let parsed = field_rules.iter().map(|rule| ValidationRule::parse(rule)).collect::<Result<Vec<_>, _>>().map_err(|e| format!("New{}.{}: {}", struct_name, field, e))?;
+        validations.push((field.clone(), nullable, parsed));
+    }
+
+    Ok(validations)
+}
+
+// Fail before running diesel_ext if any configured rule is unknown
+fn check_validation_rules(config: &Config) -> Result<(), String> {
+    let structs_config = config.codegen().structs;
+    for name in structs_config.overrides.keys() {
+        for (field, rules) in structs_config.validations_for(name, name)? {
+            for rule in rules {
+                ValidationRule::parse(&rule).map_err(|e| format!("[codegen.structs.{}.validate] {}: {}", name, field, e))?;
+            }
         }
     }
+    Ok(())
+}
 
-    if updated {
-        if let Err(e) = fs::write(&mod_file_path, mod_file_content) {
-            crate::logger::error(&format!("Error writing mod.rs file: {}", e)).unwrap_or_default();
-            return false;
+fn option_inner(field_type: &str) -> &str {
+    field_type.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>')).unwrap_or(field_type).trim()
+}
+
+// Copy types are moved out of the row, everything else cloned
+fn copy_expression(field: &str, field_type: &str) -> String {
+    const COPY_TYPES: &[&str] = &["bool", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "NaiveDate", "NaiveDateTime", "NaiveTime"];
+    let type_name = option_inner(field_type).rsplit("::").next().unwrap_or_default();
+    if COPY_TYPES.contains(&type_name) {
+        format!("record.{}", field)
+    } else {
+        format!("record.{}.clone()", field)
+    }
+}
+
+// New<T> with its From<&T> conversion, builder-style constructor and (when rules are configured) Validate impl
+fn insertable_source(table_name: &str, struct_name: &str, fields: &[(String, String)], optional_defaults: &[String], validations: &[(String, bool, Vec<ValidationRule>)]) -> String {
+    let field_lines: String = fields.iter().map(|(name, ty)| format!("    pub {}: {},\n", name, ty)).collect();
+    // A NOT NULL column with a DEFAULT is an Option only in the insertable; the row always has a value
+    let copied: String = fields
+        .iter()
+        .map(|(name, ty)| if optional_defaults.contains(name) { format!("            {}: Some({}),\n", name, copy_expression(name, ty)) } else { format!("            {}: {},\n", name, copy_expression(name, ty)) })
+        .collect();
+
+    let required: Vec<&(String, String)> = fields.iter().filter(|(_, ty)| !ty.starts_with("Option<")).collect();
+    let arguments = required.iter().map(|(name, ty)| format!("{}: {}", name, ty)).collect::<Vec<_>>().join(", ");
+    let initializers: String = fields
+        .iter()
+        .map(|(name, ty)| if ty.starts_with("Option<") { format!("            {}: None,\n", name) } else { format!("            {},\n", name) })
+        .collect();
+    let setters: String = fields
+        .iter()
+        .filter(|(_, ty)| ty.starts_with("Option<"))
+        .map(|(name, ty)| format!("\n    pub fn with_{0}(mut self, {0}: {1}) -> Self {{\n        self.{0} = Some({0});\n        self\n    }}\n", name, option_inner(ty)))
+        .collect();
+    let allow_arguments = if required.len() > 7 { "    #[allow(clippy::too_many_arguments)]\n" } else { "" };
+
+    let mut source = format!(
+        r#"use crate::database::schema::{table_name};
+use crate::structs::{struct_name};
+use diesel::{{Insertable, Queryable, AsChangeset}};
+use serde::{{Serialize, Deserialize}};
+
+#[derive(Debug, Insertable, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = {table_name})]
+pub struct New{struct_name} {{
+{field_lines}}}
+
+impl New{struct_name} {{
+    // Required fields up front; optional ones start as None and are set with the with_* methods
+{allow_arguments}    pub fn new({arguments}) -> Self {{
+        New{struct_name} {{
+{initializers}        }}
+    }}
+{setters}}}
+
+// Writable fields of an existing row, e.g. to change a few and pass to update_by_id()
+impl From<&{struct_name}> for New{struct_name} {{
+    fn from(record: &{struct_name}) -> Self {{
+        New{struct_name} {{
+{copied}        }}
+    }}
+}}
+"#
+    );
+
+    if !validations.is_empty() {
+        let mut checks = String::new();
+        for (field, nullable, rules) in validations {
+            let rule_checks: String = rules.iter().map(|rule| rule.check(field)).collect();
+            if *nullable {
+                let indented: String = rule_checks.lines().map(|line| format!("    {}\n", line)).collect();
+                checks.push_str(&format!("        if let Some(value) = self.{}.as_deref() {{\n{}        }}\n", field, indented));
+            } else {
+                checks.push_str(&format!("        let value = self.{}.as_str();\n{}", field, rule_checks));
+            }
         }
+
+        source.push_str(&format!(
+            r#"
+// From [codegen.structs.{table_name}.validate]; the generated model runs it before create() and update_by_id()
+impl Validate for New{struct_name} {{
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {{
+        let mut errors = Vec::new();
+{checks}
+        if errors.is_empty() {{
+            Ok(())
+        }} else {{
+            Err(errors)
+        }}
+    }}
+}}
+"#
+        ));
+        let uses_email = validations.iter().any(|(_, _, rules)| rules.contains(&ValidationRule::Email));
+        let imports = if uses_email { "is_email, Validate, ValidationError" } else { "Validate, ValidationError" };
+        source = source.replacen("use serde::{Serialize, Deserialize};", &format!("use serde::{{Serialize, Deserialize}};\nuse super::validate::{{{}}};", imports), 1);
+    }
+
+    source
+}
+
+// Shared Validate trait and helpers, rewritten on every run so it tracks the blast version
+const VALIDATE_SOURCE: &str = r#"// Validation hook for insertable structs
+use std::fmt;
+
+// One failed check on an insertable field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+// Generated for structs with [codegen.structs.<table>.validate] rules; implement it by hand for custom checks
+pub trait Validate {
+    fn validate(&self) -> Result<(), Vec<ValidationError>>;
+}
+
+// Lets the generated models report failures through MeltDown like any other database error
+pub fn validation_failed(errors: Vec<ValidationError>) -> diesel::result::Error {
+    let message = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ");
+    diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::CheckViolation, Box::new(message))
+}
+
+pub fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && !domain.contains('@') && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.') && !value.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+"#;
+
+// Rewrite the generated sections of the structs and insertable mod.rs files from every table
+// the manifest has a file for, so tables that were dropped stop being declared
+fn update_mod_file(config: &Config, manifest: &CodegenManifest) -> bool {
+    let codegen = config.codegen();
+    let output_dir = codegen.structs_dir.as_str();
+    let insertable_dir = format!("{}/insertable", output_dir);
+
+    let mut insertable_modules = vec!["validate".to_string()];
+    insertable_modules.extend(manifest.tables_in(&insertable_dir));
+
+    let result = mod_file::sync(&config.project_dir.join(output_dir).join("mod.rs"), &manifest.tables_in(output_dir), true)
+        .and_then(|_| mod_file::sync(&config.project_dir.join(&insertable_dir).join("mod.rs"), &insertable_modules, true))
+        .and_then(|_| if codegen.forms.enabled { crate::forms::sync_mod(config, manifest) } else { Ok(()) });
+    if let Err(e) = result {
+        crate::logger::error(&format!("Error writing mod.rs file: {}", e)).unwrap_or_default();
+        return false;
     }
 
     true
 }
 
-pub fn generate(config: &Config) -> bool {
+// Regenerate structs for tables that changed since the last run (every table with `all`), limited to `selection`
+pub fn generate(config: &Config, all: bool, selection: &TableSelection) -> bool {
+    let codegen = config.codegen();
+    let structs_dir = codegen.structs_dir.clone();
+
+    let ignored = |table: &str| crate::codegen_ignore::ignored_by(&codegen, Generator::Structs, table).is_some();
+    if let Err(e) = selection.check_schema(config) {
+        crate::logger::error(&e).unwrap_or_default();
+        return false;
+    }
+    let (snapshot, changes) = match crate::codegen_snapshot::plan(config, "structs", ignored, all, selection, |table| !config.project_dir.join(format!("{}/{}.rs", structs_dir, table)).exists()) {
+        Ok(plan) => plan,
+        // Without a readable schema there is nothing to compare; let the full run report the problem
+        Err(_) => return generate_tables(config, None),
+    };
+
+    let mut ok = changes.changed.is_empty() || generate_tables(config, Some(&changes.changed));
+
+    // Files of tables dropped from the schema go too, unless they were edited
+    if !changes.removed.is_empty() {
+        let mut manifest = CodegenManifest::load(&config.project_dir);
+        for (dir, generator) in [(structs_dir.clone(), Generator::Structs), (format!("{}/insertable", structs_dir), Generator::Insertable), (codegen.forms.dir.clone(), Generator::Insertable)] {
+            // Files of ignored tables are left alone, like ignored tables themselves
+            let kept: Vec<String> = snapshot
+                .table_names()
+                .into_iter()
+                .chain(manifest.tables_in(&dir).into_iter().filter(|table| crate::codegen_ignore::ignored_by(&codegen, generator, table).is_some()))
+                .collect();
+            if let Err(e) = manifest.remove_stale(&dir, &kept) {
+                crate::logger::warning(&e).unwrap_or_default();
+                ok = false;
+            }
+        }
+        ok &= update_mod_file(config, &manifest);
+        if let Err(e) = manifest.save() {
+            crate::logger::warning(&e).unwrap_or_default();
+        }
+    }
+
+    if ok {
+        if let Err(e) = crate::codegen_snapshot::save(config, "structs", &snapshot) {
+            crate::logger::warning(&e).unwrap_or_default();
+        }
+    }
+    crate::logger::success(&format!("Structs: {}", changes.summary())).unwrap_or_default();
+    ok
+}
+
+// Generate structs, optionally restricted to the given tables
+pub fn generate_tables(config: &Config, only: Option<&[String]>) -> bool {
     let progress = ProgressManager::new_spinner();
     progress.set_message("Generating database structs...");
 
     // Get schema file path
-    let schema_path = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("schema_file"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("src/database/schema.rs");
+    let codegen = config.codegen();
+    let schema_path = codegen.schema_path();
 
     // Check if schema file exists
-    if !Path::new(schema_path).exists() {
+    if !config.project_dir.join(schema_path).exists() {
         progress.error(&format!("Schema file not found at {}", schema_path));
         return false;
     }
 
-    // Get ignored structs list from Catalyst.toml - try both naming conventions
-    let ignore_list: Vec<String> = config
-        .assets
-        .get("codegen")
-        .and_then(|codegen| codegen.get("structs"))
-        .and_then(|s| s.get("ignore"))
-        .and_then(|v| v.as_array())
-        .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-        .unwrap_or_else(|| {
-            // Fallback to old naming convention
-            config
-                .assets
-                .get("codegen")
-                .and_then(|codegen| codegen.get("structs"))
-                .and_then(|s| s.get("ignored_structs"))
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|s| s.as_str().map(String::from)).collect())
-                .unwrap_or_default()
-        });
+    let ignore_list: Vec<&str> = codegen.ignore.iter().chain(&codegen.structs.ignore).map(String::as_str).collect();
 
     // Print ignored structs for debugging
     if !ignore_list.is_empty() {
         progress.set_message(&format!("Ignoring struct generation for: {}", ignore_list.join(", ")));
     }
 
+    if let Err(e) = check_validation_rules(config) {
+        progress.error(&e);
+        return false;
+    }
+
     // Load schema table names
-    let schema_tables = match load_schema_table_names(schema_path) {
+    let schema_tables = match load_schema_table_names(config, schema_path) {
         Ok(tables) => {
             if tables.is_empty() {
                 progress.error("No tables found in schema file");
@@ -534,29 +649,43 @@ pub fn generate(config: &Config) -> bool {
     };
 
     // Create output directory
-    let output_dir = config.assets.get("codegen").and_then(|codegen| codegen.get("structs_dir")).and_then(|v| v.as_str()).unwrap_or("src/structs");
+    let output_dir = codegen.structs_dir.as_str();
 
-    if let Err(e) = fs::create_dir_all(output_dir) {
+    if let Err(e) = file_writer::create_dir_all(config.project_dir.join(output_dir)) {
         progress.error(&format!("Error creating structs directory: {}", e));
         return false;
     }
 
     // Also create the insertable directory
     let insertable_dir = format!("{}/insertable", output_dir);
-    if let Err(_) = fs::create_dir_all(&insertable_dir) {
+    if file_writer::create_dir_all(config.project_dir.join(&insertable_dir)).is_err() {
         progress.error("Error creating insertable directory");
         return false;
     }
 
     // Initialize the insertable/mod.rs file if it doesn't exist
-    let insertable_mod_path = format!("{}/insertable/mod.rs", output_dir);
-    if !Path::new(&insertable_mod_path).exists() {
-        if let Err(_) = fs::write(&insertable_mod_path, "// Auto-generated insertable struct exports\n") {
+    let insertable_mod_path = config.project_dir.join(output_dir).join("insertable/mod.rs");
+    if !file_writer::exists(&insertable_mod_path) {
+        if file_writer::write(&insertable_mod_path, "// Auto-generated insertable struct exports\n").is_err() {
             progress.error("Error creating insertable/mod.rs file");
             return false;
         }
     }
 
+    // Validate trait used by the generated validate() impls and models
+    let mut manifest = CodegenManifest::load(&config.project_dir);
+    let validate_path = format!("{}/validate.rs", insertable_dir);
+    if let Err(e) = crate::codegen_format::render(&config.project_dir, &validate_path, VALIDATE_SOURCE, schema_path, None).and_then(|source| manifest.write(&validate_path, &source, None)) {
+        progress.error(&format!("Error writing insertable/validate.rs: {}", e));
+        return false;
+    }
+    if codegen.forms.enabled {
+        if let Err(e) = crate::forms::write_support(config, &mut manifest) {
+            progress.error(&format!("Error writing the form support module: {}", e));
+            return false;
+        }
+    }
+
     // Run diesel_ext
     let output = match run_diesel_ext(config) {
         Ok(output) => {
@@ -573,11 +702,80 @@ pub fn generate(config: &Config) -> bool {
     };
 
     // Parse and process
-    if let Some(_tables) = parse_and_process_structs(&output, config, &schema_tables) {
-        // Success message already shown in parse_and_process_structs
-        true
-    } else {
-        // Error message already shown in parse_and_process_structs
-        false
+    let processed = parse_and_process_structs(&output, config, &schema_tables, only, &mut manifest);
+
+    if let Err(e) = manifest.save() {
+        crate::logger::warning(&e).unwrap_or_default();
+    }
+
+    // Success and error messages were already shown in parse_and_process_structs
+    processed.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> Vec<String> {
+        ["user", "users", "user_settings", "city_boundaries"].iter().map(|table| table.to_string()).collect()
+    }
+
+    fn struct_def(name: &str, attribute: Option<&str>) -> String {
+        format!("#[derive(Queryable, Debug)]\n{}pub struct {} {{\n    pub id: i32,\n}}\n", attribute.map(|attribute| format!("{}\n", attribute)).unwrap_or_default(), name)
+    }
+
+    // diesel_ext singularizes struct names, so user/users collide and user_settings/city_boundaries look like other tables
+    fn assigned_files(output: &str) -> Vec<(String, String)> {
+        assign_tables(split_structs(output), &tables()).unwrap().into_iter().map(|(table, _)| (to_pascal(&table), format!("src/structs/{}.rs", table))).collect()
+    }
+
+    #[test]
+    fn table_name_attributes_map_structs_to_exact_tables() {
+        // Out of schema order, to show the attribute decides
+        let output = [
+            struct_def("CityBoundary", Some("#[diesel(table_name = city_boundaries)]")),
+            struct_def("User", Some("#[diesel(table_name = users)]")),
+            struct_def("UserSetting", Some("#[table_name = \"user_settings\"]")),
+            struct_def("User", Some("#[diesel(table_name = crate::schema::user)]")),
+        ]
+        .concat();
+
+        assert_eq!(
+            assigned_files(&output),
+            vec![
+                ("CityBoundaries".to_string(), "src/structs/city_boundaries.rs".to_string()),
+                ("Users".to_string(), "src/structs/users.rs".to_string()),
+                ("UserSettings".to_string(), "src/structs/user_settings.rs".to_string()),
+                ("User".to_string(), "src/structs/user.rs".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn structs_without_attributes_follow_schema_order() {
+        let output = ["User", "User", "UserSetting", "CityBoundary"].iter().map(|name| struct_def(name, None)).collect::<String>();
+
+        assert_eq!(
+            assigned_files(&output),
+            vec![
+                ("User".to_string(), "src/structs/user.rs".to_string()),
+                ("Users".to_string(), "src/structs/users.rs".to_string()),
+                ("UserSettings".to_string(), "src/structs/user_settings.rs".to_string()),
+                ("CityBoundaries".to_string(), "src/structs/city_boundaries.rs".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatchable_structs_are_an_error() {
+        // Fewer structs than tables and no attributes: positions mean nothing
+        let output = ["User", "UserSetting"].iter().map(|name| struct_def(name, None)).collect::<String>();
+        assert!(assign_tables(split_structs(&output), &tables()).is_err());
+
+        let output = struct_def("City", Some("#[diesel(table_name = city)]"));
+        assert!(assign_tables(split_structs(&output), &tables()).is_err());
+
+        let output = [struct_def("User", Some("#[diesel(table_name = users)]")), struct_def("User", Some("#[diesel(table_name = users)]"))].concat();
+        assert!(assign_tables(split_structs(&output), &tables()).is_err());
     }
 }
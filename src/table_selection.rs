@@ -0,0 +1,73 @@
+use crate::configs::Config;
+
+// Tables named with --only and --except on gen structs, gen models and refresh. Applied after the schema is
+// parsed, on top of [codegen] ignore
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableSelection {
+    pub only: Vec<String>,
+    pub except: Vec<String>,
+}
+
+fn table_list(value: Option<String>) -> Vec<String> {
+    value.map(|value| value.split(',').map(|table| table.trim().to_string()).filter(|table| !table.is_empty()).collect()).unwrap_or_default()
+}
+
+impl TableSelection {
+    // From the values of --only and --except, e.g. "users,posts"
+    pub fn from_flags(only: Option<String>, except: Option<String>) -> Self {
+        TableSelection { only: table_list(only), except: table_list(except) }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.only.is_empty() || !self.except.is_empty()
+    }
+
+    pub fn includes(&self, table: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|name| name == table)) && !self.except.iter().any(|name| name == table)
+    }
+
+    // Every named table has to be in schema.rs; a typo would otherwise select nothing and look like success
+    pub fn check(&self, schema_tables: &[String]) -> Result<(), String> {
+        let candidates: Vec<&str> = schema_tables.iter().map(String::as_str).collect();
+        for (flag, names) in [("--only", &self.only), ("--except", &self.except)] {
+            if let Some(unknown) = names.iter().find(|name| !schema_tables.contains(name)) {
+                return Err(match crate::configs::nearest_key(unknown, &candidates) {
+                    Some(suggestion) => format!("Unknown table '{}' in {}, did you mean '{}'?", unknown, flag, suggestion),
+                    None => format!("Unknown table '{}' in {}; schema.rs has no such table", unknown, flag),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // check() against the project's schema.rs
+    pub fn check_schema(&self, config: &Config) -> Result<(), String> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let schema_path = config.codegen().schema_path().to_string();
+        let schema = crate::file_writer::read(config.project_dir.join(&schema_path)).map_err(|e| format!("Failed to read {}: {}", schema_path, e))?;
+        let tables: Vec<String> = crate::models::parse_schema_tables(&schema).into_iter().map(|table| table.name).collect();
+        self.check(&tables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_named_tables_and_suggests_on_typos() {
+        let selection = TableSelection::from_flags(Some("users, posts".to_string()), Some("posts".to_string()));
+        assert!(selection.includes("users"));
+        assert!(!selection.includes("posts"));
+        assert!(!selection.includes("comments"));
+        assert!(TableSelection::default().includes("comments"));
+
+        let tables = vec!["users".to_string(), "posts".to_string()];
+        assert!(selection.check(&tables).is_ok());
+        let typo = TableSelection::from_flags(Some("usres".to_string()), None).check(&tables).unwrap_err();
+        assert_eq!(typo, "Unknown table 'usres' in --only, did you mean 'users'?");
+        assert!(TableSelection::from_flags(None, Some("invoices_archive".to_string())).check(&tables).unwrap_err().contains("no such table"));
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+use regex::Regex;
+use tera::ast::{Expr, ExprVal, FunctionCall, Node};
+use tera::{Template, Tera};
+use walkdir::WalkDir;
+
+use crate::build::ASSET_EXTENSIONS;
+use crate::configs::Config;
+use crate::logger;
+
+pub const TEMPLATES_DIR: &str = "templates";
+pub const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+// Registered by Tera itself; anything else has to come from a register_* call in the project
+const BUILTIN_FUNCTIONS: &[&str] = &["range", "now", "throw", "get_random", "get_env"];
+const BUILTIN_FILTERS: &[&str] = &[
+    "safe", "default", "upper", "lower", "trim", "trim_start", "trim_end", "trim_start_matches", "trim_end_matches", "truncate", "wordcount", "replace",
+    "capitalize", "title", "linebreaksbr", "indent", "striptags", "spaceless", "urlencode", "urlencode_strict", "escape", "escape_xml", "slugify",
+    "addslashes", "split", "int", "float", "first", "last", "nth", "join", "sort", "unique", "slice", "group_by", "filter", "map", "concat", "abs",
+    "pluralize", "round", "filesizeformat", "length", "reverse", "date", "json_encode", "as_str", "get",
+];
+const BUILTIN_TESTERS: &[&str] = &[
+    "defined", "undefined", "odd", "even", "string", "number", "divisibleby", "iterable", "object", "starting_with", "ending_with", "containing", "matching",
+];
+
+// A template file as Rocket loads it: "users/show.html.tera" is rendered as "users/show"
+struct TemplateFile {
+    path: PathBuf,
+    display: String,
+    relative: String,
+    name: String,
+    content: String,
+}
+
+// Functions, filters and testers the project registers on its Tera instance
+#[derive(Default)]
+struct Registered {
+    functions: BTreeSet<String>,
+    filters: BTreeSet<String>,
+    testers: BTreeSet<String>,
+}
+
+// Route handler names and the full paths they are mounted at
+#[derive(Default)]
+struct Routes {
+    handlers: BTreeSet<String>,
+    paths: Vec<String>,
+}
+
+// Everything a template calls or points at, collected from its AST
+#[derive(Default)]
+struct Usage {
+    functions: Vec<FunctionCall>,
+    filters: Vec<String>,
+    testers: Vec<String>,
+    macro_calls: Vec<(String, String)>,
+    includes: Vec<(Vec<String>, bool)>,
+}
+
+#[derive(Default)]
+struct Findings {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+// `blast check templates [--watch]`
+pub fn check_templates(config: &Config, watch: bool) -> Result<(), String> {
+    if !watch {
+        return validate(config);
+    }
+
+    logger::info(&format!("Watching templates/, src/ and {}/ for changes (Ctrl+C to stop)", config.public_dir()))?;
+    let mut last_fingerprint = String::new();
+    loop {
+        let fingerprint = watch_fingerprint(config);
+        if fingerprint != last_fingerprint {
+            last_fingerprint = fingerprint;
+            // Keep watching after a failed check; the errors are already on screen
+            if let Err(e) = validate(config) {
+                logger::error(&e)?;
+            }
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+// Parse every template under templates/ and cross-check what it references. Used by `blast build` too
+pub fn validate(config: &Config) -> Result<(), String> {
+    let templates_dir = config.project_dir.join(TEMPLATES_DIR);
+    if !templates_dir.exists() {
+        return logger::info("No templates/ directory, nothing to check");
+    }
+
+    let files = load_template_files(config, &templates_dir)?;
+    let findings = check_files(config, &files);
+
+    for warning in &findings.warnings {
+        logger::warning(warning)?;
+    }
+    for error in &findings.errors {
+        logger::error(error)?;
+    }
+
+    if !findings.errors.is_empty() {
+        return Err(format!("{} error(s) in {} template(s)", findings.errors.len(), files.len()));
+    }
+    logger::success(&format!("{} template(s) OK", files.len()))
+}
+
+fn load_template_files(config: &Config, templates_dir: &Path) -> Result<Vec<TemplateFile>, String> {
+    let mut files = Vec::new();
+    let entries = WalkDir::new(templates_dir).sort_by_file_name().into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file());
+    for entry in entries {
+        let relative = entry.path().strip_prefix(templates_dir).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+        // Rocket only loads files with the engine's extension
+        let Some(without_tera) = relative.strip_suffix(".tera") else {
+            continue;
+        };
+
+        let content = fs::read_to_string(entry.path()).map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+        let name = match without_tera.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() && !stem.ends_with('/') => stem.to_string(),
+            _ => without_tera.to_string(),
+        };
+        files.push(TemplateFile {
+            path: entry.path().to_path_buf(),
+            display: entry.path().strip_prefix(&config.project_dir).unwrap_or(entry.path()).display().to_string(),
+            relative: relative.clone(),
+            name,
+            content,
+        });
+    }
+    Ok(files)
+}
+
+fn check_files(config: &Config, files: &[TemplateFile]) -> Findings {
+    let mut findings = Findings::default();
+    let registered = registered_extensions(config);
+    let routes = find_routes(config);
+    let public_dir = config.project_dir.join(config.public_dir());
+
+    // Templates may be referred to as "base", "base.html" or "base.html.tera"
+    let mut parsed: Vec<(&TemplateFile, Template)> = Vec::new();
+    for file in files {
+        match Template::new(&file.name, Some(file.path.to_string_lossy().into_owned()), &file.content) {
+            Ok(template) => parsed.push((file, template)),
+            Err(e) => findings.errors.push(parse_error(file, &e.to_string())),
+        }
+    }
+    // Files that failed to parse still exist, so references to them aren't reported a second time
+    let known: BTreeSet<String> = files.iter().flat_map(aliases).collect();
+    let mut by_name: HashMap<String, &Template> = HashMap::new();
+    for (file, template) in &parsed {
+        for alias in aliases(file) {
+            by_name.insert(alias, template);
+        }
+    }
+
+    for (file, template) in &parsed {
+        let mut usage = Usage::default();
+        walk_nodes(&template.ast, &mut usage);
+
+        if let Some(parent) = &template.parent {
+            if !known.contains(parent) {
+                findings.errors.push(located(file, &format!("\"{}\"", parent), &format!("extends \"{}\", which doesn't exist", parent)));
+            }
+        }
+        for (names, ignore_missing) in &usage.includes {
+            if !ignore_missing && !names.iter().any(|name| known.contains(name)) {
+                let name = names.join("\", \"");
+                findings.errors.push(located(file, &format!("\"{}\"", names[0]), &format!("includes \"{}\", which doesn't exist", name)));
+            }
+        }
+
+        // Imports of the template and of everything it extends are in scope
+        let mut imports: HashMap<&str, &str> = HashMap::new();
+        // Tera itself overflows the stack on circular inheritance, so it has to be caught here
+        let mut chain = vec![template.name.as_str()];
+        let mut current = Some(template);
+        while let Some(tpl) = current {
+            for (file_name, namespace) in &tpl.imported_macro_files {
+                imports.entry(namespace.as_str()).or_insert(file_name.as_str());
+            }
+            current = tpl.parent.as_ref().and_then(|parent| by_name.get(parent).copied());
+            if let Some(parent) = current {
+                let circular = chain.contains(&parent.name.as_str());
+                chain.push(&parent.name);
+                if circular {
+                    let extends = format!("\"{}\"", template.parent.as_deref().unwrap_or_default());
+                    findings.errors.push(located(file, &extends, &format!("circular inheritance: {}", chain.join(" -> "))));
+                    break;
+                }
+            }
+        }
+        for (file_name, _) in &template.imported_macro_files {
+            if !known.contains(file_name) {
+                findings.errors.push(located(file, &format!("\"{}\"", file_name), &format!("imports macros from \"{}\", which doesn't exist", file_name)));
+            }
+        }
+        for (namespace, name) in &usage.macro_calls {
+            let call = format!("{}::{}", namespace, name);
+            let source = if namespace == "self" { Some(template) } else { imports.get(namespace.as_str()).and_then(|file_name| by_name.get(*file_name).copied()) };
+            match source {
+                Some(source) if !source.macros.contains_key(name) => findings.errors.push(located(file, &call, &format!("calls {}(), which isn't defined", call))),
+                None if !imports.contains_key(namespace.as_str()) => findings.errors.push(located(file, &call, &format!("calls {}() without importing '{}'", call, namespace))),
+                _ => {}
+            }
+        }
+
+        for call in &usage.functions {
+            if !BUILTIN_FUNCTIONS.contains(&call.name.as_str()) && !registered.functions.contains(&call.name) {
+                findings.errors.push(located(file, &format!("{}(", call.name), &format!("unknown function '{}' (not built into Tera or registered in src/)", call.name)));
+            }
+            check_function_references(file, call, &routes, &public_dir, &mut findings);
+        }
+        for filter in &usage.filters {
+            if !BUILTIN_FILTERS.contains(&filter.as_str()) && !registered.filters.contains(filter) {
+                findings.errors.push(located(file, filter, &format!("unknown filter '{}' (not built into Tera or registered in src/)", filter)));
+            }
+        }
+        for tester in &usage.testers {
+            if !BUILTIN_TESTERS.contains(&tester.as_str()) && !registered.testers.contains(tester) {
+                findings.errors.push(located(file, tester, &format!("unknown test '{}' (not built into Tera or registered in src/)", tester)));
+            }
+        }
+
+        check_links(file, &routes, &public_dir, &mut findings);
+    }
+
+    // Loading them all together the way the app does catches what single files can't, like circular inheritance
+    if findings.errors.is_empty() {
+        let mut tera = Tera::default();
+        let sources = parsed.iter().flat_map(|(file, _)| aliases(file).into_iter().map(move |alias| (alias, file.content.as_str())));
+        if let Err(e) = tera.add_raw_templates(sources) {
+            findings.errors.push(e.to_string());
+        }
+    }
+
+    findings
+}
+
+fn aliases(file: &TemplateFile) -> BTreeSet<String> {
+    BTreeSet::from([file.name.clone(), file.relative.trim_end_matches(".tera").to_string(), file.relative.clone()])
+}
+
+// " --> 3:9 | ... = expected tag" becomes "templates/index.html.tera:3:9: expected tag"
+fn parse_error(file: &TemplateFile, message: &str) -> String {
+    let position = Regex::new(r"-->\s*(\d+):(\d+)").ok().and_then(|regex| regex.captures(message).map(|capture| format!("{}:{}", &capture[1], &capture[2])));
+    let reason = message.lines().rev().find_map(|line| line.trim().strip_prefix("= ")).unwrap_or(message.trim());
+    match position {
+        Some(position) => format!("{}:{}: {}", file.display, position, reason),
+        None => format!("{}: {}", file.display, reason),
+    }
+}
+
+// The AST carries no positions, so point at the first line mentioning the offending name
+fn located(file: &TemplateFile, needle: &str, message: &str) -> String {
+    match file.content.lines().position(|line| line.contains(needle)) {
+        Some(index) => format!("{}:{}: {}", file.display, index + 1, message),
+        None => format!("{}: {}", file.display, message),
+    }
+}
+
+fn walk_nodes(nodes: &[Node], usage: &mut Usage) {
+    for node in nodes {
+        match node {
+            Node::VariableBlock(_, expr) => walk_expr(expr, usage),
+            Node::MacroDefinition(_, definition, _) => {
+                definition.args.values().flatten().for_each(|expr| walk_expr(expr, usage));
+                walk_nodes(&definition.body, usage);
+            }
+            Node::Include(_, names, ignore_missing) => usage.includes.push((names.clone(), *ignore_missing)),
+            Node::Set(_, set) => walk_expr(&set.value, usage),
+            Node::FilterSection(_, section, _) => {
+                usage.filters.push(section.filter.name.clone());
+                section.filter.args.values().for_each(|expr| walk_expr(expr, usage));
+                walk_nodes(&section.body, usage);
+            }
+            Node::Block(_, block, _) => walk_nodes(&block.body, usage),
+            Node::Forloop(_, forloop, _) => {
+                walk_expr(&forloop.container, usage);
+                walk_nodes(&forloop.body, usage);
+                if let Some(empty_body) = &forloop.empty_body {
+                    walk_nodes(empty_body, usage);
+                }
+            }
+            Node::If(condition, _) => {
+                for (_, expr, body) in &condition.conditions {
+                    walk_expr(expr, usage);
+                    walk_nodes(body, usage);
+                }
+                if let Some((_, body)) = &condition.otherwise {
+                    walk_nodes(body, usage);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn walk_expr(expr: &Expr, usage: &mut Usage) {
+    for filter in &expr.filters {
+        usage.filters.push(filter.name.clone());
+        filter.args.values().for_each(|arg| walk_expr(arg, usage));
+    }
+    walk_value(&expr.val, usage);
+}
+
+fn walk_value(value: &ExprVal, usage: &mut Usage) {
+    match value {
+        ExprVal::Math(math) => {
+            walk_expr(&math.lhs, usage);
+            walk_expr(&math.rhs, usage);
+        }
+        ExprVal::Logic(logic) => {
+            walk_expr(&logic.lhs, usage);
+            walk_expr(&logic.rhs, usage);
+        }
+        ExprVal::In(contains) => {
+            walk_expr(&contains.lhs, usage);
+            walk_expr(&contains.rhs, usage);
+        }
+        ExprVal::Test(test) => {
+            usage.testers.push(test.name.clone());
+            test.args.iter().for_each(|arg| walk_expr(arg, usage));
+        }
+        ExprVal::MacroCall(call) => {
+            usage.macro_calls.push((call.namespace.clone(), call.name.clone()));
+            call.args.values().for_each(|arg| walk_expr(arg, usage));
+        }
+        ExprVal::FunctionCall(call) => {
+            call.args.values().for_each(|arg| walk_expr(arg, usage));
+            usage.functions.push(call.clone());
+        }
+        ExprVal::Array(items) => items.iter().for_each(|item| walk_expr(item, usage)),
+        ExprVal::StringConcat(concat) => concat.values.iter().for_each(|value| walk_value(value, usage)),
+        _ => {}
+    }
+}
+
+// register_function("url_for", ...) and friends anywhere under src/
+fn registered_extensions(config: &Config) -> Registered {
+    let mut registered = Registered::default();
+    let Ok(registration) = Regex::new(r#"register_(function|filter|tester)\s*\(\s*"([^"]+)""#) else {
+        return registered;
+    };
+
+    for content in rust_sources(&config.project_dir.join("src")) {
+        for capture in registration.captures_iter(&content) {
+            let names = match &capture[1] {
+                "function" => &mut registered.functions,
+                "filter" => &mut registered.filters,
+                _ => &mut registered.testers,
+            };
+            names.insert(capture[2].to_string());
+        }
+    }
+    registered
+}
+
+// Handlers and mounted paths as `blast routes` lists them
+fn find_routes(config: &Config) -> Routes {
+    let found = crate::routes::scan(config).unwrap_or_default();
+    Routes { handlers: found.iter().map(|route| route.handler.clone()).collect(), paths: found.into_iter().map(|route| route.path).collect() }
+}
+
+fn rust_sources(dir: &Path) -> Vec<String> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .collect()
+}
+
+fn is_asset(path: &str) -> bool {
+    Path::new(path).extension().and_then(|ext| ext.to_str()).map(|ext| ASSET_EXTENSIONS.contains(&ext.to_lowercase().as_str())).unwrap_or(false)
+}
+
+// Rocket serves public/ at the site root; some projects keep the prefix in their URLs
+fn in_public(public_dir: &Path, path: &str) -> bool {
+    let path = path.trim_start_matches('/');
+    public_dir.join(path).exists() || public_dir.join(path.split_once('/').map(|(_, rest)| rest).unwrap_or(path)).exists()
+}
+
+// url_for("users")/url_for(path="/users") must name a route; asset("css/app.css") must exist in public/
+fn check_function_references(file: &TemplateFile, call: &FunctionCall, routes: &Routes, public_dir: &Path, findings: &mut Findings) {
+    let is_url = call.name.contains("url");
+    let is_asset_function = call.name.contains("asset") || call.name.contains("static");
+    if !is_url && !is_asset_function {
+        return;
+    }
+
+    for arg in call.args.values() {
+        let ExprVal::String(value) = &arg.val else {
+            continue;
+        };
+        let needle = format!("\"{}\"", value);
+        if is_asset_function {
+            if !in_public(public_dir, value) {
+                findings.errors.push(located(file, &needle, &format!("{}() points at {}, which isn't in {}", call.name, value, public_dir.display())));
+            }
+        } else if routes.handlers.is_empty() {
+            continue;
+        } else if value.starts_with('/') {
+            if !routes.paths.iter().any(|route| crate::routes::route_matches(route, value)) && !in_public(public_dir, value) {
+                findings.errors.push(located(file, &needle, &format!("{}() points at {}, which no route matches", call.name, value)));
+            }
+        } else if !routes.handlers.contains(value) {
+            findings.errors.push(located(file, &needle, &format!("{}() names route '{}', which isn't a route handler", call.name, value)));
+        }
+    }
+}
+
+// Literal href/src/action paths: assets must be in public/, other links should match a route
+fn check_links(file: &TemplateFile, routes: &Routes, public_dir: &Path, findings: &mut Findings) {
+    let Ok(reference) = Regex::new(r#"(?:href|src|action)\s*=\s*["'](/[^"']*)["']"#) else {
+        return;
+    };
+
+    for (index, line) in file.content.lines().enumerate() {
+        for capture in reference.captures_iter(line) {
+            let target = capture[1].split(['?', '#']).next().unwrap_or_default();
+            // Protocol-relative URLs and paths built from template expressions can't be checked
+            if target.starts_with("//") || target.contains('{') {
+                continue;
+            }
+
+            if is_asset(target) {
+                if !in_public(public_dir, target) {
+                    findings.errors.push(format!("{}:{}: missing asset {}", file.display, index + 1, target));
+                }
+            } else if !routes.handlers.is_empty() && !routes.paths.iter().any(|route| crate::routes::route_matches(route, target)) && !in_public(public_dir, target) {
+                findings.warnings.push(format!("{}:{}: no route matches {}", file.display, index + 1, target));
+            }
+        }
+    }
+}
+
+// Paths, sizes and modification times of everything a check reads
+fn watch_fingerprint(config: &Config) -> String {
+    fingerprint(config, &[PathBuf::from(TEMPLATES_DIR), PathBuf::from("src"), PathBuf::from(config.public_dir())])
+}
+
+// Paths, sizes and modification times of every file under the project-relative dirs; changes when any of them does
+pub fn fingerprint(config: &Config, dirs: &[PathBuf]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for dir in dirs {
+        for entry in WalkDir::new(config.project_dir.join(dir)).sort_by_file_name().into_iter().filter_map(|entry| entry.ok()) {
+            if let Ok(metadata) = entry.metadata() {
+                entry.path().hash(&mut hasher);
+                metadata.len().hash(&mut hasher);
+                if let Ok(modified) = metadata.modified().map(|time| time.duration_since(UNIX_EPOCH).unwrap_or_default()) {
+                    modified.as_nanos().hash(&mut hasher);
+                }
+            }
+        }
+    }
+    format!("{:016x}", hasher.finish())
+}
@@ -0,0 +1,78 @@
+use std::process::Command;
+use std::time::Instant;
+
+use crate::configs::Config;
+use crate::logger;
+
+// `blast test`: prepare the test database, then run `cargo test` against it.
+// Without keep_db the database is dropped and recreated, so every run starts from the migrations (and test seeds)
+pub fn run_tests(config: &Config, keep_db: bool, seed: bool, db_suffix: Option<&str>, cargo_args: &[String]) -> Result<(), String> {
+    let database_url = test_database_url(config, db_suffix)?;
+    let database_name = url::Url::parse(&database_url).map(|url| url.path().trim_start_matches('/').to_string()).unwrap_or_default();
+    let label = format!("test database {}", database_name);
+
+    let prep_started = Instant::now();
+    if !keep_db && !crate::database::drop_database_url(config, &label, &database_url, true) {
+        return Err(format!("Failed to drop {}", label));
+    }
+    if !crate::database::create_database_url(config, &label, &database_url) {
+        return Err(format!("Failed to create {}", label));
+    }
+    if crate::database::has_migrations(config) && !crate::database::migrate_database_url(config, &label, &database_url) {
+        return Err(format!("Failed to migrate {}", label));
+    }
+    if seed && !crate::database::seed_database_url(config, &database_url, "test") {
+        return Err(format!("Failed to seed {} from src/database/seeds/test", label));
+    }
+    let prep_time = prep_started.elapsed();
+
+    logger::plain(&format!("Running cargo test against {}", database_name))?;
+    let tests_started = Instant::now();
+    let status = logger::command_status("cargo", Command::new("cargo").arg("test").args(cargo_args).current_dir(&config.project_dir).env("DATABASE_URL", &database_url))
+        .map_err(|e| format!("Failed to run cargo test: {}", e))?;
+    let test_time = tests_started.elapsed();
+
+    logger::plain(&format!("Database prep {:.1}s, tests {:.1}s", prep_time.as_secs_f64(), test_time.as_secs_f64()))?;
+    if !status.success() {
+        return Err(match status.code() {
+            Some(code) => format!("cargo test failed with exit code {}", code),
+            None => "cargo test was interrupted".to_string(),
+        });
+    }
+    Ok(())
+}
+
+// DATABASE_URL_TEST, or DATABASE_URL with `_test` appended to the database name. A suffix gives parallel
+// runs a database each: `--db-suffix 2` turns myapp_test into myapp_test_2
+fn test_database_url(config: &Config, db_suffix: Option<&str>) -> Result<String, String> {
+    let env = crate::envfile::load_effective(&config.project_dir, &config.environment);
+    let database_url = env.get("DATABASE_URL");
+
+    let mut url = match env.get("DATABASE_URL_TEST") {
+        Some(test_url) => url::Url::parse(&test_url).map_err(|e| format!("Invalid DATABASE_URL_TEST: {}", e))?,
+        None => {
+            let database_url = database_url.clone().ok_or_else(|| "Neither DATABASE_URL_TEST nor DATABASE_URL is set in the env files".to_string())?;
+            let mut url = url::Url::parse(&database_url).map_err(|e| format!("Invalid DATABASE_URL: {}", e))?;
+            let name = url.path().trim_start_matches('/').to_string();
+            if name.is_empty() {
+                return Err("DATABASE_URL does not include a database name to derive the test database from".to_string());
+            }
+            url.set_path(&format!("/{}_test", name));
+            url
+        }
+    };
+
+    if let Some(suffix) = db_suffix {
+        if suffix.is_empty() || !suffix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("--db-suffix '{}' must be letters, digits and _", suffix));
+        }
+        let name = url.path().trim_start_matches('/').to_string();
+        url.set_path(&format!("/{}_{}", name, suffix));
+    }
+
+    // The test database is dropped on every run
+    if database_url.and_then(|database_url| url::Url::parse(&database_url).ok()).as_ref() == Some(&url) {
+        return Err("The test database is the same as DATABASE_URL. Point DATABASE_URL_TEST at a separate database".to_string());
+    }
+    Ok(url.to_string())
+}
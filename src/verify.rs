@@ -0,0 +1,165 @@
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+use crate::envfile::EnvFile;
+use crate::logger;
+
+// Disposable database the generated project is initialized against. It is dropped and recreated
+pub const DATABASE_URL_VAR: &str = "BLAST_VERIFY_DATABASE_URL";
+
+const PROJECT_NAME: &str = "blast_verify_app";
+
+// `blast verify`: create a project from the template in a temp directory, initialize it against a
+// disposable database and `cargo check` the result
+pub fn run(template: Option<String>, keep: bool) -> Result<(), String> {
+    let root = std::env::temp_dir().join(format!("blast-verify-{}", std::process::id()));
+    if root.exists() {
+        fs::remove_dir_all(&root).map_err(|e| format!("Failed to clean up {}: {}", root.display(), e))?;
+    }
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let result = verify_in(&root, template);
+
+    if keep {
+        logger::plain(&format!("Kept the generated project at {}", root.join(PROJECT_NAME).display()))?;
+    } else if let Err(e) = fs::remove_dir_all(&root) {
+        logger::warning(&format!("Failed to remove {}: {}", root.display(), e))?;
+    }
+    result
+}
+
+fn verify_in(root: &Path, template: Option<String>) -> Result<(), String> {
+    let blast = std::env::current_exe().map_err(|e| format!("Failed to locate the blast executable: {}", e))?;
+    let project = root.join(PROJECT_NAME);
+
+    // 1. Project from the template, without prompts
+    let mut new_args = vec!["new", PROJECT_NAME, "--yes"];
+    if let Some(template) = &template {
+        new_args.extend(["--template", template.as_str()]);
+    }
+    step("Creating project from the template")?;
+    run_blast(&blast, root, &new_args)?;
+    if !project.join("Cargo.toml").exists() {
+        return Err("blast new did not produce a project; see the output above".to_string());
+    }
+
+    // 2. Migrations, schema and codegen against the disposable database
+    match std::env::var(DATABASE_URL_VAR).ok().filter(|url| !url.trim().is_empty()) {
+        Some(database_url) => {
+            // .env.local wins over .env and .env.<environment>
+            let mut env = EnvFile::load(&project.join(".env.local"))?;
+            env.set("DATABASE_URL", &database_url);
+            env.save()?;
+
+            step("Resetting the verification database")?;
+            run_blast(&blast, &project, &["db", "reset", "--yes"])?;
+            step("Running blast init")?;
+            run_blast(&blast, &project, &["init", "--no-baseline"])?;
+        }
+        None => {
+            logger::warning(&format!("{} is not set; skipping blast init, so only the template itself is checked, not the generated code", DATABASE_URL_VAR))?;
+        }
+    }
+
+    // 3. Does it compile?
+    step("Running cargo check")?;
+    cargo_check(&project)
+}
+
+fn step(message: &str) -> Result<(), String> {
+    logger::plain(&format!("==> {}", message))
+}
+
+// Run blast itself as a subprocess with no terminal input, so any leftover prompt takes its default
+fn run_blast(blast: &Path, dir: &Path, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(blast)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run blast {}: {}", args.join(" "), e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("blast {} failed with {}", args.join(" "), status))
+    }
+}
+
+// Stream compiler errors as they arrive, each tagged with the step that wrote the file it points at
+fn cargo_check(project: &Path) -> Result<(), String> {
+    // Plain output gets the compiler's messages without color codes
+    let format = if logger::plain_output() { "--message-format=json" } else { "--message-format=json-diagnostic-rendered-ansi" };
+    let mut child = Command::new("cargo")
+        .args(["check", format])
+        .current_dir(project)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("Failed to run cargo check: {}", e))?;
+
+    let mut errors = 0;
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if message["reason"] != "compiler-message" || message["message"]["level"] != "error" {
+                continue;
+            }
+
+            errors += 1;
+            let file = primary_file(&message["message"]).unwrap_or_default();
+            logger::plain(&format!("[{}] {}", generation_step(&file), file))?;
+            logger::plain(message["message"]["rendered"].as_str().unwrap_or_default().trim_end())?;
+        }
+    }
+
+    let status = child.wait().map_err(|e| format!("Failed to wait for cargo check: {}", e))?;
+    if status.success() {
+        logger::success("The generated project compiles")?;
+        Ok(())
+    } else if errors > 0 {
+        Err(format!("cargo check found {} error(s) in the generated project", errors))
+    } else {
+        Err(format!("cargo check failed with {}", status))
+    }
+}
+
+fn primary_file(message: &Value) -> Option<String> {
+    let spans = message["spans"].as_array()?;
+    spans
+        .iter()
+        .find(|span| span["is_primary"] == true)
+        .or(spans.first())
+        .and_then(|span| span["file_name"].as_str())
+        .map(str::to_string)
+}
+
+// Which part of blast wrote a file in the generated project
+fn generation_step(file: &str) -> &'static str {
+    let file = file.trim_start_matches("./");
+    if file.starts_with("src/structs/insertable/") {
+        "gen structs (insertable)"
+    } else if file.starts_with("src/structs/") {
+        "gen structs"
+    } else if file == "src/meltdown/generated.rs" {
+        "gen models (ModelError)"
+    } else if file.starts_with("src/models/") {
+        "gen models"
+    } else if file.starts_with("src/database/schema") || file == "src/database/mod.rs" {
+        "schema generation"
+    } else if file == "src/database/db.rs" {
+        "schema generation (db.rs connections)"
+    } else if file.starts_with("src/services/sparks/") {
+        "spark install"
+    } else if file.starts_with("src/") {
+        "template"
+    } else {
+        "dependency"
+    }
+}
@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN nickname VARCHAR(64) UNIQUE, ADD COLUMN team_id INTEGER NOT NULL DEFAULT 1, ADD FOREIGN KEY (team_id) REFERENCES teams(id);
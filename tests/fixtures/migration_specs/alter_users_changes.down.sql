@@ -0,0 +1,12 @@
+ALTER TABLE users DROP CONSTRAINT IF EXISTS chk_users_1;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS fk_users_team_id;
+DROP INDEX IF EXISTS uniq_users_email;
+ALTER TABLE users RENAME COLUMN full_name TO name;
+-- WARNING: DATA LOSS. The up migration dropped column 'notes' from 'users'.
+-- This re-creates the column as nullable, but its previous values and constraints cannot be restored.
+-- The column type could not be recovered from existing migrations; fill it in before running this.
+-- ALTER TABLE users ADD COLUMN notes <TYPE>;
+-- WARNING: DATA LOSS. The up migration dropped column 'legacy_flag' from 'users'.
+-- This re-creates the column as nullable, but its previous values and constraints cannot be restored.
+ALTER TABLE users ADD COLUMN legacy_flag BOOLEAN;
+ALTER TABLE users DROP COLUMN IF EXISTS score;
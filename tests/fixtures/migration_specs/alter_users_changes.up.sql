@@ -0,0 +1,7 @@
+ALTER TABLE users ADD COLUMN score INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users DROP COLUMN legacy_flag;
+ALTER TABLE users DROP COLUMN notes;
+ALTER TABLE users RENAME COLUMN name TO full_name;
+CREATE UNIQUE INDEX uniq_users_email ON users (email) WHERE deleted_at IS NULL;
+ALTER TABLE users ADD CONSTRAINT fk_users_team_id FOREIGN KEY (team_id) REFERENCES teams(id);
+ALTER TABLE users ADD CONSTRAINT chk_users_1 CHECK (score >= 0);
@@ -0,0 +1 @@
+-- reverse changes here
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS posts (
+    id SERIAL NOT NULL UNIQUE PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    body TEXT,
+    published BOOLEAN NOT NULL DEFAULT false,
+    user_id INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
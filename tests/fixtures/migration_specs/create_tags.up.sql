@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS tags (
+    id SERIAL NOT NULL UNIQUE PRIMARY KEY,
+    labels TEXT[]
+);
@@ -0,0 +1,38 @@
+// One table per way of storing created_at/updated_at, as diesel print-schema writes them
+diesel::table! {
+    events (id) {
+        id -> Int4,
+        archived -> Bool,
+        created_at -> Int8,
+        updated_at -> Int8,
+    }
+}
+
+diesel::table! {
+    posts (id) {
+        id -> Int4,
+        event_id -> Int4,
+        published -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    audits (id) {
+        id -> Int4,
+        event_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    flags (id) {
+        id -> Int4,
+        enabled -> Bool,
+    }
+}
+
+diesel::joinable!(posts -> events (event_id));
+diesel::joinable!(audits -> events (event_id));
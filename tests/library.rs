@@ -0,0 +1,41 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use blast::api::{self, GenOptions, LogLevel, LogSink};
+
+#[derive(Clone, Default)]
+struct Collect(Arc<Mutex<Vec<(LogLevel, String)>>>);
+
+impl LogSink for Collect {
+    fn log(&self, level: LogLevel, message: &str) {
+        self.0.lock().unwrap().push((level, message.to_string()));
+    }
+}
+
+// Operations take the Project from open_project, report failures as errors and log through the installed sink
+#[test]
+fn operations_log_through_the_sink_and_return_errors() {
+    let root = std::env::temp_dir().join(format!("blast-library-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src/database")).unwrap();
+    fs::write(root.join("Catalyst.toml"), "[settings]\nenvironment = \"dev\"\n").unwrap();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"shop\"\nversion = \"0.1.0\"\n").unwrap();
+    fs::write(root.join("src/database/schema.rs"), "diesel::table! {\n    users (id) {\n        id -> Int4,\n    }\n}\n").unwrap();
+
+    let sink = Collect::default();
+    api::set_log_sink(sink.clone());
+
+    let project = api::open_project(&root.join("src/database")).unwrap();
+    assert_eq!(project.name(), "shop");
+    assert_eq!(project.dir(), root);
+
+    let cwd = std::env::current_dir().unwrap();
+    let error = api::generate_models(&project, GenOptions::new().only(["usres"])).unwrap_err();
+    assert!(error.message().contains("Model generation failed"), "{}", error);
+    assert_eq!(std::env::current_dir().unwrap(), cwd);
+
+    let lines = sink.0.lock().unwrap();
+    assert!(lines.iter().any(|(level, message)| *level == LogLevel::Error && message.contains("did you mean 'users'")), "{:?}", lines);
+
+    let _ = fs::remove_dir_all(&root);
+}
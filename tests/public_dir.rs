@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn blast(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_blast")).args(args).current_dir(dir).output().expect("failed to run blast");
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "blast {} failed:\n{}", args.join(" "), text);
+    text
+}
+
+// Assets land in a renamed public_dir, and Rocket.toml tells the app's static mount where to find them
+#[test]
+fn assets_are_published_to_the_configured_public_dir() {
+    let root = std::env::temp_dir().join(format!("blast-public-dir-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for dir in ["src/assets/js", "src/assets/css", "src/assets/sass"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    fs::write(root.join("Catalyst.toml"), "public_dir = \"static\"\n\n[settings]\nenvironment = \"dev\"\n\n[assets]\n").unwrap();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"shop\"\nversion = \"0.1.0\"\n").unwrap();
+    fs::write(root.join(".env"), "").unwrap();
+    fs::write(root.join("Rocket.toml"), "[default]\nlimits = { forms = \"64 kB\" }\n").unwrap();
+    fs::write(root.join("src/assets/js/app.js"), "console.log(\"shop\");\n").unwrap();
+    fs::write(root.join("src/assets/css/site.css"), "body { color: red; }\n").unwrap();
+    fs::write(root.join("src/assets/sass/app.scss"), "$accent: red;\nbody { color: $accent; }\n").unwrap();
+
+    for step in ["js", "css", "scss"] {
+        blast(&root, &[step]);
+    }
+    for file in ["js/app/app.min.js", "css/app/site.min.css", "css/app.min.css"] {
+        assert!(root.join("static").join(file).is_file(), "static/{} is missing", file);
+    }
+    assert!(!root.join("public").exists());
+
+    let rocket: toml::Value = fs::read_to_string(root.join("Rocket.toml")).unwrap().parse().unwrap();
+    assert_eq!(rocket["default"]["public_dir"].as_str(), Some("static"));
+    assert_eq!(rocket["default"]["limits"]["forms"].as_str(), Some("64 kB"));
+
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn blast(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_blast")).args(args).current_dir(dir).env("EDITOR", "true").output().expect("failed to run blast");
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "blast {} failed:\n{}", args.join(" "), text);
+    text
+}
+
+// A project with the sparks module a template would have
+fn project(root: &Path) -> std::path::PathBuf {
+    let project = root.join("app");
+    let sparks = project.join("src/services/sparks");
+    fs::create_dir_all(&sparks).unwrap();
+    fs::write(project.join("Catalyst.toml"), "[settings]\nenvironment = \"dev\"\n").unwrap();
+    fs::write(project.join("Cargo.toml"), "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nrocket = \"0.5\"\n").unwrap();
+    fs::write(project.join(".env"), "").unwrap();
+    fs::write(sparks.join("mod.rs"), "pub mod registry;\n").unwrap();
+    fs::write(sparks.join("registry.rs"), "use super::*;\n\npub fn register_by_name(name: &str) -> bool {\n    match name {\n        _ => false,\n    }\n}\n").unwrap();
+    project
+}
+
+// A scaffolded spark lints clean and installs into a project: files, mod.rs, registry, Cargo.toml and .env
+#[test]
+fn scaffolded_spark_installs_into_a_project() {
+    let root = std::env::temp_dir().join(format!("blast-spark-scaffold-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+
+    blast(&root, &["spark", "new", "demo_spark"]);
+    let spark = root.join("demo_spark");
+    blast(&root, &["spark", "lint", "demo_spark"]);
+
+    // A file: URL is a directory to copy, no repository needed
+    let project = project(&root);
+    let sparks = project.join("src/services/sparks");
+    blast(&project, &["spark", "add", &format!("file://{}", spark.display())]);
+
+    assert!(sparks.join("demo_spark/manifest.toml").exists());
+    assert!(sparks.join("demo_spark/src/lib.rs").exists());
+    assert!(fs::read_to_string(sparks.join("mod.rs")).unwrap().contains("pub mod demo_spark;"));
+    assert!(fs::read_to_string(sparks.join("registry_generated.rs")).unwrap().contains("register_spark(name, demo_spark::create_spark);"));
+    assert!(fs::read_to_string(sparks.join("registry.rs")).unwrap().contains("include!(\"registry_generated.rs\")"));
+    assert!(fs::read_to_string(project.join("Cargo.toml")).unwrap().contains("serde = { version = \"1.0\", features = [\"derive\"] }"));
+    assert!(fs::read_to_string(project.join(".env")).unwrap().contains("DEMO_SPARK_API_KEY"));
+    assert!(fs::read_to_string(project.join("Catalyst.toml")).unwrap().contains("demo_spark"));
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+// `spark add --link` symlinks the spark's directory and records it as linked, so edits show up right away
+#[test]
+fn linked_spark_follows_its_directory() {
+    let root = std::env::temp_dir().join(format!("blast-spark-link-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    blast(&root, &["spark", "new", "dev_spark"]);
+    let project = project(&root);
+
+    blast(&project, &["spark", "add", "../dev_spark", "--link"]);
+
+    let installed = project.join("src/services/sparks/dev_spark");
+    assert!(installed.symlink_metadata().unwrap().file_type().is_symlink());
+    let catalyst: toml::Value = fs::read_to_string(project.join("Catalyst.toml")).unwrap().parse().unwrap();
+    assert_eq!(catalyst["sparks"]["dev_spark"]["path"].as_str(), Some("../dev_spark"));
+    assert_eq!(catalyst["sparks"]["dev_spark"]["link"].as_bool(), Some(true));
+
+    fs::write(root.join("dev_spark/NOTES.md"), "edited\n").unwrap();
+    assert_eq!(fs::read_to_string(installed.join("NOTES.md")).unwrap(), "edited\n");
+    assert!(blast(&project, &["spark", "list"]).contains("symlinked"));
+
+    // Fixing a removed link relinks it rather than cloning
+    fs::remove_file(&installed).unwrap();
+    blast(&project, &["spark", "verify", "--fix"]);
+    assert!(installed.symlink_metadata().unwrap().file_type().is_symlink());
+
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,11 @@
+use std::process::Command;
+
+// Creates a project from the Catalyst template and cargo checks it. Needs network access and,
+// for the codegen steps, BLAST_VERIFY_DATABASE_URL pointing at a disposable database:
+//   BLAST_VERIFY_DATABASE_URL=postgres://... cargo test --test verify -- --ignored
+#[test]
+#[ignore]
+fn generated_project_compiles() {
+    let status = Command::new(env!("CARGO_BIN_EXE_blast")).arg("verify").status().expect("failed to run blast verify");
+    assert!(status.success(), "blast verify failed");
+}
@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn blast(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_blast")).args(args).current_dir(dir).output().expect("failed to run blast");
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "blast {} failed:\n{}", args.join(" "), text);
+    text
+}
+
+fn app(dir: &Path, name: &str, seed: &str) {
+    fs::create_dir_all(dir.join("src/database/seeds")).unwrap();
+    fs::write(dir.join("Catalyst.toml"), "[settings]\nenvironment = \"dev\"\n").unwrap();
+    fs::write(dir.join("Cargo.toml"), format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name)).unwrap();
+    fs::write(dir.join(".env"), "").unwrap();
+    fs::write(dir.join("src/database/seeds").join(seed), "SELECT 1;\n").unwrap();
+}
+
+// Commands find Catalyst.toml in a parent directory, and a [workspace] root runs them in one member or all of them
+#[test]
+fn commands_run_from_subdirectories_and_workspace_members() {
+    let root = std::env::temp_dir().join(format!("blast-workspace-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    app(&root.join("apps/web"), "web", "01_pages.sql");
+    app(&root.join("apps/admin"), "admin", "01_admins.sql");
+    fs::write(root.join("Catalyst.toml"), "[workspace]\nmembers = [\"apps/web\", \"apps/admin\"]\n").unwrap();
+
+    // Seeds are read from src/database/seeds relative to the project, wherever blast was started
+    let nested = blast(&root.join("apps/web/src/database"), &["seed", "--list"]);
+    assert!(nested.contains("src/database/seeds/01_pages.sql"), "{}", nested);
+
+    let member = blast(&root, &["-p", "admin", "seed", "--list"]);
+    assert!(member.contains("01_admins.sql") && !member.contains("01_pages.sql"), "{}", member);
+
+    let all = blast(&root.join("apps"), &["--workspace", "seed", "--list"]);
+    assert!(all.contains("01_pages.sql") && all.contains("01_admins.sql"), "{}", all);
+    assert!(all.contains("Done in all 2 workspace members"), "{}", all);
+
+    // The root itself is not an app
+    let output = Command::new(env!("CARGO_BIN_EXE_blast")).args(["seed", "--list"]).current_dir(&root).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is a workspace root"));
+
+    let _ = fs::remove_dir_all(&root);
+}